@@ -76,12 +76,24 @@ pub struct BackendState {
 	/// labeled by ordinal position
 	pub has_row_labels: bool,
 
+	/// Whether the source object has row names other than the row positions
+	pub has_meaningful_row_names: Option<bool>,
+
+	/// How the rows of the table are currently labeled
+	pub row_label_options: Option<RowLabelOptions>,
+
+	/// Metadata of spatial data frames, such as sf objects
+	pub spatial_metadata: Option<SpatialMetadata>,
+
 	/// The currently applied column filters
 	pub column_filters: Vec<ColumnFilter>,
 
 	/// The currently applied row filters
 	pub row_filters: Vec<RowFilter>,
 
+	/// The groups referenced by the currently applied row filters
+	pub row_filter_groups: Option<Vec<RowFilterGroup>>,
+
 	/// The currently applied column sort keys
 	pub sort_keys: Vec<ColumnSortKey>,
 
@@ -97,6 +109,47 @@ pub struct BackendState {
 	pub error_message: Option<String>
 }
 
+/// Options for labeling the rows of a table
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RowLabelOptions {
+	/// Whether rows are labeled by their row names, by the values of a
+	/// column, or not labeled at all
+	pub mode: RowLabelMode,
+
+	/// The index of the column whose values label the rows, when mode is
+	/// 'column'
+	pub column_index: Option<i64>
+}
+
+/// Metadata of a spatial data frame
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SpatialMetadata {
+	/// The index of the column holding the active geometries
+	pub geometry_column_index: i64,
+
+	/// The coordinate reference system of the geometries, e.g. 'EPSG:4326'
+	pub crs: Option<String>,
+
+	/// The bounding box of the geometries, if not empty
+	pub bbox: Option<SpatialBoundingBox>
+}
+
+/// The bounding box of a set of geometries
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SpatialBoundingBox {
+	/// Minimum x coordinate
+	pub xmin: f64,
+
+	/// Minimum y coordinate
+	pub ymin: f64,
+
+	/// Maximum x coordinate
+	pub xmax: f64,
+
+	/// Maximum y coordinate
+	pub ymax: f64
+}
+
 /// Schema for a column in a table
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct ColumnSchema {
@@ -168,7 +221,29 @@ pub struct FormatOptions {
 	pub max_value_length: i64,
 
 	/// Thousands separator string
-	pub thousands_sep: Option<String>
+	pub thousands_sep: Option<String>,
+
+	/// Decimal separator string. Defaults to '.'.
+	pub decimal_sep: Option<String>,
+
+	/// Formatting of date-time values. Date-times are formatted in their own
+	/// time zone with the default format of the backend when missing.
+	pub datetime_options: Option<DatetimeFormatOptions>
+}
+
+/// Formatting options for date-time values
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DatetimeFormatOptions {
+	/// Whether to format date-times in ISO-8601 format or with the
+	/// conventions of the locale
+	pub style: DatetimeStyle,
+
+	/// Time zone to convert date-times to, e.g. 'UTC' or 'Europe/Paris'.
+	/// Defaults to the time zone of the values.
+	pub timezone: Option<String>,
+
+	/// Number of digits of sub-second precision, from 0 to 6. Defaults to 0.
+	pub sub_second_digits: Option<i64>
 }
 
 /// The schema for a table-like object
@@ -211,7 +286,28 @@ pub struct RowFilter {
 	pub error_message: Option<String>,
 
 	/// The row filter type-specific parameters
-	pub params: Option<RowFilterParams>
+	pub params: Option<RowFilterParams>,
+
+	/// Identifier of the group the filter belongs to, if any
+	pub group_id: Option<String>,
+
+	/// Human-readable summary of the filter computed by the backend, e.g.
+	/// 'height < 60'. Ignored when sent by the frontend.
+	pub display_text: Option<String>
+}
+
+/// Groups row filters in a parenthesized sub-expression
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RowFilterGroup {
+	/// Unique identifier for this group
+	pub group_id: String,
+
+	/// The binary condition to use to combine with preceding row filters or
+	/// groups
+	pub condition: RowFilterCondition,
+
+	/// Identifier of the enclosing group, if the group is nested
+	pub parent_id: Option<String>
 }
 
 /// Support status for a row filter type
@@ -370,7 +466,10 @@ pub struct ColumnSummaryStats {
 	pub datetime_stats: Option<SummaryStatsDatetime>,
 
 	/// Summary statistics for any other data types
-	pub other_stats: Option<SummaryStatsOther>
+	pub other_stats: Option<SummaryStatsOther>,
+
+	/// Statistics for a list data type
+	pub list_stats: Option<SummaryStatsList>
 }
 
 /// SummaryStatsNumber in Schemas
@@ -402,6 +501,19 @@ pub struct SummaryStatsBoolean {
 	pub false_count: i64
 }
 
+/// SummaryStatsList in Schemas
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SummaryStatsList {
+	/// The number of non-null elements of length zero
+	pub num_empty: i64,
+
+	/// Minimum length of the non-null elements
+	pub min_length: Option<i64>,
+
+	/// Maximum length of the non-null elements
+	pub max_length: Option<i64>
+}
+
 /// SummaryStatsOther in Schemas
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct SummaryStatsOther {
@@ -530,7 +642,11 @@ pub struct ColumnSortKey {
 	pub column_index: i64,
 
 	/// Sort order, ascending (true) or descending (false)
-	pub ascending: bool
+	pub ascending: bool,
+
+	/// Whether to sort strings in natural order, comparing runs of digits by
+	/// their numeric value
+	pub natural: Option<bool>
 }
 
 /// For each field, returns flags indicating supported features
@@ -1003,6 +1119,46 @@ pub enum ExportFormat {
 	Html
 }
 
+/// Possible values for ExportFactorValues
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, strum_macros::Display, strum_macros::EnumString)]
+pub enum ExportFactorValues {
+	#[serde(rename = "labels")]
+	#[strum(to_string = "labels")]
+	Labels,
+
+	#[serde(rename = "codes")]
+	#[strum(to_string = "codes")]
+	Codes
+}
+
+/// Possible values for DatetimeStyle
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, strum_macros::Display, strum_macros::EnumString)]
+pub enum DatetimeStyle {
+	#[serde(rename = "iso")]
+	#[strum(to_string = "iso")]
+	Iso,
+
+	#[serde(rename = "locale")]
+	#[strum(to_string = "locale")]
+	Locale
+}
+
+/// Possible values for RowLabelMode
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, strum_macros::Display, strum_macros::EnumString)]
+pub enum RowLabelMode {
+	#[serde(rename = "row_names")]
+	#[strum(to_string = "row_names")]
+	RowNames,
+
+	#[serde(rename = "hidden")]
+	#[strum(to_string = "hidden")]
+	Hidden,
+
+	#[serde(rename = "column")]
+	#[strum(to_string = "column")]
+	Column
+}
+
 /// Possible values for SupportStatus
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, strum_macros::Display, strum_macros::EnumString)]
 pub enum SupportStatus {
@@ -1133,6 +1289,13 @@ pub struct GetRowLabelsParams {
 	pub format_options: FormatOptions,
 }
 
+/// Parameters for the SetRowLabelOptions method.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SetRowLabelOptionsParams {
+	/// How to label the rows of the table
+	pub options: RowLabelOptions,
+}
+
 /// Parameters for the ExportDataSelection method.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct ExportDataSelectionParams {
@@ -1141,6 +1304,14 @@ pub struct ExportDataSelectionParams {
 
 	/// Result string format
 	pub format: ExportFormat,
+
+	/// Whether to export the labels or the integer codes of factors. Defaults
+	/// to labels.
+	pub factor_values: Option<ExportFactorValues>,
+
+	/// Formatting of date-time values. Defaults to ISO-8601 in the time zone
+	/// of the values.
+	pub datetime_options: Option<DatetimeFormatOptions>,
 }
 
 /// Parameters for the ConvertToCode method.
@@ -1152,6 +1323,9 @@ pub struct ConvertToCodeParams {
 	/// Zero or more row filters to apply
 	pub row_filters: Vec<RowFilter>,
 
+	/// The groups referenced by the row filters
+	pub row_filter_groups: Option<Vec<RowFilterGroup>>,
+
 	/// Zero or more sort keys to apply
 	pub sort_keys: Vec<ColumnSortKey>,
 
@@ -1159,6 +1333,14 @@ pub struct ConvertToCodeParams {
 	pub code_syntax_name: CodeSyntaxName,
 }
 
+/// Parameters for the OpenElement method.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct OpenElementParams {
+	/// The row index (relative to the sorted/filtered view) of the element to
+	/// open
+	pub row_index: i64,
+}
+
 /// Parameters for the SetColumnFilters method.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct SetColumnFiltersParams {
@@ -1171,6 +1353,14 @@ pub struct SetColumnFiltersParams {
 pub struct SetRowFiltersParams {
 	/// Zero or more filters to apply
 	pub filters: Vec<RowFilter>,
+
+	/// The groups referenced by the filters
+	pub groups: Option<Vec<RowFilterGroup>>,
+
+	/// The formatting options of the displayed values. Filter values of
+	/// numeric columns are parsed with the same thousands and decimal
+	/// separators.
+	pub format_options: Option<FormatOptions>,
 }
 
 /// Parameters for the SetSortColumns method.
@@ -1242,6 +1432,14 @@ pub enum DataExplorerBackendRequest {
 	#[serde(rename = "get_row_labels")]
 	GetRowLabels(GetRowLabelsParams),
 
+	/// Set how rows are labeled
+	///
+	/// Label rows by their row names, by the values of a column, or hide the
+	/// row labels. The options are kept for the lifetime of the data
+	/// explorer.
+	#[serde(rename = "set_row_label_options")]
+	SetRowLabelOptions(SetRowLabelOptionsParams),
+
 	/// Export data selection as a string in different formats
 	///
 	/// Export data selection as a string in different formats like CSV, TSV,
@@ -1263,6 +1461,13 @@ pub enum DataExplorerBackendRequest {
 	#[serde(rename = "suggest_code_syntax")]
 	SuggestCodeSyntax,
 
+	/// Open an element of a list in a new data explorer
+	///
+	/// Open the element of a list at a given row in a new data explorer, for
+	/// tables that show the elements of a list as rows
+	#[serde(rename = "open_element")]
+	OpenElement(OpenElementParams),
+
 	/// Set column filters to select subset of table columns
 	///
 	/// Set or clear column filters on table, replacing any previous filters
@@ -1316,6 +1521,9 @@ pub enum DataExplorerBackendReply {
 	/// Requested formatted row labels
 	GetRowLabelsReply(TableRowLabels),
 
+	/// Reply for the set_row_label_options method (no result)
+	SetRowLabelOptionsReply(),
+
 	/// Exported result
 	ExportDataSelectionReply(ExportedData),
 
@@ -1325,6 +1533,9 @@ pub enum DataExplorerBackendReply {
 	/// Syntax to use for code conversion
 	SuggestCodeSyntaxReply(CodeSyntaxName),
 
+	/// Reply for the open_element method (no result)
+	OpenElementReply(),
+
 	/// Reply for the set_column_filters method (no result)
 	SetColumnFiltersReply(),
 
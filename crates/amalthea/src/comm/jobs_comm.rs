@@ -0,0 +1,133 @@
+// @generated
+
+/*---------------------------------------------------------------------------------------------
+ *  Copyright (C) 2024-2025 Posit Software, PBC. All rights reserved.
+ *--------------------------------------------------------------------------------------------*/
+
+//
+// AUTO-GENERATED from jobs.json; do not edit.
+//
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Possible values for JobState
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, strum_macros::Display, strum_macros::EnumString)]
+pub enum JobState {
+	#[serde(rename = "running")]
+	#[strum(to_string = "running")]
+	Running,
+
+	#[serde(rename = "succeeded")]
+	#[strum(to_string = "succeeded")]
+	Succeeded,
+
+	#[serde(rename = "failed")]
+	#[strum(to_string = "failed")]
+	Failed,
+
+	#[serde(rename = "cancelled")]
+	#[strum(to_string = "cancelled")]
+	Cancelled
+}
+
+/// Possible values for JobStream
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, strum_macros::Display, strum_macros::EnumString)]
+pub enum JobStream {
+	#[serde(rename = "stdout")]
+	#[strum(to_string = "stdout")]
+	Stdout,
+
+	#[serde(rename = "stderr")]
+	#[strum(to_string = "stderr")]
+	Stderr
+}
+
+/// Parameters for the Output method.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct OutputParams {
+	/// The stream the output was written to
+	pub stream: JobStream,
+
+	/// The output text
+	pub text: String,
+}
+
+/// Parameters for the State method.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct StateParams {
+	/// The new state of the job
+	pub state: JobState,
+
+	/// The exit code of the job's process, if known
+	pub exit_code: Option<i64>,
+
+	/// The error message of a failed job, if any
+	pub message: Option<String>,
+}
+
+/**
+ * Backend RPC request types for the jobs comm
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "method", content = "params")]
+pub enum JobsBackendRequest {
+	/// Get the state of the job
+	#[serde(rename = "get_state")]
+	GetState,
+
+	/// Cancel the job
+	///
+	/// Requests the job to be cancelled. Returns whether the job was running
+	/// and has been cancelled.
+	#[serde(rename = "cancel")]
+	Cancel,
+
+}
+
+/**
+ * Backend RPC Reply types for the jobs comm
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "method", content = "result")]
+pub enum JobsBackendReply {
+	/// The state of the job
+	GetStateReply(JobState),
+
+	/// Whether the job was cancelled
+	CancelReply(bool),
+
+}
+
+/**
+ * Frontend RPC request types for the jobs comm
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "method", content = "params")]
+pub enum JobsFrontendRequest {
+}
+
+/**
+ * Frontend RPC Reply types for the jobs comm
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "method", content = "result")]
+pub enum JobsFrontendReply {
+}
+
+/**
+ * Frontend events for the jobs comm
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "method", content = "params")]
+pub enum JobsFrontendEvent {
+	/// Output streamed by the job
+	#[serde(rename = "output")]
+	Output(OutputParams),
+
+	/// The state of the job changed
+	#[serde(rename = "state")]
+	State(StateParams),
+
+}
+
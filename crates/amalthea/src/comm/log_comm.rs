@@ -0,0 +1,179 @@
+// @generated
+
+/*---------------------------------------------------------------------------------------------
+ *  Copyright (C) 2024-2025 Posit Software, PBC. All rights reserved.
+ *--------------------------------------------------------------------------------------------*/
+
+//
+// AUTO-GENERATED from log.json; do not edit.
+//
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// A log record emitted by the kernel
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LogRecord {
+	/// The time the record was emitted, in milliseconds since the Unix epoch
+	pub timestamp: i64,
+
+	/// The level of the record
+	pub level: LogLevel,
+
+	/// The module path of the code that emitted the record
+	pub target: String,
+
+	/// The message of the record
+	pub message: String
+}
+
+/// The level of a module
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ModuleLevel {
+	/// The module
+	pub module: LogModule,
+
+	/// The level of the module
+	pub level: LogLevel
+}
+
+/// Possible values for LogLevel
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, strum_macros::Display, strum_macros::EnumString)]
+pub enum LogLevel {
+	#[serde(rename = "error")]
+	#[strum(to_string = "error")]
+	Error,
+
+	#[serde(rename = "warn")]
+	#[strum(to_string = "warn")]
+	Warn,
+
+	#[serde(rename = "info")]
+	#[strum(to_string = "info")]
+	Info,
+
+	#[serde(rename = "debug")]
+	#[strum(to_string = "debug")]
+	Debug,
+
+	#[serde(rename = "trace")]
+	#[strum(to_string = "trace")]
+	Trace
+}
+
+/// Possible values for LogModule
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, strum_macros::Display, strum_macros::EnumString)]
+pub enum LogModule {
+	#[serde(rename = "kernel")]
+	#[strum(to_string = "kernel")]
+	Kernel,
+
+	#[serde(rename = "lsp")]
+	#[strum(to_string = "lsp")]
+	Lsp,
+
+	#[serde(rename = "data_explorer")]
+	#[strum(to_string = "data_explorer")]
+	DataExplorer,
+
+	#[serde(rename = "variables")]
+	#[strum(to_string = "variables")]
+	Variables
+}
+
+/// Parameters for the GetRecords method.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct GetRecordsParams {
+	/// The maximum number of records to return, most recent last. All
+	/// buffered records are returned by default.
+	pub limit: Option<i64>,
+}
+
+/// Parameters for the SetLevel method.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SetLevelParams {
+	/// The module to configure
+	pub module: LogModule,
+
+	/// The level of the module
+	pub level: LogLevel,
+}
+
+/// Parameters for the Record method.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RecordParams {
+	/// The log record
+	pub record: LogRecord,
+}
+
+/**
+ * Backend RPC request types for the log comm
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "method", content = "params")]
+pub enum LogBackendRequest {
+	/// Get recent log records
+	///
+	/// Returns the most recent log records buffered by the kernel.
+	#[serde(rename = "get_records")]
+	GetRecords(GetRecordsParams),
+
+	/// Get the levels of the modules
+	///
+	/// Returns the levels the modules are currently configured with.
+	#[serde(rename = "get_levels")]
+	GetLevels,
+
+	/// Set the level of a module
+	///
+	/// Configures the level of the log records emitted by a module.
+	#[serde(rename = "set_level")]
+	SetLevel(SetLevelParams),
+
+}
+
+/**
+ * Backend RPC Reply types for the log comm
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "method", content = "result")]
+pub enum LogBackendReply {
+	/// The most recent log records
+	GetRecordsReply(Vec<LogRecord>),
+
+	/// The levels of the configured modules
+	GetLevelsReply(Vec<ModuleLevel>),
+
+	/// Reply for the set_level method (no result)
+	SetLevelReply(),
+
+}
+
+/**
+ * Frontend RPC request types for the log comm
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "method", content = "params")]
+pub enum LogFrontendRequest {
+}
+
+/**
+ * Frontend RPC Reply types for the log comm
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "method", content = "result")]
+pub enum LogFrontendReply {
+}
+
+/**
+ * Frontend events for the log comm
+ */
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "method", content = "params")]
+pub enum LogFrontendEvent {
+	/// A log record was emitted
+	#[serde(rename = "record")]
+	Record(RecordParams),
+
+}
+
@@ -18,6 +18,10 @@ pub mod event;
 #[rustfmt::skip]
 pub mod help_comm;
 #[rustfmt::skip]
+pub mod jobs_comm;
+#[rustfmt::skip]
+pub mod log_comm;
+#[rustfmt::skip]
 pub mod plot_comm;
 pub mod server_comm;
 #[rustfmt::skip]
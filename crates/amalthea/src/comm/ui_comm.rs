@@ -98,6 +98,31 @@ pub struct Range {
 	pub end: Position
 }
 
+/// A node of the call tree of a profile
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ProfileNode {
+	/// The name of the function
+	pub name: String,
+
+	/// The file of the line being executed in the function, if known
+	pub file: Option<String>,
+
+	/// The line being executed in the function, if known (1-based)
+	pub line: Option<i64>,
+
+	/// The number of samples in which the call was on the stack
+	pub samples: i64,
+
+	/// The number of samples in which the call was the innermost one
+	pub self_samples: i64,
+
+	/// The memory allocated while the call was on the stack, in bytes
+	pub memory_alloc: i64,
+
+	/// The calls made by this call
+	pub children: Vec<ProfileNode>
+}
+
 /// Possible values for Kind in OpenEditor
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, strum_macros::Display, strum_macros::EnumString)]
 pub enum OpenEditorKind {
@@ -236,6 +261,16 @@ pub struct WorkingDirectoryParams {
 	pub directory: String,
 }
 
+/// Parameters for the Profile method.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ProfileParams {
+	/// The sampling interval in milliseconds
+	pub interval: f64,
+
+	/// The root of the call tree. Its children are the top-level calls.
+	pub root: ProfileNode,
+}
+
 /// Parameters for the DebugSleep method.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct DebugSleepParams {
@@ -535,6 +570,11 @@ pub enum UiFrontendEvent {
 	#[serde(rename = "working_directory")]
 	WorkingDirectory(WorkingDirectoryParams),
 
+	/// This event delivers the samples collected by the profiler, as a call
+	/// tree suitable for flame graphs
+	#[serde(rename = "profile")]
+	Profile(ProfileParams),
+
 	/// Use this to open a workspace in Positron
 	#[serde(rename = "open_workspace")]
 	OpenWorkspace(OpenWorkspaceParams),
@@ -106,6 +106,27 @@ pub struct Variable {
 	pub updated_time: i64
 }
 
+/// The result of deleting variables
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DeletionResult {
+	/// The names of the variables that were deleted, or that would be deleted
+	/// in a dry run
+	pub deleted: Vec<String>,
+
+	/// The variables that could not be deleted, e.g. because they are locked
+	pub failed: Vec<FailedDeletion>
+}
+
+/// A variable that could not be deleted
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct FailedDeletion {
+	/// The name of the variable
+	pub name: String,
+
+	/// Why the variable could not be deleted
+	pub reason: String
+}
+
 /// Possible values for Format in ClipboardFormat
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, strum_macros::Display, strum_macros::EnumString)]
 pub enum ClipboardFormatFormat {
@@ -179,6 +200,10 @@ pub enum VariableKind {
 pub struct ClearParams {
 	/// Whether to clear hidden objects in addition to normal variables
 	pub include_hidden_objects: bool,
+
+	/// Whether to only report which variables would be deleted, e.g. to ask
+	/// for confirmation
+	pub dry_run: Option<bool>,
 }
 
 /// Parameters for the Delete method.
@@ -186,6 +211,37 @@ pub struct ClearParams {
 pub struct DeleteParams {
 	/// The names of the variables to delete.
 	pub names: Vec<String>,
+
+	/// Whether to only report which variables would be deleted, e.g. to ask
+	/// for confirmation
+	pub dry_run: Option<bool>,
+}
+
+/// Parameters for the SaveRds method.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SaveRdsParams {
+	/// The path to the variable to save, as an array of access keys.
+	pub path: Vec<String>,
+
+	/// The path of the `.rds` file to write.
+	pub file: String,
+
+	/// An identifier for the operation, used to report its progress.
+	pub operation_id: String,
+}
+
+/// Parameters for the LoadFile method.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct LoadFileParams {
+	/// The path of the `.rds` or `.RData` file to load.
+	pub file: String,
+
+	/// The name to assign the object of an `.rds` file to. Defaults to the
+	/// name of the file without its extension. Ignored for `.RData` files.
+	pub name: Option<String>,
+
+	/// An identifier for the operation, used to report its progress.
+	pub operation_id: String,
 }
 
 /// Parameters for the Inspect method.
@@ -253,6 +309,22 @@ pub struct RefreshParams {
 	pub version: i64,
 }
 
+/// Parameters for the Progress method.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ProgressParams {
+	/// The identifier of the operation, as given in the request.
+	pub operation_id: String,
+
+	/// A description of the current step of the operation.
+	pub message: String,
+
+	/// The number of bytes processed so far.
+	pub bytes: i64,
+
+	/// The total number of bytes to process, if known.
+	pub total_bytes: Option<i64>,
+}
+
 /**
  * Backend RPC request types for the variables comm
  */
@@ -267,16 +339,32 @@ pub enum VariablesBackendRequest {
 
 	/// Clear all variables
 	///
-	/// Clears (deletes) all variables in the current session.
+	/// Clears (deletes) all variables in the current session, skipping the
+	/// variables that cannot be deleted.
 	#[serde(rename = "clear")]
 	Clear(ClearParams),
 
 	/// Deletes a set of named variables
 	///
-	/// Deletes the named variables from the current session.
+	/// Deletes the named variables from the current session, skipping the
+	/// variables that cannot be deleted.
 	#[serde(rename = "delete")]
 	Delete(DeleteParams),
 
+	/// Save a variable to an RDS file
+	///
+	/// Serializes a variable to an `.rds` file, reporting progress with
+	/// `progress` events.
+	#[serde(rename = "save_rds")]
+	SaveRds(SaveRdsParams),
+
+	/// Load a file into the session
+	///
+	/// Loads the objects of an `.rds` or `.RData` file into the current
+	/// session, reporting progress with `progress` events.
+	#[serde(rename = "load_file")]
+	LoadFile(LoadFileParams),
+
 	/// Inspect a variable
 	///
 	/// Returns the children of a variable, as an array of variables.
@@ -314,11 +402,19 @@ pub enum VariablesBackendReply {
 	/// A view containing a list of variables in the session.
 	ListReply(VariableList),
 
-	/// Reply for the clear method (no result)
-	ClearReply(),
+	/// The variables that were deleted and the ones that could not be
+	/// deleted.
+	ClearReply(DeletionResult),
+
+	/// The variables that were deleted and the ones that could not be
+	/// deleted.
+	DeleteReply(DeletionResult),
 
-	/// The names of the variables that were successfully deleted.
-	DeleteReply(Vec<String>),
+	/// Reply for the save_rds method (no result)
+	SaveRdsReply(),
+
+	/// The names of the variables that were loaded.
+	LoadFileReply(Vec<String>),
 
 	/// An inspected variable.
 	InspectReply(InspectedVariable),
@@ -365,5 +461,10 @@ pub enum VariablesFrontendEvent {
 	#[serde(rename = "refresh")]
 	Refresh(RefreshParams),
 
+	/// Reports the progress of a long-running operation, such as saving or
+	/// loading a file.
+	#[serde(rename = "progress")]
+	Progress(ProgressParams),
+
 }
 
@@ -15,8 +15,10 @@ use crate::session::Session;
 use crate::socket::socket::Socket;
 use crate::wire::execute_input::ExecuteInput;
 use crate::wire::execute_request::ExecuteRequest;
+use crate::wire::execute_warnings::ExecuteWarning;
 use crate::wire::handshake_reply::HandshakeReply;
 use crate::wire::input_reply::InputReply;
+use crate::wire::input_request::InputRequest;
 use crate::wire::jupyter_message::JupyterMessage;
 use crate::wire::jupyter_message::Message;
 use crate::wire::jupyter_message::ProtocolMessage;
@@ -36,7 +38,7 @@ pub struct DummyConnection {
 }
 
 pub struct DummyFrontend {
-    pub _control_socket: Socket,
+    pub control_socket: Socket,
     pub shell_socket: Socket,
     pub iopub_socket: Socket,
     pub stdin_socket: Socket,
@@ -132,7 +134,7 @@ impl DummyFrontend {
         // the Jupyter specification, these must share a ZeroMQ identity.
         let shell_id = rand::thread_rng().gen::<[u8; 16]>();
 
-        let _control_socket = Socket::new(
+        let control_socket = Socket::new(
             connection.session.clone(),
             connection.ctx.clone(),
             String::from("Control"),
@@ -198,7 +200,7 @@ impl DummyFrontend {
         });
 
         Self {
-            _control_socket,
+            control_socket,
             shell_socket,
             iopub_socket,
             stdin_socket,
@@ -224,6 +226,12 @@ impl DummyFrontend {
         })
     }
 
+    /// Sends a Jupyter message on the Control socket; returns the ID of the
+    /// newly created message
+    pub fn send_control<T: ProtocolMessage>(&self, msg: T) -> String {
+        Self::send(&self.control_socket, &self.session, msg)
+    }
+
     /// Sends a Jupyter message on the Stdin socket
     pub fn send_stdin<T: ProtocolMessage>(&self, msg: T) {
         Self::send(&self.stdin_socket, &self.session, msg);
@@ -246,8 +254,13 @@ impl DummyFrontend {
         //
         // Note that the panic hook will still have run to record the panic, so we'll get
         // expected panic information in the test output.
+        Message::try_from(&Self::recv_wire(socket)).unwrap()
+    }
+
+    /// Receives a message without parsing it, e.g. to inspect its metadata
+    pub fn recv_wire(socket: &Socket) -> WireMessage {
         if socket.poll_incoming(10000).unwrap() {
-            return Message::read_from_socket(socket).unwrap();
+            return WireMessage::read_from_socket(socket).unwrap();
         }
 
         panic!("Timeout while expecting message on socket {}", socket.name);
@@ -258,6 +271,16 @@ impl DummyFrontend {
         Self::recv(&self.shell_socket)
     }
 
+    /// Receives an unparsed message from the Shell socket
+    pub fn recv_shell_wire(&self) -> WireMessage {
+        Self::recv_wire(&self.shell_socket)
+    }
+
+    /// Receives a Jupyter message from the Control socket
+    pub fn recv_control(&self) -> Message {
+        Self::recv(&self.control_socket)
+    }
+
     /// Receives a Jupyter message from the IOPub socket
     pub fn recv_iopub(&self) -> Message {
         Self::recv(&self.iopub_socket)
@@ -280,6 +303,16 @@ impl DummyFrontend {
         })
     }
 
+    /// Receive from Shell and assert `ExecuteReplyAborted` message
+    #[track_caller]
+    pub fn recv_shell_execute_reply_aborted(&self) {
+        let msg = self.recv_shell();
+
+        assert_matches!(msg, Message::ExecuteReplyAborted(data) => {
+            assert_eq!(data.content.status, Status::Aborted);
+        });
+    }
+
     /// Receive from Shell and assert `ExecuteReplyException` message.
     /// Returns `execution_count`.
     #[track_caller]
@@ -337,6 +370,17 @@ impl DummyFrontend {
         })
     }
 
+    /// Receive from IOPub and assert ExecuteWarnings message. Returns the
+    /// warnings.
+    #[track_caller]
+    pub fn recv_iopub_execute_warnings(&self) -> Vec<ExecuteWarning> {
+        let msg = self.recv_iopub();
+
+        assert_matches!(msg, Message::ExecuteWarnings(data) => {
+            data.content.warnings
+        })
+    }
+
     #[track_caller]
     pub fn recv_iopub_display_data(&self) {
         let msg = self.recv_iopub();
@@ -427,6 +471,17 @@ impl DummyFrontend {
         })
     }
 
+    /// Receive from Stdin and assert `InputRequest` message.
+    /// Returns the whole request, including its metadata.
+    #[track_caller]
+    pub fn recv_stdin_input_request_content(&self) -> InputRequest {
+        let msg = self.recv_stdin();
+
+        assert_matches!(msg, Message::InputRequest(data) => {
+            data.content
+        })
+    }
+
     /// Send back an `InputReply` to an `InputRequest` over Stdin
     #[track_caller]
     pub fn send_stdin_input_reply(&self, value: String) {
@@ -460,6 +515,10 @@ impl DummyFrontend {
             has_incoming = true;
             Self::flush_incoming("Shell", &self.shell_socket);
         }
+        if self.control_socket.has_incoming_data().unwrap() {
+            has_incoming = true;
+            Self::flush_incoming("Control", &self.control_socket);
+        }
         if self.stdin_socket.has_incoming_data().unwrap() {
             has_incoming = true;
             Self::flush_incoming("StdIn", &self.stdin_socket);
@@ -27,6 +27,7 @@ use crate::language::shell_handler::ShellHandler;
 use crate::registration_file::RegistrationFile;
 use crate::session::Session;
 use crate::socket::control::Control;
+use crate::socket::execution_queue::ExecutionQueue;
 use crate::socket::heartbeat::Heartbeat;
 use crate::socket::iopub::IOPub;
 use crate::socket::iopub::IOPubMessage;
@@ -103,7 +104,12 @@ pub fn connect(
     )?;
     let shell_port = port_finalize(&shell_socket, connection_file.shell_port)?;
 
+    // Shared by the Shell and Control threads so that pending execute
+    // requests can be cancelled while the Shell thread is busy
+    let execution_queue = ExecutionQueue::new();
+
     let iopub_tx_clone = iopub_tx.clone();
+    let shell_execution_queue = execution_queue.clone();
     spawn!(format!("{name}-shell"), move || {
         shell_thread(
             shell_socket,
@@ -111,6 +117,7 @@ pub fn connect(
             comm_manager_tx,
             shell_handler,
             server_handlers,
+            shell_execution_queue,
         )
     });
 
@@ -258,6 +265,7 @@ pub fn connect(
             iopub_tx_clone,
             control_handler,
             stdin_interrupt_tx,
+            execution_queue,
         );
         log::error!("Control thread exited");
     });
@@ -344,8 +352,15 @@ fn control_thread(
     iopub_tx: Sender<IOPubMessage>,
     handler: Arc<Mutex<dyn ControlHandler>>,
     stdin_interrupt_tx: Sender<bool>,
+    execution_queue: ExecutionQueue,
 ) {
-    let control = Control::new(socket, iopub_tx, handler, stdin_interrupt_tx);
+    let control = Control::new(
+        socket,
+        iopub_tx,
+        handler,
+        stdin_interrupt_tx,
+        execution_queue,
+    );
     control.listen();
 }
 
@@ -356,6 +371,7 @@ fn shell_thread(
     comm_manager_tx: Sender<CommManagerEvent>,
     shell_handler: Box<dyn ShellHandler>,
     server_handlers: HashMap<String, Arc<Mutex<dyn ServerHandler>>>,
+    execution_queue: ExecutionQueue,
 ) -> Result<(), Error> {
     let mut shell = Shell::new(
         socket,
@@ -363,6 +379,7 @@ fn shell_thread(
         comm_manager_tx,
         shell_handler,
         server_handlers,
+        execution_queue,
     );
     shell.listen();
     Ok(())
@@ -640,7 +657,9 @@ fn handshake(
     // Check that the client did indeed connect successfully
     match status {
         Status::Ok => Ok(()),
-        Status::Error => Err(crate::anyhow!("Client failed to connect to ports.")),
+        Status::Error | Status::Aborted => {
+            Err(crate::anyhow!("Client failed to connect to ports."))
+        },
     }
 }
 
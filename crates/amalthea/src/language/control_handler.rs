@@ -7,8 +7,10 @@
 
 use async_trait::async_trait;
 
+use crate::wire::diagnostics_reply::DiagnosticsReply;
 use crate::wire::exception::Exception;
 use crate::wire::interrupt_reply::InterruptReply;
+use crate::wire::jupyter_message::Status;
 use crate::wire::shutdown_reply::ShutdownReply;
 use crate::wire::shutdown_request::ShutdownRequest;
 
@@ -28,4 +30,14 @@ pub trait ControlHandler: Send {
     ///
     /// https://jupyter-client.readthedocs.io/en/stable/messaging.html#kernel-interrupt
     async fn handle_interrupt_request(&self) -> Result<InterruptReply, Exception>;
+
+    /// Handles a request for a report on the state of the kernel. This is an
+    /// extension of the Jupyter protocol. Kernels that don't support it reply
+    /// with an empty report.
+    async fn handle_diagnostics_request(&self) -> Result<DiagnosticsReply, Exception> {
+        Ok(DiagnosticsReply {
+            status: Status::Ok,
+            diagnostics: serde_json::Value::Object(serde_json::Map::new()),
+        })
+    }
 }
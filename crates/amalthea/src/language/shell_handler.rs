@@ -52,6 +52,14 @@ pub trait ShellHandler: Send {
         req: &ExecuteRequest,
     ) -> crate::Result<ExecuteReply>;
 
+    /// Takes the metadata of the reply to the last execute request, e.g. the
+    /// duration of the execution. It is sent in the metadata frame of the
+    /// reply, which frontends that don't know about it ignore. Called once
+    /// `handle_execute_request()` has returned.
+    fn take_execute_reply_metadata(&mut self) -> Option<serde_json::Value> {
+        None
+    }
+
     /// Handles a request to provide completions for the given code fragment.
     ///
     /// Docs: https://jupyter-client.readthedocs.io/en/stable/messaging.html#completion
@@ -19,13 +19,18 @@ use stdext::unwrap;
 
 use crate::error::Error;
 use crate::language::control_handler::ControlHandler;
+use crate::socket::execution_queue::ExecutionQueue;
 use crate::socket::iopub::IOPubContextChannel;
 use crate::socket::iopub::IOPubMessage;
 use crate::socket::socket::Socket;
+use crate::wire::cancel_reply::CancelReply;
+use crate::wire::cancel_request::CancelRequest;
+use crate::wire::diagnostics_request::DiagnosticsRequest;
 use crate::wire::interrupt_request::InterruptRequest;
 use crate::wire::jupyter_message::JupyterMessage;
 use crate::wire::jupyter_message::Message;
 use crate::wire::jupyter_message::ProtocolMessage;
+use crate::wire::jupyter_message::Status;
 use crate::wire::shutdown_request::ShutdownRequest;
 use crate::wire::status::ExecutionState;
 use crate::wire::status::KernelStatus;
@@ -35,6 +40,7 @@ pub struct Control {
     iopub_tx: Sender<IOPubMessage>,
     handler: Arc<Mutex<dyn ControlHandler>>,
     stdin_interrupt_tx: Sender<bool>,
+    execution_queue: ExecutionQueue,
 }
 
 impl Control {
@@ -43,12 +49,14 @@ impl Control {
         iopub_tx: Sender<IOPubMessage>,
         handler: Arc<Mutex<dyn ControlHandler>>,
         stdin_interrupt_tx: Sender<bool>,
+        execution_queue: ExecutionQueue,
     ) -> Self {
         Self {
             socket,
             iopub_tx,
             handler,
             stdin_interrupt_tx,
+            execution_queue,
         }
     }
 
@@ -79,6 +87,12 @@ impl Control {
             Message::InterruptRequest(req) => {
                 self.handle_request(req, |r| self.handle_interrupt_request(r))
             },
+            Message::CancelRequest(req) => {
+                self.handle_request(req, |r| self.handle_cancel_request(r))
+            },
+            Message::DiagnosticsRequest(req) => {
+                self.handle_request(req, |r| self.handle_diagnostics_request(r))
+            },
             _ => Err(Error::UnsupportedMessage(message, String::from("control"))),
         }
     }
@@ -154,6 +168,12 @@ impl Control {
             req
         );
 
+        // Abort the queued requests once the interrupted one returns. This
+        // must be recorded before interrupting so the Shell thread sees it.
+        if req.content.clear_queue {
+            self.execution_queue.clear();
+        }
+
         // Notify StdIn socket first in case it's waiting for
         // input which is never going to come because of the
         // interrupt
@@ -182,4 +202,46 @@ impl Control {
 
         Ok(())
     }
+
+    fn handle_cancel_request(&self, req: JupyterMessage<CancelRequest>) -> Result<(), Error> {
+        info!("Received cancel request: {:?}", req);
+
+        self.execution_queue.cancel(req.content.msg_ids.clone());
+
+        unwrap!(
+            req.send_reply(CancelReply { status: Status::Ok }, &self.socket),
+            Err(err) => {
+                log::error!("Failed to reply to cancel request: {err:?}");
+            }
+        );
+
+        Ok(())
+    }
+
+    fn handle_diagnostics_request(
+        &self,
+        req: JupyterMessage<DiagnosticsRequest>,
+    ) -> Result<(), Error> {
+        info!("Received diagnostics request: {:?}", req);
+
+        // Lock the control handler object on this thread
+        let control_handler = self.handler.lock().unwrap();
+
+        let reply = unwrap!(
+            block_on(control_handler.handle_diagnostics_request()),
+            Err(err) => {
+                log::error!("Failed to handle diagnostics request: {err:?}");
+                return Ok(())
+            }
+        );
+
+        unwrap!(
+            req.send_reply(reply, &self.socket),
+            Err(err) => {
+                log::error!("Failed to reply to diagnostics request: {err:?}");
+            }
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,106 @@
+/*
+ * execution_queue.rs
+ *
+ * Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+ *
+ */
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+/// Tracks cancellations of the execute requests queued on the Shell socket
+///
+/// While an execute request runs, the Shell thread is blocked and the
+/// requests sent after it wait in the socket. The Control thread records
+/// cancellations here and the Shell thread consults them before executing
+/// each request, aborting the cancelled ones instead.
+#[derive(Clone, Default)]
+pub struct ExecutionQueue {
+    state: Arc<Mutex<ExecutionQueueState>>,
+}
+
+#[derive(Default)]
+struct ExecutionQueueState {
+    /// Message ID of the execute request currently running
+    running: Option<String>,
+
+    /// Whether to abort all execute requests queued behind the running one
+    clear: bool,
+
+    /// Message IDs of the pending execute requests to abort. Only tracked
+    /// until the requests queued in the socket have been dequeued.
+    cancelled: HashSet<String>,
+}
+
+impl ExecutionQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels pending execute requests by message ID. Requests that have
+    /// already started executing are not affected. Does nothing when idle
+    /// since there is no queue then.
+    pub fn cancel(&self, msg_ids: Vec<String>) {
+        let mut state = self.state.lock().unwrap();
+
+        let Some(running) = state.running.clone() else {
+            log::trace!("Ignoring cancellation of {msg_ids:?} while idle");
+            return;
+        };
+
+        let msg_ids = msg_ids.into_iter().filter(|msg_id| *msg_id != running);
+        state.cancelled.extend(msg_ids);
+    }
+
+    /// Aborts all execute requests queued behind the running one. Does
+    /// nothing when idle since there is no queue then.
+    pub fn clear(&self) {
+        let mut state = self.state.lock().unwrap();
+        if state.running.is_some() {
+            state.clear = true;
+        }
+    }
+
+    /// Marks the start of an execute request. Returns `false` if the request
+    /// was cancelled and should be aborted instead.
+    pub(crate) fn start(&self, msg_id: &str) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.cancelled.remove(msg_id) {
+            return false;
+        }
+        state.running = Some(msg_id.to_string());
+        true
+    }
+
+    /// Marks the end of an execute request
+    pub(crate) fn finish(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.running = None;
+    }
+
+    /// Marks the requests queued in the socket as all dequeued. The IDs
+    /// that are still tracked didn't match any pending request and are
+    /// forgotten.
+    pub(crate) fn drained(&self) {
+        let mut state = self.state.lock().unwrap();
+        let cancelled = std::mem::take(&mut state.cancelled);
+        if !cancelled.is_empty() {
+            log::trace!("Forgetting unknown cancelled requests {cancelled:?}");
+        }
+    }
+
+    /// Whether the requests queued behind the last execution should be
+    /// aborted. Resets the flag.
+    pub(crate) fn take_clear(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+
+        let clear = std::mem::take(&mut state.clear);
+        if clear {
+            // All pending requests are aborted, no need to track them
+            // individually
+            state.cancelled.clear();
+        }
+        clear
+    }
+}
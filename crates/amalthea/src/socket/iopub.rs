@@ -20,6 +20,7 @@ use crate::wire::display_data::DisplayData;
 use crate::wire::execute_error::ExecuteError;
 use crate::wire::execute_input::ExecuteInput;
 use crate::wire::execute_result::ExecuteResult;
+use crate::wire::execute_warnings::ExecuteWarnings;
 use crate::wire::header::JupyterHeader;
 use crate::wire::jupyter_message::JupyterMessage;
 use crate::wire::jupyter_message::Message;
@@ -86,6 +87,7 @@ pub enum IOPubMessage {
     ExecuteResult(ExecuteResult),
     ExecuteError(ExecuteError),
     ExecuteInput(ExecuteInput),
+    ExecuteWarnings(ExecuteWarnings),
     Stream(StreamOutput),
     CommOpen(CommOpen),
     CommMsgReply(JupyterHeader, CommWireMsg),
@@ -224,6 +226,12 @@ impl IOPub {
             IOPubMessage::ExecuteInput(content) => self.forward(Message::ExecuteInput(
                 self.message_with_context(content, IOPubContextChannel::Shell),
             )),
+            IOPubMessage::ExecuteWarnings(content) => {
+                self.flush_stream();
+                self.forward(Message::ExecuteWarnings(
+                    self.message_with_context(content, IOPubContextChannel::Shell),
+                ))
+            },
             IOPubMessage::Stream(content) => self.process_stream_message(content),
             IOPubMessage::CommOpen(content) => {
                 self.forward(Message::CommOpen(self.message(content)))
@@ -7,6 +7,7 @@
 
 pub mod comm;
 pub mod control;
+pub mod execution_queue;
 pub mod heartbeat;
 pub mod iopub;
 pub mod shell;
@@ -29,6 +29,7 @@ use crate::language::server_handler::ServerHandler;
 use crate::language::shell_handler::ShellHandler;
 use crate::socket::comm::CommInitiator;
 use crate::socket::comm::CommSocket;
+use crate::socket::execution_queue::ExecutionQueue;
 use crate::socket::iopub::IOPubContextChannel;
 use crate::socket::iopub::IOPubMessage;
 use crate::socket::socket::Socket;
@@ -39,6 +40,8 @@ use crate::wire::comm_info_request::CommInfoRequest;
 use crate::wire::comm_msg::CommWireMsg;
 use crate::wire::comm_open::CommOpen;
 use crate::wire::exception::Exception;
+use crate::wire::execute_reply_aborted::ExecuteReplyAborted;
+use crate::wire::execute_request::ExecuteRequest;
 use crate::wire::header::JupyterHeader;
 use crate::wire::jupyter_message::JupyterMessage;
 use crate::wire::jupyter_message::Message;
@@ -66,6 +69,10 @@ pub struct Shell {
 
     /// Channel used to deliver comm events to the comm manager
     comm_manager_tx: Sender<CommManagerEvent>,
+
+    /// Cancellations of pending execute requests, shared with the Control
+    /// thread
+    execution_queue: ExecutionQueue,
 }
 
 impl Shell {
@@ -77,12 +84,14 @@ impl Shell {
     /// * `comm_changed_rx` - A channel that receives messages from the comm manager thread
     /// * `shell_handler` - The language's shell channel handler
     /// * `server_handlers` - A map of server handler target names to their handlers
+    /// * `execution_queue` - Cancellations of pending execute requests
     pub fn new(
         socket: Socket,
         iopub_tx: Sender<IOPubMessage>,
         comm_manager_tx: Sender<CommManagerEvent>,
         shell_handler: Box<dyn ShellHandler>,
         server_handlers: HashMap<String, Arc<Mutex<dyn ServerHandler>>>,
+        execution_queue: ExecutionQueue,
     ) -> Self {
         // Need a RefCell to allow handler methods to be mutable.
         // We only run one handler at a time so this is safe.
@@ -93,6 +102,7 @@ impl Shell {
             shell_handler,
             server_handlers,
             comm_manager_tx,
+            execution_queue,
         }
     }
 
@@ -116,9 +126,64 @@ impl Shell {
             if let Err(err) = self.process_message(message) {
                 log::error!("Could not handle shell message: {err}");
             }
+
+            // The frontend interrupted the last execution and asked to clear
+            // the queue behind it
+            if self.execution_queue.take_clear() {
+                self.abort_queue();
+            }
+
+            // Cancellations only target requests that were waiting in the
+            // socket. Once it's empty, the remaining ones are stale.
+            if let Ok(false) = self.socket.has_incoming_data() {
+                self.execution_queue.drained();
+            }
+        }
+    }
+
+    /// Aborts the execute requests waiting in the socket. Other requests are
+    /// handled as usual.
+    ///
+    /// Note that execute requests sent after the interrupt but received
+    /// before the queue is drained are aborted too.
+    fn abort_queue(&self) {
+        loop {
+            match self.socket.has_incoming_data() {
+                Ok(true) => {},
+                Ok(false) => break,
+                Err(err) => {
+                    log::error!("Could not poll shell socket: {err}");
+                    break;
+                },
+            }
+
+            let message = match Message::read_from_socket(&self.socket) {
+                Ok(m) => m,
+                Err(err) => {
+                    log::warn!("Could not read message from shell socket: {err}");
+                    continue;
+                },
+            };
+
+            let result = match message {
+                Message::ExecuteRequest(req) => self.abort_execute_request(req),
+                message => self.process_message(message),
+            };
+            if let Err(err) = result {
+                log::error!("Could not handle shell message: {err}");
+            }
         }
     }
 
+    fn abort_execute_request(&self, req: JupyterMessage<ExecuteRequest>) -> crate::Result<()> {
+        log::info!("Aborting execute request {}", req.header.msg_id);
+        self.handle_request(req, |_| {
+            Ok(ExecuteReplyAborted {
+                status: Status::Aborted,
+            })
+        })
+    }
+
     /// Process a message received from the front-end, optionally dispatching
     /// messages to the IOPub or execution threads
     fn process_message(&self, msg: Message) -> crate::Result<()> {
@@ -132,11 +197,19 @@ impl Shell {
                 block_on(shell_handler.handle_is_complete_request(msg))
             }),
             Message::ExecuteRequest(req) => {
+                if !self.execution_queue.start(&req.header.msg_id) {
+                    return self.abort_execute_request(req);
+                }
+
                 // FIXME: We should ideally not pass the originator to the language kernel
                 let originator = Originator::from(&req);
-                self.handle_request(req, |msg| {
-                    block_on(shell_handler.handle_execute_request(originator, msg))
-                })
+                let result = self.handle_request_with_metadata(req, |msg| {
+                    let reply = block_on(shell_handler.handle_execute_request(originator, msg));
+                    (reply, shell_handler.take_execute_reply_metadata())
+                });
+
+                self.execution_queue.finish();
+                result
             },
             Message::CompleteRequest(req) => self.handle_request(req, |msg| {
                 block_on(shell_handler.handle_complete_request(msg))
@@ -173,6 +246,21 @@ impl Shell {
         Req: ProtocolMessage,
         Rep: ProtocolMessage,
         Handler: FnOnce(&Req) -> crate::Result<Rep>,
+    {
+        self.handle_request_with_metadata(req, |msg| (handler(msg), None))
+    }
+
+    /// Like `handle_request()`, but the handler also returns the metadata
+    /// sent in the metadata frame of the reply
+    fn handle_request_with_metadata<Req, Rep, Handler>(
+        &self,
+        req: JupyterMessage<Req>,
+        handler: Handler,
+    ) -> crate::Result<()>
+    where
+        Req: ProtocolMessage,
+        Rep: ProtocolMessage,
+        Handler: FnOnce(&Req) -> (crate::Result<Rep>, Option<serde_json::Value>),
     {
         // Enter the kernel-busy state in preparation for handling the message.
         self.iopub_tx
@@ -188,13 +276,13 @@ impl Shell {
         // is so we can mark the kernel as no longer busy when we're done, it'd
         // be better to take an async fn `handler` here just mark kernel as idle
         // when it finishes.
-        let result = handler(&req.content);
+        let (result, metadata) = handler(&req.content);
 
         let result = match result {
-            Ok(reply) => req.send_reply(reply, &self.socket),
+            Ok(reply) => req.send_reply_with_metadata(reply, metadata, &self.socket),
             Err(crate::Error::ShellErrorReply(error)) => req.send_error::<Rep>(error, &self.socket),
             Err(crate::Error::ShellErrorExecuteReply(error, exec_count)) => {
-                req.send_execute_error(error, exec_count, &self.socket)
+                req.send_execute_error(error, exec_count, metadata, &self.socket)
             },
             Err(err) => {
                 let error = Exception::internal_error(format!("{err:?}"));
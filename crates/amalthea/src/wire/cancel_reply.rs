@@ -0,0 +1,25 @@
+/*
+ * cancel_reply.rs
+ *
+ * Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+ *
+ */
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::wire::jupyter_message::MessageType;
+use crate::wire::jupyter_message::Status;
+
+/// Represents a reply to a cancel_request message
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CancelReply {
+    /// The status; always Ok
+    pub status: Status,
+}
+
+impl MessageType for CancelReply {
+    fn message_type() -> String {
+        String::from("cancel_reply")
+    }
+}
@@ -0,0 +1,26 @@
+/*
+ * cancel_request.rs
+ *
+ * Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+ *
+ */
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::wire::jupyter_message::MessageType;
+
+/// Represents a request from the frontend to cancel pending execute requests
+/// before they start executing. This is an extension of the Jupyter protocol,
+/// sent on the Control socket since the Shell socket is busy while code runs.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CancelRequest {
+    /// The message IDs of the execute requests to cancel
+    pub msg_ids: Vec<String>,
+}
+
+impl MessageType for CancelRequest {
+    fn message_type() -> String {
+        String::from("cancel_request")
+    }
+}
@@ -0,0 +1,28 @@
+/*
+ * diagnostics_reply.rs
+ *
+ * Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+ *
+ */
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::wire::jupyter_message::MessageType;
+use crate::wire::jupyter_message::Status;
+
+/// Represents a reply to a diagnostics_request message
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DiagnosticsReply {
+    /// The status; always Ok
+    pub status: Status,
+
+    /// The kernel specific report
+    pub diagnostics: serde_json::Value,
+}
+
+impl MessageType for DiagnosticsReply {
+    fn message_type() -> String {
+        String::from("diagnostics_reply")
+    }
+}
@@ -0,0 +1,24 @@
+/*
+ * diagnostics_request.rs
+ *
+ * Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+ *
+ */
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::wire::jupyter_message::MessageType;
+
+/// Represents a request from the frontend for a report on the state of the
+/// kernel, to help with remote bug triage. This is an extension of the
+/// Jupyter protocol, sent on the Control socket so that it is answered even
+/// when the kernel is busy.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DiagnosticsRequest {}
+
+impl MessageType for DiagnosticsRequest {
+    fn message_type() -> String {
+        String::from("diagnostics_request")
+    }
+}
@@ -20,6 +20,12 @@ pub struct ExecuteError {
     /// The exception that occurred during execution
     #[serde(flatten)]
     pub exception: Exception,
+
+    /// Structured information about the exception that frontends may use for
+    /// richer display, e.g. the classes and fields of R conditions. This is
+    /// an extension to the Jupyter protocol that other frontends ignore.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
 }
 
 impl MessageType for ExecuteError {
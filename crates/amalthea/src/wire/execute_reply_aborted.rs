@@ -0,0 +1,26 @@
+/*
+ * execute_reply_aborted.rs
+ *
+ * Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+ *
+ */
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::wire::jupyter_message::MessageType;
+use crate::wire::jupyter_message::Status;
+
+/// Represents a reply to an execute_request message that was cancelled before
+/// it started executing
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExecuteReplyAborted {
+    /// The status; always Aborted
+    pub status: Status,
+}
+
+impl MessageType for ExecuteReplyAborted {
+    fn message_type() -> String {
+        String::from("execute_reply")
+    }
+}
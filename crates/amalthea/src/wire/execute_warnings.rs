@@ -0,0 +1,39 @@
+/*
+ * execute_warnings.rs
+ *
+ * Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+ *
+ */
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::wire::jupyter_message::MessageType;
+
+/// Represents the warnings emitted while executing a request, sent on IOPub
+/// once the execution is complete. This message is an extension of the
+/// Jupyter protocol, frontends that don't know about it ignore it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ExecuteWarnings {
+    /// The warnings emitted during the execution, in order
+    pub warnings: Vec<ExecuteWarning>,
+}
+
+/// A warning emitted while executing code
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ExecuteWarning {
+    /// The message of the warning
+    pub message: String,
+
+    /// The deparsed call that emitted the warning, if any
+    pub call: Option<String>,
+
+    /// The classes of the warning condition
+    pub class: Vec<String>,
+}
+
+impl MessageType for ExecuteWarnings {
+    fn message_type() -> String {
+        String::from("execute_warnings")
+    }
+}
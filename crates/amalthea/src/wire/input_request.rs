@@ -1,7 +1,7 @@
 /*
  * input_request.rs
  *
- * Copyright (C) 2022-2024 Posit Software, PBC. All rights reserved.
+ * Copyright (C) 2022-2025 Posit Software, PBC. All rights reserved.
  *
  */
 
@@ -24,6 +24,12 @@ pub struct InputRequest {
     /// Whether the string being requested is a password (and should therefore
     /// be obscured)
     pub password: bool,
+
+    /// Structured information about the prompt that frontends may use to
+    /// show a picker, e.g. the choices of a menu. This is an extension to the
+    /// Jupyter protocol that other frontends ignore.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<serde_json::Value>,
 }
 
 /// An input request originating from a Shell handler
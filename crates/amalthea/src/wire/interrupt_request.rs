@@ -12,7 +12,12 @@ use crate::wire::jupyter_message::MessageType;
 
 /// Represents request from the frontend to the kernel to get information
 #[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct InterruptRequest {}
+pub struct InterruptRequest {
+    /// Whether to also abort the execute requests queued behind the
+    /// interrupted one. This is an extension of the Jupyter protocol.
+    #[serde(default)]
+    pub clear_queue: bool,
+}
 
 impl MessageType for InterruptRequest {
     fn message_type() -> String {
@@ -20,6 +20,8 @@ use crate::comm::ui_comm::UiFrontendRequest;
 use crate::error::Error;
 use crate::session::Session;
 use crate::socket::socket::Socket;
+use crate::wire::cancel_reply::CancelReply;
+use crate::wire::cancel_request::CancelRequest;
 use crate::wire::comm_close::CommClose;
 use crate::wire::comm_info_reply::CommInfoReply;
 use crate::wire::comm_info_request::CommInfoRequest;
@@ -27,14 +29,18 @@ use crate::wire::comm_msg::CommWireMsg;
 use crate::wire::comm_open::CommOpen;
 use crate::wire::complete_reply::CompleteReply;
 use crate::wire::complete_request::CompleteRequest;
+use crate::wire::diagnostics_reply::DiagnosticsReply;
+use crate::wire::diagnostics_request::DiagnosticsRequest;
 use crate::wire::error_reply::ErrorReply;
 use crate::wire::exception::Exception;
 use crate::wire::execute_error::ExecuteError;
 use crate::wire::execute_input::ExecuteInput;
 use crate::wire::execute_reply::ExecuteReply;
+use crate::wire::execute_reply_aborted::ExecuteReplyAborted;
 use crate::wire::execute_reply_exception::ExecuteReplyException;
 use crate::wire::execute_request::ExecuteRequest;
 use crate::wire::execute_result::ExecuteResult;
+use crate::wire::execute_warnings::ExecuteWarnings;
 use crate::wire::header::JupyterHeader;
 use crate::wire::input_reply::InputReply;
 use crate::wire::input_request::InputRequest;
@@ -87,6 +93,7 @@ pub enum Message {
     CompleteRequest(JupyterMessage<CompleteRequest>),
     ExecuteReply(JupyterMessage<ExecuteReply>),
     ExecuteReplyException(JupyterMessage<ExecuteReplyException>),
+    ExecuteReplyAborted(JupyterMessage<ExecuteReplyAborted>),
     ExecuteRequest(JupyterMessage<ExecuteRequest>),
     InspectReply(JupyterMessage<InspectReply>),
     InspectRequest(JupyterMessage<InspectRequest>),
@@ -102,6 +109,10 @@ pub enum Message {
     InterruptReply(JupyterMessage<InterruptReply>),
     InterruptRequest(JupyterMessage<InterruptRequest>),
     ShutdownRequest(JupyterMessage<ShutdownRequest>),
+    CancelReply(JupyterMessage<CancelReply>),
+    CancelRequest(JupyterMessage<CancelRequest>),
+    DiagnosticsReply(JupyterMessage<DiagnosticsReply>),
+    DiagnosticsRequest(JupyterMessage<DiagnosticsRequest>),
     // Registration
     HandshakeRequest(JupyterMessage<HandshakeRequest>),
     HandshakeReply(JupyterMessage<HandshakeReply>),
@@ -110,6 +121,7 @@ pub enum Message {
     ExecuteResult(JupyterMessage<ExecuteResult>),
     ExecuteError(JupyterMessage<ExecuteError>),
     ExecuteInput(JupyterMessage<ExecuteInput>),
+    ExecuteWarnings(JupyterMessage<ExecuteWarnings>),
     Stream(JupyterMessage<StreamOutput>),
     DisplayData(JupyterMessage<DisplayData>),
     UpdateDisplayData(JupyterMessage<UpdateDisplayData>),
@@ -136,6 +148,7 @@ pub enum OutboundMessage {
 pub enum Status {
     Ok,
     Error,
+    Aborted,
 }
 
 /// Conversion from a `Message` to a `WireMessage`; used to send messages over a
@@ -149,10 +162,12 @@ impl TryFrom<&Message> for WireMessage {
             Message::CompleteRequest(msg) => WireMessage::try_from(msg),
             Message::ExecuteReply(msg) => WireMessage::try_from(msg),
             Message::ExecuteReplyException(msg) => WireMessage::try_from(msg),
+            Message::ExecuteReplyAborted(msg) => WireMessage::try_from(msg),
             Message::ExecuteRequest(msg) => WireMessage::try_from(msg),
             Message::ExecuteResult(msg) => WireMessage::try_from(msg),
             Message::ExecuteError(msg) => WireMessage::try_from(msg),
             Message::ExecuteInput(msg) => WireMessage::try_from(msg),
+            Message::ExecuteWarnings(msg) => WireMessage::try_from(msg),
             Message::InputReply(msg) => WireMessage::try_from(msg),
             Message::InputRequest(msg) => WireMessage::try_from(msg),
             Message::InspectReply(msg) => WireMessage::try_from(msg),
@@ -164,6 +179,10 @@ impl TryFrom<&Message> for WireMessage {
             Message::KernelInfoReply(msg) => WireMessage::try_from(msg),
             Message::KernelInfoRequest(msg) => WireMessage::try_from(msg),
             Message::ShutdownRequest(msg) => WireMessage::try_from(msg),
+            Message::CancelReply(msg) => WireMessage::try_from(msg),
+            Message::CancelRequest(msg) => WireMessage::try_from(msg),
+            Message::DiagnosticsReply(msg) => WireMessage::try_from(msg),
+            Message::DiagnosticsRequest(msg) => WireMessage::try_from(msg),
             Message::Status(msg) => WireMessage::try_from(msg),
             Message::CommInfoReply(msg) => WireMessage::try_from(msg),
             Message::CommInfoRequest(msg) => WireMessage::try_from(msg),
@@ -218,6 +237,14 @@ impl TryFrom<&WireMessage> for Message {
             }
             // else fallthrough to try `ExecuteRequest` which has the same message type
         }
+        if kind == ExecuteReplyAborted::message_type() {
+            if let Ok(data) = JupyterMessage::<ExecuteReplyAborted>::try_from(msg) {
+                if data.content.status == Status::Aborted {
+                    return Ok(Message::ExecuteReplyAborted(data));
+                }
+            }
+            // else fallthrough to try `ExecuteReply` which has the same message type
+        }
         if kind == ExecuteRequest::message_type() {
             return Ok(Message::ExecuteRequest(JupyterMessage::try_from(msg)?));
         }
@@ -233,6 +260,9 @@ impl TryFrom<&WireMessage> for Message {
         if kind == ExecuteInput::message_type() {
             return Ok(Message::ExecuteInput(JupyterMessage::try_from(msg)?));
         }
+        if kind == ExecuteWarnings::message_type() {
+            return Ok(Message::ExecuteWarnings(JupyterMessage::try_from(msg)?));
+        }
         if kind == CompleteRequest::message_type() {
             return Ok(Message::CompleteRequest(JupyterMessage::try_from(msg)?));
         }
@@ -272,6 +302,18 @@ impl TryFrom<&WireMessage> for Message {
         if kind == InterruptReply::message_type() {
             return Ok(Message::InterruptReply(JupyterMessage::try_from(msg)?));
         }
+        if kind == CancelRequest::message_type() {
+            return Ok(Message::CancelRequest(JupyterMessage::try_from(msg)?));
+        }
+        if kind == CancelReply::message_type() {
+            return Ok(Message::CancelReply(JupyterMessage::try_from(msg)?));
+        }
+        if kind == DiagnosticsRequest::message_type() {
+            return Ok(Message::DiagnosticsRequest(JupyterMessage::try_from(msg)?));
+        }
+        if kind == DiagnosticsReply::message_type() {
+            return Ok(Message::DiagnosticsReply(JupyterMessage::try_from(msg)?));
+        }
         if kind == InputReply::message_type() {
             return Ok(Message::InputReply(JupyterMessage::try_from(msg)?));
         }
@@ -364,7 +406,21 @@ where
     /// Sends a reply to the message; convenience method combining creating the
     /// reply and sending it.
     pub fn send_reply<R: ProtocolMessage>(&self, content: R, socket: &Socket) -> crate::Result<()> {
-        let reply = self.reply_msg(content, &socket.session)?;
+        self.send_reply_with_metadata(content, None, socket)
+    }
+
+    /// Sends a reply to the message with `metadata` in the metadata frame of
+    /// the reply.
+    pub fn send_reply_with_metadata<R: ProtocolMessage>(
+        &self,
+        content: R,
+        metadata: Option<serde_json::Value>,
+        socket: &Socket,
+    ) -> crate::Result<()> {
+        let mut reply = self.reply_msg(content, &socket.session)?;
+        if let Some(metadata) = metadata {
+            reply.metadata = metadata;
+        }
         reply.send(&socket)
     }
 
@@ -382,6 +438,7 @@ where
         &self,
         exception: Exception,
         exec_count: u32,
+        metadata: Option<serde_json::Value>,
         socket: &Socket,
     ) -> crate::Result<()> {
         let rep = ExecuteReplyException {
@@ -389,7 +446,7 @@ where
             execution_count: exec_count,
             exception,
         };
-        self.send_reply(rep, socket)
+        self.send_reply_with_metadata(rep, metadata, socket)
     }
 
     /// Create a raw reply message to this message.
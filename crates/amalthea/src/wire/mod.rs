@@ -5,6 +5,8 @@
  *
  */
 
+pub mod cancel_reply;
+pub mod cancel_request;
 pub mod comm_close;
 pub mod comm_info_reply;
 pub mod comm_info_request;
@@ -12,15 +14,19 @@ pub mod comm_msg;
 pub mod comm_open;
 pub mod complete_reply;
 pub mod complete_request;
+pub mod diagnostics_reply;
+pub mod diagnostics_request;
 pub mod display_data;
 pub mod error_reply;
 pub mod exception;
 pub mod execute_error;
 pub mod execute_input;
 pub mod execute_reply;
+pub mod execute_reply_aborted;
 pub mod execute_reply_exception;
 pub mod execute_request;
 pub mod execute_result;
+pub mod execute_warnings;
 pub mod handshake_reply;
 pub mod handshake_request;
 pub mod header;
@@ -13,12 +13,14 @@ use amalthea::comm::comm_channel::CommMsg;
 use amalthea::comm::event::CommManagerEvent;
 use amalthea::socket::comm::CommInitiator;
 use amalthea::socket::comm::CommSocket;
+use amalthea::wire::cancel_request::CancelRequest;
 use amalthea::wire::comm_close::CommClose;
 use amalthea::wire::comm_info_reply::CommInfoTargetName;
 use amalthea::wire::comm_info_request::CommInfoRequest;
 use amalthea::wire::comm_msg::CommWireMsg;
 use amalthea::wire::comm_open::CommOpen;
 use amalthea::wire::jupyter_message::Message;
+use amalthea::wire::jupyter_message::Status;
 use amalthea::wire::kernel_info_request::KernelInfoRequest;
 use amalthea::wire::status::ExecutionState;
 use assert_matches::assert_matches;
@@ -87,6 +89,48 @@ fn test_amalthea_input_request() {
     frontend.recv_iopub_idle();
 }
 
+#[test]
+fn test_amalthea_cancel_request() {
+    let frontend = DummyAmaltheaFrontend::lock();
+
+    // Block the Shell thread on an input prompt so the next requests queue up
+    frontend.send_execute_request("prompt", Default::default());
+    frontend.recv_iopub_busy();
+    let input = frontend.recv_iopub_execute_input();
+    frontend.recv_stdin_input_request();
+
+    let cancelled = frontend.send_execute_request("1", Default::default());
+    frontend.send_execute_request("2", Default::default());
+
+    frontend.send_control(CancelRequest {
+        msg_ids: vec![cancelled],
+    });
+    frontend.recv_iopub_busy();
+    assert_matches!(frontend.recv_control(), Message::CancelReply(reply) => {
+        assert_eq!(reply.content.status, Status::Ok);
+    });
+    frontend.recv_iopub_idle();
+
+    frontend.send_stdin_input_reply(String::from("42"));
+    frontend.recv_iopub_stream_stdout("42");
+    assert_eq!(frontend.recv_iopub_execute_result(), "prompt");
+    assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
+    frontend.recv_iopub_idle();
+
+    // The cancelled request is aborted without being executed
+    frontend.recv_iopub_busy();
+    frontend.recv_shell_execute_reply_aborted();
+    frontend.recv_iopub_idle();
+
+    // The other one runs as usual
+    frontend.recv_iopub_busy();
+    let input = frontend.recv_iopub_execute_input();
+    assert_eq!(input.code, "2");
+    assert_eq!(frontend.recv_iopub_execute_result(), "2");
+    assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
+    frontend.recv_iopub_idle();
+}
+
 #[test]
 fn test_amalthea_heartbeat() {
     let frontend = DummyAmaltheaFrontend::lock();
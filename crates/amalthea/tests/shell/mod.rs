@@ -74,6 +74,7 @@ impl Shell {
                 request: InputRequest {
                     prompt: String::from("Amalthea Echo> "),
                     password: false,
+                    metadata: None,
                 },
             }))
         {
@@ -174,6 +175,7 @@ impl ShellHandler for Shell {
 
             if let Err(err) = self.iopub.send(IOPubMessage::ExecuteError(ExecuteError {
                 exception: exception.clone(),
+                metadata: None,
             })) {
                 warn!(
                     "Could not publish error from computation {} on iopub: {}",
@@ -104,9 +104,7 @@ impl RConnection {
                     unsafe {
                         let mut call = RFunction::from(".ps.connection_list_objects");
                         call.add(RObject::from(self.comm.comm_id.clone()));
-                        for obj in path {
-                            call.param(obj.kind.as_str(), obj.name);
-                        }
+                        call.param_all(path.into_iter().map(|obj| (obj.kind, obj.name)));
                         // returns a data.frame with columns name and type
                         let tables = call.call()?;
 
@@ -140,9 +138,7 @@ impl RConnection {
                     unsafe {
                         let mut call = RFunction::from(".ps.connection_list_fields");
                         call.add(RObject::from(self.comm.comm_id.clone()));
-                        for obj in path {
-                            call.param(obj.kind.as_str(), obj.name);
-                        }
+                        call.param_all(path.into_iter().map(|obj| (obj.kind, obj.name)));
                         let fields = call.call()?;
 
                         // for now we only need the name column
@@ -176,9 +172,7 @@ impl RConnection {
                 r_task(|| -> Result<(), anyhow::Error> {
                     let mut call = RFunction::from(".ps.connection_preview_object");
                     call.add(RObject::from(self.comm.comm_id.clone()));
-                    for obj in path {
-                        call.param(obj.kind.as_str(), obj.name);
-                    }
+                    call.param_all(path.into_iter().map(|obj| (obj.kind, obj.name)));
                     call.call()?;
                     Ok(())
                 })?;
@@ -190,9 +184,7 @@ impl RConnection {
                     unsafe {
                         let mut call = RFunction::from(".ps.connection_icon");
                         call.add(RObject::from(self.comm.comm_id.clone()));
-                        for obj in path {
-                            call.param(obj.kind.as_str(), obj.name);
-                        }
+                        call.param_all(path.into_iter().map(|obj| (obj.kind, obj.name)));
 
                         let icon = call.call()?;
 
@@ -213,9 +205,8 @@ impl RConnection {
                         let mut contains_data_call: RFunction =
                             RFunction::from(".ps.connection_contains_data");
                         contains_data_call.add(RObject::from(self.comm.comm_id.clone()));
-                        for obj in path {
-                            contains_data_call.param(obj.kind.as_str(), obj.name);
-                        }
+                        contains_data_call
+                            .param_all(path.into_iter().map(|obj| (obj.kind, obj.name)));
                         let contains_data = contains_data_call.call()?;
                         Ok(RObject::to::<bool>(contains_data)?)
                     }
@@ -0,0 +1,297 @@
+//
+// console_hyperlinks.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use std::borrow::Cow;
+use std::path::Path;
+use std::path::PathBuf;
+
+use tree_sitter::Parser;
+use url::Url;
+
+use crate::treesitter::NamespaceOperatorType;
+use crate::treesitter::NodeType;
+use crate::treesitter::NodeTypeExt;
+
+/// Frontend command that runs code at the console. Takes an object with the
+/// `langId` and the `code` to run.
+const RUN_CODE_COMMAND: &str = "workbench.action.executeCode.console";
+
+/// Frontend command that shows a help topic. Takes the language ID and the
+/// topic, optionally qualified with a package as in `pkg::topic`.
+const SHOW_HELP_COMMAND: &str = "positron.help.showHelpTopic";
+
+const OSC8_START: &str = "\x1b]8;";
+
+/// Translates the targets of the OSC 8 hyperlinks of console output into
+/// links the frontend can open
+///
+/// Hyperlinks are written as `ESC ] 8 ; params ; URI ST`, where ST is
+/// either `ESC \` or `BEL`, and an empty URI closes the link.
+///
+/// - `x-r-run:code` links, e.g. emitted by cli's `{.run}`, become command
+///   links running the code at the console. Only single calls to a
+///   namespaced function like `pkg::fun(arg)` are accepted, so that printing
+///   a string can't make a click run arbitrary code.
+///
+/// - `x-r-help:topic` links, e.g. emitted by cli's `{.help}`, become command
+///   links showing the help topic.
+///
+/// - `file://` links are made absolute and their `line` and `col` params are
+///   moved to a `#L<line>,<col>` fragment, which the frontend uses to reveal
+///   the location.
+///
+/// Other links are left alone.
+pub(crate) fn translate_hyperlinks(text: &str) -> Cow<'_, str> {
+    if !text.contains(OSC8_START) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find(OSC8_START) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let Some((params, uri, terminator, len)) = parse_osc8(rest) else {
+            // Not a complete sequence, e.g. split across writes, pass it on
+            // as is
+            out.push_str(OSC8_START);
+            rest = &rest[OSC8_START.len()..];
+            continue;
+        };
+
+        let (params, uri) = match translate_uri(params, uri) {
+            Some((params, uri)) => (Cow::Owned(params), Cow::Owned(uri)),
+            None => (Cow::Borrowed(params), Cow::Borrowed(uri)),
+        };
+
+        out.push_str(OSC8_START);
+        out.push_str(&params);
+        out.push(';');
+        out.push_str(&uri);
+        out.push_str(terminator);
+
+        rest = &rest[len..];
+    }
+
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+/// Parses the OSC 8 sequence at the start of `text`. Returns its params, URI,
+/// terminator, and total length.
+fn parse_osc8(text: &str) -> Option<(&str, &str, &str, usize)> {
+    let body = text.strip_prefix(OSC8_START)?;
+
+    let end = body.find(['\x07', '\x1b'])?;
+    let terminator = if body[end..].starts_with('\x07') {
+        "\x07"
+    } else if body[end..].starts_with("\x1b\\") {
+        "\x1b\\"
+    } else {
+        return None;
+    };
+
+    let (params, uri) = body[..end].split_once(';')?;
+    let len = OSC8_START.len() + end + terminator.len();
+
+    Some((params, uri, terminator, len))
+}
+
+/// Returns the translated params and URI of a hyperlink, or `None` if the
+/// link should be left alone
+fn translate_uri(params: &str, uri: &str) -> Option<(String, String)> {
+    if let Some(code) = uri.strip_prefix("x-r-run:") {
+        if !is_namespaced_call(code) {
+            return None;
+        }
+        let args = serde_json::json!([{ "langId": "r", "code": code }]);
+        return Some((params.to_string(), command_uri(RUN_CODE_COMMAND, args)?));
+    }
+
+    if let Some(topic) = uri.strip_prefix("x-r-help:") {
+        let args = serde_json::json!(["r", topic]);
+        return Some((params.to_string(), command_uri(SHOW_HELP_COMMAND, args)?));
+    }
+
+    if let Some(path) = uri.strip_prefix("file://") {
+        return translate_file_uri(params, path);
+    }
+
+    None
+}
+
+/// Whether `code` parses as a single `pkg::fun(...)` call
+fn is_namespaced_call(code: &str) -> bool {
+    let mut parser = Parser::new();
+    if parser
+        .set_language(&tree_sitter_r::LANGUAGE.into())
+        .is_err()
+    {
+        return false;
+    }
+
+    let Some(tree) = parser.parse(code, None) else {
+        return false;
+    };
+    let root = tree.root_node();
+
+    if root.has_error() || root.named_child_count() != 1 {
+        return false;
+    }
+    let Some(call) = root.named_child(0) else {
+        return false;
+    };
+    if call.node_type() != NodeType::Call {
+        return false;
+    }
+
+    let Some(function) = call.child_by_field_name("function") else {
+        return false;
+    };
+    function.node_type() == NodeType::NamespaceOperator(NamespaceOperatorType::External)
+}
+
+fn command_uri(command: &str, args: serde_json::Value) -> Option<String> {
+    let mut url = Url::parse(&format!("command:{command}")).ok()?;
+    url.set_query(Some(&args.to_string()));
+    Some(url.to_string())
+}
+
+/// cli writes file links as `file://<path>` where the path may be relative,
+/// with the location in `line=` and `col=` params separated by `:`
+fn translate_file_uri(params: &str, path: &str) -> Option<(String, String)> {
+    let mut line = None;
+    let mut col = None;
+    let mut other = Vec::new();
+
+    for param in params.split(':').filter(|param| !param.is_empty()) {
+        match param.split_once('=') {
+            Some(("line", value)) => line = value.parse::<u32>().ok(),
+            Some(("col", value)) => col = value.parse::<u32>().ok(),
+            _ => other.push(param),
+        }
+    }
+
+    let path = absolute_path(path)?;
+    let mut url = Url::from_file_path(path).ok()?;
+
+    if let Some(line) = line {
+        let fragment = match col {
+            Some(col) => format!("L{line},{col}"),
+            None => format!("L{line}"),
+        };
+        url.set_fragment(Some(&fragment));
+    }
+
+    Some((other.join(":"), url.to_string()))
+}
+
+fn absolute_path(path: &str) -> Option<PathBuf> {
+    // Already percent-encoded paths are decoded so they aren't encoded twice
+    let path = match Url::parse(&format!("file://{path}")) {
+        Ok(url) if path.starts_with('/') => url.to_file_path().ok()?,
+        _ => PathBuf::from(path),
+    };
+
+    if path.is_absolute() {
+        return Some(path);
+    }
+
+    // R changes the working directory of the process with `setwd()`
+    let wd = std::env::current_dir().ok()?;
+    Some(wd.join(Path::new(&path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::console_hyperlinks::translate_hyperlinks;
+
+    fn link(params: &str, uri: &str, text: &str) -> String {
+        format!("\x1b]8;{params};{uri}\x1b\\{text}\x1b]8;;\x1b\\")
+    }
+
+    #[test]
+    fn test_translate_hyperlinks_passthrough() {
+        assert_eq!(translate_hyperlinks("plain output\n"), "plain output\n");
+
+        let https = link("", "https://cran.r-project.org", "CRAN");
+        assert_eq!(translate_hyperlinks(&https), https);
+
+        // Incomplete sequences are left alone
+        let incomplete = "\x1b]8;;x-r-run:foo()";
+        assert_eq!(translate_hyperlinks(incomplete), incomplete);
+    }
+
+    #[test]
+    fn test_translate_hyperlinks_run_rejects_other_code() {
+        for code in [
+            "foo()",
+            "pkg:::foo()",
+            "pkg::foo",
+            "pkg::foo(); unlink('~')",
+            "pkg::foo()\nunlink('~')",
+            "x <- pkg::foo()",
+            "pkg::foo(",
+            "",
+        ] {
+            let text = link("", &format!("x-r-run:{code}"), "run");
+            assert_eq!(translate_hyperlinks(&text), text);
+        }
+    }
+
+    #[test]
+    fn test_translate_hyperlinks_run_and_help() {
+        let text = format!(
+            "Run {} now",
+            link("", "x-r-run:utils::head(mtcars)", "head()")
+        );
+        assert_eq!(
+            translate_hyperlinks(&text),
+            format!(
+                "Run {} now",
+                link(
+                    "",
+                    "command:workbench.action.executeCode.console?[{%22langId%22:%22r%22,%22code%22:%22utils::head(mtcars)%22}]",
+                    "head()"
+                )
+            )
+        );
+
+        // BEL terminators are preserved
+        let text = "\x1b]8;;x-r-help:base::mean\x07mean\x1b]8;;\x07";
+        assert_eq!(
+            translate_hyperlinks(text),
+            "\x1b]8;;command:positron.help.showHelpTopic?[%22r%22,%22base::mean%22]\x07mean\x1b]8;;\x07"
+        );
+    }
+
+    #[test]
+    fn test_translate_hyperlinks_files() {
+        let text = link("line=10:col=5", "file:///tmp/foo.R", "foo.R:10:5");
+        assert_eq!(
+            translate_hyperlinks(&text),
+            link("", "file:///tmp/foo.R#L10,5", "foo.R:10:5")
+        );
+
+        let text = link("id=1:line=3", "file:///tmp/foo.R", "foo.R");
+        assert_eq!(
+            translate_hyperlinks(&text),
+            link("id=1", "file:///tmp/foo.R#L3", "foo.R")
+        );
+
+        // Relative paths are resolved against the working directory
+        let wd = std::env::current_dir().unwrap();
+        let expected = url::Url::from_file_path(wd.join("R/foo.R")).unwrap();
+        let text = link("", "file://R/foo.R", "foo.R");
+        assert_eq!(
+            translate_hyperlinks(&text),
+            link("", expected.as_str(), "foo.R")
+        );
+    }
+}
@@ -5,25 +5,84 @@
  *
  */
 
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Once;
+use std::time::Duration;
+use std::time::Instant;
+
 use amalthea::language::control_handler::ControlHandler;
+use amalthea::socket::iopub::IOPubMessage;
+use amalthea::wire::diagnostics_reply::DiagnosticsReply;
 use amalthea::wire::exception::Exception;
 use amalthea::wire::interrupt_reply::InterruptReply;
 use amalthea::wire::jupyter_message::Status;
 use amalthea::wire::shutdown_reply::ShutdownReply;
 use amalthea::wire::shutdown_request::ShutdownRequest;
+use amalthea::wire::stream::Stream;
+use amalthea::wire::stream::StreamOutput;
 use async_trait::async_trait;
+use crossbeam::channel::bounded;
+use crossbeam::channel::unbounded;
+use crossbeam::channel::Receiver;
+use crossbeam::channel::RecvTimeoutError;
 use crossbeam::channel::Sender;
+use harp::exec::RFunction;
+use harp::exec::RFunctionExt;
+use serde_json::json;
+use stdext::spawn;
 
+use crate::events::EVENTS;
+use crate::modules::ARK_ENVS;
+use crate::r_task;
+use crate::r_task::RTaskLane;
 use crate::request::RRequest;
+use crate::shutdown::force_exit_after;
+use crate::shutdown::ShutdownProgress;
+use crate::shutdown::EXIT_TIMEOUT;
+use crate::shutdown::SHUTDOWN_TIMEOUT;
+
+/// How long R has to respond to each step of an interrupt escalation
+const INTERRUPT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How often the watchdog checks whether R has responded to an interrupt
+const INTERRUPT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long a step of the shutdown sequence runs before the frontend is told
+/// what R is busy with
+const SLOW_SHUTDOWN_STEP: Duration = Duration::from_secs(1);
+
+/// How long the R thread has to respond to a diagnostics request before it is
+/// reported as unresponsive
+const DIAGNOSTICS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Incremented on each interrupt request so that only the watchdog of the
+/// latest request escalates
+static INTERRUPT_GENERATION: AtomicUsize = AtomicUsize::new(0);
+
+/// Generation of the latest interrupt R has responded to. Updated by the R
+/// thread so that the watchdog doesn't read R's interrupt flags from another
+/// thread.
+static INTERRUPT_HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+/// Whether R is evaluating a top-level input. Reported in diagnostics.
+static R_EVALUATING: AtomicBool = AtomicBool::new(false);
+
+/// Number of garbage collections run by R. Reported in diagnostics.
+static R_GC_COUNT: AtomicUsize = AtomicUsize::new(0);
 
 pub struct Control {
     r_request_tx: Sender<RRequest>,
+    iopub_tx: Sender<IOPubMessage>,
 }
 
 impl Control {
-    pub fn new(sender: Sender<RRequest>) -> Self {
+    pub fn new(sender: Sender<RRequest>, iopub_tx: Sender<IOPubMessage>) -> Self {
+        listen_r_activity();
         Self {
             r_request_tx: sender,
+            iopub_tx,
         }
     }
 }
@@ -37,19 +96,36 @@ impl ControlHandler for Control {
         log::info!("Received shutdown request: {msg:?}");
 
         // According to the Jupyter protocol we should block here until the
-        // shutdown is complete. However AFAICS ipykernel doesn't wait
-        // until complete shutdown before replying and instead just signals
-        // a shutdown via a global flag picked up by an event loop.
+        // shutdown is complete. We reply right away instead so that the
+        // Control socket stays responsive while R runs its shutdown sequence
+        // (`.Last()`, sinks and connections cleanup, finalizers), e.g. to
+        // interrupt a `.Last()` that hangs. The sequence is watched on a
+        // separate thread that reports its progress to the frontend and
+        // forcefully terminates the process if it doesn't complete in time.
+        let (progress_tx, progress_rx) = unbounded::<ShutdownProgress>();
 
-        let status = if let Err(err) = self.r_request_tx.send(RRequest::Shutdown(msg.restart)) {
+        let request = RRequest::Shutdown(msg.restart, Some(progress_tx));
+        if let Err(err) = self.r_request_tx.send(request) {
             log::error!("Could not deliver shutdown request to execution thread: {err:?}");
-            Status::Error
-        } else {
-            Status::Ok
-        };
+            force_exit_after(EXIT_TIMEOUT);
+            return Ok(ShutdownReply {
+                status: Status::Error,
+                restart: msg.restart,
+            });
+        }
+
+        let iopub_tx = self.iopub_tx.clone();
+        spawn!("ark-shutdown-watcher", move || {
+            watch_shutdown(progress_rx, &iopub_tx, SHUTDOWN_TIMEOUT);
+
+            // R exits on its own once the sequence is complete. This is a
+            // fallback in case it hangs while doing so, or never got to run
+            // the sequence.
+            force_exit_after(EXIT_TIMEOUT);
+        });
 
         Ok(ShutdownReply {
-            status,
+            status: Status::Ok,
             restart: msg.restart,
         })
     }
@@ -57,6 +133,366 @@ impl ControlHandler for Control {
     async fn handle_interrupt_request(&self) -> Result<InterruptReply, Exception> {
         log::info!("Received interrupt request");
         crate::sys::control::handle_interrupt_request();
+        watch_interrupt(self.iopub_tx.clone());
         Ok(InterruptReply { status: Status::Ok })
     }
+
+    async fn handle_diagnostics_request(&self) -> Result<DiagnosticsReply, Exception> {
+        log::info!("Received diagnostics request");
+
+        let pending_tasks = json!({
+            "interrupt": r_task::pending_tasks(RTaskLane::Interactive),
+            "background": r_task::pending_tasks(RTaskLane::Background),
+            "idle": r_task::pending_tasks(RTaskLane::Idle),
+        });
+        let queue_latency = queue_latency_diagnostics();
+
+        // The session is inspected on the R thread. If R is busy, we report
+        // that it is unresponsive rather than blocking the Control socket.
+        let (tx, rx) = bounded(1);
+        let start = std::time::Instant::now();
+        spawn!("ark-diagnostics", move || {
+            let session = r_task(|| -> anyhow::Result<serde_json::Value> {
+                let session =
+                    RFunction::new("", "sessionDiagnostics").call_in(ARK_ENVS.positron_ns)?;
+                Ok(serde_json::Value::try_from(session)?)
+            });
+            let _ = tx.send(session);
+        });
+
+        let (responsive, session) = match rx.recv_timeout(DIAGNOSTICS_TIMEOUT) {
+            Ok(Ok(session)) => (true, session),
+            Ok(Err(err)) => {
+                log::error!("Can't collect session diagnostics: {err:?}");
+                (true, json!({ "error": format!("{err}") }))
+            },
+            Err(_) => (false, serde_json::Value::Null),
+        };
+
+        let diagnostics = json!({
+            "kernel_version": env!("CARGO_PKG_VERSION"),
+            "r_thread": {
+                "responsive": responsive,
+                "response_time_ms": responsive.then(|| start.elapsed().as_millis() as u64),
+                "evaluating": R_EVALUATING.load(Ordering::Relaxed),
+                "gc_count": R_GC_COUNT.load(Ordering::Relaxed),
+            },
+            "pending_tasks": pending_tasks,
+            "queue_latency": queue_latency,
+            "session": session,
+        });
+
+        Ok(DiagnosticsReply {
+            status: Status::Ok,
+            diagnostics,
+        })
+    }
+}
+
+/// Follows the activity of the R thread through its events, so that it can be
+/// reported even when R is unresponsive
+fn listen_r_activity() {
+    static ONCE: Once = Once::new();
+    ONCE.call_once(|| {
+        EVENTS
+            .before_eval
+            .listen(|_| R_EVALUATING.store(true, Ordering::Relaxed));
+        EVENTS
+            .after_eval
+            .listen(|_| R_EVALUATING.store(false, Ordering::Relaxed));
+        EVENTS.gc.listen(|_| {
+            R_GC_COUNT.fetch_add(1, Ordering::Relaxed);
+        });
+    });
+}
+
+/// Time tasks of each lane waited before running on the R thread
+fn queue_latency_diagnostics() -> serde_json::Value {
+    let lane = |lane: RTaskLane| {
+        let latency = r_task::queue_latency(lane);
+        json!({
+            "started": latency.count,
+            "mean_ms": latency.mean().as_millis() as u64,
+            "max_ms": latency.max.as_millis() as u64,
+        })
+    };
+
+    json!({
+        "interactive": lane(RTaskLane::Interactive),
+        "background": lane(RTaskLane::Background),
+        "idle": lane(RTaskLane::Idle),
+    })
+}
+
+/// Follows the progress of the shutdown sequence run by the R thread
+///
+/// Steps that take a while, failed steps, and timeouts are reported to the
+/// frontend. Returns whether the sequence completed within `timeout`.
+fn watch_shutdown(
+    progress_rx: Receiver<ShutdownProgress>,
+    iopub_tx: &Sender<IOPubMessage>,
+    timeout: Duration,
+) -> bool {
+    let start = Instant::now();
+    let deadline = start + timeout;
+
+    // R may be busy before it gets to the shutdown request
+    let mut slow_step = Some((String::from("Waiting for R to be idle"), start));
+
+    loop {
+        let step_deadline = match &slow_step {
+            Some((_, step_start)) => deadline.min(*step_start + SLOW_SHUTDOWN_STEP),
+            None => deadline,
+        };
+
+        match progress_rx.recv_deadline(step_deadline) {
+            Ok(ShutdownProgress::Step(step)) => {
+                log::info!("Shutdown progress: {step}");
+                slow_step = Some((step, Instant::now()));
+            },
+            Ok(ShutdownProgress::Failed(step)) => {
+                log::warn!("Shutdown step failed: {step}");
+                notify_shutdown(iopub_tx, format!("Shutdown step failed: {step}"));
+            },
+            Ok(ShutdownProgress::Complete) => {
+                return true;
+            },
+            Err(RecvTimeoutError::Timeout) if Instant::now() < deadline => {
+                // Only report each slow step once
+                if let Some((step, _)) = slow_step.take() {
+                    notify_shutdown(iopub_tx, format!("Shutting down R: {step}..."));
+                }
+            },
+            Err(RecvTimeoutError::Timeout) => {
+                log::error!("R didn't complete its shutdown sequence within {timeout:?}");
+                notify_shutdown(
+                    iopub_tx,
+                    format!(
+                        "R didn't complete its shutdown sequence within {timeout:?}, terminating the process."
+                    ),
+                );
+                return false;
+            },
+            Err(RecvTimeoutError::Disconnected) => {
+                log::error!("R thread stopped before completing its shutdown sequence");
+                return false;
+            },
+        }
+    }
+}
+
+fn notify_shutdown(iopub_tx: &Sender<IOPubMessage>, text: String) {
+    let message = IOPubMessage::Stream(StreamOutput {
+        name: Stream::Stderr,
+        text: format!("{text}\n"),
+    });
+    if let Err(err) = iopub_tx.send(message) {
+        log::error!("Can't notify frontend of shutdown progress: {err}");
+    }
+}
+
+/// Marks pending interrupts as handled. Called from the R thread when it gets
+/// back to the event loop of `read_console()`, at which point R is responsive
+/// again, whether it handled the interrupt or was already idle.
+pub(crate) fn acknowledge_interrupts() {
+    let generation = INTERRUPT_GENERATION.load(Ordering::SeqCst);
+    INTERRUPT_HANDLED.store(generation, Ordering::SeqCst);
+}
+
+/// Steps taken, in order, while R doesn't respond to an interrupt
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InterruptEscalation {
+    /// Deliver the interrupt to R again
+    HardInterrupt,
+    /// Let the user know that only a restart stops the computation
+    Notify,
+}
+
+/// Escalates an interrupt that R hasn't responded to
+///
+/// Interrupts are only handled when R checks for them, i.e. in the evaluator
+/// or when C code calls `R_CheckUserInterrupt()`. Long running C routines that
+/// don't check for interrupts, e.g. a big `dist()` call, can't be stopped
+/// until they return. Each step of `InterruptEscalation` is taken when R
+/// hasn't gotten back to the prompt within `INTERRUPT_TIMEOUT` of the previous
+/// one. A newer interrupt request takes over the escalation.
+fn watch_interrupt(iopub_tx: Sender<IOPubMessage>) {
+    let generation = INTERRUPT_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let is_handled = move || {
+        INTERRUPT_GENERATION.load(Ordering::SeqCst) != generation ||
+            INTERRUPT_HANDLED.load(Ordering::SeqCst) >= generation
+    };
+
+    spawn!("ark-interrupt-watchdog", move || {
+        escalate_interrupt(is_handled, INTERRUPT_TIMEOUT, |step| {
+            log::warn!("R didn't respond to the interrupt, escalating: {step:?}");
+
+            match step {
+                InterruptEscalation::HardInterrupt => {
+                    crate::sys::control::handle_hard_interrupt_request()
+                },
+                InterruptEscalation::Notify => notify_unresponsive_interrupt(&iopub_tx),
+            }
+        });
+    });
+}
+
+fn escalate_interrupt(
+    is_handled: impl Fn() -> bool,
+    timeout: Duration,
+    mut escalate: impl FnMut(InterruptEscalation),
+) {
+    let steps = [
+        InterruptEscalation::HardInterrupt,
+        InterruptEscalation::Notify,
+    ];
+
+    for step in steps {
+        let start = Instant::now();
+        while !is_handled() {
+            if start.elapsed() >= timeout {
+                break;
+            }
+            std::thread::sleep(INTERRUPT_POLL_INTERVAL);
+        }
+
+        if is_handled() {
+            return;
+        }
+        escalate(step);
+    }
+}
+
+fn notify_unresponsive_interrupt(iopub_tx: &Sender<IOPubMessage>) {
+    let message = IOPubMessage::Stream(StreamOutput {
+        name: Stream::Stderr,
+        text: String::from(
+            "R is not responding to the interrupt. The running computation doesn't check for interrupts and will only stop once it completes. Restart R to stop it now.\n",
+        ),
+    });
+    if let Err(err) = iopub_tx.send(message) {
+        log::error!("Can't notify frontend of unresponsive interrupt: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    use amalthea::socket::iopub::IOPubMessage;
+    use crossbeam::channel::unbounded;
+    use crossbeam::channel::Receiver;
+
+    use crate::control::escalate_interrupt;
+    use crate::control::watch_shutdown;
+    use crate::control::InterruptEscalation;
+    use crate::shutdown::ShutdownProgress;
+
+    fn stderr_messages(iopub_rx: &Receiver<IOPubMessage>) -> Vec<String> {
+        iopub_rx
+            .try_iter()
+            .filter_map(|message| match message {
+                IOPubMessage::Stream(stream) => Some(stream.text),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_watch_shutdown_complete() {
+        let (progress_tx, progress_rx) = unbounded();
+        let (iopub_tx, iopub_rx) = unbounded();
+
+        progress_tx
+            .send(ShutdownProgress::Step(String::from("Running `.Last()`")))
+            .unwrap();
+        progress_tx
+            .send(ShutdownProgress::Failed(String::from(
+                "Running `.Last()`: oops",
+            )))
+            .unwrap();
+        progress_tx.send(ShutdownProgress::Complete).unwrap();
+
+        assert!(watch_shutdown(
+            progress_rx,
+            &iopub_tx,
+            Duration::from_secs(10)
+        ));
+
+        // Fast steps are not reported, failures are
+        assert_eq!(stderr_messages(&iopub_rx), vec![String::from(
+            "Shutdown step failed: Running `.Last()`: oops\n"
+        )]);
+    }
+
+    #[test]
+    fn test_watch_shutdown_slow_step_and_timeout() {
+        let (progress_tx, progress_rx) = unbounded();
+        let (iopub_tx, iopub_rx) = unbounded();
+
+        progress_tx
+            .send(ShutdownProgress::Step(String::from("Running `.Last()`")))
+            .unwrap();
+
+        assert!(!watch_shutdown(
+            progress_rx,
+            &iopub_tx,
+            Duration::from_millis(1500)
+        ));
+
+        let messages = stderr_messages(&iopub_rx);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0], "Shutting down R: Running `.Last()`...\n");
+        assert!(messages[1].starts_with("R didn't complete its shutdown sequence"));
+
+        drop(progress_tx);
+    }
+
+    #[test]
+    fn test_watch_shutdown_r_thread_stopped() {
+        let (progress_tx, progress_rx) = unbounded::<ShutdownProgress>();
+        let (iopub_tx, _iopub_rx) = unbounded();
+
+        drop(progress_tx);
+        assert!(!watch_shutdown(
+            progress_rx,
+            &iopub_tx,
+            Duration::from_secs(10)
+        ));
+    }
+
+    #[test]
+    fn test_escalate_interrupt_unresponsive() {
+        let mut steps = Vec::new();
+        escalate_interrupt(|| false, Duration::from_millis(10), |step| steps.push(step));
+
+        assert_eq!(steps, vec![
+            InterruptEscalation::HardInterrupt,
+            InterruptEscalation::Notify,
+        ]);
+    }
+
+    #[test]
+    fn test_escalate_interrupt_stops_once_handled() {
+        let handled = AtomicBool::new(false);
+        let mut steps = Vec::new();
+
+        // R responds to the hard interrupt
+        escalate_interrupt(
+            || handled.load(Ordering::SeqCst),
+            Duration::from_millis(10),
+            |step| {
+                steps.push(step);
+                handled.store(true, Ordering::SeqCst);
+            },
+        );
+        assert_eq!(steps, vec![InterruptEscalation::HardInterrupt]);
+
+        // R responds right away
+        steps.clear();
+        escalate_interrupt(|| true, Duration::from_secs(10), |step| steps.push(step));
+        assert!(steps.is_empty());
+    }
 }
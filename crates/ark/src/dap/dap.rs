@@ -73,7 +73,7 @@ pub struct Dap {
     /// a `FrameInfo`, or an arbitrarily nested child of one of those
     /// environments if the child has its own children. Reset after each debug step,
     /// allowing us to free our references to the R objects.
-    pub variables_reference_to_r_object: HashMap<i64, RThreadSafe<RObject>>,
+    pub variables_reference_to_r_object: HashMap<i64, Arc<RThreadSafe<RObject>>>,
 
     /// The current `variables_reference`. Unique within a debug session. Reset after
     /// `stop_debug()`, not between debug steps like the hash maps are. If we reset
@@ -193,7 +193,7 @@ impl Dap {
             self.frame_id_to_variables_reference
                 .insert(frame.id, self.current_variables_reference);
             self.variables_reference_to_r_object
-                .insert(self.current_variables_reference, environment);
+                .insert(self.current_variables_reference, Arc::new(environment));
 
             self.current_variables_reference += 1;
         }
@@ -222,7 +222,7 @@ impl Dap {
         let variables_reference = self.current_variables_reference;
 
         self.variables_reference_to_r_object
-            .insert(variables_reference, x);
+            .insert(variables_reference, Arc::new(x));
         self.current_variables_reference += 1;
 
         variables_reference
@@ -75,6 +75,9 @@ pub struct FrameInfo {
     pub start_column: i64,
     pub end_line: i64,
     pub end_column: i64,
+    /// Whether the frame is part of S3, S4 or R6 method dispatch. These frames
+    /// are deemphasized in the call stack and stepped through.
+    pub dispatch: bool,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -331,6 +334,10 @@ impl RMainDap {
             let end_column: i32 = RObject::view(end_column).try_into()?;
             let end_column = end_column + 1;
 
+            i += 1;
+            let dispatch = VECTOR_ELT(info, i);
+            let dispatch: bool = RObject::view(dispatch).try_into()?;
+
             let id = self.next_frame_id();
 
             Ok(FrameInfo {
@@ -343,6 +350,7 @@ impl RMainDap {
                 start_column: start_column.try_into()?,
                 end_line: end_line.try_into()?,
                 end_column: end_column.try_into()?,
+                dispatch,
             })
         }
     }
@@ -357,3 +365,87 @@ impl RMainDap {
         self.current_frame_info_id = 0;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use harp::exec::RFunction;
+    use harp::exec::RFunctionExt;
+
+    use crate::fixtures::package_is_installed;
+    use crate::modules::ARK_ENVS;
+    use crate::r_task;
+
+    #[test]
+    fn test_is_dispatch_frame_s3() {
+        r_task(|| {
+            assert!(is_dispatch_frame("function(x, ...) UseMethod('foo')"));
+            assert!(is_dispatch_frame("function(x, ...) { UseMethod('foo') }"));
+            assert!(!is_dispatch_frame("function(x, ...) x"));
+            assert!(!is_dispatch_frame(
+                "function(x, ...) { UseMethod('foo'); x }"
+            ));
+            assert!(!is_dispatch_frame("base::sum"));
+            assert!(!is_dispatch_frame("1"));
+        })
+    }
+
+    #[test]
+    fn test_is_dispatch_frame_s4() {
+        r_task(|| {
+            harp::parse_eval_global(
+                "arkDispatchEnv <- local({
+                    env <- new.env()
+                    methods::setGeneric(
+                        'arkDispatch',
+                        function(x, ...) standardGeneric('arkDispatch'),
+                        where = env
+                    )
+                    # Same signature as the generic
+                    methods::setMethod('arkDispatch', 'character', function(x, ...) x, where = env)
+                    # Extra argument, wrapped in a `.local()` method
+                    methods::setMethod('arkDispatch', 'numeric', function(x, y = 1, ...) x + y, where = env)
+                    env
+                })",
+            )
+            .unwrap();
+
+            let method = |class: &str| {
+                format!("methods::getMethod('arkDispatch', '{class}', where = arkDispatchEnv)")
+            };
+
+            assert!(is_dispatch_frame(
+                "methods::getGeneric('arkDispatch', where = arkDispatchEnv)"
+            ));
+            assert!(is_dispatch_frame(&method("numeric")));
+            assert!(!is_dispatch_frame(&method("character")));
+
+            harp::parse_eval_global("rm(arkDispatchEnv)").unwrap();
+        })
+    }
+
+    #[test]
+    fn test_is_dispatch_frame_r6() {
+        r_task(|| {
+            if !package_is_installed("R6") {
+                return;
+            }
+
+            // The generator's `new()` is dispatch machinery, the methods of
+            // the class are not
+            let generator = "R6::R6Class('ArkDispatch', public = list(hello = function() 1))";
+            assert!(is_dispatch_frame(&format!("{generator}$new")));
+            assert!(!is_dispatch_frame(&format!(
+                "{generator}$public_methods$hello"
+            )));
+        })
+    }
+
+    fn is_dispatch_frame(fn_code: &str) -> bool {
+        let fun = harp::parse_eval_global(fn_code).unwrap();
+        let out = RFunction::new("", "is_dispatch_frame")
+            .add(fun)
+            .call_in(ARK_ENVS.positron_ns)
+            .unwrap();
+        bool::try_from(out).unwrap()
+    }
+}
@@ -17,6 +17,7 @@ use std::sync::Mutex;
 use amalthea::comm::comm_channel::CommMsg;
 use amalthea::comm::server_comm::ServerStartMessage;
 use amalthea::comm::server_comm::ServerStartedMessage;
+use anyhow::anyhow;
 use crossbeam::channel::bounded;
 use crossbeam::channel::unbounded;
 use crossbeam::channel::Receiver;
@@ -28,6 +29,10 @@ use dap::requests::*;
 use dap::responses::*;
 use dap::server::ServerOutput;
 use dap::types::*;
+use harp::environment::R_ENVS;
+use harp::exec::RFunction;
+use harp::exec::RFunctionExt;
+use harp::object::RObject;
 use stdext::result::ResultOrLog;
 use stdext::spawn;
 
@@ -36,7 +41,9 @@ use super::dap::DapBackendEvent;
 use crate::dap::dap::DapStoppedEvent;
 use crate::dap::dap_r_main::FrameInfo;
 use crate::dap::dap_r_main::FrameSource;
+use crate::dap::dap_variables::evaluate_variable;
 use crate::dap::dap_variables::object_variables;
+use crate::dap::dap_variables::set_env_variable;
 use crate::dap::dap_variables::RVariable;
 use crate::r_task;
 use crate::request::debug_request_command;
@@ -237,6 +244,9 @@ impl<R: Read, W: Write> DapServer<R, W> {
             Command::Threads => {
                 self.handle_threads(req);
             },
+            Command::SetBreakpoints(args) => {
+                self.handle_set_breakpoints(req, args);
+            },
             Command::SetExceptionBreakpoints(args) => {
                 self.handle_set_exception_breakpoints(req, args);
             },
@@ -252,6 +262,12 @@ impl<R: Read, W: Write> DapServer<R, W> {
             Command::Variables(args) => {
                 self.handle_variables(req, args);
             },
+            Command::SetVariable(args) => {
+                self.handle_set_variable(req, args);
+            },
+            Command::Evaluate(args) => {
+                self.handle_evaluate(req, args);
+            },
             Command::Continue(args) => {
                 let resp = ResponseBody::Continue(ContinueResponse {
                     all_threads_continued: Some(true),
@@ -280,6 +296,10 @@ impl<R: Read, W: Write> DapServer<R, W> {
     fn handle_initialize(&mut self, req: Request, _args: InitializeArguments) {
         let rsp = req.success(ResponseBody::Initialize(types::Capabilities {
             supports_restart_request: Some(true),
+            supports_conditional_breakpoints: Some(true),
+            supports_hit_conditional_breakpoints: Some(true),
+            supports_log_points: Some(true),
+            supports_set_variable: Some(true),
             ..Default::default()
         }));
         self.server.respond(rsp).unwrap();
@@ -339,6 +359,28 @@ impl<R: Read, W: Write> DapServer<R, W> {
         self.server.respond(rsp).unwrap();
     }
 
+    fn handle_set_breakpoints(&mut self, req: Request, args: SetBreakpointsArguments) {
+        let source_breakpoints = args.breakpoints.unwrap_or_default();
+
+        let breakpoints = match &args.source.path {
+            Some(path) => r_task(|| set_breakpoints(path, &source_breakpoints)),
+            None => Err(anyhow!("Breakpoints require a source with a path")),
+        };
+
+        let breakpoints = breakpoints.unwrap_or_else(|err| {
+            log::error!("DAP: Can't set breakpoints: {err:?}");
+            source_breakpoints
+                .iter()
+                .map(|breakpoint| into_dap_breakpoint(None, breakpoint.line, None))
+                .collect()
+        });
+
+        let rsp = req.success(ResponseBody::SetBreakpoints(SetBreakpointsResponse {
+            breakpoints,
+        }));
+        self.server.respond(rsp).unwrap();
+    }
+
     fn handle_set_exception_breakpoints(
         &mut self,
         req: Request,
@@ -461,36 +503,107 @@ impl<R: Read, W: Write> DapServer<R, W> {
         let mut out = Vec::with_capacity(variables.len());
 
         for variable in variables.into_iter() {
-            let name = variable.name;
-            let value = variable.value;
-            let type_field = variable.type_field;
-            let variables_reference_object = variable.variables_reference_object;
-
-            // If we have a `variables_reference_object`, then this variable is
-            // structured and has children. We need a new unique
-            // `variables_reference` to return that will map to this object in
-            // a followup `Variables` request.
-            let variables_reference = match variables_reference_object {
-                Some(x) => state.insert_variables_reference_object(x),
-                None => 0,
-            };
+            out.push(into_dap_variable(&mut state, variable));
+        }
+
+        out
+    }
+
+    fn handle_set_variable(&mut self, req: Request, args: SetVariableArguments) {
+        let variable = self.set_r_variable(args.variables_reference, &args.name, &args.value);
 
-            let variable = Variable {
-                name,
-                value,
-                type_field,
-                presentation_hint: None,
-                evaluate_name: None,
-                variables_reference,
-                named_variables: None,
-                indexed_variables: None,
-                memory_reference: None,
+        let rsp = match variable {
+            Ok(variable) => {
+                let variable = {
+                    let mut state = self.state.lock().unwrap();
+                    into_dap_variable(&mut state, variable)
+                };
+                req.success(ResponseBody::SetVariable(SetVariableResponse {
+                    value: variable.value,
+                    type_field: variable.type_field,
+                    variables_reference: Some(variable.variables_reference),
+                    named_variables: None,
+                    indexed_variables: None,
+                }))
+            },
+            Err(err) => req.error(&format!("{err}")),
+        };
+
+        self.server.respond(rsp).unwrap();
+    }
+
+    fn set_r_variable(
+        &self,
+        variables_reference: i64,
+        name: &str,
+        value: &str,
+    ) -> anyhow::Result<RVariable> {
+        // Don't hold the lock while R evaluates `value`
+        let object = {
+            let state = self.state.lock().unwrap();
+            let variables_reference_to_r_object = &state.variables_reference_to_r_object;
+
+            let Some(object) = variables_reference_to_r_object.get(&variables_reference) else {
+                return Err(anyhow!(
+                    "Failed to locate R object for `variables_reference` {variables_reference}."
+                ));
             };
+            object.clone()
+        };
 
-            out.push(variable);
-        }
+        r_task(|| set_env_variable(object.get().sexp, name, value))
+    }
 
-        out
+    // Watch expressions are evaluated again by the frontend on each stop, in
+    // the frame selected at that time
+    fn handle_evaluate(&mut self, req: Request, args: EvaluateArguments) {
+        let variable = self.evaluate_r_expression(&args.expression, args.frame_id);
+
+        let rsp = match variable {
+            Ok(variable) => {
+                let variable = {
+                    let mut state = self.state.lock().unwrap();
+                    into_dap_variable(&mut state, variable)
+                };
+                req.success(ResponseBody::Evaluate(EvaluateResponse {
+                    result: variable.value,
+                    type_field: variable.type_field,
+                    presentation_hint: None,
+                    variables_reference: variable.variables_reference,
+                    named_variables: None,
+                    indexed_variables: None,
+                    memory_reference: None,
+                }))
+            },
+            Err(err) => req.error(&format!("{err}")),
+        };
+
+        self.server.respond(rsp).unwrap();
+    }
+
+    fn evaluate_r_expression(
+        &self,
+        expression: &str,
+        frame_id: Option<i64>,
+    ) -> anyhow::Result<RVariable> {
+        // Without a frame, or for frames without variables like the top
+        // level one, evaluate in the global environment. Don't hold the lock
+        // while R evaluates the expression.
+        let environment = {
+            let state = self.state.lock().unwrap();
+            frame_id
+                .and_then(|id| state.frame_id_to_variables_reference.get(&id))
+                .and_then(|reference| state.variables_reference_to_r_object.get(reference))
+                .cloned()
+        };
+
+        r_task(|| {
+            let environment = match environment {
+                Some(environment) => environment.get().sexp,
+                None => R_ENVS.global,
+            };
+            evaluate_variable(expression, environment)
+        })
     }
 
     fn handle_step<A>(&mut self, req: Request, _args: A, cmd: DebugRequest, resp: ResponseBody) {
@@ -515,6 +628,29 @@ impl<R: Read, W: Write> DapServer<R, W> {
     }
 }
 
+fn into_dap_variable(state: &mut Dap, variable: RVariable) -> Variable {
+    // If we have a `variables_reference_object`, then this variable is
+    // structured and has children. We need a new unique
+    // `variables_reference` to return that will map to this object in
+    // a followup `Variables` request.
+    let variables_reference = match variable.variables_reference_object {
+        Some(x) => state.insert_variables_reference_object(x),
+        None => 0,
+    };
+
+    Variable {
+        name: variable.name,
+        value: variable.value,
+        type_field: variable.type_field,
+        presentation_hint: None,
+        evaluate_name: None,
+        variables_reference,
+        named_variables: None,
+        indexed_variables: None,
+        memory_reference: None,
+    }
+}
+
 fn into_dap_frame(frame: &FrameInfo, fallback_sources: &HashMap<String, String>) -> StackFrame {
     let id = frame.id;
     let source_name = frame.source_name.clone();
@@ -558,6 +694,240 @@ fn into_dap_frame(frame: &FrameInfo, fallback_sources: &HashMap<String, String>)
         can_restart: None,
         instruction_pointer_reference: None,
         module_id: None,
-        presentation_hint: None,
+        presentation_hint: frame.dispatch.then_some(StackFramePresentationhint::Subtle),
+    }
+}
+
+const UNVERIFIED_BREAKPOINT_MESSAGE: &str =
+    "No function defined in this file contains this line. Source the file and try again.";
+
+/// Injects the breakpoints of the file at `path` in the functions it defines.
+/// Breakpoints on lines outside of any known function are not verified.
+fn set_breakpoints(
+    path: &str,
+    breakpoints: &[SourceBreakpoint],
+) -> anyhow::Result<Vec<Breakpoint>> {
+    let lines: Vec<i64> = breakpoints
+        .iter()
+        .map(|breakpoint| breakpoint.line)
+        .collect();
+
+    // Missing fields are sent as empty strings
+    let or_empty = |field: &Option<String>| field.clone().unwrap_or_default();
+    let conditions: Vec<String> = breakpoints
+        .iter()
+        .map(|breakpoint| or_empty(&breakpoint.condition))
+        .collect();
+    let hit_conditions: Vec<String> = breakpoints
+        .iter()
+        .map(|breakpoint| or_empty(&breakpoint.hit_condition))
+        .collect();
+    let log_messages: Vec<String> = breakpoints
+        .iter()
+        .map(|breakpoint| or_empty(&breakpoint.log_message))
+        .collect();
+
+    let result = RFunction::from(".ps.debug.setBreakpoints")
+        .param("path", path)
+        .param("lines", &lines)
+        .param("conditions", conditions)
+        .param("hit_conditions", hit_conditions)
+        .param("log_messages", log_messages)
+        .call()?;
+
+    let ids = Vec::<i32>::try_from(&RObject::view(harp::list_get(result.sexp, 0)))?;
+    let verified = Vec::<i32>::try_from(&RObject::view(harp::list_get(result.sexp, 1)))?;
+
+    let breakpoints = breakpoints
+        .iter()
+        .zip(ids.into_iter().zip(verified))
+        .map(|(breakpoint, (id, verified))| {
+            let message = (verified == 0).then(|| String::from(UNVERIFIED_BREAKPOINT_MESSAGE));
+            into_dap_breakpoint(Some(id as i64), breakpoint.line, message)
+        })
+        .collect();
+
+    Ok(breakpoints)
+}
+
+fn into_dap_breakpoint(id: Option<i64>, line: i64, message: Option<String>) -> Breakpoint {
+    Breakpoint {
+        id,
+        verified: message.is_none() && id.is_some(),
+        message,
+        source: None,
+        line: Some(line),
+        column: None,
+        end_line: None,
+        end_column: None,
+        instruction_reference: None,
+        offset: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+    use std::io::BufWriter;
+    use std::io::Cursor;
+    use std::io::Write;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use crossbeam::channel::unbounded;
+    use dap::types::SourceBreakpoint;
+    use serde_json::json;
+
+    use crate::dap::dap::Dap;
+    use crate::dap::dap_server::set_breakpoints;
+    use crate::dap::dap_server::DapServer;
+    use crate::dap::dap_server::UNVERIFIED_BREAKPOINT_MESSAGE;
+    use crate::r_task;
+
+    #[test]
+    fn test_set_breakpoints_request() {
+        r_task(|| {
+            let file =
+                source_file("ark_test_breakpoints <- function(x) {\n  y <- x + 1\n  y * 2\n}\n");
+            let path = r_path(&file);
+
+            let response = serve_request(json!({
+                "seq": 1,
+                "type": "request",
+                "command": "setBreakpoints",
+                "arguments": {
+                    "source": { "path": path },
+                    "breakpoints": [
+                        { "line": 2, "condition": "x > 100" },
+                        { "line": 10 }
+                    ]
+                }
+            }));
+
+            // The breakpoint inside the function is verified, the one past
+            // the end of the file is not
+            let breakpoints = response["body"]["breakpoints"].as_array().unwrap();
+            assert_eq!(breakpoints.len(), 2);
+            assert_eq!(breakpoints[0]["verified"], json!(true));
+            assert_eq!(breakpoints[0]["line"], json!(2));
+            assert_eq!(breakpoints[1]["verified"], json!(false));
+            assert_eq!(
+                breakpoints[1]["message"],
+                json!(UNVERIFIED_BREAKPOINT_MESSAGE)
+            );
+
+            // The guard is injected and doesn't pause when the condition is false
+            assert!(is_injected("ark_test_breakpoints"));
+            let value = harp::parse_eval_global("ark_test_breakpoints(1)").unwrap();
+            assert_eq!(f64::try_from(value).unwrap(), 4.0);
+
+            // Clearing the breakpoints of the file restores the function
+            let response = serve_request(json!({
+                "seq": 2,
+                "type": "request",
+                "command": "setBreakpoints",
+                "arguments": {
+                    "source": { "path": path },
+                    "breakpoints": []
+                }
+            }));
+            assert_eq!(response["body"]["breakpoints"], json!([]));
+            assert!(!is_injected("ark_test_breakpoints"));
+
+            harp::parse_eval_global("rm(ark_test_breakpoints)").unwrap();
+        })
+    }
+
+    #[test]
+    fn test_set_breakpoints_logpoint_hit_condition() {
+        r_task(|| {
+            let file = source_file("ark_test_logpoints <- function(x) {\n  x\n}\n");
+            let path = r_path(&file);
+
+            let breakpoint = SourceBreakpoint {
+                line: 2,
+                column: None,
+                condition: None,
+                hit_condition: Some(String::from(">= 2")),
+                log_message: Some(String::from("x is {x}")),
+            };
+            let breakpoints = set_breakpoints(&path, &[breakpoint]).unwrap();
+            assert_eq!(breakpoints.len(), 1);
+            assert!(breakpoints[0].verified);
+
+            // Logpoints print instead of pausing, from the second hit on
+            let output = harp::parse_eval_global(
+                "utils::capture.output(for (i in 1:3) ark_test_logpoints(i))",
+            )
+            .unwrap();
+            assert_eq!(Vec::<String>::try_from(output).unwrap(), vec![
+                String::from("x is 2"),
+                String::from("x is 3"),
+            ]);
+
+            set_breakpoints(&path, &[]).unwrap();
+            assert!(!is_injected("ark_test_logpoints"));
+
+            harp::parse_eval_global("rm(ark_test_logpoints)").unwrap();
+        })
+    }
+
+    /// Writes `code` to a file and sources it in the global environment with
+    /// srcrefs, which breakpoints need to find the functions of the file
+    fn source_file(code: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::Builder::new().suffix(".R").tempfile().unwrap();
+        write!(file, "{code}").unwrap();
+
+        let path = r_path(&file);
+        harp::parse_eval_global(&format!("source('{path}', keep.source = TRUE)")).unwrap();
+
+        file
+    }
+
+    fn r_path(file: &tempfile::NamedTempFile) -> String {
+        file.path().to_string_lossy().replace('\\', "/")
+    }
+
+    fn is_injected(name: &str) -> bool {
+        let code =
+            format!("any(grepl('.ps.debug.breakpointHit', deparse(body({name})), fixed = TRUE))");
+        bool::try_from(harp::parse_eval_global(&code).unwrap()).unwrap()
+    }
+
+    /// Runs a DAP request through a server and returns its response
+    fn serve_request(request: serde_json::Value) -> serde_json::Value {
+        let request = request.to_string();
+        let input = format!("Content-Length: {}\r\n\r\n{request}", request.len());
+
+        let output = SharedBuffer::default();
+        let (r_request_tx, _r_request_rx) = unbounded();
+        let (comm_tx, _comm_rx) = unbounded();
+
+        let mut server = DapServer::new(
+            BufReader::new(Cursor::new(input.into_bytes())),
+            BufWriter::new(output.clone()),
+            Dap::new_shared(r_request_tx.clone()),
+            r_request_tx,
+            comm_tx,
+        );
+        assert!(server.serve());
+
+        let output = output.0.lock().unwrap();
+        let output = String::from_utf8_lossy(&output);
+        let (_header, body) = output.split_once("\r\n\r\n").unwrap();
+        serde_json::from_str(body).unwrap()
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
     }
 }
@@ -5,6 +5,9 @@
 //
 //
 
+use anyhow::anyhow;
+use harp::exec::RFunction;
+use harp::exec::RFunctionExt;
 use harp::object::*;
 use harp::r_symbol;
 use harp::symbol::RSymbol;
@@ -92,6 +95,38 @@ pub(super) fn object_variables(x: SEXP) -> Vec<RVariable> {
     }
 }
 
+/// Assigns the result of evaluating `value` in the environment `env` to `name`
+///
+/// Used by `SetVariable` DAP requests. The value is evaluated in `env` so
+/// that it may refer to other variables of the frame, e.g. `x + 1`.
+pub(super) fn set_env_variable(env: SEXP, name: &str, value: &str) -> anyhow::Result<RVariable> {
+    if r_typeof(env) != ENVSXP {
+        return Err(anyhow!(
+            "Can't set `{name}`, only variables of environments can be modified."
+        ));
+    }
+
+    let value = harp::parse_eval0(value, env)?;
+
+    // Goes through `assign()` so that locked bindings are reported as errors
+    RFunction::new("base", "assign")
+        .add(name)
+        .add(value.sexp)
+        .param("envir", env)
+        .call()?;
+
+    Ok(object_variable(String::from(name), value.sexp))
+}
+
+/// Evaluates `expression` in the environment `env`
+///
+/// Used by `Evaluate` DAP requests, e.g. for the watch expressions that the
+/// frontend evaluates again each time the debugger stops.
+pub(super) fn evaluate_variable(expression: &str, env: SEXP) -> anyhow::Result<RVariable> {
+    let value = harp::parse_eval0(expression, env)?;
+    Ok(object_variable(String::from(expression), value.sexp))
+}
+
 fn env_variables(x: SEXP) -> Vec<RVariable> {
     let names = RObject::from(r_env_names(x));
     let names = Vec::<String>::try_from(names).unwrap_or(Vec::new());
@@ -411,6 +446,8 @@ mod tests {
     use libr::*;
 
     use crate::dap::dap_variables::env_binding_variable;
+    use crate::dap::dap_variables::evaluate_variable;
+    use crate::dap::dap_variables::set_env_variable;
     use crate::r_task;
 
     #[test]
@@ -478,4 +515,54 @@ mod tests {
             assert_eq!(variable.type_field, Some(String::from("<active binding>")));
         })
     }
+
+    #[test]
+    fn test_set_env_variable() {
+        r_task(|| {
+            let env = RFunction::new("base", "new.env")
+                .param("parent", R_ENVS.base)
+                .call()
+                .unwrap();
+            harp::parse_eval0("a <- 1L", env.sexp).unwrap();
+
+            // The value is evaluated in the environment
+            let variable = set_env_variable(env.sexp, "a", "a + 1L").unwrap();
+            assert_eq!(variable.name, String::from("a"));
+            assert_eq!(variable.value, String::from("2L"));
+
+            let variable = env_binding_variable(String::from("a"), env.sexp).unwrap();
+            assert_eq!(variable.value, String::from("2L"));
+
+            // New bindings can be created too
+            let variable = set_env_variable(env.sexp, "b", "TRUE").unwrap();
+            assert_eq!(variable.type_field, Some(String::from("<logical>")));
+
+            // Locked bindings and evaluation errors are reported
+            harp::parse_eval0("lockBinding('a', environment())", env.sexp).unwrap();
+            assert!(set_env_variable(env.sexp, "a", "3L").is_err());
+            assert!(set_env_variable(env.sexp, "b", "stop('oh no')").is_err());
+        })
+    }
+
+    #[test]
+    fn test_evaluate_variable() {
+        r_task(|| {
+            let env = RFunction::new("base", "new.env")
+                .param("parent", R_ENVS.base)
+                .call()
+                .unwrap();
+            harp::parse_eval0("a <- 1L", env.sexp).unwrap();
+
+            let variable = evaluate_variable("a + 1L", env.sexp).unwrap();
+            assert_eq!(variable.name, String::from("a + 1L"));
+            assert_eq!(variable.value, String::from("2L"));
+            assert_eq!(variable.type_field, Some(String::from("<integer>")));
+
+            // Lists can be expanded
+            let variable = evaluate_variable("list(a = a)", env.sexp).unwrap();
+            assert!(variable.variables_reference_object.is_some());
+
+            assert!(evaluate_variable("b", env.sexp).is_err());
+        })
+    }
 }
@@ -0,0 +1,195 @@
+//
+// backend.rs
+//
+// Copyright (C) 2025 by Posit Software, PBC
+//
+//
+
+use std::collections::HashMap;
+
+use amalthea::comm::data_explorer_comm::ColumnSortKey;
+use amalthea::comm::data_explorer_comm::RowFilter;
+use amalthea::comm::data_explorer_comm::RowFilterGroup;
+use anyhow::bail;
+use harp::exec::RFunction;
+use harp::exec::RFunctionExt;
+use harp::object::RObject;
+use harp::tbl_get_column;
+use harp::utils::r_typeof;
+use harp::vector::ListBuilder;
+use harp::vector::LogicalVector;
+use harp::TableKind;
+use libr::STRSXP;
+
+use crate::data_explorer::natural_sort::natural_ranks;
+use crate::data_explorer::r_data_explorer::SortCollation;
+use crate::data_explorer::row_filter_tree::row_filter_tree;
+use crate::data_explorer::table::Table;
+use crate::modules::ARK_ENVS;
+use crate::r_task;
+
+/// The rows that pass a set of row filters
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FilteredRows {
+    /// The 1-based indices of the rows that pass the filters, in ascending
+    /// order
+    pub indices: Vec<i32>,
+
+    /// An error per filter, where `None` means the filter was applied
+    /// successfully
+    pub errors: Vec<Option<String>>,
+}
+
+/// The computations on the viewed data that the data explorer delegates to
+/// its backend. The state of the view (sort keys, filters, and the indices
+/// they produce) is managed by `ViewState` independently of the backend, so
+/// that it can be tested with a mock table and shared by other kinds of
+/// tables in the future (e.g. Arrow or polars data).
+///
+/// Row indices are 1-based.
+pub(crate) trait TableBackend {
+    /// Orders all the rows of the table according to `sort_keys`. The sort
+    /// must be stable and missing values must come last.
+    fn sort_rows(&self, sort_keys: &[ColumnSortKey]) -> anyhow::Result<Vec<i32>>;
+
+    /// Finds the rows that pass `filters`, combined according to `groups`
+    /// (see `row_filter_tree()`). Filters that fail are reported as errors
+    /// rather than failing the whole computation.
+    fn filter_rows(
+        &self,
+        filters: &[RowFilter],
+        groups: &[RowFilterGroup],
+    ) -> anyhow::Result<FilteredRows>;
+}
+
+/// Backend for data frames and matrices living in the R session. Its
+/// methods can be called from any thread, the computations are sent to the R
+/// thread with `r_task()`.
+pub(crate) struct RTableBackend<'a> {
+    table: &'a Table,
+    kind: TableKind,
+}
+
+impl<'a> RTableBackend<'a> {
+    pub fn new(table: &'a Table, kind: TableKind) -> Self {
+        Self { table, kind }
+    }
+}
+
+impl TableBackend for RTableBackend<'_> {
+    fn sort_rows(&self, sort_keys: &[ColumnSortKey]) -> anyhow::Result<Vec<i32>> {
+        r_task(|| self.r_sort_rows(sort_keys))
+    }
+
+    fn filter_rows(
+        &self,
+        filters: &[RowFilter],
+        groups: &[RowFilterGroup],
+    ) -> anyhow::Result<FilteredRows> {
+        r_task(|| self.r_filter_rows(filters, groups))
+    }
+}
+
+// Methods that must be run on the main R thread
+impl RTableBackend<'_> {
+    /// Sort the rows of the data object according to the sort keys.
+    ///
+    /// The sort is stable: rows that compare equal on all sort keys keep
+    /// their original order, in both ascending and descending sorts. Missing
+    /// values always come last. Character columns are compared with the
+    /// collation set by the `ark.data_explorer.collation` option, see
+    /// `SortCollation`, or in natural order for keys with the `natural` flag,
+    /// see `natural_cmp()`.
+    ///
+    /// Returns a vector containing the sorted row indices.
+    fn r_sort_rows(&self, sort_keys: &[ColumnSortKey]) -> anyhow::Result<Vec<i32>> {
+        let mut order = RFunction::new("base", "order");
+        let collation = SortCollation::from_option();
+
+        // Allocate a vector to hold the sort order for each column
+        let mut decreasing: Vec<bool> = Vec::new();
+
+        // For each sort key, add an argument to order
+        for key in sort_keys {
+            // Get the column to sort by
+            let column =
+                tbl_get_column(self.table.get()?.sexp, key.column_index as i32, self.kind)?;
+
+            // Character columns are replaced by their ranks in the collation
+            // so that they can still be sorted with the radix method below.
+            // Natural ranks are computed in Rust, which is much faster than
+            // comparing the strings with R callbacks.
+            let column = if key.natural.unwrap_or(false) && r_typeof(column.sexp) == STRSXP {
+                natural_ranks(column.sexp)?
+            } else {
+                RFunction::new("", "sort_collation_key")
+                    .add(column)
+                    .add(collation.to_string())
+                    .call_in(ARK_ENVS.positron_ns)?
+            };
+
+            order.add(column);
+            decreasing.push(!key.ascending);
+        }
+        // Add the sort order per column
+        order.param("decreasing", LogicalVector::from_iter(decreasing));
+        order.param("method", RObject::from("radix"));
+
+        // Invoke the order function and return the result
+        let result = order.call()?;
+        let indices: Vec<i32> = result.try_into()?;
+        Ok(indices)
+    }
+
+    /// Filter all the rows in the data object according to the row filters.
+    ///
+    /// Returns all the row indices that pass the filters and a vector of
+    /// errors, where None means no error happened.
+    fn r_filter_rows(
+        &self,
+        row_filters: &[RowFilter],
+        row_filter_groups: &[RowFilterGroup],
+    ) -> anyhow::Result<FilteredRows> {
+        // Convert each filter to an R object by marshaling through the JSON
+        // layer.
+        //
+        // This feels a little weird since the filters were *unmarshaled* from
+        // JSON earlier in the RPC stack, but it's the easiest way to create R
+        // objects from the filter data without creating an unnecessary
+        // intermediate representation.
+        let mut filters = ListBuilder::with_capacity(row_filters.len());
+        for filter in row_filters {
+            let filter = serde_json::to_value(filter)?;
+            filters.push(RObject::try_from(filter)?);
+        }
+
+        // The filters are combined according to their groups
+        let tree: Vec<serde_json::Value> = row_filter_tree(row_filters, row_filter_groups)
+            .iter()
+            .map(|node| node.to_json())
+            .collect();
+        let tree = RObject::try_from(tree)?;
+
+        // Pass the row filters to R and get the resulting row indices
+        let filters = filters.build()?;
+        let result: HashMap<String, RObject> = RFunction::new("", ".ps.filter_rows")
+            .param("table", self.table.get()?.sexp)
+            .param("row_filters", filters)
+            .param("filter_tree", tree)
+            .call_in(ARK_ENVS.positron_ns)?
+            .try_into()?;
+
+        // Handle errors that occured in the filters
+        let indices = match result.get("indices") {
+            Some(indices) => Vec::<i32>::try_from(indices.clone())?,
+            None => bail!("Unexpected output from .ps.filter_rows. Expected 'indices' field."),
+        };
+
+        let errors = match result.get("errors") {
+            Some(errors) => Vec::<Option<String>>::try_from(errors.clone())?,
+            None => bail!("Unexpected output from .ps.filter_rows. Expected 'errors' field."),
+        };
+
+        Ok(FilteredRows { indices, errors })
+    }
+}
@@ -31,6 +31,7 @@ use crate::data_explorer::histogram;
 use crate::data_explorer::summary_stats::summary_stats;
 use crate::data_explorer::table::Table;
 use crate::data_explorer::utils::display_type;
+use crate::data_explorer::utils::is_list_column;
 use crate::modules::ARK_ENVS;
 
 pub struct ProcessColumnsProfilesParams {
@@ -261,6 +262,16 @@ fn profile_histogram(
             _ => return Err(anyhow!("Wrong type of parameters for the histogram.")),
         },
     };
+
+    // For list columns, the histogram is computed over the lengths of the elements
+    let column = if is_list_column(column.sexp) {
+        RFunction::from("list_lengths")
+            .add(column)
+            .call_in(ARK_ENVS.positron_ns)?
+    } else {
+        column
+    };
+
     let histogram = histogram::profile_histogram(column.sexp, &params, &format_options)?;
     Ok(histogram)
 }
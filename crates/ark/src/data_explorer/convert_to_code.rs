@@ -11,10 +11,15 @@ use amalthea::comm::data_explorer_comm::ConvertToCodeParams;
 use amalthea::comm::data_explorer_comm::ConvertedCode;
 use amalthea::comm::data_explorer_comm::FilterComparisonOp;
 use amalthea::comm::data_explorer_comm::RowFilter;
+use amalthea::comm::data_explorer_comm::RowFilterCondition;
+use amalthea::comm::data_explorer_comm::RowFilterGroup;
 use amalthea::comm::data_explorer_comm::RowFilterParams;
 use amalthea::comm::data_explorer_comm::RowFilterType;
 use amalthea::comm::data_explorer_comm::TextSearchType;
 
+use crate::data_explorer::row_filter_tree::row_filter_tree;
+use crate::data_explorer::row_filter_tree::RowFilterNode;
+
 /// Sort key with resolved column name
 #[derive(Clone, Debug)]
 pub struct ResolvedSortKey {
@@ -92,23 +97,122 @@ impl FilterHandler for DplyrFilterHandler {
 }
 
 impl DplyrFilterHandler {
-    fn convert_filters(&self, filters: &[RowFilter]) -> Option<String> {
+    fn convert_filters(&self, filters: &[RowFilter], groups: &[RowFilterGroup]) -> Option<String> {
         if filters.is_empty() {
             return None;
         }
 
-        let filter_expressions: Vec<String> = filters
+        let nodes = row_filter_tree(filters, groups);
+
+        let converted: Vec<(&RowFilterCondition, FilterExpr)> = nodes
             .iter()
-            .filter_map(|filter| self.convert_filter(filter))
+            .filter_map(|node| Some((node.condition(), self.convert_node(node)?)))
             .collect();
 
-        if filter_expressions.is_empty() {
-            None
+        if converted.is_empty() {
+            return None;
+        }
+
+        // When all the top-level filters are combined with AND, pass them as
+        // separate arguments to `filter()`. Otherwise, combine them in a
+        // single expression.
+        let filter_expressions: Vec<String> = if converted
+            .iter()
+            .skip(1)
+            .all(|(condition, _)| **condition == RowFilterCondition::And)
+        {
+            converted.into_iter().map(|(_, expr)| expr.code).collect()
+        } else {
+            self.convert_nodes(&nodes)
+                .map(|(expr, _)| expr.code)
+                .into_iter()
+                .collect()
+        };
+
+        Some(format!(
+            "filter(\n    {}\n  )",
+            filter_expressions.join(",\n    ")
+        ))
+    }
+
+    fn convert_node(&self, node: &RowFilterNode) -> Option<FilterExpr> {
+        match node {
+            RowFilterNode::Filter { filter, .. } => {
+                let code = self.convert_filter(filter)?;
+                Some(FilterExpr {
+                    code,
+                    has_or: filter.filter_type == RowFilterType::NotBetween,
+                    is_group: false,
+                })
+            },
+            RowFilterNode::Group { children, .. } => {
+                let (expr, n) = self.convert_nodes(children)?;
+                Some(FilterExpr {
+                    is_group: expr.is_group || n > 1,
+                    ..expr
+                })
+            },
+        }
+    }
+
+    /// Combines the nodes from left to right. The condition of the first
+    /// node is ignored. Also returns the number of converted nodes.
+    fn convert_nodes(&self, nodes: &[RowFilterNode]) -> Option<(FilterExpr, usize)> {
+        let mut result: Option<FilterExpr> = None;
+        let mut n = 0;
+
+        for node in nodes {
+            let Some(expr) = self.convert_node(node) else {
+                continue;
+            };
+            n += 1;
+
+            result = Some(match result {
+                None => expr,
+                Some(lhs) => {
+                    let condition = node.condition();
+                    let op = match condition {
+                        RowFilterCondition::And => "&",
+                        RowFilterCondition::Or => "|",
+                    };
+                    FilterExpr {
+                        code: format!(
+                            "{} {} {}",
+                            lhs.operand(condition),
+                            op,
+                            expr.operand(condition)
+                        ),
+                        has_or: *condition == RowFilterCondition::Or,
+                        is_group: false,
+                    }
+                },
+            });
+        }
+
+        result.map(|expr| (expr, n))
+    }
+}
+
+/// A filter expression in R code
+struct FilterExpr {
+    code: String,
+
+    /// Whether the expression has a top-level `|` that needs parentheses
+    /// when combined with `&`
+    has_or: bool,
+
+    /// Whether the expression is a group, which is always parenthesized
+    /// when combined with another expression
+    is_group: bool,
+}
+
+impl FilterExpr {
+    /// The code of the expression as an operand of `&` or `|`
+    fn operand(&self, condition: &RowFilterCondition) -> String {
+        if self.is_group || (self.has_or && *condition == RowFilterCondition::And) {
+            format!("({})", self.code)
         } else {
-            Some(format!(
-                "filter(\n    {}\n  )",
-                filter_expressions.join(",\n    ")
-            ))
+            self.code.clone()
         }
     }
 }
@@ -155,7 +259,10 @@ impl CodeConverter for DplyrCodeConverter {
         let sort_handler = DplyrSortHandler;
 
         // Add filter operations
-        if let Some(filter_op) = filter_handler.convert_filters(&params.row_filters) {
+        let row_filter_groups = params.row_filter_groups.as_deref().unwrap_or_default();
+        if let Some(filter_op) =
+            filter_handler.convert_filters(&params.row_filters, row_filter_groups)
+        {
             builder.add_operation(filter_op);
         }
 
@@ -482,6 +589,8 @@ mod tests {
             })),
             is_valid: Some(true),
             error_message: None,
+            group_id: None,
+            display_text: None,
         }
     }
 
@@ -503,6 +612,8 @@ mod tests {
             })),
             is_valid: Some(true),
             error_message: None,
+            group_id: None,
+            display_text: None,
         }
     }
 
@@ -525,6 +636,8 @@ mod tests {
             })),
             is_valid: Some(true),
             error_message: None,
+            group_id: None,
+            display_text: None,
         }
     }
 
@@ -675,6 +788,8 @@ mod tests {
             params: None,
             is_valid: Some(true),
             error_message: None,
+            group_id: None,
+            display_text: None,
         };
         let result_true = filter_handler.convert_filter(&is_true_filter);
         assert_eq!(result_true, Some("active".to_string()));
@@ -688,6 +803,8 @@ mod tests {
             params: None,
             is_valid: Some(true),
             error_message: None,
+            group_id: None,
+            display_text: None,
         };
         let result_false = filter_handler.convert_filter(&is_false_filter);
         assert_eq!(result_false, Some("!active".to_string()));
@@ -752,13 +869,71 @@ mod tests {
             ),
         ];
 
-        let result = filter_handler.convert_filters(&filters);
+        let result = filter_handler.convert_filters(&filters, &[]);
         assert_eq!(
             result,
             Some("filter(\n    price > 100,\n    category == \"Electronics\"\n  )".to_string())
         );
     }
 
+    #[test]
+    fn test_filter_groups() {
+        let filter_handler = DplyrFilterHandler;
+        let filter = |column_name: &str, value: &str, group_id: Option<&str>| RowFilter {
+            group_id: group_id.map(String::from),
+            ..comparison_filter(
+                column_name,
+                FilterComparisonOp::Eq,
+                value,
+                ColumnDisplayType::Integer,
+            )
+        };
+        let group = |group_id: &str, condition: RowFilterCondition, parent_id: Option<&str>| {
+            RowFilterGroup {
+                group_id: group_id.to_string(),
+                condition,
+                parent_id: parent_id.map(String::from),
+            }
+        };
+
+        // `x == 1, (y == 2 | z == 3)`
+        let mut z = filter("z", "3", Some("g"));
+        z.condition = RowFilterCondition::Or;
+        let filters = vec![filter("x", "1", None), filter("y", "2", Some("g")), z];
+        let groups = vec![group("g", RowFilterCondition::And, None)];
+        assert_eq!(
+            filter_handler.convert_filters(&filters, &groups),
+            Some("filter(\n    x == 1,\n    y == 2 | z == 3\n  )".to_string())
+        );
+
+        // `x == 1 | (y == 2 & (z == 3 | w == 4))`
+        let mut w = filter("w", "4", Some("inner"));
+        w.condition = RowFilterCondition::Or;
+        let filters = vec![
+            filter("x", "1", None),
+            filter("y", "2", Some("outer")),
+            filter("z", "3", Some("inner")),
+            w,
+        ];
+        let groups = vec![
+            group("outer", RowFilterCondition::Or, None),
+            group("inner", RowFilterCondition::And, Some("outer")),
+        ];
+        assert_eq!(
+            filter_handler.convert_filters(&filters, &groups),
+            Some("filter(\n    x == 1 | (y == 2 & (z == 3 | w == 4))\n  )".to_string())
+        );
+
+        // Filters are combined from left to right: `(x == 1 | y == 2) & z == 3`
+        let mut y = filter("y", "2", None);
+        y.condition = RowFilterCondition::Or;
+        let filters = vec![filter("x", "1", None), y, filter("z", "3", None)];
+        assert_eq!(
+            filter_handler.convert_filters(&filters, &[]),
+            Some("filter(\n    (x == 1 | y == 2) & z == 3\n  )".to_string())
+        );
+    }
+
     #[test]
     fn test_complete_conversion_filters_only() {
         let params = ConvertToCodeParams {
@@ -769,6 +944,7 @@ mod tests {
                 "100",
                 ColumnDisplayType::Floating,
             )],
+            row_filter_groups: None,
             sort_keys: vec![],
             code_syntax_name: amalthea::comm::data_explorer_comm::CodeSyntaxName {
                 code_syntax_name: "dplyr".to_string(),
@@ -789,6 +965,7 @@ mod tests {
         let params = ConvertToCodeParams {
             column_filters: vec![],
             row_filters: vec![],
+            row_filter_groups: None,
             sort_keys: vec![], // Note: sort_keys in params are ignored, we use resolved_sort_keys
             code_syntax_name: amalthea::comm::data_explorer_comm::CodeSyntaxName {
                 code_syntax_name: "dplyr".to_string(),
@@ -827,6 +1004,7 @@ mod tests {
                     ColumnDisplayType::String,
                 ),
             ],
+            row_filter_groups: None,
             sort_keys: vec![],
             code_syntax_name: amalthea::comm::data_explorer_comm::CodeSyntaxName {
                 code_syntax_name: "dplyr".to_string(),
@@ -858,6 +1036,7 @@ mod tests {
         let params = ConvertToCodeParams {
             column_filters: vec![],
             row_filters: vec![],
+            row_filter_groups: None,
             sort_keys: vec![],
             code_syntax_name: amalthea::comm::data_explorer_comm::CodeSyntaxName {
                 code_syntax_name: "dplyr".to_string(),
@@ -984,6 +1163,7 @@ mod tests {
                 "80",
                 ColumnDisplayType::Floating,
             )],
+            row_filter_groups: None,
             sort_keys: vec![],
             code_syntax_name: amalthea::comm::data_explorer_comm::CodeSyntaxName {
                 code_syntax_name: "dplyr".to_string(),
@@ -1092,12 +1272,15 @@ mod execution_tests {
             })),
             is_valid: Some(true),
             error_message: None,
+            group_id: None,
+            display_text: None,
         };
 
         // Create convert_to_code request
         let params = ConvertToCodeParams {
             column_filters: vec![],
             row_filters: vec![row_filter],
+            row_filter_groups: None,
             sort_keys: vec![],
             code_syntax_name: CodeSyntaxName {
                 code_syntax_name: "dplyr".to_string(),
@@ -10,6 +10,8 @@ use amalthea::comm::data_explorer_comm::DataSelectionCellRange;
 use amalthea::comm::data_explorer_comm::DataSelectionIndices;
 use amalthea::comm::data_explorer_comm::DataSelectionRange;
 use amalthea::comm::data_explorer_comm::DataSelectionSingleCell;
+use amalthea::comm::data_explorer_comm::DatetimeFormatOptions;
+use amalthea::comm::data_explorer_comm::ExportFactorValues;
 use amalthea::comm::data_explorer_comm::ExportFormat;
 use amalthea::comm::data_explorer_comm::Selection;
 use amalthea::comm::data_explorer_comm::TableSelection;
@@ -30,11 +32,16 @@ use crate::modules::ARK_ENVS;
 //   Must be applied before the selection rules if selection affects rows.
 // - selection: The selected region of the data frame
 // - format: The format to export the data frame to (csv, tsv and html are currently supported).
+// - factor_values: Whether factors are exported as their labels or their integer codes.
+// - datetime_options: How date-times are formatted. Defaults to ISO-8601 strings in the
+//   time zone of the values. Dates are always exported as ISO-8601 strings.
 pub fn export_selection(
     data: SEXP,
     view_indices: &Option<Vec<i32>>,
     selection: TableSelection,
     format: ExportFormat,
+    factor_values: ExportFactorValues,
+    datetime_options: &Option<DatetimeFormatOptions>,
 ) -> anyhow::Result<String> {
     let region = get_selection(data, view_indices, selection.clone())?;
     let format_string = match format {
@@ -42,6 +49,10 @@ pub fn export_selection(
         ExportFormat::Tsv => "tsv",
         ExportFormat::Html => "html",
     };
+    let factor_values = match factor_values {
+        ExportFactorValues::Labels => "labels",
+        ExportFactorValues::Codes => "codes",
+    };
     let include_header = match selection.kind {
         TableSelectionKind::SingleCell => false,
         TableSelectionKind::CellRange => true,
@@ -55,6 +66,21 @@ pub fn export_selection(
         .param("x", region)
         .param("format", format_string)
         .param("include_header", include_header)
+        .param("factor_values", factor_values)
+        .param(
+            "datetime_style",
+            datetime_options.as_ref().map(|x| x.style.to_string()),
+        )
+        .param(
+            "datetime_tz",
+            datetime_options.as_ref().and_then(|x| x.timezone.clone()),
+        )
+        .param(
+            "datetime_digits",
+            datetime_options
+                .as_ref()
+                .and_then(|x| x.sub_second_digits.map(|digits| digits as i32)),
+        )
         .call_in(ARK_ENVS.positron_ns)?
         .try_into()?)
 }
@@ -150,6 +176,8 @@ mod tests {
     use amalthea::comm::data_explorer_comm::DataSelectionIndices;
     use amalthea::comm::data_explorer_comm::DataSelectionRange;
     use amalthea::comm::data_explorer_comm::DataSelectionSingleCell;
+    use amalthea::comm::data_explorer_comm::DatetimeFormatOptions;
+    use amalthea::comm::data_explorer_comm::DatetimeStyle;
     use amalthea::comm::data_explorer_comm::ExportFormat;
     use amalthea::comm::data_explorer_comm::Selection;
     use harp::object::RObject;
@@ -166,7 +194,15 @@ mod tests {
         selection: TableSelection,
         format: ExportFormat,
     ) -> String {
-        export_selection(data.sexp, &None, selection, format).unwrap()
+        export_selection(
+            data.sexp,
+            &None,
+            selection,
+            format,
+            ExportFactorValues::Labels,
+            &None,
+        )
+        .unwrap()
     }
 
     fn export_selection_helper_with_view_indices(
@@ -174,7 +210,15 @@ mod tests {
         view_indices: Vec<i32>,
         selection: TableSelection,
     ) -> String {
-        export_selection(data.sexp, &Some(view_indices), selection, ExportFormat::Csv).unwrap()
+        export_selection(
+            data.sexp,
+            &Some(view_indices),
+            selection,
+            ExportFormat::Csv,
+            ExportFactorValues::Labels,
+            &None,
+        )
+        .unwrap()
     }
 
     fn small_test_data() -> RObject {
@@ -583,6 +627,91 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_factor_values() {
+        r_task(|| {
+            let data = harp::parse_eval_global(
+                "data.frame(x = factor(c('b', 'a', NA), levels = c('b', 'a')))",
+            )
+            .unwrap();
+            let export = |factor_values| {
+                export_selection(
+                    data.sexp,
+                    &None,
+                    column_indices_selection(vec![0]),
+                    ExportFormat::Csv,
+                    factor_values,
+                    &None,
+                )
+                .unwrap()
+            };
+
+            assert_eq!(export(ExportFactorValues::Labels), "x\nb\na\n");
+            assert_eq!(export(ExportFactorValues::Codes), "x\n1\n2\n");
+        });
+    }
+
+    #[test]
+    fn test_dates_iso_8601() {
+        r_task(|| {
+            let data = harp::parse_eval_global(
+                "data.frame(
+                    date = as.Date(c('2024-01-02', NA)),
+                    datetime = as.POSIXct(c('2024-01-02 03:04:05', NA), tz = 'UTC')
+                )",
+            )
+            .unwrap();
+
+            assert_eq!(
+                export_selection_helper(data.clone(), row_range_selection(0, 1)),
+                "date,datetime\n2024-01-02,2024-01-02T03:04:05+00:00\n,"
+            );
+
+            // Time zones are exported as offsets
+            let data = harp::parse_eval_global(
+                "data.frame(x = as.POSIXct('2024-07-01 12:00:00', tz = 'Europe/Paris'))",
+            )
+            .unwrap();
+            assert_eq!(
+                export_selection_helper(data, single_cell_selection(0, 0)),
+                "2024-07-01T12:00:00+02:00"
+            );
+        });
+    }
+
+    #[test]
+    fn test_datetime_options() {
+        r_task(|| {
+            let data = harp::parse_eval_global(
+                "data.frame(x = as.POSIXct(c('2024-01-02 03:04:05.5', NA), tz = 'UTC'))",
+            )
+            .unwrap();
+            let export = |timezone: Option<&str>, sub_second_digits| {
+                let datetime_options = DatetimeFormatOptions {
+                    style: DatetimeStyle::Iso,
+                    timezone: timezone.map(String::from),
+                    sub_second_digits,
+                };
+                export_selection(
+                    data.sexp,
+                    &None,
+                    column_indices_selection(vec![0]),
+                    ExportFormat::Csv,
+                    ExportFactorValues::Labels,
+                    &Some(datetime_options),
+                )
+                .unwrap()
+            };
+
+            assert_eq!(export(None, None), "x\n2024-01-02T03:04:05+00:00\n");
+            assert_eq!(export(None, Some(1)), "x\n2024-01-02T03:04:05.5+00:00\n");
+            assert_eq!(
+                export(Some("America/New_York"), None),
+                "x\n2024-01-01T22:04:05-05:00\n"
+            );
+        });
+    }
+
     #[test]
     fn test_cross_platform_line_endings() {
         r_task(|| {
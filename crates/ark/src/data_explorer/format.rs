@@ -6,6 +6,7 @@
 //
 
 use amalthea::comm::data_explorer_comm::ColumnValue;
+use amalthea::comm::data_explorer_comm::DatetimeFormatOptions;
 use amalthea::comm::data_explorer_comm::FormatOptions;
 use harp::exec::RFunction;
 use harp::exec::RFunctionExt;
@@ -16,6 +17,7 @@ use harp::object::RObject;
 use harp::r_null;
 use harp::utils::r_classes;
 use harp::utils::r_format_vec;
+use harp::utils::r_inherits;
 use harp::utils::r_is_null;
 use harp::utils::r_typeof;
 use harp::vector::CharacterVector;
@@ -28,7 +30,10 @@ use libr::SEXP;
 use libr::*;
 use stdext::unwrap;
 
+use crate::data_explorer::utils::is_geometry_column;
 use crate::modules::ARK_ENVS;
+use crate::number_format;
+use crate::number_format::NumberFormat;
 
 const FALLBACK_FORMAT_STRING: &str = "????";
 
@@ -84,6 +89,16 @@ fn unknown_format(x: SEXP) -> Vec<FormattedValue> {
 
 // Format a column of data for display in the data explorer.
 fn format_values(x: SEXP, format_options: &FormatOptions) -> anyhow::Result<Vec<FormattedValue>> {
+    if is_geometry_column(x) {
+        return format_geometry(x);
+    }
+
+    if let Some(datetime_options) = &format_options.datetime_options {
+        if r_inherits(x, "POSIXct") {
+            return format_datetime(x, datetime_options);
+        }
+    }
+
     if let Some(_) = r_classes(x) {
         return Ok(format_object(x));
     }
@@ -162,6 +177,47 @@ fn format_object(x: SEXP) -> Vec<FormattedValue> {
         .collect()
 }
 
+// Geometries are formatted as WKT summaries such as `POINT (1 2)`, whether
+// or not sf is loaded.
+fn format_geometry(x: SEXP) -> anyhow::Result<Vec<FormattedValue>> {
+    let formatted: Vec<Option<String>> = RFunction::from("format_geometry")
+        .add(x)
+        .call_in(ARK_ENVS.positron_ns)?
+        .try_into()?;
+
+    Ok(formatted
+        .into_iter()
+        .map(|x| match x {
+            Some(v) => FormattedValue::Value(v),
+            None => FormattedValue::NA,
+        })
+        .collect())
+}
+
+// Date-times are formatted with the style, time zone, and sub-second
+// precision requested by the frontend. Without these options they go through
+// their `format()` method like other objects.
+fn format_datetime(
+    x: SEXP,
+    options: &DatetimeFormatOptions,
+) -> anyhow::Result<Vec<FormattedValue>> {
+    let formatted: Vec<Option<String>> = RFunction::from("format_datetime")
+        .add(x)
+        .param("style", options.style.to_string())
+        .param("tz", options.timezone.clone())
+        .param("digits", options.sub_second_digits.unwrap_or(0) as i32)
+        .call_in(ARK_ENVS.positron_ns)?
+        .try_into()?;
+
+    Ok(formatted
+        .into_iter()
+        .map(|x| match x {
+            Some(v) => FormattedValue::Value(v),
+            None => FormattedValue::NA,
+        })
+        .collect())
+}
+
 fn format_list(x: SEXP) -> Vec<FormattedValue> {
     let len = r_length(x);
     let mut output = Vec::<FormattedValue>::with_capacity(len as usize);
@@ -250,24 +306,23 @@ fn format_chr(x: CharacterVector) -> Vec<FormattedValue> {
 }
 
 fn format_int(x: IntegerVector, options: &FormatOptions) -> Vec<FormattedValue> {
-    x.iter().map(|x| format_int_elt(x, options)).collect()
+    let format = NumberFormat::from(options);
+    x.iter().map(|x| format_int_elt(x, &format)).collect()
 }
 
-fn format_int_elt(x: Option<i32>, options: &FormatOptions) -> FormattedValue {
+fn format_int_elt(x: Option<i32>, format: &NumberFormat) -> FormattedValue {
     match x {
         None => FormattedValue::NA,
-        Some(v) => FormattedValue::Value(apply_thousands_sep(
-            v.to_string(),
-            options.thousands_sep.clone(),
-        )),
+        Some(v) => FormattedValue::Value(number_format::format_int(v, format)),
     }
 }
 
 fn format_dbl(x: NumericVector, options: &FormatOptions) -> Vec<FormattedValue> {
-    x.iter().map(|x| format_dbl_elt(x, options)).collect()
+    let format = NumberFormat::from(options);
+    x.iter().map(|x| format_dbl_elt(x, &format)).collect()
 }
 
-fn format_dbl_elt(x: Option<f64>, options: &FormatOptions) -> FormattedValue {
+fn format_dbl_elt(x: Option<f64>, format: &NumberFormat) -> FormattedValue {
     match x {
         None => FormattedValue::NA,
         Some(v) => {
@@ -275,7 +330,7 @@ fn format_dbl_elt(x: Option<f64>, options: &FormatOptions) -> FormattedValue {
                 FormattedValue::NaN
             } else if r_dbl_is_finite(v) {
                 // finite values that are not NaN nor NA
-                format_dbl_value(v, options)
+                FormattedValue::Value(number_format::format_dbl(v, format))
             } else if v > 0.0 {
                 FormattedValue::Inf
             } else {
@@ -285,115 +340,6 @@ fn format_dbl_elt(x: Option<f64>, options: &FormatOptions) -> FormattedValue {
     }
 }
 
-fn format_dbl_value(x: f64, options: &FormatOptions) -> FormattedValue {
-    // The limit for large numbers before switching to scientific
-    // notation
-    let upper_threshold = f64::powf(10.0, options.max_integral_digits as f64);
-
-    // The limit for small numbers before switching to scientific
-    // notation
-    let lower_threshold = f64::powf(10.0, -(options.small_num_digits as f64));
-
-    let large_num_digits = options.large_num_digits as usize;
-    let small_num_digits = options.small_num_digits as usize;
-
-    let abs_x = x.abs();
-
-    let formatted = if abs_x >= upper_threshold {
-        // large numbers use scientific notation
-        // rust makes 1e7 instead of 1e+7 which aligns baddly
-        let v = format!("{:.large_num_digits$e}", x).replace("e", "e+");
-        pad_exponent(v)
-    } else if abs_x >= 1.0 {
-        // this is considered medium numbers and they use a fixed amount of
-        // digits after the decimal point
-        apply_thousands_sep(
-            format!("{:.large_num_digits$}", x),
-            options.thousands_sep.clone(),
-        )
-    } else if abs_x >= lower_threshold {
-        // small numbers but not that small are formatted with a different
-        // amount of digits after the decimal point
-        apply_thousands_sep(
-            format!("{:.small_num_digits$}", x),
-            options.thousands_sep.clone(),
-        )
-    } else if abs_x == 0.0 {
-        // zero is special cased to behave like a medium number.
-        format!("{:.large_num_digits$}", x)
-    } else {
-        // very small numbers use scientific notation
-        let v = format!("{:.large_num_digits$e}", x);
-        pad_exponent(v)
-    };
-
-    FormattedValue::Value(formatted)
-}
-
-fn apply_thousands_sep(x: String, sep: Option<String>) -> String {
-    match sep {
-        None => x,
-        Some(sep) => {
-            let mut formatted = String::new();
-
-            // Find the decimal point if any
-            let decimal_point = x.find('.').unwrap_or(x.len());
-
-            // Walk backwards on the string to add the thousands separator
-            let mut count: usize = 0;
-            for (i, c) in x.chars().rev().enumerate() {
-                // Now walk backwards until we reach the decimal point.
-                // After the point, start adding the thousands separator.
-                if i < (x.len() - decimal_point) {
-                    formatted.push(c);
-                    continue;
-                }
-
-                // For negative numbers, break the iteration.
-                // `continue` should have the same effect as `break` as there shouldn't exist
-                // any character before `-`.
-                // This avoids '-100' to be formatted as '-,100'.
-                if c == '-' {
-                    formatted.push(c);
-                    continue;
-                }
-
-                // Add a `sep` every three characters
-                if count % 3 == 0 && count != 0 {
-                    formatted.push_str(&sep);
-                }
-
-                formatted.push(c);
-                count += 1;
-            }
-            formatted.chars().rev().collect::<String>()
-        },
-    }
-}
-
-// exponents of the scientific notation should have a minimum of length 2
-// to match the other implementations
-// the string must have already been processed to include the e+ in positive values
-fn pad_exponent(x: String) -> String {
-    // find the exponent position
-    let e_pos = match x.find('e') {
-        Some(v) => v,
-        None => return x, // if no e is found, return the string as is
-    };
-
-    // "1e-12" the e_pos (1) + 3 is < x.len() (5)
-    // "1e-1"  the e_pos (1) + 3 is == x.len() (4)
-    if (e_pos + 1 + 2) < x.len() {
-        return x; // the exponent already have 2 digits
-    }
-
-    // add zeros to the exponent
-    let mut formatted = x;
-    formatted.insert(e_pos + 2, '0');
-
-    formatted
-}
-
 // This type is only internally used with the intent of being easy to convert to
 // ColumnValue or String when needed.
 #[derive(Clone)]
@@ -439,9 +385,11 @@ impl Into<String> for FormattedValue {
 
 #[cfg(test)]
 mod tests {
+    use amalthea::comm::data_explorer_comm::DatetimeStyle;
+
     use super::*;
-    use crate::r_task;
     use crate::fixtures::package_is_installed;
+    use crate::r_task;
 
     fn default_options() -> FormatOptions {
         FormatOptions {
@@ -449,62 +397,12 @@ mod tests {
             small_num_digits: 4,
             max_integral_digits: 7,
             thousands_sep: Some(",".to_string()),
+            decimal_sep: None,
             max_value_length: 100,
+            datetime_options: None,
         }
     }
 
-    #[test]
-    fn test_pad_exponents() {
-        assert_eq!(pad_exponent("1.00e+111".to_string()), "1.00e+111");
-        assert_eq!(pad_exponent("1.00e+11".to_string()), "1.00e+11");
-        assert_eq!(pad_exponent("1.00e+01".to_string()), "1.00e+01");
-        assert_eq!(pad_exponent("1.00e+1".to_string()), "1.00e+01");
-        assert_eq!(pad_exponent("1.00e+00".to_string()), "1.00e+00");
-        assert_eq!(pad_exponent("1.00e-01".to_string()), "1.00e-01");
-        assert_eq!(pad_exponent("1.00e-1".to_string()), "1.00e-01");
-        assert_eq!(pad_exponent("1.00e-00".to_string()), "1.00e-00");
-    }
-
-    #[test]
-    fn test_thousands_sep() {
-        assert_eq!(
-            apply_thousands_sep("1000000".to_string(), Some(",".to_string())),
-            "1,000,000"
-        );
-        assert_eq!(
-            apply_thousands_sep("1000000.000".to_string(), Some(",".to_string())),
-            "1,000,000.000"
-        );
-        assert_eq!(
-            apply_thousands_sep("1.00".to_string(), Some(",".to_string())),
-            "1.00"
-        );
-        assert_eq!(
-            apply_thousands_sep("1000.00".to_string(), Some(",".to_string())),
-            "1,000.00"
-        );
-        assert_eq!(
-            apply_thousands_sep("100.00".to_string(), Some(",".to_string())),
-            "100.00"
-        );
-        assert_eq!(
-            apply_thousands_sep("1000000.00".to_string(), None),
-            "1000000.00"
-        );
-        assert_eq!(
-            apply_thousands_sep("-100".to_string(), Some(",".to_string())),
-            "-100"
-        );
-        assert_eq!(
-            apply_thousands_sep("-100000".to_string(), Some(",".to_string())),
-            "-100,000"
-        );
-        assert_eq!(
-            apply_thousands_sep("-100000.00".to_string(), Some(",".to_string())),
-            "-100,000.00"
-        );
-    }
-
     #[test]
     fn test_real_formatting() {
         r_task(|| {
@@ -538,7 +436,9 @@ mod tests {
                 small_num_digits: 4,
                 max_integral_digits: 7,
                 thousands_sep: None,
+                decimal_sep: None,
                 max_value_length: 100,
+                datetime_options: None,
             };
             let expected = vec![
                 "0.00",
@@ -565,7 +465,9 @@ mod tests {
                 small_num_digits: 4,
                 max_integral_digits: 7,
                 thousands_sep: Some("_".to_string()),
+                decimal_sep: None,
                 max_value_length: 100,
+                datetime_options: None,
             };
 
             let expected = vec![
@@ -681,8 +583,10 @@ mod tests {
                 return;
             }
 
-            let data =
-                harp::parse_eval_global("survival::Surv(survival::lung$time[1:5], survival::lung$status[1:5])").unwrap();
+            let data = harp::parse_eval_global(
+                "survival::Surv(survival::lung$time[1:5], survival::lung$status[1:5])",
+            )
+            .unwrap();
             let formatted = format_column(data.sexp, &default_options());
             assert_eq!(formatted, vec![
                 ColumnValue::FormattedValue("306".to_string()),
@@ -757,6 +661,47 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_datetime_format_options() {
+        r_task(|| {
+            let data = harp::parse_eval_global(
+                r#"as.POSIXct(c("2024-01-02 03:04:05.5", NA), tz = "UTC")"#,
+            )
+            .unwrap();
+
+            let format = |style, timezone: Option<&str>, sub_second_digits| {
+                let mut options = default_options();
+                options.datetime_options = Some(DatetimeFormatOptions {
+                    style,
+                    timezone: timezone.map(String::from),
+                    sub_second_digits,
+                });
+                format_column(data.sexp, &options)
+            };
+
+            assert_eq!(format(DatetimeStyle::Iso, None, None), vec![
+                ColumnValue::FormattedValue("2024-01-02T03:04:05+00:00".to_string()),
+                FormattedValue::NA.into(),
+            ]);
+            assert_eq!(format(DatetimeStyle::Iso, None, Some(3)), vec![
+                ColumnValue::FormattedValue("2024-01-02T03:04:05.500+00:00".to_string()),
+                FormattedValue::NA.into(),
+            ]);
+            assert_eq!(format(DatetimeStyle::Iso, Some("Asia/Tokyo"), None), vec![
+                ColumnValue::FormattedValue("2024-01-02T12:04:05+09:00".to_string()),
+                FormattedValue::NA.into(),
+            ]);
+
+            // The date format depends on the locale, but the time zone is
+            // always shown
+            let formatted = format(DatetimeStyle::Locale, Some("Asia/Tokyo"), None);
+            let ColumnValue::FormattedValue(value) = &formatted[0] else {
+                panic!("Expected a formatted value");
+            };
+            assert!(value.ends_with("12:04:05 JST"));
+        })
+    }
+
     #[test]
     fn test_truncation() {
         r_task(|| {
@@ -174,7 +174,9 @@ mod tests {
             small_num_digits: 4,
             max_integral_digits: 7,
             thousands_sep: Some(",".to_string()),
+            decimal_sep: None,
             max_value_length: 100,
+            datetime_options: None,
         }
     }
 
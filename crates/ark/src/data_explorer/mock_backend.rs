@@ -0,0 +1,244 @@
+//
+// mock_backend.rs
+//
+// Copyright (C) 2025 by Posit Software, PBC
+//
+//
+
+use std::cmp::Ordering;
+
+use amalthea::comm::data_explorer_comm::ColumnSortKey;
+use amalthea::comm::data_explorer_comm::FilterComparisonOp;
+use amalthea::comm::data_explorer_comm::RowFilter;
+use amalthea::comm::data_explorer_comm::RowFilterCondition;
+use amalthea::comm::data_explorer_comm::RowFilterGroup;
+use amalthea::comm::data_explorer_comm::RowFilterParams;
+use amalthea::comm::data_explorer_comm::RowFilterType;
+use anyhow::anyhow;
+
+use crate::data_explorer::backend::FilteredRows;
+use crate::data_explorer::backend::TableBackend;
+use crate::data_explorer::row_filter_tree::row_filter_tree;
+use crate::data_explorer::row_filter_tree::RowFilterNode;
+
+/// A column of a `MockTable`. `None` values are missing.
+#[derive(Debug, Clone)]
+pub(crate) enum MockColumn {
+    Numbers(Vec<Option<f64>>),
+    Strings(Vec<Option<String>>),
+}
+
+/// An in-memory table backend for unit tests that don't need R. Supports
+/// the `is_null`, `not_null`, `is_empty`, `not_empty` and `compare` filter
+/// types, other filters fail and are reported as errors like in the R
+/// backend. Strings are compared bytewise, like the default `C` collation.
+#[derive(Debug, Clone)]
+pub(crate) struct MockTable {
+    pub columns: Vec<MockColumn>,
+}
+
+impl MockTable {
+    pub fn new(columns: Vec<MockColumn>) -> Self {
+        Self { columns }
+    }
+
+    pub fn num_rows(&self) -> i32 {
+        match self.columns.first() {
+            Some(MockColumn::Numbers(values)) => values.len() as i32,
+            Some(MockColumn::Strings(values)) => values.len() as i32,
+            None => 0,
+        }
+    }
+
+    fn column(&self, index: i64) -> anyhow::Result<&MockColumn> {
+        self.columns
+            .get(index as usize)
+            .ok_or_else(|| anyhow!("Column index out of range {index}"))
+    }
+
+    /// Compares two rows on a column. Missing values are last in both
+    /// directions.
+    fn cmp_rows(column: &MockColumn, i: usize, j: usize, ascending: bool) -> Ordering {
+        fn cmp<T>(
+            x: &Option<T>,
+            y: &Option<T>,
+            ascending: bool,
+            cmp: fn(&T, &T) -> Ordering,
+        ) -> Ordering {
+            match (x, y) {
+                (Some(x), Some(y)) if ascending => cmp(x, y),
+                (Some(x), Some(y)) => cmp(y, x),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        }
+
+        match column {
+            MockColumn::Numbers(values) => cmp(&values[i], &values[j], ascending, f64::total_cmp),
+            MockColumn::Strings(values) => cmp(&values[i], &values[j], ascending, String::cmp),
+        }
+    }
+
+    /// The rows matched by a filter, or an error message if the filter
+    /// can't be applied
+    fn filter_matches(&self, filter: &RowFilter) -> Result<Vec<bool>, String> {
+        let column = self
+            .column(filter.column_schema.column_index)
+            .map_err(|err| err.to_string())?;
+
+        let is_missing: Vec<bool> = match column {
+            MockColumn::Numbers(values) => values.iter().map(Option::is_none).collect(),
+            MockColumn::Strings(values) => values.iter().map(Option::is_none).collect(),
+        };
+
+        match (&filter.filter_type, column) {
+            (RowFilterType::IsNull, _) => Ok(is_missing),
+            (RowFilterType::NotNull, _) => Ok(is_missing.iter().map(|x| !x).collect()),
+
+            (RowFilterType::IsEmpty, MockColumn::Strings(values)) => {
+                Ok(values.iter().map(|x| x.as_deref() == Some("")).collect())
+            },
+            (RowFilterType::NotEmpty, MockColumn::Strings(values)) => {
+                Ok(values.iter().map(|x| x.as_deref() != Some("")).collect())
+            },
+
+            (RowFilterType::Compare, column) => {
+                let Some(RowFilterParams::Comparison(comparison)) = &filter.params else {
+                    return Err(String::from("Missing compare filter params"));
+                };
+
+                let matches = |ordering: Option<Ordering>| match ordering {
+                    None => false,
+                    Some(ordering) => match comparison.op {
+                        FilterComparisonOp::Eq => ordering == Ordering::Equal,
+                        FilterComparisonOp::NotEq => ordering != Ordering::Equal,
+                        FilterComparisonOp::Lt => ordering == Ordering::Less,
+                        FilterComparisonOp::LtEq => ordering != Ordering::Greater,
+                        FilterComparisonOp::Gt => ordering == Ordering::Greater,
+                        FilterComparisonOp::GtEq => ordering != Ordering::Less,
+                    },
+                };
+
+                match column {
+                    MockColumn::Numbers(values) => {
+                        let value: f64 = comparison
+                            .value
+                            .parse()
+                            .map_err(|_| format!("Can't compare to `{}`", comparison.value))?;
+                        Ok(values
+                            .iter()
+                            .map(|x| matches(x.and_then(|x| x.partial_cmp(&value))))
+                            .collect())
+                    },
+                    MockColumn::Strings(values) => Ok(values
+                        .iter()
+                        .map(|x| matches(x.as_ref().map(|x| x.as_str().cmp(&comparison.value))))
+                        .collect()),
+                }
+            },
+
+            (filter_type, _) => Err(format!(
+                "Unsupported filter type `{filter_type}` in mock table"
+            )),
+        }
+    }
+
+    /// Combines the nodes from left to right, skipping the filters that
+    /// failed. Returns `None` when no filter could be applied.
+    fn combine(nodes: &[RowFilterNode], matches: &[Option<Vec<bool>>]) -> Option<Vec<bool>> {
+        let mut out: Option<Vec<bool>> = None;
+
+        for node in nodes {
+            let node_matches = match node {
+                RowFilterNode::Filter { index, .. } => matches[*index].clone(),
+                RowFilterNode::Group { children, .. } => Self::combine(children, matches),
+            };
+            let Some(node_matches) = node_matches else {
+                continue;
+            };
+
+            // Like the R backend, the first node is combined with `TRUE`
+            let out_matches = out.unwrap_or_else(|| vec![true; node_matches.len()]);
+            out = Some(
+                out_matches
+                    .into_iter()
+                    .zip(node_matches)
+                    .map(|(x, y)| match node.condition() {
+                        RowFilterCondition::And => x && y,
+                        RowFilterCondition::Or => x || y,
+                    })
+                    .collect(),
+            );
+        }
+
+        out
+    }
+}
+
+impl TableBackend for MockTable {
+    fn sort_rows(&self, sort_keys: &[ColumnSortKey]) -> anyhow::Result<Vec<i32>> {
+        let columns = sort_keys
+            .iter()
+            .map(|key| Ok((self.column(key.column_index)?, key.ascending)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut indices: Vec<usize> = (0..self.num_rows() as usize).collect();
+
+        // `sort_by()` is stable
+        indices.sort_by(|&i, &j| {
+            columns
+                .iter()
+                .map(|(column, ascending)| Self::cmp_rows(column, i, j, *ascending))
+                .find(|ordering| *ordering != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        Ok(indices.into_iter().map(|i| i as i32 + 1).collect())
+    }
+
+    fn filter_rows(
+        &self,
+        filters: &[RowFilter],
+        groups: &[RowFilterGroup],
+    ) -> anyhow::Result<FilteredRows> {
+        let mut matches: Vec<Option<Vec<bool>>> = Vec::with_capacity(filters.len());
+        let mut errors: Vec<Option<String>> = Vec::with_capacity(filters.len());
+
+        for filter in filters {
+            // Filters that are already marked as invalid are not applied
+            let result = if filter.is_valid == Some(false) {
+                Err(filter
+                    .error_message
+                    .clone()
+                    .unwrap_or_else(|| String::from("Invalid filter for unknown reason")))
+            } else {
+                self.filter_matches(filter)
+            };
+
+            match result {
+                Ok(filter_matches) => {
+                    matches.push(Some(filter_matches));
+                    errors.push(None);
+                },
+                Err(err) => {
+                    matches.push(None);
+                    errors.push(Some(err));
+                },
+            }
+        }
+
+        let tree = row_filter_tree(filters, groups);
+        let indices = match Self::combine(&tree, &matches) {
+            Some(matches) => matches
+                .iter()
+                .enumerate()
+                .filter(|(_, matched)| **matched)
+                .map(|(i, _)| i as i32 + 1)
+                .collect(),
+            None => (1..=self.num_rows()).collect(),
+        };
+
+        Ok(FilteredRows { indices, errors })
+    }
+}
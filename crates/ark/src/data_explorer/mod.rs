@@ -5,12 +5,19 @@
 //
 //
 
+pub mod backend;
 pub mod column_profile;
 pub mod convert_to_code;
 pub mod export_selection;
 pub mod format;
 pub mod histogram;
+#[cfg(test)]
+pub(crate) mod mock_backend;
+pub mod natural_sort;
 pub mod r_data_explorer;
+pub mod row_filter_display;
+pub mod row_filter_tree;
 pub mod summary_stats;
 pub mod table;
 pub mod utils;
+pub mod view_state;
@@ -0,0 +1,143 @@
+//
+// natural_sort.rs
+//
+// Copyright (C) 2025 by Posit Software, PBC
+//
+//
+
+use std::cmp::Ordering;
+
+use harp::object::RObject;
+use harp::vector::CharacterVector;
+use harp::vector::IntegerVector;
+use harp::vector::Vector;
+use libr::SEXP;
+
+/// Compare strings in natural order, where runs of digits are compared by
+/// their numeric value, e.g. `"file2"` sorts before `"file10"`. Other
+/// characters are compared by code point.
+///
+/// Numbers that only differ by their leading zeros, e.g. `"01"` and `"1"`,
+/// are ordered by their length so that the comparison is a total order.
+pub fn natural_cmp(x: &str, y: &str) -> Ordering {
+    let mut x_chars = x.chars().peekable();
+    let mut y_chars = y.chars().peekable();
+
+    // Tie breaker for numbers that are equal but differ in leading zeros
+    let mut zeros = Ordering::Equal;
+
+    loop {
+        let (x_char, y_char) = match (x_chars.peek(), y_chars.peek()) {
+            (None, None) => return zeros,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x_char), Some(y_char)) => (*x_char, *y_char),
+        };
+
+        if x_char.is_ascii_digit() && y_char.is_ascii_digit() {
+            let x_digits = take_digits(&mut x_chars);
+            let y_digits = take_digits(&mut y_chars);
+
+            let x_number = x_digits.trim_start_matches('0');
+            let y_number = y_digits.trim_start_matches('0');
+
+            // Numbers with more significant digits are larger, otherwise
+            // compare digit by digit
+            let ordering = x_number
+                .len()
+                .cmp(&y_number.len())
+                .then_with(|| x_number.cmp(y_number));
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+
+            if zeros == Ordering::Equal {
+                zeros = x_digits.len().cmp(&y_digits.len());
+            }
+            continue;
+        }
+
+        let ordering = x_char.cmp(&y_char);
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+
+        x_chars.next();
+        y_chars.next();
+    }
+}
+
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(char) = chars.next_if(|char| char.is_ascii_digit()) {
+        digits.push(char);
+    }
+    digits
+}
+
+/// Ranks of the elements of a character vector in natural order, see
+/// `natural_cmp()`. Equal strings share the same rank and missing values
+/// have a missing rank. The ranks can be sorted by `order()` in place of
+/// the strings.
+pub fn natural_ranks(x: SEXP) -> anyhow::Result<RObject> {
+    let x = CharacterVector::new(x)?;
+    let values: Vec<Option<String>> = x.iter().collect();
+
+    let mut sorted: Vec<(usize, &str)> = values
+        .iter()
+        .enumerate()
+        .filter_map(|(i, value)| value.as_deref().map(|value| (i, value)))
+        .collect();
+    sorted.sort_by(|(_, x), (_, y)| natural_cmp(x, y));
+
+    let mut ranks: Vec<Option<i32>> = vec![None; values.len()];
+    let mut rank = 0;
+    let mut previous: Option<&str> = None;
+
+    for (position, (i, value)) in sorted.into_iter().enumerate() {
+        if previous != Some(value) {
+            rank = position as i32 + 1;
+            previous = Some(value);
+        }
+        ranks[i] = Some(rank);
+    }
+
+    Ok(IntegerVector::from_iter(ranks).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r_task;
+
+    #[test]
+    fn test_natural_cmp() {
+        assert_eq!(natural_cmp("file2", "file10"), Ordering::Less);
+        assert_eq!(natural_cmp("file10", "file2"), Ordering::Greater);
+        assert_eq!(natural_cmp("file10", "file10"), Ordering::Equal);
+        assert_eq!(natural_cmp("file", "file1"), Ordering::Less);
+        assert_eq!(natural_cmp("a10b2", "a10b10"), Ordering::Less);
+        assert_eq!(natural_cmp("x9", "y1"), Ordering::Less);
+
+        // Leading zeros only break ties
+        assert_eq!(natural_cmp("file01", "file1"), Ordering::Greater);
+        assert_eq!(natural_cmp("file01", "file2"), Ordering::Less);
+
+        // Numbers larger than any integer type
+        assert_eq!(
+            natural_cmp("99999999999999999999998", "99999999999999999999999"),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_natural_ranks() {
+        r_task(|| {
+            let x =
+                harp::parse_eval_global(r#"c("file10", "file2", NA, "file1", "file2")"#).unwrap();
+            let ranks = natural_ranks(x.sexp).unwrap();
+            let ranks: Vec<Option<i32>> = IntegerVector::new(ranks.sexp).unwrap().iter().collect();
+            assert_eq!(ranks, vec![Some(4), Some(2), None, Some(1), Some(2)]);
+        })
+    }
+}
@@ -5,14 +5,14 @@
 //
 //
 
-use std::cmp;
 use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
 
 use amalthea::comm::comm_channel::CommMsg;
 use amalthea::comm::data_explorer_comm::ArraySelection;
 use amalthea::comm::data_explorer_comm::BackendState;
 use amalthea::comm::data_explorer_comm::CodeSyntaxName;
-use amalthea::comm::data_explorer_comm::ColumnDisplayType;
 use amalthea::comm::data_explorer_comm::ColumnFilter;
 use amalthea::comm::data_explorer_comm::ColumnFilterParams;
 use amalthea::comm::data_explorer_comm::ColumnFilterType;
@@ -21,7 +21,6 @@ use amalthea::comm::data_explorer_comm::ColumnProfileType;
 use amalthea::comm::data_explorer_comm::ColumnProfileTypeSupportStatus;
 use amalthea::comm::data_explorer_comm::ColumnSchema;
 use amalthea::comm::data_explorer_comm::ColumnSelection;
-use amalthea::comm::data_explorer_comm::ColumnSortKey;
 use amalthea::comm::data_explorer_comm::ColumnValue;
 use amalthea::comm::data_explorer_comm::ConvertToCodeFeatures;
 use amalthea::comm::data_explorer_comm::ConvertToCodeParams;
@@ -29,21 +28,22 @@ use amalthea::comm::data_explorer_comm::ConvertedCode;
 use amalthea::comm::data_explorer_comm::DataExplorerBackendReply;
 use amalthea::comm::data_explorer_comm::DataExplorerBackendRequest;
 use amalthea::comm::data_explorer_comm::DataExplorerFrontendEvent;
+use amalthea::comm::data_explorer_comm::DatetimeFormatOptions;
 use amalthea::comm::data_explorer_comm::ExportDataSelectionFeatures;
 use amalthea::comm::data_explorer_comm::ExportDataSelectionParams;
+use amalthea::comm::data_explorer_comm::ExportFactorValues;
 use amalthea::comm::data_explorer_comm::ExportFormat;
 use amalthea::comm::data_explorer_comm::ExportedData;
-use amalthea::comm::data_explorer_comm::FilterComparisonOp;
-use amalthea::comm::data_explorer_comm::FilterResult;
 use amalthea::comm::data_explorer_comm::FormatOptions;
 use amalthea::comm::data_explorer_comm::GetColumnProfilesFeatures;
 use amalthea::comm::data_explorer_comm::GetColumnProfilesParams;
 use amalthea::comm::data_explorer_comm::GetDataValuesParams;
 use amalthea::comm::data_explorer_comm::GetSchemaParams;
-use amalthea::comm::data_explorer_comm::RowFilter;
-use amalthea::comm::data_explorer_comm::RowFilterParams;
+use amalthea::comm::data_explorer_comm::OpenElementParams;
 use amalthea::comm::data_explorer_comm::RowFilterType;
 use amalthea::comm::data_explorer_comm::RowFilterTypeSupportStatus;
+use amalthea::comm::data_explorer_comm::RowLabelMode;
+use amalthea::comm::data_explorer_comm::RowLabelOptions;
 use amalthea::comm::data_explorer_comm::SearchSchemaFeatures;
 use amalthea::comm::data_explorer_comm::SearchSchemaParams;
 use amalthea::comm::data_explorer_comm::SearchSchemaResult;
@@ -51,8 +51,11 @@ use amalthea::comm::data_explorer_comm::SearchSchemaSortOrder;
 use amalthea::comm::data_explorer_comm::SetColumnFiltersFeatures;
 use amalthea::comm::data_explorer_comm::SetRowFiltersFeatures;
 use amalthea::comm::data_explorer_comm::SetRowFiltersParams;
+use amalthea::comm::data_explorer_comm::SetRowLabelOptionsParams;
 use amalthea::comm::data_explorer_comm::SetSortColumnsFeatures;
 use amalthea::comm::data_explorer_comm::SetSortColumnsParams;
+use amalthea::comm::data_explorer_comm::SpatialBoundingBox;
+use amalthea::comm::data_explorer_comm::SpatialMetadata;
 use amalthea::comm::data_explorer_comm::SupportStatus;
 use amalthea::comm::data_explorer_comm::SupportedFeatures;
 use amalthea::comm::data_explorer_comm::TableData;
@@ -65,12 +68,13 @@ use amalthea::comm::event::CommManagerEvent;
 use amalthea::socket::comm::CommInitiator;
 use amalthea::socket::comm::CommSocket;
 use anyhow::anyhow;
-use anyhow::bail;
 use crossbeam::channel::unbounded;
 use crossbeam::channel::Sender;
 use crossbeam::select;
+use harp::environment::R_ENVS;
 use harp::exec::RFunction;
 use harp::exec::RFunctionExt;
+use harp::object::r_null_or_try_into;
 use harp::object::RObject;
 use harp::r_symbol;
 use harp::table_kind;
@@ -78,9 +82,11 @@ use harp::tbl_get_column;
 use harp::vector::CharacterVector;
 use harp::vector::Vector;
 use harp::ColumnNames;
+use harp::DataFrameBuilder;
 use harp::TableKind;
 use itertools::Itertools;
 use libr::*;
+use once_cell::sync::Lazy;
 use serde::Deserialize;
 use serde::Serialize;
 use stdext::local;
@@ -90,6 +96,7 @@ use stdext::unwrap;
 use tracing::Instrument;
 use uuid::Uuid;
 
+use crate::data_explorer::backend::RTableBackend;
 use crate::data_explorer::column_profile::handle_columns_profiles_requests;
 use crate::data_explorer::column_profile::ProcessColumnsProfilesParams;
 use crate::data_explorer::convert_to_code;
@@ -99,10 +106,13 @@ use crate::data_explorer::format::format_string;
 use crate::data_explorer::table::Table;
 use crate::data_explorer::utils::display_type;
 use crate::data_explorer::utils::tbl_subset_with_view_indices;
+use crate::data_explorer::view_state::ViewState;
+use crate::events::EVENTS;
 use crate::interface::RMain;
-use crate::lsp::events::EVENTS;
 use crate::modules::ARK_ENVS;
+use crate::number_format::NumberFormat;
 use crate::r_task;
+use crate::r_task::RTaskToken;
 use crate::thread::RThreadSafe;
 use crate::variables::variable::WorkspaceVariableDisplayType;
 
@@ -130,6 +140,10 @@ pub struct RDataExplorer {
     /// The data object that the data viewer is currently viewing.
     table: Table,
 
+    /// When viewing a list, the list itself. The `table` is then a data frame
+    /// with a row per element of the list, see `list_as_table()`.
+    list: Option<RThreadSafe<RObject>>,
+
     /// An optional binding to the environment containing the data object.
     /// This can be omitted for cases wherein the data object isn't in an
     /// environment (e.g. a temporary or unnamed object)
@@ -139,44 +153,90 @@ pub struct RDataExplorer {
     /// column of the data object.
     shape: DataObjectShape,
 
-    /// A cache containing the current set of sort keys.
-    sort_keys: Vec<ColumnSortKey>,
-
-    /// A cache containing the current set of row filters.
-    row_filters: Vec<RowFilter>,
+    /// The sorts and filters applied to the data object, and the row
+    /// indices they produce.
+    view: ViewState,
 
     /// A cache containing the current set of column filters
     col_filters: Vec<ColumnFilter>,
 
-    /// The set of sorted row indices, if any sorts are applied. This always
-    /// includes all row indices.
-    sorted_indices: Option<Vec<i32>>,
-
-    /// The set of filtered row indices, if any filters are applied. These are
-    /// the row indices that remain after applying all row filters. They're
-    /// sorted in ascending order.
-    filtered_indices: Option<Vec<i32>>,
-
-    /// When any sorts or filters are applied, the set of sorted and filtered
-    /// row indices. This is the set of row indices that are displayed in the
-    /// data viewer.
-    view_indices: Option<Vec<i32>>,
+    /// How rows are labeled. Kept across updates of the data object, as long
+    /// as the labeling column still exists.
+    row_label_options: RowLabelOptions,
 
     /// The communication socket for the data viewer.
     comm: CommSocket,
 
     /// A channel to send messages to the CommManager.
     comm_manager_tx: Sender<CommManagerEvent>,
+
+    /// Cancels pending column profile tasks once the data explorer is closed
+    profiles_token: RTaskToken,
 }
 #[derive(Deserialize, Serialize)]
 struct Metadata {
     title: String,
 }
 
+/// The collation of character columns when sorting rows, set with the
+/// `ark.data_explorer.collation` option
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, strum_macros::Display, strum_macros::EnumString,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum SortCollation {
+    /// Compare the bytes of the strings, e.g. `"B"` sorts before `"a"`. This
+    /// is the same on all platforms.
+    #[default]
+    C,
+
+    /// The collation of the `LC_COLLATE` locale, as used by `sort()`
+    Locale,
+
+    /// The ICU collation of the default stringi locale. Falls back to
+    /// `Locale` when stringi isn't installed.
+    Icu,
+}
+
+impl SortCollation {
+    pub(crate) fn from_option() -> Self {
+        let option = harp::get_option("ark.data_explorer.collation");
+
+        let collation: Option<String> = match r_null_or_try_into(option) {
+            Ok(collation) => collation,
+            Err(err) => {
+                log::warn!("Can't read `ark.data_explorer.collation` option: {err}");
+                None
+            },
+        };
+        let Some(collation) = collation else {
+            return Self::default();
+        };
+
+        match Self::from_str(&collation.to_lowercase()) {
+            Ok(collation) => collation,
+            Err(_) => {
+                log::warn!("Unknown collation `{collation}` in `ark.data_explorer.collation`");
+                Self::default()
+            },
+        }
+    }
+}
+
+/// The variable names and titles of the data explorers viewing variables of
+/// the global environment, keyed by comm ID. Saved sessions reopen these
+/// data explorers when they are restored, see `session.R`.
+static GLOBAL_VIEWS: Lazy<Mutex<HashMap<String, (String, String)>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
 impl Drop for RDataExplorer {
     fn drop(&mut self) {
         // We guarantee that the table is deleted from the global store.
         self.table.delete();
+
+        self.profiles_token.cancel();
+
+        GLOBAL_VIEWS.lock().unwrap().remove(&self.comm.comm_id);
     }
 }
 
@@ -195,10 +255,25 @@ impl RDataExplorer {
             String::from("positron.dataExplorer"),
         );
 
+        // Lists are viewed through a table with a row per element
+        let (data, list) = match Self::r_list_as_table(&data)? {
+            Some(table) => (table, Some(RThreadSafe::new(data))),
+            None => (data, None),
+        };
+
         // To be able to `Send` the `data` to the thread to be owned by the data
         // viewer, it needs to be made thread safe
         let table = Table::new(RThreadSafe::new(data));
 
+        if let Some(binding) = &binding {
+            if binding.env.get().sexp == R_ENVS.global {
+                GLOBAL_VIEWS
+                    .lock()
+                    .unwrap()
+                    .insert(id.clone(), (binding.name.clone(), title.clone()));
+            }
+        }
+
         spawn!(format!("ark-data-viewer-{}-{}", title, id), move || {
             // Get the initial set of column schemas for the data object
             let shape = r_task(|| Self::r_get_shape(table.get()?));
@@ -209,16 +284,18 @@ impl RDataExplorer {
                     let viewer = Self {
                         title,
                         table,
+                        list,
                         binding,
+                        view: ViewState::new(shape.num_rows),
                         shape,
-                        sorted_indices: None,
-                        filtered_indices: None,
-                        view_indices: None,
-                        sort_keys: vec![],
-                        row_filters: vec![],
                         col_filters: vec![],
+                        row_label_options: RowLabelOptions {
+                            mode: RowLabelMode::RowNames,
+                            column_index: None,
+                        },
                         comm,
                         comm_manager_tx,
+                        profiles_token: RTaskToken::new(),
                     };
 
                     // Start the data viewer's execution thread
@@ -232,6 +309,8 @@ impl RDataExplorer {
                         err
                     );
 
+                    GLOBAL_VIEWS.lock().unwrap().remove(&comm.comm_id);
+
                     // Close the comm immediately since we can't proceed without
                     // the schema
                     comm_manager_tx
@@ -249,7 +328,7 @@ impl RDataExplorer {
         // `CommManagerEvent::Opened` to ensure we can't miss any console prompt events
         // (#781).
         let (prompt_signal_tx, prompt_signal_rx) = unbounded::<()>();
-        let listen_id = EVENTS.console_prompt.listen({
+        let prompt_subscription = EVENTS.console_prompt.subscribe({
             move |_| {
                 prompt_signal_tx.send(()).unwrap();
             }
@@ -320,7 +399,7 @@ impl RDataExplorer {
             }
         }
 
-        EVENTS.console_prompt.remove(listen_id);
+        drop(prompt_subscription);
 
         if !user_initiated_close {
             // Send a close message to the frontend if the frontend didn't
@@ -357,15 +436,22 @@ impl RDataExplorer {
                 log::error!("Old table has been deleted? This is unexpected, but we'll update the data explorer table.");
                 // It's `unsafe` because RObject::new calls protect, and it shouldn't
                 // be called outside of the R main thread.
-                self.table.set(RThreadSafe::new(RObject::new(new)));
+                self.r_set_data(RObject::new(new));
                 return true;
             });
 
-            if new == old.sexp {
+            // When viewing a list, the binding refers to the list rather than
+            // to the table
+            let old = match &self.list {
+                Some(list) => list.get().sexp,
+                None => old.sexp,
+            };
+
+            if new == old {
                 false
             } else {
                 // Safety is same as above. We guarantee this is the R main thread.
-                self.table.set(RThreadSafe::new(RObject::new(new)));
+                self.r_set_data(RObject::new(new));
                 true
             }
         });
@@ -396,23 +482,14 @@ impl RDataExplorer {
         let event = if self.shape.columns != new_shape.columns {
             // Columns changed, so update our cache, and we need to send a
             // schema update event
+            self.row_label_options_update(&new_shape);
             self.shape = new_shape;
 
-            // Update row filters to reflect the new schema
-            self.row_filters_update()?;
-
-            // Clear precomputed indices
-            self.sorted_indices = None;
-            self.filtered_indices = None;
-            self.view_indices = None;
-
-            // Clear active sort keys
-            self.sort_keys.clear();
-
-            // Recompute and apply filters and sorts.
-            let (indices, _) = self.row_filters_compute()?;
-            self.filtered_indices = indices;
-            self.apply_sorts_and_filters();
+            // Update row filters to reflect the new schema, clear sorts, and
+            // recompute the view
+            let backend = RTableBackend::new(&self.table, self.shape.kind);
+            self.view
+                .update_schema(&backend, &self.shape.columns, self.shape.num_rows)?;
 
             DataExplorerFrontendEvent::SchemaUpdate
         } else {
@@ -420,16 +497,10 @@ impl RDataExplorer {
             // so we need to set the shape to the new_shape
             self.shape = new_shape;
 
-            // Columns didn't change, but the data has. If there are sort
-            // keys, we need to sort the rows again to reflect the new data.
-            if self.sort_keys.len() > 0 {
-                self.sorted_indices = Some(r_task(|| self.r_sort_rows())?);
-            }
-
-            // Recompute and apply filters and sorts.
-            let (indices, _) = self.row_filters_compute()?;
-            self.filtered_indices = indices;
-            self.apply_sorts_and_filters();
+            // Columns didn't change, but the data has. Sort and filter the
+            // rows again to reflect the new data.
+            let backend = RTableBackend::new(&self.table, self.shape.kind);
+            self.view.update_data(&backend, self.shape.num_rows)?;
 
             DataExplorerFrontendEvent::DataUpdate
         };
@@ -440,37 +511,74 @@ impl RDataExplorer {
         Ok(true)
     }
 
-    // Marks row_filters as invalid if the column no longer exists
-    // If the column still exists, update the column schema of the filter
-    // and check if they are still valid.
-    // Should be called whenever there's a schema update, ie. when `self.shape` changes.
-    fn row_filters_update(&mut self) -> anyhow::Result<()> {
-        for rf in self.row_filters.iter_mut() {
-            let new_schema = self
-                .shape
-                .columns
-                .iter()
-                .find(|c| c.column_name == rf.column_schema.column_name);
-
-            match new_schema {
-                Some(schema) => {
-                    rf.column_schema = schema.clone();
-                    let is_valid = Self::is_valid_filter(rf)?;
-                    rf.is_valid = Some(is_valid);
-                    rf.error_message = if is_valid {
-                        None
-                    } else {
-                        Some("Unsupported column type for filter".to_string())
-                    };
+    /// Replaces the viewed data object, going through the list adapter if we
+    /// were viewing a list and the new object is still a list
+    fn r_set_data(&mut self, data: RObject) {
+        if self.list.is_some() {
+            match Self::r_list_as_table(&data) {
+                Ok(Some(table)) => {
+                    self.list = Some(RThreadSafe::new(data));
+                    self.table.set(RThreadSafe::new(table));
+                    return;
                 },
-                None => {
-                    // the column no longer exists
-                    rf.is_valid = Some(false);
-                    rf.error_message = Some("Column was removed".to_string());
-                },
-            };
+                Ok(None) => {},
+                Err(err) => log::error!("Can't convert list to a table: {err}"),
+            }
+            self.list = None;
+        }
+
+        self.table.set(RThreadSafe::new(data));
+    }
+
+    /// Converts a list to a table with a row per element, or returns `None`
+    /// if `data` is not a list
+    fn r_list_as_table(data: &RObject) -> harp::Result<Option<RObject>> {
+        let is_list: bool = RFunction::new("", "is_viewable_list")
+            .add(data.sexp)
+            .call_in(ARK_ENVS.positron_ns)?
+            .try_into()?;
+
+        if !is_list {
+            return Ok(None);
         }
-        Ok(())
+
+        let table = RFunction::new("", "list_as_table")
+            .add(data.sexp)
+            .call_in(ARK_ENVS.positron_ns)?;
+
+        Ok(Some(table))
+    }
+
+    // Follows the labeling column to its position in the new schema, or falls
+    // back to the row names if the column no longer exists. Should be called
+    // before `self.shape` is updated.
+    fn row_label_options_update(&mut self, new_shape: &DataObjectShape) {
+        let RowLabelMode::Column = self.row_label_options.mode else {
+            return;
+        };
+
+        let old_column = self
+            .row_label_options
+            .column_index
+            .and_then(|index| self.shape.columns.get(index as usize));
+
+        let new_index = old_column.and_then(|old| {
+            new_shape
+                .columns
+                .iter()
+                .position(|column| column.column_name == old.column_name)
+        });
+
+        self.row_label_options = match new_index {
+            Some(index) => RowLabelOptions {
+                mode: RowLabelMode::Column,
+                column_index: Some(index as i64),
+            },
+            None => RowLabelOptions {
+                mode: RowLabelMode::RowNames,
+                column_index: None,
+            },
+        };
     }
 
     fn handle_rpc(
@@ -490,42 +598,22 @@ impl RDataExplorer {
             DataExplorerBackendRequest::SetSortColumns(SetSortColumnsParams {
                 sort_keys: keys,
             }) => {
-                // Save the new sort keys
-                self.sort_keys = keys.clone();
-
-                // If there are no sort keys, clear the precomputed sorted
-                // indices; otherwise, sort the rows and save the result
-                self.sorted_indices = match keys.len() {
-                    0 => None,
-                    _ => Some(r_task(|| self.r_sort_rows())?),
-                };
-
-                // Apply sorts to the filtered indices to create view indices
-                self.apply_sorts_and_filters();
-
+                let backend = RTableBackend::new(&self.table, self.shape.kind);
+                self.view.set_sort_keys(&backend, keys)?;
                 Ok(DataExplorerBackendReply::SetSortColumnsReply())
             },
 
-            DataExplorerBackendRequest::SetRowFilters(SetRowFiltersParams { filters }) => {
-                // Save the new row filters
-                self.row_filters = filters;
-
-                // Compute the filtered indices
-                let (indices, had_errors) = self.row_filters_compute()?;
-                self.filtered_indices = indices;
-
-                // Apply sorts to the filtered indices to create view indices
-                self.apply_sorts_and_filters();
-
-                Ok(DataExplorerBackendReply::SetRowFiltersReply({
-                    FilterResult {
-                        selected_num_rows: match self.filtered_indices {
-                            Some(ref indices) => indices.len() as i64,
-                            None => self.shape.num_rows as i64,
-                        },
-                        had_errors,
-                    }
-                }))
+            DataExplorerBackendRequest::SetRowFilters(SetRowFiltersParams {
+                filters,
+                groups,
+                format_options,
+            }) => {
+                let backend = RTableBackend::new(&self.table, self.shape.kind);
+                let groups = groups.unwrap_or_default();
+                self.view
+                    .set_filter_number_format(format_options.as_ref().map(NumberFormat::from));
+                let result = self.view.set_row_filters(&backend, filters, groups)?;
+                Ok(DataExplorerBackendReply::SetRowFiltersReply(result))
             },
 
             DataExplorerBackendRequest::GetColumnProfiles(params) => {
@@ -559,18 +647,44 @@ impl RDataExplorer {
                 ))
             },
 
+            DataExplorerBackendRequest::SetRowLabelOptions(SetRowLabelOptionsParams {
+                options,
+            }) => {
+                if let RowLabelMode::Column = options.mode {
+                    let Some(column_index) = options.column_index else {
+                        return Err(anyhow!("A column is required to label rows by column"));
+                    };
+                    if column_index < 0 || column_index as usize >= self.shape.columns.len() {
+                        return Err(anyhow!("Column index out of range {column_index}"));
+                    }
+                }
+
+                self.row_label_options = options;
+                Ok(DataExplorerBackendReply::SetRowLabelOptionsReply())
+            },
+
             DataExplorerBackendRequest::ExportDataSelection(ExportDataSelectionParams {
                 selection,
                 format,
+                factor_values,
+                datetime_options,
             }) => Ok(DataExplorerBackendReply::ExportDataSelectionReply(
                 ExportedData {
-                    data: self.r_export_data_selection(selection, format.clone())?,
+                    data: self.r_export_data_selection(
+                        selection,
+                        format.clone(),
+                        factor_values.unwrap_or(ExportFactorValues::Labels),
+                        datetime_options,
+                    )?,
                     format,
                 },
             )),
             DataExplorerBackendRequest::ConvertToCode(params) => Ok(
                 DataExplorerBackendReply::ConvertToCodeReply(self.convert_to_code(params)),
             ),
+            DataExplorerBackendRequest::OpenElement(OpenElementParams { row_index }) => {
+                r_task(|| self.r_open_element(row_index))
+            },
             DataExplorerBackendRequest::SuggestCodeSyntax => Ok(
                 DataExplorerBackendReply::SuggestCodeSyntaxReply(self.suggest_code_syntax()),
             ),
@@ -670,12 +784,12 @@ impl RDataExplorer {
 
         let params = ProcessColumnsProfilesParams {
             table: self.table.clone(),
-            indices: self.filtered_indices.clone(),
+            indices: self.view.filtered_indices().clone(),
             kind: self.shape.kind,
             request: params,
         };
         let comm = self.comm.clone();
-        r_task::spawn_idle(|| async move {
+        r_task::spawn_idle_cancellable(&self.profiles_token, || async move {
             log::trace!("Processing GetColumnProfile request: {id}");
             handle_columns_profiles_requests(params, comm)
                 .instrument(tracing::info_span!("get_columns_profile", ns = id))
@@ -684,212 +798,6 @@ impl RDataExplorer {
         });
     }
 
-    /// Sort the rows of the data object according to the sort keys in
-    /// self.sort_keys.
-    ///
-    /// Returns a vector containing the sorted row indices.
-    fn r_sort_rows(&self) -> anyhow::Result<Vec<i32>> {
-        let mut order = RFunction::new("base", "order");
-
-        // Allocate a vector to hold the sort order for each column
-        let mut decreasing: Vec<bool> = Vec::new();
-
-        // For each element of self.sort_keys, add an argument to order
-        for key in &self.sort_keys {
-            // Get the column to sort by
-            order.add(tbl_get_column(
-                self.table.get()?.sexp,
-                key.column_index as i32,
-                self.shape.kind,
-            )?);
-            decreasing.push(!key.ascending);
-        }
-        // Add the sort order per column
-        order.param("decreasing", RObject::try_from(&decreasing)?);
-        order.param("method", RObject::from("radix"));
-
-        // Invoke the order function and return the result
-        let result = order.call()?;
-        let indices: Vec<i32> = result.try_into()?;
-        Ok(indices)
-    }
-
-    /// Filter all the rows in the data object according to the row filters in
-    /// self.row_filters.
-    ///
-    /// Returns a tuple containing a vector of all the row indices that pass the filters and
-    /// a character vector of errors, where None means no error happened.
-    fn r_filter_rows(&self) -> anyhow::Result<(Vec<i32>, Vec<Option<String>>)> {
-        let mut filters: Vec<RObject> = vec![];
-
-        // Shortcut: If there are no row filters, the filtered indices include
-        // all row indices.
-        if self.row_filters.is_empty() {
-            return Ok(((1..=self.shape.num_rows).collect(), vec![]));
-        }
-
-        // Convert each filter to an R object by marshaling through the JSON
-        // layer.
-        //
-        // This feels a little weird since the filters were *unmarshaled* from
-        // JSON earlier in the RPC stack, but it's the easiest way to create R
-        // objects from the filter data without creating an unnecessary
-        // intermediate representation.
-        for filter in &self.row_filters {
-            let filter = serde_json::to_value(filter)?;
-            let filter = RObject::try_from(filter)?;
-            filters.push(filter);
-        }
-
-        // Pass the row filters to R and get the resulting row indices
-        let filters = RObject::try_from(filters)?;
-        let result: HashMap<String, RObject> = RFunction::new("", ".ps.filter_rows")
-            .param("table", self.table.get()?.sexp)
-            .param("row_filters", filters)
-            .call_in(ARK_ENVS.positron_ns)?
-            .try_into()?;
-
-        // Handle errors that occured in the filters
-        let row_indices = match result.get("indices") {
-            Some(indices) => Vec::<i32>::try_from(indices.clone())?,
-            None => bail!("Unexpected output from .ps.filter_rows. Expected 'indices' field."),
-        };
-
-        let errors = match result.get("errors") {
-            Some(errors) => Vec::<Option<String>>::try_from(errors.clone())?,
-            None => bail!("Unexpected output from .ps.filter_rows. Expected 'errors' field."),
-        };
-
-        Ok((row_indices, errors))
-    }
-
-    // Compute filtered indices out of the current `row_filters`.
-    //
-    // Implicitly updates the `row_filters` with validity status and error messages, if they
-    // fail during the computation.
-    fn row_filters_compute(&mut self) -> anyhow::Result<(Option<Vec<i32>>, Option<bool>)> {
-        if self.row_filters.len() == 0 {
-            return Ok((None, None));
-        }
-
-        let (indices, errors) = r_task(|| self.r_filter_rows())?;
-        // this is called for the side-effect of updating the row_filters with validty status and
-        // error messages
-        let had_errors = Some(self.apply_filter_errors(errors)?);
-
-        Ok((Some(indices), had_errors))
-    }
-
-    // Check if a filter is valid by looking at it's type and the type of the column its applied to.
-    // Uses logic similar to python side: https://github.com/posit-dev/positron/blob/aafe313a261fd133b9f4a9f87c92bb10dc9966ad/extensions/positron-python/python_files/positron/positron_ipykernel/data_explorer.py#L743-L744
-    fn is_valid_filter(filter: &RowFilter) -> anyhow::Result<bool> {
-        let display_type = &filter.column_schema.type_display;
-        let filter_type = &filter.filter_type;
-
-        let is_compare_supported = |x: &ColumnDisplayType| match x {
-            ColumnDisplayType::Integer |
-            ColumnDisplayType::Floating |
-            ColumnDisplayType::Decimal |
-            ColumnDisplayType::Date |
-            ColumnDisplayType::Datetime |
-            ColumnDisplayType::Time => true,
-            _ => false,
-        };
-
-        match filter_type {
-            RowFilterType::IsEmpty | RowFilterType::NotEmpty | RowFilterType::Search => {
-                // String-only filter types
-                Ok(display_type == &ColumnDisplayType::String)
-            },
-            RowFilterType::Compare => {
-                if let Some(params) = &filter.params {
-                    match params {
-                        RowFilterParams::Comparison(comparison) => match comparison.op {
-                            FilterComparisonOp::Eq | FilterComparisonOp::NotEq => Ok(true),
-                            _ => Ok(is_compare_supported(display_type)),
-                        },
-                        _ => Err(anyhow!("Missing compare filter params")),
-                    }
-                } else {
-                    Err(anyhow!("Missing compare_params for filter"))
-                }
-            },
-            RowFilterType::Between | RowFilterType::NotBetween => {
-                Ok(is_compare_supported(display_type))
-            },
-            RowFilterType::IsTrue | RowFilterType::IsFalse => {
-                Ok(display_type == &ColumnDisplayType::Boolean)
-            },
-            RowFilterType::IsNull | RowFilterType::NotNull | RowFilterType::SetMembership => {
-                // Filters always supported
-                Ok(true)
-            },
-        }
-    }
-
-    // Handle errors that occured in the filters
-    //
-    // This function mutates the `row_filters` attribute to include error messages and validity status.
-    fn apply_filter_errors(&mut self, errors: Vec<Option<String>>) -> anyhow::Result<bool> {
-        let mut had_errors = false;
-        for (i, error) in errors.iter().enumerate() {
-            match error {
-                None => {
-                    self.row_filters[i].is_valid = Some(true);
-                },
-                Some(error) => {
-                    self.row_filters[i].is_valid = Some(false);
-                    self.row_filters[i].error_message = Some(error.clone());
-                    had_errors = true;
-                },
-            }
-        }
-        return Ok(had_errors);
-    }
-
-    /// Sort the filtered indices according to the sort keys, storing the
-    /// result in view_indices.
-    fn apply_sorts_and_filters(&mut self) {
-        // If there are no filters or sorts, we don't need any view indices
-        if self.filtered_indices.is_none() && self.sorted_indices.is_none() {
-            self.view_indices = None;
-            return;
-        }
-
-        // If there are filters but no sorts, the view indices are the filtered
-        // indices
-        if self.sorted_indices.is_none() {
-            self.view_indices = self.filtered_indices.clone();
-            return;
-        }
-
-        // If there are sorts but no filters, the view indices are the sorted
-        // indices
-        if self.filtered_indices.is_none() {
-            self.view_indices = self.sorted_indices.clone();
-            return;
-        }
-
-        // There are both sorts and filters, so we need to combine them.
-        // self.sorted_indices contains all the indices; self.filtered_indices
-        // contains the subset of indices that pass the filters, in ascending
-        // order.
-        //
-        // Derive the set of indices that pass the filters and are sorted
-        // according to the sort keys.
-        let filtered_indices = self.filtered_indices.as_ref().unwrap();
-        let sorted_indices = self.sorted_indices.as_ref().unwrap();
-        let mut view_indices = Vec::<i32>::with_capacity(filtered_indices.len());
-        for &index in sorted_indices {
-            // We can use a binary search here for performance because
-            // filtered_indices is already sorted in ascending order.
-            if let Ok(_) = filtered_indices.binary_search(&index) {
-                view_indices.push(index);
-            }
-        }
-        self.view_indices = Some(view_indices);
-    }
-
     /// Search the schema for columns matching the given filters and sort order.
     ///
     /// - `params`: The search parameters including filters and sort order.
@@ -1043,29 +951,43 @@ impl RDataExplorer {
     }
 
     fn r_get_state(&self) -> anyhow::Result<DataExplorerBackendReply> {
+        let table = self.table.get()?;
+
         let row_names = RFunction::new("base", "row.names")
-            .add(self.table.get()?)
+            .add(table.clone())
             .call_in(ARK_ENVS.positron_ns)?;
 
+        let has_meaningful_row_names: bool = RFunction::new("", "has_meaningful_row_names")
+            .add(table.clone())
+            .call_in(ARK_ENVS.positron_ns)?
+            .try_into()?;
+
+        let spatial_metadata = self.r_spatial_metadata(table)?;
+
         let state = BackendState {
             display_name: self.title.clone(),
             connected: Some(true),
             error_message: None,
             table_shape: TableShape {
-                num_rows: match self.filtered_indices {
-                    Some(ref indices) => indices.len() as i64,
-                    None => self.shape.num_rows as i64,
-                },
+                num_rows: self.view.num_filtered_rows(),
                 num_columns: self.shape.columns.len() as i64,
             },
             table_unfiltered_shape: TableShape {
                 num_rows: self.shape.num_rows as i64,
                 num_columns: self.shape.columns.len() as i64,
             },
-            row_filters: self.row_filters.clone(),
+            row_filters: self.view.row_filters().to_vec(),
+            row_filter_groups: Some(self.view.row_filter_groups().to_vec()),
             column_filters: self.col_filters.clone(),
-            sort_keys: self.sort_keys.clone(),
-            has_row_labels: !row_names.is_null(),
+            sort_keys: self.view.sort_keys().to_vec(),
+            has_row_labels: match self.row_label_options.mode {
+                RowLabelMode::RowNames => !row_names.is_null(),
+                RowLabelMode::Hidden => false,
+                RowLabelMode::Column => true,
+            },
+            has_meaningful_row_names: Some(has_meaningful_row_names),
+            row_label_options: Some(self.row_label_options.clone()),
+            spatial_metadata,
             supported_features: SupportedFeatures {
                 get_column_profiles: GetColumnProfilesFeatures {
                     support_status: SupportStatus::Supported,
@@ -1130,10 +1052,7 @@ impl RDataExplorer {
                         support_status: SupportStatus::Supported,
                     })
                     .collect(),
-                    // Temporarily disabled for https://github.com/posit-dev/positron/issues/3489
-                    // on 6/11/2024. This will be enabled again when the UI has been reworked to
-                    // support grouping.
-                    supports_conditions: SupportStatus::Unsupported,
+                    supports_conditions: SupportStatus::Supported,
                 },
                 set_column_filters: SetColumnFiltersFeatures {
                     support_status: SupportStatus::Unsupported,
@@ -1161,6 +1080,61 @@ impl RDataExplorer {
         Ok(DataExplorerBackendReply::GetStateReply(state))
     }
 
+    /// The geometry column, CRS, and bounding box of sf objects, see
+    /// `spatial_metadata()`
+    fn r_spatial_metadata(&self, table: RObject) -> anyhow::Result<Option<SpatialMetadata>> {
+        let metadata = RFunction::new("", "spatial_metadata")
+            .add(table)
+            .call_in(ARK_ENVS.positron_ns)?;
+
+        let Some(metadata) = r_null_or_try_into::<HashMap<String, RObject>>(metadata)? else {
+            return Ok(None);
+        };
+        let get = |name: &str| -> anyhow::Result<RObject> {
+            metadata
+                .get(name)
+                .cloned()
+                .ok_or(anyhow!("Missing '{name}' in spatial metadata"))
+        };
+
+        // The geometry column is located in the schema by name
+        let geometry_column: String = get("geometry_column")?.try_into()?;
+        let Some(geometry_column_index) = self
+            .shape
+            .columns
+            .iter()
+            .position(|column| column.column_name == geometry_column)
+        else {
+            return Ok(None);
+        };
+
+        let crs: Option<String> = r_null_or_try_into(get("crs")?)?;
+
+        let bbox = match r_null_or_try_into::<HashMap<String, RObject>>(get("bbox")?)? {
+            Some(bbox) => {
+                let coord = |name: &str| -> anyhow::Result<f64> {
+                    match bbox.get(name) {
+                        Some(value) => Ok(value.clone().try_into()?),
+                        None => Err(anyhow!("Missing '{name}' in bounding box")),
+                    }
+                };
+                Some(SpatialBoundingBox {
+                    xmin: coord("xmin")?,
+                    ymin: coord("ymin")?,
+                    xmax: coord("xmax")?,
+                    ymax: coord("ymax")?,
+                })
+            },
+            None => None,
+        };
+
+        Ok(Some(SpatialMetadata {
+            geometry_column_index: geometry_column_index as i64,
+            crs,
+            bbox,
+        }))
+    }
+
     fn r_get_data_values(
         &self,
         columns: Vec<ColumnSelection>,
@@ -1170,8 +1144,8 @@ impl RDataExplorer {
         for selection in columns {
             let tbl = tbl_subset_with_view_indices(
                 self.table.get()?.sexp,
-                &self.view_indices,
-                Some(self.get_row_selection_indices(selection.spec)),
+                self.view.view_indices(),
+                Some(self.view.row_selection_indices(selection.spec)),
                 Some(vec![selection.column_index]),
             )?;
 
@@ -1193,10 +1167,31 @@ impl RDataExplorer {
         selection: ArraySelection,
         format_options: &FormatOptions,
     ) -> anyhow::Result<Vec<String>> {
+        match self.row_label_options.mode {
+            RowLabelMode::RowNames => {},
+            RowLabelMode::Hidden => return Ok(vec![]),
+            RowLabelMode::Column => {
+                let Some(column_index) = self.row_label_options.column_index else {
+                    return Err(anyhow!("No column to label rows with"));
+                };
+
+                let tbl = tbl_subset_with_view_indices(
+                    self.table.get()?.sexp,
+                    self.view.view_indices(),
+                    Some(self.view.row_selection_indices(selection)),
+                    Some(vec![column_index]),
+                )?;
+
+                // The column is at index 0 because we only selected that column
+                let column = tbl_get_column(tbl.sexp, 0, self.shape.kind)?;
+                return Ok(format_string(column.sexp, format_options));
+            },
+        }
+
         let tbl = tbl_subset_with_view_indices(
             self.table.get()?.sexp,
-            &self.view_indices,
-            Some(self.get_row_selection_indices(selection)),
+            self.view.view_indices(),
+            Some(self.view.row_selection_indices(selection)),
             Some(vec![]), // Use empty vec, because we only need the row names.
         )?;
 
@@ -1218,44 +1213,59 @@ impl RDataExplorer {
         }
     }
 
-    // Given an ArraySelection, this materializes the indices that will actually be used.
-    // Also does some sanity checks to avoid OOB access.
-    fn get_row_selection_indices(&self, selection: ArraySelection) -> Vec<i64> {
-        let num_view_rows = match self.view_indices {
-            Some(ref indices) => indices.len() as i32,
-            None => self.shape.num_rows,
-        } as i64;
-
-        // Returns the indices that will be collected
-        match selection {
-            ArraySelection::SelectRange(range) => {
-                let lower_bound = cmp::min(range.first_index, num_view_rows);
-                let upper_bound = cmp::min(range.last_index + 1, num_view_rows);
-                (lower_bound..upper_bound).collect()
-            },
-            ArraySelection::SelectIndices(indices) => indices
-                .indices
-                .into_iter()
-                .filter(|v| *v < num_view_rows)
-                .collect(),
-        }
-    }
-
     fn r_export_data_selection(
         &self,
         selection: TableSelection,
         format: ExportFormat,
+        factor_values: ExportFactorValues,
+        datetime_options: Option<DatetimeFormatOptions>,
     ) -> anyhow::Result<String> {
         r_task(|| {
             export_selection::export_selection(
                 self.table.get()?.sexp,
-                &self.view_indices,
+                self.view.view_indices(),
                 selection,
                 format,
+                factor_values,
+                &datetime_options,
             )
         })
     }
 
+    /// Opens the element of the viewed list at `row_index` in a new data
+    /// explorer. The new data explorer shows a snapshot of the element and is
+    /// not updated when the list changes.
+    fn r_open_element(&self, row_index: i64) -> anyhow::Result<DataExplorerBackendReply> {
+        let Some(list) = &self.list else {
+            return Err(anyhow!("Can only open elements of lists"));
+        };
+
+        // Convert from the sorted and filtered view to the 1-based index of
+        // the element
+        let index = match self.view.view_indices() {
+            Some(indices) => {
+                let Some(index) = indices.get(row_index as usize) else {
+                    return Err(anyhow!("Row index {row_index} is out of bounds"));
+                };
+                *index
+            },
+            None => i32::try_from(row_index + 1)?,
+        };
+
+        let info = RFunction::new("", "list_element_view")
+            .add(list.get().sexp)
+            .add(index)
+            .add(self.title.as_str())
+            .call_in(ARK_ENVS.positron_ns)?;
+
+        let element = RObject::new(harp::list_get(info.sexp, 0));
+        let title = String::try_from(RObject::view(harp::list_get(info.sexp, 1)))?;
+
+        RDataExplorer::start(title, element, None, self.comm_manager_tx.clone())?;
+
+        Ok(DataExplorerBackendReply::OpenElementReply())
+    }
+
     /// Suggest code syntax for code conversion
     ///
     /// Returns the preferred code syntax for converting data explorer operations to code.
@@ -1275,7 +1285,7 @@ impl RDataExplorer {
             .map(|b| b.name.as_str())
             .or_else(|| Some(self.title.as_str()));
 
-        // Resolve column names for sort keys using the same pattern as r_sort_rows()
+        // Resolve column names for sort keys using the same pattern as `RTableBackend::r_sort_rows()`
         let resolved_sort_keys: Vec<convert_to_code::ResolvedSortKey> = params
             .sort_keys
             .iter()
@@ -1351,3 +1361,18 @@ pub unsafe extern "C-unwind" fn ps_view_data_frame(
 
     Ok(R_NilValue)
 }
+
+/// Returns a data frame of the variable names and titles of the data
+/// explorers viewing variables of the global environment
+#[harp::register]
+pub unsafe extern "C-unwind" fn ps_data_explorer_global_views() -> anyhow::Result<SEXP> {
+    let (names, titles): (Vec<String>, Vec<String>) =
+        GLOBAL_VIEWS.lock().unwrap().values().cloned().unzip();
+
+    let views = DataFrameBuilder::new()
+        .column("name", CharacterVector::from_iter(names))
+        .column("title", CharacterVector::from_iter(titles))
+        .build()?;
+
+    Ok(views.sexp)
+}
@@ -0,0 +1,221 @@
+//
+// row_filter_display.rs
+//
+// Copyright (C) 2025 by Posit Software, PBC
+//
+//
+
+use amalthea::comm::data_explorer_comm::ColumnDisplayType;
+use amalthea::comm::data_explorer_comm::FilterComparisonOp;
+use amalthea::comm::data_explorer_comm::RowFilter;
+use amalthea::comm::data_explorer_comm::RowFilterParams;
+use amalthea::comm::data_explorer_comm::RowFilterType;
+use amalthea::comm::data_explorer_comm::TextSearchType;
+
+/// A human-readable summary of a row filter, e.g. `height < 60` or
+/// `text contains '.'`, returned to the frontends in `BackendState` so that
+/// they all display the filters in the same way.
+///
+/// Values are shown as entered by the user. They are quoted unless the
+/// column is numeric or logical.
+pub fn row_filter_display_text(filter: &RowFilter) -> String {
+    let column = &filter.column_schema.column_name;
+    let value = |x: &str| display_value(x, &filter.column_schema.type_display);
+
+    match (&filter.filter_type, &filter.params) {
+        (RowFilterType::IsNull, _) => format!("{column} is missing"),
+        (RowFilterType::NotNull, _) => format!("{column} is not missing"),
+        (RowFilterType::IsEmpty, _) => format!("{column} is empty"),
+        (RowFilterType::NotEmpty, _) => format!("{column} is not empty"),
+        (RowFilterType::IsTrue, _) => format!("{column} is true"),
+        (RowFilterType::IsFalse, _) => format!("{column} is false"),
+
+        (RowFilterType::Compare, Some(RowFilterParams::Comparison(comparison))) => {
+            let op = match comparison.op {
+                FilterComparisonOp::Eq => "=",
+                FilterComparisonOp::NotEq => "!=",
+                FilterComparisonOp::Lt => "<",
+                FilterComparisonOp::LtEq => "<=",
+                FilterComparisonOp::Gt => ">",
+                FilterComparisonOp::GtEq => ">=",
+            };
+            format!("{column} {op} {}", value(&comparison.value))
+        },
+
+        (RowFilterType::Between, Some(RowFilterParams::Between(between))) => format!(
+            "{column} is between {} and {}",
+            value(&between.left_value),
+            value(&between.right_value)
+        ),
+        (RowFilterType::NotBetween, Some(RowFilterParams::Between(between))) => format!(
+            "{column} is not between {} and {}",
+            value(&between.left_value),
+            value(&between.right_value)
+        ),
+
+        (RowFilterType::Search, Some(RowFilterParams::TextSearch(search))) => {
+            let verb = match search.search_type {
+                TextSearchType::Contains => "contains",
+                TextSearchType::NotContains => "does not contain",
+                TextSearchType::StartsWith => "starts with",
+                TextSearchType::EndsWith => "ends with",
+                TextSearchType::RegexMatch => "matches",
+            };
+            let case = if search.case_sensitive {
+                " (case-sensitive)"
+            } else {
+                ""
+            };
+            format!("{column} {verb} {}{case}", quote(&search.term))
+        },
+
+        (RowFilterType::SetMembership, Some(RowFilterParams::SetMembership(set))) => {
+            let values: Vec<String> = set.values.iter().map(|x| value(x)).collect();
+            let verb = if set.inclusive {
+                "is one of"
+            } else {
+                "is not one of"
+            };
+            format!("{column} {verb} {}", values.join(", "))
+        },
+
+        // Filters with missing or mismatched parameters are invalid, we
+        // still show the column and the type of filter
+        (filter_type, _) => format!("{column} {filter_type}"),
+    }
+}
+
+fn display_value(x: &str, display_type: &ColumnDisplayType) -> String {
+    match display_type {
+        ColumnDisplayType::Integer |
+        ColumnDisplayType::Floating |
+        ColumnDisplayType::Decimal |
+        ColumnDisplayType::Boolean => x.to_string(),
+        _ => quote(x),
+    }
+}
+
+fn quote(x: &str) -> String {
+    format!("'{}'", x.replace('\'', "\\'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use amalthea::comm::data_explorer_comm::ColumnDisplayType;
+    use amalthea::comm::data_explorer_comm::ColumnSchema;
+    use amalthea::comm::data_explorer_comm::FilterBetween;
+    use amalthea::comm::data_explorer_comm::FilterComparison;
+    use amalthea::comm::data_explorer_comm::FilterComparisonOp;
+    use amalthea::comm::data_explorer_comm::FilterSetMembership;
+    use amalthea::comm::data_explorer_comm::FilterTextSearch;
+    use amalthea::comm::data_explorer_comm::RowFilter;
+    use amalthea::comm::data_explorer_comm::RowFilterCondition;
+    use amalthea::comm::data_explorer_comm::RowFilterParams;
+    use amalthea::comm::data_explorer_comm::RowFilterType;
+    use amalthea::comm::data_explorer_comm::TextSearchType;
+
+    use crate::data_explorer::row_filter_display::row_filter_display_text;
+
+    fn filter(
+        column_name: &str,
+        type_display: ColumnDisplayType,
+        filter_type: RowFilterType,
+        params: Option<RowFilterParams>,
+    ) -> RowFilter {
+        RowFilter {
+            filter_id: "filter".to_string(),
+            filter_type,
+            column_schema: ColumnSchema {
+                column_name: column_name.to_string(),
+                column_label: None,
+                column_index: 0,
+                type_name: "".to_string(),
+                type_display,
+                description: None,
+                children: None,
+                precision: None,
+                scale: None,
+                timezone: None,
+                type_size: None,
+            },
+            condition: RowFilterCondition::And,
+            is_valid: None,
+            error_message: None,
+            params,
+            group_id: None,
+            display_text: None,
+        }
+    }
+
+    #[test]
+    fn test_row_filter_display_text() {
+        let compare = |type_display, op, value: &str| {
+            let params = RowFilterParams::Comparison(FilterComparison {
+                op,
+                value: value.to_string(),
+            });
+            let filter = filter("height", type_display, RowFilterType::Compare, Some(params));
+            row_filter_display_text(&filter)
+        };
+        assert_eq!(
+            compare(ColumnDisplayType::Floating, FilterComparisonOp::Lt, "60"),
+            "height < 60"
+        );
+        assert_eq!(
+            compare(ColumnDisplayType::String, FilterComparisonOp::NotEq, "it's"),
+            "height != 'it\\'s'"
+        );
+
+        let search = RowFilterParams::TextSearch(FilterTextSearch {
+            search_type: TextSearchType::Contains,
+            term: ".".to_string(),
+            case_sensitive: false,
+        });
+        let filter_search = filter(
+            "text",
+            ColumnDisplayType::String,
+            RowFilterType::Search,
+            Some(search),
+        );
+        assert_eq!(row_filter_display_text(&filter_search), "text contains '.'");
+
+        let between = RowFilterParams::Between(FilterBetween {
+            left_value: "2024-01-01".to_string(),
+            right_value: "2024-12-31".to_string(),
+        });
+        let filter_between = filter(
+            "date",
+            ColumnDisplayType::Date,
+            RowFilterType::NotBetween,
+            Some(between),
+        );
+        assert_eq!(
+            row_filter_display_text(&filter_between),
+            "date is not between '2024-01-01' and '2024-12-31'"
+        );
+
+        let set = RowFilterParams::SetMembership(FilterSetMembership {
+            values: vec!["1".to_string(), "2".to_string()],
+            inclusive: true,
+        });
+        let filter_set = filter(
+            "x",
+            ColumnDisplayType::Integer,
+            RowFilterType::SetMembership,
+            Some(set),
+        );
+        assert_eq!(row_filter_display_text(&filter_set), "x is one of 1, 2");
+
+        let filter_null = filter("x", ColumnDisplayType::Integer, RowFilterType::IsNull, None);
+        assert_eq!(row_filter_display_text(&filter_null), "x is missing");
+
+        // Missing parameters
+        let filter_compare = filter(
+            "x",
+            ColumnDisplayType::Integer,
+            RowFilterType::Compare,
+            None,
+        );
+        assert_eq!(row_filter_display_text(&filter_compare), "x compare");
+    }
+}
@@ -0,0 +1,201 @@
+//
+// row_filter_tree.rs
+//
+// Copyright (C) 2025 by Posit Software, PBC
+//
+//
+
+use amalthea::comm::data_explorer_comm::RowFilter;
+use amalthea::comm::data_explorer_comm::RowFilterCondition;
+use amalthea::comm::data_explorer_comm::RowFilterGroup;
+use serde_json::json;
+
+/// A row filter or a group of row filters. Nodes are combined from left to
+/// right with the condition of the right-hand side node.
+#[derive(Debug)]
+pub enum RowFilterNode<'a> {
+    Filter {
+        /// The position of the filter in the list of row filters
+        index: usize,
+        filter: &'a RowFilter,
+    },
+    Group {
+        group: &'a RowFilterGroup,
+        children: Vec<RowFilterNode<'a>>,
+    },
+}
+
+impl RowFilterNode<'_> {
+    pub fn condition(&self) -> &RowFilterCondition {
+        match self {
+            RowFilterNode::Filter { filter, .. } => &filter.condition,
+            RowFilterNode::Group { group, .. } => &group.condition,
+        }
+    }
+
+    /// Representation of the node passed to `.ps.filter_rows()`, where
+    /// filters are referred to by their 1-based index
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            RowFilterNode::Filter { index, filter } => json!({
+                "index": index + 1,
+                "condition": filter.condition,
+            }),
+            RowFilterNode::Group { group, children } => json!({
+                "condition": group.condition,
+                "children": children.iter().map(|node| node.to_json()).collect::<Vec<_>>(),
+            }),
+        }
+    }
+}
+
+/// Arranges the row filters in a tree according to their groups. Filters
+/// keep their relative order, and a group takes the position of its first
+/// filter. Filters that refer to an unknown group are kept at the level of
+/// the last known group.
+pub fn row_filter_tree<'a>(
+    filters: &'a [RowFilter],
+    groups: &'a [RowFilterGroup],
+) -> Vec<RowFilterNode<'a>> {
+    let mut nodes: Vec<RowFilterNode<'a>> = Vec::new();
+
+    for (index, filter) in filters.iter().enumerate() {
+        let mut children = &mut nodes;
+
+        for group in group_path(filter, groups) {
+            let position = children.iter().position(|node| match node {
+                RowFilterNode::Group { group: other, .. } => other.group_id == group.group_id,
+                RowFilterNode::Filter { .. } => false,
+            });
+
+            let position = match position {
+                Some(position) => position,
+                None => {
+                    children.push(RowFilterNode::Group {
+                        group,
+                        children: vec![],
+                    });
+                    children.len() - 1
+                },
+            };
+
+            children = match &mut children[position] {
+                RowFilterNode::Group { children, .. } => children,
+                RowFilterNode::Filter { .. } => unreachable!(),
+            };
+        }
+
+        children.push(RowFilterNode::Filter { index, filter });
+    }
+
+    nodes
+}
+
+/// The groups enclosing a filter, from the outermost to the innermost
+fn group_path<'a>(filter: &RowFilter, groups: &'a [RowFilterGroup]) -> Vec<&'a RowFilterGroup> {
+    let mut path: Vec<&'a RowFilterGroup> = vec![];
+    let mut group_id = filter.group_id.as_ref();
+
+    while let Some(id) = group_id {
+        let Some(group) = groups.iter().find(|group| &group.group_id == id) else {
+            break;
+        };
+
+        // Guard against cycles in the parent links
+        if path.iter().any(|other| other.group_id == group.group_id) {
+            break;
+        }
+
+        path.push(group);
+        group_id = group.parent_id.as_ref();
+    }
+
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use amalthea::comm::data_explorer_comm::ColumnDisplayType;
+    use amalthea::comm::data_explorer_comm::ColumnSchema;
+    use amalthea::comm::data_explorer_comm::RowFilterType;
+
+    use super::*;
+
+    fn filter(group_id: Option<&str>) -> RowFilter {
+        RowFilter {
+            filter_id: "filter".to_string(),
+            filter_type: RowFilterType::IsNull,
+            column_schema: ColumnSchema {
+                column_name: "x".to_string(),
+                column_label: None,
+                column_index: 0,
+                type_name: "dbl".to_string(),
+                type_display: ColumnDisplayType::Floating,
+                description: None,
+                children: None,
+                precision: None,
+                scale: None,
+                timezone: None,
+                type_size: None,
+            },
+            condition: RowFilterCondition::And,
+            is_valid: None,
+            error_message: None,
+            params: None,
+            group_id: group_id.map(String::from),
+            display_text: None,
+        }
+    }
+
+    fn group(group_id: &str, parent_id: Option<&str>) -> RowFilterGroup {
+        RowFilterGroup {
+            group_id: group_id.to_string(),
+            condition: RowFilterCondition::Or,
+            parent_id: parent_id.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_row_filter_tree_nested() {
+        let filters = vec![
+            filter(None),
+            filter(Some("inner")),
+            filter(Some("outer")),
+            filter(Some("inner")),
+            filter(None),
+        ];
+        let groups = vec![group("outer", None), group("inner", Some("outer"))];
+
+        // `f1 (((f2 f4) f3)) f5`
+        let tree = row_filter_tree(&filters, &groups);
+        assert_eq!(
+            tree.iter().map(|node| node.to_json()).collect::<Vec<_>>(),
+            vec![
+                json!({"index": 1, "condition": "and"}),
+                json!({"condition": "or", "children": [
+                    {"condition": "or", "children": [
+                        {"index": 2, "condition": "and"},
+                        {"index": 4, "condition": "and"},
+                    ]},
+                    {"index": 3, "condition": "and"},
+                ]}),
+                json!({"index": 5, "condition": "and"}),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_row_filter_tree_unknown_groups() {
+        let filters = vec![filter(Some("unknown")), filter(Some("cycle"))];
+        let groups = vec![group("cycle", Some("cycle"))];
+
+        let tree = row_filter_tree(&filters, &groups);
+        assert_eq!(tree.len(), 2);
+        assert!(matches!(tree[0], RowFilterNode::Filter { index: 0, .. }));
+        assert!(matches!(
+            &tree[1],
+            RowFilterNode::Group { children, .. } if children.len() == 1
+        ));
+    }
+}
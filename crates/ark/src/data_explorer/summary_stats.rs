@@ -15,6 +15,7 @@ use amalthea::comm::data_explorer_comm::FormatOptions;
 use amalthea::comm::data_explorer_comm::SummaryStatsBoolean;
 use amalthea::comm::data_explorer_comm::SummaryStatsDate;
 use amalthea::comm::data_explorer_comm::SummaryStatsDatetime;
+use amalthea::comm::data_explorer_comm::SummaryStatsList;
 use amalthea::comm::data_explorer_comm::SummaryStatsNumber;
 use amalthea::comm::data_explorer_comm::SummaryStatsString;
 use anyhow::anyhow;
@@ -28,6 +29,8 @@ use libr::SEXP;
 use stdext::unwrap;
 
 use crate::data_explorer::format::format_string;
+use crate::data_explorer::utils::is_geometry_column;
+use crate::data_explorer::utils::is_list_column;
 use crate::modules::ARK_ENVS;
 
 pub fn summary_stats(
@@ -55,6 +58,10 @@ pub fn summary_stats(
         },
         ColumnDisplayType::Date => stats.date_stats = Some(summary_stats_date(column)?),
         ColumnDisplayType::Datetime => stats.datetime_stats = Some(summary_stats_datetime(column)?),
+        _ if is_list_column(column) => stats.list_stats = Some(summary_stats_list(column)?),
+        // Geometries have no summary stats, but they shouldn't fail the
+        // profile of the column either
+        _ if is_geometry_column(column) => {},
         _ => {
             return Err(anyhow::anyhow!("Unkown type"));
         },
@@ -177,6 +184,21 @@ fn summary_stats_datetime(column: SEXP) -> anyhow::Result<SummaryStatsDatetime>
     })
 }
 
+fn summary_stats_list(column: SEXP) -> anyhow::Result<SummaryStatsList> {
+    let r_stats: HashMap<String, RObject> =
+        call_summary_fn("summary_stats_list", column)?.try_into()?;
+
+    Ok(SummaryStatsList {
+        num_empty: get_stat::<i32, RObject>(&r_stats, "num_empty")? as i64,
+        min_length: get_stat::<i32, RObject>(&r_stats, "min_length")
+            .ok()
+            .map(|x| x as i64),
+        max_length: get_stat::<i32, RObject>(&r_stats, "max_length")
+            .ok()
+            .map(|x| x as i64),
+    })
+}
+
 fn call_summary_fn(function: &str, column: SEXP) -> anyhow::Result<RObject> {
     Ok(RFunction::from(function)
         .add(column)
@@ -192,6 +214,7 @@ fn empty_column_summary_stats() -> data_explorer_comm::ColumnSummaryStats {
         date_stats: None,
         datetime_stats: None,
         other_stats: None,
+        list_stats: None,
     }
 }
 
@@ -224,7 +247,9 @@ mod tests {
             small_num_digits: 4,
             max_integral_digits: 7,
             thousands_sep: Some(",".to_string()),
+            decimal_sep: None,
             max_value_length: 100,
+            datetime_options: None,
         }
     }
 
@@ -379,6 +404,31 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_list_summary() {
+        crate::r_task(|| {
+            let column = harp::parse_eval_global("list(1:3, NULL, character(), 'a', NA)").unwrap();
+            let stats =
+                summary_stats(column.sexp, ColumnDisplayType::Unknown, &default_options()).unwrap();
+            let expected = SummaryStatsList {
+                num_empty: 1,
+                min_length: Some(0),
+                max_length: Some(3),
+            };
+            assert_eq!(stats.list_stats, Some(expected));
+
+            let column = harp::parse_eval_global("list(NULL, NULL)").unwrap();
+            let stats =
+                summary_stats(column.sexp, ColumnDisplayType::Unknown, &default_options()).unwrap();
+            let expected = SummaryStatsList {
+                num_empty: 0,
+                min_length: None,
+                max_length: None,
+            };
+            assert_eq!(stats.list_stats, Some(expected));
+        })
+    }
+
     #[test]
     fn test_haven_labelled() {
         crate::r_task(|| {
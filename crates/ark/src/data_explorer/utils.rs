@@ -6,6 +6,7 @@ use harp::utils::r_inherits;
 use harp::utils::r_is_object;
 use harp::utils::r_is_s4;
 use harp::utils::r_typeof;
+use harp::vector::IntegerVector;
 use libr::*;
 
 use crate::modules::ARK_ENVS;
@@ -36,11 +37,18 @@ pub fn tbl_subset_with_view_indices(
 fn tbl_subset(x: SEXP, i: Option<Vec<i64>>, j: Option<Vec<i64>>) -> anyhow::Result<RObject> {
     let mut call = RFunction::from(".ps.table_subset");
     call.param("x", x);
+    // Indices are bounded by the dimensions of the table so they fit in R integers
     if let Some(i) = i {
-        call.param("i", &i);
+        call.param(
+            "i",
+            IntegerVector::from_iter(i.into_iter().map(|i| i as i32)),
+        );
     }
     if let Some(j) = j {
-        call.param("j", &j);
+        call.param(
+            "j",
+            IntegerVector::from_iter(j.into_iter().map(|j| j as i32)),
+        );
     }
 
     Ok(call.call_in(ARK_ENVS.positron_ns)?)
@@ -115,3 +123,19 @@ pub fn display_type(x: SEXP) -> ColumnDisplayType {
         _ => return ColumnDisplayType::Unknown,
     }
 }
+
+/// Whether a column holds the geometries of a spatial data frame, i.e. an
+/// `sfc` column of an sf object. These are lists of geometries internally.
+pub fn is_geometry_column(x: SEXP) -> bool {
+    r_typeof(x) == VECSXP && r_inherits(x, "sfc")
+}
+
+/// Whether a column is a list of elements, such as a list-column of a tibble
+/// or a vctrs `list_of`. Data frames and records such as `POSIXlt` are also
+/// lists internally but are not considered list columns.
+pub fn is_list_column(x: SEXP) -> bool {
+    if r_typeof(x) != VECSXP {
+        return false;
+    }
+    !r_is_object(x) || r_inherits(x, "list")
+}
@@ -0,0 +1,692 @@
+//
+// view_state.rs
+//
+// Copyright (C) 2025 by Posit Software, PBC
+//
+//
+
+use std::cmp;
+
+use amalthea::comm::data_explorer_comm::ArraySelection;
+use amalthea::comm::data_explorer_comm::ColumnDisplayType;
+use amalthea::comm::data_explorer_comm::ColumnSchema;
+use amalthea::comm::data_explorer_comm::ColumnSortKey;
+use amalthea::comm::data_explorer_comm::FilterComparisonOp;
+use amalthea::comm::data_explorer_comm::FilterResult;
+use amalthea::comm::data_explorer_comm::RowFilter;
+use amalthea::comm::data_explorer_comm::RowFilterGroup;
+use amalthea::comm::data_explorer_comm::RowFilterParams;
+use amalthea::comm::data_explorer_comm::RowFilterType;
+use anyhow::anyhow;
+
+use crate::data_explorer::backend::TableBackend;
+use crate::data_explorer::row_filter_display::row_filter_display_text;
+use crate::number_format::NumberFormat;
+
+/// The sorts and filters applied to the viewed table, and the row indices
+/// they produce. Computations on the data are delegated to a
+/// `TableBackend`, so the state transitions can be tested without R.
+///
+/// Row indices are 1-based.
+#[derive(Debug, Default)]
+pub(crate) struct ViewState {
+    /// The number of rows of the table, before filtering
+    num_rows: i32,
+
+    /// The current set of sort keys.
+    sort_keys: Vec<ColumnSortKey>,
+
+    /// The current set of row filters.
+    row_filters: Vec<RowFilter>,
+
+    /// The groups referenced by the row filters.
+    row_filter_groups: Vec<RowFilterGroup>,
+
+    /// The format of the numbers displayed when the filters were set. The
+    /// values of filters on numeric columns are written with its separators.
+    filter_number_format: Option<NumberFormat>,
+
+    /// The set of sorted row indices, if any sorts are applied. This always
+    /// includes all row indices.
+    sorted_indices: Option<Vec<i32>>,
+
+    /// The set of filtered row indices, if any filters are applied. These are
+    /// the row indices that remain after applying all row filters. They're
+    /// sorted in ascending order.
+    filtered_indices: Option<Vec<i32>>,
+
+    /// When any sorts or filters are applied, the set of sorted and filtered
+    /// row indices. This is the set of row indices that are displayed in the
+    /// data viewer.
+    view_indices: Option<Vec<i32>>,
+}
+
+impl ViewState {
+    pub fn new(num_rows: i32) -> Self {
+        Self {
+            num_rows,
+            ..Default::default()
+        }
+    }
+
+    pub fn sort_keys(&self) -> &[ColumnSortKey] {
+        &self.sort_keys
+    }
+
+    pub fn row_filters(&self) -> &[RowFilter] {
+        &self.row_filters
+    }
+
+    pub fn row_filter_groups(&self) -> &[RowFilterGroup] {
+        &self.row_filter_groups
+    }
+
+    pub fn filtered_indices(&self) -> &Option<Vec<i32>> {
+        &self.filtered_indices
+    }
+
+    pub fn view_indices(&self) -> &Option<Vec<i32>> {
+        &self.view_indices
+    }
+
+    /// The number of rows that pass the filters
+    pub fn num_filtered_rows(&self) -> i64 {
+        match self.filtered_indices {
+            Some(ref indices) => indices.len() as i64,
+            None => self.num_rows as i64,
+        }
+    }
+
+    /// The number of rows that are displayed. Same as the number of filtered
+    /// rows since sorts only reorder rows.
+    pub fn num_view_rows(&self) -> i64 {
+        match self.view_indices {
+            Some(ref indices) => indices.len() as i64,
+            None => self.num_rows as i64,
+        }
+    }
+
+    pub fn set_sort_keys(
+        &mut self,
+        backend: &dyn TableBackend,
+        sort_keys: Vec<ColumnSortKey>,
+    ) -> anyhow::Result<()> {
+        // If there are no sort keys, clear the precomputed sorted indices;
+        // otherwise, sort the rows and save the result
+        self.sorted_indices = match sort_keys.len() {
+            0 => None,
+            _ => Some(backend.sort_rows(&sort_keys)?),
+        };
+        self.sort_keys = sort_keys;
+
+        // Apply sorts to the filtered indices to create view indices
+        self.apply_sorts_and_filters();
+        Ok(())
+    }
+
+    /// Sets the number format used to parse the values of filters on numeric
+    /// columns, see `NumberFormat::normalize_separators()`. Takes effect the
+    /// next time the filters are computed.
+    pub fn set_filter_number_format(&mut self, format: Option<NumberFormat>) {
+        self.filter_number_format = format;
+    }
+
+    pub fn set_row_filters(
+        &mut self,
+        backend: &dyn TableBackend,
+        filters: Vec<RowFilter>,
+        groups: Vec<RowFilterGroup>,
+    ) -> anyhow::Result<FilterResult> {
+        self.row_filters = filters;
+        self.row_filter_groups = groups;
+
+        for filter in self.row_filters.iter_mut() {
+            filter.display_text = Some(row_filter_display_text(filter));
+        }
+
+        // Compute the filtered indices
+        let had_errors = self.row_filters_compute(backend)?;
+
+        // Apply sorts to the filtered indices to create view indices
+        self.apply_sorts_and_filters();
+
+        Ok(FilterResult {
+            selected_num_rows: self.num_filtered_rows(),
+            had_errors,
+        })
+    }
+
+    /// Recomputes the view after the values of the table have changed but
+    /// its columns haven't
+    pub fn update_data(&mut self, backend: &dyn TableBackend, num_rows: i32) -> anyhow::Result<()> {
+        self.num_rows = num_rows;
+
+        // If there are sort keys, we need to sort the rows again to reflect
+        // the new data.
+        if self.sort_keys.len() > 0 {
+            self.sorted_indices = Some(backend.sort_rows(&self.sort_keys)?);
+        }
+
+        // Recompute and apply filters and sorts.
+        self.row_filters_compute(backend)?;
+        self.apply_sorts_and_filters();
+        Ok(())
+    }
+
+    /// Recomputes the view after the columns of the table have changed. Sorts
+    /// are cleared and filters are validated against the new columns.
+    pub fn update_schema(
+        &mut self,
+        backend: &dyn TableBackend,
+        columns: &[ColumnSchema],
+        num_rows: i32,
+    ) -> anyhow::Result<()> {
+        self.num_rows = num_rows;
+
+        // Update row filters to reflect the new schema
+        self.row_filters_update(columns)?;
+
+        // Clear precomputed indices
+        self.sorted_indices = None;
+        self.filtered_indices = None;
+        self.view_indices = None;
+
+        // Clear active sort keys
+        self.sort_keys.clear();
+
+        // Recompute and apply filters and sorts.
+        self.row_filters_compute(backend)?;
+        self.apply_sorts_and_filters();
+        Ok(())
+    }
+
+    // Marks row_filters as invalid if the column no longer exists
+    // If the column still exists, update the column schema of the filter
+    // and check if they are still valid.
+    fn row_filters_update(&mut self, columns: &[ColumnSchema]) -> anyhow::Result<()> {
+        for rf in self.row_filters.iter_mut() {
+            let new_schema = columns
+                .iter()
+                .find(|c| c.column_name == rf.column_schema.column_name);
+
+            match new_schema {
+                Some(schema) => {
+                    rf.column_schema = schema.clone();
+                    rf.display_text = Some(row_filter_display_text(rf));
+                    let is_valid = is_valid_filter(rf)?;
+                    rf.is_valid = Some(is_valid);
+                    rf.error_message = if is_valid {
+                        None
+                    } else {
+                        Some("Unsupported column type for filter".to_string())
+                    };
+                },
+                None => {
+                    // the column no longer exists
+                    rf.is_valid = Some(false);
+                    rf.error_message = Some("Column was removed".to_string());
+                },
+            };
+        }
+        Ok(())
+    }
+
+    // Compute filtered indices out of the current `row_filters`.
+    //
+    // Implicitly updates the `row_filters` with validity status and error messages, if they
+    // fail during the computation. Returns whether any filter failed.
+    fn row_filters_compute(&mut self, backend: &dyn TableBackend) -> anyhow::Result<Option<bool>> {
+        if self.row_filters.len() == 0 {
+            self.filtered_indices = None;
+            return Ok(None);
+        }
+
+        // The filters keep the values as entered by the user, the backend
+        // gets the values of numeric filters in the syntax of R
+        let filters: Vec<RowFilter> = match &self.filter_number_format {
+            Some(format) => self
+                .row_filters
+                .iter()
+                .map(|filter| normalize_filter_values(filter, format))
+                .collect(),
+            None => self.row_filters.clone(),
+        };
+
+        let rows = backend.filter_rows(&filters, &self.row_filter_groups)?;
+        self.filtered_indices = Some(rows.indices);
+
+        // this is called for the side-effect of updating the row_filters with validty status and
+        // error messages
+        Ok(Some(self.apply_filter_errors(rows.errors)))
+    }
+
+    // Handle errors that occured in the filters
+    //
+    // This function mutates the `row_filters` attribute to include error messages and validity status.
+    fn apply_filter_errors(&mut self, errors: Vec<Option<String>>) -> bool {
+        let mut had_errors = false;
+        for (filter, error) in self.row_filters.iter_mut().zip(errors) {
+            match error {
+                None => {
+                    filter.is_valid = Some(true);
+                },
+                Some(error) => {
+                    filter.is_valid = Some(false);
+                    filter.error_message = Some(error);
+                    had_errors = true;
+                },
+            }
+        }
+        had_errors
+    }
+
+    /// Sort the filtered indices according to the sort keys, storing the
+    /// result in view_indices.
+    fn apply_sorts_and_filters(&mut self) {
+        self.view_indices = match (&self.filtered_indices, &self.sorted_indices) {
+            // If there are no filters or sorts, we don't need any view indices
+            (None, None) => None,
+
+            // If there are filters but no sorts, the view indices are the
+            // filtered indices
+            (Some(filtered_indices), None) => Some(filtered_indices.clone()),
+
+            // If there are sorts but no filters, the view indices are the
+            // sorted indices
+            (None, Some(sorted_indices)) => Some(sorted_indices.clone()),
+
+            // There are both sorts and filters, so we need to combine them.
+            // `sorted_indices` contains all the indices; `filtered_indices`
+            // contains the subset of indices that pass the filters, in
+            // ascending order.
+            //
+            // Derive the set of indices that pass the filters and are sorted
+            // according to the sort keys. We can use a binary search here
+            // for performance because `filtered_indices` is already sorted
+            // in ascending order.
+            (Some(filtered_indices), Some(sorted_indices)) => Some(
+                sorted_indices
+                    .iter()
+                    .filter(|index| filtered_indices.binary_search(index).is_ok())
+                    .copied()
+                    .collect(),
+            ),
+        };
+    }
+
+    // Given an ArraySelection, this materializes the indices that will actually be used.
+    // Also does some sanity checks to avoid OOB access.
+    pub fn row_selection_indices(&self, selection: ArraySelection) -> Vec<i64> {
+        let num_view_rows = self.num_view_rows();
+
+        // Returns the indices that will be collected
+        match selection {
+            ArraySelection::SelectRange(range) => {
+                let lower_bound = cmp::min(range.first_index, num_view_rows);
+                let upper_bound = cmp::min(range.last_index + 1, num_view_rows);
+                (lower_bound..upper_bound).collect()
+            },
+            ArraySelection::SelectIndices(indices) => indices
+                .indices
+                .into_iter()
+                .filter(|v| *v < num_view_rows)
+                .collect(),
+        }
+    }
+}
+
+// Rewrites the values of a filter on a numeric column with the separators of
+// R, e.g. `1,5` becomes `1.5` with a `,` decimal separator. Filters on other
+// columns are returned as is.
+fn normalize_filter_values(filter: &RowFilter, format: &NumberFormat) -> RowFilter {
+    let mut filter = filter.clone();
+
+    if !matches!(
+        filter.column_schema.type_display,
+        ColumnDisplayType::Integer | ColumnDisplayType::Floating | ColumnDisplayType::Decimal
+    ) {
+        return filter;
+    }
+
+    match &mut filter.params {
+        Some(RowFilterParams::Comparison(comparison)) => {
+            comparison.value = format.normalize_separators(&comparison.value);
+        },
+        Some(RowFilterParams::Between(between)) => {
+            between.left_value = format.normalize_separators(&between.left_value);
+            between.right_value = format.normalize_separators(&between.right_value);
+        },
+        Some(RowFilterParams::SetMembership(set)) => {
+            for value in set.values.iter_mut() {
+                *value = format.normalize_separators(value);
+            }
+        },
+        Some(RowFilterParams::TextSearch(_)) | None => {},
+    }
+
+    filter
+}
+
+// Check if a filter is valid by looking at it's type and the type of the column its applied to.
+// Uses logic similar to python side: https://github.com/posit-dev/positron/blob/aafe313a261fd133b9f4a9f87c92bb10dc9966ad/extensions/positron-python/python_files/positron/positron_ipykernel/data_explorer.py#L743-L744
+pub(crate) fn is_valid_filter(filter: &RowFilter) -> anyhow::Result<bool> {
+    let display_type = &filter.column_schema.type_display;
+    let filter_type = &filter.filter_type;
+
+    let is_compare_supported = |x: &ColumnDisplayType| match x {
+        ColumnDisplayType::Integer |
+        ColumnDisplayType::Floating |
+        ColumnDisplayType::Decimal |
+        ColumnDisplayType::Date |
+        ColumnDisplayType::Datetime |
+        ColumnDisplayType::Time => true,
+        _ => false,
+    };
+
+    match filter_type {
+        RowFilterType::IsEmpty | RowFilterType::NotEmpty | RowFilterType::Search => {
+            // String-only filter types
+            Ok(display_type == &ColumnDisplayType::String)
+        },
+        RowFilterType::Compare => {
+            if let Some(params) = &filter.params {
+                match params {
+                    RowFilterParams::Comparison(comparison) => match comparison.op {
+                        FilterComparisonOp::Eq | FilterComparisonOp::NotEq => Ok(true),
+                        _ => Ok(is_compare_supported(display_type)),
+                    },
+                    _ => Err(anyhow!("Missing compare filter params")),
+                }
+            } else {
+                Err(anyhow!("Missing compare_params for filter"))
+            }
+        },
+        RowFilterType::Between | RowFilterType::NotBetween => {
+            Ok(is_compare_supported(display_type))
+        },
+        RowFilterType::IsTrue | RowFilterType::IsFalse => {
+            Ok(display_type == &ColumnDisplayType::Boolean)
+        },
+        RowFilterType::IsNull | RowFilterType::NotNull | RowFilterType::SetMembership => {
+            // Filters always supported
+            Ok(true)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amalthea::comm::data_explorer_comm::ArraySelection;
+    use amalthea::comm::data_explorer_comm::ColumnDisplayType;
+    use amalthea::comm::data_explorer_comm::ColumnSchema;
+    use amalthea::comm::data_explorer_comm::ColumnSortKey;
+    use amalthea::comm::data_explorer_comm::DataSelectionIndices;
+    use amalthea::comm::data_explorer_comm::DataSelectionRange;
+    use amalthea::comm::data_explorer_comm::FilterComparison;
+    use amalthea::comm::data_explorer_comm::FilterComparisonOp;
+    use amalthea::comm::data_explorer_comm::FilterResult;
+    use amalthea::comm::data_explorer_comm::RowFilter;
+    use amalthea::comm::data_explorer_comm::RowFilterCondition;
+    use amalthea::comm::data_explorer_comm::RowFilterGroup;
+    use amalthea::comm::data_explorer_comm::RowFilterParams;
+    use amalthea::comm::data_explorer_comm::RowFilterType;
+
+    use crate::data_explorer::mock_backend::MockColumn;
+    use crate::data_explorer::mock_backend::MockTable;
+    use crate::data_explorer::view_state::ViewState;
+    use crate::number_format::NumberFormat;
+
+    fn column(name: &str, index: i64, type_display: ColumnDisplayType) -> ColumnSchema {
+        ColumnSchema {
+            column_name: name.to_string(),
+            column_label: None,
+            column_index: index,
+            type_name: "".to_string(),
+            type_display,
+            description: None,
+            children: None,
+            precision: None,
+            scale: None,
+            timezone: None,
+            type_size: None,
+        }
+    }
+
+    fn table() -> (MockTable, Vec<ColumnSchema>) {
+        let table = MockTable::new(vec![
+            MockColumn::Numbers(vec![Some(3.0), None, Some(1.0), Some(2.0), Some(1.0)]),
+            MockColumn::Strings(vec![
+                Some(String::from("b")),
+                Some(String::from("a")),
+                None,
+                Some(String::from("")),
+                Some(String::from("a")),
+            ]),
+        ]);
+        let columns = vec![
+            column("x", 0, ColumnDisplayType::Floating),
+            column("y", 1, ColumnDisplayType::String),
+        ];
+        (table, columns)
+    }
+
+    fn filter(
+        column: &ColumnSchema,
+        filter_type: RowFilterType,
+        params: Option<RowFilterParams>,
+    ) -> RowFilter {
+        RowFilter {
+            filter_id: "filter".to_string(),
+            filter_type,
+            column_schema: column.clone(),
+            condition: RowFilterCondition::And,
+            is_valid: None,
+            error_message: None,
+            params,
+            group_id: None,
+            display_text: None,
+        }
+    }
+
+    fn compare(op: FilterComparisonOp, value: &str) -> Option<RowFilterParams> {
+        Some(RowFilterParams::Comparison(FilterComparison {
+            op,
+            value: value.to_string(),
+        }))
+    }
+
+    fn sort_key(column_index: i64, ascending: bool) -> ColumnSortKey {
+        ColumnSortKey {
+            column_index,
+            ascending,
+            natural: None,
+        }
+    }
+
+    #[test]
+    fn test_view_state_sorts_and_filters() {
+        let (table, columns) = table();
+        let mut view = ViewState::new(table.num_rows());
+        assert_eq!(view.view_indices(), &None);
+        assert_eq!(view.num_view_rows(), 5);
+
+        // Stable, and missing values are last in both directions
+        view.set_sort_keys(&table, vec![sort_key(0, true)]).unwrap();
+        assert_eq!(view.view_indices(), &Some(vec![3, 5, 4, 1, 2]));
+        view.set_sort_keys(&table, vec![sort_key(0, false)])
+            .unwrap();
+        assert_eq!(view.view_indices(), &Some(vec![1, 4, 3, 5, 2]));
+
+        // Ties are broken by the next key
+        view.set_sort_keys(&table, vec![sort_key(0, true), sort_key(1, false)])
+            .unwrap();
+        assert_eq!(view.view_indices(), &Some(vec![5, 3, 4, 1, 2]));
+
+        // Filters keep the sort order
+        let filters = vec![filter(
+            &columns[0],
+            RowFilterType::Compare,
+            compare(FilterComparisonOp::LtEq, "2"),
+        )];
+        let result = view.set_row_filters(&table, filters, vec![]).unwrap();
+        assert_eq!(result, FilterResult {
+            selected_num_rows: 3,
+            had_errors: Some(false),
+        });
+        assert_eq!(view.filtered_indices(), &Some(vec![3, 4, 5]));
+        assert_eq!(view.view_indices(), &Some(vec![5, 3, 4]));
+        assert_eq!(view.row_filters()[0].is_valid, Some(true));
+        assert_eq!(
+            view.row_filters()[0].display_text.as_deref(),
+            Some("x <= 2")
+        );
+
+        // Clearing the sorts keeps the filters
+        view.set_sort_keys(&table, vec![]).unwrap();
+        assert_eq!(view.view_indices(), &Some(vec![3, 4, 5]));
+
+        // Clearing the filters shows all rows in their original order
+        let result = view.set_row_filters(&table, vec![], vec![]).unwrap();
+        assert_eq!(result.selected_num_rows, 5);
+        assert_eq!(result.had_errors, None);
+        assert_eq!(view.view_indices(), &None);
+    }
+
+    #[test]
+    fn test_view_state_filter_groups_and_errors() {
+        let (table, columns) = table();
+        let mut view = ViewState::new(table.num_rows());
+
+        // `(is_null(x) OR y == "a") AND is_true(x)`, where the last filter
+        // fails and is skipped
+        let mut is_null = filter(&columns[0], RowFilterType::IsNull, None);
+        is_null.group_id = Some(String::from("group"));
+        let mut is_a = filter(
+            &columns[1],
+            RowFilterType::Compare,
+            compare(FilterComparisonOp::Eq, "a"),
+        );
+        is_a.group_id = Some(String::from("group"));
+        is_a.condition = RowFilterCondition::Or;
+        let unsupported = filter(&columns[0], RowFilterType::IsTrue, None);
+
+        let groups = vec![RowFilterGroup {
+            group_id: String::from("group"),
+            condition: RowFilterCondition::And,
+            parent_id: None,
+        }];
+
+        let result = view
+            .set_row_filters(&table, vec![is_null, unsupported, is_a], groups)
+            .unwrap();
+        assert_eq!(result.selected_num_rows, 2);
+        assert_eq!(result.had_errors, Some(true));
+        assert_eq!(view.filtered_indices(), &Some(vec![2, 5]));
+
+        let filters = view.row_filters();
+        assert_eq!(filters[0].is_valid, Some(true));
+        assert_eq!(filters[1].is_valid, Some(false));
+        assert!(filters[1].error_message.is_some());
+        assert_eq!(filters[2].is_valid, Some(true));
+    }
+
+    #[test]
+    fn test_view_state_updates() {
+        let (table, columns) = table();
+        let mut view = ViewState::new(table.num_rows());
+
+        view.set_sort_keys(&table, vec![sort_key(0, true)]).unwrap();
+        let filters = vec![
+            filter(&columns[0], RowFilterType::NotNull, None),
+            filter(&columns[1], RowFilterType::NotEmpty, None),
+        ];
+        view.set_row_filters(&table, filters, vec![]).unwrap();
+        assert_eq!(view.view_indices(), &Some(vec![3, 5, 1]));
+
+        // New values: sorts and filters are applied again
+        let table = MockTable::new(vec![
+            MockColumn::Numbers(vec![Some(2.0), Some(1.0)]),
+            MockColumn::Strings(vec![Some(String::from("a")), Some(String::from("b"))]),
+        ]);
+        view.update_data(&table, table.num_rows()).unwrap();
+        assert_eq!(view.sort_keys().len(), 1);
+        assert_eq!(view.view_indices(), &Some(vec![2, 1]));
+
+        // The second column is removed: its filter becomes invalid and is
+        // no longer applied, and the sorts are cleared
+        let table = MockTable::new(vec![MockColumn::Numbers(vec![None, Some(1.0)])]);
+        view.update_schema(&table, &columns[..1], table.num_rows())
+            .unwrap();
+        assert!(view.sort_keys().is_empty());
+        assert_eq!(view.view_indices(), &Some(vec![2]));
+
+        let filters = view.row_filters();
+        assert_eq!(filters[0].is_valid, Some(true));
+        assert_eq!(filters[1].is_valid, Some(false));
+        assert_eq!(
+            filters[1].error_message.as_deref(),
+            Some("Column was removed")
+        );
+    }
+
+    #[test]
+    fn test_view_state_filter_number_format() {
+        let (table, columns) = table();
+        let mut view = ViewState::new(table.num_rows());
+
+        let filters = vec![filter(
+            &columns[0],
+            RowFilterType::Compare,
+            compare(FilterComparisonOp::Lt, "1,5"),
+        )];
+
+        // `1,5` is not a number with the default separators
+        let result = view.set_row_filters(&table, filters.clone(), vec![]);
+        assert_eq!(result.unwrap().had_errors, Some(true));
+
+        view.set_filter_number_format(Some(NumberFormat {
+            thousands_sep: Some(".".to_string()),
+            decimal_sep: Some(",".to_string()),
+            ..NumberFormat::variables()
+        }));
+        let result = view.set_row_filters(&table, filters, vec![]).unwrap();
+        assert_eq!(result, FilterResult {
+            selected_num_rows: 2,
+            had_errors: Some(false),
+        });
+        assert_eq!(view.filtered_indices(), &Some(vec![3, 5]));
+
+        // The filter keeps the value entered by the user
+        let Some(RowFilterParams::Comparison(comparison)) = &view.row_filters()[0].params else {
+            panic!("Expected comparison params");
+        };
+        assert_eq!(comparison.value, "1,5");
+    }
+
+    #[test]
+    fn test_view_state_row_selection() {
+        let (table, columns) = table();
+        let mut view = ViewState::new(table.num_rows());
+
+        let selection = |first_index, last_index| {
+            ArraySelection::SelectRange(DataSelectionRange {
+                first_index,
+                last_index,
+            })
+        };
+        assert_eq!(view.row_selection_indices(selection(1, 2)), vec![1, 2]);
+        assert_eq!(view.row_selection_indices(selection(3, 10)), vec![3, 4]);
+
+        // Selections refer to the filtered rows
+        let filters = vec![filter(&columns[0], RowFilterType::NotNull, None)];
+        view.set_row_filters(&table, filters, vec![]).unwrap();
+        assert_eq!(view.row_selection_indices(selection(0, 10)), vec![
+            0, 1, 2, 3
+        ]);
+
+        let selection = ArraySelection::SelectIndices(DataSelectionIndices {
+            indices: vec![0, 3, 4, 10],
+        });
+        assert_eq!(view.row_selection_indices(selection), vec![0, 3]);
+    }
+}
@@ -15,17 +15,26 @@ use libr::Rf_lcons;
 use libr::SEXP;
 use log::info;
 use log::warn;
+use serde_json::Value;
 use stdext::unwrap;
 
 use crate::interface::RMain;
 
+/// Same as the default of R's `nwarnings` option
+const MAX_RECORDED_WARNINGS: usize = 50;
+
 #[harp::register]
-unsafe extern "C-unwind" fn ps_record_error(evalue: SEXP, traceback: SEXP) -> anyhow::Result<SEXP> {
+unsafe extern "C-unwind" fn ps_record_error(
+    evalue: SEXP,
+    traceback: SEXP,
+    metadata: SEXP,
+) -> anyhow::Result<SEXP> {
     let main = RMain::get_mut();
 
     // Convert to `RObject` for access to `try_from()` / `try_into()` methods.
     let evalue = RObject::new(evalue);
     let traceback = RObject::new(traceback);
+    let metadata = RObject::new(metadata);
 
     let evalue: String = unwrap!(evalue.try_into(), Err(error) => {
         warn!("Can't convert `evalue` to a Rust string: {}.", error);
@@ -37,9 +46,46 @@ unsafe extern "C-unwind" fn ps_record_error(evalue: SEXP, traceback: SEXP) -> an
         Vec::<String>::new()
     });
 
+    let metadata = match Value::try_from(metadata) {
+        Ok(Value::Null) => None,
+        Ok(metadata) => Some(metadata),
+        Err(error) => {
+            warn!("Can't convert error `metadata` to JSON: {}.", error);
+            None
+        },
+    };
+
     main.error_occurred = true;
     main.error_message = evalue;
     main.error_traceback = traceback;
+    main.error_metadata = metadata;
+
+    Ok(R_NilValue)
+}
+
+#[harp::register]
+unsafe extern "C-unwind" fn ps_record_warning(warning: SEXP) -> anyhow::Result<SEXP> {
+    let main = RMain::get_mut();
+
+    // Warnings emitted by tasks or while idle don't belong to the request
+    if !main.is_recording_warnings() {
+        return Ok(R_NilValue);
+    }
+
+    // Like R, only keep the first warnings of a long series
+    if main.warnings.len() >= MAX_RECORDED_WARNINGS {
+        return Ok(R_NilValue);
+    }
+
+    // Never throw from the warning handler
+    let warning = Value::try_from(RObject::new(warning))
+        .map_err(anyhow::Error::from)
+        .and_then(|warning| Ok(serde_json::from_value(warning)?));
+
+    match warning {
+        Ok(warning) => main.warnings.push(warning),
+        Err(error) => warn!("Can't record warning: {error}."),
+    }
 
     Ok(R_NilValue)
 }
@@ -0,0 +1,151 @@
+//
+// events.rs
+//
+// Copyright (C) 2022 Posit Software, PBC. All rights reserved.
+//
+//
+
+use once_cell::sync::Lazy;
+use stdext::event::Event;
+
+/// Events emitted by the R thread
+///
+/// Components such as the data explorer or the variables pane subscribe to
+/// the events they are interested in with `Event::subscribe()`. Listeners
+/// run synchronously on the R thread, they should return quickly and
+/// typically just notify their own thread through a channel.
+#[derive(Default)]
+pub struct Events {
+    /// R is prompting for console input
+    pub console_prompt: Event<()>,
+
+    /// A top-level input is about to be evaluated
+    pub before_eval: Event<()>,
+
+    /// A top-level input has been evaluated, right before the execution
+    /// reply is sent
+    pub after_eval: Event<()>,
+
+    /// A garbage collection has run
+    pub gc: Event<()>,
+
+    /// A package namespace has been loaded. Carries the package name.
+    pub package_load: Event<String>,
+
+    /// A package namespace has been unloaded. Carries the package name.
+    pub package_unload: Event<String>,
+
+    /// A package has been attached to the search path. Carries the package
+    /// name.
+    pub package_attach: Event<String>,
+
+    /// A package is about to be detached from the search path. Carries the
+    /// package name.
+    pub package_detach: Event<String>,
+}
+
+pub static EVENTS: Lazy<Events> = Lazy::new(|| Events::default());
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use harp::exec::RFunction;
+    use harp::exec::RFunctionExt;
+
+    use crate::events::EVENTS;
+    use crate::modules::ARK_ENVS;
+    use crate::r_task;
+
+    #[test]
+    fn test_package_hooks_emit_events() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let record = |name: &'static str| {
+            let received = received.clone();
+            move |pkg: &String| received.lock().unwrap().push(format!("{name}:{pkg}"))
+        };
+        let _unload = EVENTS.package_unload.subscribe(record("unload"));
+        let _attach = EVENTS.package_attach.subscribe(record("attach"));
+        let _detach = EVENTS.package_detach.subscribe(record("detach"));
+
+        r_task(|| {
+            for hook in ["ark_attach_hook", "ark_detach_hook", "ark_onunload_hook"] {
+                RFunction::new("", hook)
+                    .add("foo")
+                    .add("path")
+                    .call_in(ARK_ENVS.positron_ns)
+                    .unwrap();
+            }
+        });
+
+        assert_eq!(*received.lock().unwrap(), vec![
+            String::from("attach:foo"),
+            String::from("detach:foo"),
+            String::from("unload:foo"),
+        ]);
+    }
+
+    #[test]
+    fn test_gc_hook_emits_event() {
+        let count = Arc::new(Mutex::new(0));
+
+        let _gc = EVENTS.gc.subscribe({
+            let count = count.clone();
+            move |_| *count.lock().unwrap() += 1
+        });
+
+        r_task(|| {
+            RFunction::new("", "register_gc_hook")
+                .call_in(ARK_ENVS.positron_ns)
+                .unwrap();
+            RFunction::new("base", "gc").call().unwrap();
+        });
+
+        assert!(*count.lock().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_get_hook_injects_package_hooks() {
+        r_task(|| {
+            let code = "local({
+                inherits_first <- function(hook_name, class) {
+                    hooks <- ark_getHook(hook_name)
+                    inherits(hooks[[1]], class)
+                }
+                c(
+                    inherits_first(packageEvent('foo', 'onLoad'), 'ark_onload_hook'),
+                    inherits_first(packageEvent('foo', 'onUnload'), 'ark_onunload_hook'),
+                    inherits_first(packageEvent('foo', 'attach'), 'ark_attach_hook'),
+                    inherits_first(packageEvent('foo', 'detach'), 'ark_detach_hook')
+                )
+            })";
+            let injected: Vec<bool> = harp::parse_eval0(code, ARK_ENVS.positron_ns)
+                .unwrap()
+                .try_into()
+                .unwrap();
+            assert_eq!(injected, vec![true, true, true, true]);
+
+            // Our hook is injected only once, before the user hooks
+            let code = "local({
+                name <- packageEvent('foo', 'onUnload')
+                on.exit(setHook(name, NULL, 'replace'))
+                setHook(name, function(pkg, path) NULL)
+                setHook(name, ark_onunload_hook)
+                hooks <- ark_getHook(name)
+                c(length(hooks), sum(vapply(hooks, inherits, TRUE, 'ark_onunload_hook')))
+            })";
+            let counts: Vec<i32> = harp::parse_eval0(code, ARK_ENVS.positron_ns)
+                .unwrap()
+                .try_into()
+                .unwrap();
+            assert_eq!(counts, vec![2, 1]);
+
+            // Other hooks are left alone
+            let hooks = harp::parse_eval0("length(ark_getHook('foo'))", ARK_ENVS.positron_ns);
+            let hooks: i32 = hooks.unwrap().try_into().unwrap();
+            assert_eq!(hooks, 0);
+        })
+    }
+}
@@ -32,6 +32,7 @@ struct DummyArkFrontendOptions {
     session_mode: SessionMode,
     default_repos: DefaultRepos,
     startup_file: Option<String>,
+    positron_profile: Option<String>,
 }
 
 /// Wrapper around `DummyArkFrontend` that uses `SessionMode::Notebook`
@@ -112,6 +113,7 @@ impl DummyArkFrontend {
                 Some(registration_file),
                 r_args,
                 options.startup_file,
+                options.positron_profile,
                 options.session_mode,
                 false,
                 options.default_repos,
@@ -272,6 +274,7 @@ impl Default for DummyArkFrontendOptions {
             session_mode: SessionMode::Console,
             default_repos: DefaultRepos::Auto,
             startup_file: None,
+            positron_profile: None,
         }
     }
 }
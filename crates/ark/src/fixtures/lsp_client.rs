@@ -0,0 +1,387 @@
+//
+// fixtures/lsp_client.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use std::collections::HashMap;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::time::Duration;
+
+use crossbeam::channel::Receiver;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+use serde_json::Value;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::io::DuplexStream;
+use tokio::io::ReadHalf;
+use tokio::io::WriteHalf;
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::oneshot;
+use tower_lsp::lsp_types::notification::DidOpenTextDocument;
+use tower_lsp::lsp_types::notification::Initialized;
+use tower_lsp::lsp_types::notification::Notification;
+use tower_lsp::lsp_types::request::Completion;
+use tower_lsp::lsp_types::request::FoldingRangeRequest;
+use tower_lsp::lsp_types::request::Initialize;
+use tower_lsp::lsp_types::request::Request;
+use tower_lsp::lsp_types::ClientCapabilities;
+use tower_lsp::lsp_types::CompletionItem;
+use tower_lsp::lsp_types::CompletionParams;
+use tower_lsp::lsp_types::CompletionResponse;
+use tower_lsp::lsp_types::DidOpenTextDocumentParams;
+use tower_lsp::lsp_types::FoldingRange;
+use tower_lsp::lsp_types::FoldingRangeParams;
+use tower_lsp::lsp_types::InitializeParams;
+use tower_lsp::lsp_types::InitializedParams;
+use tower_lsp::lsp_types::Position;
+use tower_lsp::lsp_types::TextDocumentIdentifier;
+use tower_lsp::lsp_types::TextDocumentItem;
+use tower_lsp::lsp_types::TextDocumentPositionParams;
+use url::Url;
+
+use crate::lsp::backend;
+
+/// How long to wait for the server before failing the test
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+// The LSP main loop communicates with some global state (e.g. the channel
+// of the auxiliary loop), so only one server can run at a time
+static LSP_CLIENT_LOCK: Mutex<()> = Mutex::new(());
+
+type PendingRequests = Arc<Mutex<HashMap<i64, oneshot::Sender<Value>>>>;
+
+/// An in-memory LSP client for testing
+///
+/// Starts the LSP in-process on a background thread and connects to it with
+/// in-memory streams, so that tests can exercise the handlers through the
+/// same JSON-RPC path as a real frontend. Requests made by the server, like
+/// `workspace/configuration`, are answered with default values.
+///
+/// ```ignore
+/// let client = LspClient::new();
+/// let uri = client.open_document("test.R", "f <- function() {\n  1\n}");
+/// let ranges = client.folding_ranges(&uri);
+/// ```
+///
+/// Handlers may call into R, so don't use the client within an `r_task()`.
+pub struct LspClient {
+    runtime: Runtime,
+    outgoing: Option<UnboundedSender<Value>>,
+    pending: PendingRequests,
+    notifications: Receiver<Value>,
+    next_id: AtomicI64,
+    server: Option<std::thread::JoinHandle<()>>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl LspClient {
+    /// Start a server and initialize it with default client capabilities
+    pub fn new() -> Self {
+        Self::with_capabilities(ClientCapabilities::default())
+    }
+
+    /// Start a server and initialize it with `capabilities`
+    pub fn with_capabilities(capabilities: ClientCapabilities) -> Self {
+        // Don't cascade failures from other tests
+        let lock = LSP_CLIENT_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let (client_stream, server_stream) = tokio::io::duplex(64 * 1024);
+
+        // The server runs on its own thread, like in `start_lsp()`
+        let handle = runtime.handle().clone();
+        let server = std::thread::Builder::new()
+            .name(String::from("ark-lsp-test-server"))
+            .spawn(move || {
+                let (read, write) = tokio::io::split(server_stream);
+                handle.block_on(backend::serve(read, write, |_| {}));
+            })
+            .unwrap();
+
+        let (read, write) = tokio::io::split(client_stream);
+        let (outgoing_tx, outgoing_rx) = tokio::sync::mpsc::unbounded_channel::<Value>();
+        let (notifications_tx, notifications_rx) = crossbeam::channel::unbounded::<Value>();
+        let pending = PendingRequests::default();
+
+        runtime.spawn(write_messages(write, outgoing_rx));
+        runtime.spawn(read_messages(
+            read,
+            outgoing_tx.clone(),
+            notifications_tx,
+            pending.clone(),
+        ));
+
+        let client = Self {
+            runtime,
+            outgoing: Some(outgoing_tx),
+            pending,
+            notifications: notifications_rx,
+            next_id: AtomicI64::new(1),
+            server: Some(server),
+            _lock: lock,
+        };
+
+        client.request::<Initialize>(InitializeParams {
+            capabilities,
+            ..Default::default()
+        });
+        client.notify::<Initialized>(InitializedParams {});
+
+        client
+    }
+
+    /// Send a request and block until the server responds
+    ///
+    /// Panics if the server responds with an error.
+    pub fn request<R: Request>(&self, params: R::Params) -> R::Result {
+        self.custom_request(R::METHOD, params)
+    }
+
+    /// Send a request for a method that isn't part of the protocol, e.g.
+    /// `positron/textDocument/statementRange`
+    pub fn custom_request<P, T>(&self, method: &str, params: P) -> T
+    where
+        P: Serialize,
+        T: DeserializeOwned,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        let (response_tx, response_rx) = oneshot::channel::<Value>();
+        self.pending.lock().unwrap().insert(id, response_tx);
+
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        }));
+
+        let response = self
+            .runtime
+            .block_on(async { tokio::time::timeout(TIMEOUT, response_rx).await })
+            .unwrap_or_else(|_| panic!("Timed out waiting for a response to `{method}`"))
+            .unwrap();
+
+        if let Some(error) = response.get("error") {
+            panic!("Request `{method}` failed: {error}");
+        }
+
+        let result = response.get("result").cloned().unwrap_or(Value::Null);
+        serde_json::from_value(result).unwrap()
+    }
+
+    /// Send a notification
+    pub fn notify<N: Notification>(&self, params: N::Params) {
+        self.send(json!({
+            "jsonrpc": "2.0",
+            "method": N::METHOD,
+            "params": params,
+        }));
+    }
+
+    /// Wait for the next notification of type `N` sent by the server,
+    /// skipping other notifications like log messages
+    pub fn recv_notification<N: Notification>(&self) -> N::Params {
+        loop {
+            let notification = self
+                .notifications
+                .recv_timeout(TIMEOUT)
+                .unwrap_or_else(|_| panic!("Timed out waiting for `{}`", N::METHOD));
+
+            let method = notification
+                .get("method")
+                .and_then(|method| method.as_str());
+            if method == Some(N::METHOD) {
+                let params = notification.get("params").cloned().unwrap_or(Value::Null);
+                return serde_json::from_value(params).unwrap();
+            }
+        }
+    }
+
+    /// Open a document named `name` and return its URI
+    pub fn open_document(&self, name: &str, text: &str) -> Url {
+        let uri = Url::parse(&format!("file:///{name}")).unwrap();
+
+        self.notify::<DidOpenTextDocument>(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem {
+                uri: uri.clone(),
+                language_id: String::from("r"),
+                version: 0,
+                text: String::from(text),
+            },
+        });
+
+        uri
+    }
+
+    pub fn folding_ranges(&self, uri: &Url) -> Vec<FoldingRange> {
+        self.request::<FoldingRangeRequest>(FoldingRangeParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .unwrap_or_default()
+    }
+
+    pub fn completions(&self, uri: &Url, position: Position) -> Vec<CompletionItem> {
+        let response = self.request::<Completion>(CompletionParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier::new(uri.clone()),
+                position,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        });
+
+        match response {
+            Some(CompletionResponse::Array(items)) => items,
+            Some(CompletionResponse::List(list)) => list.items,
+            None => vec![],
+        }
+    }
+
+    fn send(&self, message: Value) {
+        self.outgoing
+            .as_ref()
+            .unwrap()
+            .send(message)
+            .expect("Can't send message to the LSP");
+    }
+}
+
+impl Drop for LspClient {
+    fn drop(&mut self) {
+        // Closing the outgoing channel closes the connection, which shuts
+        // down the server. Wait for it before dropping the runtime.
+        self.outgoing.take();
+
+        if let Some(server) = self.server.take() {
+            let _ = server.join();
+        }
+    }
+}
+
+async fn write_messages(
+    mut write: WriteHalf<DuplexStream>,
+    mut outgoing_rx: tokio::sync::mpsc::UnboundedReceiver<Value>,
+) {
+    while let Some(message) = outgoing_rx.recv().await {
+        let body = message.to_string();
+        let message = format!("Content-Length: {}\r\n\r\n{body}", body.len());
+
+        if write.write_all(message.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+
+    let _ = write.shutdown().await;
+}
+
+async fn read_messages(
+    read: ReadHalf<DuplexStream>,
+    outgoing_tx: UnboundedSender<Value>,
+    notifications_tx: crossbeam::channel::Sender<Value>,
+    pending: PendingRequests,
+) {
+    let mut read = BufReader::new(read);
+
+    while let Ok(Some(message)) = read_message(&mut read).await {
+        let id = message.get("id").cloned();
+        let method = message.get("method").and_then(|method| method.as_str());
+
+        match (id, method) {
+            // Request from the server
+            (Some(id), Some(method)) => {
+                let result = default_response(method, &message);
+                let _ = outgoing_tx.send(json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "result": result,
+                }));
+            },
+
+            // Notification from the server
+            (None, Some(_)) => {
+                let _ = notifications_tx.send(message);
+            },
+
+            // Response to one of our requests
+            (Some(id), None) => {
+                let Some(id) = id.as_i64() else {
+                    continue;
+                };
+                if let Some(response_tx) = pending.lock().unwrap().remove(&id) {
+                    let _ = response_tx.send(message);
+                }
+            },
+
+            (None, None) => {},
+        }
+    }
+}
+
+/// Answer to requests from the server. Configuration requests get one
+/// `null` per item so the server falls back to its defaults.
+fn default_response(method: &str, message: &Value) -> Value {
+    if method != "workspace/configuration" {
+        return Value::Null;
+    }
+
+    let n_items = message
+        .pointer("/params/items")
+        .and_then(|items| items.as_array())
+        .map(|items| items.len())
+        .unwrap_or(0);
+
+    Value::Array(vec![Value::Null; n_items])
+}
+
+async fn read_message(
+    read: &mut BufReader<ReadHalf<DuplexStream>>,
+) -> anyhow::Result<Option<Value>> {
+    let mut length: Option<usize> = None;
+
+    loop {
+        let mut line = String::new();
+        if read.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            length = Some(value.trim().parse()?);
+        }
+    }
+
+    let Some(length) = length else {
+        return Err(anyhow::anyhow!("Missing `Content-Length` header"));
+    };
+
+    let mut body = vec![0; length];
+    read.read_exact(&mut body).await?;
+
+    Ok(Some(serde_json::from_slice(&body)?))
+}
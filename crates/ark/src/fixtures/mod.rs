@@ -1,5 +1,7 @@
 pub mod dummy_frontend;
+pub mod lsp_client;
 pub mod utils;
 
 pub use dummy_frontend::*;
+pub use lsp_client::*;
 pub use utils::*;
@@ -22,6 +22,7 @@ use std::sync::Arc;
 use std::sync::Mutex;
 use std::task::Poll;
 use std::time::Duration;
+use std::time::Instant;
 
 use amalthea::comm::base_comm::JsonRpcReply;
 use amalthea::comm::event::CommManagerEvent;
@@ -39,6 +40,8 @@ use amalthea::wire::execute_input::ExecuteInput;
 use amalthea::wire::execute_reply::ExecuteReply;
 use amalthea::wire::execute_request::ExecuteRequest;
 use amalthea::wire::execute_result::ExecuteResult;
+use amalthea::wire::execute_warnings::ExecuteWarning;
+use amalthea::wire::execute_warnings::ExecuteWarnings;
 use amalthea::wire::input_reply::InputReply;
 use amalthea::wire::input_request::InputRequest;
 use amalthea::wire::input_request::ShellInputRequest;
@@ -50,6 +53,8 @@ use amalthea::wire::stream::Stream;
 use amalthea::wire::stream::StreamOutput;
 use amalthea::Error;
 use anyhow::*;
+use base64::engine::general_purpose;
+use base64::Engine;
 use bus::Bus;
 use crossbeam::channel::bounded;
 use crossbeam::channel::Receiver;
@@ -73,16 +78,15 @@ use harp::object::RObject;
 use harp::r_symbol;
 use harp::routines::r_register_routines;
 use harp::session::r_traceback;
-use harp::utils::r_is_data_frame;
 use harp::utils::r_typeof;
 use harp::R_MAIN_THREAD_ID;
 use libr::R_BaseNamespace;
-use libr::R_GlobalEnv;
 use libr::R_ProcessEvents;
 use libr::R_RunPendingFinalizers;
 use libr::Rf_error;
 use libr::Rf_findVarInFrame;
 use libr::Rf_onintr;
+use libr::RAWSXP;
 use libr::SEXP;
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -92,14 +96,16 @@ use stdext::*;
 use tokio::sync::mpsc::UnboundedReceiver as AsyncUnboundedReceiver;
 use uuid::Uuid;
 
+use crate::console_hyperlinks::translate_hyperlinks;
 use crate::dap::dap::DapBackendEvent;
 use crate::dap::dap_r_main::FrameInfoId;
 use crate::dap::dap_r_main::RMainDap;
 use crate::dap::Dap;
 use crate::errors;
+use crate::events::EVENTS;
 use crate::help::message::HelpEvent;
 use crate::help::r_help::RHelp;
-use crate::lsp::events::EVENTS;
+use crate::lsp::main_loop::DidChangePackageParams;
 use crate::lsp::main_loop::DidCloseVirtualDocumentParams;
 use crate::lsp::main_loop::DidOpenVirtualDocumentParams;
 use crate::lsp::main_loop::Event;
@@ -118,11 +124,14 @@ use crate::r_task::RTaskStatus;
 use crate::repos::apply_default_repos;
 use crate::repos::DefaultRepos;
 use crate::request::debug_request_command;
+use crate::request::ExecuteCodeReply;
 use crate::request::KernelRequest;
 use crate::request::RRequest;
+use crate::shutdown;
 use crate::signals::initialize_signal_handlers;
 use crate::signals::interrupts_pending;
 use crate::signals::set_interrupts_pending;
+use crate::source_file;
 use crate::srcref::ark_uri;
 use crate::srcref::ns_populate_srcref;
 use crate::srcref::resource_loaded_namespaces;
@@ -135,6 +144,10 @@ use crate::ui::UiCommSender;
 pub static CAPTURE_CONSOLE_OUTPUT: AtomicBool = AtomicBool::new(false);
 static RE_DEBUG_PROMPT: Lazy<Regex> = Lazy::new(|| Regex::new(r"Browse\[\d+\]").unwrap());
 
+/// Maximum number of steps taken through dispatch frames after a single step
+/// in. Generous since stepping through R6's `new()` takes a step per expression.
+const MAX_DEBUG_DISPATCH_STEPS: usize = 1000;
+
 /// An enum representing the different modes in which the R session can run.
 #[derive(PartialEq, Clone, Copy)]
 pub enum SessionMode {
@@ -148,6 +161,34 @@ pub enum SessionMode {
     Background,
 }
 
+/// How inputs containing multiple expressions are executed. Set with the
+/// `ark.execution_granularity` option, either `"block"` or `"expression"`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum ExecutionGranularity {
+    /// The input is sent to R line by line and the first error discards the
+    /// rest of the input.
+    Block,
+
+    /// The input is split into its top-level expressions, which are evaluated
+    /// in turn. Errors are reported as they happen and evaluation continues
+    /// with the next expression. The request still fails if any expression
+    /// failed.
+    Expression,
+}
+
+impl ExecutionGranularity {
+    fn from_option() -> Self {
+        let granularity: Option<String> = harp::get_option("ark.execution_granularity")
+            .try_into()
+            .ok();
+
+        match granularity.as_deref() {
+            Some("expression") => Self::Expression,
+            _ => Self::Block,
+        }
+    }
+}
+
 // --- Globals ---
 // These values must be global in order for them to be accessible from R
 // callbacks, which do not have a facility for passing or returning context.
@@ -207,6 +248,7 @@ pub struct RMain {
 
     /// Channel to send and receive tasks from `RTask`s
     tasks_interrupt_rx: Receiver<RTask>,
+    tasks_background_rx: Receiver<RTask>,
     tasks_idle_rx: Receiver<RTask>,
     pending_futures: HashMap<Uuid, (BoxFuture<'static, ()>, RTaskStartInfo)>,
 
@@ -218,6 +260,18 @@ pub struct RMain {
     pub error_occurred: bool,
     pub error_message: String, // `evalue` in the Jupyter protocol
     pub error_traceback: Vec<String>,
+    /// Classes and fields of the condition, sent as metadata of the IOPub
+    /// error message. See `condition_metadata()` in `errors.R`.
+    pub error_metadata: Option<serde_json::Value>,
+
+    /// Warnings emitted while executing the active request. Sent on IOPub
+    /// once the request is complete, and flagged in the metadata of the
+    /// execute reply.
+    pub warnings: Vec<ExecuteWarning>,
+
+    /// Number of `RTask`s currently running on the R thread. Warnings emitted
+    /// by tasks, e.g. from the LSP, don't belong to the active request.
+    tasks_running: usize,
 
     /// Channel to communicate with the Help thread
     help_event_tx: Option<Sender<HelpEvent>>,
@@ -231,12 +285,30 @@ pub struct RMain {
     /// initially connects and after an LSP restart.
     lsp_virtual_documents: HashMap<String, String>,
 
+    /// Code evaluated at the console since the LSP was last notified of the
+    /// console inputs. Used by the LSP to rank completions.
+    lsp_console_code: Vec<String>,
+
     dap: RMainDap,
 
     pub positron_ns: Option<RObject>,
 
     pending_lines: Vec<String>,
 
+    /// Lines of the current expression sent to R so far. When the top-level
+    /// and continuation prompts are the same, e.g. with `options(prompt = "> ",
+    /// continue = "> ")`, this tells whether R is waiting for the rest of an
+    /// expression.
+    pending_expression: String,
+
+    /// Granularity of the input being sent to R line by line
+    execution_granularity: ExecutionGranularity,
+
+    /// Error of an intermediate expression of the active request, along with
+    /// its metadata. Only set with `ExecutionGranularity::Expression`, where
+    /// errors don't stop evaluation of the remaining expressions.
+    isolated_error: Option<(Exception, Option<serde_json::Value>)>,
+
     /// Banner output accumulated during startup, but set to `None` after we complete
     /// the initialization procedure and forward the banner on
     banner: Option<String>,
@@ -267,6 +339,18 @@ pub struct RMain {
     /// Ever increasing debug session index. Used to create URIs that are only
     /// valid for a single session.
     debug_session_index: u32,
+
+    /// Whether the last debugger command was a step in. Used to step through
+    /// S3, S4 and R6 dispatch frames until we land in the selected method.
+    debug_stepping_in: bool,
+
+    /// Number of consecutive steps taken through dispatch frames, to avoid
+    /// stepping through R6 internals forever if something goes wrong.
+    debug_dispatch_steps: usize,
+
+    /// A shutdown request received in a `browser()` prompt. It is handled at
+    /// the next top level prompt, once the debugger has been quit.
+    pending_shutdown: Option<RRequest>,
 }
 
 /// Represents the currently active execution request from the frontend. It
@@ -275,7 +359,9 @@ struct ActiveReadConsoleRequest {
     exec_count: u32,
     request: ExecuteRequest,
     originator: Originator,
-    reply_tx: Sender<amalthea::Result<ExecuteReply>>,
+    reply_tx: Sender<ExecuteCodeReply>,
+    /// When the request started executing, to report its duration
+    start: Instant,
 }
 
 /// Represents kernel metadata (available after the kernel has fully started)
@@ -337,6 +423,7 @@ impl RMain {
     pub(crate) fn start(
         r_args: Vec<String>,
         startup_file: Option<String>,
+        positron_profile: Option<String>,
         comm_manager_tx: Sender<CommManagerEvent>,
         r_request_rx: Receiver<RRequest>,
         stdin_request_tx: Sender<StdInRequest>,
@@ -359,10 +446,11 @@ impl RMain {
             };
         }
 
-        let (tasks_interrupt_rx, tasks_idle_rx) = r_task::take_receivers();
+        let (tasks_interrupt_rx, tasks_background_rx, tasks_idle_rx) = r_task::take_receivers();
 
         R_MAIN.set(UnsafeCell::new(RMain::new(
             tasks_interrupt_rx,
+            tasks_background_rx,
             tasks_idle_rx,
             comm_manager_tx,
             r_request_rx,
@@ -497,14 +585,17 @@ impl RMain {
             graphics_device_rx,
         );
 
-        // Now that R has started and libr and ark have fully initialized, run site and user
-        // level R profiles, in that order
+        // Now that R has started and libr and ark have fully initialized, run site, user,
+        // and Positron level R profiles, in that order
         if !ignore_site_r_profile {
             startup::source_site_r_profile(&r_home);
         }
         if !ignore_user_r_profile {
             startup::source_user_r_profile();
         }
+        if let Some(path) = &positron_profile {
+            startup::source_positron_r_profile(path);
+        }
 
         // Start the REPL. Does not return!
         crate::sys::interface::run_r();
@@ -582,6 +673,7 @@ impl RMain {
 
     pub fn new(
         tasks_interrupt_rx: Receiver<RTask>,
+        tasks_background_rx: Receiver<RTask>,
         tasks_idle_rx: Receiver<RTask>,
         comm_manager_tx: Sender<CommManagerEvent>,
         r_request_rx: Receiver<RRequest>,
@@ -606,17 +698,25 @@ impl RMain {
             error_occurred: false,
             error_message: String::new(),
             error_traceback: Vec::new(),
+            error_metadata: None,
+            warnings: Vec::new(),
+            tasks_running: 0,
             help_event_tx: None,
             help_port: None,
             lsp_events_tx: None,
             lsp_virtual_documents: HashMap::new(),
+            lsp_console_code: Vec::new(),
             dap: RMainDap::new(dap),
             tasks_interrupt_rx,
+            tasks_background_rx,
             tasks_idle_rx,
             pending_futures: HashMap::new(),
             session_mode,
             positron_ns: None,
             pending_lines: Vec::new(),
+            pending_expression: String::new(),
+            execution_granularity: ExecutionGranularity::Block,
+            isolated_error: None,
             banner: None,
             r_error_buffer: None,
             captured_output: String::new(),
@@ -624,6 +724,9 @@ impl RMain {
             debug_last_stack: vec![],
             debug_env: None,
             debug_session_index: 1,
+            debug_stepping_in: false,
+            debug_dispatch_steps: 0,
+            pending_shutdown: None,
         }
     }
 
@@ -648,6 +751,12 @@ impl RMain {
         R_INIT.get().is_some()
     }
 
+    /// Whether warnings emitted now belong to the active execute request. This
+    /// is not the case when idle or when running a task.
+    pub fn is_recording_warnings(&self) -> bool {
+        self.active_request.is_some() && self.tasks_running == 0
+    }
+
     /// Access a reference to the singleton instance of this struct
     ///
     /// SAFETY: Accesses must occur after `RMain::start()` initializes it.
@@ -702,16 +811,20 @@ impl RMain {
         // Reset the autoprint buffer
         self.autoprint_output = String::new();
 
+        // Requests to run a file are evaluated as the equivalent R call
+        let code = source_file::translate_source_request(&req.code).unwrap_or(req.code.clone());
+
         // Increment counter if we are storing this execution in history
         if req.store_history {
             self.execution_count = self.execution_count + 1;
+            self.lsp_console_code.push(code.clone());
         }
 
         // If the code is not to be executed silently, re-broadcast the
         // execution to all frontends
         if !req.silent {
             if let Err(err) = self.iopub_tx.send(IOPubMessage::ExecuteInput(ExecuteInput {
-                code: req.code.clone(),
+                code: code.clone(),
                 execution_count: self.execution_count,
             })) {
                 log::warn!(
@@ -723,7 +836,7 @@ impl RMain {
         }
 
         // Return the code to the R console to be evaluated and the corresponding exec count
-        (ConsoleInput::Input(req.code.clone()), self.execution_count)
+        (ConsoleInput::Input(code), self.execution_count)
     }
 
     /// Invoked by R to read console input from the user.
@@ -746,6 +859,11 @@ impl RMain {
         let info = self.prompt_info(prompt);
         log::trace!("R prompt: {}", info.input_prompt);
 
+        // R is done with the expression we were sending line by line
+        if !info.incomplete && !info.input_request {
+            self.pending_expression.clear();
+        }
+
         // Upon entering read-console, finalize any debug call text that we were capturing.
         // At this point, the user can either advance the debugger, causing us to capture
         // a new expression, or execute arbitrary code, where we will reuse a finalized
@@ -808,7 +926,13 @@ impl RMain {
         EVENTS.console_prompt.emit(());
 
         if info.browser {
-            self.start_debug();
+            if self.start_debug() {
+                // Step through dispatch frames without waiting for the user
+                return match Self::on_console_input(buf, buflen, String::from("s")) {
+                    Ok(()) => ConsoleResult::NewInput,
+                    Err(err) => ConsoleResult::Error(err),
+                };
+            }
         } else {
             if self.dap.is_debugging() {
                 self.stop_debug();
@@ -822,6 +946,7 @@ impl RMain {
         let stdin_reply_rx = self.stdin_reply_rx.clone();
         let kernel_request_rx = self.kernel_request_rx.clone();
         let tasks_interrupt_rx = self.tasks_interrupt_rx.clone();
+        let tasks_background_rx = self.tasks_background_rx.clone();
         let tasks_idle_rx = self.tasks_idle_rx.clone();
 
         // Process R's polled events regularly while waiting for console input.
@@ -834,6 +959,7 @@ impl RMain {
         let stdin_reply_index = select.recv(&stdin_reply_rx);
         let kernel_request_index = select.recv(&kernel_request_rx);
         let tasks_interrupt_index = select.recv(&tasks_interrupt_rx);
+        let tasks_background_index = select.recv(&tasks_background_rx);
         let polled_events_index = select.recv(&polled_events_rx);
 
         // Don't process idle tasks in browser prompts. We currently don't want
@@ -849,6 +975,11 @@ impl RMain {
         };
 
         loop {
+            // We're back in the event loop, so any interrupt is either handled
+            // or about to be. This stops the escalation of the interrupt
+            // watchdog. Also runs on each tick of `polled_events_rx`.
+            crate::control::acknowledge_interrupts();
+
             // If an interrupt was signaled and we are in a user
             // request prompt, e.g. `readline()`, we need to propagate
             // the interrupt to the R stack. This needs to happen before
@@ -872,6 +1003,13 @@ impl RMain {
             // to be handled in a blocking way to ensure subscribers are
             // notified before the next incoming message is processed.
 
+            // A shutdown request deferred until the debugger has been quit
+            if let Some(req) = self.pending_shutdown.take() {
+                if let Some(input) = self.handle_execute_request(req, &info, buf, buflen) {
+                    return input;
+                }
+            }
+
             // First handle execute requests outside of `select` to ensure they
             // have priority. `select` chooses at random.
             if let Ok(req) = r_request_rx.try_recv() {
@@ -914,9 +1052,20 @@ impl RMain {
                     self.handle_task_interrupt(task);
                 },
 
+                // A background task woke us up. `select` chooses at random so
+                // run any pending interactive tasks first.
+                i if i == tasks_background_index => {
+                    let task = oper.recv(&tasks_background_rx).unwrap();
+                    while let Ok(interactive_task) = tasks_interrupt_rx.try_recv() {
+                        self.handle_task_interrupt(interactive_task);
+                    }
+                    self.handle_task_interrupt(task);
+                },
+
                 // An idle task woke us up
                 i if Some(i) == tasks_idle_index => {
                     let task = oper.recv(&tasks_idle_rx).unwrap();
+                    task.record_queue_latency();
                     self.handle_task(task);
                 },
 
@@ -941,8 +1090,17 @@ impl RMain {
         let prompt_slice = unsafe { CStr::from_ptr(prompt_c) };
         let prompt = prompt_slice.to_string_lossy().into_owned();
 
-        let continuation_prompt: String = harp::get_option("continue").try_into().unwrap();
-        let matches_continuation = prompt == continuation_prompt;
+        let top_level_prompt = prompt_option("prompt", "> ");
+        let continuation_prompt = prompt_option("continue", "+ ");
+
+        // When the continuation prompt can't be told apart from the top-level
+        // prompt, check whether the lines sent so far form an incomplete
+        // expression
+        let matches_continuation = if prompt == continuation_prompt && prompt == top_level_prompt {
+            self.is_pending_expression_incomplete()
+        } else {
+            prompt == continuation_prompt
+        };
 
         // Detect browser prompt by matching the prompt string
         // https://github.com/posit-dev/positron/issues/4742.
@@ -976,6 +1134,17 @@ impl RMain {
         };
     }
 
+    fn is_pending_expression_incomplete(&self) -> bool {
+        if self.pending_expression.is_empty() {
+            return false;
+        }
+        let input = harp::ParseInput::Text(&self.pending_expression);
+        matches!(
+            harp::parse_status(&input),
+            Ok(harp::ParseResult::Incomplete)
+        )
+    }
+
     fn read_console_cleanup(&mut self) {
         // The debug environment is only valid while R is idle
         self.debug_env = None;
@@ -1055,6 +1224,8 @@ impl RMain {
             // so that the `parent` message is set correctly in any Jupyter messages)
             graphics_device::on_did_execute_request();
 
+            EVENTS.after_eval.emit(());
+
             // Let frontend know the last request is complete. This turns us
             // back to Idle.
             self.reply_execute_request(req, &info);
@@ -1086,12 +1257,28 @@ impl RMain {
                     request: exec_req,
                     originator,
                     reply_tx,
+                    start: Instant::now(),
                 });
+                self.warnings.clear();
+                self.isolated_error = None;
+
+                EVENTS.before_eval.emit(());
 
                 input
             },
 
-            RRequest::Shutdown(_) => ConsoleInput::EOF,
+            RRequest::Shutdown(restart, progress_tx) => {
+                if info.browser {
+                    // Quit the debugger first so that the frames being
+                    // debugged are unwound and their `on.exit()` handlers
+                    // run. We shut down at the next top level prompt.
+                    self.pending_shutdown = Some(RRequest::Shutdown(restart, progress_tx));
+                    ConsoleInput::Input(String::from("Q"))
+                } else {
+                    shutdown::run_shutdown_sequence(progress_tx.as_ref());
+                    ConsoleInput::EOF
+                }
+            },
 
             RRequest::DebugCommand(cmd) => {
                 // Just ignore command in case we left the debugging state already
@@ -1112,6 +1299,9 @@ impl RMain {
             ConsoleInput::Input(code) => {
                 // Handle commands for the debug interpreter
                 if self.dap.is_debugging() {
+                    self.debug_stepping_in = code == "s";
+                    self.debug_dispatch_steps = 0;
+
                     let continue_cmds = vec!["n", "f", "c", "cont"];
                     if continue_cmds.contains(&&code[..]) {
                         // We're stepping so we want to focus the next location we stop at
@@ -1189,9 +1379,19 @@ impl RMain {
         return ConsoleResult::Error(Error::InvalidInputRequest(message));
     }
 
-    fn start_debug(&mut self) {
+    /// Returns `true` when we stepped into an S3, S4 or R6 dispatch frame, in
+    /// which case the caller should step in again rather than stop there.
+    fn start_debug(&mut self) -> bool {
         match self.dap.stack_info() {
             Ok(stack) => {
+                if self.debug_stepping_in &&
+                    self.debug_dispatch_steps < MAX_DEBUG_DISPATCH_STEPS &&
+                    stack.first().is_some_and(|frame| frame.dispatch)
+                {
+                    self.debug_dispatch_steps += 1;
+                    return true;
+                }
+
                 if let Some(frame) = stack.first() {
                     if let Some(ref env) = frame.environment {
                         // This is reset on exit in the cleanup phase, see `r_read_console()`
@@ -1224,6 +1424,8 @@ impl RMain {
             },
             Err(err) => log::error!("ReadConsole: Can't get stack info: {err}"),
         };
+
+        false
     }
 
     fn stop_debug(&mut self) {
@@ -1370,6 +1572,8 @@ impl RMain {
     /// they are running, they should return very quickly. The log message helps
     /// monitor excessively long-running tasks.
     fn handle_task_interrupt(&mut self, mut task: RTask) {
+        task.record_queue_latency();
+
         if let Some(start_info) = task.start_info_mut() {
             // Log excessive waiting before starting task
             if start_info.start_time.elapsed() > std::time::Duration::from_millis(50) {
@@ -1405,6 +1609,18 @@ impl RMain {
         // to 0 to prevent `readline()` from blocking the task.
         let _interactive = harp::raii::RLocalInteractive::new(false);
 
+        if task.is_cancelled() {
+            return self.cancel_task(task);
+        }
+
+        self.tasks_running += 1;
+        let info = self.run_task(task);
+        self.tasks_running -= 1;
+
+        info
+    }
+
+    fn run_task(&mut self, task: RTask) -> Option<RTaskStartInfo> {
         match task {
             RTask::Sync(task) => {
                 // Immediately let caller know we have started so it can set up the
@@ -1429,6 +1645,7 @@ impl RMain {
                     id,
                     tasks_tx: task.tasks_tx.clone(),
                     start_info: task.start_info,
+                    token: task.token,
                 });
                 self.poll_task(Some(task.fut), waker)
             },
@@ -1437,6 +1654,31 @@ impl RMain {
         }
     }
 
+    /// Drops a cancelled task without running it any further
+    fn cancel_task(&mut self, task: RTask) -> Option<RTaskStartInfo> {
+        match task {
+            RTask::Sync(task) => {
+                log::trace!("Skipping cancelled task");
+
+                // Drop the closure before unblocking the caller as it may
+                // borrow from the caller's stack
+                drop(task.fun);
+                if let Some(ref status_tx) = task.status_tx {
+                    status_tx.send(RTaskStatus::Cancelled).unwrap();
+                }
+            },
+            RTask::Async(_) => {
+                log::trace!("Skipping cancelled async task");
+            },
+            RTask::Parked(waker) => {
+                log::trace!("Dropping cancelled async task {}", waker.id);
+                self.pending_futures.remove(&waker.id);
+            },
+        }
+
+        None
+    }
+
     fn poll_task(
         &mut self,
         fut: Option<BoxFuture<'static, ()>>,
@@ -1508,7 +1750,7 @@ impl RMain {
         self.ui_comm_tx.as_ref()
     }
 
-    fn get_mut_ui_comm_tx(&mut self) -> Option<&mut UiCommSender> {
+    pub(crate) fn get_mut_ui_comm_tx(&mut self) -> Option<&mut UiCommSender> {
         self.ui_comm_tx.as_mut()
     }
 
@@ -1546,11 +1788,19 @@ impl RMain {
 
     fn handle_pending_line(&mut self, buf: *mut c_uchar, buflen: c_int) -> Option<ConsoleResult> {
         if self.error_occurred {
-            // If an error has occurred, we've already sent a complete expression that resulted in
-            // an error. Flush the remaining lines and return to `read_console()`, who will handle
-            // that error.
-            self.pending_lines.clear();
-            return None;
+            if self.execution_granularity == ExecutionGranularity::Expression &&
+                !self.pending_lines.is_empty()
+            {
+                // Report the error of this expression and carry on with the
+                // next one
+                self.isolate_error();
+            } else {
+                // If an error has occurred, we've already sent a complete expression that resulted in
+                // an error. Flush the remaining lines and return to `read_console()`, who will handle
+                // that error.
+                self.pending_lines.clear();
+                return None;
+            }
         }
 
         let Some(input) = self.pending_lines.pop() else {
@@ -1558,6 +1808,9 @@ impl RMain {
             return None;
         };
 
+        self.pending_expression.push_str(&input);
+        self.pending_expression.push('\n');
+
         match Self::on_console_input(buf, buflen, input) {
             Ok(()) => Some(ConsoleResult::NewInput),
             Err(err) => Some(ConsoleResult::Error(err)),
@@ -1586,8 +1839,25 @@ impl RMain {
     }
 
     fn buffer_console_input(&mut self, input: &str) -> String {
+        // Debugger commands are always sent as is
+        self.execution_granularity = if self.active_request.is_some() && !self.dap.is_debugging() {
+            ExecutionGranularity::from_option()
+        } else {
+            ExecutionGranularity::Block
+        };
+
+        // Put each top-level expression on its own lines, so that R evaluates
+        // them separately even if they share a line, as in `1; stop(); 2`.
+        // Inputs with syntax errors are sent as is so that R reports them.
+        let input = match self.execution_granularity {
+            ExecutionGranularity::Expression => split_expressions(input)
+                .map(|exprs| exprs.join("\n"))
+                .unwrap_or_else(|| input.to_string()),
+            ExecutionGranularity::Block => input.to_string(),
+        };
+
         // Split into lines and reverse them to be able to `pop()` from the front
-        let mut lines: Vec<String> = lines(input).rev().map(String::from).collect();
+        let mut lines: Vec<String> = lines(&input).rev().map(String::from).collect();
 
         // SAFETY: There is always at least one line because:
         // - `lines("")` returns 1 element containing `""`
@@ -1598,6 +1868,9 @@ impl RMain {
         assert!(self.pending_lines.is_empty());
         self.pending_lines.append(&mut lines);
 
+        self.pending_expression.push_str(&first);
+        self.pending_expression.push('\n');
+
         first
     }
 
@@ -1662,16 +1935,35 @@ impl RMain {
     fn reply_execute_request(&mut self, req: ActiveReadConsoleRequest, prompt_info: &PromptInfo) {
         let prompt = &prompt_info.input_prompt;
 
-        let (reply, result) = if prompt_info.incomplete {
+        let (reply, result, metadata) = if prompt_info.incomplete {
             log::trace!("Got prompt {} signaling incomplete request", prompt);
-            (new_incomplete_reply(&req.request, req.exec_count), None)
+            (
+                new_incomplete_reply(&req.request, req.exec_count),
+                None,
+                None,
+            )
         } else if prompt_info.input_request {
             unreachable!();
         } else {
             log::trace!("Got R prompt '{}', completing execution", prompt);
 
-            self.make_execute_reply_error(req.exec_count)
-                .unwrap_or_else(|| self.make_execute_reply(req.exec_count))
+            let metadata = self.execute_reply_metadata(&req);
+            self.send_warnings();
+
+            let isolated_error = self.isolated_error.take();
+
+            let (reply, result) = self
+                .make_execute_reply_error(req.exec_count)
+                .unwrap_or_else(|| self.make_execute_reply(req.exec_count));
+
+            // The request fails if an intermediate expression failed, even
+            // though evaluation carried on
+            let reply = match (reply, isolated_error) {
+                (Ok(_), Some((exception, _))) => new_execute_reply_error(exception, req.exec_count),
+                (reply, _) => reply,
+            };
+
+            (reply, result, Some(metadata))
         };
 
         if let Some(result) = result {
@@ -1679,7 +1971,40 @@ impl RMain {
         }
 
         log::trace!("Sending `execute_reply`: {reply:?}");
-        req.reply_tx.send(reply).unwrap();
+        req.reply_tx.send((reply, metadata)).unwrap();
+    }
+
+    /// Metadata of the execute reply so that frontends can show timings and
+    /// error badges: the wall-clock duration of the request, whether warnings
+    /// were emitted, and the classes of the error condition, if any.
+    fn execute_reply_metadata(&mut self, req: &ActiveReadConsoleRequest) -> serde_json::Value {
+        let error_metadata = if self.error_occurred {
+            self.error_metadata.as_ref()
+        } else {
+            self.isolated_error
+                .as_ref()
+                .and_then(|(_, metadata)| metadata.as_ref())
+        };
+        let error_class = error_metadata.and_then(|metadata| metadata.get("class").cloned());
+
+        json!({
+            "duration_ms": req.start.elapsed().as_millis() as u64,
+            "warnings": !self.warnings.is_empty(),
+            "error_class": error_class,
+        })
+    }
+
+    /// Sends the warnings emitted by the request on IOPub in structured form,
+    /// in addition to R's own printing of warnings
+    fn send_warnings(&mut self) {
+        let warnings = std::mem::take(&mut self.warnings);
+        if warnings.is_empty() {
+            return;
+        }
+
+        self.iopub_tx
+            .send(IOPubMessage::ExecuteWarnings(ExecuteWarnings { warnings }))
+            .unwrap();
     }
 
     fn make_execute_reply_error(
@@ -1710,23 +2035,33 @@ impl RMain {
         // We don't fill out `ename` with anything meaningful because typically
         // R errors don't have names. We could consider using the condition class
         // here, which r-lib/tidyverse packages have been using more heavily.
-        let mut exception = if error_occurred {
-            Exception {
-                ename: String::from(""),
-                evalue: self.error_message.clone(),
-                traceback: self.error_traceback.clone(),
-            }
+        let error_metadata = self.error_metadata.take();
+
+        let exception = if error_occurred {
+            self.new_exception(self.error_message.clone(), self.error_traceback.clone())
         } else {
             // Call `base::traceback()` since we don't have a handled error
             // object carrying a backtrace. This won't be formatted as a
             // tree which is just as well since the recursive calls would
             // push a tree too far to the right.
             let traceback = r_traceback();
-            Exception {
-                ename: String::from(""),
-                evalue: err_buf.clone(),
-                traceback,
-            }
+            self.new_exception(err_buf.clone(), traceback)
+        };
+
+        let reply = new_execute_reply_error(exception.clone(), exec_count);
+        let result = IOPubMessage::ExecuteError(ExecuteError {
+            exception,
+            metadata: if error_occurred { error_metadata } else { None },
+        });
+
+        Some((reply, Some(result)))
+    }
+
+    fn new_exception(&self, evalue: String, traceback: Vec<String>) -> Exception {
+        let mut exception = Exception {
+            ename: String::from(""),
+            evalue,
+            traceback,
         };
 
         // Jupyter clients typically discard the `evalue` when a `traceback` is
@@ -1739,21 +2074,33 @@ impl RMain {
             exception.traceback.insert(0, exception.evalue.clone())
         }
 
-        let reply = new_execute_reply_error(exception.clone(), exec_count);
-        let result = IOPubMessage::ExecuteError(ExecuteError { exception });
+        exception
+    }
 
-        Some((reply, Some(result)))
+    /// Reports the error of an intermediate expression on IOPub and resets
+    /// the error state so that evaluation continues with the next expression.
+    /// The error is kept around so that the request still fails.
+    fn isolate_error(&mut self) {
+        self.error_occurred = false;
+
+        let exception =
+            self.new_exception(self.error_message.clone(), self.error_traceback.clone());
+        let metadata = self.error_metadata.take();
+
+        self.iopub_tx
+            .send(IOPubMessage::ExecuteError(ExecuteError {
+                exception: exception.clone(),
+                metadata: metadata.clone(),
+            }))
+            .unwrap();
+
+        self.isolated_error = Some((exception, metadata));
     }
 
     fn make_execute_reply(
         &mut self,
         exec_count: u32,
     ) -> (amalthea::Result<ExecuteReply>, Option<IOPubMessage>) {
-        // TODO: Implement rich printing of certain outputs.
-        // Will we need something similar to the RStudio model,
-        // where we implement custom print() methods? Or can
-        // we make the stub below behave sensibly even when
-        // streaming R output?
         let mut data = serde_json::Map::new();
 
         // The output generated by autoprint is emitted as an
@@ -1770,18 +2117,15 @@ impl RMain {
             data.insert("text/plain".to_string(), json!(autoprint));
         }
 
-        // Include HTML representation of data.frame
-        unsafe {
-            let value = Rf_findVarInFrame(R_GlobalEnv, r_symbol!(".Last.value"));
-            if r_is_data_frame(value) {
-                match to_html(value) {
-                    Ok(html) => data.insert("text/html".to_string(), json!(html)),
-                    Err(err) => {
-                        log::error!("{:?}", err);
-                        None
-                    },
-                };
-            }
+        // Include the rich representations of the value, e.g. HTML for data
+        // frames. The printed output takes precedence for `text/plain`.
+        match mime_bundle(self.session_mode == SessionMode::Notebook) {
+            Ok(bundle) => {
+                for (mime, value) in bundle {
+                    data.entry(mime).or_insert(value);
+                }
+            },
+            Err(err) => log::error!("Can't render the value of the last expression: {err:?}"),
         }
 
         let reply = new_execute_reply(exec_count);
@@ -1834,13 +2178,19 @@ impl RMain {
         // before all of the IOPub messages have been processed by the frontend.
         std::thread::sleep(std::time::Duration::from_millis(100));
 
+        let metadata = input_request_metadata();
+        let password = metadata
+            .as_ref()
+            .is_some_and(|metadata| metadata["kind"] == "password");
+
         unwrap!(
             self.stdin_request_tx
             .send(StdInRequest::Input(ShellInputRequest {
                 originator,
                 request: InputRequest {
                     prompt,
-                    password: false,
+                    password,
+                    metadata,
                 },
             })),
             Err(err) => panic!("Could not send input request: {}", err)
@@ -1876,6 +2226,9 @@ impl RMain {
         // match based fallback
         r_main.dap.handle_stdout(&content);
 
+        // Make hyperlinks, e.g. cli's `run:` and `help:` links, clickable
+        let content = translate_hyperlinks(&content).into_owned();
+
         let stream = if otype == 0 {
             Stream::Stdout
         } else {
@@ -1980,7 +2333,7 @@ impl RMain {
     fn polled_events(&mut self) {
         // Don't process tasks until R is fully initialized
         if !Self::is_initialized() {
-            if !self.tasks_interrupt_rx.is_empty() {
+            if !self.tasks_interrupt_rx.is_empty() || !self.tasks_background_rx.is_empty() {
                 log::trace!("Delaying execution of interrupt task as R isn't initialized yet");
             }
             return;
@@ -1994,9 +2347,14 @@ impl RMain {
         }
 
         // Coalesce up to three concurrent tasks in case the R event loop is
-        // slowed down
+        // slowed down. Interactive tasks take precedence over background ones.
         for _ in 0..3 {
-            if let Ok(task) = self.tasks_interrupt_rx.try_recv() {
+            let task = self
+                .tasks_interrupt_rx
+                .try_recv()
+                .or_else(|_| self.tasks_background_rx.try_recv());
+
+            if let Ok(task) = task {
                 self.handle_task_interrupt(task);
             } else {
                 break;
@@ -2097,13 +2455,25 @@ impl RMain {
 
     fn refresh_lsp(&mut self) {
         match console_inputs() {
-            Ok(inputs) => {
+            Ok(mut inputs) => {
+                inputs.console_code = std::mem::take(&mut self.lsp_console_code);
                 self.send_lsp_notification(KernelNotification::DidChangeConsoleInputs(inputs));
             },
             Err(err) => log::error!("Can't retrieve console inputs: {err:?}"),
         }
     }
 
+    /// Refreshes the console scopes of the LSP without waiting for the next
+    /// prompt, e.g. after a package was attached by a long-running script
+    fn refresh_lsp_scopes(&mut self) {
+        match console_inputs() {
+            Ok(inputs) => {
+                self.send_lsp_notification(KernelNotification::DidChangeConsoleInputs(inputs))
+            },
+            Err(err) => log::error!("Can't retrieve console inputs: {err:?}"),
+        }
+    }
+
     fn notify_lsp_of_known_virtual_documents(&mut self) {
         // Clone the whole HashMap since we need to own the uri/contents to send them
         // over anyways. We don't want to clear the map in case the LSP restarts later on
@@ -2224,6 +2594,44 @@ impl RMain {
     }
 }
 
+/// The value of the `prompt` or `continue` option. R only allows strings
+/// but fall back to R's default just in case.
+fn prompt_option(name: &str, default: &str) -> String {
+    harp::get_option(name)
+        .try_into()
+        .unwrap_or_else(|_| String::from(default))
+}
+
+/// Splits `code` into the text of its top-level expressions using their
+/// srcrefs. Comments and whitespace between expressions are dropped. Returns
+/// `None` if the code can't be parsed.
+fn split_expressions(code: &str) -> Option<Vec<String>> {
+    let parsed = harp::parse_exprs_with_spans(code).ok()?;
+    let lines: Vec<&str> = lines(code).collect();
+
+    let mut exprs = Vec::with_capacity(parsed.spans.len());
+
+    for span in parsed.spans {
+        let first = span.line.start as usize;
+        let last = span.line.end.checked_sub(1)? as usize;
+        let start = span.column_byte.start as usize;
+        let end = span.column_byte.end as usize;
+
+        let expr = if first == last {
+            lines.get(first)?.get(start..end)?.to_string()
+        } else {
+            let mut expr = vec![lines.get(first)?.get(start..)?];
+            expr.extend(lines.get(first + 1..last)?);
+            expr.push(lines.get(last)?.get(..end)?);
+            expr.join("\n")
+        };
+
+        exprs.push(expr);
+    }
+
+    Some(exprs)
+}
+
 /// Report an incomplete request to the frontend
 fn new_incomplete_reply(req: &ExecuteRequest, exec_count: u32) -> amalthea::Result<ExecuteReply> {
     let error = Exception {
@@ -2246,17 +2654,54 @@ fn new_execute_reply_error(error: Exception, exec_count: u32) -> amalthea::Resul
     Err(amalthea::Error::ShellErrorExecuteReply(error, exec_count))
 }
 
-/// Converts a data frame to HTML
-fn to_html(frame: SEXP) -> Result<String> {
-    unsafe {
-        let result = RFunction::from(".ps.format.toHtml")
-            .add(frame)
-            .call()?
-            .to::<String>()?;
-        Ok(result)
+/// Structured information about the prompt of an input request, e.g. the
+/// choices of `menu()`. See `input_request_metadata()` in `console.R`.
+fn input_request_metadata() -> Option<serde_json::Value> {
+    let metadata = RFunction::new("", "input_request_metadata")
+        .call_in(ARK_ENVS.positron_ns)
+        .and_then(serde_json::Value::try_from);
+
+    match metadata {
+        Ok(serde_json::Value::Null) => None,
+        Ok(metadata) => Some(metadata),
+        Err(err) => {
+            log::warn!("Can't compute input request metadata: {err:?}");
+            None
+        },
     }
 }
 
+/// Representations of the value of the last top-level expression, keyed by
+/// MIME type, rendered by the renderers registered in `display.R`. Binary
+/// representations are base64-encoded.
+fn mime_bundle(notebook: bool) -> anyhow::Result<Vec<(String, serde_json::Value)>> {
+    let value = unsafe { Rf_findVarInFrame(R_ENVS.base, r_symbol!(".Last.value")) };
+
+    let bundle = RFunction::new("", "displayMimeBundle")
+        .add(value)
+        .add(notebook)
+        .call_in(ARK_ENVS.positron_ns)?;
+
+    if bundle.length() == 0 {
+        return Ok(Vec::new());
+    }
+    let bundle: HashMap<String, RObject> = bundle.try_into()?;
+
+    let mut out = Vec::with_capacity(bundle.len());
+    for (mime, value) in bundle {
+        let value = match r_typeof(value.sexp) {
+            RAWSXP => {
+                let bytes: Vec<u8> = (&value).try_into()?;
+                json!(general_purpose::STANDARD.encode(bytes))
+            },
+            _ => json!(String::try_from(value)?),
+        };
+        out.push((mime, value));
+    }
+
+    Ok(out)
+}
+
 // Inputs generated by `ReadConsole` for the LSP
 pub(crate) fn console_inputs() -> anyhow::Result<ConsoleInputs> {
     // TODO: Should send the debug environment if debugging:
@@ -2273,6 +2718,7 @@ pub(crate) fn console_inputs() -> anyhow::Result<ConsoleInputs> {
     Ok(ConsoleInputs {
         console_scopes: scopes,
         installed_packages,
+        console_code: Vec::new(),
     })
 }
 
@@ -2365,6 +2811,8 @@ unsafe extern "C-unwind" fn ps_onload_hook(pkg: SEXP, _path: SEXP) -> anyhow::Re
     // Need to reset parent as this might run in the context of another thread's R task
     let _span = tracing::trace_span!(parent: None, "onload_hook", pkg = pkg).entered();
 
+    EVENTS.package_load.emit(pkg.clone());
+
     // Populate fake source refs if needed
     if do_resource_namespaces() {
         r_task::spawn_idle(|| async move {
@@ -2377,6 +2825,53 @@ unsafe extern "C-unwind" fn ps_onload_hook(pkg: SEXP, _path: SEXP) -> anyhow::Re
     Ok(RObject::null().sexp)
 }
 
+#[harp::register]
+unsafe extern "C-unwind" fn ps_onunload_hook(pkg: SEXP) -> anyhow::Result<SEXP> {
+    let pkg: String = RObject::view(pkg).try_into()?;
+    EVENTS.package_unload.emit(pkg);
+    Ok(RObject::null().sexp)
+}
+
+#[harp::register]
+unsafe extern "C-unwind" fn ps_gc_hook() -> anyhow::Result<SEXP> {
+    EVENTS.gc.emit(());
+    Ok(RObject::null().sexp)
+}
+
+// These hooks are called like user attach and detach hooks but for every
+// package attached to or detached from the search path
+#[harp::register]
+unsafe extern "C-unwind" fn ps_attach_hook(pkg: SEXP) -> anyhow::Result<SEXP> {
+    let pkg: String = RObject::view(pkg).try_into()?;
+    EVENTS.package_attach.emit(pkg.clone());
+    notify_lsp_of_search_path_change(pkg);
+    Ok(RObject::null().sexp)
+}
+
+#[harp::register]
+unsafe extern "C-unwind" fn ps_detach_hook(pkg: SEXP) -> anyhow::Result<SEXP> {
+    let pkg: String = RObject::view(pkg).try_into()?;
+    EVENTS.package_detach.emit(pkg.clone());
+    notify_lsp_of_search_path_change(pkg);
+    Ok(RObject::null().sexp)
+}
+
+fn notify_lsp_of_search_path_change(pkg: String) {
+    if !RMain::is_initialized() {
+        return;
+    }
+
+    RMain::get_mut().send_lsp_notification(KernelNotification::DidChangePackage(
+        DidChangePackageParams { name: pkg },
+    ));
+
+    // Detach hooks run before the package is removed from the search path so
+    // we refresh the console scopes once R gets back to us
+    r_task::spawn_interrupt(|| async move {
+        RMain::get_mut().refresh_lsp_scopes();
+    });
+}
+
 fn do_resource_namespaces() -> bool {
     // Don't slow down integration tests with srcref generation
     if stdext::IS_TESTING {
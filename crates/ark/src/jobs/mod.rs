@@ -0,0 +1,8 @@
+//
+// mod.rs
+//
+// Copyright (C) 2025 by Posit Software, PBC
+//
+//
+
+pub mod r_job;
@@ -0,0 +1,276 @@
+//
+// r_job.rs
+//
+// Copyright (C) 2025 by Posit Software, PBC
+//
+//
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use amalthea::comm::comm_channel::CommMsg;
+use amalthea::comm::event::CommManagerEvent;
+use amalthea::comm::jobs_comm::JobState;
+use amalthea::comm::jobs_comm::JobStream;
+use amalthea::comm::jobs_comm::JobsBackendReply;
+use amalthea::comm::jobs_comm::JobsBackendRequest;
+use amalthea::comm::jobs_comm::JobsFrontendEvent;
+use amalthea::comm::jobs_comm::OutputParams;
+use amalthea::comm::jobs_comm::StateParams;
+use amalthea::socket::comm::CommInitiator;
+use amalthea::socket::comm::CommSocket;
+use crossbeam::channel::Sender;
+use crossbeam::select;
+use harp::exec::RFunction;
+use harp::exec::RFunctionExt;
+use harp::object::RObject;
+use harp::utils::r_is_null;
+use libr::SEXP;
+use serde::Deserialize;
+use serde::Serialize;
+use stdext::spawn;
+use uuid::Uuid;
+
+use crate::interface::RMain;
+use crate::modules::ARK_ENVS;
+use crate::r_task;
+
+/// How often running jobs are polled for output and completion
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Metadata {
+    pub name: String,
+    pub kind: String,
+}
+
+/// A background job, e.g. a `callr::r_bg()` process or a `mirai::mirai()`
+/// task, tracked by the frontend
+///
+/// The job itself lives at R level in `jobs.R`. This thread polls it at a
+/// regular interval, streams its output to the frontend, and reports its
+/// state once it completes.
+pub struct RJob {
+    metadata: Metadata,
+    comm: CommSocket,
+    comm_manager_tx: Sender<CommManagerEvent>,
+    state: JobState,
+}
+
+/// The result of polling a job at R level
+struct JobPoll {
+    stdout: String,
+    stderr: String,
+    state: JobState,
+    exit_code: Option<i64>,
+    message: Option<String>,
+}
+
+impl RJob {
+    pub fn start(
+        metadata: Metadata,
+        comm_manager_tx: Sender<CommManagerEvent>,
+        comm_id: String,
+    ) -> anyhow::Result<String> {
+        let comm = CommSocket::new(
+            CommInitiator::BackEnd,
+            comm_id.clone(),
+            String::from("positron.job"),
+        );
+
+        let job = Self {
+            metadata,
+            comm,
+            comm_manager_tx,
+            state: JobState::Running,
+        };
+
+        log::info!("Jobs: Channel created id:{comm_id}");
+        job.open_and_register_comm()?;
+
+        spawn!(format!("ark-job-{comm_id}"), move || {
+            if let Err(err) = job.handle_messages() {
+                log::error!("Jobs: Error while handling messages: {err:?}");
+            }
+        });
+
+        Ok(comm_id)
+    }
+
+    fn open_and_register_comm(&self) -> anyhow::Result<()> {
+        let comm_open_json = serde_json::to_value(self.metadata.clone())?;
+
+        // Notify the frontend that a new job has been launched
+        let event = CommManagerEvent::Opened(self.comm.clone(), comm_open_json);
+        self.comm_manager_tx.send(event)?;
+        Ok(())
+    }
+
+    fn handle_messages(mut self) -> anyhow::Result<()> {
+        let poll_rx = crossbeam::channel::tick(POLL_INTERVAL);
+
+        // Set to true if the frontend closes the channel
+        let mut user_initiated_close = false;
+
+        loop {
+            select! {
+                recv(poll_rx) -> _ => {
+                    // Completed jobs are kept around until the frontend
+                    // closes them but are no longer polled
+                    if self.state == JobState::Running {
+                        self.poll()?;
+                    }
+                },
+
+                recv(&self.comm.incoming_rx) -> msg => {
+                    let msg = match msg {
+                        Ok(msg) => msg,
+                        Err(err) => {
+                            log::error!("Jobs: Error receiving message from frontend: {err:?}");
+                            break;
+                        },
+                    };
+                    log::trace!("Jobs: Received message from frontend: {msg:?}");
+
+                    if let CommMsg::Close = msg {
+                        // Stop tracking the job. It keeps running if it
+                        // hasn't completed yet.
+                        log::trace!("Jobs: Received a close message.");
+                        self.forget()?;
+                        user_initiated_close = true;
+                        break;
+                    }
+
+                    let comm = self.comm.clone();
+                    comm.handle_request(msg, |req| self.handle_rpc(req));
+                },
+            }
+        }
+
+        if !user_initiated_close {
+            self.comm.outgoing_tx.send(CommMsg::Close)?;
+        }
+
+        Ok(())
+    }
+
+    fn handle_rpc(&mut self, message: JobsBackendRequest) -> anyhow::Result<JobsBackendReply> {
+        match message {
+            JobsBackendRequest::GetState => Ok(JobsBackendReply::GetStateReply(self.state)),
+            JobsBackendRequest::Cancel => {
+                if self.state != JobState::Running {
+                    return Ok(JobsBackendReply::CancelReply(false));
+                }
+
+                let cancelled = r_task(|| -> anyhow::Result<bool> {
+                    let cancelled = RFunction::new("", "jobCancel")
+                        .add(self.comm.comm_id.clone())
+                        .call_in(ARK_ENVS.positron_ns)?;
+                    Ok(cancelled.try_into()?)
+                })?;
+
+                // Report the cancellation along with the last output
+                if cancelled {
+                    self.poll()?;
+                }
+
+                Ok(JobsBackendReply::CancelReply(cancelled))
+            },
+        }
+    }
+
+    fn poll(&mut self) -> anyhow::Result<()> {
+        let Some(poll) = r_task(|| self.r_poll())? else {
+            // The job hasn't been registered yet
+            return Ok(());
+        };
+
+        if !poll.stdout.is_empty() {
+            self.send_output(JobStream::Stdout, poll.stdout)?;
+        }
+        if !poll.stderr.is_empty() {
+            self.send_output(JobStream::Stderr, poll.stderr)?;
+        }
+
+        if poll.state != self.state {
+            self.state = poll.state;
+            self.send_event(JobsFrontendEvent::State(StateParams {
+                state: poll.state,
+                exit_code: poll.exit_code,
+                message: poll.message,
+            }))?;
+        }
+
+        Ok(())
+    }
+
+    fn r_poll(&self) -> anyhow::Result<Option<JobPoll>> {
+        let poll = RFunction::new("", "jobPoll")
+            .add(self.comm.comm_id.clone())
+            .call_in(ARK_ENVS.positron_ns)?;
+
+        if r_is_null(poll.sexp) {
+            return Ok(None);
+        }
+        let mut poll: HashMap<String, RObject> = poll.try_into()?;
+
+        let mut field = |name: &str| {
+            poll.remove(name)
+                .ok_or_else(|| anyhow::anyhow!("Missing `{name}` in job poll"))
+        };
+
+        let state: String = field("state")?.try_into()?;
+
+        Ok(Some(JobPoll {
+            stdout: field("stdout")?.try_into()?,
+            stderr: field("stderr")?.try_into()?,
+            state: JobState::from_str(&state)?,
+            exit_code: field("exit_code")?.try_into()?,
+            message: field("message")?.try_into()?,
+        }))
+    }
+
+    fn forget(&self) -> anyhow::Result<()> {
+        r_task(|| -> anyhow::Result<()> {
+            RFunction::new("", "jobForget")
+                .add(self.comm.comm_id.clone())
+                .call_in(ARK_ENVS.positron_ns)?;
+            Ok(())
+        })
+    }
+
+    fn send_output(&self, stream: JobStream, text: String) -> anyhow::Result<()> {
+        self.send_event(JobsFrontendEvent::Output(OutputParams { stream, text }))
+    }
+
+    fn send_event(&self, event: JobsFrontendEvent) -> anyhow::Result<()> {
+        let data = serde_json::to_value(event)?;
+        self.comm.outgoing_tx.send(CommMsg::Data(data))?;
+        Ok(())
+    }
+}
+
+#[harp::register]
+pub unsafe extern "C-unwind" fn ps_job_opened(name: SEXP, kind: SEXP) -> anyhow::Result<SEXP> {
+    let id = Uuid::new_v4().to_string();
+    let id_r: RObject = id.clone().into();
+
+    if !RMain::is_initialized() {
+        // We are probably in unit tests, the job is tracked at R level but
+        // not polled
+        log::warn!("Jobs: RMain is not initialized. Job will not be started.");
+        return Ok(id_r.sexp);
+    }
+
+    let main = RMain::get();
+
+    let metadata = Metadata {
+        name: RObject::view(name).to::<String>()?,
+        kind: RObject::view(kind).to::<String>()?,
+    };
+
+    RJob::start(metadata, main.get_comm_manager_tx().clone(), id)?;
+
+    Ok(id_r.sexp)
+}
@@ -9,30 +9,38 @@ pub mod analysis;
 pub mod ark_comm;
 pub mod browser;
 pub mod connections;
+pub mod console_hyperlinks;
 pub mod control;
 pub mod coordinates;
 pub mod dap;
 pub mod data_explorer;
 pub mod debug;
 pub mod errors;
+pub mod events;
 pub mod fixtures;
 pub mod help;
 pub mod help_proxy;
 pub mod interface;
+pub mod jobs;
 pub mod json;
 pub mod logger;
+pub mod logger_comm;
 pub mod logger_hprof;
 pub mod lsp;
 pub mod methods;
 pub mod modules;
 pub mod modules_utils;
+pub mod number_format;
 pub mod plots;
+pub mod profiler;
 pub mod r_task;
 pub mod repos;
 pub mod request;
 pub mod reticulate;
 pub mod shell;
+pub mod shutdown;
 pub mod signals;
+pub mod source_file;
 pub mod srcref;
 pub mod start;
 pub mod startup;
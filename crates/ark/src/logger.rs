@@ -5,19 +5,63 @@
 //
 //
 
+use std::collections::VecDeque;
+use std::str::FromStr;
+use std::sync::Mutex;
 use std::sync::Once;
 
+use amalthea::comm::log_comm::LogLevel;
+use amalthea::comm::log_comm::LogModule;
+use amalthea::comm::log_comm::LogRecord;
+use amalthea::comm::log_comm::ModuleLevel;
+use crossbeam::channel::bounded;
+use crossbeam::channel::Receiver;
+use crossbeam::channel::Sender;
+use crossbeam::channel::TrySendError;
+use harp::object::RObject;
+use libr::R_NilValue;
+use libr::SEXP;
+use once_cell::sync::Lazy;
 use once_cell::sync::OnceCell;
 use regex::Regex;
+use tracing::level_filters::LevelFilter;
+use tracing::Subscriber;
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::Context;
 use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::reload;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::Layer;
+use tracing_subscriber::Registry;
 
 use crate::logger_hprof;
 
+/// Number of recent log records kept around for the frontend
+const LOG_RECORDS_CAPACITY: usize = 1000;
+
+/// Number of records waiting to be streamed to a frontend log comm. Records
+/// are dropped when a comm falls behind, the logger never blocks on it.
+const LOG_SUBSCRIBER_CAPACITY: usize = 1000;
+
+/// Handle to the filter of the log layer, used to reconfigure module levels at
+/// runtime
+static LOG_FILTER: OnceCell<reload::Handle<EnvFilter, Registry>> = OnceCell::new();
+
+/// The directives the logger was started with, from `RUST_LOG`. Module levels
+/// are applied on top of these.
+static LOG_DIRECTIVES: OnceCell<String> = OnceCell::new();
+
+/// Module levels configured at runtime
+static MODULE_LEVELS: Lazy<Mutex<Vec<ModuleLevel>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+static LOG_RECORDS: Lazy<Mutex<VecDeque<LogRecord>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(LOG_RECORDS_CAPACITY)));
+
+/// Channels of the frontend log comms that records are streamed to
+static LOG_SUBSCRIBERS: Lazy<Mutex<Vec<Sender<LogRecord>>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
 pub fn init(log_file: Option<&str>, profile_file: Option<&str>) {
     static ONCE: Once = Once::new();
 
@@ -46,6 +90,12 @@ pub fn init(log_file: Option<&str>, profile_file: Option<&str>) {
         static LOG_GUARD: OnceCell<WorkerGuard> = OnceCell::new();
         let log_writer = non_blocking(log_file, &LOG_GUARD);
 
+        // Save the directives so module levels can be applied on top of them,
+        // and make the filter reloadable
+        LOG_DIRECTIVES.set(env_filter.to_string()).unwrap();
+        let (env_filter, filter_handle) = reload::Layer::new(env_filter);
+        LOG_FILTER.set(filter_handle).unwrap();
+
         let log = tracing_subscriber::fmt::layer()
             // Use pretty representation. This has more spacing
             // and a clearer layout for fields.
@@ -63,7 +113,9 @@ pub fn init(log_file: Option<&str>, profile_file: Option<&str>) {
             .with_target(false)
             // Use our custom file writer
             .with_writer(log_writer)
-            // Filter based on `RUST_LOG` envvar
+            // Keep recent records around for the frontend
+            .and_then(RecordsLayer)
+            // Filter based on `RUST_LOG` envvar and module levels
             .with_filter(env_filter);
 
         // Subscriber for adding span information to errors
@@ -112,3 +164,224 @@ fn non_blocking(file: Option<&str>, cell: &OnceCell<WorkerGuard>) -> BoxMakeWrit
         BoxMakeWriter::new(std::io::stderr)
     }
 }
+
+/// Sets the level of the records emitted by a module
+///
+/// More specific modules take precedence, e.g. the LSP level applies to the
+/// LSP even if the kernel level is configured too.
+pub fn set_module_level(module: LogModule, level: LogLevel) -> anyhow::Result<()> {
+    let Some(handle) = LOG_FILTER.get() else {
+        return Err(anyhow::anyhow!("The logger is not initialized"));
+    };
+
+    let mut levels = MODULE_LEVELS.lock().unwrap();
+
+    let mut directives: Vec<String> = LOG_DIRECTIVES
+        .get()
+        .map(|directives| directives.split(',').map(String::from).collect())
+        .unwrap_or_default();
+
+    let mut new_levels: Vec<ModuleLevel> = levels
+        .iter()
+        .filter(|configured| configured.module != module)
+        .cloned()
+        .collect();
+    new_levels.push(ModuleLevel { module, level });
+
+    for ModuleLevel { module, level } in new_levels.iter() {
+        for target in module_targets(*module) {
+            directives.push(format!("{target}={level}"));
+        }
+    }
+
+    let filter = EnvFilter::try_new(directives.join(","))?;
+    handle.reload(filter)?;
+    *levels = new_levels;
+
+    // Records emitted with the `log` crate are filtered on its global max level
+    // before reaching us, so it must follow the filter
+    let max_level = LevelFilter::current().to_string();
+    if let Ok(max_level) = log::LevelFilter::from_str(&max_level) {
+        log::set_max_level(max_level);
+    }
+
+    log::info!("Set log level of {module} to {level}");
+    Ok(())
+}
+
+/// The module levels configured at runtime
+pub fn module_levels() -> Vec<ModuleLevel> {
+    MODULE_LEVELS.lock().unwrap().clone()
+}
+
+/// The most recent log records, oldest first
+pub fn recent_records(limit: Option<usize>) -> Vec<LogRecord> {
+    let records = LOG_RECORDS.lock().unwrap();
+    let skip = match limit {
+        Some(limit) => records.len().saturating_sub(limit),
+        None => 0,
+    };
+    records.iter().skip(skip).cloned().collect()
+}
+
+/// Streams log records as they are emitted. Stops once the receiver is
+/// dropped. Records are dropped while the receiver is full.
+pub fn subscribe() -> Receiver<LogRecord> {
+    let (tx, rx) = bounded(LOG_SUBSCRIBER_CAPACITY);
+    LOG_SUBSCRIBERS.lock().unwrap().push(tx);
+    rx
+}
+
+fn module_targets(module: LogModule) -> &'static [&'static str] {
+    match module {
+        LogModule::Kernel => &["ark", "amalthea", "harp", "stdext"],
+        LogModule::Lsp => &["ark::lsp"],
+        LogModule::DataExplorer => &["ark::data_explorer"],
+        LogModule::Variables => &["ark::variables"],
+    }
+}
+
+/// Layer recording events in `LOG_RECORDS` and streaming them to subscribers
+///
+/// This must not log anything as it runs within the logger.
+struct RecordsLayer;
+
+impl<S: Subscriber> Layer<S> for RecordsLayer {
+    fn on_event(&self, event: &tracing::Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+
+        let mut visitor = RecordVisitor::default();
+        event.record(&mut visitor);
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as i64)
+            .unwrap_or(0);
+
+        let level = match *metadata.level() {
+            tracing::Level::ERROR => LogLevel::Error,
+            tracing::Level::WARN => LogLevel::Warn,
+            tracing::Level::INFO => LogLevel::Info,
+            tracing::Level::DEBUG => LogLevel::Debug,
+            tracing::Level::TRACE => LogLevel::Trace,
+        };
+
+        let record = LogRecord {
+            timestamp,
+            level,
+            // Records emitted with the `log` crate carry their module path in a field
+            target: visitor
+                .target
+                .unwrap_or_else(|| metadata.target().to_string()),
+            message: visitor.message,
+        };
+
+        let mut records = LOG_RECORDS.lock().unwrap();
+        if records.len() == LOG_RECORDS_CAPACITY {
+            records.pop_front();
+        }
+        records.push_back(record.clone());
+        drop(records);
+
+        LOG_SUBSCRIBERS
+            .lock()
+            .unwrap()
+            .retain(|tx| match tx.try_send(record.clone()) {
+                Ok(()) | Err(TrySendError::Full(_)) => true,
+                Err(TrySendError::Disconnected(_)) => false,
+            });
+    }
+}
+
+#[derive(Default)]
+struct RecordVisitor {
+    message: String,
+    target: Option<String>,
+}
+
+impl tracing::field::Visit for RecordVisitor {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        match field.name() {
+            "log.target" => self.target = Some(value.to_string()),
+            _ => self.record_debug(field, &value),
+        }
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        match field.name() {
+            "message" => self.message.insert_str(0, &format!("{value:?}")),
+            name if name.starts_with("log.") => (),
+            name => self.message.push_str(&format!(" {name}={value:?}")),
+        }
+    }
+}
+
+#[harp::register]
+pub unsafe extern "C-unwind" fn ps_log_set_level(
+    module: SEXP,
+    level: SEXP,
+) -> anyhow::Result<SEXP> {
+    let module: String = RObject::view(module).try_into()?;
+    let level: String = RObject::view(level).try_into()?;
+
+    set_module_level(LogModule::from_str(&module)?, LogLevel::from_str(&level)?)?;
+
+    Ok(R_NilValue)
+}
+
+#[cfg(test)]
+mod tests {
+    use amalthea::comm::log_comm::LogLevel;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use crate::logger::recent_records;
+    use crate::logger::subscribe;
+    use crate::logger::RecordsLayer;
+    use crate::logger::LOG_SUBSCRIBER_CAPACITY;
+
+    #[test]
+    fn test_records_layer() {
+        let subscriber = tracing_subscriber::Registry::default().with(RecordsLayer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(count = 2, "ark-test-record");
+        });
+
+        let records = recent_records(None);
+        let record = records
+            .iter()
+            .find(|record| record.message.starts_with("ark-test-record"))
+            .unwrap();
+
+        assert_eq!(record.message, "ark-test-record count=2");
+        assert_eq!(record.level, LogLevel::Warn);
+        assert_eq!(record.target, "ark::logger::tests");
+
+        assert_eq!(recent_records(Some(1)).len(), 1);
+    }
+
+    #[test]
+    fn test_records_layer_drops_records_of_full_subscribers() {
+        let subscriber = tracing_subscriber::Registry::default().with(RecordsLayer);
+        let records_rx = subscribe();
+
+        tracing::subscriber::with_default(subscriber, || {
+            for _ in 0..(LOG_SUBSCRIBER_CAPACITY + 10) {
+                tracing::trace!("ark-test-full-subscriber");
+            }
+        });
+
+        // Other tests may log concurrently, but never past the capacity
+        assert_eq!(records_rx.len(), LOG_SUBSCRIBER_CAPACITY);
+
+        // The subscriber is still streamed to once it catches up
+        while records_rx.try_recv().is_ok() {}
+        let subscriber = tracing_subscriber::Registry::default().with(RecordsLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::trace!("ark-test-full-subscriber-after");
+        });
+        assert!(records_rx
+            .try_iter()
+            .any(|record| record.message == "ark-test-full-subscriber-after"));
+    }
+}
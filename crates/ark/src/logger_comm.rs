@@ -0,0 +1,105 @@
+//
+// logger_comm.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use amalthea::comm::comm_channel::CommMsg;
+use amalthea::comm::log_comm::LogBackendReply;
+use amalthea::comm::log_comm::LogBackendRequest;
+use amalthea::comm::log_comm::LogFrontendEvent;
+use amalthea::comm::log_comm::LogRecord;
+use amalthea::comm::log_comm::RecordParams;
+use amalthea::socket::comm::CommSocket;
+use crossbeam::channel::Receiver;
+use crossbeam::select;
+use stdext::spawn;
+
+use crate::logger;
+
+/// Records of the modules forwarding our comm messages to the frontend are
+/// not streamed. Each record we send would cause these modules to emit
+/// another one at trace level, in an endless loop.
+const UNSTREAMED_TARGETS: &[&str] = &["amalthea::comm", "amalthea::socket"];
+
+/// A frontend comm streaming log records, e.g. for a diagnostics pane. It also
+/// lets the frontend configure the levels of the modules.
+pub struct LogComm {
+    comm: CommSocket,
+    records_rx: Receiver<LogRecord>,
+}
+
+impl LogComm {
+    pub fn handle_comm_open(comm: CommSocket) -> amalthea::Result<bool> {
+        log::info!("Opening log comm: {}", comm.comm_id);
+
+        let log_comm = Self {
+            comm,
+            records_rx: logger::subscribe(),
+        };
+        spawn!("ark-log-comm", move || log_comm.process_messages());
+
+        Ok(true)
+    }
+
+    fn process_messages(self) {
+        loop {
+            select! {
+                recv(&self.records_rx) -> record => {
+                    let Ok(record) = record else {
+                        break;
+                    };
+                    if UNSTREAMED_TARGETS.iter().any(|target| record.target.starts_with(target)) {
+                        continue;
+                    }
+                    if let Err(err) = self.send_record(record) {
+                        // The record emitted here is streamed back to us, but
+                        // we stop listening right away
+                        log::error!("Log comm: Can't send record: {err:?}");
+                        break;
+                    }
+                },
+
+                recv(&self.comm.incoming_rx) -> msg => {
+                    let Ok(msg) = msg else {
+                        break;
+                    };
+
+                    if let CommMsg::Close = msg {
+                        break;
+                    }
+
+                    self.comm.handle_request(msg, |req| Self::handle_rpc(req));
+                },
+            }
+        }
+
+        log::info!("Log comm: Channel closed");
+    }
+
+    fn handle_rpc(request: LogBackendRequest) -> anyhow::Result<LogBackendReply> {
+        match request {
+            LogBackendRequest::GetRecords(params) => {
+                let limit = params.limit.map(|limit| limit.max(0) as usize);
+                Ok(LogBackendReply::GetRecordsReply(logger::recent_records(
+                    limit,
+                )))
+            },
+            LogBackendRequest::GetLevels => {
+                Ok(LogBackendReply::GetLevelsReply(logger::module_levels()))
+            },
+            LogBackendRequest::SetLevel(params) => {
+                logger::set_module_level(params.module, params.level)?;
+                Ok(LogBackendReply::SetLevelReply())
+            },
+        }
+    }
+
+    fn send_record(&self, record: LogRecord) -> anyhow::Result<()> {
+        let event = LogFrontendEvent::Record(RecordParams { record });
+        let data = serde_json::to_value(event)?;
+        self.comm.outgoing_tx.send(CommMsg::Data(data))?;
+        Ok(())
+    }
+}
@@ -17,6 +17,8 @@ use amalthea::comm::ui_comm::UiFrontendEvent;
 use crossbeam::channel::Sender;
 use serde_json::Value;
 use stdext::result::ResultOrLog;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
 use tokio::net::TcpListener;
 use tokio::runtime::Runtime;
 use tokio::sync::mpsc::unbounded_channel as tokio_unbounded_channel;
@@ -34,6 +36,9 @@ use tower_lsp::Server;
 
 use super::main_loop::LSP_HAS_CRASHED;
 use crate::interface::RMain;
+use crate::lsp::breadcrumbs;
+use crate::lsp::breadcrumbs::BreadcrumbsParams;
+use crate::lsp::breadcrumbs::BreadcrumbsResponse;
 use crate::lsp::handlers::VirtualDocumentParams;
 use crate::lsp::handlers::VirtualDocumentResponse;
 use crate::lsp::handlers::ARK_VDOC_REQUEST;
@@ -49,6 +54,9 @@ use crate::lsp::main_loop::TokioUnboundedSender;
 use crate::lsp::statement_range;
 use crate::lsp::statement_range::StatementRangeParams;
 use crate::lsp::statement_range::StatementRangeResponse;
+use crate::lsp::telemetry;
+use crate::lsp::telemetry::TelemetryParams;
+use crate::lsp::telemetry::TelemetryResponse;
 use crate::r_task;
 
 // This enum is useful for two things. First it allows us to distinguish a
@@ -147,10 +155,14 @@ pub(crate) enum LspRequest {
     References(ReferenceParams),
     StatementRange(StatementRangeParams),
     HelpTopic(HelpTopicParams),
+    Breadcrumbs(BreadcrumbsParams),
     OnTypeFormatting(DocumentOnTypeFormattingParams),
     CodeAction(CodeActionParams),
+    LinkedEditingRange(LinkedEditingRangeParams),
+    CodeLens(CodeLensParams),
     VirtualDocument(VirtualDocumentParams),
     InputBoundaries(InputBoundariesParams),
+    Telemetry(TelemetryParams),
 }
 
 #[derive(Debug)]
@@ -170,10 +182,14 @@ pub(crate) enum LspResponse {
     References(Option<Vec<Location>>),
     StatementRange(Option<StatementRangeResponse>),
     HelpTopic(Option<HelpTopicResponse>),
+    Breadcrumbs(BreadcrumbsResponse),
     OnTypeFormatting(Option<Vec<TextEdit>>),
     CodeAction(Option<CodeActionResponse>),
+    LinkedEditingRange(Option<LinkedEditingRanges>),
+    CodeLens(Option<Vec<CodeLens>>),
     VirtualDocument(VirtualDocumentResponse),
     InputBoundaries(InputBoundariesResponse),
+    Telemetry(TelemetryResponse),
 }
 
 #[derive(Debug)]
@@ -407,6 +423,25 @@ impl LanguageServer for Backend {
             LspResponse::CodeAction
         )
     }
+
+    async fn linked_editing_range(
+        &self,
+        params: LinkedEditingRangeParams,
+    ) -> Result<Option<LinkedEditingRanges>> {
+        cast_response!(
+            self,
+            self.request(LspRequest::LinkedEditingRange(params)).await,
+            LspResponse::LinkedEditingRange
+        )
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        cast_response!(
+            self,
+            self.request(LspRequest::CodeLens(params)).await,
+            LspResponse::CodeLens
+        )
+    }
 }
 
 // Custom methods for the backend.
@@ -447,6 +482,14 @@ impl Backend {
         )
     }
 
+    async fn breadcrumbs(&self, params: BreadcrumbsParams) -> jsonrpc::Result<BreadcrumbsResponse> {
+        cast_response!(
+            self,
+            self.request(LspRequest::Breadcrumbs(params)).await,
+            LspResponse::Breadcrumbs
+        )
+    }
+
     async fn virtual_document(
         &self,
         params: VirtualDocumentParams,
@@ -469,6 +512,17 @@ impl Backend {
         )
     }
 
+    async fn telemetry(
+        &self,
+        params: TelemetryParams,
+    ) -> tower_lsp::jsonrpc::Result<TelemetryResponse> {
+        cast_response!(
+            self,
+            self.request(LspRequest::Telemetry(params)).await,
+            LspResponse::Telemetry
+        )
+    }
+
     async fn notification(&self, params: Option<Value>) {
         log::info!("Received Positron notification: {:?}", params);
     }
@@ -508,66 +562,20 @@ pub fn start_lsp(
         log::trace!("LSP: Connected to client: '{address}'");
         let (read, write) = tokio::io::split(stream);
 
-        let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel::<()>(1);
-
-        let init = |client: Client| {
-            let state = GlobalState::new(client);
-            let events_tx = state.events_tx();
-
-            // Start main loop and hold onto the handle that keeps it alive
-            let main_loop = state.start();
-
+        serve(read, write, |events_tx| {
             // Forward event channel along to `RMain`.
             // This also updates an outdated channel after a reconnect.
             // `RMain` should be initialized by now, since the caller of this
             // function waits to receive the init notification sent on
             // `kernel_init_rx`. Even if it isn't, this should be okay because
             // `r_task()` defensively blocks until its sender is initialized.
-            r_task({
-                let events_tx = events_tx.clone();
-                move || {
-                    RMain::with_mut(|main| main.set_lsp_channel(events_tx));
-                }
+            r_task(move || {
+                RMain::with_mut(|main| main.set_lsp_channel(events_tx));
             });
+        })
+        .await;
 
-            Backend {
-                shutdown_tx,
-                events_tx,
-                _main_loop: main_loop,
-            }
-        };
-
-        let (service, socket) = LspService::build(init)
-            .custom_method(
-                statement_range::POSITRON_STATEMENT_RANGE_REQUEST,
-                Backend::statement_range,
-            )
-            .custom_method(help_topic::POSITRON_HELP_TOPIC_REQUEST, Backend::help_topic)
-            .custom_method(ARK_VDOC_REQUEST, Backend::virtual_document)
-            // In principle this should probably be a Jupyter request
-            .custom_method(
-                input_boundaries::POSITRON_INPUT_BOUNDARIES_REQUEST,
-                Backend::input_boundaries,
-            )
-            .custom_method("positron/notification", Backend::notification)
-            .finish();
-
-        let server = Server::new(read, write, socket);
-
-        tokio::select! {
-            _ = server.serve(service) => {
-                log::trace!(
-                    "LSP: Thread exiting gracefully after connection closed ({:?}).",
-                    address
-                );
-            },
-            _ = shutdown_rx.recv() => {
-                log::trace!(
-                    "LSP: Thread exiting after receiving a shutdown request ({:?}).",
-                    address
-                );
-            }
-        }
+        log::trace!("LSP: Thread exiting, disconnected from client: '{address}'");
 
         // Remove the LSP channel on the way out, we can no longer handle any LSP updates
         // from `RMain`, at least until someone starts the LSP up again.
@@ -579,6 +587,67 @@ pub fn start_lsp(
     })
 }
 
+/// Serve the LSP to the client connected to `read` and `write`
+///
+/// Returns when the connection is closed or after a handler has crashed.
+/// `on_connect` is called with the transmission channel of the main loop
+/// once it has started.
+pub(crate) async fn serve<I, O, F>(read: I, write: O, on_connect: F)
+where
+    I: AsyncRead + Unpin,
+    O: AsyncWrite,
+    F: FnOnce(TokioUnboundedSender<Event>),
+{
+    let (shutdown_tx, mut shutdown_rx) = tokio::sync::mpsc::channel::<()>(1);
+
+    let init = |client: Client| {
+        let state = GlobalState::new(client);
+        let events_tx = state.events_tx();
+
+        // Start main loop and hold onto the handle that keeps it alive
+        let main_loop = state.start();
+
+        on_connect(events_tx.clone());
+
+        Backend {
+            shutdown_tx,
+            events_tx,
+            _main_loop: main_loop,
+        }
+    };
+
+    let (service, socket) = LspService::build(init)
+        .custom_method(
+            statement_range::POSITRON_STATEMENT_RANGE_REQUEST,
+            Backend::statement_range,
+        )
+        .custom_method(help_topic::POSITRON_HELP_TOPIC_REQUEST, Backend::help_topic)
+        .custom_method(
+            breadcrumbs::POSITRON_BREADCRUMBS_REQUEST,
+            Backend::breadcrumbs,
+        )
+        .custom_method(ARK_VDOC_REQUEST, Backend::virtual_document)
+        // In principle this should probably be a Jupyter request
+        .custom_method(
+            input_boundaries::POSITRON_INPUT_BOUNDARIES_REQUEST,
+            Backend::input_boundaries,
+        )
+        .custom_method(telemetry::ARK_TELEMETRY_REQUEST, Backend::telemetry)
+        .custom_method("positron/notification", Backend::notification)
+        .finish();
+
+    let server = Server::new(read, write, socket);
+
+    tokio::select! {
+        _ = server.serve(service) => {
+            log::trace!("LSP: Connection closed by client.");
+        },
+        _ = shutdown_rx.recv() => {
+            log::trace!("LSP: Received a shutdown request.");
+        }
+    }
+}
+
 fn new_jsonrpc_error(message: String) -> jsonrpc::Error {
     jsonrpc::Error {
         code: jsonrpc::ErrorCode::ServerError(-1),
@@ -0,0 +1,169 @@
+//
+// breadcrumbs.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use serde::Deserialize;
+use serde::Serialize;
+use tower_lsp::lsp_types::DocumentSymbol;
+use tower_lsp::lsp_types::Position;
+use tower_lsp::lsp_types::Range;
+use tower_lsp::lsp_types::SymbolKind;
+use tower_lsp::lsp_types::TextDocumentIdentifier;
+
+use crate::lsp::documents::Document;
+use crate::lsp::symbols::document_symbols_of;
+
+pub static POSITRON_BREADCRUMBS_REQUEST: &'static str = "positron/textDocument/breadcrumbs";
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreadcrumbsParams {
+    /// The document to provide breadcrumbs for.
+    pub text_document: TextDocumentIdentifier,
+    /// The location of the cursor.
+    pub position: Position,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreadcrumbsResponse {
+    /// The symbols enclosing the cursor, from the outermost to the innermost.
+    pub breadcrumbs: Vec<Breadcrumb>,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Breadcrumb {
+    /// The name of the symbol, e.g. a function name or a section title.
+    pub name: String,
+    /// The kind of the symbol, as in document symbols.
+    pub kind: SymbolKind,
+    /// The full range of the symbol, including the body of functions up to
+    /// their closing brace and the contents of comment sections.
+    pub range: Range,
+}
+
+/// Computes the chain of functions, classes, and comment sections enclosing
+/// `position`. These are the document symbols whose range contains the
+/// position, so the editor can show them as sticky scopes.
+pub(crate) fn breadcrumbs(
+    document: &Document,
+    position: Position,
+) -> anyhow::Result<BreadcrumbsResponse> {
+    // Always include assignments in blocks, they might enclose the cursor
+    let symbols = document_symbols_of(document, true)?;
+
+    let mut breadcrumbs = Vec::new();
+    let mut symbols = symbols.as_slice();
+
+    // Symbols are sorted and don't overlap at a given level, but take the
+    // innermost match just in case
+    while let Some(symbol) = symbols
+        .iter()
+        .rev()
+        .find(|symbol| contains(&symbol.range, position))
+    {
+        breadcrumbs.push(breadcrumb(symbol));
+
+        symbols = match &symbol.children {
+            Some(children) => children.as_slice(),
+            None => break,
+        };
+    }
+
+    Ok(BreadcrumbsResponse { breadcrumbs })
+}
+
+fn breadcrumb(symbol: &DocumentSymbol) -> Breadcrumb {
+    Breadcrumb {
+        name: symbol.name.clone(),
+        kind: symbol.kind,
+        range: symbol.range,
+    }
+}
+
+fn contains(range: &Range, position: Position) -> bool {
+    range.start <= position && position <= range.end
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Position;
+    use tower_lsp::lsp_types::SymbolKind;
+
+    use crate::lsp::breadcrumbs::breadcrumbs;
+    use crate::lsp::documents::Document;
+
+    fn names(code: &str, position: Position) -> Vec<(String, SymbolKind)> {
+        let document = Document::new(code, None);
+        breadcrumbs(&document, position)
+            .unwrap()
+            .breadcrumbs
+            .into_iter()
+            .map(|breadcrumb| (breadcrumb.name, breadcrumb.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_breadcrumbs() {
+        let code = "
+# Helpers ----
+outer <- function() {
+  # Inner section ----
+  inner <- function(x) {
+    x + 1
+  }
+}
+
+# Other ----
+x <- 1
+";
+
+        // Inside of `inner`'s body
+        assert_eq!(names(code, Position::new(5, 4)), vec![
+            (String::from("Helpers"), SymbolKind::STRING),
+            (String::from("outer"), SymbolKind::FUNCTION),
+            (String::from("Inner section"), SymbolKind::STRING),
+            (String::from("inner"), SymbolKind::FUNCTION),
+        ]);
+
+        // On the closing brace of `inner`
+        assert_eq!(names(code, Position::new(6, 3)), vec![
+            (String::from("Helpers"), SymbolKind::STRING),
+            (String::from("outer"), SymbolKind::FUNCTION),
+            (String::from("Inner section"), SymbolKind::STRING),
+            (String::from("inner"), SymbolKind::FUNCTION),
+        ]);
+
+        // On the closing brace of `outer`, outside of the inner section
+        assert_eq!(names(code, Position::new(7, 1)), vec![
+            (String::from("Helpers"), SymbolKind::STRING),
+            (String::from("outer"), SymbolKind::FUNCTION),
+        ]);
+
+        assert_eq!(names(code, Position::new(10, 0)), vec![
+            (String::from("Other"), SymbolKind::STRING),
+            (String::from("x"), SymbolKind::VARIABLE),
+        ]);
+    }
+
+    #[test]
+    fn test_breadcrumbs_methods() {
+        let code = "
+list(
+  foo = function() {
+    1
+  }
+)
+";
+
+        // On the name of the method
+        assert_eq!(names(code, Position::new(2, 3)), vec![(
+            String::from("foo"),
+            SymbolKind::METHOD
+        )]);
+    }
+}
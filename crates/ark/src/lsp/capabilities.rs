@@ -90,11 +90,11 @@ impl Capabilities {
             return None;
         }
 
-        // Currently we only support documentation generating code actions, which don't
-        // map to an existing kind. rust-analyzer maps them to `EMPTY`, so we follow suit.
-        // Currently no code actions require delayed resolution.
+        // Documentation generating code actions don't map to an existing kind.
+        // rust-analyzer maps them to `EMPTY`, so we follow suit. Spelling corrections
+        // are quick fixes. Currently no code actions require delayed resolution.
         Some(CodeActionProviderCapability::Options(CodeActionOptions {
-            code_action_kinds: Some(vec![CodeActionKind::EMPTY]),
+            code_action_kinds: Some(vec![CodeActionKind::EMPTY, CodeActionKind::QUICKFIX]),
             work_done_progress_options: WorkDoneProgressOptions::default(),
             resolve_provider: Some(false),
         }))
@@ -14,10 +14,15 @@ use tree_sitter::Range;
 use url::Url;
 
 use crate::lsp::capabilities::Capabilities;
+use crate::lsp::code_action::imports::import_suggestions;
 use crate::lsp::code_action::roxygen::roxygen_documentation;
+use crate::lsp::code_action::spelling::spelling_corrections;
 use crate::lsp::documents::Document;
+use crate::lsp::inputs::exports_index::ExportsIndex;
 
+mod imports;
 mod roxygen;
+mod spelling;
 
 /// A small wrapper around [CodeActionResponse] that make a few things more ergonomic
 pub(crate) struct CodeActions {
@@ -28,11 +33,22 @@ pub(crate) fn code_actions(
     uri: &Url,
     document: &Document,
     range: Range,
+    diagnostics: &[lsp_types::Diagnostic],
+    exports: &ExportsIndex,
     capabilities: &Capabilities,
 ) -> lsp_types::CodeActionResponse {
     let mut actions = CodeActions::new();
 
     roxygen_documentation(&mut actions, uri, document, range, capabilities);
+    spelling_corrections(&mut actions, uri, document, diagnostics, capabilities);
+    import_suggestions(
+        &mut actions,
+        uri,
+        document,
+        diagnostics,
+        exports,
+        capabilities,
+    );
 
     actions.into_response()
 }
@@ -0,0 +1,239 @@
+use tower_lsp::lsp_types;
+use tree_sitter::Point;
+use url::Url;
+
+use crate::lsp::capabilities::Capabilities;
+use crate::lsp::code_action::code_action;
+use crate::lsp::code_action::code_action_workspace_text_edit;
+use crate::lsp::code_action::CodeActions;
+use crate::lsp::diagnostics::UNDEFINED_SYMBOL_DIAGNOSTIC_CODE;
+use crate::lsp::documents::Document;
+use crate::lsp::encoding::convert_point_to_position;
+use crate::lsp::inputs::exports_index::ExportsIndex;
+use crate::treesitter::node_is_call;
+
+/// Maximum number of packages suggested for a single symbol
+const MAX_PACKAGES: usize = 5;
+
+/// Quick fixes for symbols not in scope that are exported by installed
+/// packages, either attaching the package with `library()` at the top of the
+/// file or qualifying the symbol with `pkg::`
+pub(crate) fn import_suggestions(
+    actions: &mut CodeActions,
+    uri: &Url,
+    document: &Document,
+    diagnostics: &[lsp_types::Diagnostic],
+    exports: &ExportsIndex,
+    capabilities: &Capabilities,
+) -> Option<()> {
+    if !capabilities.code_action_literal_support() {
+        // This code action returns literal `CodeAction`s, so must have support for them
+        return None;
+    }
+
+    let code = lsp_types::NumberOrString::String(String::from(UNDEFINED_SYMBOL_DIAGNOSTIC_CODE));
+
+    for diagnostic in diagnostics {
+        if diagnostic.code.as_ref() != Some(&code) {
+            continue;
+        }
+
+        let Some(symbol) = diagnostic
+            .data
+            .as_ref()
+            .and_then(|data| data.get("symbol"))
+            .and_then(|symbol| symbol.as_str())
+        else {
+            continue;
+        };
+
+        let packages = exports.packages(symbol);
+        let packages = &packages[..packages.len().min(MAX_PACKAGES)];
+
+        // Attaching the package only makes sense in plain R files, the top of
+        // R Markdown documents isn't code
+        if document.r_markdown.is_none() {
+            let (position, newline) = library_position(document);
+
+            for package in packages {
+                let text = if newline {
+                    format!("\nlibrary({package})")
+                } else {
+                    format!("library({package})\n")
+                };
+                let edit =
+                    lsp_types::TextEdit::new(lsp_types::Range::new(position, position), text);
+                let edit = code_action_workspace_text_edit(
+                    uri.clone(),
+                    document.version,
+                    vec![edit],
+                    capabilities,
+                );
+
+                let mut action = code_action(
+                    format!("Add `library({package})`"),
+                    lsp_types::CodeActionKind::QUICKFIX,
+                    edit,
+                );
+                action.diagnostics = Some(vec![diagnostic.clone()]);
+                action.is_preferred = Some(packages.len() == 1);
+
+                actions.add_action(action);
+            }
+        }
+
+        for package in packages {
+            let edit = lsp_types::TextEdit::new(
+                lsp_types::Range::new(diagnostic.range.start, diagnostic.range.start),
+                format!("{package}::"),
+            );
+            let edit = code_action_workspace_text_edit(
+                uri.clone(),
+                document.version,
+                vec![edit],
+                capabilities,
+            );
+
+            let mut action = code_action(
+                format!("Use `{package}::{symbol}`"),
+                lsp_types::CodeActionKind::QUICKFIX,
+                edit,
+            );
+            action.diagnostics = Some(vec![diagnostic.clone()]);
+
+            actions.add_action(action);
+        }
+    }
+
+    Some(())
+}
+
+/// Where to insert a `library()` call: after the existing top-level
+/// `library()` and `require()` calls if any, otherwise at the top of the file.
+/// Also returns whether a newline must be inserted first, when the last call
+/// is on the last line of a file without a trailing newline.
+fn library_position(document: &Document) -> (lsp_types::Position, bool) {
+    let root = document.ast.root_node();
+    let mut cursor = root.walk();
+
+    let last = root
+        .children(&mut cursor)
+        .filter(|node| {
+            node_is_call(node, "library", &document.contents) ||
+                node_is_call(node, "require", &document.contents)
+        })
+        .last();
+
+    let Some(last) = last else {
+        return (lsp_types::Position::new(0, 0), false);
+    };
+
+    let row = last.end_position().row + 1;
+    if row < document.contents.len_lines() {
+        let point = Point::new(row, 0);
+        return (convert_point_to_position(&document.contents, point), false);
+    }
+
+    let point = Point::new(row - 1, document.contents.line(row - 1).len_bytes());
+    (convert_point_to_position(&document.contents, point), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+    use tower_lsp::lsp_types;
+    use url::Url;
+
+    use crate::lsp::capabilities::Capabilities;
+    use crate::lsp::code_action::imports::import_suggestions;
+    use crate::lsp::code_action::CodeActions;
+    use crate::lsp::diagnostics::UNDEFINED_SYMBOL_DIAGNOSTIC_CODE;
+    use crate::lsp::documents::Document;
+    use crate::lsp::inputs::exports_index::ExportsIndex;
+
+    fn undefined_symbol_diagnostic(symbol: &str, range: lsp_types::Range) -> lsp_types::Diagnostic {
+        let mut diagnostic = lsp_types::Diagnostic::new_simple(
+            range,
+            format!("No symbol named '{symbol}' in scope."),
+        );
+        diagnostic.code = Some(lsp_types::NumberOrString::String(String::from(
+            UNDEFINED_SYMBOL_DIAGNOSTIC_CODE,
+        )));
+        diagnostic.data = Some(serde_json::json!({ "symbol": symbol }));
+        diagnostic
+    }
+
+    #[test]
+    fn test_import_suggestions() {
+        let library = TempDir::new().unwrap();
+        let path = library.path().join("mypkg");
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join("DESCRIPTION"), "Package: mypkg\nVersion: 1.0\n").unwrap();
+        fs::write(path.join("NAMESPACE"), "export(my_fun)\n").unwrap();
+        let exports = ExportsIndex::build(&[library.path().to_path_buf()]);
+
+        let uri = Url::parse("file:///test.R").unwrap();
+        let document = Document::new("library(utils)\nx <- my_fun(1)\n", None);
+        let range = lsp_types::Range::new(
+            lsp_types::Position::new(1, 5),
+            lsp_types::Position::new(1, 11),
+        );
+        let diagnostics = vec![undefined_symbol_diagnostic("my_fun", range)];
+
+        let capabilities = Capabilities::default()
+            .with_code_action_literal_support(true)
+            .with_workspace_edit_document_changes(false);
+
+        let mut actions = CodeActions::new();
+        import_suggestions(
+            &mut actions,
+            &uri,
+            &document,
+            &diagnostics,
+            &exports,
+            &capabilities,
+        );
+
+        let edits: Vec<(String, lsp_types::TextEdit)> = actions
+            .into_response()
+            .into_iter()
+            .map(|action| match action {
+                lsp_types::CodeActionOrCommand::CodeAction(action) => {
+                    assert_eq!(action.kind, Some(lsp_types::CodeActionKind::QUICKFIX));
+                    let mut changes = action.edit.unwrap().changes.unwrap();
+                    let edit = changes.remove(&uri).unwrap().remove(0);
+                    (action.title, edit)
+                },
+                lsp_types::CodeActionOrCommand::Command(_) => panic!("Unexpected command"),
+            })
+            .collect();
+
+        assert_eq!(edits.len(), 2);
+
+        // `library()` goes after the existing `library()` calls
+        let (title, edit) = &edits[0];
+        assert_eq!(title, "Add `library(mypkg)`");
+        assert_eq!(edit.range.start, lsp_types::Position::new(1, 0));
+        assert_eq!(edit.new_text, "library(mypkg)\n");
+
+        let (title, edit) = &edits[1];
+        assert_eq!(title, "Use `mypkg::my_fun`");
+        assert_eq!(edit.range.start, lsp_types::Position::new(1, 5));
+        assert_eq!(edit.new_text, "mypkg::");
+
+        // Symbols that aren't exported by any package don't have suggestions
+        let diagnostics = vec![undefined_symbol_diagnostic("unknown", range)];
+        let mut actions = CodeActions::new();
+        import_suggestions(
+            &mut actions,
+            &uri,
+            &document,
+            &diagnostics,
+            &exports,
+            &capabilities,
+        );
+        assert!(actions.into_response().is_empty());
+    }
+}
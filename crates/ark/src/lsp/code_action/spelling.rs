@@ -0,0 +1,120 @@
+use tower_lsp::lsp_types;
+use url::Url;
+
+use crate::lsp::capabilities::Capabilities;
+use crate::lsp::code_action::code_action;
+use crate::lsp::code_action::code_action_workspace_text_edit;
+use crate::lsp::code_action::CodeActions;
+use crate::lsp::diagnostics_spelling::SPELLING_DIAGNOSTIC_CODE;
+use crate::lsp::documents::Document;
+
+/// Quick fixes replacing misspelled words with the corrections suggested by
+/// spelling diagnostics
+pub(crate) fn spelling_corrections(
+    actions: &mut CodeActions,
+    uri: &Url,
+    document: &Document,
+    diagnostics: &[lsp_types::Diagnostic],
+    capabilities: &Capabilities,
+) -> Option<()> {
+    if !capabilities.code_action_literal_support() {
+        // This code action returns literal `CodeAction`s, so must have support for them
+        return None;
+    }
+
+    let code = lsp_types::NumberOrString::String(String::from(SPELLING_DIAGNOSTIC_CODE));
+
+    for diagnostic in diagnostics {
+        if diagnostic.code.as_ref() != Some(&code) {
+            continue;
+        }
+
+        let Some(suggestions) = diagnostic
+            .data
+            .as_ref()
+            .and_then(|data| data.get("suggestions"))
+            .and_then(|suggestions| suggestions.as_array())
+        else {
+            continue;
+        };
+
+        for (i, suggestion) in suggestions.iter().enumerate() {
+            let Some(suggestion) = suggestion.as_str() else {
+                continue;
+            };
+
+            let edit = lsp_types::TextEdit::new(diagnostic.range, suggestion.to_string());
+            let edit = code_action_workspace_text_edit(
+                uri.clone(),
+                document.version,
+                vec![edit],
+                capabilities,
+            );
+
+            let mut action = code_action(
+                format!("Change spelling to `{suggestion}`"),
+                lsp_types::CodeActionKind::QUICKFIX,
+                edit,
+            );
+            action.diagnostics = Some(vec![diagnostic.clone()]);
+            action.is_preferred = Some(i == 0);
+
+            actions.add_action(action);
+        }
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types;
+    use url::Url;
+
+    use crate::lsp::capabilities::Capabilities;
+    use crate::lsp::code_action::spelling::spelling_corrections;
+    use crate::lsp::code_action::CodeActions;
+    use crate::lsp::diagnostics_spelling::spelling_diagnostics;
+    use crate::lsp::documents::Document;
+
+    #[test]
+    fn test_spelling_corrections() {
+        let uri = Url::parse("file:///test.R").unwrap();
+        let document = Document::new("# wheather and teh\nx <- 1\n", None);
+        let diagnostics = spelling_diagnostics(&document);
+
+        let capabilities = Capabilities::default()
+            .with_code_action_literal_support(true)
+            .with_workspace_edit_document_changes(false);
+
+        let mut actions = CodeActions::new();
+        spelling_corrections(&mut actions, &uri, &document, &diagnostics, &capabilities);
+
+        let titles: Vec<String> = actions
+            .into_response()
+            .into_iter()
+            .map(|action| match action {
+                lsp_types::CodeActionOrCommand::CodeAction(action) => {
+                    assert_eq!(action.kind, Some(lsp_types::CodeActionKind::QUICKFIX));
+                    action.title
+                },
+                lsp_types::CodeActionOrCommand::Command(_) => panic!("Unexpected command"),
+            })
+            .collect();
+
+        assert_eq!(titles, vec![
+            "Change spelling to `weather`",
+            "Change spelling to `whether`",
+            "Change spelling to `the`",
+        ]);
+
+        // Other diagnostics don't have corrections
+        let mut actions = CodeActions::new();
+        let diagnostic = lsp_types::Diagnostic::new_simple(
+            lsp_types::Range::default(),
+            String::from("Some other diagnostic"),
+        );
+        spelling_corrections(&mut actions, &uri, &document, &[diagnostic], &capabilities);
+        assert!(actions.into_response().is_empty());
+    }
+}
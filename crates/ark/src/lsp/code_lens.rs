@@ -0,0 +1,544 @@
+//
+// code_lens.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::anyhow;
+use harp::exec::RFunction;
+use harp::exec::RFunctionExt;
+use ropey::Rope;
+use serde_json::Value;
+use tower_lsp::lsp_types::CodeLens;
+use tower_lsp::lsp_types::Command;
+use tower_lsp::lsp_types::Position;
+use tower_lsp::lsp_types::Range;
+use tree_sitter::Node;
+use url::Url;
+
+use crate::lsp::documents::Document;
+use crate::lsp::encoding::convert_point_to_position;
+use crate::lsp::state::WorldState;
+use crate::lsp::symbols::parse_comment_as_section;
+use crate::lsp::traits::rope::RopeExt;
+use crate::r_task;
+use crate::treesitter::NodeTypeExt;
+
+/// Client command that opens a file in an editor
+const OPEN_COMMAND: &str = "vscode.open";
+
+/// Server command that runs lines of a document at the console. Takes the
+/// URI of the document, the first line, and the end line (exclusive).
+pub(crate) const RUN_LINES_COMMAND: &str = "ark.runLines";
+
+/// Server commands that run tests at the console. They take the path of the
+/// test file as first argument, and `ark.runTest` takes the description of
+/// the test as second argument.
+pub(crate) const RUN_TEST_COMMAND: &str = "ark.runTest";
+pub(crate) const RUN_FILE_COMMAND: &str = "ark.runFile";
+
+/// Code lenses of a document
+///
+/// - Top-level comment sections get "Run section" and "Run above" lenses, see
+///   [section_lenses()].
+///
+/// - Files in `R/` get a "Go to tests" lens when they have a matching
+///   `tests/testthat/test-<name>.R` file.
+///
+/// - Test files in `tests/testthat/` get a "Go to source" lens when they have
+///   a matching file in `R/`, a "Run file" lens, and a "Run test" lens on
+///   every top-level `test_that()` call.
+pub(crate) fn code_lenses(uri: &Url, document: &Document) -> anyhow::Result<Vec<CodeLens>> {
+    let mut lenses = section_lenses(uri, document)?;
+
+    let Ok(path) = uri.to_file_path() else {
+        return Ok(lenses);
+    };
+
+    lenses.extend(package_lenses(&path, document)?);
+
+    Ok(lenses)
+}
+
+/// Lenses that run comment sections like notebook cells
+///
+/// Sections are delimited by the comment headers detected by
+/// [parse_comment_as_section()], e.g. `# Title ----`. A section extends to
+/// the next header of the same or a higher level, so that running a section
+/// also runs its subsections.
+fn section_lenses(uri: &Url, document: &Document) -> anyhow::Result<Vec<CodeLens>> {
+    // The prose of R Markdown documents has its own sections
+    if document.r_markdown.is_some() {
+        return Ok(Vec::new());
+    }
+
+    let headers = section_headers(document)?;
+    let n_lines = document.contents.len_lines();
+
+    let mut lenses = Vec::new();
+
+    for (i, (line, level)) in headers.iter().enumerate() {
+        let end = headers[i + 1..]
+            .iter()
+            .find(|(_, next_level)| next_level <= level)
+            .map_or(n_lines, |(next_line, _)| *next_line);
+
+        let position = Position::new(*line as u32, 0);
+
+        lenses.push(run_lines_lens("Run section", uri, position, *line, end));
+
+        if *line > 0 {
+            lenses.push(run_lines_lens("Run above", uri, position, 0, *line));
+        }
+    }
+
+    Ok(lenses)
+}
+
+/// The lines and levels of the top-level section headers of a document
+fn section_headers(document: &Document) -> anyhow::Result<Vec<(usize, usize)>> {
+    let root = document.ast.root_node();
+    let mut cursor = root.walk();
+
+    let mut headers = Vec::new();
+
+    for node in root.children(&mut cursor) {
+        if !node.is_comment() {
+            continue;
+        }
+
+        let comment = document.contents.node_slice(&node)?.to_string();
+        if let Some((level, _title)) = parse_comment_as_section(&comment) {
+            headers.push((node.start_position().row, level));
+        }
+    }
+
+    Ok(headers)
+}
+
+fn run_lines_lens(
+    title: &str,
+    uri: &Url,
+    position: Position,
+    start: usize,
+    end: usize,
+) -> CodeLens {
+    CodeLens {
+        range: Range::new(position, position),
+        command: Some(Command {
+            title: String::from(title),
+            command: String::from(RUN_LINES_COMMAND),
+            arguments: Some(vec![
+                Value::String(uri.to_string()),
+                Value::from(start),
+                Value::from(end),
+            ]),
+        }),
+        data: None,
+    }
+}
+
+fn package_lenses(path: &Path, document: &Document) -> anyhow::Result<Vec<CodeLens>> {
+    let mut lenses = Vec::new();
+
+    if let Some(test_path) = test_file(path) {
+        if let Some(lens) = open_lens("Go to tests", &test_path) {
+            lenses.push(lens);
+        }
+        return Ok(lenses);
+    }
+
+    if !is_test_file(path) {
+        return Ok(lenses);
+    }
+
+    if let Some(lens) = source_file(path).and_then(|path| open_lens("Go to source", &path)) {
+        lenses.push(lens);
+    }
+
+    let path_arg = Value::String(path.to_string_lossy().to_string());
+
+    lenses.push(CodeLens {
+        range: Range::new(Position::new(0, 0), Position::new(0, 0)),
+        command: Some(Command {
+            title: String::from("Run file"),
+            command: String::from(RUN_FILE_COMMAND),
+            arguments: Some(vec![path_arg.clone()]),
+        }),
+        data: None,
+    });
+
+    let root = document.ast.root_node();
+    let mut cursor = root.walk();
+
+    for node in root.children(&mut cursor) {
+        let Some(description) = test_that_description(&node, &document.contents)? else {
+            continue;
+        };
+
+        let start = convert_point_to_position(&document.contents, node.start_position());
+
+        lenses.push(CodeLens {
+            range: Range::new(start, start),
+            command: Some(Command {
+                title: String::from("Run test"),
+                command: String::from(RUN_TEST_COMMAND),
+                arguments: Some(vec![path_arg.clone(), Value::String(description)]),
+            }),
+            data: None,
+        });
+    }
+
+    Ok(lenses)
+}
+
+/// Runs the code of a code lens at the console
+///
+/// The code is evaluated at idle time, so its output is streamed to the
+/// console through the kernel.
+pub(crate) fn execute_command(
+    command: &str,
+    arguments: &[Value],
+    state: &WorldState,
+) -> anyhow::Result<()> {
+    if command == RUN_LINES_COMMAND {
+        return execute_run_lines(arguments, state);
+    }
+
+    let Some(Value::String(path)) = arguments.first() else {
+        return Err(anyhow!("`{command}` requires a file path"));
+    };
+
+    let description = match command {
+        RUN_FILE_COMMAND => None,
+        RUN_TEST_COMMAND => {
+            let Some(Value::String(description)) = arguments.get(1) else {
+                return Err(anyhow!("`{command}` requires a test description"));
+            };
+            Some(description.clone())
+        },
+        _ => return Err(anyhow!("Unknown command `{command}`")),
+    };
+
+    let path = path.clone();
+
+    r_task::spawn_idle(move || async move {
+        let result = RFunction::new("testthat", "test_file")
+            .add(path.as_str())
+            .param("desc", description)
+            .call();
+
+        if let Err(err) = result {
+            log::error!("Can't run tests in '{path}': {err}");
+        }
+    });
+
+    Ok(())
+}
+
+fn execute_run_lines(arguments: &[Value], state: &WorldState) -> anyhow::Result<()> {
+    let [Value::String(uri), start, end] = arguments else {
+        return Err(anyhow!(
+            "`{RUN_LINES_COMMAND}` requires a URI and a range of lines"
+        ));
+    };
+    let (Some(start), Some(end)) = (start.as_u64(), end.as_u64()) else {
+        return Err(anyhow!("`{RUN_LINES_COMMAND}` requires line numbers"));
+    };
+
+    let uri = Url::parse(uri)?;
+    let document = state.get_document(&uri)?;
+    let code = lines_code(&document.contents, start as usize, end as usize);
+
+    r_task::spawn_idle(move || async move {
+        let result = RFunction::new("base", "parse")
+            .param("text", code.as_str())
+            .param("keep.source", false)
+            .call()
+            .and_then(|exprs| {
+                RFunction::new("base", "source")
+                    .param("exprs", exprs)
+                    .param("echo", true)
+                    .call()
+            });
+
+        if let Err(err) = result {
+            log::error!("Can't run lines of '{uri}': {err}");
+        }
+    });
+
+    Ok(())
+}
+
+/// The code of the lines from `start` to `end` (exclusive)
+fn lines_code(contents: &Rope, start: usize, end: usize) -> String {
+    let end = end.min(contents.len_lines());
+    let start = start.min(end);
+
+    let start = contents.line_to_char(start);
+    let end = contents.line_to_char(end);
+
+    contents.slice(start..end).to_string()
+}
+
+fn open_lens(title: &str, path: &Path) -> Option<CodeLens> {
+    let uri = Url::from_file_path(path).ok()?;
+
+    Some(CodeLens {
+        range: Range::new(Position::new(0, 0), Position::new(0, 0)),
+        command: Some(Command {
+            title: String::from(title),
+            command: String::from(OPEN_COMMAND),
+            arguments: Some(vec![Value::String(uri.to_string())]),
+        }),
+        data: None,
+    })
+}
+
+/// The test file of a package file in `R/`, if it exists
+fn test_file(path: &Path) -> Option<PathBuf> {
+    let dir = path.parent()?;
+    if dir.file_name()? != "R" {
+        return None;
+    }
+
+    let name = path.file_name()?.to_str()?;
+    let package = dir.parent()?;
+
+    let test = package
+        .join("tests")
+        .join("testthat")
+        .join(format!("test-{name}"));
+
+    test.is_file().then_some(test)
+}
+
+fn is_test_file(path: &Path) -> bool {
+    let Some(dir) = path.parent() else {
+        return false;
+    };
+    if dir.file_name().is_none_or(|name| name != "testthat") {
+        return false;
+    }
+
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with("test"))
+}
+
+/// The package file in `R/` tested by a test file, if it exists
+fn source_file(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+    let name = name
+        .strip_prefix("test-")
+        .or_else(|| name.strip_prefix("test_"))?;
+
+    let package = path.parent()?.parent()?.parent()?;
+    let source = package.join("R").join(name);
+
+    source.is_file().then_some(source)
+}
+
+/// The description of a `test_that()` call, when supplied as a string
+fn test_that_description(node: &Node, contents: &Rope) -> anyhow::Result<Option<String>> {
+    if !node.is_call() {
+        return Ok(None);
+    }
+
+    let Some(mut callee) = node.child_by_field_name("function") else {
+        return Ok(None);
+    };
+
+    // Allow `testthat::test_that()`
+    if callee.is_namespace_operator() {
+        callee = match callee.child_by_field_name("rhs") {
+            Some(rhs) => rhs,
+            None => return Ok(None),
+        };
+    }
+
+    if !callee.is_identifier() || contents.node_slice(&callee)?.to_string() != "test_that" {
+        return Ok(None);
+    }
+
+    let Some(arguments) = node.child_by_field_name("arguments") else {
+        return Ok(None);
+    };
+
+    let mut cursor = arguments.walk();
+    let Some(first) = arguments
+        .children_by_field_name("argument", &mut cursor)
+        .next()
+        .and_then(|argument| argument.child_by_field_name("value"))
+    else {
+        return Ok(None);
+    };
+
+    if !first.is_string() {
+        return Ok(None);
+    }
+
+    let Some(content) = first.child_by_field_name("content") else {
+        return Ok(None);
+    };
+
+    Ok(Some(contents.node_slice(&content)?.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+    use url::Url;
+
+    use crate::lsp::code_lens::code_lenses;
+    use crate::lsp::code_lens::lines_code;
+    use crate::lsp::code_lens::RUN_FILE_COMMAND;
+    use crate::lsp::code_lens::RUN_LINES_COMMAND;
+    use crate::lsp::code_lens::RUN_TEST_COMMAND;
+    use crate::lsp::documents::Document;
+
+    #[test]
+    fn test_code_lenses_sections() {
+        let uri = Url::parse("untitled:Untitled-1").unwrap();
+        let code = "\
+library(dplyr)
+
+# Load ----
+x <- 1
+
+## Clean ----
+y <- x
+
+# Model ----
+fit <- lm(y ~ x)
+";
+        let document = Document::new(code, None);
+        let lenses = code_lenses(&uri, &document).unwrap();
+
+        let lenses: Vec<_> = lenses
+            .iter()
+            .map(|lens| {
+                let command = lens.command.as_ref().unwrap();
+                assert_eq!(command.command, RUN_LINES_COMMAND);
+                let arguments = command.arguments.clone().unwrap();
+                (
+                    lens.range.start.line,
+                    command.title.as_str(),
+                    arguments[1].as_u64().unwrap(),
+                    arguments[2].as_u64().unwrap(),
+                )
+            })
+            .collect();
+
+        // Sections include their subsections
+        assert_eq!(lenses, vec![
+            (2, "Run section", 2, 8),
+            (2, "Run above", 0, 2),
+            (5, "Run section", 5, 8),
+            (5, "Run above", 0, 5),
+            (8, "Run section", 8, 11),
+            (8, "Run above", 0, 8),
+        ]);
+
+        let contents = &document.contents;
+        assert_eq!(lines_code(contents, 5, 8), "## Clean ----\ny <- x\n\n");
+        assert_eq!(
+            lines_code(contents, 8, 100),
+            "# Model ----\nfit <- lm(y ~ x)\n"
+        );
+    }
+
+    #[test]
+    fn test_code_lenses_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("R").join("foo.R");
+        let test = dir.path().join("tests").join("testthat").join("test-foo.R");
+
+        std::fs::create_dir_all(source.parent().unwrap()).unwrap();
+        std::fs::create_dir_all(test.parent().unwrap()).unwrap();
+        std::fs::write(&source, "").unwrap();
+        std::fs::write(&test, "").unwrap();
+
+        let source_uri = Url::from_file_path(&source).unwrap();
+        let test_uri = Url::from_file_path(&test).unwrap();
+
+        // Source file
+        let document = Document::new("foo <- function() 1", None);
+        let lenses = code_lenses(&source_uri, &document).unwrap();
+        assert_eq!(lenses.len(), 1);
+
+        let command = lenses[0].command.as_ref().unwrap();
+        assert_eq!(command.title, "Go to tests");
+        assert_eq!(
+            command.arguments,
+            Some(vec![Value::String(test_uri.to_string())])
+        );
+
+        // Test file
+        let document = Document::new(
+            "
+test_that('foo works', {
+  expect_equal(foo(), 1)
+})
+
+testthat::test_that('foo still works', {})
+
+helper <- function() test_that('nested', {})
+",
+            None,
+        );
+        let lenses = code_lenses(&test_uri, &document).unwrap();
+
+        let commands: Vec<_> = lenses
+            .iter()
+            .map(|lens| lens.command.as_ref().unwrap())
+            .collect();
+        let titles: Vec<_> = commands
+            .iter()
+            .map(|command| command.title.as_str())
+            .collect();
+        assert_eq!(titles, vec![
+            "Go to source",
+            "Run file",
+            "Run test",
+            "Run test"
+        ]);
+
+        assert_eq!(
+            commands[0].arguments,
+            Some(vec![Value::String(source_uri.to_string())])
+        );
+        assert_eq!(commands[1].command, RUN_FILE_COMMAND);
+        assert_eq!(commands[2].command, RUN_TEST_COMMAND);
+
+        let path = Value::String(test.to_string_lossy().to_string());
+        assert_eq!(
+            commands[2].arguments,
+            Some(vec![path.clone(), Value::String(String::from("foo works"))])
+        );
+        assert_eq!(
+            commands[3].arguments,
+            Some(vec![path, Value::String(String::from("foo still works"))])
+        );
+        assert_eq!(lenses[3].range.start.line, 5);
+    }
+
+    #[test]
+    fn test_code_lenses_outside_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("script.R");
+        let uri = Url::from_file_path(&file).unwrap();
+
+        let document = Document::new("test_that('foo', {})", None);
+        assert!(code_lenses(&uri, &document).unwrap().is_empty());
+
+        // Source files without tests don't get a lens
+        let file = dir.path().join("R").join("bar.R");
+        let uri = Url::from_file_path(&file).unwrap();
+        assert!(code_lenses(&uri, &document).unwrap().is_empty());
+    }
+}
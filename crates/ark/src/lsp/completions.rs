@@ -7,6 +7,7 @@
 
 mod completion_context;
 mod completion_item;
+pub(crate) mod frecency;
 mod function_context;
 mod provide;
 mod resolve;
@@ -18,3 +19,8 @@ mod tests;
 
 pub(crate) use provide::provide_completions;
 pub(crate) use resolve::resolve_completion;
+pub(crate) use sources::composite::data_mask::data_mask_data;
+pub(crate) use sources::composite::formula::formula_term;
+pub(crate) use sources::unique::comment::roxygen_tag_documentation;
+pub(crate) use sources::unique::help::help_topic_node;
+pub(crate) use sources::utils::COMPLETION_EVAL_TIMEOUT;
@@ -6,7 +6,9 @@
 //
 
 use std::cell::OnceCell;
+use std::path::PathBuf;
 
+use tower_lsp::lsp_types::Url;
 use tree_sitter::Node;
 
 use crate::lsp::completions::function_context::FunctionContext;
@@ -18,6 +20,9 @@ use crate::treesitter::node_find_containing_call;
 pub(crate) struct CompletionContext<'a> {
     pub(crate) document_context: &'a DocumentContext<'a>,
     pub(crate) state: &'a WorldState,
+    /// The URI of the document, if known. Used to resolve paths relative to
+    /// the document.
+    pub(crate) uri: Option<&'a Url>,
     pipe_root_cell: OnceCell<Option<PipeRoot>>,
     containing_call_cell: OnceCell<Option<Node<'a>>>,
     function_context_cell: OnceCell<FunctionContext>,
@@ -28,12 +33,19 @@ impl<'a> CompletionContext<'a> {
         Self {
             document_context,
             state,
+            uri: None,
             pipe_root_cell: OnceCell::new(),
             containing_call_cell: OnceCell::new(),
             function_context_cell: OnceCell::new(),
         }
     }
 
+    /// The directory containing the document, if it lives on disk
+    pub fn document_dir(&self) -> Option<PathBuf> {
+        let path = self.uri?.to_file_path().ok()?;
+        Some(path.parent()?.to_path_buf())
+    }
+
     pub fn pipe_root(&self) -> Option<PipeRoot> {
         let call_node = self.containing_call_node();
 
@@ -59,6 +59,35 @@ pub(super) fn completion_item(
     })
 }
 
+pub(super) fn completion_item_from_snippet(
+    label: &str,
+    snippet: &str,
+    description: &str,
+) -> anyhow::Result<CompletionItem> {
+    let mut item = completion_item(label, CompletionData::Snippet {
+        text: snippet.to_string(),
+    })?;
+
+    // Markup shows up in the quick suggestion documentation window,
+    // so you can see what the snippet expands to
+    let markup = vec!["```r", snippet, "```"].join("\n");
+    let markup = MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: markup,
+    };
+
+    item.documentation = Some(Documentation::MarkupContent(markup));
+    item.kind = Some(CompletionItemKind::SNIPPET);
+    item.insert_text = Some(snippet.to_string());
+    item.insert_text_format = Some(InsertTextFormat::SNIPPET);
+    item.label_details = Some(CompletionItemLabelDetails {
+        detail: None,
+        description: Some(description.to_string()),
+    });
+
+    Ok(item)
+}
+
 pub(super) fn completion_item_from_file(entry: DirEntry) -> anyhow::Result<CompletionItem> {
     let name = entry.file_name().to_string_lossy().to_string();
     let mut item = completion_item(name, CompletionData::File { path: entry.path() })?;
@@ -315,6 +344,61 @@ pub(super) unsafe fn completion_item_from_dataset(name: &str) -> anyhow::Result<
     Ok(item)
 }
 
+pub(super) fn completion_item_from_option(name: &str) -> anyhow::Result<CompletionItem> {
+    let mut item = completion_item(name, CompletionData::Unknown)?;
+    item.kind = Some(CompletionItemKind::PROPERTY);
+
+    if let Some(description) = r_option_description(name) {
+        item.documentation = Some(Documentation::MarkupContent(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: description.to_string(),
+        }));
+    }
+
+    Ok(item)
+}
+
+/// Short descriptions of well-known base R options, adapted from `?options`
+fn r_option_description(name: &str) -> Option<&'static str> {
+    let description = match name {
+        "browser" => "Default HTML browser used by `help.start()` and `browseURL()`.",
+        "contrasts" => "Default contrasts used in model fitting, such as with `aov()` or `lm()`.",
+        "digits" => "Number of significant digits to use when printing numeric values. Defaults to `7`.",
+        "digits.secs" => "Maximum number of digits to print when formatting time values in seconds.",
+        "download.file.method" => "Method used by `download.file()`, e.g. `\"libcurl\"`.",
+        "echo" => "Whether input should be echoed in non-interactive sessions.",
+        "editor" => "Editor used by `edit()` and related functions.",
+        "encoding" => "Default encoding for connections, e.g. `\"native.enc\"`.",
+        "error" => "Function or expression called when an error occurs, e.g. `recover`.",
+        "expressions" => "Limit on the number of nested expressions that will be evaluated.",
+        "help_type" => "Default type of help, `\"text\"` or `\"html\"`.",
+        "HTTPUserAgent" => "User agent string sent by R when making HTTP requests.",
+        "keep.source" => "Whether the source code of functions is kept so it can be printed with comments.",
+        "max.print" => "Maximum number of entries printed by `print()`.",
+        "mc.cores" => "Maximum number of cores used by the parallel functions of the parallel package.",
+        "na.action" => "Name of the function used to handle missing values in model frames, e.g. `\"na.omit\"`.",
+        "Ncpus" => "Number of parallel processes used by `install.packages()` when building from source.",
+        "nwarnings" => "Maximum number of warnings stored for `warnings()`.",
+        "OutDec" => "Character used as decimal separator when printing numbers.",
+        "pkgType" => "Default type of packages installed by `install.packages()`.",
+        "prompt" => "String used as the console prompt.",
+        "repos" => "URLs of the repositories used by `install.packages()` and related functions.",
+        "scipen" => "Penalty applied when deciding between fixed and scientific notation. Positive values favour fixed notation.",
+        "showWarnCalls" => "Whether a call stack is printed with warnings.",
+        "stringsAsFactors" => "Default for the `stringsAsFactors` argument of `data.frame()` and `read.table()`.",
+        "timeout" => "Timeout in seconds for some internet operations, such as `download.file()`. Defaults to `60`.",
+        "useFancyQuotes" => "Whether to use directional quotes in messages.",
+        "verbose" => "Whether R reports extra information on progress.",
+        "warn" => "How warnings are handled. Negative values ignore them, `0` defers them, `1` prints them immediately, and `2` turns them into errors.",
+        "warning.length" => "Maximum length in characters of error and warning messages.",
+        "warnPartialMatchDollar" => "Whether partial matching by `$` emits a warning.",
+        "width" => "Number of columns used when printing to the console.",
+        _ => return None,
+    };
+
+    Some(description)
+}
+
 pub(super) unsafe fn completion_item_from_data_variable(
     name: &str,
     owner: &str,
@@ -337,6 +421,39 @@ pub(super) unsafe fn completion_item_from_data_variable(
     Ok(item)
 }
 
+/// Completion item for a member of an R6 or S4 object, see
+/// `.ps.completions.getExtractorMembers()`
+pub(super) fn completion_item_from_member(
+    name: &str,
+    kind: &str,
+    detail: &str,
+    topic: &str,
+) -> anyhow::Result<CompletionItem> {
+    let mut item = completion_item(name.to_string(), CompletionData::Member {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        topic: topic.to_string(),
+    })?;
+
+    if !is_symbol_valid(name) {
+        item.insert_text = Some(sym_quote(name));
+    }
+
+    item.kind = Some(match kind {
+        "method" => CompletionItemKind::METHOD,
+        "active" => CompletionItemKind::PROPERTY,
+        _ => CompletionItemKind::FIELD,
+    });
+
+    item.detail = match (kind, detail) {
+        ("active", _) => Some("Active binding".to_string()),
+        (_, "") => None,
+        (_, detail) => Some(detail.to_string()),
+    };
+
+    Ok(item)
+}
+
 pub(super) unsafe fn completion_item_from_object(
     name: &str,
     object: SEXP,
@@ -0,0 +1,163 @@
+//
+// frecency.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use tree_sitter::Node;
+use tree_sitter::Parser;
+
+use crate::treesitter::NodeTypeExt;
+
+/// Number of events after which the weight of a use is halved
+const HALF_LIFE: f64 = 50.0;
+
+/// Maximum number of symbols we keep track of
+const MAX_ENTRIES: usize = 2000;
+
+/// Usage statistics of symbols, used to rank completions
+///
+/// Symbols are recorded when they are evaluated at the console or appear in
+/// a saved document. Each recording is an event that advances a logical
+/// clock. The score of a symbol is the number of events it was recorded in,
+/// with older events weighing less than recent ones.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct Frecency {
+    entries: HashMap<String, FrecencyEntry>,
+    clock: u64,
+}
+
+#[derive(Clone, Debug)]
+struct FrecencyEntry {
+    /// Decayed number of uses, as of `last_used`
+    weight: f64,
+    last_used: u64,
+}
+
+impl Frecency {
+    /// Records the symbols used in a chunk of R code
+    pub(crate) fn record_code(&mut self, code: &str) {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_r::LANGUAGE.into())
+            .unwrap();
+
+        let Some(tree) = parser.parse(code, None) else {
+            return;
+        };
+
+        self.record(code_symbols(tree.root_node(), code));
+    }
+
+    /// Records a single event in which all `symbols` were used
+    pub(crate) fn record(&mut self, symbols: HashSet<String>) {
+        if symbols.is_empty() {
+            return;
+        }
+
+        self.clock += 1;
+
+        for symbol in symbols {
+            let weight = self.score(&symbol).unwrap_or(0.0) + 1.0;
+            self.entries.insert(symbol, FrecencyEntry {
+                weight,
+                last_used: self.clock,
+            });
+        }
+
+        if self.entries.len() > MAX_ENTRIES {
+            self.prune();
+        }
+    }
+
+    /// The current score of `symbol`, if it has ever been recorded
+    pub(crate) fn score(&self, symbol: &str) -> Option<f64> {
+        let entry = self.entries.get(symbol)?;
+        let age = (self.clock - entry.last_used) as f64;
+        Some(entry.weight * 0.5_f64.powf(age / HALF_LIFE))
+    }
+
+    /// Drops the least relevant half of the entries
+    fn prune(&mut self) {
+        let mut scores: Vec<(String, f64)> = self
+            .entries
+            .keys()
+            .map(|symbol| (symbol.clone(), self.score(symbol).unwrap_or(0.0)))
+            .collect();
+
+        scores.sort_by(|(_, lhs), (_, rhs)| rhs.total_cmp(lhs));
+
+        for (symbol, _) in scores.into_iter().skip(MAX_ENTRIES / 2) {
+            self.entries.remove(&symbol);
+        }
+    }
+}
+
+/// Collects the identifiers of a syntax tree, excluding argument names
+pub(crate) fn code_symbols(root: Node, contents: &str) -> HashSet<String> {
+    let mut symbols = HashSet::new();
+    let mut cursor = root.walk();
+
+    loop {
+        let node = cursor.node();
+
+        if node.is_identifier() && cursor.field_name() != Some("name") {
+            if let Ok(text) = node.utf8_text(contents.as_bytes()) {
+                symbols.insert(text.to_string());
+            }
+        }
+
+        if cursor.goto_first_child() || cursor.goto_next_sibling() {
+            continue;
+        }
+
+        loop {
+            if !cursor.goto_parent() {
+                return symbols;
+            }
+            if cursor.goto_next_sibling() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lsp::completions::frecency::Frecency;
+
+    #[test]
+    fn test_frecency_record_code() {
+        let mut frecency = Frecency::default();
+        frecency.record_code("x <- mutate(df, y = mean(z))");
+
+        assert_eq!(frecency.score("mutate"), Some(1.0));
+        assert_eq!(frecency.score("mean"), Some(1.0));
+        assert_eq!(frecency.score("df"), Some(1.0));
+
+        // Argument names are not symbols
+        assert_eq!(frecency.score("y"), None);
+        assert_eq!(frecency.score("foo"), None);
+    }
+
+    #[test]
+    fn test_frecency_decay() {
+        let mut frecency = Frecency::default();
+        frecency.record_code("old()");
+        frecency.record_code("old()");
+        frecency.record_code("recent()");
+
+        assert!(frecency.score("old").unwrap() > frecency.score("recent").unwrap());
+
+        for _ in 0..100 {
+            frecency.record_code("recent()");
+        }
+
+        assert!(frecency.score("old").unwrap() < frecency.score("recent").unwrap());
+        assert!(frecency.score("old").unwrap() < 1.0);
+    }
+}
@@ -6,6 +6,7 @@
 //
 
 use tower_lsp::lsp_types::CompletionItem;
+use tower_lsp::lsp_types::Url;
 
 use crate::lsp::completions::completion_context::CompletionContext;
 use crate::lsp::completions::sources::composite;
@@ -20,6 +21,7 @@ use crate::treesitter::NodeTypeExt;
 pub(crate) fn provide_completions(
     document_context: &DocumentContext,
     state: &WorldState,
+    uri: Option<&Url>,
 ) -> anyhow::Result<Vec<CompletionItem>> {
     log::info!(
         "provide_completions() - Completion node text: '{node_text}', Node type: '{node_type:?}'",
@@ -28,7 +30,8 @@ pub(crate) fn provide_completions(
         node_type = document_context.node.node_type()
     );
 
-    let completion_context = CompletionContext::new(document_context, state);
+    let mut completion_context = CompletionContext::new(document_context, state);
+    completion_context.uri = uri;
 
     // Try unique sources first
     if let Some(completions) = unique::get_completions(&completion_context)? {
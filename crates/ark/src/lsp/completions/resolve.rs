@@ -6,6 +6,8 @@
 //
 
 use anyhow::bail;
+use harp::exec::RFunction;
+use harp::exec::RFunctionExt;
 use stdext::*;
 use tower_lsp::lsp_types::CompletionItem;
 use tower_lsp::lsp_types::Documentation;
@@ -31,12 +33,18 @@ pub fn resolve_completion(item: &mut CompletionItem) -> anyhow::Result<bool> {
         CompletionData::Function { name, package } => {
             resolve_function_completion_item(item, name.as_str(), package.as_deref())
         },
+        CompletionData::HelpTopic { topic, package } => {
+            resolve_help_topic_completion_item(item, topic.as_str(), package.as_str())
+        },
         CompletionData::Package { name } => resolve_package_completion_item(item, name.as_str()),
         CompletionData::Parameter { name, function } => {
             resolve_parameter_completion_item(item, name.as_str(), function.as_str())
         },
         CompletionData::Object { name: _ } => Ok(false),
         CompletionData::Keyword { name: _ } => Ok(false),
+        CompletionData::Member { name, kind, topic } => {
+            resolve_member_completion_item(item, name.as_str(), kind.as_str(), topic.as_str())
+        },
         CompletionData::RoxygenTag { tag: _ } => Ok(false),
         CompletionData::ScopeVariable { name: _ } => Ok(false),
         CompletionData::ScopeParameter { name: _ } => Ok(false),
@@ -66,6 +74,25 @@ fn resolve_package_completion_item(
     Ok(true)
 }
 
+fn resolve_help_topic_completion_item(
+    item: &mut CompletionItem,
+    topic: &str,
+    package: &str,
+) -> anyhow::Result<bool> {
+    let help = unwrap!(RHtmlHelp::from_topic(topic, Some(package))?, None => {
+        return Ok(false);
+    });
+
+    let markup = MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: help.markdown()?,
+    };
+
+    item.documentation = Some(Documentation::MarkupContent(markup));
+
+    Ok(true)
+}
+
 fn resolve_function_completion_item(
     item: &mut CompletionItem,
     name: &str,
@@ -109,3 +136,29 @@ fn resolve_parameter_completion_item(
     item.documentation = Some(Documentation::MarkupContent(markup));
     Ok(true)
 }
+
+fn resolve_member_completion_item(
+    item: &mut CompletionItem,
+    name: &str,
+    kind: &str,
+    topic: &str,
+) -> anyhow::Result<bool> {
+    let documentation = RFunction::from(".ps.completions.getMemberDocumentation")
+        .param("topic", topic)
+        .param("name", name)
+        .param("kind", kind)
+        .call()?;
+
+    if documentation.is_null() {
+        return Ok(false);
+    }
+
+    let markup = MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: String::try_from(documentation)?,
+    };
+
+    item.documentation = Some(Documentation::MarkupContent(markup));
+
+    Ok(true)
+}
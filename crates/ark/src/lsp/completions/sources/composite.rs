@@ -6,10 +6,13 @@
 //
 
 mod call;
+pub(crate) mod data_mask;
 mod document;
+pub(crate) mod formula;
 mod keyword;
 pub(crate) mod pipe;
 mod search_path;
+mod snippet;
 mod subset;
 mod workspace;
 
@@ -21,6 +24,7 @@ use tower_lsp::lsp_types::CompletionItemKind;
 use tree_sitter::Node;
 
 use crate::lsp::completions::completion_context::CompletionContext;
+use crate::lsp::completions::frecency::Frecency;
 use crate::lsp::completions::sources::collect_completions;
 use crate::lsp::completions::sources::utils::has_priority_prefix;
 use crate::lsp::completions::sources::CompletionSource;
@@ -69,6 +73,16 @@ pub(crate) fn get_completions(
     // pipe completions, such as column names of a data frame
     push_completions(pipe::PipeSource, completion_context, &mut completions)?;
 
+    // data-masking completions, such as column names in `filter(df, )`
+    push_completions(
+        data_mask::DataMaskSource,
+        completion_context,
+        &mut completions,
+    )?;
+
+    // special terms of model formulas, such as `s()` in `gam(y ~ )`
+    push_completions(formula::FormulaSource, completion_context, &mut completions)?;
+
     // subset completions (`[` or `[[`)
     push_completions(subset::SubsetSource, completion_context, &mut completions)?;
 
@@ -79,6 +93,7 @@ pub(crate) fn get_completions(
         is_identifier_like(completion_context.document_context.node)
     {
         push_completions(keyword::KeywordSource, completion_context, &mut completions)?;
+        push_completions(snippet::SnippetSource, completion_context, &mut completions)?;
 
         push_completions(
             search_path::SearchPathSource,
@@ -100,7 +115,7 @@ pub(crate) fn get_completions(
     }
 
     // Simplify to plain old CompletionItems and sort them
-    let completions = finalize_completions(completions);
+    let completions = finalize_completions(completions, &completion_context.state.frecency);
 
     Ok(Some(completions))
 }
@@ -141,13 +156,14 @@ where
 /// Produce plain old CompletionItems and sort them
 fn finalize_completions(
     completions: HashMap<CompletionItemKey, CompletionItemWithSource>,
+    frecency: &Frecency,
 ) -> Vec<CompletionItem> {
     let mut items: Vec<CompletionItem> = completions
         .into_values()
         .map(|completion_with_source| completion_with_source.item)
         .collect();
 
-    sort_completions(&mut items);
+    sort_completions(&mut items, frecency);
 
     items
 }
@@ -157,7 +173,10 @@ fn finalize_completions(
 // to 'bin' different completion types differently; e.g. we place parameter
 // completions at the front, followed by variable completions (like pipe
 // completions and subset completions), followed by anything else.
-fn sort_completions(completions: &mut Vec<CompletionItem>) {
+//
+// Within each bin except for arguments, symbols recently used at the console
+// or in documents come first, ordered by their frecency score.
+fn sort_completions(completions: &mut Vec<CompletionItem>, frecency: &Frecency) {
     for item in completions {
         // Start with existing `sort_text` if one exists
         let sort_text = item.sort_text.take();
@@ -172,6 +191,13 @@ fn sort_completions(completions: &mut Vec<CompletionItem>) {
             None => item.label.clone(),
         };
 
+        let sort_text = match frecency.score(&item.label) {
+            Some(score) if item.kind != Some(CompletionItemKind::FIELD) => {
+                join!["0-", frecency_rank(score), "-", sort_text]
+            },
+            _ => sort_text,
+        };
+
         case! {
             // Argument name
             item.kind == Some(CompletionItemKind::FIELD) => {
@@ -194,6 +220,12 @@ fn sort_completions(completions: &mut Vec<CompletionItem>) {
     }
 }
 
+/// Two digits rank, lower for higher scores
+fn frecency_rank(score: f64) -> String {
+    let rank = (99.0 / (1.0 + score)).round() as u32;
+    format!("{:02}", rank.min(99))
+}
+
 fn is_identifier_like(x: Node) -> bool {
     if x.is_identifier() {
         // Obvious case
@@ -216,7 +248,10 @@ fn is_identifier_like(x: Node) -> bool {
 
 #[cfg(test)]
 mod tests {
+    use tower_lsp::lsp_types::Position;
+
     use crate::fixtures::point_from_cursor;
+    use crate::fixtures::LspClient;
     use crate::lsp::completions::completion_context::CompletionContext;
     use crate::lsp::completions::sources::composite::get_completions;
     use crate::lsp::completions::sources::composite::is_identifier_like;
@@ -249,36 +284,46 @@ mod tests {
 
     #[test]
     fn test_get_completions_on_empty_document() {
-        r_task(|| {
-            let (text, point) = point_from_cursor("@");
-            let document = Document::new(text.as_str(), None);
-            let document_context = DocumentContext::new(&document, point, None);
-            let state = WorldState::default();
-            let context = CompletionContext::new(&document_context, &state);
+        let (text, point) = point_from_cursor("@");
 
-            assert!(context.document_context.node.is_program());
-
-            let completions = get_completions(&context).unwrap();
-            assert!(completions.is_some());
-            assert!(!completions.unwrap().is_empty());
-        });
+        let client = LspClient::new();
+        let uri = client.open_document("test.R", &text);
+        let position = Position::new(point.row as u32, point.column as u32);
+        let completions = client.completions(&uri, position);
+        assert!(!completions.is_empty());
     }
 
     #[test]
     fn test_get_completions_on_empty_line_in_non_empty_document() {
+        let code = "x <- 1:3\n@\nrnorm(3)";
+        let (text, point) = point_from_cursor(code);
+
+        let client = LspClient::new();
+        let uri = client.open_document("test.R", &text);
+        let position = Position::new(point.row as u32, point.column as u32);
+        let completions = client.completions(&uri, position);
+        assert!(!completions.is_empty());
+    }
+
+    #[test]
+    fn test_get_completions_ranks_recently_used_symbols_first() {
         r_task(|| {
-            let code = "x <- 1:3\n@\nrnorm(3)";
-            let (text, point) = point_from_cursor(code);
+            let (text, point) = point_from_cursor("nc@");
             let document = Document::new(text.as_str(), None);
             let document_context = DocumentContext::new(&document, point, None);
-            let state = WorldState::default();
-            let context = CompletionContext::new(&document_context, &state);
 
-            assert!(context.document_context.node.is_program());
+            let sort_text = |state: &WorldState, label: &str| {
+                let context = CompletionContext::new(&document_context, state);
+                let completions = get_completions(&context).unwrap().unwrap();
+                let item = completions.into_iter().find(|item| item.label == label);
+                item.unwrap().sort_text.unwrap()
+            };
+
+            let mut state = WorldState::default();
+            assert!(sort_text(&state, "nchar") < sort_text(&state, "ncol"));
 
-            let completions = get_completions(&context).unwrap();
-            assert!(completions.is_some());
-            assert!(!completions.unwrap().is_empty());
+            state.frecency.record_code("ncol(x)");
+            assert!(sort_text(&state, "ncol") < sort_text(&state, "nchar"));
         });
     }
 }
@@ -19,6 +19,7 @@ use crate::lsp::completions::completion_item::completion_item_from_parameter;
 use crate::lsp::completions::sources::utils::call_node_position_type;
 use crate::lsp::completions::sources::utils::set_sort_text_by_first_appearance;
 use crate::lsp::completions::sources::utils::CallNodePositionType;
+use crate::lsp::completions::sources::utils::COMPLETION_EVAL_TIMEOUT;
 use crate::lsp::completions::sources::CompletionSource;
 use crate::lsp::document_context::DocumentContext;
 use crate::lsp::indexer;
@@ -129,6 +130,7 @@ fn get_first_argument(context: &DocumentContext, node: &Node) -> anyhow::Result<
 
     let options = RParseEvalOptions {
         forbid_function_calls: true,
+        timeout: Some(COMPLETION_EVAL_TIMEOUT),
         ..Default::default()
     };
 
@@ -148,6 +150,10 @@ fn get_first_argument(context: &DocumentContext, node: &Node) -> anyhow::Result<
                 log::trace!("Can't evaluate first argument: {message}");
                 return Ok(None);
             },
+            Error::Timeout { .. } => {
+                log::warn!("Can't evaluate first argument: {err}");
+                return Ok(None);
+            },
             _ => {
                 log::error!("Can't evaluate first argument: {err}");
                 return Ok(None);
@@ -193,6 +199,7 @@ fn completions_from_session_arguments(
     // for S3 methods based on `object`.
     let r_callable = harp::parse_eval(callable, RParseEvalOptions {
         forbid_function_calls: true,
+        timeout: Some(COMPLETION_EVAL_TIMEOUT),
         ..Default::default()
     });
 
@@ -0,0 +1,512 @@
+//
+// data_mask.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use harp::eval::RParseEvalOptions;
+use harp::utils::r_inherits;
+use tower_lsp::lsp_types::CompletionItem;
+use tree_sitter::Node;
+
+use crate::lsp::completions::completion_context::CompletionContext;
+use crate::lsp::completions::sources::common::subset::is_within_subset_delimiters;
+use crate::lsp::completions::sources::utils::completions_from_evaluated_object_names;
+use crate::lsp::completions::sources::utils::COMPLETION_EVAL_TIMEOUT;
+use crate::lsp::completions::sources::CompletionSource;
+use crate::lsp::document_context::DocumentContext;
+use crate::lsp::traits::rope::RopeExt;
+use crate::treesitter::BinaryOperatorType;
+use crate::treesitter::NodeType;
+use crate::treesitter::NodeTypeExt;
+use crate::treesitter::UnaryOperatorType;
+
+pub(super) struct DataMaskSource;
+
+impl CompletionSource for DataMaskSource {
+    fn name(&self) -> &'static str {
+        "data_mask"
+    }
+
+    fn provide_completions(
+        &self,
+        completion_context: &CompletionContext,
+    ) -> anyhow::Result<Option<Vec<CompletionItem>>> {
+        completions_from_data_mask(
+            completion_context.document_context,
+            &completion_context
+                .state
+                .config
+                .completions
+                .data_masking_functions,
+        )
+    }
+}
+
+/// Functions that evaluate some of their arguments in the context of the data
+/// frame supplied as first argument. Users can add their own with the
+/// `positron.r.completions.dataMaskingFunctions` setting.
+const DATA_MASKING_FUNCTIONS: &[&str] = &[
+    // dplyr
+    "add_count",
+    "arrange",
+    "count",
+    "distinct",
+    "filter",
+    "group_by",
+    "mutate",
+    "pull",
+    "reframe",
+    "relocate",
+    "rename",
+    "select",
+    "slice_max",
+    "slice_min",
+    "summarise",
+    "summarize",
+    "transmute",
+    // tidyr
+    "drop_na",
+    "fill",
+    "nest",
+    "pivot_longer",
+    "separate",
+    "unite",
+    "unnest",
+    // base
+    "subset",
+    "transform",
+    "with",
+    "within",
+];
+
+/// Names of the data argument, when supplied by name
+const DATA_ARGUMENT_NAMES: &[&str] = &[".data", "data"];
+
+/// Completions for the columns of a data frame inside data-masking verbs,
+/// e.g. `filter(df, <here>)` or `mutate(df, y = mean(<here>))`, inside
+/// formulas of calls with a `data` argument, e.g. `lm(y ~ <here>, data = df)`,
+/// inside ggplot2 aesthetics, e.g. `ggplot(df, aes(<here>))`, and inside
+/// data.table subsets, e.g. `dt[<here> > 1]`
+///
+/// When the data is piped in, as in `df |> filter(<here>)`, the columns are
+/// provided by the pipe source instead.
+fn completions_from_data_mask(
+    context: &DocumentContext,
+    functions: &[String],
+) -> anyhow::Result<Option<Vec<CompletionItem>>> {
+    const ENQUOTE: bool = false;
+
+    let Some(data) = data_mask_data(context, functions)? else {
+        return Ok(None);
+    };
+
+    completions_from_evaluated_object_names(&data, ENQUOTE, NodeType::Call)
+}
+
+/// The code of the data that is masking the cursor position, e.g. `df` in
+/// `filter(df, <here>)` or `lm(y ~ <here>, data = df)`. `functions` are
+/// data-masking functions supplied by the user on top of the builtin ones.
+pub(crate) fn data_mask_data(
+    context: &DocumentContext,
+    functions: &[String],
+) -> anyhow::Result<Option<String>> {
+    let Some((call, kind)) = find_data_mask_call(context, functions)? else {
+        return Ok(None);
+    };
+
+    let data = match kind {
+        DataMaskKind::Verb => {
+            if is_piped(&call, context)? {
+                return Ok(None);
+            }
+            data_argument(&call, context)?
+        },
+        DataMaskKind::Formula => named_data_argument(&call, context)?,
+        DataMaskKind::Aes => aes_data_argument(&call, context)?,
+        DataMaskKind::DataTable => call.child_by_field_name("function"),
+    };
+
+    let Some(data) = data else {
+        return Ok(None);
+    };
+
+    // No column completions for the data argument itself
+    if data.start_position() <= context.point && context.point <= data.end_position() {
+        return Ok(None);
+    }
+
+    Ok(Some(
+        context.document.contents.node_slice(&data)?.to_string(),
+    ))
+}
+
+#[derive(Debug, PartialEq)]
+enum DataMaskKind {
+    /// A data-masking verb like `filter()`
+    Verb,
+    /// A formula argument of a call with a `data` argument, like `lm()`
+    Formula,
+    /// A ggplot2 aesthetic mapping, `aes()`
+    Aes,
+    /// The `[` method of data.table
+    DataTable,
+}
+
+/// Walks up the tree to find the innermost data-masking call we are an
+/// argument of, stopping at function boundaries
+fn find_data_mask_call<'tree>(
+    context: &DocumentContext<'tree>,
+    functions: &[String],
+) -> anyhow::Result<Option<(Node<'tree>, DataMaskKind)>> {
+    let mut node = context.node;
+    let mut in_formula = false;
+
+    loop {
+        if node.is_braced_expression() || node.is_function_definition() {
+            return Ok(None);
+        }
+
+        if matches!(
+            node.node_type(),
+            NodeType::BinaryOperator(BinaryOperatorType::Tilde) |
+                NodeType::UnaryOperator(UnaryOperatorType::Tilde)
+        ) {
+            in_formula = true;
+        }
+
+        if node.is_call() && is_within_subset_delimiters(&context.point, &node) {
+            let name = call_name(&node, context)?;
+            let name = name.as_deref().unwrap_or_default();
+
+            if name == "aes" {
+                return Ok(Some((node, DataMaskKind::Aes)));
+            }
+            if DATA_MASKING_FUNCTIONS.contains(&name) || functions.iter().any(|f| f == name) {
+                return Ok(Some((node, DataMaskKind::Verb)));
+            }
+            if in_formula && named_data_argument(&node, context)?.is_some() {
+                return Ok(Some((node, DataMaskKind::Formula)));
+            }
+        }
+
+        // Other objects use the default `[` method which doesn't mask
+        if node.is_subset() && is_within_subset_delimiters(&context.point, &node) {
+            if let Some(object) = node.child_by_field_name("function") {
+                let object = context.document.contents.node_slice(&object)?.to_string();
+                if is_data_table(&object) {
+                    return Ok(Some((node, DataMaskKind::DataTable)));
+                }
+            }
+        }
+
+        node = match node.parent() {
+            Some(parent) => parent,
+            None => return Ok(None),
+        };
+    }
+}
+
+/// The name of the called function, without namespace, e.g. `filter` for
+/// `dplyr::filter()`
+fn call_name(call: &Node, context: &DocumentContext) -> anyhow::Result<Option<String>> {
+    let Some(mut callee) = call.child_by_field_name("function") else {
+        return Ok(None);
+    };
+
+    if callee.is_namespace_operator() {
+        callee = match callee.child_by_field_name("rhs") {
+            Some(rhs) => rhs,
+            None => return Ok(None),
+        };
+    }
+
+    if !callee.is_identifier() {
+        return Ok(None);
+    }
+
+    Ok(Some(
+        context.document.contents.node_slice(&callee)?.to_string(),
+    ))
+}
+
+fn is_data_table(object: &str) -> bool {
+    let options = RParseEvalOptions {
+        forbid_function_calls: true,
+        timeout: Some(COMPLETION_EVAL_TIMEOUT),
+        ..Default::default()
+    };
+
+    match harp::parse_eval(object, options) {
+        Ok(object) => r_inherits(object.sexp, "data.table"),
+        Err(_) => false,
+    }
+}
+
+fn is_piped(call: &Node, context: &DocumentContext) -> anyhow::Result<bool> {
+    let Some(parent) = call.parent() else {
+        return Ok(false);
+    };
+    if !parent.is_pipe_operator(&context.document.contents)? {
+        return Ok(false);
+    }
+    Ok(parent.child_by_field_name("rhs") == Some(*call))
+}
+
+/// Finds the value of the `data` argument, when supplied by name
+fn named_data_argument<'tree>(
+    call: &Node<'tree>,
+    context: &DocumentContext,
+) -> anyhow::Result<Option<Node<'tree>>> {
+    let Some(arguments) = call.child_by_field_name("arguments") else {
+        return Ok(None);
+    };
+
+    let mut cursor = arguments.walk();
+    for argument in arguments.children_by_field_name("argument", &mut cursor) {
+        let (Some(name), Some(value)) = (
+            argument.child_by_field_name("name"),
+            argument.child_by_field_name("value"),
+        ) else {
+            continue;
+        };
+
+        if context.document.contents.node_slice(&name)?.to_string() == "data" {
+            return Ok(Some(value));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Finds the value of the data argument, either supplied by name or as the
+/// first unnamed argument
+fn data_argument<'tree>(
+    call: &Node<'tree>,
+    context: &DocumentContext,
+) -> anyhow::Result<Option<Node<'tree>>> {
+    let Some(arguments) = call.child_by_field_name("arguments") else {
+        return Ok(None);
+    };
+
+    let mut first_unnamed = None;
+
+    let mut cursor = arguments.walk();
+    for argument in arguments.children_by_field_name("argument", &mut cursor) {
+        let Some(value) = argument.child_by_field_name("value") else {
+            continue;
+        };
+
+        let Some(name) = argument.child_by_field_name("name") else {
+            first_unnamed = first_unnamed.or(Some(value));
+            continue;
+        };
+
+        let name = context.document.contents.node_slice(&name)?.to_string();
+        if DATA_ARGUMENT_NAMES.contains(&name.as_str()) {
+            return Ok(Some(value));
+        }
+    }
+
+    Ok(first_unnamed)
+}
+
+/// Finds the data of the plot layer an `aes()` call belongs to. The data is
+/// supplied to the layer, as in `geom_point(aes(<here>), data = df)`, or to
+/// the `ggplot()` call, as in `ggplot(df, aes(<here>))` or
+/// `ggplot(df) + geom_point(aes(<here>))`.
+fn aes_data_argument<'tree>(
+    aes: &Node<'tree>,
+    context: &DocumentContext,
+) -> anyhow::Result<Option<Node<'tree>>> {
+    // `aes()` -> argument -> arguments -> call
+    let Some(call) = aes
+        .parent()
+        .and_then(|x| x.parent())
+        .and_then(|x| x.parent())
+    else {
+        return Ok(None);
+    };
+    if !call.is_call() {
+        return Ok(None);
+    }
+
+    if let Some(data) = named_data_argument(&call, context)? {
+        return Ok(Some(data));
+    }
+
+    if call_name(&call, context)?.as_deref() == Some("ggplot") {
+        return ggplot_data_argument(&call, aes, context);
+    }
+
+    // A layer added to a plot, find the `ggplot()` call at the start of the
+    // chain of `+`
+    let mut node = call;
+    while let Some(parent) = node.parent() {
+        if !is_plus(&parent) {
+            break;
+        }
+        node = parent;
+    }
+    while is_plus(&node) {
+        node = match node.child_by_field_name("lhs") {
+            Some(lhs) => lhs,
+            None => return Ok(None),
+        };
+    }
+
+    if node.is_call() && call_name(&node, context)?.as_deref() == Some("ggplot") {
+        return ggplot_data_argument(&node, aes, context);
+    }
+
+    Ok(None)
+}
+
+fn ggplot_data_argument<'tree>(
+    ggplot: &Node<'tree>,
+    aes: &Node<'tree>,
+    context: &DocumentContext,
+) -> anyhow::Result<Option<Node<'tree>>> {
+    if is_piped(ggplot, context)? {
+        return Ok(None);
+    }
+
+    let Some(data) = data_argument(ggplot, context)? else {
+        return Ok(None);
+    };
+
+    // In `ggplot(aes(<here>))` the mapping is the first unnamed argument
+    if data == *aes {
+        return Ok(None);
+    }
+
+    Ok(Some(data))
+}
+
+fn is_plus(node: &Node) -> bool {
+    node.node_type() == NodeType::BinaryOperator(BinaryOperatorType::Plus)
+}
+
+#[cfg(test)]
+mod tests {
+    use harp::eval::parse_eval_global;
+
+    use crate::fixtures::package_is_installed;
+    use crate::fixtures::point_from_cursor;
+    use crate::lsp::completions::sources::composite::data_mask::completions_from_data_mask;
+    use crate::lsp::document_context::DocumentContext;
+    use crate::lsp::documents::Document;
+    use crate::r_task;
+
+    fn labels(code: &str) -> Option<Vec<String>> {
+        labels_with_functions(code, &[])
+    }
+
+    fn labels_with_functions(code: &str, functions: &[String]) -> Option<Vec<String>> {
+        let (text, point) = point_from_cursor(code);
+        let document = Document::new(text.as_str(), None);
+        let context = DocumentContext::new(&document, point, None);
+
+        let completions = completions_from_data_mask(&context, functions).unwrap()?;
+        Some(completions.into_iter().map(|item| item.label).collect())
+    }
+
+    #[test]
+    fn test_data_mask_completions() {
+        r_task(|| {
+            parse_eval_global("my_df <- data.frame(a = 1, b = 2)").unwrap();
+
+            let expected = Some(vec![String::from("a"), String::from("b")]);
+
+            assert_eq!(labels("filter(my_df, @)"), expected);
+            assert_eq!(labels("dplyr::mutate(my_df, y = @)"), expected);
+            assert_eq!(labels("mutate(my_df, y = mean(@))"), expected);
+            assert_eq!(labels("subset(my_df, @)"), expected);
+            assert_eq!(labels("filter(.data = my_df, @)"), expected);
+
+            // Not a data-masking function
+            assert_eq!(labels("identity(my_df, @)"), None);
+
+            // Piped data is handled by the pipe source
+            assert_eq!(labels("my_df |> filter(@)"), None);
+
+            // Not in the data argument itself
+            assert_eq!(labels("filter(my_d@)"), None);
+
+            // Not across function boundaries
+            assert_eq!(labels("mutate(my_df, y = function(x) { @ })"), None);
+
+            // Formulas of calls with a `data` argument
+            assert_eq!(labels("lm(a ~ @, data = my_df)"), expected);
+            assert_eq!(labels("lm(@ ~ b, data = my_df)"), expected);
+            assert_eq!(labels("lm(a ~ log(@), data = my_df)"), expected);
+            assert_eq!(
+                labels("aggregate(~ @, data = my_df, FUN = length)"),
+                expected
+            );
+
+            // Not outside of the formula or without `data` argument
+            assert_eq!(labels("lm(a ~ b, data = my_df, weights = @)"), None);
+            assert_eq!(labels("lm(a ~ @, my_df)"), None);
+
+            // User-supplied data-masking functions
+            let functions = vec![String::from("my_verb")];
+            assert_eq!(labels("my_verb(my_df, @)"), None);
+            assert_eq!(
+                labels_with_functions("my_verb(my_df, @)", &functions),
+                expected
+            );
+            assert_eq!(
+                labels_with_functions("pkg::my_verb(my_df, @)", &functions),
+                expected
+            );
+
+            // ggplot2 aesthetics
+            assert_eq!(labels("ggplot(my_df, aes(@))"), expected);
+            assert_eq!(
+                labels("ggplot2::ggplot(my_df, ggplot2::aes(x = @))"),
+                expected
+            );
+            assert_eq!(labels("ggplot(mapping = aes(@), data = my_df)"), expected);
+            assert_eq!(labels("geom_point(aes(@), data = my_df)"), expected);
+            assert_eq!(labels("ggplot(my_df) + geom_point(aes(@))"), expected);
+            assert_eq!(
+                labels("ggplot(my_df) + geom_line() + geom_point(aes(@))"),
+                expected
+            );
+            assert_eq!(labels("ggplot(aes(@))"), None);
+            assert_eq!(labels("my_df |> ggplot(aes(@))"), None);
+            assert_eq!(labels("geom_point(aes(@))"), None);
+
+            // Not a data.table, the default `[` method doesn't mask
+            assert_eq!(labels("my_df[@]"), None);
+
+            parse_eval_global("rm(my_df)").unwrap();
+        })
+    }
+
+    #[test]
+    fn test_data_mask_completions_data_table() {
+        r_task(|| {
+            if !package_is_installed("data.table") {
+                return;
+            }
+
+            parse_eval_global("my_dt <- data.table::data.table(a = 1, b = 2)").unwrap();
+
+            let expected = Some(vec![String::from("a"), String::from("b")]);
+            assert_eq!(labels("my_dt[@ > 1]"), expected);
+            assert_eq!(labels("my_dt[, .(m = mean(@))]"), expected);
+
+            // Masking verbs inside `[` still use their own data
+            parse_eval_global("my_df <- data.frame(c = 1)").unwrap();
+            assert_eq!(
+                labels("my_dt[, filter(my_df, @)]"),
+                Some(vec![String::from("c")])
+            );
+
+            parse_eval_global("rm(my_dt, my_df)").unwrap();
+        })
+    }
+}
@@ -0,0 +1,278 @@
+//
+// formula.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use tower_lsp::lsp_types::CompletionItem;
+use tree_sitter::Node;
+use tree_sitter::Point;
+
+use crate::lsp::completions::completion_context::CompletionContext;
+use crate::lsp::completions::completion_item::completion_item_from_function;
+use crate::lsp::completions::sources::common::subset::is_within_subset_delimiters;
+use crate::lsp::completions::sources::CompletionSource;
+use crate::lsp::document_context::DocumentContext;
+use crate::lsp::traits::rope::RopeExt;
+use crate::treesitter::BinaryOperatorType;
+use crate::treesitter::NodeType;
+use crate::treesitter::NodeTypeExt;
+use crate::treesitter::UnaryOperatorType;
+
+pub(super) struct FormulaSource;
+
+impl CompletionSource for FormulaSource {
+    fn name(&self) -> &'static str {
+        "formula"
+    }
+
+    fn provide_completions(
+        &self,
+        completion_context: &CompletionContext,
+    ) -> anyhow::Result<Option<Vec<CompletionItem>>> {
+        completions_from_formula(completion_context)
+    }
+}
+
+/// A special term of model formulas, documented in `package`
+#[derive(Debug, PartialEq)]
+pub(crate) struct FormulaTerm {
+    pub(crate) name: &'static str,
+    pub(crate) package: &'static str,
+}
+
+const fn term(name: &'static str, package: &'static str) -> FormulaTerm {
+    FormulaTerm { name, package }
+}
+
+/// Terms understood by `model.frame()`, and hence by most modelling functions
+const STATS_TERMS: &[FormulaTerm] = &[
+    term("I", "base"),
+    term("offset", "stats"),
+    term("poly", "stats"),
+];
+
+const MGCV_TERMS: &[FormulaTerm] = &[
+    term("I", "base"),
+    term("offset", "stats"),
+    term("poly", "stats"),
+    term("s", "mgcv"),
+    term("te", "mgcv"),
+    term("ti", "mgcv"),
+    term("t2", "mgcv"),
+];
+
+const SURVIVAL_TERMS: &[FormulaTerm] = &[
+    term("I", "base"),
+    term("offset", "stats"),
+    term("poly", "stats"),
+    term("strata", "survival"),
+    term("cluster", "survival"),
+];
+
+/// Special terms of formulas, by modelling function
+const FORMULA_TERMS: &[(&str, &[FormulaTerm])] = &[
+    // stats
+    ("aov", STATS_TERMS),
+    ("glm", STATS_TERMS),
+    ("lm", STATS_TERMS),
+    ("loess", STATS_TERMS),
+    ("model.frame", STATS_TERMS),
+    ("model.matrix", STATS_TERMS),
+    // lme4
+    ("glmer", STATS_TERMS),
+    ("lmer", STATS_TERMS),
+    // mgcv
+    ("bam", MGCV_TERMS),
+    ("gam", MGCV_TERMS),
+    ("gamm", MGCV_TERMS),
+    // survival
+    ("coxph", SURVIVAL_TERMS),
+    ("survfit", SURVIVAL_TERMS),
+    ("survreg", SURVIVAL_TERMS),
+];
+
+/// Completions for the special terms of formulas passed to modelling
+/// functions, e.g. `s()` in `mgcv::gam(y ~ <here>, data = df)`
+///
+/// Columns of the `data` argument are provided by the data mask source.
+fn completions_from_formula(
+    completion_context: &CompletionContext,
+) -> anyhow::Result<Option<Vec<CompletionItem>>> {
+    let context = completion_context.document_context;
+
+    let Some(terms) = formula_terms(context.node, context.point, context)? else {
+        return Ok(None);
+    };
+
+    let mut completions = vec![];
+
+    for term in terms {
+        completions.push(completion_item_from_function(
+            term.name,
+            Some(term.package),
+            completion_context.function_context(),
+        )?);
+    }
+
+    Ok(Some(completions))
+}
+
+/// The special term called at `node`, e.g. `s` in `gam(y ~ s(x), data = df)`
+pub(crate) fn formula_term(
+    node: &Node,
+    context: &DocumentContext,
+) -> anyhow::Result<Option<&'static FormulaTerm>> {
+    let Some(call) = node.parent() else {
+        return Ok(None);
+    };
+    if !call.is_call() || call.child_by_field_name("function") != Some(*node) {
+        return Ok(None);
+    }
+
+    let Some(terms) = formula_terms(call, node.start_position(), context)? else {
+        return Ok(None);
+    };
+
+    let name = context.document.contents.node_slice(node)?.to_string();
+    Ok(terms.iter().find(|term| term.name == name))
+}
+
+/// Walks up the tree from `node` to find the formula we are in, and returns
+/// the special terms of the modelling function the formula is passed to.
+/// Stops at function boundaries.
+fn formula_terms(
+    mut node: Node,
+    point: Point,
+    context: &DocumentContext,
+) -> anyhow::Result<Option<&'static [FormulaTerm]>> {
+    let mut in_formula = false;
+
+    loop {
+        if node.is_braced_expression() || node.is_function_definition() {
+            return Ok(None);
+        }
+
+        if matches!(
+            node.node_type(),
+            NodeType::BinaryOperator(BinaryOperatorType::Tilde) |
+                NodeType::UnaryOperator(UnaryOperatorType::Tilde)
+        ) {
+            in_formula = true;
+        }
+
+        // Calls within the formula, like `log(x)`, are skipped
+        if in_formula && node.is_call() && is_within_subset_delimiters(&point, &node) {
+            if let Some(terms) = modelling_function_terms(&node, context)? {
+                return Ok(Some(terms));
+            }
+        }
+
+        node = match node.parent() {
+            Some(parent) => parent,
+            None => return Ok(None),
+        };
+    }
+}
+
+fn modelling_function_terms(
+    call: &Node,
+    context: &DocumentContext,
+) -> anyhow::Result<Option<&'static [FormulaTerm]>> {
+    let Some(mut callee) = call.child_by_field_name("function") else {
+        return Ok(None);
+    };
+
+    // Allow `mgcv::gam()`
+    if callee.is_namespace_operator() {
+        callee = match callee.child_by_field_name("rhs") {
+            Some(rhs) => rhs,
+            None => return Ok(None),
+        };
+    }
+
+    if !callee.is_identifier() {
+        return Ok(None);
+    }
+
+    let name = context.document.contents.node_slice(&callee)?.to_string();
+
+    Ok(FORMULA_TERMS
+        .iter()
+        .find(|(function, _)| *function == name)
+        .map(|(_, terms)| *terms))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::point_from_cursor;
+    use crate::lsp::completions::completion_context::CompletionContext;
+    use crate::lsp::completions::sources::composite::formula::completions_from_formula;
+    use crate::lsp::completions::sources::composite::formula::formula_term;
+    use crate::lsp::document_context::DocumentContext;
+    use crate::lsp::documents::Document;
+    use crate::lsp::state::WorldState;
+    use crate::r_task;
+
+    fn labels(code: &str) -> Option<Vec<String>> {
+        let (text, point) = point_from_cursor(code);
+        let document = Document::new(text.as_str(), None);
+        let document_context = DocumentContext::new(&document, point, None);
+        let state = WorldState::default();
+        let context = CompletionContext::new(&document_context, &state);
+
+        let completions = completions_from_formula(&context).unwrap()?;
+        Some(completions.into_iter().map(|item| item.label).collect())
+    }
+
+    #[test]
+    fn test_formula_completions() {
+        r_task(|| {
+            let stats = Some(vec![
+                String::from("I"),
+                String::from("offset"),
+                String::from("poly"),
+            ]);
+
+            assert_eq!(labels("lm(y ~ @, data = df)"), stats);
+            assert_eq!(labels("stats::glm(y ~ x + @, df)"), stats);
+            assert_eq!(labels("lm(y ~ log(@), data = df)"), stats);
+
+            let mgcv = labels("mgcv::gam(y ~ @, data = df)").unwrap();
+            assert!(mgcv.contains(&String::from("s")));
+            assert!(mgcv.contains(&String::from("te")));
+            assert!(mgcv.contains(&String::from("I")));
+
+            // Not a modelling function
+            assert_eq!(labels("identity(y ~ @)"), None);
+
+            // Not outside of the formula
+            assert_eq!(labels("lm(y ~ x, data = df, weights = @)"), None);
+
+            // Not across function boundaries
+            assert_eq!(labels("lm(y ~ x, data = function(x) { @ })"), None);
+        })
+    }
+
+    #[test]
+    fn test_formula_term() {
+        r_task(|| {
+            let term = |code: &str| {
+                let (text, point) = point_from_cursor(code);
+                let document = Document::new(text.as_str(), None);
+                let context = DocumentContext::new(&document, point, None);
+                formula_term(&context.closest_node, &context)
+                    .unwrap()
+                    .map(|term| (term.name, term.package))
+            };
+
+            assert_eq!(term("gam(y ~ @s(x), data = df)"), Some(("s", "mgcv")));
+            assert_eq!(term("lm(y ~ @I(x^2), data = df)"), Some(("I", "base")));
+
+            // Only as calls of the formula of a modelling function
+            assert_eq!(term("lm(y ~ @s(x), data = df)"), None);
+            assert_eq!(term("gam(y ~ x, data = @s(df))"), None);
+        })
+    }
+}
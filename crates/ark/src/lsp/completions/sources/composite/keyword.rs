@@ -9,13 +9,10 @@ use stdext::unwrap;
 use tower_lsp::lsp_types::CompletionItem;
 use tower_lsp::lsp_types::CompletionItemKind;
 use tower_lsp::lsp_types::CompletionItemLabelDetails;
-use tower_lsp::lsp_types::Documentation;
-use tower_lsp::lsp_types::InsertTextFormat;
-use tower_lsp::lsp_types::MarkupContent;
-use tower_lsp::lsp_types::MarkupKind;
 
 use crate::lsp::completions::completion_context::CompletionContext;
 use crate::lsp::completions::completion_item::completion_item;
+use crate::lsp::completions::completion_item::completion_item_from_snippet;
 use crate::lsp::completions::sources::CompletionSource;
 use crate::lsp::completions::types::CompletionData;
 
@@ -28,17 +25,20 @@ impl CompletionSource for KeywordSource {
 
     fn provide_completions(
         &self,
-        _completion_context: &CompletionContext,
+        completion_context: &CompletionContext,
     ) -> anyhow::Result<Option<Vec<CompletionItem>>> {
-        completions_from_keywords()
+        completions_from_keywords(completion_context.state.config.completions.snippets)
     }
 }
 
-pub fn completions_from_keywords() -> anyhow::Result<Option<Vec<CompletionItem>>> {
+pub fn completions_from_keywords(snippets: bool) -> anyhow::Result<Option<Vec<CompletionItem>>> {
     let mut completions = vec![];
 
     add_bare_keywords(&mut completions);
-    add_keyword_snippets(&mut completions);
+
+    if snippets {
+        add_keyword_snippets(&mut completions);
+    }
 
     Ok(Some(completions))
 }
@@ -140,11 +140,9 @@ fn add_keyword_snippets(completions: &mut Vec<CompletionItem>) {
         label_details_description,
     } in KEYWORD_SNIPPETS
     {
-        let item = completion_item(label.to_string(), CompletionData::Snippet {
-            text: snippet.to_string(),
-        });
+        let item = completion_item_from_snippet(label, snippet, label_details_description);
 
-        let mut item = match item {
+        let item = match item {
             Ok(item) => item,
             Err(err) => {
                 log::trace!("Failed to construct completion item for reserved keyword '{keyword}' due to {err:?}");
@@ -152,23 +150,6 @@ fn add_keyword_snippets(completions: &mut Vec<CompletionItem>) {
             },
         };
 
-        // Markup shows up in the quick suggestion documentation window,
-        // so you can see what the snippet expands to
-        let markup = vec!["```r", snippet, "```"].join("\n");
-        let markup = MarkupContent {
-            kind: MarkupKind::Markdown,
-            value: markup,
-        };
-
-        item.documentation = Some(Documentation::MarkupContent(markup));
-        item.kind = Some(CompletionItemKind::SNIPPET);
-        item.insert_text = Some(snippet.to_string());
-        item.insert_text_format = Some(InsertTextFormat::SNIPPET);
-        item.label_details = Some(CompletionItemLabelDetails {
-            detail: None,
-            description: Some(label_details_description.to_string()),
-        });
-
         completions.push(item);
     }
 }
@@ -179,7 +160,7 @@ mod tests {
 
     #[test]
     fn test_presence_bare_keywords() {
-        let completions = super::completions_from_keywords().unwrap().unwrap();
+        let completions = super::completions_from_keywords(true).unwrap().unwrap();
         let keyword_completions: Vec<_> = completions
             .iter()
             .filter(|item| item.kind == Some(tower_lsp::lsp_types::CompletionItemKind::KEYWORD))
@@ -206,7 +187,7 @@ mod tests {
 
     #[test]
     fn test_presence_keyword_snippets() {
-        let completions = super::completions_from_keywords().unwrap().unwrap();
+        let completions = super::completions_from_keywords(true).unwrap().unwrap();
         let snippet_completions: Vec<_> = completions
             .iter()
             .filter(|item| item.kind == Some(tower_lsp::lsp_types::CompletionItemKind::SNIPPET))
@@ -225,4 +206,16 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_keyword_snippets_setting() {
+        let completions = super::completions_from_keywords(false).unwrap().unwrap();
+
+        // Bare keywords are still offered
+        assert!(completions.iter().any(|item| item.label == "if"));
+
+        assert!(!completions
+            .iter()
+            .any(|item| item.kind == Some(tower_lsp::lsp_types::CompletionItemKind::SNIPPET)));
+    }
 }
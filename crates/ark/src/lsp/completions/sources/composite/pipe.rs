@@ -13,6 +13,7 @@ use tree_sitter::Node;
 
 use crate::lsp::completions::completion_context::CompletionContext;
 use crate::lsp::completions::sources::utils::completions_from_object_names;
+use crate::lsp::completions::sources::utils::COMPLETION_EVAL_TIMEOUT;
 use crate::lsp::completions::sources::CompletionSource;
 use crate::lsp::document_context::DocumentContext;
 use crate::lsp::traits::rope::RopeExt;
@@ -87,6 +88,7 @@ pub fn find_pipe_root(
 fn eval_pipe_root(name: &str) -> Option<RObject> {
     let options = RParseEvalOptions {
         forbid_function_calls: true,
+        timeout: Some(COMPLETION_EVAL_TIMEOUT),
         ..Default::default()
     };
 
@@ -105,6 +107,10 @@ fn eval_pipe_root(name: &str) -> Option<RObject> {
                 log::trace!("Can't evaluate pipe root: {message}");
                 return None;
             },
+            Error::Timeout { .. } => {
+                log::warn!("Can't evaluate pipe root: {err}");
+                return None;
+            },
             _ => {
                 log::error!("Can't evaluate pipe root: {err:?}");
                 return None;
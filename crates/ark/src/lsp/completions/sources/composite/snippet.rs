@@ -0,0 +1,147 @@
+//
+// snippet.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use tower_lsp::lsp_types::CompletionItem;
+
+use crate::lsp::completions::completion_context::CompletionContext;
+use crate::lsp::completions::completion_item::completion_item_from_snippet;
+use crate::lsp::completions::sources::CompletionSource;
+
+pub(super) struct SnippetSource;
+
+impl CompletionSource for SnippetSource {
+    fn name(&self) -> &'static str {
+        "snippet"
+    }
+
+    fn provide_completions(
+        &self,
+        completion_context: &CompletionContext,
+    ) -> anyhow::Result<Option<Vec<CompletionItem>>> {
+        if !completion_context.state.config.completions.snippets {
+            return Ok(None);
+        }
+        completions_from_snippets()
+    }
+}
+
+struct Snippet {
+    label: &'static str,
+    snippet: &'static str,
+    label_details_description: &'static str,
+}
+
+/// Snippets for common R idioms. Snippets for bare control flow constructs
+/// like `if` or `for` are provided by the keyword source, which is why they
+/// are not part of this list.
+const SNIPPETS: &[Snippet] = &[
+    Snippet {
+        label: "if/else",
+        snippet: "if (${1:condition}) {\n\t${2}\n} else {\n\t${0}\n}",
+        label_details_description: "Insert `if`/`else` statement",
+    },
+    Snippet {
+        label: "tryCatch",
+        snippet: "tryCatch(\n\t${1:expr},\n\terror = function(cnd) {\n\t\t${0}\n\t}\n)",
+        label_details_description: "Insert `tryCatch()` block",
+    },
+    Snippet {
+        label: "R6Class",
+        snippet: "${1:Name} <- R6::R6Class(\"${1:Name}\",\n\tpublic = list(\n\t\tinitialize = function(${2}) {\n\t\t\t${0}\n\t\t}\n\t)\n)",
+        label_details_description: "Define an R6 class",
+    },
+    Snippet {
+        label: "test_that",
+        snippet: "test_that(\"${1:description}\", {\n\t${0}\n})",
+        label_details_description: "Insert testthat test",
+    },
+];
+
+fn completions_from_snippets() -> anyhow::Result<Option<Vec<CompletionItem>>> {
+    let mut completions = vec![];
+
+    for Snippet {
+        label,
+        snippet,
+        label_details_description,
+    } in SNIPPETS
+    {
+        let item = match completion_item_from_snippet(label, snippet, label_details_description) {
+            Ok(item) => item,
+            Err(err) => {
+                log::trace!(
+                    "Failed to construct completion item for snippet '{label}' due to {err:?}"
+                );
+                continue;
+            },
+        };
+
+        completions.push(item);
+    }
+
+    Ok(Some(completions))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::CompletionItemKind;
+    use tower_lsp::lsp_types::InsertTextFormat;
+
+    use crate::fixtures::point_from_cursor;
+    use crate::lsp::completions::completion_context::CompletionContext;
+    use crate::lsp::completions::sources::composite::keyword::completions_from_keywords;
+    use crate::lsp::completions::sources::composite::snippet::completions_from_snippets;
+    use crate::lsp::completions::sources::composite::snippet::SnippetSource;
+    use crate::lsp::completions::sources::CompletionSource;
+    use crate::lsp::document_context::DocumentContext;
+    use crate::lsp::documents::Document;
+    use crate::lsp::state::WorldState;
+
+    #[test]
+    fn test_snippets() {
+        let completions = completions_from_snippets().unwrap().unwrap();
+        assert!(!completions.is_empty());
+
+        for item in completions.iter() {
+            assert_eq!(item.kind, Some(CompletionItemKind::SNIPPET));
+            assert_eq!(item.insert_text_format, Some(InsertTextFormat::SNIPPET));
+        }
+    }
+
+    #[test]
+    fn test_snippets_are_not_keyword_snippets() {
+        let snippets = completions_from_snippets().unwrap().unwrap();
+        let keywords = completions_from_keywords(true).unwrap().unwrap();
+
+        for snippet in snippets.iter() {
+            assert!(!keywords
+                .iter()
+                .any(|keyword| keyword.label == snippet.label));
+        }
+    }
+
+    #[test]
+    fn test_snippets_setting() {
+        let (text, point) = point_from_cursor("try@");
+        let document = Document::new(text.as_str(), None);
+        let document_context = DocumentContext::new(&document, point, None);
+
+        let mut state = WorldState::default();
+        let context = CompletionContext::new(&document_context, &state);
+        assert!(SnippetSource
+            .provide_completions(&context)
+            .unwrap()
+            .is_some());
+
+        state.config.completions.snippets = false;
+        let context = CompletionContext::new(&document_context, &state);
+        assert!(SnippetSource
+            .provide_completions(&context)
+            .unwrap()
+            .is_none());
+    }
+}
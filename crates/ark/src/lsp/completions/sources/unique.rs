@@ -6,11 +6,13 @@
 //
 
 mod colon;
-mod comment;
+pub(crate) mod comment;
 mod custom;
 mod extractor;
 mod file_path;
+pub(crate) mod help;
 mod namespace;
+mod package_file;
 mod string;
 mod subset;
 
@@ -23,7 +25,9 @@ use crate::lsp::completions::sources::unique::comment::CommentSource;
 use crate::lsp::completions::sources::unique::custom::CustomSource;
 use crate::lsp::completions::sources::unique::extractor::AtSource;
 use crate::lsp::completions::sources::unique::extractor::DollarSource;
+use crate::lsp::completions::sources::unique::help::HelpSource;
 use crate::lsp::completions::sources::unique::namespace::NamespaceSource;
+use crate::lsp::completions::sources::unique::package_file::PackageFileSource;
 use crate::lsp::completions::sources::unique::string::StringSource;
 
 /// Each unique source is tried in order until one returns completions
@@ -32,6 +36,11 @@ pub(crate) fn get_completions(
 ) -> anyhow::Result<Option<Vec<CompletionItem>>> {
     log::info!("Getting completions from unique sources");
 
+    // DESCRIPTION and NAMESPACE files have their own completions
+    if let Some(completions) = collect_completions(PackageFileSource, completion_context)? {
+        return Ok(Some(completions));
+    }
+
     // Try to detect a single colon first, which is a special case where we
     // don't provide any completions
     if let Some(completions) = collect_completions(SingleColonSource, completion_context)? {
@@ -43,21 +52,27 @@ pub(crate) fn get_completions(
         return Ok(Some(completions));
     }
 
-    // could be a file path
-    if let Some(completions) = collect_completions(StringSource, completion_context)? {
+    // pkg::xxx or pkg:::xxx
+    if let Some(completions) = collect_completions(NamespaceSource, completion_context)? {
         return Ok(Some(completions));
     }
 
-    // pkg::xxx or pkg:::xxx
-    if let Some(completions) = collect_completions(NamespaceSource, completion_context)? {
+    // help topics, as in `?topic` or `help("topic")`
+    if let Some(completions) = collect_completions(HelpSource, completion_context)? {
         return Ok(Some(completions));
     }
 
-    // custom completions for, e.g., options or env vars
+    // custom completions for, e.g., options or env vars. Consulted before the
+    // string source so that `Sys.getenv("<tab>")` doesn't complete file paths.
     if let Some(completions) = collect_completions(CustomSource, completion_context)? {
         return Ok(Some(completions));
     }
 
+    // could be a file path
+    if let Some(completions) = collect_completions(StringSource, completion_context)? {
+        return Ok(Some(completions));
+    }
+
     // as in foo$bar
     if let Some(completions) = collect_completions(DollarSource, completion_context)? {
         return Ok(Some(completions));
@@ -10,19 +10,26 @@ use std::path::Path;
 use harp::exec::RFunction;
 use harp::exec::RFunctionExt;
 use regex::Regex;
+use ropey::Rope;
 use tower_lsp::lsp_types::CompletionItem;
 use tower_lsp::lsp_types::Documentation;
 use tower_lsp::lsp_types::InsertTextFormat;
 use tower_lsp::lsp_types::MarkupContent;
 use tower_lsp::lsp_types::MarkupKind;
+use tree_sitter::Node;
+use tree_sitter::Point;
 use yaml_rust::YamlLoader;
 
 use crate::lsp::completions::completion_context::CompletionContext;
 use crate::lsp::completions::completion_item::completion_item;
+use crate::lsp::completions::completion_item::completion_item_from_scope_parameter;
+use crate::lsp::completions::sources::unique::package_file::completions_from_exported_symbols;
+use crate::lsp::completions::sources::unique::package_file::completions_from_installed_packages;
 use crate::lsp::completions::sources::CompletionSource;
 use crate::lsp::completions::types::CompletionData;
 use crate::lsp::document_context::DocumentContext;
 use crate::lsp::traits::rope::RopeExt;
+use crate::treesitter::BinaryOperatorType;
 use crate::treesitter::NodeTypeExt;
 
 pub(super) struct CommentSource;
@@ -36,10 +43,20 @@ impl CompletionSource for CommentSource {
         &self,
         completion_context: &CompletionContext,
     ) -> anyhow::Result<Option<Vec<CompletionItem>>> {
+        if let Some(completions) = completions_from_roxygen_arguments(completion_context)? {
+            return Ok(Some(completions));
+        }
         completions_from_comment(completion_context.document_context)
     }
 }
 
+/// A roxygen tag as described in `roxygen2-tags.yml`
+struct RoxygenTag {
+    name: String,
+    template: Option<String>,
+    description: Option<String>,
+}
+
 fn completions_from_comment(context: &DocumentContext) -> anyhow::Result<Option<Vec<CompletionItem>>> {
     let node = context.node;
 
@@ -60,8 +77,27 @@ fn completions_from_comment(context: &DocumentContext) -> anyhow::Result<Option<
         return Ok(Some(completions));
     }
 
-    // TODO: cache these?
-    // TODO: use an indexer to build the tag list?
+    for tag in roxygen_tags()? {
+        let template = tag
+            .template
+            .as_deref()
+            .map(inject_roxygen_comment_after_newline);
+
+        let item = completion_item_from_roxygen(
+            &tag.name,
+            template.as_deref(),
+            tag.description.as_deref(),
+        )?;
+
+        completions.push(item);
+    }
+
+    Ok(Some(completions))
+}
+
+// TODO: cache these?
+// TODO: use an indexer to build the tag list?
+fn roxygen_tags() -> anyhow::Result<Vec<RoxygenTag>> {
     let tags = unsafe {
         RFunction::new("base", "system.file")
             .param("package", "roxygen2")
@@ -71,36 +107,232 @@ fn completions_from_comment(context: &DocumentContext) -> anyhow::Result<Option<
     };
 
     if tags.is_empty() {
-        return Ok(Some(completions));
+        return Ok(vec![]);
     }
 
     let tags = Path::new(&tags);
     if !tags.exists() {
-        return Ok(Some(completions));
+        return Ok(vec![]);
+    }
+
+    let contents = std::fs::read_to_string(tags)?;
+    let docs = YamlLoader::load_from_str(contents.as_str())?;
+    let Some(items) = docs.get(0).and_then(|doc| doc.as_vec()) else {
+        return Ok(vec![]);
+    };
+
+    let tags = items
+        .iter()
+        .filter_map(|entry| {
+            Some(RoxygenTag {
+                name: entry["name"].as_str()?.to_string(),
+                template: entry["template"].as_str().map(String::from),
+                description: entry["description"].as_str().map(String::from),
+            })
+        })
+        .collect();
+
+    Ok(tags)
+}
+
+/// Completions for the arguments of roxygen tags: the parameters of the
+/// documented function after `@param`, and packages and their exports after
+/// `@importFrom`
+fn completions_from_roxygen_arguments(
+    completion_context: &CompletionContext,
+) -> anyhow::Result<Option<Vec<CompletionItem>>> {
+    let context = completion_context.document_context;
+    let node = context.node;
+
+    if !node.is_comment() {
+        return Ok(None);
+    }
+
+    let prefix = comment_prefix(&node, context.point, &context.document.contents)?;
+    let Some((tag, arguments)) = roxygen_arguments(&prefix) else {
+        return Ok(None);
+    };
+
+    let mut completions = vec![];
+
+    match (tag, arguments.as_slice()) {
+        ("param", [_]) => {
+            let documented = documented_parameters(&node, &context.document.contents);
+
+            for parameter in documented_function_parameters(&node, &context.document.contents)? {
+                if documented.contains(&parameter) {
+                    continue;
+                }
+                completions.push(completion_item_from_scope_parameter(&parameter, context)?);
+            }
+        },
+
+        ("importFrom", [_]) => {
+            completions = completions_from_installed_packages()?;
+        },
+
+        ("importFrom", [package, ..]) => {
+            if let Some(package) = completion_context.state.library.get(package) {
+                completions = completions_from_exported_symbols(&package)?;
+            }
+        },
+
+        _ => return Ok(None),
+    }
+
+    Ok(Some(completions))
+}
+
+/// The text of a comment up to `point`
+fn comment_prefix(node: &Node, point: Point, contents: &Rope) -> anyhow::Result<String> {
+    let text = contents.node_slice(node)?.to_string();
+    let offset = point.column.saturating_sub(node.start_position().column);
+
+    if point.row != node.start_position().row || offset > text.len() {
+        return Ok(text);
+    }
+
+    Ok(text.get(..offset).unwrap_or(&text).to_string())
+}
+
+/// Splits a roxygen line like `#' @importFrom pkg fn` into its tag and
+/// arguments. The last argument is the one being typed, and is empty after
+/// trailing whitespace. Returns `None` while the tag itself is being typed.
+fn roxygen_arguments(line: &str) -> Option<(&str, Vec<&str>)> {
+    let rest = line.strip_prefix("#'")?.trim_start();
+    let rest = rest.strip_prefix('@')?;
+
+    let mut words: Vec<&str> = rest.split_whitespace().collect();
+    if rest.is_empty() || rest.ends_with(char::is_whitespace) {
+        words.push("");
+    }
+
+    if words.len() < 2 {
+        return None;
+    }
+
+    let tag = words.remove(0);
+    Some((tag, words))
+}
+
+/// The comments of the roxygen block containing `node`
+fn roxygen_block<'tree>(node: &Node<'tree>) -> Vec<Node<'tree>> {
+    let mut first = *node;
+    while let Some(previous) = first.prev_sibling().filter(|node| node.is_comment()) {
+        first = previous;
+    }
+
+    let mut block = vec![first];
+    while let Some(next) = block.last().unwrap().next_sibling() {
+        if !next.is_comment() {
+            break;
+        }
+        block.push(next);
     }
 
-    let contents = std::fs::read_to_string(tags).unwrap();
-    let docs = YamlLoader::load_from_str(contents.as_str()).unwrap();
-    let doc = &docs[0];
+    block
+}
+
+/// Parameters already documented with `@param` in the block of `node`,
+/// including those documented jointly as in `@param x,y`
+fn documented_parameters(node: &Node, contents: &Rope) -> Vec<String> {
+    let mut documented = vec![];
 
-    let items = doc.as_vec().unwrap();
-    for entry in items.iter() {
-        let Some(name) = entry["name"].as_str() else {
+    for comment in roxygen_block(node) {
+        // Ignore the line being completed
+        if comment == *node {
+            continue;
+        }
+        let Ok(text) = contents.node_slice(&comment) else {
             continue;
         };
+        let text = text.to_string();
 
-        let template = entry["template"].as_str();
-        let template = template.map(inject_roxygen_comment_after_newline);
-        let template = template.as_deref();
+        if let Some(("param", arguments)) = roxygen_arguments(&text) {
+            if let Some(names) = arguments.first() {
+                documented.extend(names.split(',').map(String::from));
+            }
+        }
+    }
 
-        let description = entry["description"].as_str();
+    documented
+}
 
-        let item = completion_item_from_roxygen(name, template, description)?;
+/// Parameters of the function documented by the block of `node`, i.e. the
+/// function assigned right after the block
+fn documented_function_parameters(node: &Node, contents: &Rope) -> anyhow::Result<Vec<String>> {
+    let block = roxygen_block(node);
+    let Some(assignment) = block.last().and_then(|last| last.next_sibling()) else {
+        return Ok(vec![]);
+    };
 
-        completions.push(item);
+    if !assignment.is_binary_operator_of_kind(BinaryOperatorType::LeftAssignment) &&
+        !assignment.is_binary_operator_of_kind(BinaryOperatorType::EqualsAssignment)
+    {
+        return Ok(vec![]);
     }
 
-    Ok(Some(completions))
+    let Some(function) = assignment.child_by_field_name("rhs") else {
+        return Ok(vec![]);
+    };
+    if !function.is_function_definition() {
+        return Ok(vec![]);
+    }
+    let Some(parameters) = function.child_by_field_name("parameters") else {
+        return Ok(vec![]);
+    };
+
+    let mut names = vec![];
+    let mut cursor = parameters.walk();
+
+    for parameter in parameters.children_by_field_name("parameter", &mut cursor) {
+        if let Some(name) = parameter.child_by_field_name("name") {
+            names.push(contents.node_slice(&name)?.to_string());
+        }
+    }
+
+    Ok(names)
+}
+
+/// The roxygen tag under the cursor, e.g. `param` in `#' @param x`, along
+/// with its description
+pub(crate) fn roxygen_tag_documentation(
+    context: &DocumentContext,
+) -> anyhow::Result<Option<(String, String)>> {
+    let node = context.closest_node;
+    if !node.is_comment() || node.start_position().row != context.point.row {
+        return Ok(None);
+    }
+
+    let text = context.document.contents.node_slice(&node)?.to_string();
+    if !text.starts_with("#'") {
+        return Ok(None);
+    }
+
+    let column = node.start_position().column;
+    let offset = context.point.column.saturating_sub(column);
+
+    // Find the `@tag` word surrounding the cursor
+    let Some(start) = text.get(..offset).and_then(|before| before.rfind('@')) else {
+        return Ok(None);
+    };
+    let name: String = text[start + 1..]
+        .chars()
+        .take_while(|char| char.is_alphanumeric() || *char == '_' || *char == '.')
+        .collect();
+
+    if name.is_empty() || offset > start + 1 + name.len() {
+        return Ok(None);
+    }
+
+    let Some(tag) = roxygen_tags()?.into_iter().find(|tag| tag.name == name) else {
+        return Ok(None);
+    };
+    let Some(description) = tag.description else {
+        return Ok(None);
+    };
+
+    Ok(Some((name, description)))
 }
 
 fn completion_item_from_roxygen(
@@ -236,3 +468,99 @@ fn test_roxygen_completion_item() {
     assert_eq!(item.insert_text, Some("export".to_string()));
     assert_eq!(item.documentation, None);
 }
+
+#[test]
+fn test_roxygen_arguments() {
+    assert_eq!(roxygen_arguments("#' @param "), Some(("param", vec![""])));
+    assert_eq!(roxygen_arguments("#' @param x"), Some(("param", vec!["x"])));
+    assert_eq!(
+        roxygen_arguments("#'  @importFrom pkg fn "),
+        Some(("importFrom", vec!["pkg", "fn", ""]))
+    );
+
+    // Still typing the tag
+    assert_eq!(roxygen_arguments("#' @par"), None);
+
+    // Not a roxygen tag
+    assert_eq!(roxygen_arguments("# @param "), None);
+    assert_eq!(roxygen_arguments("#' param "), None);
+}
+
+#[test]
+fn test_roxygen_param_completions() {
+    use tree_sitter::Point;
+
+    use crate::lsp::documents::Document;
+    use crate::lsp::state::WorldState;
+
+    // Can't use `point_from_cursor()` since `@` is part of the syntax
+    let labels = |code: &str, point: Point| {
+        let document = Document::new(code, None);
+        let document_context = DocumentContext::new(&document, point, None);
+        let state = WorldState::default();
+        let context = CompletionContext::new(&document_context, &state);
+
+        completions_from_roxygen_arguments(&context)
+            .unwrap()
+            .map(|items| items.into_iter().map(|item| item.label).collect::<Vec<_>>())
+    };
+
+    // Parameters that are already documented are skipped
+    let code = "
+#' Title
+#' @param 
+#' @param x,y Coordinates.
+f <- function(x, y, z, ...) NULL
+";
+    assert_eq!(
+        labels(code, Point::new(2, 10)),
+        Some(vec![String::from("z"), String::from("...")])
+    );
+
+    // No documented function
+    assert_eq!(labels("#' @param \nNULL", Point::new(0, 10)), Some(vec![]));
+
+    // Not the argument of `@param`
+    assert_eq!(
+        labels("#' @param x \nf <- function(x) NULL", Point::new(0, 12)),
+        None
+    );
+}
+
+#[test]
+fn test_roxygen_tag_documentation() {
+    use libr::LOGICAL_ELT;
+    use tree_sitter::Point;
+
+    use crate::lsp::documents::Document;
+    use crate::r_task;
+
+    r_task(|| unsafe {
+        let installed = RFunction::new("", ".ps.is_installed")
+            .add("roxygen2")
+            .add("7.2.1.9000")
+            .call()
+            .unwrap();
+        let installed = LOGICAL_ELT(*installed, 0) != 0;
+
+        if !installed {
+            return;
+        }
+
+        let documentation = |code: &str, point: Point| {
+            let document = Document::new(code, None);
+            let context = DocumentContext::new(&document, point, None);
+            roxygen_tag_documentation(&context).unwrap()
+        };
+
+        let (tag, description) = documentation("#' @aliases foo", Point::new(0, 6)).unwrap();
+        assert_eq!(tag, "aliases");
+        assert!(!description.is_empty());
+
+        // Not on the tag
+        assert!(documentation("#' @aliases foo", Point::new(0, 13)).is_none());
+
+        // Not in a roxygen comment
+        assert!(documentation("# @aliases foo", Point::new(0, 5)).is_none());
+    });
+}
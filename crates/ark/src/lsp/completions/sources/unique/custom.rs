@@ -7,12 +7,8 @@
 
 use harp::exec::RFunction;
 use harp::exec::RFunctionExt;
-use harp::object::RObject;
-use harp::utils::r_typeof;
 use harp::utils::sym_quote_invalid;
-use libr::R_NilValue;
 use libr::VECSXP;
-use libr::VECTOR_ELT;
 use stdext::unwrap;
 use stdext::IntoResult;
 use tower_lsp::lsp_types::CompletionItem;
@@ -21,6 +17,7 @@ use crate::lsp;
 use crate::lsp::completions::completion_context::CompletionContext;
 use crate::lsp::completions::completion_item::completion_item;
 use crate::lsp::completions::completion_item::completion_item_from_dataset;
+use crate::lsp::completions::completion_item::completion_item_from_option;
 use crate::lsp::completions::completion_item::completion_item_from_package;
 use crate::lsp::completions::sources::utils::call_node_position_type;
 use crate::lsp::completions::sources::utils::set_sort_text_by_words_first;
@@ -28,7 +25,8 @@ use crate::lsp::completions::sources::utils::CallNodePositionType;
 use crate::lsp::completions::sources::CompletionSource;
 use crate::lsp::completions::types::CompletionData;
 use crate::lsp::signature_help::r_signature_help;
-use crate::treesitter::node_in_string;
+use crate::treesitter::node_find_string;
+
 pub(super) struct CustomSource;
 
 impl CompletionSource for CustomSource {
@@ -55,7 +53,18 @@ fn completions_from_custom_source(
 
     let document_context = context.document_context;
     let point = document_context.point;
-    let node = document_context.node;
+
+    // Inside a string, as in `Sys.getenv("<here>")`, the string determines our
+    // position in the call
+    let string = node_find_string(&document_context.node)
+        .filter(|string| string.start_position() != point && string.end_position() != point);
+    let node = string.unwrap_or(document_context.node);
+
+    // Same as the string source, no completions in strings when triggered
+    // by a character like `$`
+    if string.is_some() && document_context.trigger.is_some() {
+        return Ok(None);
+    }
 
     // Use the signature help tools to figure out the necessary pieces.
     let signatures = r_signature_help(document_context)?;
@@ -131,70 +140,67 @@ fn completions_from_custom_source(
 
     let mut completions = vec![];
 
-    unsafe {
-        // Call our custom completion function.
-        let r_completions = RFunction::from(".ps.completions.getCustomCallCompletions")
-            .param("name", name)
-            .param("argument", parameter)
-            .param("position", position)
-            .call()?;
-
-        if *r_completions == R_NilValue {
-            // No custom completions detected. Let other sources provide results.
-            return Ok(None);
-        }
-
-        if r_typeof(*r_completions) != VECSXP {
-            // Weird internal issue, but we expected completions here so return
-            // an empty set to signal that we are done
-            return Ok(Some(completions));
-        }
-
-        // TODO: Use safe access APIs here.
-        let values = VECTOR_ELT(*r_completions, 0);
-        let kind = VECTOR_ELT(*r_completions, 1);
-        let enquote = VECTOR_ELT(*r_completions, 2);
-        let append = VECTOR_ELT(*r_completions, 3);
+    // Call our custom completion function.
+    let r_completions = RFunction::from(".ps.completions.getCustomCallCompletions")
+        .param("name", name)
+        .param("argument", parameter)
+        .param("position", position)
+        .call()?;
 
-        if let Ok(values) = RObject::view(values).to::<Vec<String>>() {
-            let kind = RObject::view(kind)
-                .to::<String>()
-                .unwrap_or("unknown".to_string());
+    if r_completions.is_null() {
+        // No custom completions detected. Let other sources provide results.
+        return Ok(None);
+    }
 
-            let enquote = RObject::view(enquote).to::<bool>().unwrap_or(false);
+    if r_completions.kind() != VECSXP {
+        // Weird internal issue, but we expected completions here so return
+        // an empty set to signal that we are done
+        return Ok(Some(completions));
+    }
 
-            let append = RObject::view(append)
-                .to::<String>()
-                .unwrap_or("".to_string());
+    let values = r_completions.list_elt(0)?;
+    let kind = r_completions.list_elt(1)?;
+    let enquote = r_completions.list_elt(2)?;
+    let append = r_completions.list_elt(3)?;
 
-            for value in values.iter() {
-                let value = value.clone();
+    if let Ok(values) = Vec::<String>::try_from(values) {
+        let kind = String::try_from(kind).unwrap_or("unknown".to_string());
 
-                let item = match kind.as_str() {
-                    "package" => completion_item_from_package(&value, false),
-                    "dataset" => completion_item_from_dataset(&value),
-                    _ => completion_item(&value, CompletionData::Unknown),
-                };
+        let enquote = bool::try_from(enquote).unwrap_or(false);
 
-                let mut item = unwrap!(item, Err(err) => {
-                    log::error!("{err:?}");
-                    continue;
-                });
+        let append = String::try_from(append).unwrap_or("".to_string());
 
-                if enquote && !node_in_string(&node) {
-                    item.insert_text = Some(format!("\"{value}\""));
-                } else {
-                    let mut insert_text = sym_quote_invalid(value.as_str());
+        for value in values.iter() {
+            let value = value.clone();
 
-                    if !append.is_empty() {
-                        insert_text = format!("{insert_text}{append}");
-                    }
+            let item = match kind.as_str() {
+                "package" => completion_item_from_package(&value, false),
+                "dataset" => completion_item_from_dataset(&value),
+                "options" => completion_item_from_option(&value),
+                _ => completion_item(&value, CompletionData::Unknown),
+            };
 
-                    item.insert_text = Some(insert_text);
+            let mut item = unwrap!(item, Err(err) => {
+                log::error!("{err:?}");
+                continue;
+            });
+
+            if string.is_some() {
+                // Already quoted, insert the value verbatim
+                item.insert_text = Some(value);
+            } else if enquote {
+                item.insert_text = Some(format!("\"{value}\""));
+            } else {
+                let mut insert_text = sym_quote_invalid(value.as_str());
+
+                if !append.is_empty() {
+                    insert_text = format!("{insert_text}{append}");
                 }
 
-                completions.push(item);
+                item.insert_text = Some(insert_text);
             }
+
+            completions.push(item);
         }
     }
 
@@ -206,6 +212,10 @@ fn completions_from_custom_source(
 
 #[cfg(test)]
 mod tests {
+    use harp::exec::RFunction;
+    use harp::exec::RFunctionExt;
+    use harp::object::RObject;
+
     use crate::fixtures::point_from_cursor;
     use crate::lsp::completions::completion_context::CompletionContext;
     use crate::lsp::completions::sources::unique::custom::completions_from_custom_source;
@@ -283,7 +293,10 @@ mod tests {
     fn test_completion_custom_sys_getenv() {
         r_task(|| {
             let name = "ARK_TEST_ENVVAR";
-            harp::parse_eval_base(format!("Sys.setenv({name} = '1')").as_str()).unwrap();
+            RFunction::new("base", "Sys.setenv")
+                .param(name, "1")
+                .call()
+                .unwrap();
 
             // Inside the parentheses
             assert_has_completion("Sys.getenv(@)", name, "\"{name}\"");
@@ -309,10 +322,17 @@ mod tests {
             // After a named argument, multiline
             assert_has_completion("Sys.getenv(\n  unset = '1',\n  @\n)", name, "\"{name}\"");
 
+            // Inside a string
+            assert_has_completion("Sys.getenv('@')", name, "{name}");
+            assert_has_completion("Sys.getenv(\"ARK_@\")", name, "{name}");
+
             // Should not have it here
             assert_no_completions("Sys.getenv('foo', @)");
 
-            harp::parse_eval_base(format!("Sys.unsetenv('{name}')").as_str()).unwrap();
+            RFunction::new("base", "Sys.unsetenv")
+                .add(name)
+                .call()
+                .unwrap();
         })
     }
 
@@ -320,7 +340,10 @@ mod tests {
     fn test_completion_custom_sys_unsetenv() {
         r_task(|| {
             let name = "ARK_TEST_ENVVAR";
-            harp::parse_eval_base(format!("Sys.setenv({name} = '1')").as_str()).unwrap();
+            RFunction::new("base", "Sys.setenv")
+                .param(name, "1")
+                .call()
+                .unwrap();
 
             // Inside the parentheses
             assert_has_completion("Sys.unsetenv(@)", name, "\"{name}\"");
@@ -335,7 +358,10 @@ mod tests {
             // completions for this too, but it probably isn't that common in practice
             assert_no_completions("Sys.unsetenv(c(@))");
 
-            harp::parse_eval_base(format!("Sys.unsetenv('{name}')").as_str()).unwrap();
+            RFunction::new("base", "Sys.unsetenv")
+                .add(name)
+                .call()
+                .unwrap();
         })
     }
 
@@ -343,7 +369,10 @@ mod tests {
     fn test_completion_custom_sys_setenv() {
         r_task(|| {
             let name = "ARK_TEST_ENVVAR";
-            harp::parse_eval_base(format!("Sys.setenv({name} = '1')").as_str()).unwrap();
+            RFunction::new("base", "Sys.setenv")
+                .param(name, "1")
+                .call()
+                .unwrap();
 
             // Inside the parentheses
             assert_has_completion("Sys.setenv(@)", name, "{name} = ");
@@ -354,7 +383,10 @@ mod tests {
             // Should have it here too, this takes `...`
             assert_has_completion("Sys.setenv(foo = 'bar', @)", name, "{name} = ");
 
-            harp::parse_eval_base(format!("Sys.unsetenv('{name}')").as_str()).unwrap();
+            RFunction::new("base", "Sys.unsetenv")
+                .add(name)
+                .call()
+                .unwrap();
         })
     }
 
@@ -379,7 +411,10 @@ mod tests {
     fn test_completion_custom_get_option() {
         r_task(|| {
             let name = "ARK_TEST_OPTION";
-            harp::parse_eval_base(format!("options({name} = '1')").as_str()).unwrap();
+            RFunction::new("base", "options")
+                .param(name, "1")
+                .call()
+                .unwrap();
 
             // Inside the parentheses
             assert_has_completion("getOption(@)", name, "\"{name}\"");
@@ -393,10 +428,32 @@ mod tests {
             // After a named argument
             assert_has_completion("getOption(default = '1', @)", name, "\"{name}\"");
 
+            // Inside a string
+            assert_has_completion("getOption('ARK_@')", name, "{name}");
+
             // Should not have it here
             assert_no_completions("getOption('foo', @)");
 
-            harp::parse_eval_base(format!("options({name} = NULL)").as_str()).unwrap();
+            RFunction::new("base", "options")
+                .param(name, RObject::null())
+                .call()
+                .unwrap();
+        })
+    }
+
+    #[test]
+    fn test_completion_custom_get_option_documentation() {
+        r_task(|| {
+            let (text, point) = point_from_cursor("getOption('dig@')");
+            let state = WorldState::default();
+            let document = Document::new(text.as_str(), None);
+            let document_context = DocumentContext::new(&document, point, None);
+            let context = CompletionContext::new(&document_context, &state);
+
+            let completions = completions_from_custom_source(&context).unwrap().unwrap();
+
+            let digits = completions.iter().find(|c| c.label == "digits").unwrap();
+            assert!(digits.documentation.is_some());
         })
     }
 
@@ -404,7 +461,10 @@ mod tests {
     fn test_completion_custom_options() {
         r_task(|| {
             let name = "ARK_TEST_OPTION";
-            harp::parse_eval_base(format!("options({name} = '1')").as_str()).unwrap();
+            RFunction::new("base", "options")
+                .param(name, "1")
+                .call()
+                .unwrap();
 
             // Inside the parentheses
             assert_has_completion("options(@)", name, "{name} = ");
@@ -415,7 +475,27 @@ mod tests {
             // Should have it here too, this takes `...`
             assert_has_completion("options(foo = 'bar', @)", name, "{name} = ");
 
-            harp::parse_eval_base(format!("options({name} = NULL)").as_str()).unwrap();
+            RFunction::new("base", "options")
+                .param(name, RObject::null())
+                .call()
+                .unwrap();
+        })
+    }
+
+    #[test]
+    fn test_completion_custom_par() {
+        r_task(|| {
+            // Inside the parentheses
+            assert_has_completion("par(@)", "mar", "{name} = ");
+
+            // Typed some and then requested completions
+            assert_has_completion("par(mf@)", "mfrow", "{name} = ");
+
+            // After a named argument
+            assert_has_completion("par(mar = c(1, 1, 1, 1), @)", "cex", "{name} = ");
+
+            // Not in the value position
+            assert_no_completions("par(mar = @)");
         })
     }
 
@@ -5,9 +5,13 @@
 //
 //
 
+use std::collections::HashMap;
+
+use anyhow::anyhow;
 use harp::eval::RParseEvalOptions;
 use harp::exec::RFunction;
 use harp::exec::RFunctionExt;
+use harp::object::RObject;
 use harp::r_symbol;
 use harp::utils::r_env_has;
 use harp::utils::r_typeof;
@@ -18,7 +22,9 @@ use tree_sitter::Node;
 
 use crate::lsp::completions::completion_context::CompletionContext;
 use crate::lsp::completions::completion_item::completion_item_from_data_variable;
+use crate::lsp::completions::completion_item::completion_item_from_member;
 use crate::lsp::completions::sources::utils::set_sort_text_by_first_appearance;
+use crate::lsp::completions::sources::utils::COMPLETION_EVAL_TIMEOUT;
 use crate::lsp::completions::sources::CompletionSource;
 use crate::lsp::document_context::DocumentContext;
 use crate::lsp::traits::rope::RopeExt;
@@ -62,6 +68,7 @@ fn completions_from_dollar(
     completions_from_extractor(
         context,
         NodeType::ExtractOperator(ExtractOperatorType::Dollar),
+        "$",
         ".DollarNames",
     )
 }
@@ -70,6 +77,7 @@ fn completions_from_at(context: &DocumentContext) -> anyhow::Result<Option<Vec<C
     completions_from_extractor(
         context,
         NodeType::ExtractOperator(ExtractOperatorType::At),
+        "@",
         ".AtNames",
     )
 }
@@ -77,6 +85,7 @@ fn completions_from_at(context: &DocumentContext) -> anyhow::Result<Option<Vec<C
 fn completions_from_extractor(
     context: &DocumentContext,
     node_type: NodeType,
+    operator: &str,
     fun: &str,
 ) -> anyhow::Result<Option<Vec<CompletionItem>>> {
     let node = context.node;
@@ -101,7 +110,11 @@ fn completions_from_extractor(
     // Extract out its name from the document
     let text = context.document.contents.node_slice(&node)?.to_string();
 
-    completions.append(&mut completions_from_extractor_object(text.as_str(), fun)?);
+    completions.append(&mut completions_from_extractor_object(
+        text.as_str(),
+        operator,
+        fun,
+    )?);
 
     Ok(Some(completions))
 }
@@ -133,8 +146,12 @@ fn locate_extractor_node(node: Node, node_type: NodeType) -> Option<Node> {
     }
 }
 
-fn completions_from_extractor_object(text: &str, fun: &str) -> anyhow::Result<Vec<CompletionItem>> {
-    log::trace!("completions_from_extractor_object({text:?}, {fun:?})");
+fn completions_from_extractor_object(
+    text: &str,
+    operator: &str,
+    fun: &str,
+) -> anyhow::Result<Vec<CompletionItem>> {
+    log::trace!("completions_from_extractor_object({text:?}, {operator:?}, {fun:?})");
 
     const ENQUOTE: bool = false;
 
@@ -151,6 +168,7 @@ fn completions_from_extractor_object(text: &str, fun: &str) -> anyhow::Result<Ve
 
         let options = RParseEvalOptions {
             forbid_function_calls: true,
+            timeout: Some(COMPLETION_EVAL_TIMEOUT),
             ..Default::default()
         };
 
@@ -167,6 +185,19 @@ fn completions_from_extractor_object(text: &str, fun: &str) -> anyhow::Result<Ve
             },
         };
 
+        // Methods and fields of R6 objects and slots of S4 objects come with
+        // their signatures and classes
+        let members = RFunction::from(".ps.completions.getExtractorMembers")
+            .param("x", object.clone())
+            .param("operator", operator)
+            .call()?;
+
+        if !members.is_null() {
+            completions.append(&mut completions_from_members(members)?);
+            set_sort_text_by_first_appearance(&mut completions);
+            return Ok(completions);
+        }
+
         // Both `.DollarNames` and `.AtNames` have the same signature. Also, neither
         // provide a default value for `pattern` in the generic, but do provide a default
         // value of `pattern = ""` in the default S3 method. We manually pass through
@@ -199,13 +230,41 @@ fn completions_from_extractor_object(text: &str, fun: &str) -> anyhow::Result<Ve
     Ok(completions)
 }
 
+fn completions_from_members(members: RObject) -> anyhow::Result<Vec<CompletionItem>> {
+    let members: HashMap<String, RObject> = members.try_into()?;
+
+    let field = |name: &str| -> anyhow::Result<&RObject> {
+        members
+            .get(name)
+            .ok_or_else(|| anyhow!("Expected '{name}' field in extractor members"))
+    };
+
+    let names: Vec<String> = field("names")?.try_into()?;
+    let kinds: Vec<String> = field("kinds")?.try_into()?;
+    let details: Vec<String> = field("details")?.try_into()?;
+    let topic: String = field("topic")?.try_into()?;
+
+    let mut completions = vec![];
+
+    for ((name, kind), detail) in names.iter().zip(kinds.iter()).zip(details.iter()) {
+        match completion_item_from_member(name, kind, detail, &topic) {
+            Ok(item) => completions.push(item),
+            Err(err) => log::error!("{err:?}"),
+        }
+    }
+
+    Ok(completions)
+}
+
 #[cfg(test)]
 mod tests {
     use harp::eval::RParseEvalOptions;
     use harp::object::r_lgl_get;
+    use tower_lsp::lsp_types::CompletionItemKind;
 
     use crate::fixtures::package_is_installed;
     use crate::fixtures::point_from_cursor;
+    use crate::lsp::completions::sources::unique::extractor::completions_from_at;
     use crate::lsp::completions::sources::unique::extractor::completions_from_dollar;
     use crate::lsp::document_context::DocumentContext;
     use crate::lsp::documents::Document;
@@ -371,10 +430,11 @@ mod tests {
                 ..Default::default()
             };
 
-            // Set up an R6 class object with a method named `abc`
+            // Set up an R6 class object with a method named `abc` and a field
+            // named `count`
             harp::parse_eval(
                 r#"
-Foo <- R6::R6Class('Foo', public = list(abc = function() {}))
+Foo <- R6::R6Class('Foo', public = list(count = 1L, abc = function(x, y = 2) {}))
 foo <- Foo$new()
 "#,
                 options.clone(),
@@ -389,14 +449,66 @@ foo <- Foo$new()
             let completions = completions_from_dollar(&context).unwrap().unwrap();
 
             let completion_labels: Vec<String> = completions
-                .into_iter()
-                .map(|completion| completion.label)
+                .iter()
+                .map(|completion| completion.label.clone())
                 .collect();
 
             assert!(completion_labels.contains(&String::from("abc")));
 
+            // Methods come with their signature, fields with their class
+            let abc = completions.iter().find(|item| item.label == "abc").unwrap();
+            assert_eq!(abc.kind, Some(CompletionItemKind::METHOD));
+            assert_eq!(abc.detail, Some(String::from("abc(x, y = 2)")));
+
+            let count = completions
+                .iter()
+                .find(|item| item.label == "count")
+                .unwrap();
+            assert_eq!(count.kind, Some(CompletionItemKind::FIELD));
+            assert_eq!(count.detail, Some(String::from("integer")));
+
             // Clean up
             harp::parse_eval("remove(foo, Foo)", options.clone()).unwrap();
         })
     }
+
+    #[test]
+    fn test_at_completions_on_s4() {
+        r_task(|| {
+            let options = RParseEvalOptions {
+                forbid_function_calls: false,
+                ..Default::default()
+            };
+
+            harp::parse_eval(
+                r#"
+methods::setClass('ArkPerson', slots = c(name = 'character', age = 'numeric'))
+person <- methods::new('ArkPerson', name = 'Ada', age = 36)
+"#,
+                options.clone(),
+            )
+            .unwrap();
+
+            let (text, point) = point_from_cursor("person@@");
+            let document = Document::new(text.as_str(), None);
+            let context = DocumentContext::new(&document, point, None);
+
+            let completions = completions_from_at(&context).unwrap().unwrap();
+            assert_eq!(completions.len(), 2);
+
+            assert_eq!(completions[0].label, String::from("name"));
+            assert_eq!(completions[0].kind, Some(CompletionItemKind::FIELD));
+            assert_eq!(completions[0].detail, Some(String::from("character")));
+
+            assert_eq!(completions[1].label, String::from("age"));
+            assert_eq!(completions[1].detail, Some(String::from("numeric")));
+
+            // Clean up
+            harp::parse_eval(
+                "methods::removeClass('ArkPerson'); remove(person)",
+                options.clone(),
+            )
+            .unwrap();
+        })
+    }
 }
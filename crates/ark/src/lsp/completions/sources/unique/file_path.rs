@@ -5,7 +5,9 @@
 //
 //
 
+use std::collections::HashSet;
 use std::env::current_dir;
+use std::path::Path;
 use std::path::PathBuf;
 
 use harp::utils::r_is_string;
@@ -22,6 +24,7 @@ use crate::lsp::traits::rope::RopeExt;
 pub(super) fn completions_from_string_file_path(
     node: &Node,
     context: &DocumentContext,
+    document_dir: Option<&Path>,
 ) -> anyhow::Result<Vec<CompletionItem>> {
     log::trace!("completions_from_string_file_path()");
 
@@ -47,42 +50,60 @@ pub(super) fn completions_from_string_file_path(
         return Ok(completions);
     }
 
-    // Use R to normalize the path.
-    let path = r_normalize_path(contents)?;
-
-    // parse the file path and get the directory component
-    let mut path = PathBuf::from(path.as_str());
-    log::trace!("Normalized path: {}", path.display());
+    // Relative paths are resolved against the directory of the document and
+    // against the working directory, in that order. Other paths are normalized
+    // by R, which also takes care of expanding `~`.
+    let path = String::try_from(&contents)?;
+    let path = if is_relative_path(&path) {
+        PathBuf::from(path)
+    } else {
+        PathBuf::from(r_normalize_path(contents)?)
+    };
+    log::trace!("Path: {}", path.display());
 
-    // if this path doesn't have a root, add it on
-    if !path.has_root() {
-        let root = current_dir()?;
-        path = root.join(path);
-    }
+    let mut directories = vec![];
 
-    // if this isn't a directory, get the parent path
-    if !path.is_dir() {
-        if let Some(parent) = path.parent() {
-            path = parent.to_path_buf();
+    if path.has_root() {
+        directories.push(path);
+    } else {
+        if let Some(document_dir) = document_dir {
+            directories.push(document_dir.join(&path));
         }
+        directories.push(current_dir()?.join(&path));
     }
 
-    // look for files in this directory
-    log::trace!("Reading directory: {}", path.display());
-    let entries = std::fs::read_dir(path)?;
+    let mut labels = HashSet::new();
 
-    for entry in entries.into_iter() {
-        let entry = unwrap!(entry, Err(error) => {
-            log::error!("{}", error);
-            continue;
-        });
+    for mut directory in directories.into_iter() {
+        // if this isn't a directory, get the parent path
+        if !directory.is_dir() {
+            if let Some(parent) = directory.parent() {
+                directory = parent.to_path_buf();
+            }
+        }
 
-        let item = unwrap!(completion_item_from_direntry(entry), Err(error) => {
-            log::error!("{}", error);
+        // look for files in this directory
+        log::trace!("Reading directory: {}", directory.display());
+        let Ok(entries) = std::fs::read_dir(directory) else {
             continue;
-        });
-
-        completions.push(item);
+        };
+
+        for entry in entries.into_iter() {
+            let entry = unwrap!(entry, Err(error) => {
+                log::error!("{}", error);
+                continue;
+            });
+
+            let item = unwrap!(completion_item_from_direntry(entry), Err(error) => {
+                log::error!("{}", error);
+                continue;
+            });
+
+            // Entries found in several directories are only offered once
+            if labels.insert(item.label.clone()) {
+                completions.push(item);
+            }
+        }
     }
 
     // Push path completions starting with non-word characters to the bottom of
@@ -92,6 +113,10 @@ pub(super) fn completions_from_string_file_path(
     Ok(completions)
 }
 
+fn is_relative_path(path: &str) -> bool {
+    !path.starts_with('~') && !Path::new(path).has_root()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::fixtures::point_from_cursor;
@@ -111,8 +136,39 @@ mod tests {
             let context = DocumentContext::new(&document, point, None);
             let node = node_find_string(&context.node).unwrap();
 
-            let completions = completions_from_string_file_path(&node, &context).unwrap();
+            let completions = completions_from_string_file_path(&node, &context, None).unwrap();
             assert_eq!(completions.len(), 0);
         })
     }
+
+    #[test]
+    fn test_document_relative_path() {
+        r_task(|| {
+            let dir = tempfile::tempdir().unwrap();
+            std::fs::create_dir(dir.path().join("data")).unwrap();
+            std::fs::write(dir.path().join("data").join("penguins.csv"), "").unwrap();
+
+            let (text, point) = point_from_cursor(r#"read.csv("da@")"#);
+            let document = Document::new(text.as_str(), None);
+            let context = DocumentContext::new(&document, point, None);
+            let node = node_find_string(&context.node).unwrap();
+
+            let completions =
+                completions_from_string_file_path(&node, &context, Some(dir.path())).unwrap();
+            let item = completions
+                .iter()
+                .find(|item| item.label == "data/")
+                .unwrap();
+            assert!(item.command.is_some());
+
+            let (text, point) = point_from_cursor(r#"read.csv("data/@")"#);
+            let document = Document::new(text.as_str(), None);
+            let context = DocumentContext::new(&document, point, None);
+            let node = node_find_string(&context.node).unwrap();
+
+            let completions =
+                completions_from_string_file_path(&node, &context, Some(dir.path())).unwrap();
+            assert!(completions.iter().any(|item| item.label == "penguins.csv"));
+        })
+    }
 }
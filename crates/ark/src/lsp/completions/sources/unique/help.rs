@@ -0,0 +1,274 @@
+//
+// help.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use harp::exec::RFunction;
+use harp::exec::RFunctionExt;
+use harp::utils::sym_quote_invalid;
+use tower_lsp::lsp_types::CompletionItem;
+use tower_lsp::lsp_types::CompletionItemKind;
+use tower_lsp::lsp_types::CompletionItemLabelDetails;
+use tree_sitter::Node;
+
+use crate::lsp::completions::completion_context::CompletionContext;
+use crate::lsp::completions::completion_item::completion_item;
+use crate::lsp::completions::sources::CompletionSource;
+use crate::lsp::completions::types::CompletionData;
+use crate::lsp::document_context::DocumentContext;
+use crate::lsp::traits::rope::RopeExt;
+use crate::treesitter::node_find_string;
+use crate::treesitter::node_is_call;
+use crate::treesitter::node_is_namespaced_call;
+use crate::treesitter::NodeType;
+use crate::treesitter::NodeTypeExt;
+use crate::treesitter::UnaryOperatorType;
+
+pub(super) struct HelpSource;
+
+impl CompletionSource for HelpSource {
+    fn name(&self) -> &'static str {
+        "help"
+    }
+
+    fn provide_completions(
+        &self,
+        completion_context: &CompletionContext,
+    ) -> anyhow::Result<Option<Vec<CompletionItem>>> {
+        completions_from_help_topics(completion_context.document_context)
+    }
+}
+
+/// Completions for help topics of installed packages, as in `?dp<here>` or
+/// `help("dp<here>")`
+fn completions_from_help_topics(
+    context: &DocumentContext,
+) -> anyhow::Result<Option<Vec<CompletionItem>>> {
+    let Some(node) = help_topic_node(&context.node, &context.document.contents)? else {
+        return Ok(None);
+    };
+
+    let is_string = node.is_string();
+    let text = context.document.contents.node_slice(&node)?.to_string();
+
+    // Only the text up to the cursor counts as a prefix
+    let start = node.start_position();
+    let offset = if context.point.row == start.row {
+        context.point.column.saturating_sub(start.column)
+    } else {
+        text.len()
+    };
+    let mut prefix = text.get(..offset).unwrap_or(text.as_str());
+    if is_string {
+        prefix = prefix.trim_start_matches(['"', '\'']);
+    }
+
+    // Listing every topic of every installed package isn't useful
+    if prefix.is_empty() {
+        return Ok(None);
+    }
+
+    let topics = RFunction::from(".ps.help.topicsWithPrefix")
+        .add(prefix)
+        .call()?;
+
+    let names = topics.names().unwrap_or_default();
+    let packages = unsafe { topics.to::<Vec<String>>()? };
+
+    let mut completions = vec![];
+
+    for (topic, package) in names.into_iter().zip(packages) {
+        let Some(topic) = topic else {
+            continue;
+        };
+
+        let mut item = completion_item(&topic, CompletionData::HelpTopic {
+            topic: topic.clone(),
+            package: package.clone(),
+        })?;
+
+        item.kind = Some(CompletionItemKind::REFERENCE);
+        item.label_details = Some(CompletionItemLabelDetails {
+            detail: None,
+            description: Some(package),
+        });
+
+        // Quoted topics are inserted verbatim, bare ones may need backticks,
+        // e.g. for `[.data.frame`
+        if !is_string {
+            item.insert_text = Some(sym_quote_invalid(&topic));
+        }
+
+        completions.push(item);
+    }
+
+    Ok(Some(completions))
+}
+
+/// The node holding the help topic at `node`, if it is the topic of `?topic`
+/// or the first argument of `help()`
+///
+/// The topic is either an identifier or a string.
+pub(crate) fn help_topic_node<'tree>(
+    node: &Node<'tree>,
+    contents: &ropey::Rope,
+) -> anyhow::Result<Option<Node<'tree>>> {
+    // Inside a string, the string is the topic
+    let node = node_find_string(node).unwrap_or(*node);
+
+    if !node.is_identifier() && !node.is_string() {
+        return Ok(None);
+    }
+
+    let Some(parent) = node.parent() else {
+        return Ok(None);
+    };
+
+    // `?topic`
+    if parent.node_type() == NodeType::UnaryOperator(UnaryOperatorType::Help) {
+        return Ok(Some(node));
+    }
+
+    // `help(topic)` or `help(topic = "topic")`
+    if parent.node_type() != NodeType::Argument {
+        return Ok(None);
+    }
+    if parent.child_by_field_name("value") != Some(node) {
+        return Ok(None);
+    }
+
+    let Some(arguments) = parent.parent() else {
+        return Ok(None);
+    };
+
+    match parent.child_by_field_name("name") {
+        Some(name) => {
+            if contents.node_slice(&name)?.to_string() != "topic" {
+                return Ok(None);
+            }
+        },
+        None => {
+            let mut cursor = arguments.walk();
+            let first = arguments
+                .children_by_field_name("argument", &mut cursor)
+                .next();
+            if first != Some(parent) {
+                return Ok(None);
+            }
+        },
+    }
+
+    let Some(call) = arguments.parent() else {
+        return Ok(None);
+    };
+
+    if !node_is_call(&call, "help", contents) &&
+        !node_is_namespaced_call(&call, "utils", "help", contents)
+    {
+        return Ok(None);
+    }
+
+    Ok(Some(node))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::point_from_cursor;
+    use crate::lsp::completions::sources::unique::help::completions_from_help_topics;
+    use crate::lsp::document_context::DocumentContext;
+    use crate::lsp::documents::Document;
+    use crate::r_task;
+
+    fn completions(code: &str) -> Option<Vec<(String, Option<String>)>> {
+        let (text, point) = point_from_cursor(code);
+        let document = Document::new(text.as_str(), None);
+        let context = DocumentContext::new(&document, point, None);
+
+        let completions = completions_from_help_topics(&context).unwrap()?;
+        Some(
+            completions
+                .into_iter()
+                .map(|item| (item.label, item.insert_text))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_completion_help_topics() {
+        r_task(|| {
+            let has_topic = |code: &str, topic: &str, insert_text: Option<&str>| {
+                let completions = completions(code).unwrap();
+                assert!(completions
+                    .iter()
+                    .any(|(label, text)| label == topic && text.as_deref() == insert_text));
+            };
+
+            has_topic("?as.charac@", "as.character", Some("as.character"));
+            has_topic("help(as.charac@)", "as.character", Some("as.character"));
+            has_topic(
+                "utils::help(as.charac@)",
+                "as.character",
+                Some("as.character"),
+            );
+            has_topic("help('as.charac@')", "as.character", None);
+            has_topic("help(topic = \"as.charac@\")", "as.character", None);
+
+            // Not a help topic
+            assert_eq!(completions("help(package = as.charac@)"), None);
+            assert_eq!(completions("identity(as.charac@)"), None);
+            assert_eq!(completions("as.charac@"), None);
+        })
+    }
+
+    #[test]
+    fn test_completion_help_topics_installed_packages() {
+        r_task(|| {
+            let has_topic = |topic: &str| {
+                let code = format!("?{topic}@");
+                completions(&code)
+                    .unwrap()
+                    .iter()
+                    .any(|(label, _)| label == topic)
+            };
+
+            // Fake installed packages with a single help alias
+            harp::parse_eval_global(
+                r#"local({
+                    lib <- tempfile("lib")
+                    dir.create(lib)
+                    .ark_test_lib_paths <<- .libPaths()
+                    .ark_test_install <<- function(pkg) {
+                        path <- file.path(lib, pkg)
+                        dir.create(file.path(path, "Meta"), recursive = TRUE)
+                        dir.create(file.path(path, "help"))
+                        desc <- c(Package = pkg, Version = "1.0")
+                        write.dcf(t(desc), file.path(path, "DESCRIPTION"))
+                        saveRDS(list(DESCRIPTION = desc), file.path(path, "Meta", "package.rds"))
+                        alias <- paste0(pkg, "Topic")
+                        saveRDS(c(alias = alias), file.path(path, "help", "aliases.rds"))
+                        # Make sure the modification time changes
+                        Sys.setFileTime(lib, Sys.time() + 60 * length(dir(lib)))
+                    }
+                    .ark_test_install("arkFakeA")
+                    .libPaths(c(lib, .libPaths()))
+                })"#,
+            )
+            .unwrap();
+
+            // The cache is keyed on the library paths
+            assert!(has_topic("arkFakeATopic"));
+
+            // Installing a package invalidates the cache
+            harp::parse_eval_global(".ark_test_install('arkFakeB')").unwrap();
+            assert!(has_topic("arkFakeBTopic"));
+
+            harp::parse_eval_global(
+                ".libPaths(.ark_test_lib_paths); rm(.ark_test_lib_paths, .ark_test_install)",
+            )
+            .unwrap();
+            assert!(!has_topic("arkFakeATopic"));
+        })
+    }
+}
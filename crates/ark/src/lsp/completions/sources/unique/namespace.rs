@@ -5,6 +5,8 @@
 //
 //
 
+use std::collections::HashSet;
+
 use harp::exec::RFunction;
 use harp::exec::RFunctionExt;
 use harp::object::RObject;
@@ -15,6 +17,7 @@ use libr::Rboolean_TRUE;
 use libr::Rf_findVarInFrame;
 use libr::SEXP;
 use tower_lsp::lsp_types::CompletionItem;
+use tower_lsp::lsp_types::CompletionItemKind;
 use tree_sitter::Node;
 use tree_sitter::Point;
 
@@ -87,16 +90,27 @@ fn completions_from_namespace(
         return Ok(Some(completions));
     };
 
-    let symbols = if package == "base" {
+    // Non-exported objects are only offered after `:::` when the user opted in
+    let include_internal = package != "base" &&
+        !exports_only &&
+        completion_context.state.config.completions.internal_symbols;
+
+    let symbols = if package == "base" || include_internal {
         list_namespace_symbols(*namespace)
-    } else if exports_only {
-        list_namespace_exports(*namespace)
     } else {
-        list_namespace_symbols(*namespace)
+        list_namespace_exports(*namespace)
     };
 
     let strings = unsafe { symbols.to::<Vec<String>>()? };
 
+    let exports: HashSet<String> = if include_internal {
+        unsafe { list_namespace_exports(*namespace).to::<Vec<String>>()? }
+            .into_iter()
+            .collect()
+    } else {
+        HashSet::new()
+    };
+
     for string in strings.iter() {
         let item = unsafe {
             completion_item_from_namespace(
@@ -107,7 +121,12 @@ fn completions_from_namespace(
             )
         };
         match item {
-            Ok(item) => completions.push(item),
+            Ok(mut item) => {
+                if include_internal && !exports.contains(string) {
+                    item.kind = Some(CompletionItemKind::REFERENCE);
+                }
+                completions.push(item)
+            },
             Err(error) => log::error!("{error:?}"),
         }
     }
@@ -242,6 +261,7 @@ fn list_namespace_exports(namespace: SEXP) -> RObject {
 #[cfg(test)]
 mod tests {
     use tower_lsp::lsp_types::CompletionItem;
+    use tower_lsp::lsp_types::CompletionItemKind;
 
     use crate::fixtures::point_from_cursor;
     use crate::lsp::completions::completion_context::CompletionContext;
@@ -254,12 +274,18 @@ mod tests {
 
     pub(crate) fn get_namespace_completions_at_cursor(
         cursor_text: &str,
+    ) -> anyhow::Result<Option<Vec<CompletionItem>>> {
+        get_namespace_completions_at_cursor_with_state(cursor_text, &WorldState::default())
+    }
+
+    fn get_namespace_completions_at_cursor_with_state(
+        cursor_text: &str,
+        state: &WorldState,
     ) -> anyhow::Result<Option<Vec<CompletionItem>>> {
         let (text, point) = point_from_cursor(cursor_text);
         let document = Document::new(&text, None);
         let document_context = DocumentContext::new(&document, point, None);
-        let state = WorldState::default();
-        let context = CompletionContext::new(&document_context, &state);
+        let context = CompletionContext::new(&document_context, state);
 
         completions_from_namespace(&context)
     }
@@ -279,13 +305,29 @@ mod tests {
             let item = find_completion_by_label(&completions, "as.bibentry.bibentry");
             assert!(item.is_none());
 
-            // Internal functions with `:::`
+            // Internal functions with `:::` are opt-in
             let completions = get_namespace_completions_at_cursor("utils:::@")
                 .unwrap()
                 .unwrap();
             let item = find_completion_by_label(&completions, "as.bibentry.bibentry");
+            assert!(item.is_none());
+            let item = find_completion_by_label(&completions, "adist");
             assert!(item.is_some());
 
+            let mut state = WorldState::default();
+            state.config.completions.internal_symbols = true;
+
+            let completions = get_namespace_completions_at_cursor_with_state("utils:::@", &state)
+                .unwrap()
+                .unwrap();
+            let item = find_completion_by_label(&completions, "as.bibentry.bibentry");
+            assert!(item.is_some());
+
+            // They have a distinct kind, exported functions keep theirs
+            assert_eq!(item.unwrap().kind, Some(CompletionItemKind::REFERENCE));
+            let item = find_completion_by_label(&completions, "adist");
+            assert_eq!(item.unwrap().kind, Some(CompletionItemKind::FUNCTION));
+
             // With RHS text, which is ignored when generating completions.
             // Filtering applied on frontend side.
             let completions = get_namespace_completions_at_cursor("utils::bl@ah")
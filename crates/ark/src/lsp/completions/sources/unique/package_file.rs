@@ -0,0 +1,334 @@
+//
+// package_file.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use harp::exec::RFunction;
+use harp::exec::RFunctionExt;
+use tower_lsp::lsp_types::CompletionItem;
+use tower_lsp::lsp_types::CompletionItemKind;
+use tower_lsp::lsp_types::CompletionItemLabelDetails;
+use tower_lsp::lsp_types::Documentation;
+use tower_lsp::lsp_types::MarkupContent;
+use tower_lsp::lsp_types::MarkupKind;
+
+use crate::lsp::completions::completion_context::CompletionContext;
+use crate::lsp::completions::completion_item::completion_item;
+use crate::lsp::completions::completion_item::completion_item_from_package;
+use crate::lsp::completions::sources::CompletionSource;
+use crate::lsp::completions::types::CompletionData;
+use crate::lsp::indexer;
+use crate::lsp::inputs::package::Package;
+use crate::lsp::package_files::description_position;
+use crate::lsp::package_files::DescriptionPosition;
+use crate::lsp::package_files::PackageFile;
+use crate::lsp::package_files::DEPENDENCY_FIELDS;
+use crate::lsp::package_files::DESCRIPTION_FIELDS;
+use crate::lsp::traits::rope::RopeExt;
+use crate::treesitter::node_find_containing_call;
+use crate::treesitter::NodeType;
+use crate::treesitter::NodeTypeExt;
+
+/// Completions for the `DESCRIPTION` and `NAMESPACE` files of packages. These
+/// files aren't R scripts, so R completions are never provided for them.
+pub(super) struct PackageFileSource;
+
+impl CompletionSource for PackageFileSource {
+    fn name(&self) -> &'static str {
+        "package_file"
+    }
+
+    fn provide_completions(
+        &self,
+        completion_context: &CompletionContext,
+    ) -> anyhow::Result<Option<Vec<CompletionItem>>> {
+        let Some(file) = completion_context.uri.and_then(PackageFile::from_url) else {
+            return Ok(None);
+        };
+
+        let completions = match file {
+            PackageFile::Description => completions_from_description(completion_context)?,
+            PackageFile::Namespace => completions_from_namespace(completion_context)?,
+        };
+
+        Ok(Some(completions))
+    }
+}
+
+fn completions_from_description(
+    completion_context: &CompletionContext,
+) -> anyhow::Result<Vec<CompletionItem>> {
+    let context = completion_context.document_context;
+    let contents = &context.document.contents;
+    let point = context.point;
+
+    match description_position(contents, point) {
+        Some(DescriptionPosition::FieldName) => {
+            // Don't suggest fields that are already there
+            let existing: Vec<String> = contents
+                .lines()
+                .enumerate()
+                .filter(|(row, _)| *row != point.row)
+                .filter_map(|(_, line)| {
+                    let line = line.to_string();
+                    line.split_once(':').map(|(name, _)| name.to_string())
+                })
+                .collect();
+
+            let mut completions = vec![];
+
+            for (name, documentation) in DESCRIPTION_FIELDS {
+                if existing.iter().any(|field| field == name) {
+                    continue;
+                }
+                completions.push(completion_item_from_description_field(name, documentation)?);
+            }
+
+            Ok(completions)
+        },
+
+        Some(DescriptionPosition::FieldValue { field })
+            if DEPENDENCY_FIELDS.contains(&field.as_str()) =>
+        {
+            // Not within a version requirement
+            let line = contents.line(point.row).to_string();
+            let before = line.get(..point.column).unwrap_or_default();
+            if before.matches('(').count() > before.matches(')').count() {
+                return Ok(vec![]);
+            }
+
+            completions_from_installed_packages()
+        },
+
+        _ => Ok(vec![]),
+    }
+}
+
+fn completion_item_from_description_field(
+    name: &str,
+    documentation: &str,
+) -> anyhow::Result<CompletionItem> {
+    let mut item = completion_item(name, CompletionData::Unknown)?;
+
+    item.kind = Some(CompletionItemKind::FIELD);
+    item.insert_text = Some(format!("{name}: "));
+    item.documentation = Some(Documentation::MarkupContent(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: documentation.to_string(),
+    }));
+
+    Ok(item)
+}
+
+/// Completions for the arguments of NAMESPACE directives, e.g. the functions
+/// of the workspace in `export()` and the packages and their exports in
+/// `importFrom()`
+fn completions_from_namespace(
+    completion_context: &CompletionContext,
+) -> anyhow::Result<Vec<CompletionItem>> {
+    let context = completion_context.document_context;
+    let contents = &context.document.contents;
+
+    let Some(call) = node_find_containing_call(context.node) else {
+        return Ok(vec![]);
+    };
+    let (Some(callee), Some(arguments)) = (
+        call.child_by_field_name("function"),
+        call.child_by_field_name("arguments"),
+    ) else {
+        return Ok(vec![]);
+    };
+
+    // Must be within the parentheses
+    if context.point <= arguments.start_position() {
+        return Ok(vec![]);
+    }
+
+    let mut cursor = arguments.walk();
+    let index = arguments
+        .children(&mut cursor)
+        .filter(|child| child.node_type() == NodeType::Comma)
+        .filter(|comma| comma.end_position() <= context.point)
+        .count();
+
+    let callee = contents.node_slice(&callee)?.to_string();
+
+    match (callee.as_str(), index) {
+        ("export", _) => {
+            let mut completions = vec![];
+
+            indexer::map(|_uri, _symbol, entry| {
+                let indexer::IndexEntryData::Function { name, .. } = &entry.data else {
+                    return;
+                };
+                match completion_item(name, CompletionData::Unknown) {
+                    Ok(mut item) => {
+                        item.kind = Some(CompletionItemKind::FUNCTION);
+                        completions.push(item);
+                    },
+                    Err(err) => log::error!("{err:?}"),
+                }
+            });
+
+            Ok(completions)
+        },
+
+        ("import", _) | ("importFrom", 0) => completions_from_installed_packages(),
+
+        ("importFrom", _) => {
+            let mut cursor = arguments.walk();
+            let Some(package) = arguments
+                .children_by_field_name("argument", &mut cursor)
+                .next()
+                .and_then(|argument| argument.child_by_field_name("value"))
+            else {
+                return Ok(vec![]);
+            };
+
+            let package = contents.node_slice(&package)?.to_string();
+            let package = package.trim_matches(|char| matches!(char, '"' | '\'' | '`'));
+
+            match completion_context.state.library.get(package) {
+                Some(package) => completions_from_exported_symbols(&package),
+                None => Ok(vec![]),
+            }
+        },
+
+        _ => Ok(vec![]),
+    }
+}
+
+/// Completions for all installed packages
+pub(super) fn completions_from_installed_packages() -> anyhow::Result<Vec<CompletionItem>> {
+    let packages: Vec<String> = RFunction::new("base", ".packages")
+        .param("all.available", true)
+        .call()?
+        .try_into()?;
+
+    let mut completions = vec![];
+    for package in packages {
+        completions.push(unsafe { completion_item_from_package(&package, false) }?);
+    }
+
+    Ok(completions)
+}
+
+/// Completions for the symbols exported by `package`, resolved with their
+/// help topics
+pub(super) fn completions_from_exported_symbols(
+    package: &Package,
+) -> anyhow::Result<Vec<CompletionItem>> {
+    let mut completions = vec![];
+
+    for symbol in package.exported_symbols.iter() {
+        let mut item = completion_item(symbol, CompletionData::HelpTopic {
+            topic: symbol.clone(),
+            package: package.description.name.clone(),
+        })?;
+        item.label_details = Some(CompletionItemLabelDetails {
+            detail: None,
+            description: Some(format!("{{{}}}", package.description.name)),
+        });
+        completions.push(item);
+    }
+
+    Ok(completions)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use tree_sitter::Point;
+    use url::Url;
+
+    use crate::lsp::completions::completion_context::CompletionContext;
+    use crate::lsp::completions::sources::unique::package_file::PackageFileSource;
+    use crate::lsp::completions::sources::CompletionSource;
+    use crate::lsp::document_context::DocumentContext;
+    use crate::lsp::documents::Document;
+    use crate::lsp::inputs::library::Library;
+    use crate::lsp::inputs::package::Package;
+    use crate::lsp::inputs::package_description::Dcf;
+    use crate::lsp::inputs::package_description::Description;
+    use crate::lsp::inputs::package_namespace::Namespace;
+    use crate::lsp::state::WorldState;
+
+    fn labels(state: &WorldState, uri: &str, code: &str, point: Point) -> Option<Vec<String>> {
+        let uri = Url::parse(uri).unwrap();
+        let document = Document::new(code, None);
+        let document_context = DocumentContext::new(&document, point, None);
+        let mut context = CompletionContext::new(&document_context, state);
+        context.uri = Some(&uri);
+
+        PackageFileSource
+            .provide_completions(&context)
+            .unwrap()
+            .map(|items| items.into_iter().map(|item| item.label).collect())
+    }
+
+    #[test]
+    fn test_description_field_completions() {
+        let state = WorldState::default();
+        let code = "Package: mypkg\nTi\n";
+
+        let fields = labels(&state, "file:///pkg/DESCRIPTION", code, Point::new(1, 2)).unwrap();
+        assert!(fields.contains(&String::from("Title")));
+        assert!(fields.contains(&String::from("Imports")));
+
+        // Existing fields are skipped
+        assert!(!fields.contains(&String::from("Package")));
+
+        // No completions within values of other fields
+        assert_eq!(
+            labels(&state, "file:///pkg/DESCRIPTION", code, Point::new(0, 11)),
+            Some(vec![])
+        );
+
+        // Not a package file
+        assert_eq!(
+            labels(&state, "file:///pkg/R/file.R", code, Point::new(1, 2)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_namespace_import_from_completions() {
+        let namespace = Namespace {
+            exports: vec!["foo".to_string(), "bar".to_string()],
+            imports: vec![],
+            package_imports: vec![],
+        };
+        let description = Description {
+            name: "mypkg".to_string(),
+            version: "1.0.0".to_string(),
+            depends: vec![],
+            fields: Dcf::new(),
+        };
+        let package = Package::from_parts(PathBuf::from("/mock/path"), description, namespace);
+
+        let state = WorldState {
+            library: Library::new(vec![]).insert("mypkg", package),
+            ..Default::default()
+        };
+
+        let code = "importFrom(mypkg, )\n";
+        let symbols = labels(&state, "file:///pkg/NAMESPACE", code, Point::new(0, 18)).unwrap();
+        assert_eq!(symbols, vec![String::from("bar"), String::from("foo")]);
+
+        // Unknown packages don't have completions
+        let code = "importFrom(unknown, )\n";
+        assert_eq!(
+            labels(&state, "file:///pkg/NAMESPACE", code, Point::new(0, 20)),
+            Some(vec![])
+        );
+
+        // Outside of directives
+        assert_eq!(
+            labels(&state, "file:///pkg/NAMESPACE", code, Point::new(1, 0)),
+            Some(vec![])
+        );
+    }
+}
@@ -5,6 +5,8 @@
 //
 //
 
+use std::path::Path;
+
 use tower_lsp::lsp_types::CompletionItem;
 
 use super::file_path::completions_from_string_file_path;
@@ -25,12 +27,14 @@ impl CompletionSource for StringSource {
         &self,
         completion_context: &CompletionContext,
     ) -> anyhow::Result<Option<Vec<CompletionItem>>> {
-        completions_from_string(completion_context.document_context)
+        let document_dir = completion_context.document_dir();
+        completions_from_string(completion_context.document_context, document_dir.as_deref())
     }
 }
 
 fn completions_from_string(
     context: &DocumentContext,
+    document_dir: Option<&Path>,
 ) -> anyhow::Result<Option<Vec<CompletionItem>>> {
     let node = context.node;
 
@@ -64,7 +68,11 @@ fn completions_from_string(
     }
 
     // If no special string cases are hit, we show file path completions
-    completions.append(&mut completions_from_string_file_path(&node, context)?);
+    completions.append(&mut completions_from_string_file_path(
+        &node,
+        context,
+        document_dir,
+    )?);
 
     Ok(Some(completions))
 }
@@ -94,7 +102,7 @@ mod tests {
             let context = DocumentContext::new(&document, point, None);
 
             assert!(node_find_string(&context.node).is_some());
-            assert_eq!(completions_from_string(&context).unwrap(), None);
+            assert_eq!(completions_from_string(&context, None).unwrap(), None);
         })
     }
 
@@ -106,7 +114,7 @@ mod tests {
             let context = DocumentContext::new(&document, point, None);
 
             assert!(context.node.is_identifier());
-            assert_eq!(completions_from_string(&context).unwrap(), None);
+            assert_eq!(completions_from_string(&context, None).unwrap(), None);
         })
     }
 
@@ -121,7 +129,7 @@ mod tests {
             // `None` trigger -> Return file completions
             let context = DocumentContext::new(&document, point, None);
             assert_match!(
-                completions_from_string(&context).unwrap(),
+                completions_from_string(&context, None).unwrap(),
                 Some(items) => {
                     assert!(items.len() > 0)
                 }
@@ -129,7 +137,7 @@ mod tests {
 
             // `Some` trigger -> Should return empty completion set
             let context = DocumentContext::new(&document, point, Some(String::from("$")));
-            let res = completions_from_string(&context).unwrap();
+            let res = completions_from_string(&context, None).unwrap();
             assert_match!(res, Some(items) => { assert!(items.len() == 0) });
 
             // Check for same result when consulting (potentially all) unique sources
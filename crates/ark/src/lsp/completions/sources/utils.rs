@@ -5,6 +5,8 @@
 //
 //
 
+use std::time::Duration;
+
 use harp::error::Error;
 use harp::eval::RParseEvalOptions;
 use harp::exec::RFunction;
@@ -24,6 +26,11 @@ use crate::lsp::traits::rope::RopeExt;
 use crate::treesitter::NodeType;
 use crate::treesitter::NodeTypeExt;
 
+/// Maximum time spent evaluating objects to complete their names or show
+/// their signatures, so that slow active bindings or promises can't hang the
+/// LSP
+pub(crate) const COMPLETION_EVAL_TIMEOUT: Duration = Duration::from_millis(500);
+
 pub(crate) fn has_priority_prefix(sort_text: &str) -> bool {
     // If we want to force a completion item to the top of the list,
     // we pre-emptively set its `sort_text` to begin with "0-".
@@ -197,6 +204,7 @@ pub(super) fn completions_from_evaluated_object_names(
 
     let options = RParseEvalOptions {
         forbid_function_calls: true,
+        timeout: Some(COMPLETION_EVAL_TIMEOUT),
         ..Default::default()
     };
 
@@ -216,6 +224,10 @@ pub(super) fn completions_from_evaluated_object_names(
                 log::trace!("Can't evaluate object: {message}");
                 return Ok(None);
             },
+            Error::Timeout { .. } => {
+                log::warn!("Can't evaluate object: {err}");
+                return Ok(None);
+            },
             _ => {
                 log::error!("Can't evaluate object: {err}");
                 return Ok(None);
@@ -20,7 +20,7 @@ pub(crate) fn get_completions_at_cursor(cursor_text: &str) -> anyhow::Result<Vec
     let document_context = DocumentContext::new(&document, point, None);
     let state = WorldState::default();
 
-    match provide_completions(&document_context, &state) {
+    match provide_completions(&document_context, &state, None) {
         Ok(completions) => Ok(completions),
         Err(err) => Err(anyhow::anyhow!("Failed to get completions: {err}")),
     }
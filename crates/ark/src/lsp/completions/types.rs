@@ -29,9 +29,18 @@ pub(super) enum CompletionData {
     Object {
         name: String,
     },
+    HelpTopic {
+        topic: String,
+        package: String,
+    },
     Keyword {
         name: String,
     },
+    Member {
+        name: String,
+        kind: String,
+        topic: String,
+    },
     Package {
         name: String,
     },
@@ -25,6 +25,14 @@ pub static GLOBAL_SETTINGS: &[Setting<LspConfig>] = &[
                 .unwrap_or_else(|| DiagnosticsConfig::default().enable)
         },
     },
+    Setting {
+        key: "positron.r.diagnostics.spellCheck",
+        set: |cfg, v| {
+            cfg.diagnostics.spell_check = v
+                .as_bool()
+                .unwrap_or_else(|| DiagnosticsConfig::default().spell_check)
+        },
+    },
     Setting {
         key: "positron.r.symbols.includeAssignmentsInBlocks",
         set: |cfg, v| {
@@ -41,6 +49,64 @@ pub static GLOBAL_SETTINGS: &[Setting<LspConfig>] = &[
                 .unwrap_or_else(|| WorkspaceSymbolsConfig::default().include_comment_sections)
         },
     },
+    Setting {
+        key: "positron.r.completions.snippets",
+        set: |cfg, v| {
+            cfg.completions.snippets = v
+                .as_bool()
+                .unwrap_or_else(|| CompletionsConfig::default().snippets)
+        },
+    },
+    Setting {
+        key: "positron.r.completions.internalSymbols",
+        set: |cfg, v| {
+            cfg.completions.internal_symbols = v
+                .as_bool()
+                .unwrap_or_else(|| CompletionsConfig::default().internal_symbols)
+        },
+    },
+    Setting {
+        key: "positron.r.completions.maxItems",
+        set: |cfg, v| {
+            cfg.completions.max_items = v
+                .as_u64()
+                .filter(|n| *n > 0)
+                .map(|n| n as usize)
+                .or_else(|| CompletionsConfig::default().max_items)
+        },
+    },
+    Setting {
+        key: "positron.r.completions.dataMaskingFunctions",
+        set: |cfg, v| {
+            cfg.completions.data_masking_functions = match v.as_array() {
+                Some(functions) => functions
+                    .iter()
+                    .filter_map(|function| function.as_str().map(String::from))
+                    .collect(),
+                None => CompletionsConfig::default().data_masking_functions,
+            }
+        },
+    },
+    Setting {
+        key: "positron.r.indexer.exclude",
+        set: |cfg, v| {
+            cfg.indexer.exclude = match v.as_array() {
+                Some(globs) => globs
+                    .iter()
+                    .filter_map(|glob| glob.as_str().map(String::from))
+                    .collect(),
+                None => IndexerConfig::default().exclude,
+            }
+        },
+    },
+    Setting {
+        key: "positron.r.telemetry.enable",
+        set: |cfg, v| {
+            cfg.telemetry.enable = v
+                .as_bool()
+                .unwrap_or_else(|| TelemetryConfig::default().enable)
+        },
+    },
 ];
 
 /// These document settings are updated on a URI basis. Each document has its
@@ -86,6 +152,9 @@ pub(crate) struct LspConfig {
     pub(crate) diagnostics: DiagnosticsConfig,
     pub(crate) symbols: SymbolsConfig,
     pub(crate) workspace_symbols: WorkspaceSymbolsConfig,
+    pub(crate) completions: CompletionsConfig,
+    pub(crate) indexer: IndexerConfig,
+    pub(crate) telemetry: TelemetryConfig,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -100,6 +169,39 @@ pub struct WorkspaceSymbolsConfig {
     pub include_comment_sections: bool,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CompletionsConfig {
+    /// Whether to offer snippets for common R idioms like `tryCatch()` and
+    /// for control flow keywords like `for`.
+    pub snippets: bool,
+
+    /// Whether to offer non-exported objects of a namespace after `pkg:::`.
+    pub internal_symbols: bool,
+
+    /// Maximum number of completions sent to the client. The list is marked
+    /// as incomplete when truncated so the client asks again as the user types.
+    pub max_items: Option<usize>,
+
+    /// Functions that evaluate their arguments in the context of the data
+    /// frame supplied as first argument, on top of the builtin dplyr, tidyr,
+    /// and base ones. Columns of the data frame are offered as completions.
+    pub data_masking_functions: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct IndexerConfig {
+    /// Globs of files that are not indexed, matched against paths relative
+    /// to the workspace folder, e.g. `inst/**` or `**/*_generated.R`.
+    pub exclude: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct TelemetryConfig {
+    /// Whether to record the latency of completions, hover, diagnostics, and
+    /// folding ranges. Retrieved with the `ark/internal/telemetry` request.
+    pub enable: bool,
+}
+
 /// Configuration of a document.
 ///
 /// The naming follows <https://editorconfig.org/> where possible.
@@ -143,6 +245,17 @@ impl Default for WorkspaceSymbolsConfig {
     }
 }
 
+impl Default for CompletionsConfig {
+    fn default() -> Self {
+        Self {
+            snippets: true,
+            internal_symbols: false,
+            max_items: None,
+            data_masking_functions: Vec::new(),
+        }
+    }
+}
+
 impl Default for IndentationConfig {
     fn default() -> Self {
         Self {
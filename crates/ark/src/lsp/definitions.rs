@@ -6,10 +6,16 @@
 //
 
 use anyhow::Result;
+use harp::exec::RFunction;
+use harp::exec::RFunctionExt;
+use harp::RObject;
 use tower_lsp::lsp_types::GotoDefinitionParams;
 use tower_lsp::lsp_types::GotoDefinitionResponse;
 use tower_lsp::lsp_types::LocationLink;
+use tower_lsp::lsp_types::Position;
 use tower_lsp::lsp_types::Range;
+use tower_lsp::lsp_types::Url;
+use tree_sitter::Node;
 
 use crate::lsp::documents::Document;
 use crate::lsp::encoding::convert_point_to_position;
@@ -17,6 +23,8 @@ use crate::lsp::encoding::convert_position_to_point;
 use crate::lsp::indexer;
 use crate::lsp::traits::node::NodeExt;
 use crate::lsp::traits::rope::RopeExt;
+use crate::modules::ARK_ENVS;
+use crate::r_task;
 use crate::treesitter::NodeTypeExt;
 
 pub fn goto_definition<'a>(
@@ -61,14 +69,22 @@ pub fn goto_definition<'a>(
             let response = GotoDefinitionResponse::Link(vec![link]);
             return Ok(Some(response));
         }
+
+        // Then search for a function in the R session, e.g. `dplyr::mutate`
+        let package = namespace_package(&node, document)?;
+        let link = r_task(|| session_definition(&symbol, package.as_deref()));
+
+        match link {
+            Ok(Some(mut link)) => {
+                link.origin_selection_range = Some(range);
+                let response = GotoDefinitionResponse::Link(vec![link]);
+                return Ok(Some(response));
+            },
+            Ok(None) => {},
+            Err(err) => log::trace!("Can't find definition of `{symbol}` in the session: {err}"),
+        }
     }
 
-    // TODO: We should see if we can find the referenced item in:
-    //
-    // 1. The document's current AST,
-    // 2. The public functions from other documents in the project,
-    // 3. A definition in the R session (which we could open in a virtual document)
-    //
     // If we can't find a definition, then we can return the referenced item itself,
     // which will tell Positron to instead try to look for references for that symbol.
     let link = LocationLink {
@@ -82,6 +98,63 @@ pub fn goto_definition<'a>(
     Ok(Some(response))
 }
 
+/// The package of `pkg::fn` or `pkg:::fn` when `node` is `fn`
+fn namespace_package(node: &Node, document: &Document) -> anyhow::Result<Option<String>> {
+    let Some(parent) = node.parent() else {
+        return Ok(None);
+    };
+
+    if !parent.is_namespace_operator() || parent.child_by_field_name("rhs") != Some(*node) {
+        return Ok(None);
+    }
+
+    let Some(lhs) = parent.child_by_field_name("lhs") else {
+        return Ok(None);
+    };
+
+    Ok(Some(document.contents.node_slice(&lhs)?.to_string()))
+}
+
+/// Finds the definition of a function of the R session
+///
+/// Functions with source references point to their source file, or to the
+/// virtual namespace document of their package if it has been generated.
+/// Other functions are deparsed into a new virtual document.
+fn session_definition(symbol: &str, package: Option<&str>) -> anyhow::Result<Option<LocationLink>> {
+    let info = RFunction::new("", "definition_function_info")
+        .add(symbol)
+        .param("package", package)
+        .call_in(ARK_ENVS.positron_ns)?;
+
+    if info.is_null() {
+        return Ok(None);
+    }
+
+    let uri: String = RObject::view(harp::list_get(info.sexp, 0)).try_into()?;
+    let line: i32 = RObject::view(harp::list_get(info.sexp, 1)).try_into()?;
+    let column: i32 = RObject::view(harp::list_get(info.sexp, 2)).try_into()?;
+
+    let target_uri = if uri.starts_with("ark:") {
+        Url::parse(&uri)?
+    } else {
+        let Ok(uri) = Url::from_file_path(&uri) else {
+            return Err(anyhow::anyhow!("Can't convert `{uri}` to a URI"));
+        };
+        uri
+    };
+
+    // Source references are 1-based
+    let position = Position::new((line - 1).max(0) as u32, (column - 1).max(0) as u32);
+    let target_range = Range::new(position, position);
+
+    Ok(Some(LocationLink {
+        origin_selection_range: None,
+        target_uri,
+        target_range,
+        target_selection_range: target_range,
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
@@ -271,4 +344,64 @@ foo
             }
         );
     }
+
+    #[test]
+    fn test_goto_definition_session_function() {
+        let _guard = indexer::ResetIndexerGuard;
+
+        let file = tempfile::NamedTempFile::with_suffix(".R").unwrap();
+        std::fs::write(file.path(), "\nmy_session_fn <- function() 1\n").unwrap();
+        let path = file.path().to_string_lossy().replace('\\', "/");
+
+        r_task(|| {
+            RFunction::new("base", "source")
+                .add(path.as_str())
+                .param("keep.source", true)
+                .call()
+                .unwrap();
+        });
+
+        let doc = Document::new("my_session_fn()", None);
+        let uri = test_path("test.R");
+
+        let params = GotoDefinitionParams {
+            text_document_position_params: lsp_types::TextDocumentPositionParams {
+                text_document: lsp_types::TextDocumentIdentifier { uri },
+                position: lsp_types::Position::new(0, 2),
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+
+        assert_matches!(
+            goto_definition(&doc, params).unwrap(),
+            Some(GotoDefinitionResponse::Link(ref links)) => {
+                assert_eq!(links[0].target_uri.scheme(), "file");
+                assert!(links[0].target_uri.path().ends_with(".R"));
+                assert_eq!(links[0].target_range.start.line, 1);
+            }
+        );
+
+        r_task(|| {
+            harp::parse_eval_global("rm(my_session_fn)").unwrap();
+        });
+    }
+
+    #[test]
+    fn test_goto_definition_session_function_does_not_load_namespace() {
+        r_task(|| {
+            let is_loaded = || -> bool {
+                harp::parse_eval_global("isNamespaceLoaded('splines')")
+                    .unwrap()
+                    .try_into()
+                    .unwrap()
+            };
+            if is_loaded() {
+                return;
+            }
+
+            assert!(session_definition("bs", Some("splines")).unwrap().is_none());
+            assert!(!is_loaded());
+        });
+    }
 }
@@ -18,12 +18,14 @@ use ropey::Rope;
 use stdext::*;
 use tower_lsp::lsp_types::Diagnostic;
 use tower_lsp::lsp_types::DiagnosticSeverity;
+use tower_lsp::lsp_types::NumberOrString;
 use tree_sitter::Node;
 use tree_sitter::Point;
 use tree_sitter::Range;
 
 use crate::lsp;
 use crate::lsp::declarations::top_level_declare;
+use crate::lsp::diagnostics_spelling::spelling_diagnostics;
 use crate::lsp::diagnostics_syntax::syntax_diagnostics;
 use crate::lsp::documents::Document;
 use crate::lsp::encoding::convert_tree_sitter_range_to_lsp_range;
@@ -40,9 +42,16 @@ use crate::treesitter::NodeType;
 use crate::treesitter::NodeTypeExt;
 use crate::treesitter::UnaryOperatorType;
 
+/// Code of diagnostics for symbols not in scope, used by code actions to
+/// suggest importing the symbol from an installed package
+pub(crate) const UNDEFINED_SYMBOL_DIAGNOSTIC_CODE: &str = "undefined-symbol";
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct DiagnosticsConfig {
     pub enable: bool,
+
+    /// Whether to report misspelled words in comments and strings.
+    pub spell_check: bool,
 }
 
 #[derive(Clone)]
@@ -82,7 +91,10 @@ pub struct DiagnosticContext<'a> {
 
 impl Default for DiagnosticsConfig {
     fn default() -> Self {
-        Self { enable: true }
+        Self {
+            enable: true,
+            spell_check: false,
+        }
     }
 }
 
@@ -237,6 +249,57 @@ pub(crate) fn generate_diagnostics(
         Err(err) => log::error!("Error while generating semantic diagnostics: {err:?}"),
     }
 
+    diagnostics.append(&mut encoding_diagnostics(&doc));
+
+    if state.config.diagnostics.spell_check {
+        diagnostics.append(&mut spelling_diagnostics(&doc));
+    }
+
+    diagnostics
+}
+
+/// Warns about documents that were not valid UTF-8
+///
+/// Documents whose file on disk is not UTF-8 get a warning on their first
+/// line, whether they were transcoded when read from disk or decoded by the
+/// client. Documents sent by the client contain replacement characters where
+/// the client failed to decode the file, which usually means that the file
+/// was opened with the wrong encoding. These get a warning on the first
+/// replacement character of each line.
+fn encoding_diagnostics(doc: &Document) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    if let Some(encoding) = doc.legacy_encoding {
+        let range = tower_lsp::lsp_types::Range::default();
+        let message = format!("File is not valid UTF-8 and is assumed to be {encoding}.");
+        let mut diagnostic = Diagnostic::new_simple(range, message);
+        diagnostic.severity = Some(DiagnosticSeverity::WARNING);
+        diagnostics.push(diagnostic);
+    }
+
+    for (row, line) in doc.contents.lines().enumerate() {
+        let line: std::borrow::Cow<'_, str> = line.into();
+
+        let Some(column) = line.find(char::REPLACEMENT_CHARACTER) else {
+            continue;
+        };
+
+        let start = Point::new(row, column);
+        let end = Point::new(row, column + char::REPLACEMENT_CHARACTER.len_utf8());
+        let range = Range {
+            start_byte: 0,
+            end_byte: 0,
+            start_point: start,
+            end_point: end,
+        };
+        let range = convert_tree_sitter_range_to_lsp_range(&doc.contents, range);
+
+        let message = "Invalid character, the file may have been opened with the wrong encoding.";
+        let mut diagnostic = Diagnostic::new_simple(range, message.into());
+        diagnostic.severity = Some(DiagnosticSeverity::WARNING);
+        diagnostics.push(diagnostic);
+    }
+
     diagnostics
 }
 
@@ -1131,6 +1194,10 @@ fn check_symbol_in_scope(
     let message = format!("No symbol named '{}' in scope.", identifier);
     let mut diagnostic = Diagnostic::new_simple(range, message);
     diagnostic.severity = Some(DiagnosticSeverity::WARNING);
+    diagnostic.code = Some(NumberOrString::String(String::from(
+        UNDEFINED_SYMBOL_DIAGNOSTIC_CODE,
+    )));
+    diagnostic.data = Some(serde_json::json!({ "symbol": identifier }));
     diagnostics.push(diagnostic);
 
     true.ok()
@@ -1886,4 +1953,21 @@ foo
             assert_eq!(diagnostics.len(), 3);
         })
     }
+
+    #[test]
+    fn test_encoding_diagnostics() {
+        let document = Document::new("x <- 1\ny <- 'caf\u{FFFD}'\n", None);
+        let diagnostics = super::encoding_diagnostics(&document);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start, Position::new(1, 9));
+        assert_eq!(diagnostics[0].range.end, Position::new(1, 10));
+
+        let mut document = Document::new("x <- 1\n", None);
+        assert!(super::encoding_diagnostics(&document).is_empty());
+
+        document.legacy_encoding = Some("Latin-1");
+        let diagnostics = super::encoding_diagnostics(&document);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start, Position::new(0, 0));
+    }
 }
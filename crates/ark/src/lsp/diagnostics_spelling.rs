@@ -0,0 +1,248 @@
+//
+// diagnostics_spelling.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use ropey::Rope;
+use tower_lsp::lsp_types::Diagnostic;
+use tower_lsp::lsp_types::DiagnosticSeverity;
+use tower_lsp::lsp_types::NumberOrString;
+use tree_sitter::Node;
+use tree_sitter::Point;
+use tree_sitter::Range;
+
+use crate::lsp::documents::Document;
+use crate::lsp::encoding::convert_tree_sitter_range_to_lsp_range;
+use crate::lsp::traits::rope::RopeExt;
+use crate::treesitter::NodeType;
+use crate::treesitter::NodeTypeExt;
+
+/// Code of spelling diagnostics, used by code actions to find the
+/// diagnostics they can fix
+pub(crate) const SPELLING_DIAGNOSTIC_CODE: &str = "spelling";
+
+/// Dictionary of common misspellings, embedded in the binary so that spell
+/// checking works without network access or system dictionaries
+static MISSPELLINGS: LazyLock<HashMap<&'static str, Vec<&'static str>>> =
+    LazyLock::new(|| parse_misspellings(include_str!("../../resources/spelling/misspellings.txt")));
+
+/// Hints about misspelled words in comments, including roxygen comments, and
+/// in string literals
+///
+/// Only words of the embedded dictionary of common misspellings are
+/// reported, so that identifiers and domain-specific vocabulary are never
+/// flagged. The corrections are stored in the `data` field of the diagnostics
+/// as a `suggestions` array for quick fixes.
+pub(crate) fn spelling_diagnostics(doc: &Document) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    recurse(doc.ast.root_node(), &doc.contents, &mut diagnostics);
+    diagnostics
+}
+
+fn recurse(node: Node, contents: &Rope, diagnostics: &mut Vec<Diagnostic>) {
+    match node.node_type() {
+        NodeType::Comment | NodeType::StringContent => {
+            let Ok(text) = contents.node_slice(&node) else {
+                return;
+            };
+            let text = text.to_string();
+
+            for (offset, word) in words(&text) {
+                let Some(suggestions) = corrections(word) else {
+                    continue;
+                };
+                let start = node.start_byte() + offset;
+                let end = start + word.len();
+                diagnostics.push(spelling_diagnostic(contents, start, end, word, suggestions));
+            }
+        },
+        _ => {
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                recurse(child, contents, diagnostics);
+            }
+        },
+    }
+}
+
+fn spelling_diagnostic(
+    contents: &Rope,
+    start: usize,
+    end: usize,
+    word: &str,
+    suggestions: Vec<String>,
+) -> Diagnostic {
+    let range = Range {
+        start_byte: start,
+        end_byte: end,
+        start_point: byte_to_point(contents, start),
+        end_point: byte_to_point(contents, end),
+    };
+    let range = convert_tree_sitter_range_to_lsp_range(contents, range);
+
+    let alternatives = suggestions
+        .iter()
+        .map(|suggestion| format!("`{suggestion}`"))
+        .collect::<Vec<_>>()
+        .join(" or ");
+    let message = format!("`{word}` may be misspelled, did you mean {alternatives}?");
+
+    let mut diagnostic = Diagnostic::new_simple(range, message);
+    diagnostic.severity = Some(DiagnosticSeverity::HINT);
+    diagnostic.code = Some(NumberOrString::String(String::from(
+        SPELLING_DIAGNOSTIC_CODE,
+    )));
+    diagnostic.data = Some(serde_json::json!({ "suggestions": suggestions }));
+    diagnostic
+}
+
+fn byte_to_point(contents: &Rope, byte: usize) -> Point {
+    let row = contents.byte_to_line(byte);
+    let column = byte - contents.line_to_byte(row);
+    Point::new(row, column)
+}
+
+/// Splits text into words along with their byte offset
+///
+/// Words containing digits or underscores, words right after `@` like
+/// roxygen tags, and words in inline code like `` `x` `` are skipped since
+/// they are likely code rather than prose.
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut in_code = false;
+    let mut start: Option<usize> = None;
+
+    // Trailing space so that the last word is pushed too
+    let chars = text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')));
+
+    for (i, c) in chars {
+        if c.is_alphanumeric() || c == '_' {
+            start.get_or_insert(i);
+            continue;
+        }
+
+        if let Some(start) = start.take() {
+            if !in_code && is_prose_word(text, start, i) {
+                words.push((start, &text[start..i]));
+            }
+        }
+
+        if c == '`' {
+            in_code = !in_code;
+        }
+    }
+
+    words
+}
+
+fn is_prose_word(text: &str, start: usize, end: usize) -> bool {
+    text[start..end].chars().all(char::is_alphabetic) && !text[..start].ends_with('@')
+}
+
+/// Corrections of `word` if it is a known misspelling, following its case
+fn corrections(word: &str) -> Option<Vec<String>> {
+    let lowercase = word.to_lowercase();
+    let suggestions = MISSPELLINGS.get(lowercase.as_str())?;
+
+    let is_upper = word.chars().count() > 1 && word.chars().all(char::is_uppercase);
+    let is_capitalized = word.chars().next().is_some_and(char::is_uppercase);
+
+    let suggestions = suggestions
+        .iter()
+        .map(|suggestion| {
+            if is_upper {
+                suggestion.to_uppercase()
+            } else if is_capitalized {
+                let mut chars = suggestion.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            } else {
+                suggestion.to_string()
+            }
+        })
+        .collect();
+
+    Some(suggestions)
+}
+
+fn parse_misspellings(dictionary: &'static str) -> HashMap<&'static str, Vec<&'static str>> {
+    dictionary
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (misspelling, corrections) = line.split_once("->")?;
+            let corrections = corrections.split(',').map(str::trim).collect();
+            Some((misspelling.trim(), corrections))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::DiagnosticSeverity;
+    use tower_lsp::lsp_types::Position;
+
+    use crate::lsp::diagnostics_spelling::corrections;
+    use crate::lsp::diagnostics_spelling::spelling_diagnostics;
+    use crate::lsp::diagnostics_spelling::words;
+    use crate::lsp::diagnostics_spelling::MISSPELLINGS;
+    use crate::lsp::documents::Document;
+
+    #[test]
+    fn test_spelling_dictionary() {
+        assert!(!MISSPELLINGS.is_empty());
+        assert_eq!(MISSPELLINGS.get("teh"), Some(&vec!["the"]));
+        assert_eq!(
+            MISSPELLINGS.get("wheather"),
+            Some(&vec!["weather", "whether"])
+        );
+    }
+
+    #[test]
+    fn test_spelling_words() {
+        let words: Vec<&str> = words("#' @param x Teh `seperate` value_1 of x2")
+            .into_iter()
+            .map(|(_, word)| word)
+            .collect();
+        assert_eq!(words, vec!["x", "Teh", "of"]);
+    }
+
+    #[test]
+    fn test_spelling_corrections() {
+        assert_eq!(corrections("teh"), Some(vec![String::from("the")]));
+        assert_eq!(corrections("Teh"), Some(vec![String::from("The")]));
+        assert_eq!(corrections("TEH"), Some(vec![String::from("THE")]));
+        assert_eq!(corrections("the"), None);
+    }
+
+    #[test]
+    fn test_spelling_diagnostics() {
+        let text = "# Compute teh mean\nteh <- 'seperate values'\n";
+        let document = Document::new(text, None);
+        let diagnostics = spelling_diagnostics(&document);
+
+        // Identifiers are not checked
+        assert_eq!(diagnostics.len(), 2);
+
+        assert_eq!(diagnostics[0].range.start, Position::new(0, 10));
+        assert_eq!(diagnostics[0].range.end, Position::new(0, 13));
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::HINT));
+        assert_eq!(
+            diagnostics[0].data,
+            Some(serde_json::json!({ "suggestions": ["the"] }))
+        );
+
+        assert_eq!(diagnostics[1].range.start, Position::new(1, 8));
+        assert_eq!(diagnostics[1].range.end, Position::new(1, 16));
+    }
+}
@@ -5,6 +5,8 @@
 //
 //
 
+use std::collections::HashSet;
+
 use tower_lsp::lsp_types::Diagnostic;
 use tree_sitter::Node;
 use tree_sitter::Range;
@@ -22,11 +24,69 @@ pub(crate) fn syntax_diagnostics(
 ) -> anyhow::Result<Vec<Diagnostic>> {
     let mut diagnostics = Vec::new();
 
-    recurse(root, context, &mut diagnostics)?;
+    // Only compute delimiters when there are syntax errors
+    if !node_has_error_or_missing(&root) {
+        return Ok(diagnostics);
+    }
+    let unmatched = unmatched_delimiters(root);
+
+    recurse(root, context, &unmatched, &mut diagnostics)?;
 
     Ok(diagnostics)
 }
 
+/// Opening and closing delimiters, by token kind
+const DELIMITERS: &[(&str, &str)] = &[("(", ")"), ("{", "}"), ("[", "]"), ("[[", "]]")];
+
+/// The start bytes of delimiter tokens that don't have a matching delimiter,
+/// regardless of how tree-sitter recovered from the syntax errors. Delimiters
+/// in strings and comments are not tokens, so they are naturally ignored.
+fn unmatched_delimiters(root: Node) -> HashSet<usize> {
+    let mut unmatched = HashSet::new();
+    let mut stack: Vec<Node> = Vec::new();
+
+    let mut cursor = root.walk();
+    let mut descend = true;
+
+    loop {
+        if descend && cursor.goto_first_child() {
+            continue;
+        }
+
+        let node = cursor.node();
+        if node.child_count() == 0 && !node.is_missing() {
+            let kind = node.kind();
+
+            if DELIMITERS.iter().any(|(open, _)| *open == kind) {
+                stack.push(node);
+            } else if let Some((open, _)) = DELIMITERS.iter().find(|(_, close)| *close == kind) {
+                match stack.last() {
+                    Some(last) if last.kind() == *open => {
+                        stack.pop();
+                    },
+                    _ => {
+                        unmatched.insert(node.start_byte());
+                    },
+                }
+            }
+        }
+
+        if cursor.goto_next_sibling() {
+            descend = true;
+            continue;
+        }
+
+        // Climb up until we find a sibling to visit
+        descend = false;
+        if !cursor.goto_parent() {
+            break;
+        }
+    }
+
+    unmatched.extend(stack.iter().map(|node| node.start_byte()));
+    unmatched
+}
+
 // When we hit an `ERROR` node, i.e. a syntax error, it often has its own children
 // which can also be `ERROR`s. The goal is to target the deepest (most precise) `ERROR`
 // nodes and only report syntax errors for those. We accomplish this by recursing
@@ -34,6 +94,7 @@ pub(crate) fn syntax_diagnostics(
 fn recurse(
     node: Node,
     context: &DiagnosticContext,
+    unmatched: &HashSet<usize>,
     diagnostics: &mut Vec<Diagnostic>,
 ) -> anyhow::Result<bool> {
     if !node_has_error_or_missing(&node) {
@@ -44,13 +105,13 @@ fn recurse(
     // Always look for contextual `MISSING` issues based on the current node type
     diagnose_missing(node, context, diagnostics)?;
 
-    let mut any_errors = recurse_children(node, context, diagnostics)?;
+    let mut any_errors = recurse_children(node, context, unmatched, diagnostics)?;
 
     // Report an error when:
     // - No children were `ERROR`s
     // - We are an `ERROR`
     if !any_errors && node.is_error() {
-        diagnostics.push(syntax_diagnostic(node, context)?);
+        diagnostics.push(syntax_diagnostic(node, context, unmatched)?);
         any_errors = true;
     }
 
@@ -60,19 +121,24 @@ fn recurse(
 fn recurse_children(
     node: Node,
     context: &DiagnosticContext,
+    unmatched: &HashSet<usize>,
     diagnostics: &mut Vec<Diagnostic>,
 ) -> anyhow::Result<bool> {
     let mut any_errors = false;
     let mut cursor = node.walk();
 
     for child in node.children(&mut cursor) {
-        any_errors |= recurse(child, context, diagnostics)?;
+        any_errors |= recurse(child, context, unmatched, diagnostics)?;
     }
 
     Ok(any_errors)
 }
 
-fn syntax_diagnostic(node: Node, context: &DiagnosticContext) -> anyhow::Result<Diagnostic> {
+fn syntax_diagnostic(
+    node: Node,
+    context: &DiagnosticContext,
+    unmatched: &HashSet<usize>,
+) -> anyhow::Result<Diagnostic> {
     // We used to try and analyze the `ERROR` structure of the tree to provide "precise"
     // error messages, but this is extremely prone to false positives due to the fact that
     // tree-sitter reports a "generic" error range, and it's up to us to try and infer the
@@ -80,10 +146,81 @@ fn syntax_diagnostic(node: Node, context: &DiagnosticContext) -> anyhow::Result<
     // incorrectly. It's better to wait for us to have a custom R pratt parser, which
     // would be able to report precise error locations/messages, because it "knows" why a
     // parse error occurred.
+    //
+    // We only give hints for a few patterns that don't depend on how tree-sitter
+    // recovered from the error.
+
+    if let Some(diagnostic) = syntax_diagnostic_unmatched_delimiter(node, context, unmatched)? {
+        return Ok(diagnostic);
+    }
+
+    if let Some(diagnostic) = syntax_diagnostic_missing_comma(node, context)? {
+        return Ok(diagnostic);
+    }
 
     Ok(syntax_diagnostic_default(node, context))
 }
 
+/// An `ERROR` that consists of a single delimiter without a match in the
+/// whole document, e.g. the `}` of `1 + 1 }`
+fn syntax_diagnostic_unmatched_delimiter(
+    node: Node,
+    context: &DiagnosticContext,
+    unmatched: &HashSet<usize>,
+) -> anyhow::Result<Option<Diagnostic>> {
+    if !unmatched.contains(&node.start_byte()) {
+        return Ok(None);
+    }
+
+    let text = context.contents.node_slice(&node)?.to_string();
+
+    for (open, close) in DELIMITERS {
+        if text == *open {
+            return Ok(Some(new_missing_close_diagnostic(
+                close,
+                node.range(),
+                context,
+            )));
+        }
+        if text == *close {
+            let message = format!("Unmatched closing delimiter. Missing an opening '{open}'.");
+            return Ok(Some(new_syntax_diagnostic(message, node.range(), context)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// An `ERROR` in a list of arguments that is directly followed by another
+/// argument, e.g. the `2` of `match(1, 2 3)`
+fn syntax_diagnostic_missing_comma(
+    node: Node,
+    context: &DiagnosticContext,
+) -> anyhow::Result<Option<Diagnostic>> {
+    let Some(parent) = node.parent() else {
+        return Ok(None);
+    };
+    if parent.kind() != "arguments" {
+        return Ok(None);
+    }
+
+    // The next token must start an argument, not be a delimiter or a comma
+    let Some(next) = node.next_sibling() else {
+        return Ok(None);
+    };
+    if next.kind() != "argument" {
+        return Ok(None);
+    }
+
+    // Only for errors on a single line, otherwise the hint is likely wrong
+    if node.start_position().row != node.end_position().row {
+        return Ok(None);
+    }
+
+    let message = String::from("Missing a ',' between arguments.");
+    Ok(Some(new_syntax_diagnostic(message, node.range(), context)))
+}
+
 fn syntax_diagnostic_default(node: Node, context: &DiagnosticContext) -> Diagnostic {
     let range = node.range();
     let row_span = range.end_point.row - range.start_point.row;
@@ -521,6 +658,31 @@ function(x {
         assert_eq!(diagnostic.range.end, Position::new(0, 10));
     }
 
+    #[test]
+    fn test_multiple_syntax_errors() {
+        // Each error gets its own diagnostic
+        let text = "
+x <- 1 }
+y <- c(1, 2 3)
+";
+
+        let diagnostics = text_diagnostics(text);
+        assert_eq!(diagnostics.len(), 2);
+
+        let diagnostic = diagnostics.get(0).unwrap();
+        assert_eq!(
+            diagnostic.message,
+            "Unmatched closing delimiter. Missing an opening '{'."
+        );
+        assert_eq!(diagnostic.range.start, Position::new(1, 7));
+        assert_eq!(diagnostic.range.end, Position::new(1, 8));
+
+        let diagnostic = diagnostics.get(1).unwrap();
+        assert_eq!(diagnostic.message, "Missing a ',' between arguments.");
+        assert_eq!(diagnostic.range.start, Position::new(2, 10));
+        assert_eq!(diagnostic.range.end, Position::new(2, 11));
+    }
+
     #[test]
     fn test_no_syntax_diagnostic_on_dots_and_dot_dot_i() {
         let text = "x$...";
@@ -5,6 +5,8 @@
 //
 //
 
+use std::path::Path;
+
 use anyhow::*;
 use ropey::Rope;
 use tower_lsp::lsp_types::DidChangeTextDocumentParams;
@@ -16,6 +18,7 @@ use tree_sitter::Tree;
 
 use crate::lsp::config::DocumentConfig;
 use crate::lsp::encoding::convert_lsp_range_to_tree_sitter_range;
+use crate::lsp::r_markdown::RMarkdown;
 
 fn compute_point(point: Point, text: &str) -> Point {
     // figure out where the newlines in this edit are
@@ -34,6 +37,43 @@ fn compute_point(point: Point, text: &str) -> Point {
     }
 }
 
+/// Decodes the contents of a file into UTF-8
+///
+/// A leading byte order mark is removed. Files that are not valid UTF-8 are
+/// assumed to be Latin-1, the most common legacy encoding of R scripts, and
+/// transcoded. Every byte is a valid Latin-1 character so this never fails,
+/// though the result may be garbled if the file used another encoding.
+///
+/// Returns the contents along with the name of the encoding they were
+/// transcoded from, if any.
+pub fn decode_contents(bytes: Vec<u8>) -> (String, Option<&'static str>) {
+    match String::from_utf8(bytes) {
+        Ok(contents) => match contents.strip_prefix('\u{FEFF}') {
+            Some(contents) => (contents.to_string(), None),
+            None => (contents, None),
+        },
+        Err(err) => {
+            let contents = err.into_bytes().into_iter().map(char::from).collect();
+            (contents, Some(LEGACY_ENCODING))
+        },
+    }
+}
+
+/// The encoding of a file on disk, if it isn't valid UTF-8
+///
+/// Clients decode files themselves before syncing them, so their contents
+/// don't tell whether the file is UTF-8 on disk.
+pub fn file_legacy_encoding(path: &Path) -> Option<&'static str> {
+    let bytes = std::fs::read(path).ok()?;
+    match std::str::from_utf8(&bytes) {
+        Ok(_) => None,
+        Err(_) => Some(LEGACY_ENCODING),
+    }
+}
+
+/// The encoding assumed for files that are not valid UTF-8
+const LEGACY_ENCODING: &str = "Latin-1";
+
 #[derive(Clone)]
 pub struct Document {
     // The document's textual contents.
@@ -48,6 +88,16 @@ pub struct Document {
 
     // Configuration of the document, such as indentation settings.
     pub config: DocumentConfig,
+
+    // The host document of R Markdown and Quarto files. For these documents,
+    // `contents` and `ast` only contain the R code of the chunks, every other
+    // line is blanked out so that positions are the same in both.
+    pub r_markdown: Option<RMarkdown>,
+
+    // The encoding of the file on disk, if it isn't valid UTF-8. Documents
+    // read from disk are transcoded from it, documents synced by the client
+    // were decoded by the client.
+    pub legacy_encoding: Option<&'static str>,
 }
 
 impl std::fmt::Debug for Document {
@@ -80,6 +130,47 @@ impl Document {
             version,
             ast,
             config: Default::default(),
+            r_markdown: None,
+            legacy_encoding: None,
+        }
+    }
+
+    /// Reads a document from disk
+    ///
+    /// Files that are not valid UTF-8 are transcoded, see [decode_contents()].
+    pub fn read(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path)?;
+        let (contents, legacy_encoding) = decode_contents(bytes);
+
+        if let Some(encoding) = legacy_encoding {
+            log::warn!(
+                "'{path}' is not valid UTF-8, decoding it as {encoding}",
+                path = path.display()
+            );
+        }
+
+        Ok(Self {
+            legacy_encoding,
+            ..Self::new(contents.as_str(), None)
+        })
+    }
+
+    pub fn new_r_markdown(contents: &str, parser: &mut Parser, version: Option<i32>) -> Self {
+        let r_markdown = RMarkdown::new(contents);
+        let contents = r_markdown.virtual_contents();
+
+        Self {
+            r_markdown: Some(r_markdown),
+            ..Self::new_with_parser(&contents, parser, version)
+        }
+    }
+
+    /// Whether `point` lies within R code. This is always the case except
+    /// in the prose of R Markdown and Quarto documents.
+    pub fn is_r_code(&self, point: Point) -> bool {
+        match &self.r_markdown {
+            Some(r_markdown) => r_markdown.contains_point(point),
+            None => true,
         }
     }
 
@@ -105,21 +196,59 @@ impl Document {
             }
         }
 
+        #[cfg(debug_assertions)]
+        self.check_consistency(parser);
+
         // Set new version
         self.version = Some(new_version);
     }
 
+    /// Checks that the incrementally reparsed tree matches a tree parsed
+    /// from scratch. Only done in debug builds as this defeats the purpose
+    /// of incremental parsing.
+    #[cfg(debug_assertions)]
+    fn check_consistency(&self, parser: &mut Parser) {
+        let contents = self.contents.to_string();
+        let Some(ast) = parser.parse(&contents, None) else {
+            log::error!("Failed to parse document from scratch");
+            return;
+        };
+
+        let expected = ast.root_node().to_sexp();
+        let actual = self.ast.root_node().to_sexp();
+
+        if expected != actual {
+            log::error!(
+                "Incremental reparse is out of sync.\nExpected: {expected}\nActual: {actual}"
+            );
+        }
+    }
+
     fn update(
         &mut self,
         parser: &mut Parser,
         change: &TextDocumentContentChangeEvent,
     ) -> Result<()> {
-        // Extract edit range. Nothing to do if there wasn't an edit.
-        let range = match change.range {
-            Some(r) => r,
-            None => return Ok(()),
+        // A change without a range replaces the whole document
+        let Some(range) = change.range else {
+            return self.replace(parser, &change.text);
         };
 
+        // For R Markdown documents the edit is applied to the host document,
+        // from which we regenerate the R code. The chunk structure may have
+        // changed so we can't reuse the tree.
+        if let Some(r_markdown) = &mut self.r_markdown {
+            r_markdown.update(range, &change.text);
+
+            let contents = r_markdown.virtual_contents();
+            self.ast = parser
+                .parse(&contents, None)
+                .ok_or(anyhow!("Failed to parse R Markdown document"))?;
+            self.contents = Rope::from(contents);
+
+            return Ok(());
+        }
+
         // Update the AST. We do this before updating the underlying document
         // contents, because edit computations need to be done using the current
         // state of the document (prior to the edit being applied) so that byte
@@ -167,6 +296,25 @@ impl Document {
         Ok(())
     }
 
+    fn replace(&mut self, parser: &mut Parser, text: &str) -> Result<()> {
+        if self.r_markdown.is_some() {
+            let r_markdown = RMarkdown::new(text);
+            let contents = r_markdown.virtual_contents();
+            self.r_markdown = Some(r_markdown);
+            return self.replace_contents(parser, &contents);
+        }
+
+        self.replace_contents(parser, text)
+    }
+
+    fn replace_contents(&mut self, parser: &mut Parser, contents: &str) -> Result<()> {
+        self.ast = parser
+            .parse(contents, None)
+            .ok_or(anyhow!("Failed to parse document"))?;
+        self.contents = Rope::from(contents);
+        Ok(())
+    }
+
     /// A tree-sitter `parse_with()` callback to efficiently return a slice of the
     /// document in the `Rope` that tree-sitter can reparse with.
     ///
@@ -226,6 +374,154 @@ mod tests {
         assert_eq!(point, Point::new(1, 0));
     }
 
+    #[test]
+    fn test_r_markdown_document() {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_r::LANGUAGE.into())
+            .unwrap();
+
+        let mut document =
+            Document::new_r_markdown("# Title\n```{r}\nx <- 1\n```\n", &mut parser, None);
+        assert_eq!(document.contents.to_string(), "\n\nx <- 1\n\n");
+        assert!(!document.ast.root_node().has_error());
+
+        assert!(!document.is_r_code(Point::new(0, 2)));
+        assert!(document.is_r_code(Point::new(2, 2)));
+
+        // Edits are applied to the host document
+        let change = TextDocumentContentChangeEvent {
+            range: Some(tower_lsp::lsp_types::Range {
+                start: tower_lsp::lsp_types::Position::new(0, 2),
+                end: tower_lsp::lsp_types::Position::new(0, 7),
+            }),
+            range_length: None,
+            text: String::from("Heading"),
+        };
+        document.update(&mut parser, &change).unwrap();
+
+        let r_markdown = document.r_markdown.as_ref().unwrap();
+        assert_eq!(
+            r_markdown.contents.to_string(),
+            "# Heading\n```{r}\nx <- 1\n```\n"
+        );
+        assert_eq!(document.contents.to_string(), "\n\nx <- 1\n\n");
+    }
+
+    fn change(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(tower_lsp::lsp_types::Range {
+                start: tower_lsp::lsp_types::Position::new(start.0, start.1),
+                end: tower_lsp::lsp_types::Position::new(end.0, end.1),
+            }),
+            range_length: None,
+            text: String::from(text),
+        }
+    }
+
+    #[test]
+    fn test_incremental_update() {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_r::LANGUAGE.into())
+            .unwrap();
+
+        let mut document = Document::new_with_parser("x <- 1\ny <- 2\n", &mut parser, None);
+
+        let changes = [
+            // Insertion
+            change((1, 6), (1, 6), " + x"),
+            // Deletion spanning lines
+            change((0, 5), (1, 5), ""),
+            // Replacement with new lines
+            change((0, 0), (0, 1), "f <- function() {\n  z\n}\nw"),
+        ];
+
+        for change in changes.iter() {
+            document.update(&mut parser, change).unwrap();
+        }
+
+        let text = "f <- function() {\n  z\n}\nw <- 2 + x\n";
+        assert_eq!(document.contents.to_string(), text);
+
+        let expected = Document::new(text, None);
+        assert_eq!(
+            document.ast.root_node().to_sexp(),
+            expected.ast.root_node().to_sexp()
+        );
+    }
+
+    #[test]
+    fn test_full_update() {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_r::LANGUAGE.into())
+            .unwrap();
+
+        let mut document = Document::new_with_parser("x <- 1\n", &mut parser, None);
+
+        let change = TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: String::from("foo(bar)\n"),
+        };
+        document.update(&mut parser, &change).unwrap();
+
+        assert_eq!(document.contents.to_string(), "foo(bar)\n");
+        assert_eq!(
+            document.ast.root_node().to_sexp(),
+            Document::new("foo(bar)\n", None).ast.root_node().to_sexp()
+        );
+    }
+
+    #[test]
+    fn test_decode_contents() {
+        assert_eq!(
+            decode_contents(b"x <- 1".to_vec()),
+            (String::from("x <- 1"), None)
+        );
+
+        // Byte order marks are removed
+        assert_eq!(
+            decode_contents(b"\xEF\xBB\xBFx <- 1".to_vec()),
+            (String::from("x <- 1"), None)
+        );
+
+        // Latin-1 is transcoded
+        assert_eq!(
+            decode_contents(b"x <- 'caf\xE9'".to_vec()),
+            (String::from("x <- 'café'"), Some("Latin-1"))
+        );
+    }
+
+    #[test]
+    fn test_document_read_latin1() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.R");
+        std::fs::write(&path, b"x <- '\xE9t\xE9'\ny <- 1\n").unwrap();
+
+        let document = Document::read(&path).unwrap();
+        assert_eq!(document.legacy_encoding, Some("Latin-1"));
+        assert_eq!(document.contents.to_string(), "x <- 'été'\ny <- 1\n");
+        assert!(!document.ast.root_node().has_error());
+    }
+
+    #[test]
+    fn test_file_legacy_encoding() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let path = dir.path().join("latin1.R");
+        std::fs::write(&path, b"x <- '\xE9t\xE9'\n").unwrap();
+        assert_eq!(file_legacy_encoding(&path), Some("Latin-1"));
+
+        let path = dir.path().join("utf8.R");
+        std::fs::write(&path, "x <- 'été'\n").unwrap();
+        assert_eq!(file_legacy_encoding(&path), None);
+
+        // Unsaved or deleted files
+        assert_eq!(file_legacy_encoding(&dir.path().join("missing.R")), None);
+    }
+
     #[test]
     fn test_document_starts_at_0_0_with_leading_whitespace() {
         let document = Document::new("\n\n# hi there", None);
@@ -10,12 +10,17 @@ use std::cmp::Ordering;
 use std::sync::LazyLock;
 
 use regex::Regex;
+use ropey::Rope;
 use tower_lsp::lsp_types::FoldingRange;
 use tower_lsp::lsp_types::FoldingRangeKind;
+use tree_sitter::Point;
 
 use super::symbols::parse_comment_as_section;
 use crate::lsp;
 use crate::lsp::documents::Document;
+use crate::lsp::encoding::convert_point_to_position;
+use crate::lsp::r_markdown::parse_chunk_fence;
+use crate::treesitter::NodeTypeExt;
 
 pub fn folding_range(document: &Document) -> anyhow::Result<Vec<FoldingRange>> {
     let mut folding_ranges: Vec<FoldingRange> = Vec::new();
@@ -36,11 +41,14 @@ pub fn folding_range(document: &Document) -> anyhow::Result<Vec<FoldingRange>> {
         &mut vec![Vec::new()],
         &mut None,
         &mut None,
+        &mut None,
     );
 
     Ok(folding_ranges)
 }
 
+// The markers are passed down separately until we switch to an iterative approach
+#[allow(clippy::too_many_arguments)]
 fn parse_ts_node(
     cursor: &mut tree_sitter::TreeCursor,
     _depth: usize,
@@ -49,6 +57,7 @@ fn parse_ts_node(
     comment_stack: &mut Vec<Vec<(usize, usize)>>,
     region_marker: &mut Option<usize>,
     cell_marker: &mut Option<usize>,
+    roxygen_block: &mut Option<(usize, usize)>,
 ) {
     let node = cursor.node();
     let _field_name = match cursor.field_name() {
@@ -60,21 +69,44 @@ fn parse_ts_node(
     let end = node.end_position();
     let node_type = node.kind();
 
+    // Roxygen blocks are made of consecutive comment siblings, any other
+    // node in between terminates the current block
+    if node_type != "comment" {
+        roxygen_flush(folding_ranges, roxygen_block);
+    }
+
     match node_type {
-        "parameters" | "arguments" | "braced_expression" => {
+        "parameters" | "arguments" | "braced_expression" | "parenthesized_expression" => {
             // Ignore same line folding
             if start.row == end.row {
                 return;
             }
+            // Start after the opening delimiter and end before the closing
+            // one. LSP characters are UTF-16 code units, not bytes.
+            let contents = &document.contents;
+            let start_character =
+                convert_point_to_position(contents, Point::new(start.row, start.column + 1))
+                    .character;
+            let end_character =
+                convert_point_to_position(contents, Point::new(end.row, end.column - 1)).character;
+
             let folding_range = bracket_range(
                 start.row,
-                start.column + 1, // Start after the opening delimiter
+                start_character as usize,
                 end.row,
-                end.column - 1,
+                end_character as usize,
                 count_leading_whitespaces(document, end.row),
             );
             folding_ranges.push(folding_range);
         },
+        "binary_operator" => {
+            // Only fold the outermost operator of a multi-line pipe chain.
+            // Since pipes are left-associative, the rest of the chain is
+            // nested in the `lhs` of that operator.
+            if start.row != end.row && is_pipe_chain_root(&node, document) {
+                folding_ranges.push(pipe_chain_range(start.row, end.row));
+            }
+        },
         "comment" => {
             // Only process standalone comment
             if count_leading_whitespaces(document, start.row) != start.column {
@@ -93,6 +125,7 @@ fn parse_ts_node(
                 };
                 region_processor(folding_ranges, region_marker, start.row, &comment_line);
                 cell_processor(folding_ranges, cell_marker, start.row, &comment_line);
+                roxygen_processor(folding_ranges, roxygen_block, start.row, &comment_line);
             };
         },
         _ => (),
@@ -104,6 +137,7 @@ fn parse_ts_node(
         let mut child_comment_stack: Vec<Vec<(usize, usize)>> = vec![Vec::new()];
         let mut child_region_marker: Option<usize> = None;
         let mut child_cell_marker: Option<usize> = None;
+        let mut child_roxygen_block: Option<(usize, usize)> = None;
 
         // recursive loop
         loop {
@@ -115,6 +149,7 @@ fn parse_ts_node(
                 &mut child_comment_stack,
                 &mut child_region_marker,
                 &mut child_cell_marker,
+                &mut child_roxygen_block,
             );
             if !cursor.goto_next_sibling() {
                 break;
@@ -127,6 +162,7 @@ fn parse_ts_node(
             &mut child_comment_stack,
             &mut child_region_marker,
             &mut child_cell_marker,
+            &mut child_roxygen_block,
         );
 
         cursor.goto_parent();
@@ -161,6 +197,30 @@ fn bracket_range(
     }
 }
 
+// The first line of the chain stays visible, e.g. `data |> ...`
+fn pipe_chain_range(start_line: usize, end_line: usize) -> FoldingRange {
+    FoldingRange {
+        start_line: start_line as u32,
+        start_character: None,
+        end_line: end_line as u32,
+        end_character: None,
+        kind: Some(FoldingRangeKind::Region),
+        collapsed_text: None,
+    }
+}
+
+fn is_pipe_chain_root(node: &tree_sitter::Node, document: &Document) -> bool {
+    if !node.is_pipe_operator(&document.contents).unwrap_or(false) {
+        return false;
+    }
+
+    let Some(parent) = node.parent() else {
+        return true;
+    };
+
+    !parent.is_pipe_operator(&document.contents).unwrap_or(false)
+}
+
 fn comment_range(start_line: usize, end_line: usize) -> FoldingRange {
     FoldingRange {
         start_line: start_line as u32,
@@ -281,14 +341,16 @@ fn region_processor(
     }
 }
 
-fn parse_region_type(line_text: &str) -> Option<RegionType> {
-    // Source: https://github.com/microsoft/vscode/blob/d6d5034f/extensions/python/language-configuration.json#L45-L48
-    let region_start = Regex::new(r"^\s*#\s*region\b").unwrap();
-    let region_end = Regex::new(r"^\s*#\s*endregion\b").unwrap();
+// Source: https://github.com/microsoft/vscode/blob/d6d5034f/extensions/python/language-configuration.json#L45-L48
+static RE_REGION_START: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*#\s*region\b").unwrap());
+static RE_REGION_END: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*#\s*endregion\b").unwrap());
 
-    if region_start.is_match(line_text) {
+fn parse_region_type(line_text: &str) -> Option<RegionType> {
+    if RE_REGION_START.is_match(line_text) {
         Some(RegionType::Start)
-    } else if region_end.is_match(line_text) {
+    } else if RE_REGION_END.is_match(line_text) {
         Some(RegionType::End)
     } else {
         None
@@ -325,13 +387,63 @@ fn cell_processor(
     }
 }
 
+fn roxygen_processor(
+    folding_ranges: &mut Vec<FoldingRange>,
+    roxygen_block: &mut Option<(usize, usize)>,
+    line_idx: usize,
+    line_text: &str,
+) {
+    if !line_text.trim_start().starts_with("#'") {
+        roxygen_flush(folding_ranges, roxygen_block);
+        return;
+    }
+
+    match roxygen_block {
+        // Extend the current block
+        Some((_start_line, end_line)) if *end_line + 1 == line_idx => {
+            *end_line = line_idx;
+        },
+        _ => {
+            roxygen_flush(folding_ranges, roxygen_block);
+            roxygen_block.replace((line_idx, line_idx));
+        },
+    }
+}
+
+fn roxygen_flush(
+    folding_ranges: &mut Vec<FoldingRange>,
+    roxygen_block: &mut Option<(usize, usize)>,
+) {
+    let Some((start_line, end_line)) = roxygen_block.take() else {
+        return;
+    };
+
+    // A single line of documentation is not worth folding
+    if start_line == end_line {
+        return;
+    }
+
+    folding_ranges.push(FoldingRange {
+        start_line: start_line as u32,
+        start_character: None,
+        end_line: end_line as u32,
+        end_character: None,
+        kind: Some(FoldingRangeKind::Comment),
+        collapsed_text: None,
+    });
+}
+
 fn end_node_handler(
     folding_ranges: &mut Vec<FoldingRange>,
     line_idx: usize,
     comment_stack: &mut Vec<Vec<(usize, usize)>>,
     region_marker: &mut Option<usize>,
     cell_marker: &mut Option<usize>,
+    roxygen_block: &mut Option<(usize, usize)>,
 ) {
+    // Roxygen block at the end of the node
+    roxygen_flush(folding_ranges, roxygen_block);
+
     // Nested comment handling
     // Iterate over the last element of the comment stack and add it to the folding ranges by using the comment_range function
     if let Some(last_section) = comment_stack.last() {
@@ -362,9 +474,146 @@ fn end_node_handler(
     }
 }
 
+/// Folding ranges for R Markdown and Quarto documents
+///
+/// `contents` are the contents of the host document. These are not parsed
+/// with tree-sitter-r, so we scan the lines
+/// for code chunks, markdown sections, the YAML header, and HTML region
+/// markers like `<!-- #region -->`.
+pub fn folding_range_markdown(contents: &Rope) -> Vec<FoldingRange> {
+    let mut folding_ranges: Vec<FoldingRange> = Vec::new();
+
+    // Stack of `(level, start_line)` for open markdown sections
+    let mut sections: Vec<(usize, usize)> = Vec::new();
+    // Stack of start lines for open region markers
+    let mut regions: Vec<usize> = Vec::new();
+    // Start line and fence length of the current code chunk
+    let mut chunk: Option<(usize, usize)> = None;
+
+    // The YAML header is folded as a whole and its contents are skipped
+    let yaml_end = yaml_header_end(contents);
+    if let Some(end_line) = yaml_end {
+        folding_ranges.push(comment_range(0, end_line));
+    }
+
+    for (line_idx, line) in contents.lines().enumerate() {
+        if yaml_end.is_some_and(|end_line| line_idx <= end_line) {
+            continue;
+        }
+
+        let line: Cow<'_, str> = line.into();
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        if let Some((start_line, length)) = chunk {
+            if parse_chunk_fence(line)
+                .is_some_and(|fence| fence.length >= length && !fence.has_info)
+            {
+                folding_ranges.push(comment_range(start_line, line_idx));
+                chunk = None;
+            }
+            continue;
+        }
+
+        if let Some(fence) = parse_chunk_fence(line) {
+            chunk = Some((line_idx, fence.length));
+            continue;
+        }
+
+        if let Some(level) = parse_markdown_heading(line) {
+            // Close all sections of the same or a deeper level
+            while let Some(&(last_level, start_line)) = sections.last() {
+                if last_level < level {
+                    break;
+                }
+                push_section_range(&mut folding_ranges, start_line, line_idx - 1, contents);
+                sections.pop();
+            }
+            sections.push((level, line_idx));
+            continue;
+        }
+
+        if RE_MARKDOWN_REGION_START.is_match(line) {
+            regions.push(line_idx);
+        } else if RE_MARKDOWN_REGION_END.is_match(line) {
+            if let Some(start_line) = regions.pop() {
+                folding_ranges.push(comment_range(start_line, line_idx));
+            }
+        }
+    }
+
+    let last_line = contents.len_lines().saturating_sub(1);
+
+    // Unclosed chunks extend to the end of the document
+    if let Some((start_line, _)) = chunk {
+        push_section_range(&mut folding_ranges, start_line, last_line, contents);
+    }
+
+    for (_level, start_line) in sections.into_iter().rev() {
+        push_section_range(&mut folding_ranges, start_line, last_line, contents);
+    }
+
+    folding_ranges
+}
+
+static RE_MARKDOWN_HEADING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(#{1,6})(\s+.*)?$").unwrap());
+static RE_MARKDOWN_REGION_START: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*<!--\s*#?region\b.*-->").unwrap());
+static RE_MARKDOWN_REGION_END: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*<!--\s*#?endregion\b.*-->").unwrap());
+
+fn parse_markdown_heading(line: &str) -> Option<usize> {
+    let captures = RE_MARKDOWN_HEADING.captures(line)?;
+    Some(captures.get(1)?.as_str().len())
+}
+
+fn yaml_header_end(contents: &Rope) -> Option<usize> {
+    let mut lines = contents.lines().map(|line| {
+        let line: Cow<'_, str> = line.into();
+        line.trim_end().to_string()
+    });
+
+    if lines.next()? != "---" {
+        return None;
+    }
+
+    lines
+        .position(|line| line == "---" || line == "...")
+        .map(|position| position + 1)
+}
+
+// Sections end on their last non-blank line
+fn push_section_range(
+    folding_ranges: &mut Vec<FoldingRange>,
+    start_line: usize,
+    end_line: usize,
+    contents: &Rope,
+) {
+    let mut end_line = end_line;
+
+    while end_line > start_line && line_is_blank(contents, end_line) {
+        end_line -= 1;
+    }
+
+    // Empty sections are not worth folding
+    if end_line <= start_line {
+        return;
+    }
+
+    folding_ranges.push(comment_range(start_line, end_line));
+}
+
+fn line_is_blank(contents: &Rope, line_idx: usize) -> bool {
+    let Some(line) = contents.get_line(line_idx) else {
+        return true;
+    };
+    line.chars().all(char::is_whitespace)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures::LspClient;
     use crate::lsp::documents::Document;
 
     fn test_folding_range(code: &str) -> Vec<FoldingRange> {
@@ -382,6 +631,16 @@ mod tests {
         ranges
     }
 
+    #[test]
+    fn test_folding_range_multibyte() {
+        // Characters are counted in UTF-16 code units
+        let ranges = test_folding_range("f('été', {\n  1\n})");
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[0].start_line, 0);
+        assert_eq!(ranges[0].start_character, Some(10));
+        assert_eq!(ranges[1].start_character, Some(2));
+    }
+
     #[test]
     fn test_parse_region_type() {
         // Not regions
@@ -591,6 +850,52 @@ list <- list(
         ));
     }
 
+    #[test]
+    fn test_folding_pipe_chains() {
+        // Only the outermost pipe of a chain is folded
+        insta::assert_debug_snapshot!(test_folding_range(
+            "
+x |>
+  f() |>
+  g()
+
+y %>%
+  h()
+"
+        ));
+    }
+
+    #[test]
+    fn test_folding_roxygen() {
+        insta::assert_debug_snapshot!(test_folding_range(
+            "
+#' Title
+#'
+#' @param x A thing
+f <- function(x) {
+  x
+}
+"
+        ));
+    }
+
+    // Delimiters inside strings don't affect folding
+    #[test]
+    fn test_folding_parentheses_and_strings() {
+        insta::assert_debug_snapshot!(test_folding_range(
+            "
+y <- (
+  1 + 2
+)
+
+call(
+  \"{\",
+  '}'
+)
+"
+        ));
+    }
+
     // Test for mixed folding strategies
     #[test]
     fn test_folding_mixed() {
@@ -727,6 +1032,45 @@ function() {
         }
     }
 
+    #[test]
+    fn test_folding_markdown() {
+        let contents = Rope::from_str(
+            "---
+title: \"Test\"
+---
+
+# Section
+
+```{r}
+x <- 1
+```
+
+## Subsection
+text
+
+<!-- #region -->
+more
+<!-- #endregion -->
+",
+        );
+        insta::assert_debug_snapshot!(sorted_ranges(folding_range_markdown(&contents)));
+    }
+
+    #[test]
+    fn test_folding_markdown_headings_in_chunks() {
+        // Comments in chunks are not headings, unclosed chunks extend to the end
+        let contents = Rope::from_str(
+            "# Section
+text
+
+```{r}
+# Not a heading
+x
+",
+        );
+        insta::assert_debug_snapshot!(sorted_ranges(folding_range_markdown(&contents)));
+    }
+
     // Test for whitespace counting
     #[test]
     fn test_count_leading_whitespaces() {
@@ -757,4 +1101,18 @@ function() {
 "
         ));
     }
+
+    #[test]
+    fn test_folding_range_request() {
+        // The request goes through the server and returns the ranges of the
+        // opened document
+        let code = "f <- function() {\n  1\n}\n# Section ----\nx\n";
+
+        let client = LspClient::new();
+        let uri = client.open_document("test.R", code);
+        let ranges = sorted_ranges(client.folding_ranges(&uri));
+
+        assert_eq!(ranges, test_folding_range(code));
+        assert!(!ranges.is_empty());
+    }
 }
@@ -11,12 +11,16 @@ use stdext::unwrap;
 use stdext::unwrap::IntoResult;
 use tower_lsp::lsp_types::CodeActionParams;
 use tower_lsp::lsp_types::CodeActionResponse;
+use tower_lsp::lsp_types::CodeLens;
+use tower_lsp::lsp_types::CodeLensParams;
 use tower_lsp::lsp_types::CompletionItem;
+use tower_lsp::lsp_types::CompletionList;
 use tower_lsp::lsp_types::CompletionParams;
 use tower_lsp::lsp_types::CompletionResponse;
 use tower_lsp::lsp_types::DocumentOnTypeFormattingParams;
 use tower_lsp::lsp_types::DocumentSymbolParams;
 use tower_lsp::lsp_types::DocumentSymbolResponse;
+use tower_lsp::lsp_types::ExecuteCommandParams;
 use tower_lsp::lsp_types::FoldingRange;
 use tower_lsp::lsp_types::FoldingRangeParams;
 use tower_lsp::lsp_types::GotoDefinitionParams;
@@ -24,6 +28,8 @@ use tower_lsp::lsp_types::GotoDefinitionResponse;
 use tower_lsp::lsp_types::Hover;
 use tower_lsp::lsp_types::HoverContents;
 use tower_lsp::lsp_types::HoverParams;
+use tower_lsp::lsp_types::LinkedEditingRangeParams;
+use tower_lsp::lsp_types::LinkedEditingRanges;
 use tower_lsp::lsp_types::Location;
 use tower_lsp::lsp_types::MessageType;
 use tower_lsp::lsp_types::ReferenceParams;
@@ -42,7 +48,12 @@ use tree_sitter::Point;
 
 use crate::analysis::input_boundaries::input_boundaries;
 use crate::lsp;
+use crate::lsp::breadcrumbs::breadcrumbs;
+use crate::lsp::breadcrumbs::BreadcrumbsParams;
+use crate::lsp::breadcrumbs::BreadcrumbsResponse;
 use crate::lsp::code_action::code_actions;
+use crate::lsp::code_lens;
+use crate::lsp::code_lens::code_lenses;
 use crate::lsp::completions::provide_completions;
 use crate::lsp::completions::resolve_completion;
 use crate::lsp::definitions::goto_definition;
@@ -50,15 +61,21 @@ use crate::lsp::document_context::DocumentContext;
 use crate::lsp::encoding::convert_lsp_range_to_tree_sitter_range;
 use crate::lsp::encoding::convert_position_to_point;
 use crate::lsp::folding_range::folding_range;
+use crate::lsp::folding_range::folding_range_markdown;
 use crate::lsp::help_topic::help_topic;
 use crate::lsp::help_topic::HelpTopicParams;
 use crate::lsp::help_topic::HelpTopicResponse;
 use crate::lsp::hover::r_hover;
+use crate::lsp::indent::brace_edit;
 use crate::lsp::indent::indent_edit;
+use crate::lsp::indent::roxygen_edit;
 use crate::lsp::input_boundaries::InputBoundariesParams;
 use crate::lsp::input_boundaries::InputBoundariesResponse;
+use crate::lsp::linked_editing::linked_editing_ranges;
 use crate::lsp::main_loop::LspState;
 use crate::lsp::offset::IntoLspOffset;
+use crate::lsp::package_files::description_hover;
+use crate::lsp::package_files::PackageFile;
 use crate::lsp::references::find_references;
 use crate::lsp::selection_range::convert_selection_range_from_tree_sitter_to_lsp;
 use crate::lsp::selection_range::selection_range;
@@ -68,7 +85,11 @@ use crate::lsp::statement_range::statement_range;
 use crate::lsp::statement_range::StatementRangeParams;
 use crate::lsp::statement_range::StatementRangeResponse;
 use crate::lsp::symbols;
+use crate::lsp::telemetry;
+use crate::lsp::telemetry::TelemetryParams;
+use crate::lsp::telemetry::TelemetryResponse;
 use crate::r_task;
+use crate::r_task::r_task_async;
 
 pub static ARK_VDOC_REQUEST: &'static str = "ark/internal/virtualDocument";
 
@@ -161,6 +182,11 @@ pub(crate) fn handle_folding_range(
 ) -> anyhow::Result<Option<Vec<FoldingRange>>> {
     let uri = params.text_document.uri;
     let document = state.documents.get(&uri).into_result()?;
+
+    if let Some(r_markdown) = &document.r_markdown {
+        return Ok(Some(folding_range_markdown(&r_markdown.contents)));
+    }
+
     match folding_range(document) {
         Ok(foldings) => Ok(Some(foldings)),
         Err(err) => {
@@ -170,7 +196,19 @@ pub(crate) fn handle_folding_range(
     }
 }
 
-pub(crate) async fn handle_execute_command(client: &Client) -> anyhow::Result<Option<Value>> {
+pub(crate) async fn handle_execute_command(
+    params: ExecuteCommandParams,
+    state: &WorldState,
+    client: &Client,
+) -> anyhow::Result<Option<Value>> {
+    if matches!(
+        params.command.as_str(),
+        code_lens::RUN_LINES_COMMAND | code_lens::RUN_TEST_COMMAND | code_lens::RUN_FILE_COMMAND
+    ) {
+        code_lens::execute_command(&params.command, &params.arguments, state)?;
+        return Ok(None);
+    }
+
     match client.apply_edit(WorkspaceEdit::default()).await {
         Ok(res) if res.applied => client.log_message(MessageType::INFO, "applied").await,
         Ok(_) => client.log_message(MessageType::INFO, "rejected").await,
@@ -191,45 +229,85 @@ pub(crate) fn handle_completion(
     let position = params.text_document_position.position;
     let point = convert_position_to_point(&document.contents, position);
 
+    if !document.is_r_code(point) {
+        return Ok(None);
+    }
+
     let trigger = params.context.and_then(|ctxt| ctxt.trigger_character);
 
     // Build the document context.
     let context = DocumentContext::new(&document, point, trigger);
     lsp::log_info!("Completion context: {:#?}", context);
 
-    let completions = r_task(|| provide_completions(&context, state))?;
+    let mut completions = r_task(|| provide_completions(&context, state, Some(&uri)))?;
 
-    if !completions.is_empty() {
-        Ok(Some(CompletionResponse::Array(completions)))
-    } else {
-        Ok(None)
+    if completions.is_empty() {
+        return Ok(None);
+    }
+
+    // Keep the completions the client would show first. Clients order
+    // completions by `sort_text`, falling back to `label`.
+    if let Some(max_items) = state.config.completions.max_items {
+        if completions.len() > max_items {
+            completions.sort_by(|lhs, rhs| completion_sort_key(lhs).cmp(&completion_sort_key(rhs)));
+            completions.truncate(max_items);
+            return Ok(Some(CompletionResponse::List(CompletionList {
+                is_incomplete: true,
+                items: completions,
+            })));
+        }
     }
+
+    Ok(Some(CompletionResponse::Array(completions)))
+}
+
+fn completion_sort_key(item: &CompletionItem) -> (&str, &str) {
+    let sort_text = item.sort_text.as_deref().unwrap_or(&item.label);
+    (sort_text, &item.label)
 }
 
 #[tracing::instrument(level = "info", skip_all)]
-pub(crate) fn handle_completion_resolve(
+pub(crate) async fn handle_completion_resolve(
     mut item: CompletionItem,
 ) -> anyhow::Result<CompletionItem> {
-    r_task(|| resolve_completion(&mut item))?;
-    Ok(item)
+    r_task_async(move || -> anyhow::Result<CompletionItem> {
+        resolve_completion(&mut item)?;
+        Ok(item)
+    })
+    .await?
 }
 
 #[tracing::instrument(level = "info", skip_all)]
-pub(crate) fn handle_hover(
+pub(crate) async fn handle_hover(
     params: HoverParams,
-    state: &WorldState,
+    state: WorldState,
 ) -> anyhow::Result<Option<Hover>> {
     let uri = params.text_document_position_params.text_document.uri;
-    let document = state.get_document(&uri)?;
+    let document = state.get_document(&uri)?.clone();
 
     let position = params.text_document_position_params.position;
     let point = convert_position_to_point(&document.contents, position);
 
-    // build document context
-    let context = DocumentContext::new(&document, point, None);
+    if PackageFile::from_url(&uri) == Some(PackageFile::Description) {
+        let contents = description_hover(&document.contents, point, &state.library);
+        return Ok(contents.map(|contents| Hover {
+            contents: HoverContents::Markup(contents),
+            range: None,
+        }));
+    }
+
+    if !document.is_r_code(point) {
+        return Ok(None);
+    }
 
     // request hover information
-    let result = r_task(|| r_hover(&context));
+    let data_masking_functions = state.config.completions.data_masking_functions.clone();
+    let result = r_task_async(move || {
+        let context = DocumentContext::new(&document, point, None);
+        r_hover(&context, &data_masking_functions)
+    })
+    .await
+    .and_then(|result| result);
 
     // unwrap errors
     let result = unwrap!(result, Err(err) => {
@@ -250,20 +328,27 @@ pub(crate) fn handle_hover(
 }
 
 #[tracing::instrument(level = "info", skip_all)]
-pub(crate) fn handle_signature_help(
+pub(crate) async fn handle_signature_help(
     params: SignatureHelpParams,
-    state: &WorldState,
+    state: WorldState,
 ) -> anyhow::Result<Option<SignatureHelp>> {
     let uri = params.text_document_position_params.text_document.uri;
-    let document = state.get_document(&uri)?;
+    let document = state.get_document(&uri)?.clone();
 
     let position = params.text_document_position_params.position;
     let point = convert_position_to_point(&document.contents, position);
 
-    let context = DocumentContext::new(&document, point, None);
+    if !document.is_r_code(point) {
+        return Ok(None);
+    }
 
     // request signature help
-    let result = r_task(|| r_signature_help(&context));
+    let result = r_task_async(move || {
+        let context = DocumentContext::new(&document, point, None);
+        r_signature_help(&context)
+    })
+    .await
+    .and_then(|result| result);
 
     // unwrap errors
     let result = unwrap!(result, Err(err) => {
@@ -347,6 +432,31 @@ pub(crate) fn handle_references(
     }
 }
 
+#[tracing::instrument(level = "info", skip_all)]
+pub(crate) fn handle_code_lens(
+    params: CodeLensParams,
+    state: &WorldState,
+) -> anyhow::Result<Option<Vec<CodeLens>>> {
+    let uri = &params.text_document.uri;
+    let document = state.get_document(uri)?;
+
+    Ok(Some(code_lenses(uri, document)?))
+}
+
+#[tracing::instrument(level = "info", skip_all)]
+pub(crate) fn handle_linked_editing_range(
+    params: LinkedEditingRangeParams,
+    state: &WorldState,
+) -> anyhow::Result<Option<LinkedEditingRanges>> {
+    let uri = &params.text_document_position_params.text_document.uri;
+    let document = state.get_document(uri)?;
+
+    let position = params.text_document_position_params.position;
+    let point = convert_position_to_point(&document.contents, position);
+
+    linked_editing_ranges(document, point)
+}
+
 #[tracing::instrument(level = "info", skip_all)]
 pub(crate) fn handle_statement_range(
     params: StatementRangeParams,
@@ -381,6 +491,15 @@ pub(crate) fn handle_help_topic(
     help_topic(point, &document)
 }
 
+#[tracing::instrument(level = "info", skip_all)]
+pub(crate) fn handle_breadcrumbs(
+    params: BreadcrumbsParams,
+    state: &WorldState,
+) -> anyhow::Result<BreadcrumbsResponse> {
+    let document = state.get_document(&params.text_document.uri)?;
+    breadcrumbs(document, params.position)
+}
+
 #[tracing::instrument(level = "info", skip_all)]
 pub(crate) fn handle_indent(
     params: DocumentOnTypeFormattingParams,
@@ -393,7 +512,13 @@ pub(crate) fn handle_indent(
     let pos = ctxt.position;
     let point = convert_position_to_point(&doc.contents, pos);
 
-    let res = indent_edit(doc, point.row);
+    let res = match params.ch.as_str() {
+        "{" => brace_edit(doc, point),
+        _ => match roxygen_edit(doc, point.row) {
+            Ok(None) => indent_edit(doc, point.row),
+            res => res,
+        },
+    };
 
     Result::map(res, |opt| {
         Option::map(opt, |edits| edits.into_lsp_offset(&doc.contents))
@@ -410,7 +535,14 @@ pub(crate) fn handle_code_action(
     let doc = state.get_document(&uri)?;
     let range = convert_lsp_range_to_tree_sitter_range(&doc.contents, params.range);
 
-    let code_actions = code_actions(&uri, doc, range, &lsp_state.capabilities);
+    let code_actions = code_actions(
+        &uri,
+        doc,
+        range,
+        &params.context.diagnostics,
+        &state.exports,
+        &lsp_state.capabilities,
+    );
 
     if code_actions.is_empty() {
         Ok(None)
@@ -436,3 +568,10 @@ pub(crate) fn handle_input_boundaries(
     let boundaries = r_task(|| input_boundaries(&params.text))?;
     Ok(InputBoundariesResponse { boundaries })
 }
+
+pub(crate) fn handle_telemetry(
+    params: TelemetryParams,
+    state: &WorldState,
+) -> anyhow::Result<TelemetryResponse> {
+    Ok(telemetry::snapshot(&state.config.telemetry, params.reset))
+}
@@ -5,17 +5,44 @@
 //
 //
 
+use std::collections::HashMap;
+
 use anyhow::*;
+use harp::environment::Environment;
+use harp::environment::R_ENVS;
+use harp::utils::r_is_data_frame;
+use harp::utils::r_promise_is_forced;
+use harp::utils::r_typeof;
+use harp::DataFrame;
+use harp::RFunction;
+use harp::RFunctionExt;
+use harp::RObject;
+use libr::PROMSXP;
+use libr::PRVALUE;
 use stdext::unwrap;
 use stdext::unwrap::IntoResult;
 use tower_lsp::lsp_types::MarkupContent;
 use tower_lsp::lsp_types::MarkupKind;
 use tree_sitter::Node;
 
+use crate::lsp::completions::data_mask_data;
+use crate::lsp::completions::formula_term;
+use crate::lsp::completions::help_topic_node;
+use crate::lsp::completions::roxygen_tag_documentation;
 use crate::lsp::document_context::DocumentContext;
 use crate::lsp::help::RHtmlHelp;
+use crate::lsp::traits::node::NodeExt;
 use crate::lsp::traits::rope::RopeExt;
+use crate::treesitter::node_find_string;
 use crate::treesitter::NodeTypeExt;
+use crate::variables::variable::WorkspaceVariableDisplayType;
+use crate::variables::variable::WorkspaceVariableDisplayValue;
+
+/// Maximum number of columns listed in data frame hovers
+const HOVER_MAX_COLUMNS: usize = 10;
+
+/// Functions whose first argument is a package name
+const PACKAGE_FUNCTIONS: &[&str] = &["library", "require", "requireNamespace", "loadNamespace"];
 
 enum HoverContext {
     Topic { topic: String },
@@ -48,6 +75,15 @@ fn hover_context(node: Node, context: &DocumentContext) -> Result<Option<HoverCo
         return Ok(Some(HoverContext::QualifiedTopic { package, topic }));
     }
 
+    // special terms of model formulas are documented by the package that
+    // understands them, e.g. `s()` in `mgcv::gam(y ~ s(x), data = df)`
+    if let Some(term) = formula_term(&node, context)? {
+        return Ok(Some(HoverContext::QualifiedTopic {
+            package: term.package.to_string(),
+            topic: term.name.to_string(),
+        }));
+    }
+
     // otherwise, check for an identifier or a string
     if node.is_identifier_or_string() || node.is_keyword() {
         // only provide documentation for function calls for now,
@@ -66,15 +102,42 @@ fn hover_context(node: Node, context: &DocumentContext) -> Result<Option<HoverCo
     Ok(None)
 }
 
-pub(crate) fn r_hover(context: &DocumentContext) -> anyhow::Result<Option<MarkupContent>> {
+/// `data_masking_functions` are the user-supplied data-masking functions from
+/// the `positron.r.completions.dataMaskingFunctions` setting
+pub(crate) fn r_hover(
+    context: &DocumentContext,
+    data_masking_functions: &[String],
+) -> anyhow::Result<Option<MarkupContent>> {
     // get the node
     let node = &context.closest_node;
 
+    // roxygen tags, as in `#' @param`
+    if let Some((tag, description)) = roxygen_tag_documentation(context)? {
+        return Ok(Some(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: format!("**@{tag}**\n\n{description}"),
+        }));
+    }
+
+    // `?topic` and `help("topic")`, the cursor may be inside the string
+    if let Some(hover) = r_hover_help_topic(node, context)? {
+        return Ok(Some(hover));
+    }
+
+    // `library(pkg)`, `require(pkg)`, and `pkg` in `pkg::fun`
+    if let Some(hover) = r_hover_package(node, context)? {
+        return Ok(Some(hover));
+    }
+
     // check for identifier
     if !node.is_identifier_or_string() && !node.is_keyword() {
         return Ok(None);
     }
 
+    if let Some(hover) = r_hover_data(node, context, data_masking_functions)? {
+        return Ok(Some(hover));
+    }
+
     let ctx = hover_context(*node, context)?;
     let ctx = unwrap!(ctx, None => {
         return Ok(None);
@@ -94,9 +157,430 @@ pub(crate) fn r_hover(context: &DocumentContext) -> anyhow::Result<Option<Markup
         return Ok(None);
     });
 
-    let markdown = help.markdown()?;
+    let mut markdown = help.markdown()?;
+
+    // For `pkg::fun`, show where `pkg` is found as several versions might be
+    // installed in different libraries
+    if let Some(parent) = node.parent() {
+        if parent.is_namespace_operator() {
+            let lhs = parent.child_by_field_name("lhs").into_result()?;
+            let package = lhs.get_identifier_or_string_text(&context.document.contents)?;
+
+            if let Some(info) = PackageInfo::find(&package)? {
+                markdown.push_str(&format!(
+                    "\n\n---\n\nFrom `{package}` {version} in `{library}`",
+                    version = info.version,
+                    library = info.library
+                ));
+            }
+        }
+    }
+
+    Ok(Some(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: markdown,
+    }))
+}
+
+/// Metadata of an installed package, as resolved by `find.package()`
+struct PackageInfo {
+    title: String,
+    version: String,
+    library: String,
+    attached: bool,
+}
+
+impl PackageInfo {
+    fn find(package: &str) -> anyhow::Result<Option<Self>> {
+        if package.is_empty() {
+            return Ok(None);
+        }
+
+        let info = RFunction::from(".ps.packageInfo").add(package).call()?;
+        if info.is_null() {
+            return Ok(None);
+        }
+
+        let info: HashMap<String, RObject> = info.try_into()?;
+        let field = |name: &str| -> anyhow::Result<RObject> {
+            info.get(name)
+                .cloned()
+                .ok_or_else(|| anyhow!("Expected '{name}' field in package info"))
+        };
+
+        Ok(Some(Self {
+            title: field("title")?.try_into()?,
+            version: field("version")?.try_into()?,
+            library: field("library")?.try_into()?,
+            attached: field("attached")?.try_into()?,
+        }))
+    }
+}
+
+/// Hover for package names, as in `library(pkg)`, `require("pkg")`, or
+/// `pkg::fun`, describing the installed package
+fn r_hover_package(
+    node: &Node,
+    context: &DocumentContext,
+) -> anyhow::Result<Option<MarkupContent>> {
+    let Some(node) = package_name_node(node, context)? else {
+        return Ok(None);
+    };
+
+    let package = node.get_identifier_or_string_text(&context.document.contents)?;
+    let Some(info) = PackageInfo::find(&package)? else {
+        return Ok(None);
+    };
+
+    let status = if info.attached {
+        "Attached"
+    } else {
+        "Not attached"
+    };
+
+    let markdown = format!(
+        "**{package}** {version}\n\n{title}\n\n{status}, installed in `{library}`",
+        version = info.version,
+        title = info.title,
+        library = info.library
+    );
+
+    Ok(Some(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: markdown,
+    }))
+}
+
+/// The package name at `node`: the left-hand side of `::` and `:::`, or the
+/// first argument of `library()` and friends. Strings are matched from
+/// anywhere inside of them.
+fn package_name_node<'tree>(
+    node: &Node<'tree>,
+    context: &DocumentContext,
+) -> anyhow::Result<Option<Node<'tree>>> {
+    let node = node_find_string(node).unwrap_or(*node);
+    if !node.is_identifier_or_string() {
+        return Ok(None);
+    }
+
+    let Some(parent) = node.parent() else {
+        return Ok(None);
+    };
+
+    if parent.is_namespace_operator() {
+        let is_lhs = parent.child_by_field_name("lhs") == Some(node);
+        return Ok(is_lhs.then_some(node));
+    }
+
+    if !parent.is_argument() {
+        return Ok(None);
+    }
+    let Some(call) = parent.parent().and_then(|arguments| arguments.parent()) else {
+        return Ok(None);
+    };
+    if !call.is_call() {
+        return Ok(None);
+    }
+
+    let Some(callee) = call.child_by_field_name("function") else {
+        return Ok(None);
+    };
+    let callee = context.document.contents.node_slice(&callee)?.to_string();
+    if !PACKAGE_FUNCTIONS.contains(&callee.as_str()) {
+        return Ok(None);
+    }
+
+    // Positionally, as in `handle_package_attach_call()`
+    if call.arguments_values().flatten().nth(0) != Some(node) {
+        return Ok(None);
+    }
+
+    Ok(Some(node))
+}
+
+/// Hover for help topics, as in `?topic` or `help("topic")`, previewing the
+/// title of the topic
+fn r_hover_help_topic(
+    node: &Node,
+    context: &DocumentContext,
+) -> anyhow::Result<Option<MarkupContent>> {
+    let Some(node) = help_topic_node(node, &context.document.contents)? else {
+        return Ok(None);
+    };
+
+    let mut topic = context.document.contents.node_slice(&node)?.to_string();
+    if node.is_string() {
+        topic = topic.trim_matches(['"', '\'']).to_string();
+    }
+
+    let Some(help) = RHtmlHelp::from_topic(topic.as_str(), None)? else {
+        return Ok(None);
+    };
+    let Some(title) = help.title() else {
+        return Ok(None);
+    };
+
+    let mut markdown = format!("**{title}**");
+    if let Some(preamble) = help.topic() {
+        markdown.push_str(&format!("\n\n`{preamble}`"));
+    }
+
     Ok(Some(MarkupContent {
         kind: MarkupKind::Markdown,
         value: markdown,
     }))
 }
+
+/// Hover for data frames bound in the global environment, and for their
+/// columns in data-masking contexts
+///
+/// Only metadata is inspected: active bindings and unevaluated promises are
+/// never forced, and columns are described by type without looking at their
+/// values.
+fn r_hover_data(
+    node: &Node,
+    context: &DocumentContext,
+    data_masking_functions: &[String],
+) -> anyhow::Result<Option<MarkupContent>> {
+    if !node.is_identifier() {
+        return Ok(None);
+    }
+
+    // Function calls and qualified names are documented by help hovers
+    if let Some(parent) = node.parent() {
+        if parent.is_call() || parent.is_namespace_operator() {
+            return Ok(None);
+        }
+    }
+
+    let name = context.document.contents.node_slice(node)?.to_string();
+
+    // Columns of data-masking contexts, e.g. `x` in `lm(y ~ x, data = df)`
+    if let Some(data) = data_mask_data(context, data_masking_functions)? {
+        if let Some(value) = global_data_frame(&data)? {
+            if let Some(markdown) = data_frame_column_markdown(&name, &data, value)? {
+                return Ok(Some(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: markdown,
+                }));
+            }
+        }
+    }
+
+    let Some(value) = global_data_frame(&name)? else {
+        return Ok(None);
+    };
+
+    Ok(Some(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: data_frame_markdown(&name, value)?,
+    }))
+}
+
+/// The data frame bound to `name` in the global environment, if any
+fn global_data_frame(name: &str) -> anyhow::Result<Option<libr::SEXP>> {
+    let env = Environment::view(R_ENVS.global);
+    if !env.exists(name) || env.is_active(name.into())? {
+        return Ok(None);
+    }
+
+    let mut value = env.find(name)?;
+    if r_typeof(value) == PROMSXP {
+        if !r_promise_is_forced(value) {
+            return Ok(None);
+        }
+        value = unsafe { PRVALUE(value) };
+    }
+
+    if !r_is_data_frame(value) {
+        return Ok(None);
+    }
+
+    Ok(Some(value))
+}
+
+fn data_frame_markdown(name: &str, value: libr::SEXP) -> anyhow::Result<String> {
+    let object = RObject::view(value);
+
+    let WorkspaceVariableDisplayType { display_type, .. } =
+        WorkspaceVariableDisplayType::from(value, false);
+
+    let n_row = DataFrame::n_row(value)?;
+    let n_col = DataFrame::n_col(value)?;
+
+    let mut markdown = format!("`{name}`: {display_type} with {n_row} rows and {n_col} columns");
+
+    match object.size() {
+        std::result::Result::Ok(size) => markdown.push_str(&format!(" ({})", format_size(size))),
+        Err(err) => log::trace!("Can't compute size of `{name}`: {err}"),
+    }
+
+    let names = object.names().unwrap_or_default();
+    if names.is_empty() {
+        return Ok(markdown);
+    }
+
+    markdown.push_str("\n\n| Column | Type |\n|:--|:--|\n");
+
+    for (i, column) in names.iter().take(HOVER_MAX_COLUMNS).enumerate() {
+        let column = column.as_deref().unwrap_or("");
+        let column_value = harp::list_get(value, i as isize);
+        let WorkspaceVariableDisplayType { display_type, .. } =
+            WorkspaceVariableDisplayType::from(column_value, false);
+
+        markdown.push_str(&format!("| `{column}` | {display_type} |\n"));
+    }
+
+    if names.len() > HOVER_MAX_COLUMNS {
+        let rest = names.len() - HOVER_MAX_COLUMNS;
+        markdown.push_str(&format!("\n_and {rest} more columns_\n"));
+    }
+
+    Ok(markdown)
+}
+
+fn data_frame_column_markdown(
+    name: &str,
+    data: &str,
+    value: libr::SEXP,
+) -> anyhow::Result<Option<String>> {
+    let names = RObject::view(value).names().unwrap_or_default();
+    let Some(i) = names
+        .iter()
+        .position(|column| column.as_deref() == Some(name))
+    else {
+        return Ok(None);
+    };
+
+    let column = harp::list_get(value, i as isize);
+
+    let WorkspaceVariableDisplayType { display_type, .. } =
+        WorkspaceVariableDisplayType::from(column, true);
+    let WorkspaceVariableDisplayValue { display_value, .. } =
+        WorkspaceVariableDisplayValue::from(column);
+
+    Ok(Some(format!(
+        "`{name}`: column of `{data}`, {display_type}\n\n```\n{display_value}\n```"
+    )))
+}
+
+/// Formats an object size like `format(object.size(x), units = "auto")`
+fn format_size(size: usize) -> String {
+    let units = ["bytes", "Kb", "Mb", "Gb", "Tb"];
+
+    let mut size = size as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{size} {}", units[unit])
+    } else {
+        format!("{size:.1} {}", units[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use harp::eval::parse_eval_global;
+
+    use crate::fixtures::point_from_cursor;
+    use crate::lsp::document_context::DocumentContext;
+    use crate::lsp::documents::Document;
+    use crate::lsp::hover::format_size;
+    use crate::lsp::hover::r_hover;
+    use crate::r_task;
+
+    #[test]
+    fn test_hover_data_frame() {
+        r_task(|| {
+            parse_eval_global("hover_df <- data.frame(x = 1:3, y = letters[1:3])").unwrap();
+
+            let (text, point) = point_from_cursor("hover_@df");
+            let document = Document::new(text.as_str(), None);
+            let context = DocumentContext::new(&document, point, None);
+
+            let hover = r_hover(&context, &[]).unwrap().unwrap();
+            assert!(hover
+                .value
+                .starts_with("`hover_df`: data.frame with 3 rows and 2 columns"));
+            assert!(hover.value.contains("| `x` | int |"));
+            assert!(hover.value.contains("| `y` | str |"));
+
+            // Columns inside formulas
+            let (text, point) = point_from_cursor("lm(x ~ @y, data = hover_df)");
+            let document = Document::new(text.as_str(), None);
+            let column_context = DocumentContext::new(&document, point, None);
+
+            let hover = r_hover(&column_context, &[]).unwrap().unwrap();
+            assert!(hover
+                .value
+                .starts_with("`y`: column of `hover_df`, str [3]"));
+
+            // Not a data frame
+            parse_eval_global("hover_df <- 1").unwrap();
+            assert!(r_hover(&context, &[]).unwrap().is_none());
+
+            parse_eval_global("rm(hover_df)").unwrap();
+        })
+    }
+
+    #[test]
+    fn test_hover_help_topic() {
+        r_task(|| {
+            let hover = |code: &str| {
+                let (text, point) = point_from_cursor(code);
+                let document = Document::new(text.as_str(), None);
+                let context = DocumentContext::new(&document, point, None);
+                r_hover(&context, &[]).unwrap()
+            };
+
+            let hover_question = hover("?as.charac@ter").unwrap();
+            assert!(hover_question.value.starts_with("**Character Vectors**"));
+
+            let hover_help = hover("help(\"as.charac@ter\")").unwrap();
+            assert_eq!(hover_help.value, hover_question.value);
+
+            // Not a help topic
+            assert!(hover("help(package = \"as.charac@ter\")").is_none());
+        })
+    }
+
+    #[test]
+    fn test_hover_package() {
+        r_task(|| {
+            let hover = |code: &str| {
+                let (text, point) = point_from_cursor(code);
+                let document = Document::new(text.as_str(), None);
+                let context = DocumentContext::new(&document, point, None);
+                r_hover(&context, &[]).unwrap()
+            };
+
+            let hover_library = hover("library(ut@ils)").unwrap();
+            assert!(hover_library.value.starts_with("**utils** "));
+            assert!(hover_library.value.contains("The R Utils Package"));
+            assert!(hover_library.value.contains("installed in `"));
+
+            let hover_require = hover("require(\"ut@ils\")").unwrap();
+            assert_eq!(hover_require.value, hover_library.value);
+
+            let hover_namespace = hover("ut@ils::head").unwrap();
+            assert_eq!(hover_namespace.value, hover_library.value);
+
+            // Not a package argument
+            assert!(hover("library(utils, lib.loc = ut@ils)").is_none());
+
+            // Not installed
+            assert!(hover("library(notapack@age)").is_none());
+        })
+    }
+
+    #[test]
+    fn test_hover_format_size() {
+        assert_eq!(format_size(56), "56 bytes");
+        assert_eq!(format_size(2048), "2.0 Kb");
+        assert_eq!(format_size(3 * 1024 * 1024 + 512 * 1024), "3.5 Mb");
+    }
+}
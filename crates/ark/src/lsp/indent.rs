@@ -189,6 +189,126 @@ pub fn indent_edit(doc: &Document, line: usize) -> anyhow::Result<Option<Vec<Ark
     Ok(Some(edits))
 }
 
+/// Continue roxygen comments on new lines
+///
+/// Hooked up to format-on-type for newline characters. When `line` follows
+/// a roxygen comment, its indentation is replaced by the indentation and
+/// prefix of that comment.
+pub fn roxygen_edit(doc: &Document, line: usize) -> anyhow::Result<Option<Vec<ArkTextEdit>>> {
+    let text = &doc.contents;
+
+    if line == 0 || line >= text.len_lines() {
+        return Ok(None);
+    }
+
+    let previous = text.line(line - 1).to_string();
+    let previous_content = previous.trim_start_matches([' ', '\t']);
+    if !previous_content.starts_with("#'") {
+        return Ok(None);
+    }
+
+    let current = text.line(line).to_string();
+    let current_content = current.trim_start_matches([' ', '\t']);
+    if current_content.starts_with('#') {
+        return Ok(None);
+    }
+
+    let previous_indent = &previous[..previous.len() - previous_content.len()];
+    let current_indent = current.len() - current_content.len();
+
+    let edit = ArkTextEdit {
+        range: ArkRange {
+            start: ArkPoint {
+                row: line,
+                column: 0,
+            },
+            end: ArkPoint {
+                row: line,
+                column: current_indent,
+            },
+        },
+        new_text: format!("{previous_indent}#' "),
+    };
+
+    Ok(Some(vec![edit]))
+}
+
+/// Close empty function bodies
+///
+/// Hooked up to format-on-type for `{` characters. When `point` follows the
+/// opening brace of an empty and unclosed function body, we insert an indented
+/// empty line and the closing brace, aligned with the line of the function.
+pub fn brace_edit(
+    doc: &Document,
+    point: tree_sitter::Point,
+) -> anyhow::Result<Option<Vec<ArkTextEdit>>> {
+    let text = &doc.contents;
+    let config = &doc.config.indent;
+
+    if point.column == 0 || point.row >= text.len_lines() {
+        return Ok(None);
+    }
+
+    // Only when nothing follows the brace on the line
+    let line = text.line(point.row).to_string();
+    let Some(rest) = line.get(point.column..) else {
+        return Ok(None);
+    };
+    if !rest.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let brace = tree_sitter::Point {
+        row: point.row,
+        column: point.column - 1,
+    };
+    let Some(open) = doc.ast.root_node().descendant_for_point_range(brace, brace) else {
+        return Ok(None);
+    };
+    let Some(body) = open.parent() else {
+        return Ok(None);
+    };
+    if !body.is_braced_expression() ||
+        body.child_by_field_name("open") != Some(open) ||
+        body.named_child_count() > 0
+    {
+        return Ok(None);
+    }
+
+    let Some(function) = body.parent() else {
+        return Ok(None);
+    };
+    if !function.is_function_definition() || function.child_by_field_name("body") != Some(body) {
+        return Ok(None);
+    }
+
+    // Already closed
+    if body
+        .child_by_field_name("close")
+        .is_some_and(|close| !close.is_missing())
+    {
+        return Ok(None);
+    }
+
+    let (indent, _) = line_indent(text, function.start_position().row, config);
+
+    let new_text = format!(
+        "\n{}\n{}}}",
+        new_line_indent(config, indent + config.indent_size),
+        new_line_indent(config, indent)
+    );
+
+    let edit = ArkTextEdit {
+        range: ArkRange {
+            start: point,
+            end: point,
+        },
+        new_text,
+    };
+
+    Ok(Some(vec![edit]))
+}
+
 fn brace_parent(node: tree_sitter::Node) -> tree_sitter::Node {
     let Some(parent) = node.parent() else {
         return node;
@@ -253,8 +373,10 @@ mod tests {
     use crate::lsp::config::IndentStyle;
     use crate::lsp::config::IndentationConfig;
     use crate::lsp::documents::Document;
+    use crate::lsp::indent::brace_edit;
     use crate::lsp::indent::indent_edit;
     use crate::lsp::indent::new_line_indent;
+    use crate::lsp::indent::roxygen_edit;
     use crate::lsp::offset::apply_text_edits;
 
     // NOTE: If we keep adding tests we might want to switch to snapshot tests
@@ -508,6 +630,62 @@ mod tests {
         assert_eq!(new_line_indent(&large_tab_cfg, 12), String::from("\t    "));
     }
 
+    #[test]
+    fn test_roxygen_edit() {
+        let mut text = String::from("#' Title\n\nfoo <- 1");
+        let doc = test_doc(&text);
+        let edits = roxygen_edit(&doc, 1).unwrap().unwrap();
+        apply_text_edits(edits, &mut text).unwrap();
+        assert_eq!(text, "#' Title\n#' \nfoo <- 1");
+
+        // Indented roxygen blocks and auto-indented lines
+        let mut text = String::from("  #' @param x\n    \n");
+        let doc = test_doc(&text);
+        let edits = roxygen_edit(&doc, 1).unwrap().unwrap();
+        apply_text_edits(edits, &mut text).unwrap();
+        assert_eq!(text, "  #' @param x\n  #' \n");
+
+        // Not after regular comments or code
+        let doc = test_doc("# Title\n\n");
+        assert!(roxygen_edit(&doc, 1).unwrap().is_none());
+        let doc = test_doc("foo <- 1\n\n");
+        assert!(roxygen_edit(&doc, 1).unwrap().is_none());
+
+        // Not when the frontend already continued the comment
+        let doc = test_doc("#' Title\n#' \n");
+        assert!(roxygen_edit(&doc, 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_brace_edit() {
+        let point = |row, column| tree_sitter::Point { row, column };
+
+        let mut text = String::from("foo <- function(x) {");
+        let doc = test_doc(&text);
+        let edits = brace_edit(&doc, point(0, 20)).unwrap().unwrap();
+        apply_text_edits(edits, &mut text).unwrap();
+        assert_eq!(text, "foo <- function(x) {\n  \n}");
+
+        // Closing brace is aligned with the line of the function
+        let mut text = String::from("  foo <- function() {");
+        let doc = test_doc(&text);
+        let edits = brace_edit(&doc, point(0, 21)).unwrap().unwrap();
+        apply_text_edits(edits, &mut text).unwrap();
+        assert_eq!(text, "  foo <- function() {\n    \n  }");
+
+        // Already closed
+        let doc = test_doc("foo <- function(x) {}");
+        assert!(brace_edit(&doc, point(0, 20)).unwrap().is_none());
+
+        // Not a function body
+        let doc = test_doc("if (TRUE) {");
+        assert!(brace_edit(&doc, point(0, 11)).unwrap().is_none());
+
+        // Not at end of line
+        let doc = test_doc("foo <- function(x) { x");
+        assert!(brace_edit(&doc, point(0, 20)).unwrap().is_none());
+    }
+
     fn read_text_asset(path: &str) -> String {
         let mut asset = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         asset.push("src");
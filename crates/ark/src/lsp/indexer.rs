@@ -6,6 +6,7 @@
 //
 
 use std::collections::HashMap;
+use std::path::Path;
 use std::result::Result::Ok;
 use std::sync::Arc;
 use std::sync::LazyLock;
@@ -13,6 +14,8 @@ use std::sync::Mutex;
 
 use regex::Regex;
 use ropey::Rope;
+use serde::Deserialize;
+use serde::Serialize;
 use stdext::unwrap;
 use stdext::unwrap::IntoResult;
 use tower_lsp::lsp_types::Range;
@@ -52,7 +55,7 @@ impl FileId {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum IndexEntryData {
     Variable {
         name: String,
@@ -71,7 +74,7 @@ pub enum IndexEntryData {
     },
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct IndexEntry {
     pub key: String,
     pub range: Range,
@@ -155,6 +158,30 @@ pub fn map(mut callback: impl FnMut(&Url, &String, &IndexEntry)) {
     }
 }
 
+/// The entries indexed for a file, in no particular order
+pub(crate) fn file_entries(uri: &Url) -> Vec<IndexEntry> {
+    let index = WORKSPACE_INDEX.lock().unwrap();
+    let file_id = FileId::from_uri(uri.clone());
+
+    match index.get(&file_id) {
+        Some(file_index) => file_index.values().cloned().collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Replaces the entries of a file with entries indexed in a previous session
+pub(crate) fn restore(uri: &Url, entries: Vec<IndexEntry>) {
+    let mut index = WORKSPACE_INDEX.lock().unwrap();
+    let file_id = FileId::from_uri(uri.clone());
+
+    let file_index = index.entry(file_id).or_default();
+    file_index.clear();
+
+    for entry in entries {
+        index_insert(file_index, entry);
+    }
+}
+
 #[tracing::instrument(level = "trace", skip_all, fields(uri = %uri))]
 pub fn update(document: &Document, uri: &Url) -> anyhow::Result<()> {
     delete(uri)?;
@@ -257,6 +284,57 @@ pub fn filter_entry(entry: &DirEntry) -> bool {
     true
 }
 
+/// Whether `path` matches one of the `exclude` globs
+///
+/// Globs are matched against the path relative to the workspace `folder`,
+/// with `/` as separator. `*` and `?` don't match across path separators,
+/// `**` does.
+pub(crate) fn is_excluded(path: &Path, folder: &Path, exclude: &[String]) -> bool {
+    if exclude.is_empty() {
+        return false;
+    }
+
+    let Ok(relative) = path.strip_prefix(folder) else {
+        return false;
+    };
+
+    let relative = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    exclude
+        .iter()
+        .any(|glob| glob_matches(glob.as_bytes(), relative.as_bytes()))
+}
+
+fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+
+        // Zero or more directories
+        [b'*', b'*', b'/', rest @ ..] => {
+            glob_matches(rest, text) ||
+                text.iter()
+                    .enumerate()
+                    .any(|(i, c)| *c == b'/' && glob_matches(rest, &text[i + 1..]))
+        },
+
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_matches(rest, &text[i..])),
+
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|i| *i == 0 || text[i - 1] != b'/')
+            .any(|i| glob_matches(rest, &text[i..])),
+
+        [b'?', rest @ ..] => {
+            matches!(text, [c, tail @ ..] if *c != b'/' && glob_matches(rest, tail))
+        },
+
+        [c, rest @ ..] => matches!(text, [t, tail @ ..] if t == c && glob_matches(rest, tail)),
+    }
+}
+
 pub(crate) fn create(uri: &Url) -> anyhow::Result<()> {
     // Only index R files for file URIs. This discards `inmemory` (Console) and
     // `ark` schemes in particular.
@@ -273,11 +351,8 @@ pub(crate) fn create(uri: &Url) -> anyhow::Result<()> {
         return Ok(());
     }
 
-    // TODO: Handle document encodings here.
     // TODO: Check if there's an up-to-date buffer to be used.
-    let contents = std::fs::read(path)?;
-    let contents = String::from_utf8(contents)?;
-    let document = Document::new(contents.as_str(), None);
+    let document = Document::read(&path)?;
 
     index_document(&document, uri);
 
@@ -687,4 +762,37 @@ class <- R6::R6Class(
             IndexEntryData::Variable { name } => assert_eq!(name, "foo")
         );
     }
+
+    #[test]
+    fn test_is_excluded() {
+        let folder = Path::new("/workspace");
+        let exclude = vec![
+            String::from("inst/**"),
+            String::from("**/*_generated.R"),
+            String::from("data-raw/?.R"),
+        ];
+
+        let excluded = |path: &str| is_excluded(&folder.join(path), folder, &exclude);
+
+        assert!(excluded("inst/foo.R"));
+        assert!(excluded("inst/scripts/foo.R"));
+        assert!(excluded("foo_generated.R"));
+        assert!(excluded("R/foo_generated.R"));
+        assert!(excluded("data-raw/a.R"));
+
+        assert!(!excluded("R/foo.R"));
+        assert!(!excluded("R/inst/foo.R"));
+        assert!(!excluded("data-raw/ab.R"));
+        assert!(!excluded("data-raw/sub/a.R"));
+
+        // Paths outside of the folder are never excluded
+        assert!(!is_excluded(
+            Path::new("/elsewhere/inst/foo.R"),
+            folder,
+            &exclude
+        ));
+
+        // Nothing is excluded by default
+        assert!(!is_excluded(&folder.join("inst/foo.R"), folder, &[]));
+    }
 }
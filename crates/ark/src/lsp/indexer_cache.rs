@@ -0,0 +1,318 @@
+//
+// indexer_cache.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use rustc_hash::FxHasher;
+use serde::Deserialize;
+use serde::Serialize;
+use url::Url;
+
+use crate::lsp::indexer;
+use crate::lsp::indexer::IndexEntry;
+
+/// Bump this when the format of index entries changes so that caches written
+/// by previous versions of ark are discarded
+const CACHE_VERSION: u32 = 1;
+
+/// On-disk cache of the workspace index
+///
+/// Indexing a large workspace from scratch is slow, so the entries of every
+/// file indexed from disk are persisted across sessions. A cached file is
+/// only reused if its modification time and size haven't changed since it
+/// was indexed. Files indexed from an open document are dropped from the
+/// cache since their entries may not reflect the contents on disk. They are
+/// reindexed from disk in the next session.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexCache {
+    version: u32,
+    files: HashMap<Url, CachedFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedFile {
+    stamp: FileStamp,
+    entries: Vec<IndexEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct FileStamp {
+    /// Modification time in nanoseconds since the Unix epoch
+    modified: u128,
+    size: u64,
+}
+
+struct WorkspaceCache {
+    path: PathBuf,
+    cache: IndexCache,
+    dirty: bool,
+}
+
+static WORKSPACE_CACHE: LazyLock<Mutex<Option<WorkspaceCache>>> =
+    LazyLock::new(|| Mutex::new(None));
+
+/// Loads the cache of the workspace made of `folders` and restores the index
+/// of the files of `uris` that haven't changed since they were cached
+///
+/// Returns the files that still need to be indexed.
+pub(crate) fn start(folders: &[String], uris: Vec<Url>) -> Vec<Url> {
+    let Some(path) = cache_path(folders) else {
+        return uris;
+    };
+
+    let mut cached = read(&path).unwrap_or_else(|err| {
+        log::trace!(
+            "Can't read index cache at {path}: {err}",
+            path = path.display()
+        );
+        IndexCache::default()
+    });
+
+    // Start from a fresh cache so that files that no longer exist are dropped
+    let mut cache = IndexCache {
+        version: CACHE_VERSION,
+        files: HashMap::new(),
+    };
+    let mut stale = Vec::new();
+
+    for uri in uris {
+        let Some(file) = cached.files.remove(&uri) else {
+            stale.push(uri);
+            continue;
+        };
+
+        if file_stamp(&uri).as_ref() != Some(&file.stamp) {
+            stale.push(uri);
+            continue;
+        }
+
+        indexer::restore(&uri, file.entries.clone());
+        cache.files.insert(uri, file);
+    }
+
+    log::info!(
+        "Restored {n} files from the index cache, {m} files left to index",
+        n = cache.files.len(),
+        m = stale.len()
+    );
+
+    *WORKSPACE_CACHE.lock().unwrap() = Some(WorkspaceCache {
+        path,
+        cache,
+        dirty: true,
+    });
+
+    stale
+}
+
+/// Records the entries of a file freshly indexed from disk
+pub(crate) fn created(uri: &Url) {
+    let mut workspace = WORKSPACE_CACHE.lock().unwrap();
+    let Some(workspace) = workspace.as_mut() else {
+        return;
+    };
+
+    match file_stamp(uri) {
+        Some(stamp) => {
+            let entries = indexer::file_entries(uri);
+            workspace
+                .cache
+                .files
+                .insert(uri.clone(), CachedFile { stamp, entries });
+        },
+        None => {
+            workspace.cache.files.remove(uri);
+        },
+    }
+
+    workspace.dirty = true;
+}
+
+/// Drops a file whose index no longer reflects its contents on disk
+pub(crate) fn invalidated(uri: &Url) {
+    let mut workspace = WORKSPACE_CACHE.lock().unwrap();
+    let Some(workspace) = workspace.as_mut() else {
+        return;
+    };
+
+    if workspace.cache.files.remove(uri).is_some() {
+        workspace.dirty = true;
+    }
+}
+
+/// Writes the cache to disk if it has changed since the last write
+pub(crate) fn flush() {
+    let mut workspace = WORKSPACE_CACHE.lock().unwrap();
+    let Some(workspace) = workspace.as_mut() else {
+        return;
+    };
+
+    if !workspace.dirty {
+        return;
+    }
+
+    if let Err(err) = write(&workspace.path, &workspace.cache) {
+        log::warn!(
+            "Can't write index cache at {path}: {err}",
+            path = workspace.path.display()
+        );
+        return;
+    }
+
+    workspace.dirty = false;
+}
+
+fn read(path: &Path) -> anyhow::Result<IndexCache> {
+    let contents = std::fs::read(path)?;
+    let cache: IndexCache = serde_json::from_slice(&contents)?;
+
+    if cache.version != CACHE_VERSION {
+        return Err(anyhow::anyhow!(
+            "Unexpected cache version {version}",
+            version = cache.version
+        ));
+    }
+
+    Ok(cache)
+}
+
+fn write(path: &Path, cache: &IndexCache) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // Write to a temporary file first so that a concurrent session never
+    // reads a partially written cache
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec(cache)?)?;
+    std::fs::rename(&tmp, path)?;
+
+    Ok(())
+}
+
+fn file_stamp(uri: &Url) -> Option<FileStamp> {
+    let path = uri.to_file_path().ok()?;
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+    Some(FileStamp {
+        modified: modified.as_nanos(),
+        size: metadata.len(),
+    })
+}
+
+/// The cache file of a workspace, keyed by a hash of its folders
+fn cache_path(folders: &[String]) -> Option<PathBuf> {
+    let mut hasher = FxHasher::default();
+    folders.hash(&mut hasher);
+    let file = format!("index-{hash:016x}.json", hash = hasher.finish());
+
+    Some(cache_dir()?.join("lsp").join(file))
+}
+
+#[cfg(unix)]
+fn cache_dir() -> Option<PathBuf> {
+    let dirs = xdg::BaseDirectories::with_prefix("ark").ok()?;
+    Some(dirs.get_cache_home())
+}
+
+#[cfg(not(unix))]
+fn cache_dir() -> Option<PathBuf> {
+    let local = std::env::var_os("LOCALAPPDATA")?;
+    Some(PathBuf::from(local).join("ark").join("cache"))
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Position;
+    use tower_lsp::lsp_types::Range;
+    use url::Url;
+
+    use crate::lsp::indexer::IndexEntry;
+    use crate::lsp::indexer::IndexEntryData;
+    use crate::lsp::indexer_cache::file_stamp;
+    use crate::lsp::indexer_cache::read;
+    use crate::lsp::indexer_cache::write;
+    use crate::lsp::indexer_cache::CachedFile;
+    use crate::lsp::indexer_cache::IndexCache;
+    use crate::lsp::indexer_cache::CACHE_VERSION;
+
+    #[test]
+    fn test_index_cache_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file.R");
+        std::fs::write(&file, "foo <- function(x) x\n").unwrap();
+        let uri = Url::from_file_path(&file).unwrap();
+
+        let entry = IndexEntry {
+            key: String::from("foo"),
+            range: Range::new(Position::new(0, 0), Position::new(0, 3)),
+            data: IndexEntryData::Function {
+                name: String::from("foo"),
+                arguments: vec![String::from("x")],
+            },
+        };
+
+        let mut cache = IndexCache {
+            version: CACHE_VERSION,
+            ..Default::default()
+        };
+        cache.files.insert(uri.clone(), CachedFile {
+            stamp: file_stamp(&uri).unwrap(),
+            entries: vec![entry],
+        });
+
+        let path = dir.path().join("cache").join("index.json");
+        write(&path, &cache).unwrap();
+
+        let cache = read(&path).unwrap();
+        let file = cache.files.get(&uri).unwrap();
+        assert_eq!(Some(file.stamp.clone()), file_stamp(&uri));
+        assert_eq!(file.entries[0].key, "foo");
+        assert!(matches!(
+            file.entries[0].data,
+            IndexEntryData::Function { ref arguments, .. } if arguments == &vec![String::from("x")]
+        ));
+    }
+
+    #[test]
+    fn test_index_cache_stamp_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("file.R");
+        std::fs::write(&file, "foo <- 1\n").unwrap();
+        let uri = Url::from_file_path(&file).unwrap();
+
+        let stamp = file_stamp(&uri).unwrap();
+
+        std::fs::write(&file, "foo <- 1\nbar <- 2\n").unwrap();
+        assert_ne!(file_stamp(&uri), Some(stamp));
+
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(file_stamp(&uri), None);
+    }
+
+    #[test]
+    fn test_index_cache_version_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.json");
+
+        let cache = IndexCache {
+            version: CACHE_VERSION + 1,
+            ..Default::default()
+        };
+        write(&path, &cache).unwrap();
+
+        assert!(read(&path).is_err());
+    }
+}
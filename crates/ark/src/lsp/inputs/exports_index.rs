@@ -0,0 +1,217 @@
+//
+// exports_index.rs
+//
+// Copyright (C) 2025 by Posit Software, PBC
+//
+//
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::RwLock;
+
+use super::package::Package;
+use crate::lsp;
+
+/// Maps exported symbols to the installed packages that export them
+///
+/// The index is built once in the background by scanning the library paths.
+/// Until it's ready, lookups don't return any package. Packages are indexed
+/// again when they are invalidated, e.g. after they were installed or updated.
+#[derive(Default, Clone, Debug)]
+pub struct ExportsIndex {
+    symbols: Arc<RwLock<Option<HashMap<String, Vec<String>>>>>,
+    library_paths: Arc<Vec<PathBuf>>,
+}
+
+impl ExportsIndex {
+    /// Start building the index for the packages installed in `library_paths`
+    /// on a background thread
+    pub fn spawn(library_paths: Vec<PathBuf>) -> Self {
+        let index = Self {
+            symbols: Arc::default(),
+            library_paths: Arc::new(library_paths),
+        };
+
+        let symbols = index.symbols.clone();
+        let library_paths = index.library_paths.clone();
+        let spawned = std::thread::Builder::new()
+            .name(String::from("ark-lsp-exports-index"))
+            .spawn(move || {
+                let scanned = Self::scan(&library_paths);
+                *symbols.write().unwrap() = Some(scanned);
+            });
+
+        if let Err(err) = spawned {
+            lsp::log_error!("Can't spawn exports index thread: {err:?}");
+        }
+
+        index
+    }
+
+    /// Build the index synchronously
+    #[cfg(test)]
+    pub fn build(library_paths: &[PathBuf]) -> Self {
+        Self {
+            symbols: Arc::new(RwLock::new(Some(Self::scan(library_paths)))),
+            library_paths: Arc::new(library_paths.to_vec()),
+        }
+    }
+
+    /// Installed packages exporting `symbol`, sorted by name
+    pub fn packages(&self, symbol: &str) -> Vec<String> {
+        self.symbols
+            .read()
+            .unwrap()
+            .as_ref()
+            .and_then(|symbols| symbols.get(symbol))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Index the exports of `name` again. It might have been installed,
+    /// updated, or removed since the index was built.
+    pub fn invalidate(&self, name: &str) {
+        let mut exports = HashMap::new();
+        for library_path in self.library_paths.iter() {
+            Self::scan_package(library_path, name, &mut exports);
+        }
+
+        let mut symbols = self.symbols.write().unwrap();
+
+        // Still building, the scan picks up the current state of the package
+        let Some(symbols) = symbols.as_mut() else {
+            return;
+        };
+
+        for packages in symbols.values_mut() {
+            packages.retain(|package| package != name);
+        }
+        symbols.retain(|_, packages| !packages.is_empty());
+
+        for symbol in exports.into_keys() {
+            let packages = symbols.entry(symbol).or_default();
+            packages.push(name.to_string());
+            packages.sort();
+        }
+    }
+
+    fn scan(library_paths: &[PathBuf]) -> HashMap<String, Vec<String>> {
+        let mut symbols: HashMap<String, Vec<String>> = HashMap::new();
+
+        for library_path in library_paths {
+            let entries = match std::fs::read_dir(library_path) {
+                Ok(entries) => entries,
+                Err(err) => {
+                    tracing::warn!(
+                        "Can't read library `{path}`: {err:?}",
+                        path = library_path.to_string_lossy()
+                    );
+                    continue;
+                },
+            };
+
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let Some(name) = entry.file_name().to_str().map(String::from) else {
+                    continue;
+                };
+                Self::scan_package(library_path, &name, &mut symbols);
+            }
+        }
+
+        for packages in symbols.values_mut() {
+            packages.sort();
+            packages.dedup();
+        }
+
+        symbols
+    }
+
+    fn scan_package(library_path: &Path, name: &str, symbols: &mut HashMap<String, Vec<String>>) {
+        let package = match Package::load_from_library(library_path, name) {
+            Ok(Some(package)) => package,
+            Ok(None) => return,
+            Err(err) => {
+                tracing::trace!("Can't index exports of `{name}`: {err:?}");
+                return;
+            },
+        };
+
+        for symbol in package.exported_symbols {
+            symbols.entry(symbol).or_default().push(name.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn install_package(library: &Path, name: &str, namespace: &str) {
+        let path = library.join(name);
+        fs::create_dir(&path).unwrap();
+        fs::write(
+            path.join("DESCRIPTION"),
+            format!("Package: {name}\nVersion: 1.0\n"),
+        )
+        .unwrap();
+        fs::write(path.join("NAMESPACE"), namespace).unwrap();
+    }
+
+    #[test]
+    fn test_exports_index() {
+        let library = TempDir::new().unwrap();
+        install_package(library.path(), "pkgb", "export(filter)\nexport(only_b)\n");
+        install_package(library.path(), "pkga", "export(filter)\n");
+
+        // Not a package
+        fs::create_dir(library.path().join("notapkg")).unwrap();
+
+        let index = ExportsIndex::build(&[library.path().to_path_buf()]);
+
+        assert_eq!(index.packages("filter"), &["pkga", "pkgb"]);
+        assert_eq!(index.packages("only_b"), &["pkgb"]);
+        assert!(index.packages("unknown").is_empty());
+
+        // Unbuilt indices don't know about any symbols
+        assert!(ExportsIndex::default().packages("filter").is_empty());
+    }
+
+    #[test]
+    fn test_exports_index_invalidate() {
+        let library = TempDir::new().unwrap();
+        install_package(library.path(), "pkga", "export(filter)\nexport(old)\n");
+        install_package(library.path(), "pkgb", "export(filter)\n");
+
+        let index = ExportsIndex::build(&[library.path().to_path_buf()]);
+        assert_eq!(index.packages("old"), &["pkga"]);
+
+        // Updated package
+        fs::write(
+            library.path().join("pkga").join("NAMESPACE"),
+            "export(filter)\nexport(new)\n",
+        )
+        .unwrap();
+        // Newly installed package
+        install_package(library.path(), "pkgc", "export(filter)\n");
+
+        // Not picked up until invalidated
+        assert!(index.packages("new").is_empty());
+
+        index.invalidate("pkga");
+        index.invalidate("pkgc");
+        assert!(index.packages("old").is_empty());
+        assert_eq!(index.packages("new"), &["pkga"]);
+        assert_eq!(index.packages("filter"), &["pkga", "pkgb", "pkgc"]);
+
+        // Removed package
+        fs::remove_dir_all(library.path().join("pkgb")).unwrap();
+        index.invalidate("pkgb");
+        assert_eq!(index.packages("filter"), &["pkga", "pkgc"]);
+    }
+}
@@ -56,6 +56,13 @@ impl Library {
         pkg
     }
 
+    /// Forget the cached copy of a package, e.g. after it has been loaded or
+    /// attached in the session. It might have been installed or updated since
+    /// it was cached, including as absent.
+    pub fn invalidate(&self, name: &str) {
+        self.packages.write().unwrap().remove(name);
+    }
+
     /// Insert a package in the library for testing purposes.
     #[cfg(test)]
     pub fn insert(self, name: &str, package: Package) -> Self {
@@ -144,4 +151,28 @@ importFrom(pkg, baz)
         assert_eq!(pkg.namespace.exports, vec!["bar", "foo"]);
         assert_eq!(pkg.namespace.imports, vec!["baz"]);
     }
+
+    #[test]
+    fn test_invalidate_package() {
+        let temp_dir = TempDir::new().unwrap();
+        let lib = Library::new(vec![temp_dir.path().to_path_buf()]);
+
+        // Cached as absent
+        assert!(lib.get("mypkg").is_none());
+
+        // Install the package in the library
+        let pkg_dir = temp_dir.path().join("mypkg");
+        fs::create_dir(&pkg_dir).unwrap();
+        fs::write(
+            pkg_dir.join("DESCRIPTION"),
+            "Package: mypkg\nVersion: 1.0\n",
+        )
+        .unwrap();
+        fs::write(pkg_dir.join("NAMESPACE"), "export(foo)\n").unwrap();
+        assert!(lib.get("mypkg").is_none());
+
+        lib.invalidate("mypkg");
+        let pkg = lib.get("mypkg").unwrap();
+        assert_eq!(pkg.namespace.exports, vec!["foo"]);
+    }
 }
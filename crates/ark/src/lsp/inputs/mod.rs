@@ -5,6 +5,7 @@
 //
 //
 
+pub mod exports_index;
 pub mod library;
 pub mod package;
 pub mod package_description;
@@ -0,0 +1,265 @@
+//
+// jupyter.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use amalthea::wire::complete_reply::CompleteReply;
+use amalthea::wire::inspect_reply::InspectReply;
+use amalthea::wire::jupyter_message::Status;
+use serde_json::json;
+use tower_lsp::lsp_types::CompletionItem;
+use tower_lsp::lsp_types::CompletionItemKind;
+use tower_lsp::lsp_types::CompletionTextEdit;
+use tower_lsp::lsp_types::InsertTextFormat;
+use tree_sitter::Point;
+
+use crate::lsp::completions::provide_completions;
+use crate::lsp::document_context::DocumentContext;
+use crate::lsp::documents::Document;
+use crate::lsp::encoding::convert_position_to_point;
+use crate::lsp::hover::r_hover;
+use crate::lsp::state::WorldState;
+
+/// Completions for a Jupyter `complete_request`
+///
+/// The code is treated as a standalone document so that Jupyter frontends get
+/// the same completions as the LSP. The cursor position and the replaced
+/// range are in Unicode characters, as in the Jupyter protocol. Completion
+/// kinds are reported in the `_jupyter_types_experimental` metadata used by
+/// JupyterLab.
+///
+/// Must be within an `r_task()`.
+pub(crate) fn complete(code: &str, cursor_pos: u32) -> anyhow::Result<CompleteReply> {
+    let offset = char_to_byte(code, cursor_pos);
+    let document = Document::new(code, None);
+    let context = DocumentContext::new(&document, byte_to_point(code, offset), None);
+
+    let state = WorldState::default();
+    let items = provide_completions(&context, &state, None)?;
+
+    // Items replace the identifier before the cursor, unless they have their
+    // own text edit, e.g. for file paths
+    let start = items
+        .iter()
+        .find_map(|item| text_edit_start(item, &document, code))
+        .unwrap_or_else(|| identifier_start(code, offset));
+
+    let cursor_start = code[..start].chars().count() as u32;
+    let cursor_end = code[..offset].chars().count() as u32;
+
+    let mut matches = Vec::with_capacity(items.len());
+    let mut types = Vec::with_capacity(items.len());
+
+    for item in items {
+        let text = completion_text(&item);
+        types.push(json!({
+            "start": cursor_start,
+            "end": cursor_end,
+            "text": text,
+            "type": completion_type(item.kind),
+            "signature": item.detail.unwrap_or_default(),
+        }));
+        matches.push(text);
+    }
+
+    Ok(CompleteReply {
+        status: Status::Ok,
+        matches,
+        cursor_start,
+        cursor_end,
+        metadata: json!({ "_jupyter_types_experimental": types }),
+    })
+}
+
+/// Help for a Jupyter `inspect_request`, based on the LSP hover
+///
+/// Must be within an `r_task()`.
+pub(crate) fn inspect(code: &str, cursor_pos: u32) -> anyhow::Result<InspectReply> {
+    let offset = char_to_byte(code, cursor_pos);
+    let document = Document::new(code, None);
+    let context = DocumentContext::new(&document, byte_to_point(code, offset), None);
+
+    // Jupyter frontends don't send LSP settings, only builtin data-masking
+    // functions are known
+    let data = match r_hover(&context, &[])? {
+        Some(hover) => json!({
+            "text/plain": hover.value,
+            "text/markdown": hover.value,
+        }),
+        None => json!({}),
+    };
+
+    Ok(InspectReply {
+        status: Status::Ok,
+        found: data != json!({}),
+        data,
+        metadata: json!({}),
+    })
+}
+
+fn char_to_byte(code: &str, pos: u32) -> usize {
+    code.char_indices()
+        .nth(pos as usize)
+        .map(|(i, _)| i)
+        .unwrap_or(code.len())
+}
+
+fn byte_to_point(code: &str, offset: usize) -> Point {
+    let before = &code[..offset];
+    let row = before.matches('\n').count();
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1);
+    Point::new(row, column)
+}
+
+/// Start of the identifier that ends at `offset`
+fn identifier_start(code: &str, offset: usize) -> usize {
+    code[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphanumeric() || matches!(c, '.' | '_'))
+        .last()
+        .map_or(offset, |(i, _)| i)
+}
+
+fn text_edit_start(item: &CompletionItem, document: &Document, code: &str) -> Option<usize> {
+    let Some(CompletionTextEdit::Edit(edit)) = &item.text_edit else {
+        return None;
+    };
+    let point = convert_position_to_point(&document.contents, edit.range.start);
+
+    let line_start = code
+        .split_inclusive('\n')
+        .take(point.row)
+        .map(str::len)
+        .sum::<usize>();
+    Some((line_start + point.column).min(code.len()))
+}
+
+/// The text inserted by a completion item, with snippet placeholders
+/// replaced by their default text
+fn completion_text(item: &CompletionItem) -> String {
+    let text = match &item.text_edit {
+        Some(CompletionTextEdit::Edit(edit)) => edit.new_text.clone(),
+        Some(CompletionTextEdit::InsertAndReplace(edit)) => edit.new_text.clone(),
+        None => item
+            .insert_text
+            .clone()
+            .unwrap_or_else(|| item.label.clone()),
+    };
+
+    if item.insert_text_format == Some(InsertTextFormat::SNIPPET) {
+        snippet_text(&text)
+    } else {
+        text
+    }
+}
+
+/// Converts a snippet like `fn(${1:x}$0)` to `fn(x)`
+fn snippet_text(snippet: &str) -> String {
+    let mut out = String::with_capacity(snippet.len());
+    let mut chars = snippet.chars().peekable();
+    let mut depth = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    out.push(next);
+                }
+            },
+            '$' if chars.peek().is_some_and(|c| c.is_ascii_digit()) => {
+                while chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    chars.next();
+                }
+            },
+            '$' if chars.peek() == Some(&'{') => {
+                chars.next();
+                while chars.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    chars.next();
+                }
+                if chars.peek() == Some(&':') {
+                    chars.next();
+                }
+                depth += 1;
+            },
+            '}' if depth > 0 => depth -= 1,
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Completion types known to JupyterLab
+fn completion_type(kind: Option<CompletionItemKind>) -> &'static str {
+    match kind {
+        Some(CompletionItemKind::FUNCTION) | Some(CompletionItemKind::METHOD) => "function",
+        Some(CompletionItemKind::VARIABLE) | Some(CompletionItemKind::CONSTANT) => "instance",
+        Some(CompletionItemKind::FIELD) | Some(CompletionItemKind::PROPERTY) => "param",
+        Some(CompletionItemKind::MODULE) => "module",
+        Some(CompletionItemKind::KEYWORD) => "keyword",
+        Some(CompletionItemKind::CLASS) | Some(CompletionItemKind::STRUCT) => "class",
+        Some(CompletionItemKind::FILE) => "file",
+        Some(CompletionItemKind::FOLDER) => "path",
+        Some(CompletionItemKind::SNIPPET) => "snippet",
+        _ => "text",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lsp::jupyter::complete;
+    use crate::lsp::jupyter::inspect;
+    use crate::lsp::jupyter::snippet_text;
+    use crate::r_task;
+
+    #[test]
+    fn test_jupyter_snippet_text() {
+        assert_eq!(snippet_text("mean($0)"), "mean()");
+        assert_eq!(snippet_text("fn(${1:x}, ${2:y})$0"), "fn(x, y)");
+        assert_eq!(snippet_text("a \\$ b"), "a $ b");
+    }
+
+    #[test]
+    fn test_jupyter_complete() {
+        r_task(|| {
+            let code = "x <- as.charac";
+            let reply = complete(code, code.len() as u32).unwrap();
+
+            assert_eq!(reply.cursor_start, 5);
+            assert_eq!(reply.cursor_end, 14);
+            assert!(reply.matches.contains(&String::from("as.character()")));
+
+            let types = reply.metadata["_jupyter_types_experimental"]
+                .as_array()
+                .unwrap();
+            assert_eq!(types.len(), reply.matches.len());
+            assert!(types
+                .iter()
+                .any(|t| t["text"] == "as.character()" && t["type"] == "function"));
+
+            // Cursor positions are in characters
+            let code = "'é'; as.charac";
+            let reply = complete(code, code.chars().count() as u32).unwrap();
+            assert_eq!(reply.cursor_start, 5);
+            assert_eq!(reply.cursor_end, 14);
+        })
+    }
+
+    #[test]
+    fn test_jupyter_inspect() {
+        r_task(|| {
+            let reply = inspect("mean(x)", 2).unwrap();
+            assert!(reply.found);
+            assert!(reply.data["text/markdown"]
+                .as_str()
+                .unwrap()
+                .contains("mean"));
+
+            let reply = inspect("1 + 1", 0).unwrap();
+            assert!(!reply.found);
+        })
+    }
+}
@@ -0,0 +1,344 @@
+//
+// linked_editing.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use tower_lsp::lsp_types::LinkedEditingRanges;
+use tower_lsp::lsp_types::Range;
+use tree_sitter::Node;
+use tree_sitter::Point;
+
+use crate::lsp::documents::Document;
+use crate::lsp::encoding::convert_point_to_position;
+use crate::lsp::traits::rope::RopeExt;
+use crate::treesitter::NodeType;
+use crate::treesitter::NodeTypeExt;
+
+/// Characters that can be typed in a linked parameter name
+const IDENTIFIER_PATTERN: &str = r"[A-Za-z.][A-Za-z0-9._]*";
+
+/// Characters that can be typed in a linked quote
+const QUOTE_PATTERN: &str = r#"["']"#;
+
+/// Ranges that should be edited together with the one at `point`
+///
+/// - On the opening or closing quote of a string, the two quotes are linked
+///   so that switching from `'` to `"` updates both ends.
+///
+/// - On a function parameter, or on a use of a parameter in the function
+///   body, the parameter and all of its uses are linked. Nested functions
+///   that shadow the parameter with one of their own are left alone.
+pub(crate) fn linked_editing_ranges(
+    document: &Document,
+    point: Point,
+) -> anyhow::Result<Option<LinkedEditingRanges>> {
+    if let Some(ranges) = quote_ranges(document, point)? {
+        return Ok(Some(ranges));
+    }
+    parameter_ranges(document, point)
+}
+
+fn quote_ranges(document: &Document, point: Point) -> anyhow::Result<Option<LinkedEditingRanges>> {
+    let Some(string) = node_at_cursor(document, point, |node| node.is_string()) else {
+        return Ok(None);
+    };
+
+    let text = document.contents.node_slice(&string)?.to_string();
+
+    // Raw strings like `r"(...)"` don't start with a quote and unclosed
+    // strings don't end with one
+    let Some(quote) = text.chars().next() else {
+        return Ok(None);
+    };
+    if !matches!(quote, '"' | '\'') || text.len() < 2 || !text.ends_with(quote) {
+        return Ok(None);
+    }
+
+    let start = string.start_position();
+    let end = string.end_position();
+
+    let open = (start, Point::new(start.row, start.column + 1));
+    let close = (Point::new(end.row, end.column - 1), end);
+
+    // Only link the quotes when the cursor is on one of them
+    let on_quote = |(start, end): (Point, Point)| start <= point && point <= end;
+    if !on_quote(open) && !on_quote(close) {
+        return Ok(None);
+    }
+
+    let ranges = [open, close]
+        .into_iter()
+        .map(|(start, end)| {
+            Range::new(
+                convert_point_to_position(&document.contents, start),
+                convert_point_to_position(&document.contents, end),
+            )
+        })
+        .collect();
+
+    Ok(Some(LinkedEditingRanges {
+        ranges,
+        word_pattern: Some(String::from(QUOTE_PATTERN)),
+    }))
+}
+
+fn parameter_ranges(
+    document: &Document,
+    point: Point,
+) -> anyhow::Result<Option<LinkedEditingRanges>> {
+    let Some(identifier) = node_at_cursor(document, point, |node| node.is_identifier()) else {
+        return Ok(None);
+    };
+
+    if !is_parameter_name(&identifier) && !is_symbol_reference(&identifier) {
+        return Ok(None);
+    }
+
+    let name = document.contents.node_slice(&identifier)?.to_string();
+
+    let Some((function, parameter)) = find_binding_function(identifier, &name, document)? else {
+        return Ok(None);
+    };
+
+    let mut nodes = vec![parameter];
+
+    // Defaults of parameters may refer to other parameters, e.g. `function(x, n = length(x))`
+    if let Some(parameters) = function.child_by_field_name("parameters") {
+        let mut cursor = parameters.walk();
+        for parameter in parameters.children_by_field_name("parameter", &mut cursor) {
+            if let Some(default) = parameter.child_by_field_name("default") {
+                collect_uses(default, &name, document, &mut nodes)?;
+            }
+        }
+    }
+
+    if let Some(body) = function.child_by_field_name("body") {
+        collect_uses(body, &name, document, &mut nodes)?;
+    }
+
+    // Nothing to link if the parameter is unused
+    if nodes.len() < 2 {
+        return Ok(None);
+    }
+
+    let ranges = nodes
+        .into_iter()
+        .map(|node| {
+            Range::new(
+                convert_point_to_position(&document.contents, node.start_position()),
+                convert_point_to_position(&document.contents, node.end_position()),
+            )
+        })
+        .collect();
+
+    Ok(Some(LinkedEditingRanges {
+        ranges,
+        word_pattern: Some(String::from(IDENTIFIER_PATTERN)),
+    }))
+}
+
+/// Finds the node at the cursor that satisfies `predicate`, looking at the
+/// node before the cursor too since the cursor is typically placed at the
+/// end of the word being edited
+fn node_at_cursor<'tree>(
+    document: &'tree Document,
+    point: Point,
+    predicate: impl Fn(&Node) -> bool,
+) -> Option<Node<'tree>> {
+    let root = document.ast.root_node();
+
+    let mut points = vec![point];
+    if point.column > 0 {
+        points.push(Point::new(point.row, point.column - 1));
+    }
+
+    for point in points {
+        let mut node = root.descendant_for_point_range(point, point);
+
+        while let Some(candidate) = node {
+            if predicate(&candidate) {
+                return Some(candidate);
+            }
+            // Quotes and string contents are children of the string node
+            if !candidate.parent().is_some_and(|parent| parent.is_string()) {
+                break;
+            }
+            node = candidate.parent();
+        }
+    }
+
+    None
+}
+
+/// Walks up from `node` to find the innermost function that has a parameter
+/// called `name`. Returns the function and the name of its parameter.
+fn find_binding_function<'tree>(
+    node: Node<'tree>,
+    name: &str,
+    document: &Document,
+) -> anyhow::Result<Option<(Node<'tree>, Node<'tree>)>> {
+    let mut current = node.parent();
+
+    while let Some(node) = current {
+        if node.is_function_definition() {
+            if let Some(parameter) = function_parameter(&node, name, document)? {
+                return Ok(Some((node, parameter)));
+            }
+        }
+        current = node.parent();
+    }
+
+    Ok(None)
+}
+
+/// The name node of the parameter of `function` called `name`
+fn function_parameter<'tree>(
+    function: &Node<'tree>,
+    name: &str,
+    document: &Document,
+) -> anyhow::Result<Option<Node<'tree>>> {
+    let Some(parameters) = function.child_by_field_name("parameters") else {
+        return Ok(None);
+    };
+
+    let mut cursor = parameters.walk();
+    for parameter in parameters.children_by_field_name("parameter", &mut cursor) {
+        let Some(parameter_name) = parameter.child_by_field_name("name") else {
+            continue;
+        };
+        if document.contents.node_slice(&parameter_name)?.to_string() == name {
+            return Ok(Some(parameter_name));
+        }
+    }
+
+    Ok(None)
+}
+
+fn collect_uses<'tree>(
+    node: Node<'tree>,
+    name: &str,
+    document: &Document,
+    uses: &mut Vec<Node<'tree>>,
+) -> anyhow::Result<()> {
+    // A nested function with a parameter of the same name shadows ours
+    if node.is_function_definition() && function_parameter(&node, name, document)?.is_some() {
+        return Ok(());
+    }
+
+    if node.is_identifier() &&
+        is_symbol_reference(&node) &&
+        document.contents.node_slice(&node)?.to_string() == name
+    {
+        uses.push(node);
+        return Ok(());
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_uses(child, name, document, uses)?;
+    }
+
+    Ok(())
+}
+
+fn is_parameter_name(node: &Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    parent.node_type() == NodeType::Parameter && parent.child_by_field_name("name") == Some(*node)
+}
+
+/// Whether an identifier refers to a variable, as opposed to an argument
+/// name in a call, a parameter name, a `$` or `@` name, or a
+/// namespace-qualified name
+fn is_symbol_reference(node: &Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return true;
+    };
+
+    match parent.node_type() {
+        NodeType::Argument | NodeType::Parameter => {
+            parent.child_by_field_name("name") != Some(*node)
+        },
+        NodeType::ExtractOperator(_) => parent.child_by_field_name("rhs") != Some(*node),
+        NodeType::NamespaceOperator(_) => false,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tower_lsp::lsp_types::Position;
+    use tower_lsp::lsp_types::Range;
+
+    use crate::fixtures::point_from_cursor;
+    use crate::lsp::documents::Document;
+    use crate::lsp::linked_editing::linked_editing_ranges;
+
+    fn ranges(code: &str) -> Option<Vec<Range>> {
+        let (text, point) = point_from_cursor(code);
+        let document = Document::new(text.as_str(), None);
+        let ranges = linked_editing_ranges(&document, point).unwrap()?;
+        Some(ranges.ranges)
+    }
+
+    fn range(line: u32, start: u32, end: u32) -> Range {
+        Range::new(Position::new(line, start), Position::new(line, end))
+    }
+
+    #[test]
+    fn test_linked_editing_parameter() {
+        let expected = Some(vec![range(0, 9, 10), range(0, 12, 13), range(0, 16, 17)]);
+
+        // From the parameter
+        assert_eq!(ranges("function(x@) x + x"), expected);
+
+        // From a use
+        assert_eq!(ranges("function(x) x + @x"), expected);
+        assert_eq!(ranges("function(x) x@ + x"), expected);
+    }
+
+    #[test]
+    fn test_linked_editing_parameter_scope() {
+        // Defaults of other parameters are linked too
+        assert_eq!(
+            ranges("function(x@, n = x) n"),
+            Some(vec![range(0, 9, 10), range(0, 16, 17)])
+        );
+
+        // Shadowed in a nested function
+        assert_eq!(
+            ranges("function(x@) { x; function(x) x }"),
+            Some(vec![range(0, 9, 10), range(0, 14, 15)])
+        );
+
+        // Argument names, `$` names and namespaced names are not uses
+        assert_eq!(
+            ranges("function(x@) list(x = 1)$x + pkg::x + x"),
+            Some(vec![range(0, 9, 10), range(0, 37, 38)])
+        );
+
+        // Unused parameters and free variables have nothing to link
+        assert_eq!(ranges("function(x@) y"), None);
+        assert_eq!(ranges("function(x) y@"), None);
+        assert_eq!(ranges("y@ + 1"), None);
+    }
+
+    #[test]
+    fn test_linked_editing_quotes() {
+        let expected = Some(vec![range(0, 4, 5), range(0, 8, 9)]);
+
+        assert_eq!(ranges("foo(@'bar')"), expected);
+        assert_eq!(ranges("foo('@bar')"), expected);
+        assert_eq!(ranges("foo('bar@')"), expected);
+        assert_eq!(ranges("foo('bar'@)"), expected);
+
+        // Not on a quote
+        assert_eq!(ranges("foo('b@ar')"), None);
+
+        // Raw strings are left alone
+        assert_eq!(ranges("foo(@r\"(bar)\")"), None);
+    }
+}
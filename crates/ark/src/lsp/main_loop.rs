@@ -13,7 +13,10 @@ use std::pin::Pin;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::LazyLock;
+use std::sync::Mutex;
 use std::sync::RwLock;
+use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::anyhow;
 use futures::stream::FuturesUnordered;
@@ -39,10 +42,16 @@ use crate::lsp::diagnostics::generate_diagnostics;
 use crate::lsp::documents::Document;
 use crate::lsp::handlers;
 use crate::lsp::indexer;
+use crate::lsp::indexer_cache;
+use crate::lsp::inputs::exports_index::ExportsIndex;
 use crate::lsp::inputs::library::Library;
+use crate::lsp::package_files::description_diagnostics;
+use crate::lsp::package_files::PackageFile;
 use crate::lsp::state::WorldState;
 use crate::lsp::state_handlers;
 use crate::lsp::state_handlers::ConsoleInputs;
+use crate::lsp::telemetry::LatencyTimer;
+use crate::lsp::telemetry::RequestKind;
 
 pub(crate) type TokioUnboundedSender<T> = tokio::sync::mpsc::UnboundedSender<T>;
 pub(crate) type TokioUnboundedReceiver<T> = tokio::sync::mpsc::UnboundedReceiver<T>;
@@ -89,6 +98,7 @@ pub(crate) enum KernelNotification {
     DidChangeConsoleInputs(ConsoleInputs),
     DidOpenVirtualDocument(DidOpenVirtualDocumentParams),
     DidCloseVirtualDocument(DidCloseVirtualDocumentParams),
+    DidChangePackage(DidChangePackageParams),
 }
 
 /// A thin wrapper struct with a custom `Debug` method more appropriate for trace logs
@@ -107,6 +117,12 @@ pub(crate) struct DidCloseVirtualDocumentParams {
     pub(crate) uri: String,
 }
 
+/// A package was attached to or detached from the search path
+#[derive(Debug)]
+pub(crate) struct DidChangePackageParams {
+    pub(crate) name: String,
+}
+
 #[derive(Debug)]
 pub(crate) enum AuxiliaryEvent {
     Log(lsp_types::MessageType, String),
@@ -198,6 +214,7 @@ impl GlobalState {
 
             log::info!("Using library paths: {paths:#?}");
             let paths: Vec<PathBuf> = paths.into_iter().map(PathBuf::from).collect();
+            state.world.exports = ExportsIndex::spawn(paths.clone());
             state.world.library = Library::new(paths);
 
             Ok(())
@@ -289,8 +306,8 @@ impl GlobalState {
                         LspNotification::DidChangeTextDocument(params) => {
                             state_handlers::did_change(params, &mut self.lsp_state, &mut self.world)?;
                         },
-                        LspNotification::DidSaveTextDocument(_params) => {
-                            // Currently ignored
+                        LspNotification::DidSaveTextDocument(params) => {
+                            state_handlers::did_save(params, &mut self.world)?;
                         },
                         LspNotification::DidCloseTextDocument(params) => {
                             state_handlers::did_close(params, &mut self.lsp_state, &mut self.world)?;
@@ -310,6 +327,15 @@ impl GlobalState {
                 LspMessage::Request(request, tx) => {
                     lsp::log_info!("{request:#?}");
 
+                    // The client may have sent a `$/cancelRequest` while the
+                    // request was waiting in the queue. In that case tower-lsp
+                    // has dropped the receiving side of the response channel
+                    // and there is no point in handling the request.
+                    if tx.is_closed() {
+                        lsp::log_info!("Skipping cancelled request");
+                        return Ok(());
+                    }
+
                     match request {
                         LspRequest::Initialize(params) => {
                             respond(tx, || state_handlers::initialize(params, &mut self.lsp_state, &mut self.world), LspResponse::Initialize)?;
@@ -321,23 +347,30 @@ impl GlobalState {
                             respond(tx, || handlers::handle_document_symbol(params, &self.world), LspResponse::DocumentSymbol)?;
                         },
                         LspRequest::FoldingRange(params) => {
+                            let _timer = LatencyTimer::start(RequestKind::FoldingRange, &self.world.config.telemetry);
                             respond(tx, || handlers::handle_folding_range(params, &self.world), LspResponse::FoldingRange)?;
                         },
-                        LspRequest::ExecuteCommand(_params) => {
-                            let response = handlers::handle_execute_command(&self.client).await;
+                        LspRequest::ExecuteCommand(params) => {
+                            let response = handlers::handle_execute_command(params, &self.world, &self.client).await;
                             respond(tx, || response, LspResponse::ExecuteCommand)?;
                         },
                         LspRequest::Completion(params) => {
+                            let _timer = LatencyTimer::start(RequestKind::Completion, &self.world.config.telemetry);
                             respond(tx, || handlers::handle_completion(params, &self.world), LspResponse::Completion)?;
                         },
                         LspRequest::CompletionResolve(params) => {
-                            respond(tx, || handlers::handle_completion_resolve(params), LspResponse::CompletionResolve)?;
+                            Self::spawn_async_handler(tx, handlers::handle_completion_resolve(params), LspResponse::CompletionResolve);
                         },
                         LspRequest::Hover(params) => {
-                            respond(tx, || handlers::handle_hover(params, &self.world), LspResponse::Hover)?;
+                            let timer = LatencyTimer::start(RequestKind::Hover, &self.world.config.telemetry);
+                            let handler = handlers::handle_hover(params, self.world.clone());
+                            Self::spawn_async_handler(tx, async move {
+                                let _timer = timer;
+                                handler.await
+                            }, LspResponse::Hover);
                         },
                         LspRequest::SignatureHelp(params) => {
-                            respond(tx, || handlers::handle_signature_help(params, &self.world), LspResponse::SignatureHelp)?;
+                            Self::spawn_async_handler(tx, handlers::handle_signature_help(params, self.world.clone()), LspResponse::SignatureHelp);
                         },
                         LspRequest::GotoDefinition(params) => {
                             respond(tx, || handlers::handle_goto_definition(params, &self.world), LspResponse::GotoDefinition)?;
@@ -358,6 +391,9 @@ impl GlobalState {
                         LspRequest::HelpTopic(params) => {
                             respond(tx, || handlers::handle_help_topic(params, &self.world), LspResponse::HelpTopic)?;
                         },
+                        LspRequest::Breadcrumbs(params) => {
+                            respond(tx, || handlers::handle_breadcrumbs(params, &self.world), LspResponse::Breadcrumbs)?;
+                        },
                         LspRequest::OnTypeFormatting(params) => {
                             state_handlers::did_change_formatting_options(&params.text_document_position.text_document.uri, &params.options, &mut self.world);
                             respond(tx, || handlers::handle_indent(params, &self.world), LspResponse::OnTypeFormatting)?;
@@ -365,12 +401,21 @@ impl GlobalState {
                         LspRequest::CodeAction(params) => {
                             respond(tx, || handlers::handle_code_action(params, &self.lsp_state, &self.world), LspResponse::CodeAction)?;
                         },
+                        LspRequest::LinkedEditingRange(params) => {
+                            respond(tx, || handlers::handle_linked_editing_range(params, &self.world), LspResponse::LinkedEditingRange)?;
+                        },
+                        LspRequest::CodeLens(params) => {
+                            respond(tx, || handlers::handle_code_lens(params, &self.world), LspResponse::CodeLens)?;
+                        },
                         LspRequest::VirtualDocument(params) => {
                             respond(tx, || handlers::handle_virtual_document(params, &self.world), LspResponse::VirtualDocument)?;
                         },
                         LspRequest::InputBoundaries(params) => {
                             respond(tx, || handlers::handle_input_boundaries(params), LspResponse::InputBoundaries)?;
                         },
+                        LspRequest::Telemetry(params) => {
+                            respond(tx, || handlers::handle_telemetry(params, &self.world), LspResponse::Telemetry)?;
+                        },
                     };
                 },
             },
@@ -388,6 +433,9 @@ impl GlobalState {
                     KernelNotification::DidCloseVirtualDocument(params) => {
                         state_handlers::did_close_virtual_document(params, &mut self.world)?
                     }
+                    KernelNotification::DidChangePackage(params) => {
+                        state_handlers::did_change_package(params, &mut self.world)?
+                    }
                 }
             },
         }
@@ -422,6 +470,31 @@ impl GlobalState {
             respond(response_tx, || handler(), into_lsp_response).and(Ok(None))
         })
     }
+
+    /// Spawn async LSP request handler
+    ///
+    /// Use this for handlers that wait for R with `r_task_async()`. The main
+    /// loop keeps handling events while the R thread is busy, and the handler
+    /// responds from the spawned task once R is done. Handlers should work
+    /// from a clone of the world state taken when the request was received.
+    fn spawn_async_handler<T, Handler>(
+        response_tx: TokioUnboundedSender<RequestResponse>,
+        handler: Handler,
+        into_lsp_response: impl FnOnce(T) -> LspResponse + Send + 'static,
+    ) where
+        Handler: future::Future<Output = anyhow::Result<T>>,
+        Handler: Send + 'static,
+        T: Send + 'static,
+    {
+        let handle = tokio::spawn(async move {
+            let response = handler.await;
+            respond(response_tx, || response, into_lsp_response).and(Ok(None))
+        });
+
+        // Send the join handle to the auxiliary loop so it can log any errors
+        // or panics
+        send_auxiliary(AuxiliaryEvent::SpawnedTask(handle));
+    }
 }
 
 /// Respond to a request from the LSP
@@ -613,28 +686,32 @@ fn send_auxiliary(event: AuxiliaryEvent) {
 
 /// Send a message to the LSP client. This is non-blocking and treated on a
 /// latency-sensitive task.
+///
+/// Messages go through the kernel logger first, so they follow the level of
+/// the LSP module and are streamed to the log comm. Messages filtered out by
+/// the logger are not sent to the client either.
 pub(crate) fn log(level: lsp_types::MessageType, message: String) {
-    // We're not connected to an LSP client when running unit tests
-    if cfg!(test) {
+    let log_level = match level {
+        MessageType::ERROR => log::Level::Error,
+        MessageType::WARNING => log::Level::Warn,
+        _ => log::Level::Info,
+    };
+
+    if !log::log_enabled!(target: "ark::lsp", log_level) {
         return;
     }
+    log::log!(target: "ark::lsp", log_level, "{message}");
 
-    // Check that channel is still alive in case the LSP was closed.
-    // If closed, fallthrough.
-    if let Ok(_) = with_auxiliary_tx(|auxiliary_event_tx| {
-        auxiliary_event_tx.send(AuxiliaryEvent::Log(level, message.clone()))
-    }) {
+    // We're not connected to an LSP client when running unit tests
+    if cfg!(test) {
         return;
     }
 
-    // Log to the kernel as fallback
-    log::warn!("LSP channel is closed, redirecting messages to Jupyter kernel");
-
-    match level {
-        MessageType::ERROR => log::error!("{message}"),
-        MessageType::WARNING => log::warn!("{message}"),
-        _ => log::info!("{message}"),
-    };
+    // The channel is closed when the LSP was shut down, the message was
+    // still logged to the kernel
+    let _ = with_auxiliary_tx(|auxiliary_event_tx| {
+        auxiliary_event_tx.send(AuxiliaryEvent::Log(level, message))
+    });
 }
 
 /// Spawn a blocking task
@@ -683,6 +760,10 @@ impl std::fmt::Debug for TraceKernelNotification<'_> {
                 .debug_struct("DidCloseVirtualDocument")
                 .field("uri", &params.uri)
                 .finish(),
+            KernelNotification::DidChangePackage(params) => f
+                .debug_struct("DidChangePackage")
+                .field("name", &params.name)
+                .finish(),
         }
     }
 }
@@ -705,6 +786,7 @@ pub enum IndexerTask {
 pub(crate) struct RefreshDiagnosticsTask {
     uri: Url,
     state: WorldState,
+    generation: u64,
 }
 
 #[derive(Debug)]
@@ -742,6 +824,23 @@ static INDEXER_QUEUE: LazyLock<tokio::sync::mpsc::UnboundedSender<IndexerQueueTa
         tx
     });
 
+/// Delay between the last diagnostics request for a document and the
+/// corresponding diagnostics refresh
+const DIAGNOSTICS_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Maximum delay between the first diagnostics request for a document and the
+/// corresponding refresh, so that diagnostics are refreshed periodically even
+/// while the user is continuously typing
+const DIAGNOSTICS_MAX_DELAY: Duration = Duration::from_millis(1000);
+
+/// The latest diagnostics generation of each document
+///
+/// Incremented every time a diagnostics refresh is requested. A diagnostics
+/// task whose generation is older than the current one is outdated and is
+/// skipped when it is about to run.
+static DIAGNOSTICS_GENERATIONS: LazyLock<Mutex<HashMap<Url, u64>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
 /// Process indexer and diagnostics tasks
 ///
 /// Diagnostics need an up-to-date index to be accurate, so we synchronise
@@ -749,8 +848,10 @@ static INDEXER_QUEUE: LazyLock<tokio::sync::mpsc::UnboundedSender<IndexerQueueTa
 ///
 /// - We make sure to refresh diagnostics after every indexer updates.
 /// - Indexer tasks are batched together, same for diagnostics tasks.
-/// - Cancellation is simply dealt with by deduplicating tasks for the same URI,
-///   retaining only the most recent one.
+/// - Diagnostics tasks are debounced per document, see [DiagnosticsQueue].
+///   Cancellation is dealt with by deduplicating tasks for the same URI,
+///   retaining only the most recent one, and by skipping outdated tasks
+///   right before they run.
 ///
 /// Ideally we'd process indexer tasks continually without making them dependent
 /// on diagnostics tasks. The current setup blocks the queue loop while
@@ -764,11 +865,24 @@ static INDEXER_QUEUE: LazyLock<tokio::sync::mpsc::UnboundedSender<IndexerQueueTa
 /// natural way and they will be cancelled automatically as document updates
 /// arrive.
 async fn process_indexer_queue(mut rx: mpsc::UnboundedReceiver<IndexerQueueTask>) {
-    let mut diagnostics_batch = Vec::new();
+    let mut diagnostics_queue = DiagnosticsQueue::default();
     let mut indexer_batch = Vec::new();
 
-    while let Some(task) = rx.recv().await {
-        let mut tasks = vec![task];
+    loop {
+        // Wait for the next task, or until pending diagnostics are due
+        let task = match diagnostics_queue.next_deadline() {
+            Some(deadline) => match tokio::time::timeout_at(deadline.into(), rx.recv()).await {
+                Ok(Some(task)) => Some(task),
+                Ok(None) => return,
+                Err(_elapsed) => None,
+            },
+            None => match rx.recv().await {
+                Some(task) => Some(task),
+                None => return,
+            },
+        };
+
+        let mut tasks: Vec<IndexerQueueTask> = task.into_iter().collect();
 
         // Process diagnostics at least every 10 iterations if indexer tasks
         // keep coming in, so the user gets intermediate diagnostics refreshes
@@ -778,11 +892,12 @@ async fn process_indexer_queue(mut rx: mpsc::UnboundedReceiver<IndexerQueueTask>
             }
 
             // Separate by type
+            let now = Instant::now();
             for task in std::mem::take(&mut tasks) {
                 match task {
                     IndexerQueueTask::Indexer(indexer_task) => indexer_batch.push(indexer_task),
                     IndexerQueueTask::Diagnostics(diagnostic_task) => {
-                        diagnostics_batch.push(diagnostic_task)
+                        diagnostics_queue.push(diagnostic_task, now)
                     },
                 }
             }
@@ -797,7 +912,67 @@ async fn process_indexer_queue(mut rx: mpsc::UnboundedReceiver<IndexerQueueTask>
             process_indexer_batch(std::mem::take(&mut indexer_batch)).await;
         }
 
-        process_diagnostics_batch(std::mem::take(&mut diagnostics_batch)).await;
+        let batch = diagnostics_queue.take_due(Instant::now());
+        if !batch.is_empty() {
+            process_diagnostics_batch(batch).await;
+        }
+    }
+}
+
+/// Diagnostics tasks waiting for their debounce delay to elapse
+///
+/// A document's refresh is delayed until no new task for that document has
+/// come in for [DIAGNOSTICS_DEBOUNCE], or until [DIAGNOSTICS_MAX_DELAY] has
+/// elapsed since the first pending task. Only the most recent task of each
+/// document is retained.
+#[derive(Default)]
+struct DiagnosticsQueue {
+    pending: HashMap<Url, PendingDiagnostics>,
+}
+
+struct PendingDiagnostics {
+    task: RefreshDiagnosticsTask,
+    first: Instant,
+    last: Instant,
+}
+
+impl PendingDiagnostics {
+    fn deadline(&self) -> Instant {
+        std::cmp::min(
+            self.last + DIAGNOSTICS_DEBOUNCE,
+            self.first + DIAGNOSTICS_MAX_DELAY,
+        )
+    }
+}
+
+impl DiagnosticsQueue {
+    fn push(&mut self, task: RefreshDiagnosticsTask, now: Instant) {
+        let first = self
+            .pending
+            .get(&task.uri)
+            .map_or(now, |pending| pending.first);
+
+        self.pending.insert(task.uri.clone(), PendingDiagnostics {
+            task,
+            first,
+            last: now,
+        });
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        self.pending
+            .values()
+            .map(PendingDiagnostics::deadline)
+            .min()
+    }
+
+    fn take_due(&mut self, now: Instant) -> Vec<RefreshDiagnosticsTask> {
+        let (due, pending) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|(_uri, pending)| pending.deadline() <= now);
+        self.pending = pending;
+
+        due.into_values().map(|pending| pending.task).collect()
     }
 }
 
@@ -813,14 +988,17 @@ async fn process_indexer_batch(batch: Vec<IndexerTask>) {
             match &task {
                 IndexerTask::Create { uri } => {
                     indexer::create(uri)?;
+                    indexer_cache::created(uri);
                 },
 
                 IndexerTask::Update { uri, document } => {
                     indexer::update(&document, uri)?;
+                    indexer_cache::invalidated(uri);
                 },
 
                 IndexerTask::Delete { uri } => {
                     indexer::delete(uri)?;
+                    indexer_cache::invalidated(uri);
                 },
 
                 IndexerTask::Rename {
@@ -828,6 +1006,7 @@ async fn process_indexer_batch(batch: Vec<IndexerTask>) {
                     new: new_uri,
                 } => {
                     indexer::rename(old_uri, new_uri)?;
+                    indexer_cache::invalidated(old_uri);
                 },
             }
 
@@ -840,26 +1019,34 @@ async fn process_indexer_batch(batch: Vec<IndexerTask>) {
             continue;
         }
     }
+
+    indexer_cache::flush();
 }
 
 async fn process_diagnostics_batch(batch: Vec<RefreshDiagnosticsTask>) {
     tracing::trace!("Processing {n} diagnostic tasks", n = batch.len());
 
-    // Deduplicate tasks by keeping only the last one for each URI. We use a
-    // `HashMap` so only the last insertion is retained. This is effectively a
-    // way of cancelling diagnostics tasks for outdated documents.
-    let batch: std::collections::HashMap<_, _> = batch
-        .into_iter()
-        .map(|task| (task.uri, task.state))
-        .collect();
-
     let mut futures = FuturesUnordered::new();
 
-    for (uri, state) in batch {
+    for RefreshDiagnosticsTask {
+        uri,
+        state,
+        generation,
+    } in batch
+    {
         futures.push(task::spawn_blocking(move || {
             let _span = tracing::info_span!("diagnostics_refresh", uri = %uri).entered();
 
+            // A more recent refresh was requested for this document while we
+            // were waiting, no need to do stale work
+            if is_outdated_diagnostics(&uri, generation) {
+                tracing::trace!("Skipping outdated diagnostics task");
+                return None;
+            }
+
             if let Some(document) = state.documents.get(&uri) {
+                let _timer = LatencyTimer::start(RequestKind::Diagnostics, &state.config.telemetry);
+
                 // Special case testthat-specific behaviour. This is a simple
                 // stopgap approach that has some false positives (e.g. when we
                 // work on testthat itself the flag will always be true), but
@@ -868,7 +1055,12 @@ async fn process_diagnostics_batch(batch: Vec<RefreshDiagnosticsTask>) {
                     .components()
                     .any(|c| c.as_os_str() == "testthat");
 
-                let diagnostics = generate_diagnostics(document.clone(), state.clone(), testthat);
+                let diagnostics = match PackageFile::from_url(&uri) {
+                    Some(PackageFile::Description) => description_diagnostics(&document.contents),
+                    // Directives aren't evaluated as R code, don't lint them
+                    Some(PackageFile::Namespace) => vec![],
+                    None => generate_diagnostics(document.clone(), state.clone(), testthat),
+                };
                 Some(RefreshDiagnosticsResult {
                     uri,
                     diagnostics,
@@ -881,20 +1073,35 @@ async fn process_diagnostics_batch(batch: Vec<RefreshDiagnosticsTask>) {
     }
 
     // Publish results as they complete
-    while let Some(Ok(Some(result))) = futures.next().await {
-        publish_diagnostics(result.uri, result.diagnostics, result.version);
+    while let Some(result) = futures.next().await {
+        if let Ok(Some(result)) = result {
+            publish_diagnostics(result.uri, result.diagnostics, result.version);
+        }
     }
 }
 
+/// Documents without a generation were closed since the task was queued
+fn is_outdated_diagnostics(uri: &Url, generation: u64) -> bool {
+    let generations = DIAGNOSTICS_GENERATIONS.lock().unwrap();
+    generations
+        .get(uri)
+        .is_none_or(|current| *current > generation)
+}
+
 pub(crate) fn index_start(folders: Vec<String>, state: WorldState) {
     lsp::log_info!("Initial indexing started");
 
+    let exclude = &state.config.indexer.exclude;
+
     let uris: Vec<Url> = folders
-        .into_iter()
+        .iter()
         .flat_map(|folder| {
             walkdir::WalkDir::new(folder)
                 .into_iter()
-                .filter_entry(|e| indexer::filter_entry(e))
+                .filter_entry(move |e| {
+                    indexer::filter_entry(e) &&
+                        !indexer::is_excluded(e.path(), Path::new(folder), exclude)
+                })
                 .filter_map(|entry| {
                     let entry = match entry {
                         Ok(e) => e,
@@ -922,11 +1129,41 @@ pub(crate) fn index_start(folders: Vec<String>, state: WorldState) {
         })
         .collect();
 
+    // Files that haven't changed since the last session are restored from the
+    // on-disk cache instead of being indexed again
+    let uris = indexer_cache::start(&folders, uris);
+
     index_create(uris, state);
 }
 
+/// Reindexes the workspace after the indexer settings changed
+///
+/// Files that are now excluded are removed from the index and the remaining
+/// files of the workspace are indexed again.
+pub(crate) fn index_refresh(state: WorldState) {
+    let mut excluded = std::collections::HashSet::new();
+    indexer::map(|uri, _symbol, _entry| {
+        if is_index_excluded(uri, &state) {
+            excluded.insert(uri.clone());
+        }
+    });
+    index_delete(excluded.into_iter().collect(), state.clone());
+
+    let folders = state
+        .workspace
+        .folders
+        .iter()
+        .filter_map(|folder| folder.to_file_path().ok())
+        .filter_map(|folder| folder.to_str().map(String::from))
+        .collect();
+    index_start(folders, state);
+}
+
 pub(crate) fn index_create(uris: Vec<Url>, state: WorldState) {
     for uri in uris {
+        if is_index_excluded(&uri, &state) {
+            continue;
+        }
         INDEXER_QUEUE
             .send(IndexerQueueTask::Indexer(IndexerTask::Create { uri }))
             .unwrap_or_else(|err| crate::lsp::log_error!("Failed to queue index create: {err}"));
@@ -937,6 +1174,10 @@ pub(crate) fn index_create(uris: Vec<Url>, state: WorldState) {
 
 pub(crate) fn index_update(uris: Vec<Url>, state: WorldState) {
     for uri in uris {
+        if is_index_excluded(&uri, &state) {
+            continue;
+        }
+
         let document = match state.get_document(&uri) {
             Ok(doc) => doc.clone(),
             Err(err) => {
@@ -970,6 +1211,21 @@ pub(crate) fn index_delete(uris: Vec<Url>, state: WorldState) {
     diagnostics_refresh_all(state);
 }
 
+/// Whether `uri` matches the `positron.r.indexer.exclude` globs of one of
+/// the workspace folders
+fn is_index_excluded(uri: &Url, state: &WorldState) -> bool {
+    let Ok(path) = uri.to_file_path() else {
+        return false;
+    };
+
+    state
+        .workspace
+        .folders
+        .iter()
+        .filter_map(|folder| folder.to_file_path().ok())
+        .any(|folder| indexer::is_excluded(&path, &folder, &state.config.indexer.exclude))
+}
+
 pub(crate) fn index_rename(uris: Vec<(Url, Url)>, state: WorldState) {
     for (old, new) in uris {
         INDEXER_QUEUE
@@ -985,6 +1241,13 @@ pub(crate) fn index_rename(uris: Vec<(Url, Url)>, state: WorldState) {
     diagnostics_refresh_all(state);
 }
 
+/// Forget the diagnostics generation of a closed document so that the map of
+/// generations doesn't grow with every document ever opened. Pending
+/// diagnostics tasks for the document are outdated from then on.
+pub(crate) fn diagnostics_forget(uri: &Url) {
+    DIAGNOSTICS_GENERATIONS.lock().unwrap().remove(uri);
+}
+
 pub(crate) fn diagnostics_refresh_all(state: WorldState) {
     tracing::trace!(
         "Refreshing diagnostics for {n} documents",
@@ -992,11 +1255,110 @@ pub(crate) fn diagnostics_refresh_all(state: WorldState) {
     );
 
     for (uri, _document) in state.documents.iter() {
+        let generation = {
+            let mut generations = DIAGNOSTICS_GENERATIONS.lock().unwrap();
+            let generation = generations.entry(uri.clone()).or_default();
+            *generation += 1;
+            *generation
+        };
+
         INDEXER_QUEUE
             .send(IndexerQueueTask::Diagnostics(RefreshDiagnosticsTask {
                 uri: uri.clone(),
                 state: state.clone(),
+                generation,
             }))
             .unwrap_or_else(|err| lsp::log_error!("Failed to queue diagnostics refresh: {err}"));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use std::time::Instant;
+
+    use url::Url;
+
+    use crate::lsp::main_loop::diagnostics_forget;
+    use crate::lsp::main_loop::is_outdated_diagnostics;
+    use crate::lsp::main_loop::DiagnosticsQueue;
+    use crate::lsp::main_loop::RefreshDiagnosticsTask;
+    use crate::lsp::main_loop::DIAGNOSTICS_DEBOUNCE;
+    use crate::lsp::main_loop::DIAGNOSTICS_GENERATIONS;
+    use crate::lsp::main_loop::DIAGNOSTICS_MAX_DELAY;
+    use crate::lsp::state::WorldState;
+
+    fn task(uri: &Url, generation: u64) -> RefreshDiagnosticsTask {
+        RefreshDiagnosticsTask {
+            uri: uri.clone(),
+            state: WorldState::default(),
+            generation,
+        }
+    }
+
+    #[test]
+    fn test_diagnostics_queue_debounce() {
+        let foo = Url::parse("file:///foo.R").unwrap();
+        let bar = Url::parse("file:///bar.R").unwrap();
+
+        let mut queue = DiagnosticsQueue::default();
+        assert_eq!(queue.next_deadline(), None);
+
+        let start = Instant::now();
+        queue.push(task(&foo, 1), start);
+        queue.push(task(&bar, 1), start);
+
+        // A new request for `foo` delays its refresh and replaces the older task
+        let later = start + Duration::from_millis(100);
+        queue.push(task(&foo, 2), later);
+        assert_eq!(queue.next_deadline(), Some(start + DIAGNOSTICS_DEBOUNCE));
+
+        assert!(queue.take_due(later).is_empty());
+
+        let due = queue.take_due(start + DIAGNOSTICS_DEBOUNCE);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].uri, bar);
+
+        let due = queue.take_due(later + DIAGNOSTICS_DEBOUNCE);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].uri, foo);
+        assert_eq!(due[0].generation, 2);
+
+        assert_eq!(queue.next_deadline(), None);
+    }
+
+    #[test]
+    fn test_diagnostics_queue_max_delay() {
+        let uri = Url::parse("file:///foo.R").unwrap();
+
+        let mut queue = DiagnosticsQueue::default();
+        let start = Instant::now();
+
+        // Requests keep coming in faster than the debounce delay
+        let mut now = start;
+        while now < start + DIAGNOSTICS_MAX_DELAY {
+            queue.push(task(&uri, 1), now);
+            now += DIAGNOSTICS_DEBOUNCE / 2;
+        }
+
+        assert_eq!(queue.next_deadline(), Some(start + DIAGNOSTICS_MAX_DELAY));
+        assert_eq!(queue.take_due(start + DIAGNOSTICS_MAX_DELAY).len(), 1);
+    }
+
+    #[test]
+    fn test_diagnostics_forget() {
+        let uri = Url::parse("file:///closed.R").unwrap();
+
+        DIAGNOSTICS_GENERATIONS
+            .lock()
+            .unwrap()
+            .insert(uri.clone(), 2);
+        assert!(is_outdated_diagnostics(&uri, 1));
+        assert!(!is_outdated_diagnostics(&uri, 2));
+
+        // Closing the document prunes its generation and outdates pending tasks
+        diagnostics_forget(&uri);
+        assert!(!DIAGNOSTICS_GENERATIONS.lock().unwrap().contains_key(&uri));
+        assert!(is_outdated_diagnostics(&uri, 2));
+    }
+}
@@ -6,19 +6,21 @@
 //
 
 pub mod backend;
+pub mod breadcrumbs;
 pub mod capabilities;
 pub mod code_action;
+pub(crate) mod code_lens;
 pub mod comm;
 pub mod completions;
 mod config;
 mod declarations;
 pub mod definitions;
 pub mod diagnostics;
+pub mod diagnostics_spelling;
 pub mod diagnostics_syntax;
 pub mod document_context;
 pub mod documents;
 pub mod encoding;
-pub mod events;
 pub mod folding_range;
 pub mod handler;
 pub mod handlers;
@@ -27,11 +29,16 @@ pub mod help_topic;
 pub mod hover;
 pub mod indent;
 pub mod indexer;
+pub(crate) mod indexer_cache;
 pub mod input_boundaries;
 pub mod inputs;
+pub(crate) mod jupyter;
+pub(crate) mod linked_editing;
 pub mod main_loop;
 pub mod markdown;
 pub mod offset;
+pub(crate) mod package_files;
+pub mod r_markdown;
 pub mod references;
 pub mod selection_range;
 pub mod signature_help;
@@ -39,6 +46,7 @@ pub mod state;
 pub mod state_handlers;
 pub mod statement_range;
 pub mod symbols;
+pub(crate) mod telemetry;
 pub mod traits;
 pub mod util;
 
@@ -0,0 +1,537 @@
+//
+// package_files.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use ropey::Rope;
+use tower_lsp::lsp_types::Diagnostic;
+use tower_lsp::lsp_types::DiagnosticSeverity;
+use tower_lsp::lsp_types::MarkupContent;
+use tower_lsp::lsp_types::MarkupKind;
+use tower_lsp::lsp_types::Range;
+use tree_sitter::Point;
+use url::Url;
+
+use crate::lsp::encoding::convert_point_to_position;
+use crate::lsp::inputs::library::Library;
+
+/// Metadata files of R packages. They aren't R scripts, but package authors
+/// edit them all the time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum PackageFile {
+    /// The DESCRIPTION file, in DCF format
+    Description,
+    /// The NAMESPACE file, which is made of R calls
+    Namespace,
+}
+
+impl PackageFile {
+    pub(crate) fn from_url(uri: &Url) -> Option<Self> {
+        match uri.path().rsplit('/').next()? {
+            "DESCRIPTION" => Some(Self::Description),
+            "NAMESPACE" => Some(Self::Namespace),
+            _ => None,
+        }
+    }
+}
+
+/// Fields of DESCRIPTION files and their documentation, see the "Writing R
+/// Extensions" manual
+pub(crate) const DESCRIPTION_FIELDS: &[(&str, &str)] = &[
+    ("Package", "The name of the package."),
+    ("Type", "The type of the package, usually `Package`."),
+    (
+        "Title",
+        "A short description of the package, in title case and without a final period.",
+    ),
+    ("Version", "The version of the package, e.g. `1.0.0`."),
+    (
+        "Authors@R",
+        "The authors and maintainer of the package, as a call to `c()` of `person()` objects.",
+    ),
+    ("Author", "The authors of the package. Prefer `Authors@R`."),
+    (
+        "Maintainer",
+        "The maintainer of the package, as `Name <email>`. Prefer `Authors@R`.",
+    ),
+    (
+        "Description",
+        "A comprehensive description of what the package does, in one or more full sentences.",
+    ),
+    (
+        "License",
+        "The license of the package, e.g. `MIT + file LICENSE`.",
+    ),
+    ("URL", "URLs related to the package, separated by commas."),
+    ("BugReports", "The URL where bugs should be reported."),
+    (
+        "Depends",
+        "The minimum version of R and packages attached along with the package.",
+    ),
+    (
+        "Imports",
+        "Packages whose namespaces are used by the package.",
+    ),
+    (
+        "Suggests",
+        "Packages used in examples, tests, or vignettes, or that are used conditionally.",
+    ),
+    (
+        "Enhances",
+        "Packages enhanced by the package, e.g. with methods for their classes.",
+    ),
+    (
+        "LinkingTo",
+        "Packages whose C or C++ headers are used by the package.",
+    ),
+    (
+        "SystemRequirements",
+        "Dependencies external to R, e.g. system libraries.",
+    ),
+    (
+        "Encoding",
+        "The encoding of the package files, usually `UTF-8`.",
+    ),
+    (
+        "Language",
+        "The language of the documentation, e.g. `en-US`.",
+    ),
+    ("LazyData", "Whether datasets are lazy-loaded."),
+    ("Collate", "The order in which R files are loaded."),
+    (
+        "VignetteBuilder",
+        "The packages that build the vignettes, e.g. `knitr`.",
+    ),
+    (
+        "Roxygen",
+        "Options for roxygen2, e.g. `list(markdown = TRUE)`.",
+    ),
+    (
+        "RoxygenNote",
+        "The version of roxygen2 used to generate the documentation.",
+    ),
+    (
+        "Config/testthat/edition",
+        "The edition of testthat used by the tests.",
+    ),
+];
+
+/// Fields listing packages
+pub(crate) const DEPENDENCY_FIELDS: &[&str] =
+    &["Depends", "Imports", "Suggests", "Enhances", "LinkingTo"];
+
+static PACKAGE_NAME_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[A-Za-z][A-Za-z0-9.]*[A-Za-z0-9]$").unwrap());
+
+static VERSION_REQUIREMENT_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\(\s*(>=|>|==|<=|<|!=)\s*[0-9]+([.-][0-9]+)*\s*\)$").unwrap());
+
+/// Where the cursor is in a DESCRIPTION file
+#[derive(Debug, PartialEq)]
+pub(crate) enum DescriptionPosition {
+    /// On the name of a field, at the start of a line
+    FieldName,
+    /// In the value of `field`, possibly on a continuation line
+    FieldValue { field: String },
+}
+
+pub(crate) fn description_position(contents: &Rope, point: Point) -> Option<DescriptionPosition> {
+    let line = contents.get_line(point.row)?.to_string();
+    let before = line.get(..point.column)?;
+
+    if !line.starts_with(char::is_whitespace) {
+        return match before.split_once(':') {
+            Some((field, _)) => Some(DescriptionPosition::FieldValue {
+                field: field.trim().to_string(),
+            }),
+            None => Some(DescriptionPosition::FieldName),
+        };
+    }
+
+    // On a continuation line, the field is on the closest unindented line
+    for row in (0..point.row).rev() {
+        let line = contents.line(row).to_string();
+        if line.starts_with(char::is_whitespace) {
+            continue;
+        }
+        let (field, _) = line.split_once(':')?;
+        return Some(DescriptionPosition::FieldValue {
+            field: field.trim().to_string(),
+        });
+    }
+
+    None
+}
+
+/// The package name under the cursor in a dependency field, e.g. `dplyr` in
+/// `Imports: dplyr (>= 1.0.0)`
+pub(crate) fn description_dependency_at(contents: &Rope, point: Point) -> Option<String> {
+    let DescriptionPosition::FieldValue { field } = description_position(contents, point)? else {
+        return None;
+    };
+    if !DEPENDENCY_FIELDS.contains(&field.as_str()) {
+        return None;
+    }
+
+    let line = contents.get_line(point.row)?.to_string();
+
+    // Skip the field name
+    let value_start = match line.starts_with(char::is_whitespace) {
+        true => 0,
+        false => line.find(':')? + 1,
+    };
+
+    let is_name_char = |char: char| char.is_ascii_alphanumeric() || char == '.';
+
+    let start = line[..point.column]
+        .rfind(|char: char| !is_name_char(char))
+        .map(|index| index + 1)
+        .unwrap_or(0)
+        .max(value_start);
+    let end = line[point.column..]
+        .find(|char: char| !is_name_char(char))
+        .map(|index| point.column + index)
+        .unwrap_or(line.len());
+
+    let name = &line[start..end];
+    if !name.starts_with(|char: char| char.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    // Versions requirements are in parentheses
+    let before = &line[value_start..start];
+    if before.matches('(').count() > before.matches(')').count() {
+        return None;
+    }
+
+    Some(name.to_string())
+}
+
+/// Hover for DESCRIPTION files: documentation of field names, and the
+/// installed version of dependencies
+pub(crate) fn description_hover(
+    contents: &Rope,
+    point: Point,
+    library: &Library,
+) -> Option<MarkupContent> {
+    let markdown = match description_position(contents, point)? {
+        DescriptionPosition::FieldName => {
+            let line = contents.get_line(point.row)?.to_string();
+            let name = line.split(':').next()?.trim();
+            let (_, documentation) = DESCRIPTION_FIELDS
+                .iter()
+                .find(|(field, _)| *field == name)?;
+            format!("**{name}**\n\n{documentation}")
+        },
+
+        DescriptionPosition::FieldValue { .. } => {
+            let name = description_dependency_at(contents, point)?;
+
+            // Not a package
+            if name == "R" {
+                return None;
+            }
+
+            match library.get(&name) {
+                Some(package) => {
+                    let title = package.description.fields.get("Title").unwrap_or_default();
+                    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+                    let path = package.path.parent().unwrap_or(&package.path);
+
+                    format!(
+                        "**{name}** {version}\n\n{title}\n\nInstalled in `{path}`",
+                        version = package.description.version,
+                        path = path.display()
+                    )
+                },
+                None => format!("**{name}** is not installed"),
+            }
+        },
+    };
+
+    Some(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: markdown,
+    })
+}
+
+/// Diagnostics for malformed DESCRIPTION files: lines that aren't fields,
+/// duplicated fields, and invalid entries in dependency fields
+pub(crate) fn description_diagnostics(contents: &Rope) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+
+    let mut fields: HashSet<String> = HashSet::new();
+    let mut field: Option<String> = None;
+
+    for (row, line) in contents.lines().enumerate() {
+        let line = line.to_string();
+        let line = line.trim_end_matches(['\r', '\n']);
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        // Continuation lines
+        if line.starts_with(char::is_whitespace) {
+            match &field {
+                Some(field) if DEPENDENCY_FIELDS.contains(&field.as_str()) => {
+                    dependency_diagnostics(contents, row, line, 0, &mut diagnostics);
+                },
+                Some(_) => {},
+                None => diagnostics.push(description_diagnostic(
+                    contents,
+                    row,
+                    0,
+                    line.len(),
+                    String::from("Continuation line without a field."),
+                    DiagnosticSeverity::ERROR,
+                )),
+            }
+            continue;
+        }
+
+        let Some((name, _)) = line.split_once(':') else {
+            diagnostics.push(description_diagnostic(
+                contents,
+                row,
+                0,
+                line.len(),
+                String::from("Expected a field of the form `Name: value`."),
+                DiagnosticSeverity::ERROR,
+            ));
+            field = None;
+            continue;
+        };
+
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            diagnostics.push(description_diagnostic(
+                contents,
+                row,
+                0,
+                name.len(),
+                format!("Invalid field name `{name}`."),
+                DiagnosticSeverity::ERROR,
+            ));
+        } else if !fields.insert(name.to_string()) {
+            diagnostics.push(description_diagnostic(
+                contents,
+                row,
+                0,
+                name.len(),
+                format!("Duplicated field `{name}`."),
+                DiagnosticSeverity::WARNING,
+            ));
+        }
+
+        if DEPENDENCY_FIELDS.contains(&name) {
+            dependency_diagnostics(contents, row, line, name.len() + 1, &mut diagnostics);
+        }
+
+        field = Some(name.to_string());
+    }
+
+    diagnostics
+}
+
+/// Checks the comma-separated entries of a line of a dependency field,
+/// starting at byte `offset`. Entries are checked line by line, so entries
+/// that span several lines aren't supported.
+fn dependency_diagnostics(
+    contents: &Rope,
+    row: usize,
+    line: &str,
+    offset: usize,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut start = offset;
+
+    for entry in line[offset..].split(',') {
+        let entry_start = start + (entry.len() - entry.trim_start().len());
+        start += entry.len() + 1;
+
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+
+        let (name, requirement) = match entry.find('(') {
+            Some(index) => (entry[..index].trim_end(), Some(&entry[index..])),
+            None => (entry, None),
+        };
+
+        if !PACKAGE_NAME_RE.is_match(name) {
+            diagnostics.push(description_diagnostic(
+                contents,
+                row,
+                entry_start,
+                entry_start + name.len(),
+                format!("Invalid package name `{name}`."),
+                DiagnosticSeverity::ERROR,
+            ));
+            continue;
+        }
+
+        if let Some(requirement) = requirement {
+            if !VERSION_REQUIREMENT_RE.is_match(requirement) {
+                let requirement_start = entry_start + entry.len() - requirement.len();
+                diagnostics.push(description_diagnostic(
+                    contents,
+                    row,
+                    requirement_start,
+                    entry_start + entry.len(),
+                    format!(
+                        "Malformed version requirement `{requirement}`, expected e.g. `(>= 1.0.0)`."
+                    ),
+                    DiagnosticSeverity::ERROR,
+                ));
+            }
+        }
+    }
+}
+
+fn description_diagnostic(
+    contents: &Rope,
+    row: usize,
+    start: usize,
+    end: usize,
+    message: String,
+    severity: DiagnosticSeverity,
+) -> Diagnostic {
+    let range = Range::new(
+        convert_point_to_position(contents, Point::new(row, start)),
+        convert_point_to_position(contents, Point::new(row, end)),
+    );
+    let mut diagnostic = Diagnostic::new_simple(range, message);
+    diagnostic.severity = Some(severity);
+    diagnostic
+}
+
+#[cfg(test)]
+mod tests {
+    use ropey::Rope;
+    use tower_lsp::lsp_types::Position;
+    use tree_sitter::Point;
+    use url::Url;
+
+    use crate::lsp::package_files::description_dependency_at;
+    use crate::lsp::package_files::description_diagnostics;
+    use crate::lsp::package_files::description_position;
+    use crate::lsp::package_files::DescriptionPosition;
+    use crate::lsp::package_files::PackageFile;
+
+    const DESCRIPTION: &str = "Package: mypkg
+Version: 1.0.0
+Imports:
+    dplyr (>= 1.0.0),
+    rlang
+Suggests: testthat
+";
+
+    #[test]
+    fn test_package_file_from_url() {
+        let file = |path: &str| PackageFile::from_url(&Url::parse(path).unwrap());
+        assert_eq!(
+            file("file:///pkg/DESCRIPTION"),
+            Some(PackageFile::Description)
+        );
+        assert_eq!(file("file:///pkg/NAMESPACE"), Some(PackageFile::Namespace));
+        assert_eq!(file("file:///pkg/R/DESCRIPTION.R"), None);
+    }
+
+    #[test]
+    fn test_description_position() {
+        let contents = Rope::from_str(DESCRIPTION);
+
+        assert_eq!(
+            description_position(&contents, Point::new(0, 3)),
+            Some(DescriptionPosition::FieldName)
+        );
+        assert_eq!(
+            description_position(&contents, Point::new(1, 10)),
+            Some(DescriptionPosition::FieldValue {
+                field: String::from("Version")
+            })
+        );
+        assert_eq!(
+            description_position(&contents, Point::new(4, 6)),
+            Some(DescriptionPosition::FieldValue {
+                field: String::from("Imports")
+            })
+        );
+    }
+
+    #[test]
+    fn test_description_dependency_at() {
+        let contents = Rope::from_str(DESCRIPTION);
+
+        assert_eq!(
+            description_dependency_at(&contents, Point::new(3, 6)),
+            Some(String::from("dplyr"))
+        );
+        assert_eq!(
+            description_dependency_at(&contents, Point::new(4, 9)),
+            Some(String::from("rlang"))
+        );
+        assert_eq!(
+            description_dependency_at(&contents, Point::new(5, 12)),
+            Some(String::from("testthat"))
+        );
+
+        // Not on the version requirement or the field name
+        assert_eq!(
+            description_dependency_at(&contents, Point::new(3, 16)),
+            None
+        );
+        assert_eq!(description_dependency_at(&contents, Point::new(5, 3)), None);
+
+        // Not in a dependency field
+        assert_eq!(
+            description_dependency_at(&contents, Point::new(0, 10)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_description_diagnostics() {
+        let contents = Rope::from_str(
+            "Package: mypkg
+Version: 1.0.0
+not a field
+Version: 1.0.1
+Imports: dplyr (>= 1.0.0), 2bad,
+    rlang (> = 1.0)
+",
+        );
+
+        let diagnostics = description_diagnostics(&contents);
+        let messages: Vec<(Position, &str)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.range.start, diagnostic.message.as_str()))
+            .collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    Position::new(2, 0),
+                    "Expected a field of the form `Name: value`."
+                ),
+                (Position::new(3, 0), "Duplicated field `Version`."),
+                (Position::new(4, 27), "Invalid package name `2bad`."),
+                (
+                    Position::new(5, 10),
+                    "Malformed version requirement `(> = 1.0)`, expected e.g. `(>= 1.0.0)`."
+                ),
+            ]
+        );
+
+        // Well-formed files don't have diagnostics
+        assert!(description_diagnostics(&Rope::from_str(DESCRIPTION)).is_empty());
+    }
+}
@@ -0,0 +1,329 @@
+//
+// r_markdown.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use std::borrow::Cow;
+use std::sync::LazyLock;
+
+use regex::Regex;
+use ropey::Rope;
+use tree_sitter::Point;
+
+use crate::lsp::encoding::convert_lsp_range_to_tree_sitter_range;
+
+/// The host document of an R Markdown (`.Rmd`) or Quarto (`.qmd`) file
+///
+/// The R code of these documents lives in chunks. We expose that code to the
+/// rest of the LSP as a virtual R document (see [RMarkdown::virtual_contents()])
+/// where every line outside of an R chunk is blanked out. Lines are never
+/// added or removed, and the lines of R chunks are kept verbatim, so a
+/// position in the host document maps to the same position in the virtual
+/// document. Positions that are not inside an R chunk have no counterpart
+/// in the virtual document, see [RMarkdown::contains_point()].
+#[derive(Clone, Debug)]
+pub struct RMarkdown {
+    /// The contents of the host document, as sent by the client
+    pub contents: Rope,
+
+    /// The R chunks of the host document, in order
+    pub chunks: Vec<RChunk>,
+}
+
+/// The lines of R code in a chunk, excluding the fences
+#[derive(Clone, Debug, PartialEq)]
+pub struct RChunk {
+    /// First line of code, i.e. the line following the chunk header
+    pub start_line: usize,
+
+    /// Line of the closing fence, or the number of lines in the document
+    /// if the chunk is unclosed. Exclusive.
+    pub end_line: usize,
+}
+
+/// A code fence, e.g. ```` ```{r} ```` or ```` ``` ````
+#[derive(Debug, PartialEq)]
+pub struct ChunkFence {
+    /// Number of backticks or tildes
+    pub length: usize,
+
+    /// The engine of executable chunks, e.g. `r` for ```` ```{r label} ````
+    pub engine: Option<String>,
+
+    /// Whether the fence has an info string, e.g. `{r}` or `r`. Closing
+    /// fences never do.
+    pub has_info: bool,
+}
+
+impl RMarkdown {
+    pub fn new(contents: &str) -> Self {
+        let contents = Rope::from_str(contents);
+        let chunks = r_chunks(&contents);
+        Self { contents, chunks }
+    }
+
+    /// Applies a change sent by the client to the host document
+    pub fn update(&mut self, range: tower_lsp::lsp_types::Range, text: &str) {
+        let range = convert_lsp_range_to_tree_sitter_range(&self.contents, range);
+
+        let start_character = self.contents.byte_to_char(range.start_byte);
+        let old_end_character = self.contents.byte_to_char(range.end_byte);
+
+        self.contents.remove(start_character..old_end_character);
+        self.contents.insert(start_character, text);
+
+        self.chunks = r_chunks(&self.contents);
+    }
+
+    /// The R code of the document, with all other lines blanked out
+    pub fn virtual_contents(&self) -> String {
+        let mut out = String::with_capacity(self.contents.len_bytes());
+        let mut chunks = self.chunks.iter().peekable();
+
+        for (line_idx, line) in self.contents.lines().enumerate() {
+            while chunks.next_if(|chunk| chunk.end_line <= line_idx).is_some() {}
+
+            let in_chunk = chunks
+                .peek()
+                .is_some_and(|chunk| chunk.start_line <= line_idx);
+
+            let line: Cow<'_, str> = line.into();
+
+            if in_chunk {
+                out.push_str(&line);
+            } else {
+                out.push_str(line_ending(&line));
+            }
+        }
+
+        out
+    }
+
+    /// The R chunk that contains `line`, if any
+    pub fn chunk_at(&self, line: usize) -> Option<&RChunk> {
+        self.chunks
+            .iter()
+            .find(|chunk| chunk.start_line <= line && line < chunk.end_line)
+    }
+
+    /// Whether `point` lies within R code
+    ///
+    /// Since the host and virtual documents share positions, `point` can be
+    /// expressed in either.
+    pub fn contains_point(&self, point: Point) -> bool {
+        self.chunk_at(point.row).is_some()
+    }
+}
+
+fn line_ending(line: &str) -> &str {
+    if line.ends_with("\r\n") {
+        "\r\n"
+    } else if line.ends_with('\n') {
+        "\n"
+    } else {
+        ""
+    }
+}
+
+fn r_chunks(contents: &Rope) -> Vec<RChunk> {
+    let mut chunks = Vec::new();
+
+    // Start line and fence length of the current chunk, and whether it's R
+    let mut current: Option<(usize, usize, bool)> = None;
+
+    for (line_idx, line) in contents.lines().enumerate() {
+        let line: Cow<'_, str> = line.into();
+
+        let Some(fence) = parse_chunk_fence(&line) else {
+            continue;
+        };
+
+        match current {
+            Some((start_line, length, is_r)) => {
+                if fence.has_info || fence.length < length {
+                    continue;
+                }
+                if is_r {
+                    chunks.push(RChunk {
+                        start_line: start_line + 1,
+                        end_line: line_idx,
+                    });
+                }
+                current = None;
+            },
+            None => {
+                let is_r = fence
+                    .engine
+                    .as_deref()
+                    .is_some_and(|engine| engine.eq_ignore_ascii_case("r"));
+                current = Some((line_idx, fence.length, is_r));
+            },
+        }
+    }
+
+    // Unclosed chunks extend to the end of the document
+    if let Some((start_line, _, true)) = current {
+        chunks.push(RChunk {
+            start_line: start_line + 1,
+            end_line: contents.len_lines(),
+        });
+    }
+
+    chunks
+}
+
+static RE_CHUNK_FENCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(`{3,}|~{3,})\s*(?:\{\s*([A-Za-z]\w*)[^}]*\}|([A-Za-z]\w*))?\s*$").unwrap()
+});
+
+/// Parses a line as a code fence
+///
+/// Both executable chunks (```` ```{r} ````) and plain markdown code blocks
+/// (```` ```r ````) are recognised as fences, but only the former have an
+/// engine.
+pub fn parse_chunk_fence(line: &str) -> Option<ChunkFence> {
+    let captures = RE_CHUNK_FENCE.captures(line)?;
+    let length = captures.get(1)?.as_str().len();
+    let engine = captures.get(2).map(|engine| engine.as_str().to_string());
+    let has_info = engine.is_some() || captures.get(3).is_some();
+
+    Some(ChunkFence {
+        length,
+        engine,
+        has_info,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tree_sitter::Point;
+
+    use crate::lsp::r_markdown::parse_chunk_fence;
+    use crate::lsp::r_markdown::ChunkFence;
+    use crate::lsp::r_markdown::RChunk;
+    use crate::lsp::r_markdown::RMarkdown;
+
+    #[test]
+    fn test_parse_chunk_fence() {
+        assert_eq!(parse_chunk_fence("text"), None);
+        assert_eq!(parse_chunk_fence("``"), None);
+
+        assert_eq!(
+            parse_chunk_fence("```"),
+            Some(ChunkFence {
+                length: 3,
+                engine: None,
+                has_info: false
+            })
+        );
+        assert_eq!(
+            parse_chunk_fence("````  \n"),
+            Some(ChunkFence {
+                length: 4,
+                engine: None,
+                has_info: false
+            })
+        );
+        assert_eq!(
+            parse_chunk_fence("```{r}"),
+            Some(ChunkFence {
+                length: 3,
+                engine: Some(String::from("r")),
+                has_info: true
+            })
+        );
+        assert_eq!(
+            parse_chunk_fence("```{r label, echo=FALSE}"),
+            Some(ChunkFence {
+                length: 3,
+                engine: Some(String::from("r")),
+                has_info: true
+            })
+        );
+        assert_eq!(
+            parse_chunk_fence("```{python}"),
+            Some(ChunkFence {
+                length: 3,
+                engine: Some(String::from("python")),
+                has_info: true
+            })
+        );
+
+        // Not executed, so no engine
+        assert_eq!(
+            parse_chunk_fence("```r"),
+            Some(ChunkFence {
+                length: 3,
+                engine: None,
+                has_info: true
+            })
+        );
+    }
+
+    #[test]
+    fn test_r_markdown_chunks() {
+        let host = RMarkdown::new(
+            "# Title
+
+```{r}
+x <- 1
+```
+
+```{python}
+y = 1
+```
+
+````{r}
+```
+z
+````
+",
+        );
+
+        assert_eq!(host.chunks, vec![
+            RChunk {
+                start_line: 3,
+                end_line: 4
+            },
+            RChunk {
+                start_line: 11,
+                end_line: 13
+            }
+        ]);
+
+        assert!(!host.contains_point(Point::new(0, 0)));
+        assert!(!host.contains_point(Point::new(2, 0)));
+        assert!(host.contains_point(Point::new(3, 2)));
+        assert!(!host.contains_point(Point::new(4, 0)));
+        assert!(!host.contains_point(Point::new(7, 0)));
+        assert!(host.contains_point(Point::new(11, 0)));
+        assert!(host.contains_point(Point::new(12, 0)));
+        assert!(!host.contains_point(Point::new(13, 0)));
+    }
+
+    #[test]
+    fn test_r_markdown_virtual_contents() {
+        let host = RMarkdown::new("# Title\n\n```{r}\nx <- 1\n```\ntext\n```{r}\ny");
+        assert_eq!(host.virtual_contents(), "\n\n\nx <- 1\n\n\n\ny");
+    }
+
+    #[test]
+    fn test_r_markdown_update() {
+        let mut host = RMarkdown::new("text\n```{r}\nx\n```\n");
+
+        // Close the chunk earlier
+        let range = tower_lsp::lsp_types::Range {
+            start: tower_lsp::lsp_types::Position::new(2, 0),
+            end: tower_lsp::lsp_types::Position::new(2, 0),
+        };
+        host.update(range, "y\n```\n");
+
+        assert_eq!(host.chunks, vec![RChunk {
+            start_line: 2,
+            end_line: 3
+        }]);
+        assert_eq!(host.virtual_contents(), "\n\ny\n\n\n\n");
+    }
+}
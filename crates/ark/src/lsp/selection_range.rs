@@ -54,20 +54,51 @@ fn selection_range_one(tree: &Tree, point: Point) -> Option<SelectionRange> {
 }
 
 fn selection_range_build(node: Node) -> SelectionRange {
-    let range = range_for_node(node);
+    // Collect the chain of ranges from the innermost node outwards. Each node
+    // can contribute more than one range, e.g. the inside of a block followed
+    // by the block itself, and knows which `child` we are coming from.
+    let mut ranges: Vec<Range> = Vec::new();
+    let mut child: Option<Node> = None;
+    let mut current = Some(node);
+
+    while let Some(node) = current {
+        ranges.extend(ranges_for_node(node, child));
+        child = Some(node);
+        current = node.parent();
+    }
 
-    let parent = node.parent().and_then(|parent| {
-        let selection = selection_range_build(parent);
-        Some(Box::new(selection))
-    });
+    // Build the nested structure from the outermost range inwards
+    let mut ranges = ranges.into_iter().rev();
 
-    SelectionRange { range, parent }
+    let Some(range) = ranges.next() else {
+        return SelectionRange {
+            range: node.range(),
+            parent: None,
+        };
+    };
+
+    let mut selection = SelectionRange {
+        range,
+        parent: None,
+    };
+
+    for range in ranges {
+        selection = SelectionRange {
+            range,
+            parent: Some(Box::new(selection)),
+        };
+    }
+
+    selection
 }
 
-fn range_for_node(node: Node) -> Range {
+fn ranges_for_node(node: Node, child: Option<Node>) -> Vec<Range> {
     match node.node_type() {
-        NodeType::Arguments => range_for_arguments(node),
-        _ => range_default(node),
+        NodeType::Arguments => vec![range_for_arguments(node)],
+        NodeType::BracedExpression | NodeType::ParenthesizedExpression => {
+            ranges_for_block(node, child)
+        },
+        _ => vec![range_default(node)],
     }
 }
 
@@ -78,25 +109,64 @@ fn range_for_node(node: Node) -> Range {
 //
 // This also applies to subset and subset2, i.e. `[a, b, c]` and `[[a, b, c]]`.
 fn range_for_arguments(node: Node) -> Range {
-    let Some(open) = node.child_by_field_name("open") else {
-        return node.range();
+    range_inside_delimiters(node).unwrap_or_else(|| node.range())
+}
+
+// For `{ }` blocks and `( )` groups we first select the contents, from the
+// start of the first expression to the end of the last one, and then the
+// whole block including its delimiters. The contents step is only added when
+// we are coming from a child, because otherwise (i.e. the cursor is on the
+// delimiters themselves) it would not contain the cursor.
+fn ranges_for_block(node: Node, child: Option<Node>) -> Vec<Range> {
+    let outer = range_default(node);
+
+    let Some(child) = child else {
+        return vec![outer];
     };
-    let Some(close) = node.child_by_field_name("close") else {
-        return node.range();
+
+    let Some(inner) = range_of_contents(node) else {
+        return vec![outer];
     };
 
+    if inner == child.range() || inner == outer {
+        return vec![outer];
+    }
+
+    vec![inner, outer]
+}
+
+fn range_of_contents(node: Node) -> Option<Range> {
+    let mut cursor = node.walk();
+    let mut children = node.named_children(&mut cursor);
+
+    let first = children.next()?;
+    let last = children.last().unwrap_or(first);
+
+    Some(Range {
+        start_byte: first.start_byte(),
+        end_byte: last.end_byte(),
+        start_point: first.start_position(),
+        end_point: last.end_position(),
+    })
+}
+
+// `node` must have `"open"` and `"close"` fields
+fn range_inside_delimiters(node: Node) -> Option<Range> {
+    let open = node.child_by_field_name("open")?;
+    let close = node.child_by_field_name("close")?;
+
     let start_byte = open.end_byte();
     let start_point = open.end_position();
 
     let end_byte = close.start_byte();
     let end_point = close.start_position();
 
-    Range {
+    Some(Range {
         start_byte,
         end_byte,
         start_point,
         end_point,
-    }
+    })
 }
 
 fn range_default(node: Node) -> Range {
@@ -447,4 +517,93 @@ p@kg::fn(a)
         assert_eq!(selection.range.start_point, Point::new(1, 0));
         assert_eq!(selection.range.end_point, Point::new(1, 10));
     }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_selection_range_block_contents() {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_r::LANGUAGE.into())
+            .unwrap();
+
+        let text = "
+{
+  @x <- 1
+  y
+}
+";
+        let (text, point) = point_from_cursor(text);
+        let tree = parser.parse(text, None).unwrap();
+        let points = Vec::from([point]);
+        let selections = selection_range(&tree, points).unwrap();
+
+        // `<<x>>` `identifier` node
+        let selection = selections.get(0).unwrap();
+        assert_eq!(selection.range.start_point, Point::new(2, 2));
+        assert_eq!(selection.range.end_point, Point::new(2, 3));
+
+        // `<<x <- 1>>` assignment
+        let selection = selection.parent.as_ref().unwrap();
+        assert_eq!(selection.range.start_point, Point::new(2, 2));
+        assert_eq!(selection.range.end_point, Point::new(2, 8));
+
+        // Contents of the block, without the braces
+        let selection = selection.parent.as_ref().unwrap();
+        assert_eq!(selection.range.start_point, Point::new(2, 2));
+        assert_eq!(selection.range.end_point, Point::new(3, 3));
+
+        // The whole block
+        let selection = selection.parent.as_ref().unwrap();
+        assert_eq!(selection.range.start_point, Point::new(1, 0));
+        assert_eq!(selection.range.end_point, Point::new(4, 1));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn test_selection_range_pipe_chain() {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_r::LANGUAGE.into())
+            .unwrap();
+
+        let text = "
+x |>
+  f(@a) |>
+  g()
+";
+        let (text, point) = point_from_cursor(text);
+        let tree = parser.parse(text, None).unwrap();
+        let points = Vec::from([point]);
+        let selections = selection_range(&tree, points).unwrap();
+
+        // `<<a>>` `identifier` node
+        let selection = selections.get(0).unwrap();
+        assert_eq!(selection.range.start_point, Point::new(2, 4));
+        assert_eq!(selection.range.end_point, Point::new(2, 5));
+
+        // `<<a>>` `argument` node
+        let selection = selection.parent.as_ref().unwrap();
+        assert_eq!(selection.range.start_point, Point::new(2, 4));
+        assert_eq!(selection.range.end_point, Point::new(2, 5));
+
+        // `(<<a>>)` arguments
+        let selection = selection.parent.as_ref().unwrap();
+        assert_eq!(selection.range.start_point, Point::new(2, 4));
+        assert_eq!(selection.range.end_point, Point::new(2, 5));
+
+        // `<<f(a)>>` call
+        let selection = selection.parent.as_ref().unwrap();
+        assert_eq!(selection.range.start_point, Point::new(2, 2));
+        assert_eq!(selection.range.end_point, Point::new(2, 6));
+
+        // `<<x |> f(a)>>` first link of the chain
+        let selection = selection.parent.as_ref().unwrap();
+        assert_eq!(selection.range.start_point, Point::new(1, 0));
+        assert_eq!(selection.range.end_point, Point::new(2, 6));
+
+        // The whole pipe chain
+        let selection = selection.parent.as_ref().unwrap();
+        assert_eq!(selection.range.start_point, Point::new(1, 0));
+        assert_eq!(selection.range.end_point, Point::new(3, 5));
+    }
 }
@@ -5,6 +5,9 @@
 //
 //
 
+use std::collections::HashMap;
+use std::sync::Mutex;
+
 use harp::eval::RParseEvalOptions;
 use harp::object::*;
 use harp::r_null;
@@ -17,9 +20,11 @@ use harp::utils::sym_quote_invalid;
 use harp::RSymbol;
 use libr::*;
 use log::info;
+use once_cell::sync::Lazy;
 use stdext::unwrap;
 use stdext::unwrap::IntoResult;
 use tower_lsp::lsp_types::Documentation;
+use tower_lsp::lsp_types::MarkupContent;
 use tower_lsp::lsp_types::ParameterInformation;
 use tower_lsp::lsp_types::ParameterLabel;
 use tower_lsp::lsp_types::SignatureHelp;
@@ -27,11 +32,14 @@ use tower_lsp::lsp_types::SignatureInformation;
 use tree_sitter::Node;
 use tree_sitter::Point;
 
+use crate::events::EVENTS;
+use crate::lsp::completions::COMPLETION_EVAL_TIMEOUT;
 use crate::lsp::document_context::DocumentContext;
 use crate::lsp::help::RHtmlHelp;
 use crate::lsp::traits::node::NodeExt;
 use crate::lsp::traits::point::PointExt;
 use crate::lsp::traits::rope::RopeExt;
+use crate::thread::RThreadSafe;
 use crate::treesitter::NodeType;
 use crate::treesitter::NodeTypeExt;
 
@@ -103,6 +111,12 @@ pub(crate) fn r_signature_help(context: &DocumentContext) -> anyhow::Result<Opti
     let mut offset: Option<u32> = None;
 
     let call = loop {
+        // If we are declaring the parameters of a function, e.g. `\(x, y)` in
+        // `map(xs, \(x, y) x)`, we aren't supplying arguments to a call.
+        if parent.node_type() == NodeType::Parameters {
+            return Ok(None);
+        }
+
         // If we found an 'arguments' node, then use that to infer the current offset.
         if parent.node_type() == NodeType::Arguments {
             // If the cursor lies upon a named argument, use that as an override.
@@ -157,6 +171,13 @@ pub(crate) fn r_signature_help(context: &DocumentContext) -> anyhow::Result<Opti
         return Ok(None);
     }
 
+    // If the call is the right-hand side of a pipe, the left-hand side is
+    // implicitly supplied as the first unnamed argument, e.g. `x |> f(@)`
+    // is really `f(x, @)`.
+    if is_piped_call(&call, context)? {
+        num_unnamed_arguments += 1;
+    }
+
     // Get the left-hand side of the call.
     let callee = unwrap!(call.child(0), None => {
         return Ok(None);
@@ -168,8 +189,13 @@ pub(crate) fn r_signature_help(context: &DocumentContext) -> anyhow::Result<Opti
     // Try to figure out what R object it's associated with.
     let code = context.document.contents.node_slice(&callee)?.to_string();
 
+    // Anonymous functions called directly, e.g. `(\(x, y) x + y)(@)`, are safe to
+    // evaluate as this only creates the closure
+    let forbid_function_calls = !is_function_definition(callee);
+
     let object = harp::parse_eval(code.as_str(), RParseEvalOptions {
-        forbid_function_calls: true,
+        forbid_function_calls,
+        timeout: Some(COMPLETION_EVAL_TIMEOUT),
         ..Default::default()
     });
 
@@ -200,22 +226,8 @@ pub(crate) fn r_signature_help(context: &DocumentContext) -> anyhow::Result<Opti
         return Ok(None);
     }
 
-    // Get the formal parameter names associated with this function.
-    let formals = r_formals(*object)?;
-
-    // Get the help documentation associated with this function.
-    let help = if callee.is_namespace_operator() {
-        let package = callee.child_by_field_name("lhs").into_result()?;
-        let package = context.document.contents.node_slice(&package)?.to_string();
-
-        let name = callee.child_by_field_name("rhs").into_result()?;
-        let name = context.document.contents.node_slice(&name)?.to_string();
-
-        RHtmlHelp::from_function(name.as_str(), Some(package.as_str()))
-    } else {
-        let name = context.document.contents.node_slice(&callee)?.to_string();
-        RHtmlHelp::from_function(name.as_str(), None)
-    };
+    // Get the formal parameters of this function, along with their documentation.
+    let formals = signature_parameters(object, &callee, &code, context)?;
 
     // The signature label. We generate this as we walk through the
     // parameters, so we can more easily record offsets.
@@ -226,40 +238,28 @@ pub(crate) fn r_signature_help(context: &DocumentContext) -> anyhow::Result<Opti
     // Get the available parameters.
     let mut parameters = vec![];
 
-    // Iterate over the documentation for each parameter, and add the relevant information.
     for (index, argument) in formals.iter().enumerate() {
-        // Get argument components.
-        let argument_name = &argument.name;
-        let argument_value = &argument.value;
-        let argument_label = argument_label(argument_name.clone(), argument_value.sexp);
-
-        // Compute signature offsets.
-        let start = label.len() as u32;
-        let end = start + argument_label.len() as u32;
+        // Compute signature offsets, in UTF-16 code units like LSP positions.
+        let start = label.encode_utf16().count() as u32;
+        let end = start + argument.label.encode_utf16().count() as u32;
 
         // Add the argument label to the overall label.
-        label.push_str(argument_label.as_str());
+        label.push_str(argument.label.as_str());
         label.push_str(", ");
 
         // If we had an explicit name, and this name matches the argument,
         // then update the offset now.
-        if active_argument.as_ref() == Some(argument_name) {
+        if active_argument.as_ref() == Some(&argument.name) {
             offset = Some(index as u32);
         }
 
-        // Get documentation, if any.
-        let mut documentation = None;
-        if let Ok(Some(ref help)) = help {
-            let markup = help.parameter(argument_name);
-            if let Ok(Some(markup)) = markup {
-                documentation = Some(Documentation::MarkupContent(markup));
-            }
-        }
-
         // Add the new parameter.
         parameters.push(ParameterInformation {
             label: ParameterLabel::LabelOffsets([start, end]),
-            documentation,
+            documentation: argument
+                .documentation
+                .clone()
+                .map(Documentation::MarkupContent),
         });
     }
 
@@ -316,6 +316,172 @@ pub(crate) fn r_signature_help(context: &DocumentContext) -> anyhow::Result<Opti
     Ok(Some(help))
 }
 
+/// A formal parameter of a function, as shown in signature help
+#[derive(Clone, Debug)]
+struct SignatureParameter {
+    name: String,
+    label: String,
+    documentation: Option<MarkupContent>,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct SignatureKey {
+    /// The code of the callee, which determines the help topic
+    callee: String,
+
+    /// The address of the function
+    function: usize,
+}
+
+struct CachedSignature {
+    /// Keeps the function alive so that its address isn't reused for another
+    /// function while it is in the cache
+    _function: RThreadSafe<RObject>,
+    parameters: Vec<SignatureParameter>,
+}
+
+/// Parameters of the functions for which signature help was requested, keyed
+/// by function identity. Signature help is requested on every keystroke
+/// inside a call, and looking up the help page of the function is expensive.
+///
+/// The same code may refer to different functions once packages are loaded,
+/// attached, or detached, or once the user assigns in the global environment
+/// from the console, so the cache is cleared on these events.
+static SIGNATURE_CACHE: Lazy<Mutex<HashMap<SignatureKey, CachedSignature>>> = Lazy::new(|| {
+    EVENTS.after_eval.listen(|_| clear_signature_cache());
+    EVENTS.package_load.listen(|_| clear_signature_cache());
+    EVENTS.package_unload.listen(|_| clear_signature_cache());
+    EVENTS.package_attach.listen(|_| clear_signature_cache());
+    EVENTS.package_detach.listen(|_| clear_signature_cache());
+    Mutex::new(HashMap::new())
+});
+
+/// The cache is cleared rather than pruned once it reaches this size
+const SIGNATURE_CACHE_SIZE: usize = 256;
+
+fn clear_signature_cache() {
+    SIGNATURE_CACHE.lock().unwrap().clear();
+}
+
+/// Get the parameters of `function`, called as `code`, from the cache or
+/// compute them from its formals and help page.
+///
+/// SAFETY: Requires access to the R runtime.
+fn signature_parameters(
+    function: RObject,
+    callee: &Node,
+    code: &str,
+    context: &DocumentContext,
+) -> anyhow::Result<Vec<SignatureParameter>> {
+    let key = SignatureKey {
+        callee: code.to_string(),
+        function: function.sexp as usize,
+    };
+
+    if let Some(cached) = SIGNATURE_CACHE.lock().unwrap().get(&key) {
+        return Ok(cached.parameters.clone());
+    }
+
+    // Get the formal parameter names associated with this function.
+    let formals = r_formals(function.sexp)?;
+
+    // Get the help documentation associated with this function.
+    let help = if callee.is_namespace_operator() {
+        let package = callee.child_by_field_name("lhs").into_result()?;
+        let package = context.document.contents.node_slice(&package)?.to_string();
+
+        let name = callee.child_by_field_name("rhs").into_result()?;
+        let name = context.document.contents.node_slice(&name)?.to_string();
+
+        RHtmlHelp::from_function(name.as_str(), Some(package.as_str()))
+    } else {
+        RHtmlHelp::from_function(code, None)
+    };
+
+    let parameters: Vec<SignatureParameter> = formals
+        .iter()
+        .map(|argument| {
+            // Get documentation, if any.
+            let documentation = match help {
+                Ok(Some(ref help)) => help.parameter(&argument.name).ok().flatten(),
+                _ => None,
+            };
+
+            SignatureParameter {
+                name: argument.name.clone(),
+                label: argument_label(argument.name.clone(), argument.value.sexp),
+                documentation,
+            }
+        })
+        .collect();
+
+    let mut cache = SIGNATURE_CACHE.lock().unwrap();
+    if cache.len() >= SIGNATURE_CACHE_SIZE {
+        cache.clear();
+    }
+    cache.insert(key, CachedSignature {
+        _function: RThreadSafe::new(function),
+        parameters: parameters.clone(),
+    });
+
+    Ok(parameters)
+}
+
+/// Is `call` the right-hand side of a `|>` or `%>%` pipe?
+///
+/// Calls that use a placeholder, i.e. `_` for the native pipe and `.` for the
+/// magrittr pipe, receive the left-hand side through that argument instead
+/// and are not considered piped.
+fn is_piped_call(call: &Node, context: &DocumentContext) -> anyhow::Result<bool> {
+    let contents = &context.document.contents;
+
+    let Some(pipe) = call.parent() else {
+        return Ok(false);
+    };
+    if !pipe.is_pipe_operator(contents)? {
+        return Ok(false);
+    }
+    if pipe.child_by_field_name("rhs") != Some(*call) {
+        return Ok(false);
+    }
+
+    let placeholder = if pipe.is_native_pipe_operator() {
+        "_"
+    } else {
+        "."
+    };
+
+    let Some(arguments) = call.child_by_field_name("arguments") else {
+        return Ok(true);
+    };
+
+    let mut cursor = arguments.walk();
+    for argument in arguments.children_by_field_name("argument", &mut cursor) {
+        let Some(value) = argument.child_by_field_name("value") else {
+            continue;
+        };
+        if value.is_identifier() && contents.node_slice(&value)? == placeholder {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Is `node` a function definition, possibly wrapped in parentheses?
+fn is_function_definition(node: Node) -> bool {
+    let mut node = node;
+
+    while node.node_type() == NodeType::ParenthesizedExpression {
+        let Some(child) = node.named_child(0) else {
+            return false;
+        };
+        node = child;
+    }
+
+    node.is_function_definition()
+}
+
 fn is_within_call_parentheses(x: &Point, node: &Node) -> bool {
     if node.node_type() != NodeType::Call {
         // This would be very weird
@@ -509,11 +675,13 @@ mod tests {
     use libr::R_xlen_t;
     use tower_lsp::lsp_types::ParameterLabel;
 
+    use crate::events::EVENTS;
     use crate::fixtures::point_from_cursor;
     use crate::lsp::document_context::DocumentContext;
     use crate::lsp::documents::Document;
     use crate::lsp::signature_help::argument_label;
     use crate::lsp::signature_help::r_signature_help;
+    use crate::lsp::signature_help::SIGNATURE_CACHE;
 
     #[test]
     fn test_basic_signature_help() {
@@ -533,6 +701,25 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_signature_help_offsets_multibyte() {
+        crate::r_task(|| {
+            harp::parse_eval_global("fn <- function(x = 'été', y) NULL").unwrap();
+
+            let (text, point) = point_from_cursor("fn(@)");
+            let document = Document::new(&text, None);
+            let context = DocumentContext::new(&document, point, None);
+
+            let help = r_signature_help(&context).unwrap().unwrap();
+            let parameters = help.signatures[0].parameters.as_ref().unwrap();
+
+            // `fn(x = "été", y)`, offsets are in UTF-16 code units
+            assert_eq!(parameters[1].label, ParameterLabel::LabelOffsets([14, 15]));
+
+            harp::parse_eval_global("rm(fn)").unwrap();
+        })
+    }
+
     #[test]
     fn test_no_signature_help_outside_parentheses() {
         crate::r_task(|| {
@@ -552,6 +739,85 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_signature_help_pipe() {
+        crate::r_task(|| {
+            harp::parse_eval_global("fn <- function(x, y, z) NULL").unwrap();
+
+            let cases = [
+                ("fn(@)", 0),
+                ("1 |> fn(@)", 1),
+                ("1 %>% fn(@)", 1),
+                ("1 |> fn(2, @)", 2),
+                ("1 |> fn(x = 2, @)", 2),
+                // Placeholders disable the implicit first argument
+                ("1 |> fn(y = _, @)", 0),
+                ("1 %>% fn(., @)", 1),
+                // Only the right-hand side of the pipe is affected
+                ("fn(@) |> identity()", 0),
+            ];
+
+            for (code, expected) in cases {
+                let (text, point) = point_from_cursor(code);
+                let document = Document::new(&text, None);
+                let context = DocumentContext::new(&document, point, None);
+                let help = r_signature_help(&context).unwrap().unwrap();
+                assert_eq!(help.active_parameter, Some(expected));
+            }
+
+            // Clean up
+            harp::parse_eval_global("rm(fn)").unwrap();
+        })
+    }
+
+    #[test]
+    fn test_signature_help_lambda() {
+        crate::r_task(|| {
+            // Anonymous functions called directly
+            let (text, point) = point_from_cursor("(\\(a, b) a)(1, @)");
+            let document = Document::new(&text, None);
+            let context = DocumentContext::new(&document, point, None);
+            let help = r_signature_help(&context).unwrap().unwrap();
+            assert_eq!(help.active_parameter, Some(1));
+
+            // No signature help for the enclosing call while declaring parameters
+            let (text, point) = point_from_cursor("lapply(1, \\(a@))");
+            let document = Document::new(&text, None);
+            let context = DocumentContext::new(&document, point, None);
+            let help = r_signature_help(&context).unwrap();
+            assert!(help.is_none());
+        })
+    }
+
+    #[test]
+    fn test_signature_help_cache() {
+        crate::r_task(|| {
+            let label = || {
+                let (text, point) = point_from_cursor("fn(@)");
+                let document = Document::new(&text, None);
+                let context = DocumentContext::new(&document, point, None);
+                let help = r_signature_help(&context).unwrap().unwrap();
+                help.signatures[0].label.clone()
+            };
+
+            harp::parse_eval_global("fn <- function(a) NULL").unwrap();
+            assert_eq!(label(), "fn(a)");
+            assert_eq!(label(), "fn(a)");
+
+            // The cache is keyed by function identity, so a new function
+            // bound to the same name gets its own signature
+            harp::parse_eval_global("fn <- function(b) NULL").unwrap();
+            assert_eq!(label(), "fn(b)");
+
+            // Package events clear the cache
+            EVENTS.package_load.emit(String::from("pkg"));
+            assert!(SIGNATURE_CACHE.lock().unwrap().is_empty());
+
+            // Clean up
+            harp::parse_eval_global("rm(fn)").unwrap();
+        })
+    }
+
     #[test]
     fn test_signature_help_argument_defaults() {
         crate::r_task(|| {
@@ -4,8 +4,10 @@ use std::path::Path;
 use anyhow::anyhow;
 use url::Url;
 
+use crate::lsp::completions::frecency::Frecency;
 use crate::lsp::config::LspConfig;
 use crate::lsp::documents::Document;
+use crate::lsp::inputs::exports_index::ExportsIndex;
 use crate::lsp::inputs::library::Library;
 use crate::lsp::inputs::source_root::SourceRoot;
 
@@ -56,7 +58,15 @@ pub(crate) struct WorldState {
     /// Map of package name to package metadata for installed libraries. Lazily populated.
     pub(crate) library: Library,
 
+    /// Index of the installed packages exporting each symbol, used to suggest
+    /// imports for undefined symbols. Built in the background at startup.
+    pub(crate) exports: ExportsIndex,
+
     pub(crate) config: LspConfig,
+
+    /// Usage statistics of symbols at the console and in documents, used to
+    /// rank completions
+    pub(crate) frecency: Frecency,
 }
 
 #[derive(Clone, Default, Debug)]
@@ -91,8 +101,7 @@ where
     F: FnMut(&Document) -> anyhow::Result<T>,
 {
     let mut fallback = || {
-        let contents = std::fs::read_to_string(path)?;
-        let document = Document::new(contents.as_str(), None);
+        let document = Document::read(path)?;
         return callback(&document);
     };
 
@@ -7,6 +7,7 @@
 
 use anyhow::anyhow;
 use tower_lsp::lsp_types;
+use tower_lsp::lsp_types::CodeLensOptions;
 use tower_lsp::lsp_types::CompletionOptions;
 use tower_lsp::lsp_types::CompletionOptionsCompletionItem;
 use tower_lsp::lsp_types::CreateFilesParams;
@@ -15,6 +16,7 @@ use tower_lsp::lsp_types::DidChangeConfigurationParams;
 use tower_lsp::lsp_types::DidChangeTextDocumentParams;
 use tower_lsp::lsp_types::DidCloseTextDocumentParams;
 use tower_lsp::lsp_types::DidOpenTextDocumentParams;
+use tower_lsp::lsp_types::DidSaveTextDocumentParams;
 use tower_lsp::lsp_types::DocumentOnTypeFormattingOptions;
 use tower_lsp::lsp_types::ExecuteCommandOptions;
 use tower_lsp::lsp_types::FileOperationFilter;
@@ -27,6 +29,7 @@ use tower_lsp::lsp_types::HoverProviderCapability;
 use tower_lsp::lsp_types::ImplementationProviderCapability;
 use tower_lsp::lsp_types::InitializeParams;
 use tower_lsp::lsp_types::InitializeResult;
+use tower_lsp::lsp_types::LinkedEditingRangeServerCapabilities;
 use tower_lsp::lsp_types::OneOf;
 use tower_lsp::lsp_types::RenameFilesParams;
 use tower_lsp::lsp_types::SelectionRangeProviderCapability;
@@ -35,6 +38,8 @@ use tower_lsp::lsp_types::ServerInfo;
 use tower_lsp::lsp_types::SignatureHelpOptions;
 use tower_lsp::lsp_types::TextDocumentSyncCapability;
 use tower_lsp::lsp_types::TextDocumentSyncKind;
+use tower_lsp::lsp_types::TextDocumentSyncOptions;
+use tower_lsp::lsp_types::TextDocumentSyncSaveOptions;
 use tower_lsp::lsp_types::WorkDoneProgressOptions;
 use tower_lsp::lsp_types::WorkspaceFoldersServerCapabilities;
 use tower_lsp::lsp_types::WorkspaceServerCapabilities;
@@ -44,18 +49,23 @@ use url::Url;
 
 use crate::lsp;
 use crate::lsp::capabilities::Capabilities;
+use crate::lsp::code_lens;
+use crate::lsp::completions::frecency::code_symbols;
 use crate::lsp::config::indent_style_from_lsp;
 use crate::lsp::config::DOCUMENT_SETTINGS;
 use crate::lsp::config::GLOBAL_SETTINGS;
+use crate::lsp::documents::file_legacy_encoding;
 use crate::lsp::documents::Document;
 use crate::lsp::encoding::get_position_encoding_kind;
 use crate::lsp::inputs::package::Package;
 use crate::lsp::inputs::source_root::SourceRoot;
+use crate::lsp::main_loop::DidChangePackageParams;
 use crate::lsp::main_loop::DidCloseVirtualDocumentParams;
 use crate::lsp::main_loop::DidOpenVirtualDocumentParams;
 use crate::lsp::main_loop::LspState;
 use crate::lsp::state::workspace_uris;
 use crate::lsp::state::WorldState;
+use crate::lsp::traits::url::UrlExt;
 
 // Handlers that mutate the world state
 
@@ -72,6 +82,9 @@ pub struct ConsoleInputs {
     /// Packages currently installed in the library path. TODO: Should send
     /// library paths instead and inspect and cache package information in the LSP.
     pub installed_packages: Vec<String>,
+
+    /// Code evaluated at the console since the last notification
+    pub console_code: Vec<String>,
 }
 
 // Handlers taking exclusive references to global state
@@ -134,10 +147,16 @@ pub(crate) fn initialize(
         }),
         capabilities: ServerCapabilities {
             position_encoding: Some(get_position_encoding_kind()),
-            text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                TextDocumentSyncKind::INCREMENTAL,
+            text_document_sync: Some(TextDocumentSyncCapability::Options(
+                TextDocumentSyncOptions {
+                    open_close: Some(true),
+                    change: Some(TextDocumentSyncKind::INCREMENTAL),
+                    save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                    ..Default::default()
+                },
             )),
             selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
+            linked_editing_range_provider: Some(LinkedEditingRangeServerCapabilities::Simple(true)),
             hover_provider: Some(HoverProviderCapability::from(true)),
             completion_provider: Some(CompletionOptions {
                 resolve_provider: Some(true),
@@ -163,8 +182,15 @@ pub(crate) fn initialize(
             document_symbol_provider: Some(OneOf::Left(true)),
             folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
             workspace_symbol_provider: Some(OneOf::Left(true)),
+            code_lens_provider: Some(CodeLensOptions {
+                resolve_provider: Some(false),
+            }),
             execute_command_provider: Some(ExecuteCommandOptions {
-                commands: vec![],
+                commands: vec![
+                    String::from(code_lens::RUN_LINES_COMMAND),
+                    String::from(code_lens::RUN_TEST_COMMAND),
+                    String::from(code_lens::RUN_FILE_COMMAND),
+                ],
                 work_done_progress_options: Default::default(),
             }),
             code_action_provider: lsp_state.capabilities.code_action_provider_capability(),
@@ -198,7 +224,7 @@ pub(crate) fn initialize(
             }),
             document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
                 first_trigger_character: String::from("\n"),
-                more_trigger_character: None,
+                more_trigger_character: Some(vec![String::from("{")]),
             }),
             ..ServerCapabilities::default()
         },
@@ -220,7 +246,15 @@ pub(crate) fn did_open(
         .set_language(&tree_sitter_r::LANGUAGE.into())
         .unwrap();
 
-    let document = Document::new_with_parser(contents, &mut parser, Some(version));
+    let mut document = if uri.is_r_markdown() {
+        Document::new_r_markdown(contents, &mut parser, Some(version))
+    } else {
+        Document::new_with_parser(contents, &mut parser, Some(version))
+    };
+
+    if let Ok(path) = uri.to_file_path() {
+        document.legacy_encoding = file_legacy_encoding(&path);
+    }
 
     lsp_state.parsers.insert(uri.clone(), parser);
     state.documents.insert(uri.clone(), document.clone());
@@ -254,6 +288,22 @@ pub(crate) fn did_change(
     Ok(())
 }
 
+#[tracing::instrument(level = "info", skip_all)]
+pub(crate) fn did_save(
+    params: DidSaveTextDocumentParams,
+    state: &mut WorldState,
+) -> anyhow::Result<()> {
+    let uri = &params.text_document.uri;
+    let document = state.get_document(uri)?;
+
+    // Rank the symbols of recently edited documents higher in completions
+    let contents = document.contents.to_string();
+    let symbols = code_symbols(document.ast.root_node(), &contents);
+    state.frecency.record(symbols);
+
+    Ok(())
+}
+
 #[tracing::instrument(level = "info", skip_all)]
 pub(crate) fn did_close(
     params: DidCloseTextDocumentParams,
@@ -262,8 +312,10 @@ pub(crate) fn did_close(
 ) -> anyhow::Result<()> {
     let uri = params.text_document.uri;
 
-    // Publish empty set of diagnostics to clear them
+    // Publish empty set of diagnostics to clear them, and make sure pending
+    // refreshes don't publish them again
     lsp::publish_diagnostics(uri.clone(), Vec::new(), None);
+    lsp::main_loop::diagnostics_forget(&uri);
 
     state
         .documents
@@ -395,6 +447,7 @@ async fn update_config(
 ) -> anyhow::Result<()> {
     // Keep track of existing config to detect whether it was changed
     let diagnostics_config = state.config.diagnostics.clone();
+    let indexer_config = state.config.indexer.clone();
 
     // Build the configuration request for global and document settings
     let mut items: Vec<_> = vec![];
@@ -468,6 +521,12 @@ async fn update_config(
         lsp::main_loop::diagnostics_refresh_all(state.clone());
     }
 
+    // Reindex if the excluded files changed
+    if state.config.indexer != indexer_config {
+        tracing::info!("Reindexing workspace after configuration changed");
+        lsp::main_loop::index_refresh(state.clone());
+    }
+
     Ok(())
 }
 
@@ -479,6 +538,10 @@ pub(crate) fn did_change_console_inputs(
     state.console_scopes = inputs.console_scopes;
     state.installed_packages = inputs.installed_packages;
 
+    for code in inputs.console_code.iter() {
+        state.frecency.record_code(code);
+    }
+
     // We currently rely on global console scopes for diagnostics, in particular
     // during package development in conjunction with `devtools::load_all()`.
     // Ideally diagnostics would not rely on these though, and we wouldn't need
@@ -506,3 +569,15 @@ pub(crate) fn did_close_virtual_document(
     state.virtual_documents.remove(&params.uri);
     Ok(())
 }
+
+#[tracing::instrument(level = "info", skip_all)]
+pub(crate) fn did_change_package(
+    params: DidChangePackageParams,
+    state: &mut WorldState,
+) -> anyhow::Result<()> {
+    // The package may have been installed or updated since we cached its
+    // exports. The console scopes are refreshed separately.
+    state.library.invalidate(&params.name);
+    state.exports.invalidate(&params.name);
+    Ok(())
+}
@@ -20,6 +20,7 @@ use tower_lsp::lsp_types::SymbolKind;
 use tower_lsp::lsp_types::WorkspaceSymbolParams;
 use tree_sitter::Node;
 
+use crate::lsp::documents::Document;
 use crate::lsp::encoding::convert_point_to_position;
 use crate::lsp::indexer;
 use crate::lsp::indexer::IndexEntryData;
@@ -161,6 +162,14 @@ pub(crate) fn document_symbols(
 ) -> anyhow::Result<Vec<DocumentSymbol>> {
     let uri = &params.text_document.uri;
     let document = state.documents.get(uri).into_result()?;
+
+    document_symbols_of(document, state.config.symbols.include_assignments_in_blocks)
+}
+
+pub(crate) fn document_symbols_of(
+    document: &Document,
+    include_assignments_in_blocks: bool,
+) -> anyhow::Result<Vec<DocumentSymbol>> {
     let ast = &document.ast;
     let contents = &document.contents;
 
@@ -169,7 +178,7 @@ pub(crate) fn document_symbols(
     let mut result = Vec::new();
 
     let mut ctx = CollectContext::new();
-    ctx.include_assignments_in_blocks = state.config.symbols.include_assignments_in_blocks;
+    ctx.include_assignments_in_blocks = include_assignments_in_blocks;
 
     // Extract and process all symbols from the AST
     if let Err(err) = collect_symbols(&mut ctx, &root_node, contents, &mut result) {
@@ -483,10 +492,13 @@ fn collect_call(
         return Ok(());
     };
 
-    if callee.is_identifier() {
-        let fun_symbol = contents.node_slice(&callee)?.to_string();
+    if let Some(fun_symbol) = callee_name(&callee, contents)? {
         match fun_symbol.as_str() {
             "test_that" => return collect_call_test_that(ctx, node, contents, symbols),
+            "R6Class" => return collect_call_r6_class(ctx, node, None, node, contents, symbols),
+            "setGeneric" => return collect_call_set_generic(ctx, node, contents, symbols),
+            "setMethod" => return collect_call_set_method(ctx, node, contents, symbols),
+            "setClass" => return collect_call_set_class(ctx, node, contents, symbols),
             _ => {}, // fallthrough
         }
     }
@@ -496,6 +508,306 @@ fn collect_call(
     Ok(())
 }
 
+/// The name of the function called by `callee`, ignoring any namespace
+/// prefix as in `R6::R6Class()`
+fn callee_name(callee: &Node, contents: &Rope) -> anyhow::Result<Option<String>> {
+    let callee = if callee.is_namespace_operator() {
+        let Some(rhs) = callee.child_by_field_name("rhs") else {
+            return Ok(None);
+        };
+        rhs
+    } else {
+        *callee
+    };
+
+    if !callee.is_identifier() {
+        return Ok(None);
+    }
+
+    Ok(Some(contents.node_slice(&callee)?.to_string()))
+}
+
+/// Finds the value of an argument supplied either by `name` or as the
+/// `position`-th unnamed argument. We don't do full argument matching.
+fn call_argument_value<'tree>(
+    call: &Node<'tree>,
+    name: &str,
+    position: usize,
+    contents: &Rope,
+) -> anyhow::Result<Option<Node<'tree>>> {
+    let Some(arguments) = call.child_by_field_name("arguments") else {
+        return Ok(None);
+    };
+
+    let mut unnamed = 0;
+    let mut positional = None;
+
+    let mut cursor = arguments.walk();
+    for argument in arguments.children_by_field_name("argument", &mut cursor) {
+        let Some(value) = argument.child_by_field_name("value") else {
+            continue;
+        };
+
+        match argument.child_by_field_name("name") {
+            Some(arg_name) => {
+                if contents.node_slice(&arg_name)?.to_string() == name {
+                    return Ok(Some(value));
+                }
+            },
+            None => {
+                if unnamed == position {
+                    positional = positional.or(Some(value));
+                }
+                unnamed += 1;
+            },
+        }
+    }
+
+    Ok(positional)
+}
+
+/// The contents of a string argument, e.g. the class name of `setClass("foo")`
+fn call_argument_string(
+    call: &Node,
+    name: &str,
+    position: usize,
+    contents: &Rope,
+) -> anyhow::Result<Option<String>> {
+    let Some(value) = call_argument_value(call, name, position, contents)? else {
+        return Ok(None);
+    };
+    if !value.is_string() {
+        return Ok(None);
+    }
+    let Some(string) = value.child_by_field_name("content") else {
+        return Ok(None);
+    };
+    Ok(Some(contents.node_slice(&string)?.to_string()))
+}
+
+fn is_r6_class_call(node: &Node, contents: &Rope) -> anyhow::Result<bool> {
+    if !node.is_call() {
+        return Ok(false);
+    }
+    let Some(callee) = node.child_by_field_name("function") else {
+        return Ok(false);
+    };
+    Ok(callee_name(&callee, contents)?.as_deref() == Some("R6Class"))
+}
+
+/// Collects an R6 class with its members as children
+///
+/// The class is named after the object it is assigned to if any, otherwise
+/// after its `classname`. The range covers `range_node`, i.e. the whole
+/// assignment when the class is assigned.
+fn collect_call_r6_class(
+    ctx: &mut CollectContext,
+    node: &Node,
+    name: Option<String>,
+    range_node: &Node,
+    contents: &Rope,
+    symbols: &mut Vec<DocumentSymbol>,
+) -> anyhow::Result<()> {
+    let name = match name {
+        Some(name) => Some(name),
+        None => call_argument_string(node, "classname", 0, contents)?,
+    };
+    let Some(name) = name else {
+        return collect_call_arguments(ctx, node, contents, symbols);
+    };
+
+    let mut children = Vec::new();
+
+    for (visibility, position) in [("public", 1), ("private", 2), ("active", 3)] {
+        let Some(members) = call_argument_value(node, visibility, position, contents)? else {
+            continue;
+        };
+        collect_r6_class_members(ctx, &members, visibility, contents, &mut children)?;
+    }
+
+    let start = convert_point_to_position(contents, range_node.start_position());
+    let end = convert_point_to_position(contents, range_node.end_position());
+
+    let mut symbol = new_symbol_node(name, SymbolKind::CLASS, Range { start, end }, children);
+    symbol.detail = Some(String::from("R6Class"));
+    symbols.push(symbol);
+
+    Ok(())
+}
+
+/// Collects the methods and fields of a `public`, `private`, or `active` list
+fn collect_r6_class_members(
+    ctx: &mut CollectContext,
+    members: &Node,
+    visibility: &str,
+    contents: &Rope,
+    symbols: &mut Vec<DocumentSymbol>,
+) -> anyhow::Result<()> {
+    let Some(arguments) = members.child_by_field_name("arguments") else {
+        return Ok(());
+    };
+
+    collect_sections(
+        ctx,
+        &arguments,
+        contents,
+        symbols,
+        |ctx, argument, contents, symbols| {
+            let (Some(member), Some(value)) = (
+                argument.child_by_field_name("name"),
+                argument.child_by_field_name("value"),
+            ) else {
+                return Ok(());
+            };
+
+            if value.is_function_definition() {
+                let mut methods = Vec::new();
+                collect_method(ctx, &member, &value, contents, &mut methods)?;
+
+                for mut method in methods {
+                    method.detail = Some(format!("{visibility} function()"));
+                    symbols.push(method);
+                }
+                return Ok(());
+            }
+
+            if !member.is_identifier_or_string() {
+                return Ok(());
+            }
+
+            let start = convert_point_to_position(contents, argument.start_position());
+            let end = convert_point_to_position(contents, argument.end_position());
+
+            let mut symbol = new_symbol(
+                contents.node_slice(&member)?.to_string(),
+                SymbolKind::FIELD,
+                Range { start, end },
+            );
+            symbol.detail = Some(String::from(visibility));
+            symbols.push(symbol);
+
+            Ok(())
+        },
+    )
+}
+
+fn collect_call_set_generic(
+    ctx: &mut CollectContext,
+    node: &Node,
+    contents: &Rope,
+    symbols: &mut Vec<DocumentSymbol>,
+) -> anyhow::Result<()> {
+    let Some(name) = call_argument_string(node, "name", 0, contents)? else {
+        return collect_call_arguments(ctx, node, contents, symbols);
+    };
+
+    collect_s4_symbol(
+        ctx,
+        node,
+        name,
+        "setGeneric",
+        SymbolKind::INTERFACE,
+        contents,
+        symbols,
+    )
+}
+
+fn collect_call_set_method(
+    ctx: &mut CollectContext,
+    node: &Node,
+    contents: &Rope,
+    symbols: &mut Vec<DocumentSymbol>,
+) -> anyhow::Result<()> {
+    let Some(name) = call_argument_string(node, "f", 0, contents)? else {
+        return collect_call_arguments(ctx, node, contents, symbols);
+    };
+
+    // The signature is either a single class, e.g. `"numeric"`, or a more
+    // complex expression, e.g. `c("numeric", "character")`, which we include
+    // verbatim
+    let name = match call_argument_value(node, "signature", 1, contents)? {
+        Some(signature) if signature.is_string() => {
+            match signature.child_by_field_name("content") {
+                Some(class) => format!("{name}({class})", class = contents.node_slice(&class)?),
+                None => name,
+            }
+        },
+        Some(signature) => format!(
+            "{name}({signature})",
+            signature = contents.node_slice(&signature)?
+        ),
+        None => name,
+    };
+
+    collect_s4_symbol(
+        ctx,
+        node,
+        name,
+        "setMethod",
+        SymbolKind::METHOD,
+        contents,
+        symbols,
+    )
+}
+
+fn collect_call_set_class(
+    ctx: &mut CollectContext,
+    node: &Node,
+    contents: &Rope,
+    symbols: &mut Vec<DocumentSymbol>,
+) -> anyhow::Result<()> {
+    let Some(name) = call_argument_string(node, "Class", 0, contents)? else {
+        return collect_call_arguments(ctx, node, contents, symbols);
+    };
+
+    collect_s4_symbol(
+        ctx,
+        node,
+        name,
+        "setClass",
+        SymbolKind::CLASS,
+        contents,
+        symbols,
+    )
+}
+
+fn collect_s4_symbol(
+    ctx: &mut CollectContext,
+    node: &Node,
+    name: String,
+    detail: &str,
+    kind: SymbolKind,
+    contents: &Rope,
+    symbols: &mut Vec<DocumentSymbol>,
+) -> anyhow::Result<()> {
+    // Recurse in arguments to collect sections and symbols in method
+    // definitions
+    let mut children = Vec::new();
+    if let Some(arguments) = node.child_by_field_name("arguments") {
+        collect_sections(
+            ctx,
+            &arguments,
+            contents,
+            &mut children,
+            |ctx, child, contents, symbols| {
+                let Some(value) = child.child_by_field_name("value") else {
+                    return Ok(());
+                };
+                collect_symbols(ctx, &value, contents, symbols)
+            },
+        )?;
+    }
+
+    let start = convert_point_to_position(contents, node.start_position());
+    let end = convert_point_to_position(contents, node.end_position());
+
+    let mut symbol = new_symbol_node(name, kind, Range { start, end }, children);
+    symbol.detail = Some(String::from(detail));
+    symbols.push(symbol);
+
+    Ok(())
+}
+
 fn collect_call_arguments(
     ctx: &mut CollectContext,
     node: &Node,
@@ -562,7 +874,9 @@ fn collect_method(
     }
     let arg_name_str = contents.node_slice(&arg_fun)?.to_string();
 
-    let start = convert_point_to_position(contents, arg_value.start_position());
+    // The range covers the name of the method so that it encloses the whole
+    // `name = function() {}` argument, like assigned functions
+    let start = convert_point_to_position(contents, arg_fun.start_position());
     let end = convert_point_to_position(contents, arg_value.end_position());
 
     let mut children = vec![];
@@ -655,6 +969,18 @@ fn collect_assignment(
         return collect_assignment_with_function(ctx, node, contents, symbols);
     }
 
+    // If an R6 class, collect symbol as class named after the assigned object
+    if lhs.is_identifier_or_string() && is_r6_class_call(&rhs, contents)? {
+        let name = contents.node_slice(&lhs)?.to_string();
+
+        let old = ctx.top_level;
+        ctx.top_level = false;
+        let result = collect_call_r6_class(ctx, &rhs, Some(name), node, contents, symbols);
+        ctx.top_level = old;
+
+        return result;
+    }
+
     if ctx.top_level || ctx.include_assignments_in_blocks {
         // Collect as generic object, but typically only if we're at top-level. Assigned
         // objects in nested functions and blocks cause the outline to become
@@ -771,7 +1097,6 @@ mod tests {
     use super::*;
     use crate::lsp::config::LspConfig;
     use crate::lsp::config::WorkspaceSymbolsConfig;
-    use crate::lsp::documents::Document;
     use crate::lsp::indexer::ResetIndexerGuard;
     use crate::lsp::util::test_path;
 
@@ -1101,7 +1426,7 @@ foo <- {
         insta::assert_debug_snapshot!(test_symbol(
             "
 # section ----
-class <- r6::r6class(
+class <- R6::R6Class(
   'class',
   public = list(
     initialize = function() 'initialize',
@@ -1115,6 +1440,127 @@ class <- r6::r6class(
         ));
     }
 
+    #[test]
+    fn test_symbol_r6_class() {
+        let symbols = test_symbol(
+            "
+Foo <- R6::R6Class(
+  'Foo',
+  public = list(
+    x = NULL,
+    initialize = function(x) {
+      self$x <- x
+    }
+  ),
+  private = list(
+    secret = function() 'secret'
+  )
+)
+R6Class('Bar')
+",
+        );
+
+        assert_eq!(symbols.len(), 2);
+
+        let class = &symbols[0];
+        assert_eq!(class.name, "Foo");
+        assert_eq!(class.kind, SymbolKind::CLASS);
+
+        let children = class.children.as_ref().unwrap();
+        let members: Vec<_> = children
+            .iter()
+            .map(|child| (child.name.as_str(), child.kind, child.detail.as_deref()))
+            .collect();
+        assert_eq!(members, vec![
+            ("x", SymbolKind::FIELD, Some("public")),
+            ("initialize", SymbolKind::METHOD, Some("public function()")),
+            ("secret", SymbolKind::METHOD, Some("private function()")),
+        ]);
+
+        // Unassigned classes are named after their `classname`
+        assert_eq!(symbols[1].name, "Bar");
+        assert_eq!(symbols[1].kind, SymbolKind::CLASS);
+    }
+
+    #[test]
+    fn test_symbol_r6_class_sections() {
+        let symbols = test_symbol(
+            "
+Foo <- R6::R6Class(
+  'Foo',
+  public = list(
+    # methods ----
+    initialize = function() NULL,
+    # fields ----
+    x = NULL
+  )
+)
+",
+        );
+
+        let children = symbols[0].children.as_ref().unwrap();
+        let sections: Vec<_> = children.iter().map(|child| child.name.as_str()).collect();
+        assert_eq!(sections, vec!["methods", "fields"]);
+
+        let methods = children[0].children.as_ref().unwrap();
+        assert_eq!(methods[0].name, "initialize");
+        let fields = children[1].children.as_ref().unwrap();
+        assert_eq!(fields[0].name, "x");
+    }
+
+    #[test]
+    fn test_symbol_s4() {
+        let symbols = test_symbol(
+            "
+setClass('Person', representation(name = 'character'))
+setGeneric('greet', function(x, ...) standardGeneric('greet'))
+setMethod('greet', 'Person', function(x, ...) {
+  helper <- function() NULL
+  cat('Hello')
+})
+methods::setMethod('greet', signature('Person', 'character'), function(x, ...) NULL)
+",
+        );
+
+        let outline: Vec<_> = symbols
+            .iter()
+            .map(|symbol| (symbol.name.as_str(), symbol.kind))
+            .collect();
+        assert_eq!(outline, vec![
+            ("Person", SymbolKind::CLASS),
+            ("greet", SymbolKind::INTERFACE),
+            ("greet(Person)", SymbolKind::METHOD),
+            (
+                "greet(signature('Person', 'character'))",
+                SymbolKind::METHOD
+            ),
+        ]);
+
+        // Symbols in method definitions are collected as children
+        let children = symbols[2].children.as_ref().unwrap();
+        assert_eq!(children[0].name, "helper");
+    }
+
+    #[test]
+    fn test_symbol_s4_sections() {
+        let symbols = test_symbol(
+            "
+setMethod(
+  'greet',
+  'Person',
+  # body ----
+  function(x, ...) {
+    helper <- function() NULL
+  }
+)
+",
+        );
+
+        let children = symbols[0].children.as_ref().unwrap();
+        assert_eq!(children[0].name, "body");
+        assert_eq!(children[0].children.as_ref().unwrap()[0].name, "helper");
+    }
+
     #[test]
     // Assigned variables in nested contexts are not emitted as symbols
     fn test_symbol_nested_assignments() {
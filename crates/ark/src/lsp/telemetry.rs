@@ -0,0 +1,206 @@
+//
+// telemetry.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::lsp::config::TelemetryConfig;
+
+pub static ARK_TELEMETRY_REQUEST: &'static str = "ark/internal/telemetry";
+
+/// Upper bounds of the latency buckets in milliseconds. Latencies above the
+/// last bound are counted in an additional overflow bucket.
+pub const BUCKET_BOUNDS_MS: [u64; 12] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000, 5000];
+
+/// Latencies recorded since startup or since the last reset
+static LATENCIES: LazyLock<Mutex<BTreeMap<RequestKind, LatencyHistogram>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+/// The providers whose latency is recorded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RequestKind {
+    Completion,
+    Hover,
+    Diagnostics,
+    FoldingRange,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryParams {
+    /// Whether to clear the recorded latencies once they have been retrieved,
+    /// e.g. to measure a specific workflow.
+    #[serde(default)]
+    pub reset: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryResponse {
+    /// Whether latencies are being recorded, see `positron.r.telemetry.enable`.
+    pub enabled: bool,
+    /// Upper bounds of the histogram buckets in milliseconds.
+    pub bucket_bounds_ms: Vec<u64>,
+    /// Histograms of latencies keyed by provider.
+    pub latencies: BTreeMap<RequestKind, LatencyHistogram>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatencyHistogram {
+    pub count: u64,
+    pub total_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    /// Counts of latencies per bucket. Has one more element than
+    /// `bucket_bounds_ms` for the latencies above the last bound.
+    pub buckets: Vec<u64>,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            total_ms: 0.0,
+            min_ms: 0.0,
+            max_ms: 0.0,
+            buckets: vec![0; BUCKET_BOUNDS_MS.len() + 1],
+        }
+    }
+}
+
+impl LatencyHistogram {
+    fn record(&mut self, latency: Duration) {
+        let ms = latency.as_nanos() as f64 / 1e6;
+
+        if self.count == 0 {
+            self.min_ms = ms;
+            self.max_ms = ms;
+        } else {
+            self.min_ms = self.min_ms.min(ms);
+            self.max_ms = self.max_ms.max(ms);
+        }
+        self.count += 1;
+        self.total_ms += ms;
+
+        let bucket = BUCKET_BOUNDS_MS
+            .iter()
+            .position(|bound| ms <= *bound as f64)
+            .unwrap_or(BUCKET_BOUNDS_MS.len());
+        self.buckets[bucket] += 1;
+    }
+}
+
+/// Records the latency of a provider in the global histograms
+pub(crate) fn record(kind: RequestKind, latency: Duration) {
+    let mut latencies = LATENCIES.lock().unwrap();
+    latencies.entry(kind).or_default().record(latency);
+}
+
+/// Returns the recorded latencies, optionally clearing them
+pub(crate) fn snapshot(config: &TelemetryConfig, reset: bool) -> TelemetryResponse {
+    let mut latencies = LATENCIES.lock().unwrap();
+
+    let response = TelemetryResponse {
+        enabled: config.enable,
+        bucket_bounds_ms: BUCKET_BOUNDS_MS.to_vec(),
+        latencies: latencies.clone(),
+    };
+
+    if reset {
+        latencies.clear();
+    }
+
+    response
+}
+
+/// Records the time until it is dropped. Does nothing when telemetry is
+/// disabled so that the cost of opting out is a single check.
+pub(crate) struct LatencyTimer {
+    kind: RequestKind,
+    start: Instant,
+}
+
+impl LatencyTimer {
+    pub(crate) fn start(kind: RequestKind, config: &TelemetryConfig) -> Option<Self> {
+        if !config.enable {
+            return None;
+        }
+        Some(Self {
+            kind,
+            start: Instant::now(),
+        })
+    }
+}
+
+impl Drop for LatencyTimer {
+    fn drop(&mut self) {
+        record(self.kind, self.start.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::lsp::config::TelemetryConfig;
+    use crate::lsp::telemetry::LatencyHistogram;
+    use crate::lsp::telemetry::LatencyTimer;
+    use crate::lsp::telemetry::RequestKind;
+    use crate::lsp::telemetry::BUCKET_BOUNDS_MS;
+
+    #[test]
+    fn test_latency_histogram() {
+        let mut histogram = LatencyHistogram::default();
+        histogram.record(Duration::from_micros(500));
+        histogram.record(Duration::from_millis(15));
+        histogram.record(Duration::from_millis(20));
+        histogram.record(Duration::from_secs(10));
+
+        assert_eq!(histogram.count, 4);
+        assert_eq!(histogram.min_ms, 0.5);
+        assert_eq!(histogram.max_ms, 10000.0);
+        assert_eq!(histogram.total_ms, 10035.5);
+
+        assert_eq!(histogram.buckets.len(), BUCKET_BOUNDS_MS.len() + 1);
+        assert_eq!(histogram.buckets[0], 1);
+        // Bounds are inclusive
+        assert_eq!(histogram.buckets[4], 2);
+        assert_eq!(histogram.buckets[BUCKET_BOUNDS_MS.len()], 1);
+        assert_eq!(histogram.buckets.iter().sum::<u64>(), 4);
+    }
+
+    #[test]
+    fn test_latency_timer_is_opt_in() {
+        let config = TelemetryConfig::default();
+        assert!(LatencyTimer::start(RequestKind::Hover, &config).is_none());
+
+        let config = TelemetryConfig { enable: true };
+        assert!(LatencyTimer::start(RequestKind::Hover, &config).is_some());
+    }
+
+    #[test]
+    fn test_latency_histogram_serialization() {
+        let mut histogram = LatencyHistogram::default();
+        histogram.record(Duration::from_millis(3));
+
+        let json = serde_json::to_value(&histogram).unwrap();
+        assert_eq!(json["count"], 1);
+        assert_eq!(json["totalMs"], 3.0);
+        assert_eq!(json["buckets"][2], 1);
+
+        let json = serde_json::to_value(RequestKind::FoldingRange).unwrap();
+        assert_eq!(json, "foldingRange");
+    }
+}
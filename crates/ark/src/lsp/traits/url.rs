@@ -14,6 +14,10 @@ use tower_lsp::lsp_types::Url;
 
 pub trait UrlExt {
     fn file_path(&self) -> anyhow::Result<PathBuf>;
+
+    /// Whether the URL points to an R Markdown (`.Rmd`) or Quarto (`.qmd`)
+    /// document, which embeds R code in chunks
+    fn is_r_markdown(&self) -> bool;
 }
 
 impl UrlExt for Url {
@@ -24,4 +28,11 @@ impl UrlExt for Url {
 
         Ok(pathbuf)
     }
+
+    fn is_r_markdown(&self) -> bool {
+        let Some((_, extension)) = self.path().rsplit_once('.') else {
+            return false;
+        };
+        extension.eq_ignore_ascii_case("rmd") || extension.eq_ignore_ascii_case("qmd")
+    }
 }
@@ -41,6 +41,9 @@ Available options:
 -- arg1 arg2 ...         Set the argument list to pass to R; defaults to
                          --interactive
 --startup-file FILE      An R file to run on session startup
+--no-site-profile        Do not run the site level R profile (`Rprofile.site`)
+--no-user-profile        Do not run the user or project level R profile (`.Rprofile`)
+--positron-profile FILE  An R profile to run after the site and user level ones
 --session-mode MODE      The mode in which the session is running (console, notebook, background)
 --no-capture-streams     Do not capture stdout/stderr from R
 --default-repos          Set the default repositories to use, by name:
@@ -73,6 +76,9 @@ fn main() -> anyhow::Result<()> {
 
     let mut connection_file: Option<String> = None;
     let mut startup_file: Option<String> = None;
+    let mut positron_profile: Option<String> = None;
+    let mut site_profile = true;
+    let mut user_profile = true;
     let mut session_mode = SessionMode::Console;
     let mut log_file: Option<String> = None;
     let mut profile_file: Option<String> = None;
@@ -106,6 +112,17 @@ fn main() -> anyhow::Result<()> {
                     ));
                 }
             },
+            "--no-site-profile" => site_profile = false,
+            "--no-user-profile" => user_profile = false,
+            "--positron-profile" => {
+                if let Some(file) = argv.next() {
+                    positron_profile = Some(file);
+                } else {
+                    return Err(anyhow::anyhow!(
+                        "A profile file must be specified when using the `--positron-profile` argument."
+                    ));
+                }
+            },
             "--session-mode" => {
                 if let Some(mode) = argv.next() {
                     session_mode = match mode.as_str() {
@@ -325,6 +342,15 @@ fn main() -> anyhow::Result<()> {
         r_args.push(String::from("--interactive"));
     }
 
+    // Profiles are skipped with the R arguments, which the startup subsystem
+    // checks before sourcing them
+    if !site_profile {
+        r_args.push(String::from("--no-site-file"));
+    }
+    if !user_profile {
+        r_args.push(String::from("--no-init-file"));
+    }
+
     // This causes panics on background threads to propagate on the main
     // thread. If we don't propagate a background thread panic, the program
     // keeps running in an unstable state as all communications with this
@@ -402,6 +428,7 @@ fn main() -> anyhow::Result<()> {
         registration_file,
         r_args,
         startup_file,
+        positron_profile,
         session_mode,
         capture_streams,
         default_repos,
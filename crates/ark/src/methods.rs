@@ -68,9 +68,7 @@ impl ArkGenerics {
         call.add(generic);
         call.add(x);
 
-        for RArgument { name, value } in args.into_iter() {
-            call.param(name.as_str(), value);
-        }
+        call.param_all(args.into_iter().map(|arg| (arg.name, arg.value)));
 
         let result = call.call_in(ARK_ENVS.positron_ns)?;
 
@@ -0,0 +1,482 @@
+//
+// number_format.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+//! Deterministic formatting of numbers shared by the data explorer, the
+//! variables pane, and the clipboard. Unlike `base::format()`, the output
+//! doesn't depend on the `digits` or `scipen` options, nor on the other
+//! elements of the vector.
+
+use amalthea::comm::data_explorer_comm::FormatOptions;
+use harp::object::r_dbl_is_finite;
+use harp::object::r_dbl_is_nan;
+use harp::object::r_length;
+use harp::utils::r_classes;
+use harp::utils::r_typeof;
+use harp::vector::IntegerVector;
+use harp::vector::NumericVector;
+use harp::vector::Vector;
+use libr::INTSXP;
+use libr::REALSXP;
+use libr::SEXP;
+
+#[derive(Clone, Debug)]
+pub struct NumberFormat {
+    /// Number of decimal places for numbers over 1, and for the mantissa in
+    /// scientific notation
+    pub large_num_digits: usize,
+
+    /// Number of decimal places for numbers under 1. Smaller numbers use
+    /// scientific notation.
+    pub small_num_digits: usize,
+
+    /// Maximum number of integral digits before switching to scientific
+    /// notation
+    pub max_integral_digits: usize,
+
+    pub thousands_sep: Option<String>,
+
+    /// Separator between the integral and fractional parts, `.` by default
+    pub decimal_sep: Option<String>,
+
+    /// Whether to drop trailing zeros from the decimal places, e.g. `1.50`
+    /// becomes `1.5` and `1.00e+07` becomes `1e+07`
+    pub trim_zeros: bool,
+
+    /// Number of significant digits. When set, doubles keep this many
+    /// significant digits and use the shorter of the fixed and scientific
+    /// notations, like `print()` does, instead of a fixed number of decimal
+    /// places. The `*_digits` fields are then ignored for doubles.
+    pub significant_digits: Option<usize>,
+}
+
+impl NumberFormat {
+    /// The format of numbers in the variables pane and the clipboard, which
+    /// is close to the output of `print()` with the default `digits` option
+    pub fn variables() -> Self {
+        Self {
+            large_num_digits: 6,
+            small_num_digits: 6,
+            max_integral_digits: 15,
+            thousands_sep: None,
+            decimal_sep: None,
+            trim_zeros: true,
+            significant_digits: Some(7),
+        }
+    }
+
+    /// Converts a number written with the separators of this format, e.g.
+    /// `1.234,5` with a `.` thousands separator and a `,` decimal separator,
+    /// to the `1234.5` syntax understood by R and Rust
+    pub fn normalize_separators(&self, x: &str) -> String {
+        let mut x = x.trim().to_string();
+
+        if let Some(sep) = self.thousands_sep.as_deref().filter(|sep| !sep.is_empty()) {
+            x = x.replace(sep, "");
+        }
+        if let Some(sep) = self.decimal_sep.as_deref().filter(|sep| !sep.is_empty()) {
+            x = x.replace(sep, ".");
+        }
+
+        x
+    }
+}
+
+impl From<&FormatOptions> for NumberFormat {
+    fn from(options: &FormatOptions) -> Self {
+        Self {
+            large_num_digits: options.large_num_digits.max(0) as usize,
+            small_num_digits: options.small_num_digits.max(0) as usize,
+            max_integral_digits: options.max_integral_digits.max(0) as usize,
+            thousands_sep: options.thousands_sep.clone(),
+            decimal_sep: options.decimal_sep.clone(),
+            trim_zeros: false,
+            significant_digits: None,
+        }
+    }
+}
+
+pub fn format_int(x: i32, format: &NumberFormat) -> String {
+    apply_separators(x.to_string(), format)
+}
+
+/// Format a finite double. Special values are handled by the callers as they
+/// are represented differently across consumers.
+pub fn format_dbl(x: f64, format: &NumberFormat) -> String {
+    if let Some(digits) = format.significant_digits {
+        return apply_separators(format_dbl_significant(x, digits), format);
+    }
+
+    // The limit for large numbers before switching to scientific
+    // notation
+    let upper_threshold = f64::powf(10.0, format.max_integral_digits as f64);
+
+    // The limit for small numbers before switching to scientific
+    // notation
+    let lower_threshold = f64::powf(10.0, -(format.small_num_digits as f64));
+
+    let large_num_digits = format.large_num_digits;
+    let small_num_digits = format.small_num_digits;
+
+    let abs_x = x.abs();
+
+    let formatted = if abs_x >= upper_threshold {
+        // large numbers use scientific notation
+        // rust makes 1e7 instead of 1e+7 which aligns baddly
+        let v = format!("{:.large_num_digits$e}", x).replace("e", "e+");
+        pad_exponent(v)
+    } else if abs_x >= 1.0 {
+        // this is considered medium numbers and they use a fixed amount of
+        // digits after the decimal point
+        format!("{:.large_num_digits$}", x)
+    } else if abs_x >= lower_threshold {
+        // small numbers but not that small are formatted with a different
+        // amount of digits after the decimal point
+        format!("{:.small_num_digits$}", x)
+    } else if abs_x == 0.0 {
+        // zero is special cased to behave like a medium number.
+        format!("{:.large_num_digits$}", x)
+    } else {
+        // very small numbers use scientific notation
+        let v = format!("{:.large_num_digits$e}", x);
+        pad_exponent(v)
+    };
+
+    let formatted = if format.trim_zeros {
+        trim_zeros(formatted)
+    } else {
+        formatted
+    };
+
+    apply_separators(formatted, format)
+}
+
+// Keeps `digits` significant digits and drops trailing zeros. The fixed
+// notation is preferred unless the scientific notation is shorter, as in
+// `print()` with the default `scipen` option.
+fn format_dbl_significant(x: f64, digits: usize) -> String {
+    if x == 0.0 {
+        return String::from("0");
+    }
+
+    let precision = digits.max(1) - 1;
+
+    // Rounding to the significant digits may bump the exponent, e.g. for
+    // 9.9999999, so it's taken from the rounded scientific notation
+    let scientific = format!("{x:.precision$e}");
+    let Some((mantissa, exponent)) = scientific.split_once('e') else {
+        return scientific;
+    };
+    let Ok(exponent) = exponent.parse::<i32>() else {
+        return scientific;
+    };
+
+    let scientific = if exponent < 0 {
+        format!("{}e-{:02}", trim_zeros(mantissa.to_string()), -exponent)
+    } else {
+        format!("{}e+{:02}", trim_zeros(mantissa.to_string()), exponent)
+    };
+
+    let decimals = (precision as i32 - exponent).max(0) as usize;
+    let fixed = trim_zeros(format!("{x:.decimals$}"));
+
+    if fixed.len() <= scientific.len() {
+        fixed
+    } else {
+        scientific
+    }
+}
+
+/// Format a double, including the R special values
+pub fn format_dbl_elt(x: Option<f64>, format: &NumberFormat) -> String {
+    match x {
+        None => String::from("NA"),
+        Some(v) if r_dbl_is_nan(v) => String::from("NaN"),
+        Some(v) if r_dbl_is_finite(v) => format_dbl(v, format),
+        Some(v) if v > 0.0 => String::from("Inf"),
+        Some(_) => String::from("-Inf"),
+    }
+}
+
+pub fn format_int_elt(x: Option<i32>, format: &NumberFormat) -> String {
+    match x {
+        None => String::from("NA"),
+        Some(v) => format_int(v, format),
+    }
+}
+
+/// Whether `x` is a bare integer or double vector that can be formatted
+/// without dispatching to a `format()` method
+pub fn is_plain_number(x: SEXP) -> bool {
+    matches!(r_typeof(x), INTSXP | REALSXP) && r_classes(x).is_none()
+}
+
+/// Format the elements of a bare numeric vector in `range`. Returns `None`
+/// if `x` isn't a bare numeric vector, see `is_plain_number()`.
+pub fn format_numbers(
+    x: SEXP,
+    range: std::ops::Range<usize>,
+    format: &NumberFormat,
+) -> Option<Vec<String>> {
+    if !is_plain_number(x) {
+        return None;
+    }
+
+    let len = r_length(x) as usize;
+    let range = range.start.min(len)..range.end.min(len);
+
+    let formatted = match r_typeof(x) {
+        REALSXP => {
+            let x = unsafe { NumericVector::new_unchecked(x) };
+            range
+                .map(|i| format_dbl_elt(x.get_unchecked(i as isize), format))
+                .collect()
+        },
+        _ => {
+            let x = unsafe { IntegerVector::new_unchecked(x) };
+            range
+                .map(|i| format_int_elt(x.get_unchecked(i as isize), format))
+                .collect()
+        },
+    };
+
+    Some(formatted)
+}
+
+// The separators are applied to the integral and fractional parts
+// separately, so that the thousands separator can be a `.` when the decimal
+// separator is a `,`. Numbers in scientific notation don't get a thousands
+// separator.
+fn apply_separators(x: String, format: &NumberFormat) -> String {
+    let (integral, fractional) = match x.split_once('.') {
+        Some((integral, fractional)) => (integral.to_string(), Some(fractional)),
+        None => (x.clone(), None),
+    };
+
+    let integral = if x.contains('e') {
+        integral
+    } else {
+        apply_thousands_sep(integral, format.thousands_sep.as_deref())
+    };
+
+    match fractional {
+        Some(fractional) => {
+            let decimal_sep = format.decimal_sep.as_deref().unwrap_or(".");
+            format!("{integral}{decimal_sep}{fractional}")
+        },
+        None => integral,
+    }
+}
+
+pub(crate) fn apply_thousands_sep(x: String, sep: Option<&str>) -> String {
+    match sep {
+        None => x,
+        Some(sep) => {
+            let mut formatted = String::new();
+
+            // Find the decimal point if any
+            let decimal_point = x.find('.').unwrap_or(x.len());
+
+            // Walk backwards on the string to add the thousands separator
+            let mut count: usize = 0;
+            for (i, c) in x.chars().rev().enumerate() {
+                // Now walk backwards until we reach the decimal point.
+                // After the point, start adding the thousands separator.
+                if i < (x.len() - decimal_point) {
+                    formatted.push(c);
+                    continue;
+                }
+
+                // For negative numbers, break the iteration.
+                // `continue` should have the same effect as `break` as there shouldn't exist
+                // any character before `-`.
+                // This avoids '-100' to be formatted as '-,100'.
+                if c == '-' {
+                    formatted.push(c);
+                    continue;
+                }
+
+                // Add a `sep` every three characters
+                if count % 3 == 0 && count != 0 {
+                    formatted.push_str(sep);
+                }
+
+                formatted.push(c);
+                count += 1;
+            }
+            formatted.chars().rev().collect::<String>()
+        },
+    }
+}
+
+// exponents of the scientific notation should have a minimum of length 2
+// to match the other implementations
+// the string must have already been processed to include the e+ in positive values
+pub(crate) fn pad_exponent(x: String) -> String {
+    // find the exponent position
+    let e_pos = match x.find('e') {
+        Some(v) => v,
+        None => return x, // if no e is found, return the string as is
+    };
+
+    // "1e-12" the e_pos (1) + 3 is < x.len() (5)
+    // "1e-1"  the e_pos (1) + 3 is == x.len() (4)
+    if (e_pos + 1 + 2) < x.len() {
+        return x; // the exponent already have 2 digits
+    }
+
+    // add zeros to the exponent
+    let mut formatted = x;
+    formatted.insert(e_pos + 2, '0');
+
+    formatted
+}
+
+// Drop the trailing zeros of the decimal places, and the decimal point if
+// no decimal places are left. The exponent is left untouched.
+fn trim_zeros(x: String) -> String {
+    let (mantissa, exponent) = match x.find('e') {
+        Some(e_pos) => x.split_at(e_pos),
+        None => (x.as_str(), ""),
+    };
+
+    if !mantissa.contains('.') {
+        return x;
+    }
+
+    let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+    format!("{mantissa}{exponent}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r_task;
+
+    #[test]
+    fn test_pad_exponents() {
+        assert_eq!(pad_exponent("1.00e+111".to_string()), "1.00e+111");
+        assert_eq!(pad_exponent("1.00e+11".to_string()), "1.00e+11");
+        assert_eq!(pad_exponent("1.00e+01".to_string()), "1.00e+01");
+        assert_eq!(pad_exponent("1.00e+1".to_string()), "1.00e+01");
+        assert_eq!(pad_exponent("1.00e+00".to_string()), "1.00e+00");
+        assert_eq!(pad_exponent("1.00e-01".to_string()), "1.00e-01");
+        assert_eq!(pad_exponent("1.00e-1".to_string()), "1.00e-01");
+        assert_eq!(pad_exponent("1.00e-00".to_string()), "1.00e-00");
+    }
+
+    #[test]
+    fn test_thousands_sep() {
+        assert_eq!(
+            apply_thousands_sep("1000000".to_string(), Some(",")),
+            "1,000,000"
+        );
+        assert_eq!(
+            apply_thousands_sep("1000000.000".to_string(), Some(",")),
+            "1,000,000.000"
+        );
+        assert_eq!(apply_thousands_sep("1.00".to_string(), Some(",")), "1.00");
+        assert_eq!(
+            apply_thousands_sep("1000.00".to_string(), Some(",")),
+            "1,000.00"
+        );
+        assert_eq!(
+            apply_thousands_sep("100.00".to_string(), Some(",")),
+            "100.00"
+        );
+        assert_eq!(
+            apply_thousands_sep("1000000.00".to_string(), None),
+            "1000000.00"
+        );
+        assert_eq!(apply_thousands_sep("-100".to_string(), Some(",")), "-100");
+        assert_eq!(
+            apply_thousands_sep("-100000".to_string(), Some(",")),
+            "-100,000"
+        );
+        assert_eq!(
+            apply_thousands_sep("-100000.00".to_string(), Some(",")),
+            "-100,000.00"
+        );
+    }
+
+    #[test]
+    fn test_decimal_sep() {
+        let format = NumberFormat {
+            large_num_digits: 2,
+            small_num_digits: 4,
+            max_integral_digits: 7,
+            thousands_sep: Some(".".to_string()),
+            decimal_sep: Some(",".to_string()),
+            trim_zeros: false,
+            significant_digits: None,
+        };
+        assert_eq!(format_dbl(1234567.891, &format), "1.234.567,89");
+        assert_eq!(format_dbl(-0.5, &format), "-0,5000");
+        assert_eq!(format_dbl(1e10, &format), "1,00e+10");
+        assert_eq!(format_int(-1234, &format), "-1.234");
+
+        assert_eq!(format.normalize_separators("1.234.567,89"), "1234567.89");
+        assert_eq!(format.normalize_separators(" -0,5 "), "-0.5");
+        assert_eq!(format.normalize_separators("1,00e+10"), "1.00e+10");
+
+        // The default separators
+        let format = NumberFormat {
+            thousands_sep: Some(",".to_string()),
+            decimal_sep: None,
+            ..format
+        };
+        assert_eq!(format_dbl(1234.5, &format), "1,234.50");
+        assert_eq!(format.normalize_separators("1,234.5"), "1234.5");
+    }
+
+    #[test]
+    fn test_trim_zeros() {
+        let format = NumberFormat::variables();
+        assert_eq!(format_dbl(123.0, &format), "123");
+        assert_eq!(format_dbl(1.5, &format), "1.5");
+        assert_eq!(format_dbl(-0.25, &format), "-0.25");
+        assert_eq!(format_dbl(0.0, &format), "0");
+        assert_eq!(format_dbl(1e20, &format), "1e+20");
+        assert_eq!(format_dbl(1.5e-10, &format), "1.5e-10");
+        assert_eq!(trim_zeros("100".to_string()), "100");
+    }
+
+    #[test]
+    fn test_significant_digits() {
+        let format = NumberFormat::variables();
+        assert_eq!(format_dbl(1.2345e-5, &format), "1.2345e-05");
+        assert_eq!(format_dbl(1.0 / 3.0, &format), "0.3333333");
+        assert_eq!(format_dbl(-2.0 / 3.0, &format), "-0.6666667");
+        assert_eq!(format_dbl(123456789.0, &format), "123456789");
+        assert_eq!(format_dbl(1234.56789, &format), "1234.568");
+        assert_eq!(format_dbl(0.0001, &format), "1e-04");
+        assert_eq!(format_dbl(0.001, &format), "0.001");
+        assert_eq!(format_dbl(9.99999999, &format), "10");
+        assert_eq!(format_dbl(1e15, &format), "1e+15");
+        assert_eq!(format_dbl(1e-300, &format), "1e-300");
+    }
+
+    #[test]
+    fn test_format_numbers() {
+        r_task(|| {
+            let format = NumberFormat::variables();
+
+            let x = harp::parse_eval_global("c(1, NA, NaN, Inf, -Inf, 1/3)").unwrap();
+            assert_eq!(format_numbers(x.sexp, 0..10, &format).unwrap(), vec![
+                "1", "NA", "NaN", "Inf", "-Inf", "0.3333333"
+            ]);
+
+            let x = harp::parse_eval_global("c(1L, NA, 1000000L)").unwrap();
+            assert_eq!(format_numbers(x.sexp, 1..3, &format).unwrap(), vec![
+                "NA", "1000000"
+            ]);
+
+            // Classed vectors are formatted with their `format()` method
+            let x = harp::parse_eval_global("factor('a')").unwrap();
+            assert!(format_numbers(x.sexp, 0..1, &format).is_none());
+        })
+    }
+}
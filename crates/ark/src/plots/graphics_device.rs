@@ -23,6 +23,7 @@ use amalthea::comm::plot_comm::PlotRenderFormat;
 use amalthea::comm::plot_comm::PlotRenderSettings;
 use amalthea::comm::plot_comm::PlotResult;
 use amalthea::comm::plot_comm::PlotSize;
+use amalthea::comm::plot_comm::RenderParams;
 use amalthea::comm::plot_comm::UpdateParams;
 use amalthea::socket::comm::CommInitiator;
 use amalthea::socket::comm::CommSocket;
@@ -401,18 +402,7 @@ impl DeviceContext {
             PlotBackendRequest::Render(plot_meta) => {
                 log::trace!("PlotBackendRequest::Render");
 
-                let size = unwrap!(plot_meta.size, None => {
-                    return Err(anyhow!("Intrinsically sized plots are not yet supported."));
-                });
-
-                let settings = PlotRenderSettings {
-                    size: PlotSize {
-                        width: size.width,
-                        height: size.height,
-                    },
-                    pixel_ratio: plot_meta.pixel_ratio,
-                    format: plot_meta.format,
-                };
+                let settings = render_settings(&plot_meta, self.prerender_settings.get());
 
                 let data = self.render_plot(&id, &settings)?;
                 let mime_type = Self::get_mime_type(&plot_meta.format);
@@ -783,6 +773,21 @@ impl From<&PlotId> for RObject {
     }
 }
 
+/// Settings of a plot rendered at the request of the frontend. R plots don't
+/// have an intrinsic size, they fill the device they are replayed on. Without
+/// a requested size, we render at the size of the pre-renderings so the plot
+/// looks the same as when it was created.
+fn render_settings(
+    params: &RenderParams,
+    prerender_settings: PlotRenderSettings,
+) -> PlotRenderSettings {
+    PlotRenderSettings {
+        size: params.size.unwrap_or(prerender_settings.size),
+        pixel_ratio: params.pixel_ratio,
+        format: params.format,
+    }
+}
+
 /// Hook applied at idle time (`R_ProcessEvents()` time) to process any outstanding
 /// RPC requests from Positron
 ///
@@ -995,3 +1000,57 @@ unsafe extern "C-unwind" fn ps_graphics_before_plot_new(_name: SEXP) -> anyhow::
 
     Ok(harp::r_null())
 }
+
+#[cfg(test)]
+mod tests {
+    use amalthea::comm::plot_comm::PlotRenderFormat;
+    use amalthea::comm::plot_comm::PlotRenderSettings;
+    use amalthea::comm::plot_comm::PlotSize;
+    use amalthea::comm::plot_comm::RenderParams;
+
+    use crate::plots::graphics_device::render_settings;
+
+    #[test]
+    fn test_render_settings() {
+        let prerender_settings = PlotRenderSettings {
+            size: PlotSize {
+                width: 640,
+                height: 400,
+            },
+            pixel_ratio: 1.0,
+            format: PlotRenderFormat::Png,
+        };
+
+        // The requested size, pixel ratio, and format are used
+        let params = RenderParams {
+            size: Some(PlotSize {
+                width: 800,
+                height: 600,
+            }),
+            pixel_ratio: 2.0,
+            format: PlotRenderFormat::Svg,
+        };
+        assert_eq!(
+            render_settings(&params, prerender_settings),
+            PlotRenderSettings {
+                size: PlotSize {
+                    width: 800,
+                    height: 600,
+                },
+                pixel_ratio: 2.0,
+                format: PlotRenderFormat::Svg,
+            }
+        );
+
+        // Without a requested size, the pre-rendering size is used
+        let params = RenderParams {
+            size: None,
+            pixel_ratio: 2.0,
+            format: PlotRenderFormat::Svg,
+        };
+        let settings = render_settings(&params, prerender_settings);
+        assert_eq!(settings.size, prerender_settings.size);
+        assert_eq!(settings.pixel_ratio, 2.0);
+        assert_eq!(settings.format, PlotRenderFormat::Svg);
+    }
+}
@@ -0,0 +1,270 @@
+//
+// profiler.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use std::collections::HashMap;
+
+use amalthea::comm::ui_comm::ProfileNode;
+use amalthea::comm::ui_comm::ProfileParams;
+use amalthea::comm::ui_comm::UiFrontendEvent;
+use anyhow::anyhow;
+use harp::object::RObject;
+use libr::SEXP;
+
+use crate::interface::RMain;
+
+/// Bytes per vector cell and per cons cell, as in `profvis`
+const VCELL_BYTES: i64 = 8;
+const NODE_BYTES: i64 = 56;
+
+/// Parses the output of `Rprof()` into a call tree. Supports the memory and
+/// line profiling formats enabled by `profvis`:
+///
+/// ```text
+/// memory profiling: line profiling: sample.interval=10000
+/// #File 1: /path/to/file.R
+/// :123:4:5678:0:1#2 "f" 1#5 "g"
+/// ```
+///
+/// Stacks are recorded from the innermost call to the outermost one. A
+/// `file#line` reference precedes the name of the function whose line is
+/// being executed.
+pub fn parse_rprof(contents: &str) -> anyhow::Result<ProfileParams> {
+    let mut lines = contents.lines();
+
+    let header = lines.next().ok_or_else(|| anyhow!("Empty profile"))?;
+    let interval = header
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix("sample.interval="))
+        .ok_or_else(|| anyhow!("Can't find the sampling interval in '{header}'"))?;
+    let interval: f64 = interval.parse()?;
+
+    let mut files: HashMap<&str, String> = HashMap::new();
+    let mut root = new_node(String::from("<root>"), None, None);
+    let mut last_memory: Option<i64> = None;
+
+    for line in lines {
+        if let Some(file) = line.strip_prefix("#File ") {
+            if let Some((id, path)) = file.split_once(": ") {
+                files.insert(id, path.to_string());
+            }
+            continue;
+        }
+
+        // Headers are repeated when profiling is resumed with `append = TRUE`
+        if line.contains("sample.interval=") {
+            continue;
+        }
+
+        let (memory, stack) = parse_memory(line);
+
+        // Memory allocated since the last sample. Freed memory doesn't count.
+        let alloc = match (memory, last_memory) {
+            (Some(memory), Some(last)) => (memory - last).max(0),
+            _ => 0,
+        };
+        if memory.is_some() {
+            last_memory = memory;
+        }
+
+        let mut frames = parse_stack(stack, &files);
+        frames.reverse();
+
+        add_sample(&mut root, frames, alloc);
+    }
+
+    Ok(ProfileParams {
+        // Microseconds to milliseconds
+        interval: interval / 1000.0,
+        root,
+    })
+}
+
+fn new_node(name: String, file: Option<String>, line: Option<i64>) -> ProfileNode {
+    ProfileNode {
+        name,
+        file,
+        line,
+        samples: 0,
+        self_samples: 0,
+        memory_alloc: 0,
+        children: vec![],
+    }
+}
+
+/// Splits the `:small_vcells:large_vcells:nodes:duplications:` prefix of
+/// memory profiling from the stack and returns the memory in use in bytes
+fn parse_memory(line: &str) -> (Option<i64>, &str) {
+    let Some(rest) = line.strip_prefix(':') else {
+        return (None, line);
+    };
+
+    let fields: Vec<&str> = rest.splitn(5, ':').collect();
+    let [small, large, nodes, _duplications, stack] = fields.as_slice() else {
+        return (None, line);
+    };
+
+    let memory = || -> Option<i64> {
+        let vcells = small.parse::<i64>().ok()? + large.parse::<i64>().ok()?;
+        Some(vcells * VCELL_BYTES + nodes.parse::<i64>().ok()? * NODE_BYTES)
+    };
+
+    (memory(), *stack)
+}
+
+/// Returns the frames of the stack as `(name, file, line)`, from the
+/// innermost to the outermost
+fn parse_stack(
+    stack: &str,
+    files: &HashMap<&str, String>,
+) -> Vec<(String, Option<String>, Option<i64>)> {
+    let mut frames = vec![];
+    let mut location: Option<(Option<String>, Option<i64>)> = None;
+    let mut rest = stack.trim();
+
+    while !rest.is_empty() {
+        if let Some(quoted) = rest.strip_prefix('"') {
+            // Function names are quoted and may contain spaces
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            let name = quoted[..end].to_string();
+            rest = quoted.get(end + 1..).unwrap_or_default().trim_start();
+
+            let (file, line) = location.take().unwrap_or((None, None));
+            frames.push((name, file, line));
+        } else {
+            // A `file#line` reference to the line being executed in the
+            // next function
+            let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let reference = &rest[..end];
+            rest = rest[end..].trim_start();
+
+            if let Some((file, line)) = reference.split_once('#') {
+                location = Some((files.get(file).cloned(), line.parse().ok()));
+            }
+        }
+    }
+
+    frames
+}
+
+/// Adds a sample to the tree. `frames` go from the outermost call to the
+/// innermost one.
+fn add_sample(
+    root: &mut ProfileNode,
+    frames: Vec<(String, Option<String>, Option<i64>)>,
+    alloc: i64,
+) {
+    root.samples += 1;
+    root.memory_alloc += alloc;
+
+    let mut node = root;
+
+    for (name, file, line) in frames {
+        let index = node
+            .children
+            .iter()
+            .position(|child| child.name == name && child.file == file && child.line == line);
+
+        let index = match index {
+            Some(index) => index,
+            None => {
+                node.children.push(new_node(name, file, line));
+                node.children.len() - 1
+            },
+        };
+
+        node = &mut node.children[index];
+        node.samples += 1;
+        node.memory_alloc += alloc;
+    }
+
+    node.self_samples += 1;
+}
+
+/// Parses the profile at `path` and sends it to the frontend
+#[harp::register]
+pub unsafe extern "C-unwind" fn ps_profiler_send_profile(path: SEXP) -> anyhow::Result<SEXP> {
+    let path: String = RObject::view(path).try_into()?;
+    let profile = parse_rprof(&std::fs::read_to_string(&path)?)?;
+    let samples = profile.root.samples;
+
+    let main = RMain::get();
+    let ui_comm_tx = main
+        .get_ui_comm_tx()
+        .ok_or_else(|| anyhow!("UI comm not connected, can't send the profile."))?;
+    ui_comm_tx.send_event(UiFrontendEvent::Profile(profile));
+
+    Ok(RObject::from(samples as i32).sexp)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::profiler::parse_rprof;
+
+    #[test]
+    fn test_parse_rprof() {
+        let contents = r#"memory profiling: line profiling: sample.interval=10000
+#File 1: /path/to/file.R
+:100:0:10:0:1#2 "f" 1#5 "g"
+:200:0:10:0:"Sys.sleep" 1#2 "f" 1#5 "g"
+:150:0:10:0:1#6 "g"
+:150:0:10:0:
+"#;
+
+        let profile = parse_rprof(contents).unwrap();
+        assert_eq!(profile.interval, 10.0);
+
+        let root = &profile.root;
+        assert_eq!(root.samples, 4);
+        assert_eq!(root.self_samples, 1);
+
+        // `g` is split by the line being executed
+        assert_eq!(root.children.len(), 2);
+
+        let g = &root.children[0];
+        assert_eq!(g.name, "g");
+        assert_eq!(g.file.as_deref(), Some("/path/to/file.R"));
+        assert_eq!(g.line, Some(5));
+        assert_eq!(g.samples, 2);
+        assert_eq!(g.self_samples, 0);
+
+        let f = &g.children[0];
+        assert_eq!(f.name, "f");
+        assert_eq!(f.line, Some(2));
+        assert_eq!(f.samples, 2);
+        assert_eq!(f.self_samples, 1);
+
+        // Allocations are attributed to the whole stack
+        let sleep = &f.children[0];
+        assert_eq!(sleep.name, "Sys.sleep");
+        assert_eq!(sleep.file, None);
+        assert_eq!(sleep.memory_alloc, 800);
+        assert_eq!(g.memory_alloc, 800);
+
+        assert_eq!(root.children[1].line, Some(6));
+        assert_eq!(root.children[1].memory_alloc, 0);
+    }
+
+    #[test]
+    fn test_parse_rprof_without_memory() {
+        let contents = "sample.interval=20000\n\"f\" \"<Anonymous>\" \n\"f\" \"<Anonymous>\" \n";
+
+        let profile = parse_rprof(contents).unwrap();
+        assert_eq!(profile.interval, 20.0);
+
+        let anonymous = &profile.root.children[0];
+        assert_eq!(anonymous.name, "<Anonymous>");
+        assert_eq!(anonymous.samples, 2);
+        assert_eq!(anonymous.children[0].self_samples, 2);
+        assert_eq!(anonymous.memory_alloc, 0);
+    }
+
+    #[test]
+    fn test_parse_rprof_invalid() {
+        assert!(parse_rprof("").is_err());
+        assert!(parse_rprof("memory profiling: \n").is_err());
+    }
+}
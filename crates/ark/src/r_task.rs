@@ -7,11 +7,14 @@
 
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::sync::LazyLock;
 use std::sync::Mutex;
 use std::time::Duration;
 
+use anyhow::anyhow;
 use crossbeam::channel::bounded;
 use crossbeam::channel::unbounded;
 use crossbeam::channel::Receiver;
@@ -24,9 +27,17 @@ use crate::interface::RMain;
 /// Task channels for interrupt-time tasks
 static INTERRUPT_TASKS: LazyLock<TaskChannels> = LazyLock::new(|| TaskChannels::new());
 
+/// Task channels for interrupt-time tasks that yield to interactive ones
+static BACKGROUND_TASKS: LazyLock<TaskChannels> = LazyLock::new(|| TaskChannels::new());
+
 /// Task channels for idle-time tasks
 static IDLE_TASKS: LazyLock<TaskChannels> = LazyLock::new(|| TaskChannels::new());
 
+/// Time tasks spent waiting in their queue before being picked up by the R
+/// thread
+static QUEUE_LATENCY: LazyLock<Mutex<[QueueLatency; 3]>> =
+    LazyLock::new(|| Mutex::new([QueueLatency::default(); 3]));
+
 // Compared to `futures::BoxFuture`, this doesn't require the future to be Send.
 // We don't need this bound since the executor runs on only on the R thread
 pub(crate) type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
@@ -58,11 +69,106 @@ impl TaskChannels {
     }
 }
 
-/// Returns receivers for both interrupt and idle tasks.
+/// The lane a task is queued on
+///
+/// Interactive and background tasks both run at interrupt time, but pending
+/// interactive tasks (e.g. completions or hover requests) are always picked up
+/// first so that slow background tasks (e.g. refreshing the variables pane)
+/// can't delay them. Idle tasks only run when R is waiting for input at top
+/// level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RTaskLane {
+    Interactive,
+    Background,
+    Idle,
+}
+
+impl RTaskLane {
+    fn channels(&self) -> &'static TaskChannels {
+        match self {
+            RTaskLane::Interactive => &INTERRUPT_TASKS,
+            RTaskLane::Background => &BACKGROUND_TASKS,
+            RTaskLane::Idle => &IDLE_TASKS,
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            RTaskLane::Interactive => 0,
+            RTaskLane::Background => 1,
+            RTaskLane::Idle => 2,
+        }
+    }
+}
+
+/// Cancellation token for R tasks
+///
+/// The R thread checks the token before starting a task and, for async tasks,
+/// before each poll of the future. Cancelled tasks are dropped without running
+/// any further. A task that is already running is not interrupted.
+#[derive(Clone, Debug, Default)]
+pub struct RTaskToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl RTaskToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// Statistics on the time tasks of a lane waited before running
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct QueueLatency {
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl QueueLatency {
+    pub(crate) fn mean(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        self.total.div_f64(self.count as f64)
+    }
+}
+
+/// Number of tasks waiting to run on the R thread in `lane`
+pub(crate) fn pending_tasks(lane: RTaskLane) -> usize {
+    lane.channels().tx.len()
+}
+
+pub(crate) fn queue_latency(lane: RTaskLane) -> QueueLatency {
+    QUEUE_LATENCY.lock().unwrap()[lane.index()]
+}
+
+fn record_queue_latency(lane: RTaskLane, wait: Duration) {
+    let mut latencies = QUEUE_LATENCY.lock().unwrap();
+    let latency = &mut latencies[lane.index()];
+
+    latency.count += 1;
+    latency.total += wait;
+    latency.max = latency.max.max(wait);
+}
+
+/// Returns receivers for interactive, background, and idle tasks.
 /// Initializes the task channels if they haven't been initialized yet.
 /// Can only be called once (intended for `RMain` during init).
-pub(crate) fn take_receivers() -> (Receiver<RTask>, Receiver<RTask>) {
-    (INTERRUPT_TASKS.take_rx(), IDLE_TASKS.take_rx())
+pub(crate) fn take_receivers() -> (Receiver<RTask>, Receiver<RTask>, Receiver<RTask>) {
+    (
+        INTERRUPT_TASKS.take_rx(),
+        BACKGROUND_TASKS.take_rx(),
+        IDLE_TASKS.take_rx(),
+    )
 }
 
 pub enum RTask {
@@ -75,12 +181,14 @@ pub struct RTaskSync {
     pub fun: Box<dyn FnOnce() + Send + 'static>,
     pub status_tx: Option<Sender<RTaskStatus>>,
     pub start_info: RTaskStartInfo,
+    pub token: Option<RTaskToken>,
 }
 
 pub struct RTaskAsync {
     pub fut: BoxFuture<'static, ()>,
     pub tasks_tx: Sender<RTask>,
     pub start_info: RTaskStartInfo,
+    pub token: Option<RTaskToken>,
 }
 
 #[derive(Clone)]
@@ -88,11 +196,13 @@ pub struct RTaskWaker {
     pub id: Uuid,
     pub tasks_tx: Sender<RTask>,
     pub start_info: RTaskStartInfo,
+    pub token: Option<RTaskToken>,
 }
 
 #[derive(Debug)]
 pub enum RTaskStatus {
     Started,
+    Cancelled,
     Finished(harp::error::Result<()>),
 }
 
@@ -102,6 +212,9 @@ pub struct RTaskStartInfo {
     pub thread_name: String,
     pub start_time: std::time::Instant,
 
+    /// The lane the task was queued on
+    pub lane: RTaskLane,
+
     /// Time it took to run the time. Used to record time accumulated while
     /// running an async task in the executor. Optional because elapsed time is
     /// computed more simply from start time in other cases.
@@ -119,6 +232,34 @@ impl RTask {
             RTask::Parked(_) => None,
         }
     }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        let token = match self {
+            RTask::Sync(task) => &task.token,
+            RTask::Async(task) => &task.token,
+            RTask::Parked(waker) => &waker.token,
+        };
+        token.as_ref().is_some_and(|token| token.is_cancelled())
+    }
+
+    /// Records how long the task waited in its queue. Called when the task is
+    /// picked up by the R thread, before it starts running.
+    pub(crate) fn record_queue_latency(&self) {
+        if let Some((lane, wait)) = self.queue_wait() {
+            record_queue_latency(lane, wait);
+        }
+    }
+
+    fn queue_wait(&self) -> Option<(RTaskLane, Duration)> {
+        let start_info = match self {
+            RTask::Sync(task) => &task.start_info,
+            RTask::Async(task) => &task.start_info,
+            // Parked tasks are async tasks woken up after they were first
+            // dispatched. Their wait was recorded at that point.
+            RTask::Parked(_) => return None,
+        };
+        Some((start_info.lane, start_info.start_time.elapsed()))
+    }
 }
 
 // RTaskAsync is not Send because of the Future variant which doesn't require
@@ -135,7 +276,7 @@ impl std::task::Wake for RTaskWaker {
 }
 
 impl RTaskStartInfo {
-    pub(crate) fn new(idle: bool) -> Self {
+    pub(crate) fn new(lane: RTaskLane) -> Self {
         let thread = std::thread::current();
         let thread_id = thread.id();
         let thread_name = thread
@@ -145,12 +286,13 @@ impl RTaskStartInfo {
             .to_owned();
 
         let start_time = std::time::Instant::now();
-        let span = tracing::trace_span!("R task", thread = thread_name, interrupt = !idle,);
+        let span = tracing::trace_span!("R task", thread = thread_name, lane = ?lane);
 
         Self {
             thread_id,
             thread_name,
             start_time,
+            lane,
             elapsed_time: None,
             span,
         }
@@ -185,20 +327,58 @@ where
     F: 'env + Send,
     T: 'env + Send,
 {
+    // Can't be `None` without a cancellation token
+    r_task_ext(f, RTaskLane::Interactive, None).unwrap()
+}
+
+/// Like `r_task()` but the task yields to pending interactive tasks
+///
+/// Use this for tasks that aren't waited on by the user, e.g. refreshing the
+/// variables pane after each execution.
+pub fn r_task_background<'env, F, T>(f: F) -> T
+where
+    F: FnOnce() -> T,
+    F: 'env + Send,
+    T: 'env + Send,
+{
+    r_task_ext(f, RTaskLane::Background, None).unwrap()
+}
+
+/// Like `r_task()` but the task doesn't run if `token` is cancelled before
+/// the R thread gets to it, in which case `None` is returned
+pub fn r_task_cancellable<'env, F, T>(token: &RTaskToken, lane: RTaskLane, f: F) -> Option<T>
+where
+    F: FnOnce() -> T,
+    F: 'env + Send,
+    T: 'env + Send,
+{
+    r_task_ext(f, lane, Some(token.clone()))
+}
+
+fn r_task_ext<'env, F, T>(f: F, lane: RTaskLane, token: Option<RTaskToken>) -> Option<T>
+where
+    F: FnOnce() -> T,
+    F: 'env + Send,
+    T: 'env + Send,
+{
+    if token.as_ref().is_some_and(|token| token.is_cancelled()) {
+        return None;
+    }
+
     // Escape hatch for unit tests
     // In integration tests with dummy frontends, we have a "real" RMain and want to
     // go through the standard r-task path
     if stdext::IS_TESTING && !RMain::is_initialized() {
         let _lock = harp::fixtures::R_TEST_LOCK.lock();
         r_test_init();
-        return f();
+        return Some(harp::protect::with_protect_audit(f));
     }
 
     // Recursive case: If we're on ark-r-main already, just run the
     // task and return. This allows `r_task(|| { r_task(|| {}) })`
     // to run without deadlocking.
     if RMain::on_main_thread() {
-        return f();
+        return Some(f());
     }
 
     // The following is adapted from `Crossbeam::thread::ScopedThreadBuilder`.
@@ -229,13 +409,19 @@ where
         let task = RTask::Sync(RTaskSync {
             fun: closure,
             status_tx: Some(status_tx),
-            start_info: RTaskStartInfo::new(false),
+            start_info: RTaskStartInfo::new(lane),
+            token,
         });
-        INTERRUPT_TASKS.tx().send(task).unwrap();
+        lane.channels().tx().send(task).unwrap();
 
-        // Block until we get the signal that the task has started
+        // Block until we get the signal that the task has started. The closure
+        // has already been dropped if the task was cancelled.
         let status = status_rx.recv().unwrap();
 
+        if let RTaskStatus::Cancelled = status {
+            return None;
+        }
+
         let RTaskStatus::Started = status else {
             let trace = std::backtrace::Backtrace::force_capture();
             panic!(
@@ -271,7 +457,54 @@ where
 
     // Retrieve closure result from the synchronized shared option.
     // If we get here without panicking we know the result was assigned.
-    return result.lock().unwrap().take().unwrap();
+    return Some(result.lock().unwrap().take().unwrap());
+}
+
+/// Like `r_task()` but returns a future instead of blocking the calling thread
+///
+/// This lets async code, e.g. LSP handlers running on Tokio, wait for R
+/// without tying up a worker thread. Since the caller isn't blocked, the
+/// closure can't borrow from its stack and must be `'static`.
+///
+/// R errors and longjumps are caught on the R thread and resolve the future
+/// with an error.
+pub fn r_task_async<F, T>(f: F) -> impl Future<Output = anyhow::Result<T>>
+where
+    F: FnOnce() -> T,
+    F: 'static + Send,
+    T: 'static + Send,
+{
+    let (result_tx, result_rx) = futures::channel::oneshot::channel::<harp::Result<T>>();
+
+    // Catch R errors inside the closure so that `result_tx` is never jumped
+    // over and always either sends a result or gets dropped
+    let closure = move || {
+        let _ = result_tx.send(harp::try_catch(f));
+    };
+
+    if stdext::IS_TESTING && !RMain::is_initialized() {
+        // Escape hatch for unit tests
+        let _lock = harp::fixtures::R_TEST_LOCK.lock();
+        r_test_init();
+        harp::protect::with_protect_audit(closure);
+    } else if RMain::on_main_thread() {
+        closure();
+    } else {
+        let task = RTask::Sync(RTaskSync {
+            fun: Box::new(closure),
+            status_tx: None,
+            start_info: RTaskStartInfo::new(RTaskLane::Interactive),
+            token: None,
+        });
+        INTERRUPT_TASKS.tx().send(task).unwrap();
+    }
+
+    async move {
+        match result_rx.await {
+            Ok(result) => Ok(result?),
+            Err(_) => Err(anyhow!("R task was dropped before completing")),
+        }
+    }
 }
 
 pub(crate) fn spawn_idle<F, Fut>(fun: F)
@@ -279,7 +512,17 @@ where
     F: FnOnce() -> Fut + 'static + Send,
     Fut: Future<Output = ()> + 'static,
 {
-    spawn_ext(fun, true)
+    spawn_ext(fun, RTaskLane::Idle, None)
+}
+
+/// Like `spawn_idle()` but the task stops at the next await point once
+/// `token` is cancelled
+pub(crate) fn spawn_idle_cancellable<F, Fut>(token: &RTaskToken, fun: F)
+where
+    F: FnOnce() -> Fut + 'static + Send,
+    Fut: Future<Output = ()> + 'static,
+{
+    spawn_ext(fun, RTaskLane::Idle, Some(token.clone()))
 }
 
 pub(crate) fn spawn_interrupt<F, Fut>(fun: F)
@@ -287,32 +530,32 @@ where
     F: FnOnce() -> Fut + 'static + Send,
     Fut: Future<Output = ()> + 'static,
 {
-    spawn_ext(fun, false)
+    spawn_ext(fun, RTaskLane::Interactive, None)
 }
 
-fn spawn_ext<F, Fut>(fun: F, only_idle: bool)
+fn spawn_ext<F, Fut>(fun: F, lane: RTaskLane, token: Option<RTaskToken>)
 where
     F: FnOnce() -> Fut + 'static + Send,
     Fut: Future<Output = ()> + 'static,
 {
     // Escape hatch for unit tests
     if stdext::IS_TESTING && !RMain::is_initialized() {
+        if token.as_ref().is_some_and(|token| token.is_cancelled()) {
+            return;
+        }
         let _lock = harp::fixtures::R_TEST_LOCK.lock();
         futures::executor::block_on(fun());
         return;
     }
 
-    let tasks_tx = if only_idle {
-        IDLE_TASKS.tx()
-    } else {
-        INTERRUPT_TASKS.tx()
-    };
+    let tasks_tx = lane.channels().tx();
 
     // Send the async task to the R thread
     let task = RTask::Async(RTaskAsync {
         fut: Box::pin(fun()) as BoxFuture<'static, ()>,
         tasks_tx: tasks_tx.clone(),
-        start_info: RTaskStartInfo::new(only_idle),
+        start_info: RTaskStartInfo::new(lane),
+        token,
     });
 
     tasks_tx.send(task).unwrap();
@@ -320,3 +563,50 @@ where
 
 // Tests are tricky because `harp::fixtures::r_test_init()` is very bare bones and
 // doesn't have an `R_MAIN` or `R_MAIN_TASKS_TX`.
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crossbeam::channel::unbounded;
+    use uuid::Uuid;
+
+    use crate::r_task::r_task_async;
+    use crate::r_task::RTask;
+    use crate::r_task::RTaskLane;
+    use crate::r_task::RTaskStartInfo;
+    use crate::r_task::RTaskSync;
+    use crate::r_task::RTaskWaker;
+
+    #[test]
+    fn test_r_task_async() {
+        let value = futures::executor::block_on(r_task_async(|| 1 + 1)).unwrap();
+        assert_eq!(value, 2);
+
+        // R errors resolve the future with an error
+        let out = futures::executor::block_on(r_task_async(|| -> i32 {
+            unsafe { libr::Rf_error(c"ouch".as_ptr()) }
+        }));
+        assert!(out.unwrap_err().to_string().contains("ouch"));
+    }
+    #[test]
+    fn test_r_task_queue_wait_recorded_on_first_dispatch() {
+        let task = RTask::Sync(RTaskSync {
+            fun: Box::new(|| {}),
+            status_tx: None,
+            start_info: RTaskStartInfo::new(RTaskLane::Idle),
+            token: None,
+        });
+        assert!(task.queue_wait().is_some());
+
+        // Wakes of a parked task don't count as another wait
+        let (tasks_tx, _tasks_rx) = unbounded();
+        let task = RTask::Parked(Arc::new(RTaskWaker {
+            id: Uuid::new_v4(),
+            tasks_tx,
+            start_info: RTaskStartInfo::new(RTaskLane::Idle),
+            token: None,
+        }));
+        assert!(task.queue_wait().is_none());
+    }
+}
@@ -10,6 +10,7 @@ use amalthea::wire::execute_request::ExecuteRequest;
 use amalthea::wire::originator::Originator;
 use crossbeam::channel::Sender;
 
+use crate::shutdown::ShutdownProgress;
 use crate::ui::UiCommMessage;
 
 /// Represents requests to the primary R execution thread.
@@ -17,19 +18,20 @@ use crate::ui::UiCommMessage;
 pub enum RRequest {
     /// Fulfill an execution request from the frontend, producing either a
     /// Reply or an Exception
-    ExecuteCode(
-        ExecuteRequest,
-        Originator,
-        Sender<amalthea::Result<ExecuteReply>>,
-    ),
+    ExecuteCode(ExecuteRequest, Originator, Sender<ExecuteCodeReply>),
 
-    /// Shut down the R execution thread
-    Shutdown(bool),
+    /// Shut down the R execution thread. The boolean indicates a restart.
+    /// Progress of the shutdown sequence is reported to the optional sender.
+    Shutdown(bool, Option<Sender<ShutdownProgress>>),
 
     /// Commands from the debugger frontend
     DebugCommand(DebugRequest),
 }
 
+/// The reply to an `ExecuteCode` request, along with the metadata sent in the
+/// metadata frame of the reply message
+pub type ExecuteCodeReply = (amalthea::Result<ExecuteReply>, Option<serde_json::Value>);
+
 #[derive(Debug, Clone)]
 pub enum DebugRequest {
     Continue,
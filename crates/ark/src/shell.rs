@@ -35,7 +35,6 @@ use harp::line_ending::LineEnding;
 use harp::object::RObject;
 use harp::ParseResult;
 use log::*;
-use serde_json::json;
 use stdext::unwrap;
 use tokio::sync::mpsc::UnboundedSender as AsyncUnboundedSender;
 
@@ -44,8 +43,11 @@ use crate::help::r_help::RHelp;
 use crate::help_proxy;
 use crate::interface::KernelInfo;
 use crate::interface::RMain;
+use crate::logger_comm::LogComm;
+use crate::lsp;
 use crate::plots::graphics_device::GraphicsDeviceNotification;
 use crate::r_task;
+use crate::request::ExecuteCodeReply;
 use crate::request::KernelRequest;
 use crate::request::RRequest;
 use crate::ui::UiComm;
@@ -59,6 +61,8 @@ pub struct Shell {
     kernel_init_rx: BusReader<KernelInfo>,
     kernel_info: Option<KernelInfo>,
     graphics_device_tx: AsyncUnboundedSender<GraphicsDeviceNotification>,
+    /// Metadata of the reply to the last execute request
+    execute_reply_metadata: Option<serde_json::Value>,
 }
 
 #[derive(Debug)]
@@ -84,6 +88,7 @@ impl Shell {
             kernel_init_rx,
             kernel_info: None,
             graphics_device_tx,
+            execute_reply_metadata: None,
         }
     }
 
@@ -154,18 +159,13 @@ impl ShellHandler for Shell {
         })
     }
 
+    /// Handles a completion request with the completion engine of the LSP
     async fn handle_complete_request(
         &self,
-        _req: &CompleteRequest,
+        req: &CompleteRequest,
     ) -> amalthea::Result<CompleteReply> {
-        // No matches in this toy implementation.
-        Ok(CompleteReply {
-            matches: Vec::new(),
-            status: Status::Ok,
-            cursor_start: 0,
-            cursor_end: 0,
-            metadata: json!({}),
-        })
+        r_task(|| lsp::jupyter::complete(&req.code, req.cursor_pos))
+            .map_err(amalthea::Error::Anyhow)
     }
 
     /// Handle a request to test code for completion.
@@ -183,7 +183,7 @@ impl ShellHandler for Shell {
         originator: Originator,
         req: &ExecuteRequest,
     ) -> amalthea::Result<ExecuteReply> {
-        let (response_tx, response_rx) = unbounded::<amalthea::Result<ExecuteReply>>();
+        let (response_tx, response_rx) = unbounded::<ExecuteCodeReply>();
         let mut req_clone = req.clone();
         req_clone.code = convert_line_endings(&req_clone.code, LineEnding::Posix);
         if let Err(err) = self.r_request_tx.send(RRequest::ExecuteCode(
@@ -198,28 +198,19 @@ impl ShellHandler for Shell {
         }
 
         trace!("Code sent to R: {}", req_clone.code);
-        let result = response_rx.recv().unwrap();
+        let (result, metadata) = response_rx.recv().unwrap();
+        self.execute_reply_metadata = metadata;
 
         result
     }
 
-    /// Handles an introspection request
+    fn take_execute_reply_metadata(&mut self) -> Option<serde_json::Value> {
+        self.execute_reply_metadata.take()
+    }
+
+    /// Handles an introspection request with the hover engine of the LSP
     async fn handle_inspect_request(&self, req: &InspectRequest) -> amalthea::Result<InspectReply> {
-        let data = match req.code.as_str() {
-            "err" => {
-                json!({"text/plain": "This generates an error!"})
-            },
-            "teapot" => {
-                json!({"text/plain": "This is clearly a teapot."})
-            },
-            _ => serde_json::Value::Null,
-        };
-        Ok(InspectReply {
-            status: Status::Ok,
-            found: data != serde_json::Value::Null,
-            data,
-            metadata: json!({}),
-        })
+        r_task(|| lsp::jupyter::inspect(&req.code, req.cursor_pos)).map_err(amalthea::Error::Anyhow)
     }
 
     /// Handle a request to open a new comm channel
@@ -237,6 +228,9 @@ impl ShellHandler for Shell {
             ),
             Comm::Help => handle_comm_open_help(comm),
             Comm::Other(target_name) if target_name == "ark" => ArkComm::handle_comm_open(comm),
+            Comm::Other(target_name) if target_name == "positron.log" => {
+                LogComm::handle_comm_open(comm)
+            },
             _ => Ok(false),
         }
     }
@@ -0,0 +1,160 @@
+//
+// shutdown.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use std::time::Duration;
+
+use crossbeam::channel::Sender;
+use harp::exec::RFunction;
+use harp::exec::RFunctionExt;
+use stdext::spawn;
+
+use crate::modules::ARK_ENVS;
+
+/// How long the R thread has to run the shutdown sequence before the process
+/// is forcefully terminated
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long R has to exit once the shutdown sequence has completed. R still
+/// runs the exit finalizers and cleans up the session temporary directory.
+pub const EXIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The steps of the shutdown sequence, run in order on the R thread. They are
+/// implemented in `shutdown.R`. Failing steps don't prevent the next ones from
+/// running.
+const SHUTDOWN_STEPS: &[(&str, &str)] = &[
+    ("shutdownRunLast", "Running `.Last()`"),
+    ("shutdownCloseSinks", "Closing sinks"),
+    ("shutdownCloseConnections", "Closing connections"),
+    ("shutdownRunFinalizers", "Running finalizers"),
+];
+
+/// Progress of the shutdown sequence, reported to the control handler
+#[derive(Debug, Clone)]
+pub enum ShutdownProgress {
+    /// A step of the sequence is about to run
+    Step(String),
+
+    /// A step of the sequence failed
+    Failed(String),
+
+    /// The sequence is complete and R is about to exit
+    Complete,
+}
+
+/// Runs the shutdown sequence. Must be called on the R thread at top level,
+/// i.e. once any frames being debugged have been unwound so that their
+/// `on.exit()` handlers have run.
+pub(crate) fn run_shutdown_sequence(progress_tx: Option<&Sender<ShutdownProgress>>) {
+    let report = |progress: ShutdownProgress| {
+        let Some(tx) = progress_tx else {
+            return;
+        };
+        if let Err(err) = tx.send(progress) {
+            log::warn!("Can't report shutdown progress: {err}");
+        }
+    };
+
+    for (fun, description) in SHUTDOWN_STEPS {
+        log::info!("Shutdown: {description}");
+        report(ShutdownProgress::Step(description.to_string()));
+
+        if let Err(err) = RFunction::new("", fun).call_in(ARK_ENVS.positron_ns) {
+            log::error!("Shutdown: Step failed ({description}): {err}");
+            report(ShutdownProgress::Failed(format!("{description}: {err}")));
+        }
+    }
+
+    log::info!("Shutdown: Sequence complete, exiting R");
+    report(ShutdownProgress::Complete);
+}
+
+/// Terminates the process if it is still running after `timeout`
+pub(crate) fn force_exit_after(timeout: Duration) {
+    spawn!("ark-shutdown-watchdog", move || {
+        std::thread::sleep(timeout);
+        log::error!("R didn't exit within {timeout:?}, terminating the process");
+        log::logger().flush();
+        std::process::exit(1);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use crossbeam::channel::unbounded;
+    use harp::eval::parse_eval_global;
+
+    use crate::r_task;
+    use crate::shutdown::run_shutdown_sequence;
+    use crate::shutdown::ShutdownProgress;
+
+    fn progress(sequence: Vec<ShutdownProgress>) -> Vec<String> {
+        sequence
+            .into_iter()
+            .map(|progress| match progress {
+                ShutdownProgress::Step(step) => format!("step: {step}"),
+                ShutdownProgress::Failed(step) => format!("failed: {step}"),
+                ShutdownProgress::Complete => String::from("complete"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_shutdown_sequence_runs_last() {
+        r_task(|| {
+            parse_eval_global(".Last <- function() .ark_test_last <<- TRUE").unwrap();
+
+            let (progress_tx, progress_rx) = unbounded();
+            run_shutdown_sequence(Some(&progress_tx));
+
+            assert_eq!(progress(progress_rx.try_iter().collect()), vec![
+                "step: Running `.Last()`",
+                "step: Closing sinks",
+                "step: Closing connections",
+                "step: Running finalizers",
+                "complete",
+            ]);
+
+            // `.Last()` ran and was removed so R doesn't run it again on exit
+            let ran: bool = parse_eval_global(".ark_test_last")
+                .unwrap()
+                .try_into()
+                .unwrap();
+            assert!(ran);
+            let exists: bool = parse_eval_global("exists('.Last', envir = globalenv())")
+                .unwrap()
+                .try_into()
+                .unwrap();
+            assert!(!exists);
+
+            parse_eval_global("rm(.ark_test_last)").unwrap();
+        })
+    }
+
+    #[test]
+    fn test_shutdown_sequence_continues_after_failed_step() {
+        r_task(|| {
+            parse_eval_global(".Last <- function() stop('oops')").unwrap();
+            parse_eval_global("sink(tempfile())").unwrap();
+
+            let (progress_tx, progress_rx) = unbounded();
+            run_shutdown_sequence(Some(&progress_tx));
+
+            let progress = progress(progress_rx.try_iter().collect());
+            assert_eq!(progress[0], "step: Running `.Last()`");
+            assert!(progress[1].starts_with("failed: Running `.Last()`"));
+            assert_eq!(progress[2], "step: Closing sinks");
+            assert_eq!(progress.last().unwrap(), "complete");
+
+            // Later steps still ran
+            let sinks: i32 = parse_eval_global("sink.number()")
+                .unwrap()
+                .try_into()
+                .unwrap();
+            assert_eq!(sinks, 0);
+        })
+    }
+}
@@ -7,5 +7,5 @@
 
 pub use crate::sys::signals::initialize_signal_block;
 pub use crate::sys::signals::initialize_signal_handlers;
-pub use crate::sys::signals::interrupts_pending;
-pub use crate::sys::signals::set_interrupts_pending;
+pub use harp::interrupts::interrupts_pending;
+pub use harp::interrupts::set_interrupts_pending;
@@ -0,0 +1,114 @@
+//
+// source_file.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use serde::Deserialize;
+
+/// Magic comment of execute requests that run a file, as with
+/// `source(file, echo = TRUE)`. Followed by a JSON object of
+/// `SourceFileOptions` on the same line:
+///
+/// ```text
+/// #| ark-source: {"path": "/path/to/file.R", "echo": true, "stop_on_error": false}
+/// ```
+///
+/// This way the frontend doesn't have to build an R call with a properly
+/// escaped path.
+pub const SOURCE_FILE_MAGIC: &str = "#| ark-source:";
+
+#[derive(Debug, PartialEq, Deserialize)]
+pub struct SourceFileOptions {
+    /// The file to run
+    pub path: String,
+
+    /// Whether to echo each expression before evaluating it, with visible
+    /// results printed as at top level
+    #[serde(default = "default_true")]
+    pub echo: bool,
+
+    /// Whether to stop at the first error. Otherwise errors are reported and
+    /// evaluation continues with the next expression.
+    #[serde(default = "default_true")]
+    pub stop_on_error: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Translates the code of an execute request starting with the source magic
+/// comment into a call to `.ps.source_file()`, which evaluates the
+/// expressions of the file one by one so their output is streamed as they
+/// run. Returns `None` for other code.
+///
+/// Malformed options are translated into a call throwing an error, so that
+/// the request fails visibly.
+pub(crate) fn translate_source_request(code: &str) -> Option<String> {
+    let options = code.trim_start().strip_prefix(SOURCE_FILE_MAGIC)?;
+
+    // Only the first line is considered
+    let options = options.lines().next().unwrap_or_default();
+
+    let options: SourceFileOptions = match serde_json::from_str(options) {
+        Ok(options) => options,
+        Err(err) => {
+            let message = format!("Invalid options for `{SOURCE_FILE_MAGIC}`: {err}");
+            return Some(format!("stop({}, call. = FALSE)", r_string(&message)));
+        },
+    };
+
+    Some(format!(
+        ".ps.source_file({path}, echo = {echo}, stop_on_error = {stop_on_error})",
+        path = r_string(&options.path),
+        echo = r_logical(options.echo),
+        stop_on_error = r_logical(options.stop_on_error),
+    ))
+}
+
+/// JSON string literals are valid R string literals
+fn r_string(x: &str) -> String {
+    serde_json::Value::from(x).to_string()
+}
+
+fn r_logical(x: bool) -> &'static str {
+    if x {
+        "TRUE"
+    } else {
+        "FALSE"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::source_file::translate_source_request;
+
+    #[test]
+    fn test_translate_source_request() {
+        assert_eq!(
+            translate_source_request(r#"#| ark-source: {"path": "C:\\dir\\my \"file\".R"}"#),
+            Some(String::from(
+                r#".ps.source_file("C:\\dir\\my \"file\".R", echo = TRUE, stop_on_error = TRUE)"#
+            ))
+        );
+
+        assert_eq!(
+            translate_source_request(
+                "#| ark-source: {\"path\": \"file.R\", \"echo\": false, \"stop_on_error\": false}\n"
+            ),
+            Some(String::from(
+                r#".ps.source_file("file.R", echo = FALSE, stop_on_error = FALSE)"#
+            ))
+        );
+
+        // Malformed options throw an error in R
+        let code = translate_source_request("#| ark-source: {\"echo\": true}").unwrap();
+        assert!(code.starts_with("stop(\"Invalid options for `#| ark-source:`: missing field"));
+
+        // Other code is left alone
+        assert_eq!(translate_source_request("source('file.R')"), None);
+        assert_eq!(translate_source_request("1\n#| ark-source: {}"), None);
+    }
+}
@@ -36,6 +36,7 @@ pub fn start_kernel(
     registration_file: Option<RegistrationFile>,
     r_args: Vec<String>,
     startup_file: Option<String>,
+    positron_profile: Option<String>,
     session_mode: SessionMode,
     capture_streams: bool,
     default_repos: DefaultRepos,
@@ -89,7 +90,10 @@ pub fn start_kernel(
 
     // Create the control handler; this is used to handle shutdown/interrupt and
     // related requests
-    let control = Arc::new(Mutex::new(Control::new(r_request_tx.clone())));
+    let control = Arc::new(Mutex::new(Control::new(
+        r_request_tx.clone(),
+        iopub_tx.clone(),
+    )));
 
     // Create the stream behavior; this determines whether the kernel should
     // capture stdout/stderr and send them to the frontend as IOPub messages
@@ -135,6 +139,7 @@ pub fn start_kernel(
     crate::interface::RMain::start(
         r_args,
         startup_file,
+        positron_profile,
         comm_manager_tx,
         r_request_rx,
         stdin_request_tx,
@@ -7,6 +7,7 @@
 
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Mutex;
 
 use amalthea::socket::iopub::IOPubMessage;
 use amalthea::wire::stream::Stream;
@@ -15,10 +16,33 @@ use harp::environment::R_ENVS;
 use harp::exec::RFunction;
 use harp::exec::RFunctionExt;
 use libr::Rf_eval;
+use libr::SEXP;
+use once_cell::sync::Lazy;
 
 use crate::interface::RMain;
 use crate::sys;
 
+/// Errors that occurred while sourcing R profiles on startup. They are
+/// reported to the frontend in the console but are also kept around so they
+/// can be inspected later on with `.ps.rpc.get_startup_diagnostics()`.
+static STARTUP_DIAGNOSTICS: Lazy<Mutex<Vec<StartupDiagnostic>>> =
+    Lazy::new(|| Mutex::new(Vec::new()));
+
+#[derive(Clone, Copy, Debug, PartialEq, strum_macros::Display)]
+#[strum(serialize_all = "lowercase")]
+enum ProfileKind {
+    Site,
+    User,
+    Positron,
+}
+
+#[derive(Clone, Debug)]
+struct StartupDiagnostic {
+    kind: ProfileKind,
+    path: String,
+    message: String,
+}
+
 pub(crate) fn should_ignore_site_r_profile(args: &Vec<String>) -> bool {
     args.iter()
         .any(|arg| arg == "--no-site-file" || arg == "--vanilla")
@@ -41,7 +65,7 @@ pub(crate) fn push_ignore_user_r_profile(args: &mut Vec<String>) {
 // https://github.com/wch/r-source/blob/ee6b15303be885d118d49b441e32a9cff5cda778/src/main/startup.c#L96
 pub(crate) fn source_site_r_profile(r_home: &PathBuf) {
     match find_site_r_profile(r_home) {
-        Some(path) => source_r_profile(&path),
+        Some(path) => source_r_profile(&path, ProfileKind::Site),
         None => (),
     }
 }
@@ -51,16 +75,34 @@ pub(crate) fn source_site_r_profile(r_home: &PathBuf) {
 // Unix: https://github.com/wch/r-source/blob/ee6b15303be885d118d49b441e32a9cff5cda778/src/unix/sys-unix.c#L68
 pub(crate) fn source_user_r_profile() {
     match find_user_r_profile() {
-        Some(path) => source_r_profile(&path),
+        Some(path) => source_r_profile(&path, ProfileKind::User),
         None => (),
     }
 }
 
-fn source_r_profile(path: &PathBuf) {
+// Runs after the site and user level R profiles, so it can override any option
+// they might set. Unlike these, it is not affected by `--vanilla`.
+pub(crate) fn source_positron_r_profile(path: &str) {
+    let path = PathBuf::from(path);
+
+    if !path.exists() {
+        let message = format!(
+            "Can't find Positron R profile at path '{}'.",
+            path.display()
+        );
+        log::error!("{message}");
+        report_diagnostic(ProfileKind::Positron, &path, message);
+        return;
+    }
+
+    source_r_profile(&path, ProfileKind::Positron)
+}
+
+fn source_r_profile(path: &PathBuf, kind: ProfileKind) {
     let path = path.to_string_lossy().to_string();
     let path = path.as_str();
 
-    log::info!("Found R profile at '{path}', sourcing now");
+    log::info!("Found {kind} R profile at '{path}', sourcing now");
 
     // Must source with `top_level_exec()` rather than just calling `call()`.
     // In particular, can't source with the typical `r_safe_eval()` because it
@@ -79,27 +121,39 @@ fn source_r_profile(path: &PathBuf) {
     };
 
     let Err(err) = result else {
-        log::info!("Successfully sourced R profile at '{path}'");
+        log::info!("Successfully sourced {kind} R profile at '{path}'");
         return;
     };
 
-    log::error!("Error while sourcing R profile at '{path}': {err}");
+    log::error!("Error while sourcing {kind} R profile at '{path}': {err}");
 
     let harp::Error::TopLevelExecError { message, .. } = err else {
         unreachable!("Only `TopLevelExecError` errors should be thrown.");
     };
 
+    report_diagnostic(kind, &PathBuf::from(path), message);
+}
+
+fn report_diagnostic(kind: ProfileKind, path: &PathBuf, message: String) {
+    let path = path.to_string_lossy().to_string();
+
     // Forward the message on to the frontend to be shown in the console.
     // This technically happens outside of any parent context, but that is allowed.
     // https://jupyter-client.readthedocs.io/en/stable/messaging.html#parent-header
-    let message = format!("Error while sourcing R profile file at path '{path}':\n{message}");
+    let text = format!("Error while sourcing {kind} R profile file at path '{path}':\n{message}");
 
-    let message = IOPubMessage::Stream(StreamOutput {
+    let stream = IOPubMessage::Stream(StreamOutput {
         name: Stream::Stderr,
-        text: message,
+        text,
     });
 
-    RMain::with(|main| main.get_iopub_tx().send(message).unwrap())
+    RMain::with(|main| main.get_iopub_tx().send(stream).unwrap());
+
+    STARTUP_DIAGNOSTICS.lock().unwrap().push(StartupDiagnostic {
+        kind,
+        path,
+        message,
+    });
 }
 
 fn find_site_r_profile(r_home: &PathBuf) -> Option<PathBuf> {
@@ -161,3 +215,20 @@ fn find_user_r_profile() -> Option<PathBuf> {
 
     None
 }
+
+#[harp::register]
+pub unsafe extern "C-unwind" fn ps_startup_diagnostics() -> anyhow::Result<SEXP> {
+    let diagnostics = STARTUP_DIAGNOSTICS.lock().unwrap().clone();
+
+    let kinds: Vec<String> = diagnostics.iter().map(|x| x.kind.to_string()).collect();
+    let paths: Vec<String> = diagnostics.iter().map(|x| x.path.clone()).collect();
+    let messages: Vec<String> = diagnostics.into_iter().map(|x| x.message).collect();
+
+    let diagnostics = RFunction::from("list")
+        .param("kind", kinds)
+        .param("path", paths)
+        .param("message", messages)
+        .call()?;
+
+    Ok(diagnostics.sexp)
+}
@@ -14,3 +14,13 @@ pub fn handle_interrupt_request() {
     // processes started by R will also be interrupted.
     signal::kill(Pid::this(), Signal::SIGINT).unwrap();
 }
+
+/// Escalation of an interrupt that R hasn't responded to. The signal is
+/// delivered to R again, e.g. in case R reset `R_interrupts_pending` while
+/// interrupts were suspended. Processes started by R are left alone since
+/// they might be unrelated to the computation, such as background jobs.
+pub fn handle_hard_interrupt_request() {
+    if let Err(err) = signal::kill(Pid::this(), Signal::SIGINT) {
+        log::error!("Can't deliver hard interrupt: {err}");
+    }
+}
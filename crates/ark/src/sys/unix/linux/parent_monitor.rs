@@ -90,7 +90,7 @@ fn monitor_parent_death_signal(r_request_tx: Sender<RRequest>) {
             log::info!("Parent process has exited, initiating graceful shutdown");
 
             // Send shutdown request to R execution thread (false = final shutdown, not restart)
-            if let Err(err) = r_request_tx.send(RRequest::Shutdown(false)) {
+            if let Err(err) = r_request_tx.send(RRequest::Shutdown(false, None)) {
                 log::error!("Failed to send shutdown request, exiting: {err}");
                 // If we can't send the shutdown request, force exit as fallback
                 std::process::exit(1);
@@ -5,7 +5,7 @@
  *
  */
 
-use libr::R_interrupts_pending;
+use harp::interrupts::set_interrupts_pending;
 use nix::sys::signal::*;
 
 /// Reset the signal block.
@@ -52,18 +52,6 @@ pub fn initialize_signal_block() {
     sigprocmask(SigmaskHow::SIG_BLOCK, Some(&sigset), None).unwrap();
 }
 
-pub fn interrupts_pending() -> bool {
-    unsafe { libr::get(R_interrupts_pending) == 1 }
-}
-
-pub fn set_interrupts_pending(pending: bool) {
-    if pending {
-        unsafe { libr::set(R_interrupts_pending, 1) };
-    } else {
-        unsafe { libr::set(R_interrupts_pending, 0) };
-    }
-}
-
 /// Unix interrupt handler
 ///
 /// # Safety
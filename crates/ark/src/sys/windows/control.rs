@@ -10,3 +10,10 @@ use crate::signals::set_interrupts_pending;
 pub fn handle_interrupt_request() {
     set_interrupts_pending(true);
 }
+
+/// Escalation of an interrupt that R hasn't responded to. `UserBreak` is reset
+/// by `R_ProcessEvents()` even when interrupts are suspended, which swallows
+/// the interrupt, so we set it again.
+pub fn handle_hard_interrupt_request() {
+    set_interrupts_pending(true);
+}
@@ -629,8 +629,12 @@ pub(crate) fn point_end_of_previous_row(
 ) -> tree_sitter::Point {
     if point.row > 0 {
         let prev_row = point.row - 1;
-        let line = contents.line(prev_row as usize);
-        let line_len = line.len_chars().saturating_sub(1); // Subtract 1 for newline
+        let line = contents.line(prev_row as usize).to_string();
+
+        // Tree-sitter columns are byte offsets. Exclude the line ending,
+        // which might be `\r\n`.
+        let line_len = line.trim_end_matches(['\n', '\r']).len();
+
         tree_sitter::Point {
             row: prev_row,
             column: line_len,
@@ -826,6 +830,15 @@ mod tests {
         assert_eq!(result, Point { row: 0, column: 5 });
     }
 
+    #[test]
+    fn test_point_end_of_previous_row_bytes() {
+        // Columns are in bytes, and CRLF line endings are excluded
+        let contents = Rope::from_str("café —\r\nfoo");
+        let point = Point { row: 1, column: 1 };
+        let result = point_end_of_previous_row(point, &contents);
+        assert_eq!(result, Point { row: 0, column: 9 });
+    }
+
     #[test]
     fn test_point_end_of_previous_row_single_line() {
         let contents = Rope::from_str("hello world");
@@ -142,6 +142,24 @@ pub fn send_open_with_system_event(path: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Sends the working directory to the frontend if it has changed. Called from
+/// the `setwd()` hook. Doesn't fail when the UI comm isn't connected, as in
+/// Jupyter notebooks.
+#[harp::register]
+pub unsafe extern "C-unwind" fn ps_ui_refresh_working_directory() -> anyhow::Result<SEXP> {
+    // Can be called before the kernel is fully started, e.g. in unit tests
+    if !RMain::is_initialized() {
+        return Ok(R_NilValue);
+    }
+
+    let main = RMain::get_mut();
+    if let Some(ui_comm_tx) = main.get_mut_ui_comm_tx() {
+        ui_comm_tx.refresh_working_directory()?;
+    }
+
+    Ok(R_NilValue)
+}
+
 pub fn ps_ui_robj_as_ranges(ranges: SEXP) -> anyhow::Result<Vec<Range>> {
     let ranges_as_r_objects: Vec<RObject> = RObject::view(ranges).try_into()?;
     let ranges_as_result: Result<Vec<Vec<i32>>, _> = ranges_as_r_objects
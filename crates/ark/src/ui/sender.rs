@@ -75,7 +75,10 @@ impl UiCommSender {
 
     /// Checks for changes to the working directory, and sends an event to the
     /// frontend if the working directory has changed.
-    fn refresh_working_directory(&mut self) -> anyhow::Result<()> {
+    ///
+    /// Called at each prompt and by the `setwd()` hook, so that changes made
+    /// by code that doesn't return to the prompt, like RPCs, are seen too.
+    pub fn refresh_working_directory(&mut self) -> anyhow::Result<()> {
         // Get the current working directory
         let mut new_working_directory = std::env::current_dir()?;
 
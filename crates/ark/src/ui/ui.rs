@@ -183,12 +183,12 @@ impl UiComm {
 
         // Form an R function call from the request
         let result = r_task(|| {
-            let mut call = RFunction::from(method);
-            for param in request.params.iter() {
-                let p = RObject::try_from(param.clone())?;
-                call.add(p);
-            }
-            let result = call.call()?;
+            let params = request
+                .params
+                .iter()
+                .map(|param| RObject::try_from(param.clone()))
+                .collect::<Result<Vec<_>, _>>()?;
+            let result = RFunction::from(method).add_all(params).call()?;
             Value::try_from(result)
         })?;
 
@@ -366,4 +366,116 @@ mod tests {
             })))
             .unwrap();
     }
+
+    #[test]
+    fn test_ui_comm_set_working_directory() {
+        let comm_socket = CommSocket::new(
+            CommInitiator::FrontEnd,
+            String::from("test-ui-comm-wd-id"),
+            String::from("positron.UI"),
+        );
+        let (stdin_request_tx, _stdin_request_rx) = bounded::<StdInRequest>(1);
+        let (graphics_device_tx, _graphics_device_rx) =
+            tokio::sync::mpsc::unbounded_channel::<GraphicsDeviceNotification>();
+        let _ui_comm_tx = UiComm::start(comm_socket.clone(), stdin_request_tx, graphics_device_tx);
+
+        let old_dir = std::env::current_dir().unwrap();
+        let new_dir = tempfile::tempdir().unwrap();
+
+        let request = UiBackendRequest::CallMethod(CallMethodParams {
+            method: String::from("set_working_directory"),
+            params: vec![Value::from(new_dir.path().to_string_lossy().to_string())],
+        });
+        comm_socket
+            .incoming_tx
+            .send(CommMsg::Rpc(
+                String::from("test-id"),
+                serde_json::to_value(request).unwrap(),
+            ))
+            .unwrap();
+
+        let response = comm_socket
+            .outgoing_rx
+            .recv_timeout(std::time::Duration::from_secs(1))
+            .unwrap();
+
+        // Restore the working directory before asserting
+        let current_dir = std::env::current_dir().unwrap();
+        r_task(|| {
+            RFunction::from("setwd")
+                .add(old_dir.to_string_lossy().to_string())
+                .call()
+                .unwrap();
+        });
+
+        // The RPC returns the new working directory
+        let CommMsg::Rpc(_, result) = response else {
+            panic!("Unexpected response: {response:?}");
+        };
+        let UiBackendReply::CallMethodReply(Value::String(dir)) =
+            serde_json::from_value::<UiBackendReply>(result).unwrap()
+        else {
+            panic!("Expected the new working directory");
+        };
+
+        let new_dir = std::fs::canonicalize(new_dir.path()).unwrap();
+        assert_eq!(std::fs::canonicalize(dir).unwrap(), new_dir);
+        assert_eq!(std::fs::canonicalize(current_dir).unwrap(), new_dir);
+    }
+
+    #[test]
+    fn test_ui_comm_last_warnings() {
+        let comm_socket = CommSocket::new(
+            CommInitiator::FrontEnd,
+            String::from("test-ui-comm-warnings-id"),
+            String::from("positron.UI"),
+        );
+        let (stdin_request_tx, _stdin_request_rx) = bounded::<StdInRequest>(1);
+        let (graphics_device_tx, _graphics_device_rx) =
+            tokio::sync::mpsc::unbounded_channel::<GraphicsDeviceNotification>();
+        let _ui_comm_tx = UiComm::start(comm_socket.clone(), stdin_request_tx, graphics_device_tx);
+
+        // R sets `last.warning` when it prints warnings at top level
+        r_task(|| {
+            harp::parse_eval_global(
+                "assign('last.warning', list(oops = quote(f(x)), 'no call' = NULL), baseenv())",
+            )
+            .unwrap();
+        });
+
+        let request = UiBackendRequest::CallMethod(CallMethodParams {
+            method: String::from("last_warnings"),
+            params: vec![],
+        });
+        comm_socket
+            .incoming_tx
+            .send(CommMsg::Rpc(
+                String::from("test-id"),
+                serde_json::to_value(request).unwrap(),
+            ))
+            .unwrap();
+
+        let response = comm_socket
+            .outgoing_rx
+            .recv_timeout(std::time::Duration::from_secs(1))
+            .unwrap();
+
+        r_task(|| {
+            harp::parse_eval_global("rm('last.warning', envir = baseenv())").unwrap();
+        });
+
+        let CommMsg::Rpc(_, result) = response else {
+            panic!("Unexpected response: {response:?}");
+        };
+        let UiBackendReply::CallMethodReply(warnings) =
+            serde_json::from_value::<UiBackendReply>(result).unwrap()
+        else {
+            panic!("Expected the last warnings");
+        };
+
+        assert_eq!(warnings[0]["message"], "oops");
+        assert_eq!(warnings[0]["call"], "f(x)");
+        assert_eq!(warnings[1]["message"], "no call");
+        assert_eq!(warnings[1]["call"], Value::Null);
+    }
 }
@@ -5,11 +5,16 @@
 //
 //
 
+use std::collections::HashMap;
+
 use amalthea::comm::comm_channel::CommMsg;
 use amalthea::comm::event::CommManagerEvent;
 use amalthea::comm::variables_comm::ClipboardFormatFormat;
+use amalthea::comm::variables_comm::DeletionResult;
+use amalthea::comm::variables_comm::FailedDeletion;
 use amalthea::comm::variables_comm::FormattedVariable;
 use amalthea::comm::variables_comm::InspectedVariable;
+use amalthea::comm::variables_comm::ProgressParams;
 use amalthea::comm::variables_comm::QueryTableSummaryResult;
 use amalthea::comm::variables_comm::RefreshParams;
 use amalthea::comm::variables_comm::UpdateParams;
@@ -32,22 +37,25 @@ use harp::get_option;
 use harp::object::RObject;
 use harp::utils::r_assert_type;
 use harp::utils::r_is_function;
-use harp::vector::CharacterVector;
-use harp::vector::Vector;
-use libr::R_GlobalEnv;
-use libr::Rf_ScalarLogical;
+use libr::Rf_xlength;
 use libr::ENVSXP;
 use stdext::spawn;
 
 use crate::data_explorer::r_data_explorer::DataObjectEnvInfo;
 use crate::data_explorer::r_data_explorer::RDataExplorer;
 use crate::data_explorer::summary_stats::summary_stats;
-use crate::lsp::events::EVENTS;
+use crate::events::EVENTS;
+use crate::modules::ARK_ENVS;
 use crate::r_task;
+use crate::r_task::r_task_background;
 use crate::thread::RThreadSafe;
 use crate::variables::variable::PositronVariable;
 use crate::view::view;
 
+/// Size of the chunks in which files are written and read when saving or
+/// loading variables, so that progress can be reported between chunks
+const FILE_CHUNK_SIZE: usize = 4 * 1024 * 1024;
+
 /// Enumeration of treatments for the .Last.value variable
 pub enum LastValue {
     /// Always show the .Last.value variable in the Variables pane. This is used
@@ -66,7 +74,16 @@ pub enum LastValue {
 pub struct RVariables {
     comm: CommSocket,
     comm_manager_tx: Sender<CommManagerEvent>,
+
+    /// The environment whose variables are shown. This is the environment of
+    /// the current frame while R is stopped in `browser()`, and `root_env`
+    /// otherwise.
     pub env: RThreadSafe<RObject>,
+
+    /// The environment the variables handler was started with, typically
+    /// the global environment
+    root_env: RThreadSafe<RObject>,
+
     /// `Binding` does not currently protect anything, and therefore doesn't
     /// implement `Drop`, which might use the R API. It assumes that R SYMSXPs
     /// protect themselves, and that the binding value is protected by the
@@ -128,6 +145,7 @@ impl RVariables {
         // To be able to `Send` the `env` to the thread, it needs to be made
         // thread safe. To create `current_bindings`, we need to be on the main
         // R thread.
+        let root_env = RThreadSafe::new(env.clone());
         let env = RThreadSafe::new(env);
         let current_bindings = RThreadSafe::new(vec![]);
 
@@ -139,6 +157,7 @@ impl RVariables {
                 comm,
                 comm_manager_tx,
                 env,
+                root_env,
                 current_bindings,
                 version: 0,
                 show_last_value,
@@ -152,7 +171,7 @@ impl RVariables {
         let (prompt_signal_tx, prompt_signal_rx) = unbounded::<()>();
 
         // Register a handler for console prompt events
-        let listen_id = EVENTS.console_prompt.listen({
+        let prompt_subscription = EVENTS.console_prompt.subscribe({
             move |_| {
                 log::info!("Got console prompt signal.");
                 prompt_signal_tx.send(()).unwrap();
@@ -160,14 +179,7 @@ impl RVariables {
         });
 
         // Perform the initial environment scan and deliver to the frontend
-        let variables = self.list_variables();
-        let length = variables.len() as i64;
-        let event = VariablesFrontendEvent::Refresh(RefreshParams {
-            variables,
-            length,
-            version: self.version as i64,
-        });
-        self.send_event(event, None);
+        self.refresh();
 
         // Flag initially set to false, but set to true if the user closes the
         // channel (i.e. the frontend is closed)
@@ -179,7 +191,13 @@ impl RVariables {
             select! {
                 recv(&prompt_signal_rx) -> msg => {
                     if let Ok(()) = msg {
-                        self.update(None);
+                        // The variables of a different frame replace the
+                        // whole list rather than updating it
+                        if self.sync_env() {
+                            self.refresh();
+                        } else {
+                            self.update(None);
+                        }
                     }
                 },
 
@@ -217,7 +235,7 @@ impl RVariables {
             }
         }
 
-        EVENTS.console_prompt.remove(listen_id);
+        drop(prompt_subscription);
 
         if !user_initiated_close {
             // Send a close message to the frontend if the frontend didn't
@@ -234,6 +252,26 @@ impl RVariables {
         self.version
     }
 
+    /// Scans the environment and sends the whole list of variables to the
+    /// frontend
+    fn refresh(&mut self) {
+        let variables = self.list_variables();
+        let length = variables.len() as i64;
+        let event = VariablesFrontendEvent::Refresh(RefreshParams {
+            variables,
+            length,
+            version: self.version as i64,
+        });
+        self.send_event(event, None);
+    }
+
+    /// Switches to the environment of the frame being debugged when R stops
+    /// in `browser()`, and back to the root environment once debugging is
+    /// over. Returns `true` if the environment changed.
+    fn sync_env(&mut self) -> bool {
+        r_task(|| self.set_frame_env(debug_env()))
+    }
+
     #[tracing::instrument(level = "trace", skip_all)]
     fn list_variables(&mut self) -> Vec<Variable> {
         let mut variables: Vec<Variable> = vec![];
@@ -271,13 +309,29 @@ impl RVariables {
                 }))
             },
             VariablesBackendRequest::Clear(params) => {
-                self.clear(params.include_hidden_objects)?;
-                self.update(None);
-                Ok(VariablesBackendReply::ClearReply())
+                let dry_run = params.dry_run.unwrap_or(false);
+                let result = self.clear(params.include_hidden_objects, dry_run)?;
+                if !dry_run && !result.deleted.is_empty() {
+                    self.update(None);
+                }
+                Ok(VariablesBackendReply::ClearReply(result))
             },
             VariablesBackendRequest::Delete(params) => {
-                self.delete(params.names.clone())?;
-                Ok(VariablesBackendReply::DeleteReply(params.names))
+                let dry_run = params.dry_run.unwrap_or(false);
+                let result = self.delete(params.names, dry_run)?;
+                if !dry_run && !result.deleted.is_empty() {
+                    self.update(None);
+                }
+                Ok(VariablesBackendReply::DeleteReply(result))
+            },
+            VariablesBackendRequest::SaveRds(params) => {
+                self.save_rds(&params.path, &params.file, &params.operation_id)?;
+                Ok(VariablesBackendReply::SaveRdsReply())
+            },
+            VariablesBackendRequest::LoadFile(params) => {
+                let names = self.load_file(&params.file, params.name, &params.operation_id)?;
+                self.update(None);
+                Ok(VariablesBackendReply::LoadFileReply(names))
             },
             VariablesBackendRequest::Inspect(params) => {
                 let children = self.inspect(&params.path)?;
@@ -304,55 +358,221 @@ impl RVariables {
         }
     }
 
-    /**
-     * Clear the environment. Uses rm(envir = <env>, list = ls(<env>, all.names = TRUE))
-     */
-    fn clear(&mut self, include_hidden_objects: bool) -> Result<(), harp::error::Error> {
-        r_task(|| unsafe {
+    /// Delete all variables of the environment, skipping the ones that are
+    /// locked. With `dry_run`, only reports which variables would be deleted.
+    fn clear(
+        &mut self,
+        include_hidden_objects: bool,
+        dry_run: bool,
+    ) -> anyhow::Result<DeletionResult> {
+        r_task(|| {
             let env = self.env.get().clone();
 
-            let mut list = RFunction::new("base", "ls")
-                .param("envir", *env)
-                .param("all.names", Rf_ScalarLogical(include_hidden_objects as i32))
-                .call()?;
+            let result = RFunction::new("", ".ps.variables_clear")
+                .param("env", env)
+                .param("include_hidden_objects", include_hidden_objects)
+                .param("dry_run", dry_run)
+                .call_in(ARK_ENVS.positron_ns)?;
 
-            if *env == R_GlobalEnv {
-                list = RFunction::new("base", "setdiff")
-                    .add(list)
-                    .add(RObject::from(".Random.seed"))
-                    .call()?;
-            }
+            deletion_result(result)
+        })
+    }
 
-            RFunction::new("base", "rm")
-                .param("list", list)
-                .param("envir", *env)
-                .call()?;
+    /// Delete variables, skipping the ones that are locked. With `dry_run`,
+    /// only reports which variables would be deleted.
+    fn delete(&mut self, names: Vec<String>, dry_run: bool) -> anyhow::Result<DeletionResult> {
+        r_task(|| {
+            let env = self.env.get().clone();
 
-            Ok(())
+            let result = RFunction::new("", ".ps.variables_delete")
+                .param("env", env)
+                .param("names", names)
+                .param("dry_run", dry_run)
+                .call_in(ARK_ENVS.positron_ns)?;
+
+            deletion_result(result)
         })
     }
 
-    /**
-     * Clear the environment. Uses rm(envir = <env>, list = ls(<env>, all.names = TRUE))
-     */
-    fn delete(&mut self, variables: Vec<String>) -> Result<(), harp::error::Error> {
-        r_task(|| {
-            let variables: Vec<&str> = variables.iter().map(|s| s as &str).collect();
+    /// Save a variable to an `.rds` file. The variable is serialized up
+    /// front and then written in chunks, reporting progress after each chunk.
+    fn save_rds(
+        &mut self,
+        path: &Vec<String>,
+        file: &str,
+        operation_id: &str,
+    ) -> anyhow::Result<()> {
+        self.send_progress(operation_id, "Serializing variable", 0, None);
 
+        let (data, total) = r_task(|| -> anyhow::Result<_> {
             let env = self.env.get().clone();
+            let object = PositronVariable::resolve_data_object(env, path)?;
 
-            let result = RFunction::new("base", "rm")
-                .param("list", CharacterVector::create(variables).cast())
-                .param("envir", env)
-                .call();
+            let data = RFunction::new("", ".ps.variables_serialize")
+                .add(object)
+                .call_in(ARK_ENVS.positron_ns)?;
+            let total = unsafe { Rf_xlength(data.sexp) } as usize;
 
-            if let Err(err) = result {
-                return Err(err);
-            }
-            Ok(())
+            Ok((RThreadSafe::new(data), total))
+        })?;
+
+        let connection = r_task(|| -> anyhow::Result<_> {
+            let connection = RFunction::new("base", "gzfile")
+                .param("description", file)
+                .param("open", "wb")
+                .call()?;
+            Ok(RThreadSafe::new(connection))
+        })?;
+
+        let result = self.write_chunks(&connection, &data, total, operation_id);
+        r_task(|| close_connection(connection.get()));
+
+        result
+    }
+
+    fn write_chunks(
+        &mut self,
+        connection: &RThreadSafe<RObject>,
+        data: &RThreadSafe<RObject>,
+        total: usize,
+        operation_id: &str,
+    ) -> anyhow::Result<()> {
+        let mut written = 0;
+
+        while written < total {
+            let end = usize::min(written + FILE_CHUNK_SIZE, total);
+
+            r_task(|| {
+                RFunction::new("", ".ps.variables_write_chunk")
+                    .param("con", connection.get().clone())
+                    .param("data", data.get().clone())
+                    .param("start", (written + 1) as f64)
+                    .param("end", end as f64)
+                    .call_in(ARK_ENVS.positron_ns)
+            })?;
+
+            written = end;
+            self.send_progress(operation_id, "Writing file", written, Some(total));
+        }
+
+        Ok(())
+    }
+
+    /// Load the objects of an `.rds` or `.RData` file into the environment.
+    /// The file is read in chunks, reporting progress after each chunk, and
+    /// then deserialized.
+    ///
+    /// Returns the names of the loaded variables.
+    fn load_file(
+        &mut self,
+        file: &str,
+        name: Option<String>,
+        operation_id: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        let path = std::path::Path::new(file);
+
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+
+        let kind = match extension.as_deref() {
+            Some("rds") => "rds",
+            Some("rdata") | Some("rda") => "rdata",
+            _ => {
+                return Err(anyhow!(
+                    "Can't load '{file}': Expected an `.rds` or `.RData` file"
+                ))
+            },
+        };
+
+        let name = match name {
+            Some(name) => name,
+            None => match path.file_stem() {
+                Some(stem) => stem.to_string_lossy().to_string(),
+                None => return Err(anyhow!("Can't infer a variable name from '{file}'")),
+            },
+        };
+
+        // Compressed files are decompressed by the connection as they are read
+        let connection = r_task(|| -> anyhow::Result<_> {
+            let connection = RFunction::new("base", "gzfile")
+                .param("description", file)
+                .param("open", "rb")
+                .call()?;
+            Ok(RThreadSafe::new(connection))
+        })?;
+
+        let result = self.read_chunks(&connection, operation_id);
+        r_task(|| close_connection(connection.get()));
+        let chunks = result?;
+
+        self.send_progress(operation_id, "Loading variables", 0, None);
+
+        r_task(|| -> anyhow::Result<_> {
+            let env = self.env.get().clone();
+            let chunks: Vec<RObject> = chunks.iter().map(|chunk| chunk.get().clone()).collect();
+
+            let names = RFunction::new("", ".ps.variables_load")
+                .param("env", env)
+                .param("chunks", RObject::try_from(chunks)?)
+                .param("type", kind)
+                .param("name", name)
+                .call_in(ARK_ENVS.positron_ns)?;
+
+            Ok(Vec::<String>::try_from(names)?)
         })
     }
 
+    fn read_chunks(
+        &mut self,
+        connection: &RThreadSafe<RObject>,
+        operation_id: &str,
+    ) -> anyhow::Result<Vec<RThreadSafe<RObject>>> {
+        let mut chunks = vec![];
+        let mut read = 0;
+
+        loop {
+            let (chunk, size) = r_task(|| -> anyhow::Result<_> {
+                let chunk = RFunction::new("base", "readBin")
+                    .param("con", connection.get().clone())
+                    .param("what", "raw")
+                    .param("n", FILE_CHUNK_SIZE as f64)
+                    .call()?;
+                let size = unsafe { Rf_xlength(chunk.sexp) } as usize;
+                Ok((RThreadSafe::new(chunk), size))
+            })?;
+
+            if size == 0 {
+                break;
+            }
+
+            chunks.push(chunk);
+            read += size;
+
+            // The total size of decompressed files isn't known in advance
+            self.send_progress(operation_id, "Reading file", read, None);
+        }
+
+        Ok(chunks)
+    }
+
+    fn send_progress(
+        &mut self,
+        operation_id: &str,
+        message: &str,
+        bytes: usize,
+        total_bytes: Option<usize>,
+    ) {
+        let event = VariablesFrontendEvent::Progress(ProgressParams {
+            operation_id: operation_id.to_string(),
+            message: message.to_string(),
+            bytes: bytes as i64,
+            total_bytes: total_bytes.map(|total| total as i64),
+        });
+        self.send_event(event, None);
+    }
+
     fn clipboard_format(
         &mut self,
         path: &Vec<String>,
@@ -463,6 +683,8 @@ impl RVariables {
                             max_integral_digits: 7,
                             max_value_length: 1000,
                             thousands_sep: None,
+                            decimal_sep: None,
+                            datetime_options: None,
                         };
 
                         let summary_stats =
@@ -559,7 +781,8 @@ impl RVariables {
         let mut assigned: Vec<Variable> = vec![];
         let mut removed: Vec<String> = vec![];
 
-        r_task(|| {
+        // Refreshes yield to interactive tasks such as completions
+        r_task_background(|| {
             let new_bindings = self.bindings();
 
             let mut old_iter = self.current_bindings.get().iter();
@@ -654,14 +877,125 @@ impl RVariables {
 
     // SAFETY: The following methods must be called in an `r_task()`
 
+    /// Shows the variables of `frame_env`, or of the root environment if
+    /// `None`. Returns `true` if the environment changed.
+    fn set_frame_env(&mut self, frame_env: Option<RObject>) -> bool {
+        let env = frame_env.unwrap_or_else(|| self.root_env.get().clone());
+
+        if env.sexp == self.env.get().sexp {
+            return false;
+        }
+
+        self.env = RThreadSafe::new(env);
+        true
+    }
+
     fn bindings(&self) -> RThreadSafe<Vec<Binding>> {
         let env = self.env.get().clone();
         let env = Environment::new_filtered(env, EnvironmentFilter::ExcludeHidden);
 
-        let mut bindings: Vec<Binding> = env.iter().filter_map(|b| b.ok()).collect();
+        // Take a snapshot so that R code running while we format values
+        // doesn't affect the bindings we diff against
+        RThreadSafe::new(env.snapshot().into_bindings())
+    }
+}
+
+/// The environment of the frame R is stopped in, if any. Must be called on
+/// the R thread.
+fn debug_env() -> Option<RObject> {
+    #[cfg(not(test))] // Unit tests do not have an `RMain`
+    {
+        use crate::interface::RMain;
+        if RMain::is_initialized() {
+            return RMain::get().debug_env();
+        }
+    }
+
+    None
+}
 
-        bindings.sort_by(|a, b| a.name.cmp(&b.name));
+/// Converts the result of the `.ps.variables_` deletion helpers
+fn deletion_result(result: RObject) -> anyhow::Result<DeletionResult> {
+    let result: HashMap<String, RObject> = result.try_into()?;
+
+    let field = |name: &str| -> anyhow::Result<Vec<String>> {
+        match result.get(name) {
+            Some(value) => Ok(Vec::<String>::try_from(value.clone())?),
+            None => Err(anyhow!("Expected '{name}' field in deletion result")),
+        }
+    };
+
+    let failed = std::iter::zip(field("failed_names")?, field("failed_reasons")?)
+        .map(|(name, reason)| FailedDeletion { name, reason })
+        .collect();
+
+    Ok(DeletionResult {
+        deleted: field("deleted")?,
+        failed,
+    })
+}
+
+fn close_connection(connection: &RObject) {
+    if let Err(err) = RFunction::new("base", "close")
+        .add(connection.clone())
+        .call()
+    {
+        log::warn!("Variables: Can't close file connection: {err:?}");
+    }
+}
 
-        RThreadSafe::new(bindings)
+#[cfg(test)]
+mod tests {
+    use amalthea::socket::comm::CommInitiator;
+    use crossbeam::channel::bounded;
+
+    use super::*;
+
+    #[test]
+    fn test_variables_follow_debug_frame() {
+        r_task(|| {
+            let root_env = harp::parse_eval_base("local({ root <- 1; environment() })").unwrap();
+            let frame_env = harp::parse_eval_base("local({ frame <- 1; environment() })").unwrap();
+
+            let comm = CommSocket::new(
+                CommInitiator::FrontEnd,
+                String::from("test-variables-debug-frame"),
+                String::from("positron.variables"),
+            );
+            let (comm_manager_tx, _) = bounded::<CommManagerEvent>(0);
+
+            let mut variables = RVariables {
+                comm,
+                comm_manager_tx,
+                env: RThreadSafe::new(root_env.clone()),
+                root_env: RThreadSafe::new(root_env.clone()),
+                current_bindings: RThreadSafe::new(vec![]),
+                version: 0,
+                show_last_value: LastValue::UseOption,
+                showing_last_value: false,
+            };
+            let names = |variables: &mut RVariables| -> Vec<String> {
+                variables
+                    .list_variables()
+                    .into_iter()
+                    .map(|x| x.access_key)
+                    .collect()
+            };
+            assert_eq!(names(&mut variables), vec!["root"]);
+
+            // Entering the browser switches to the frame
+            assert!(variables.set_frame_env(Some(frame_env.clone())));
+            assert_eq!(variables.env.get().sexp, frame_env.sexp);
+            assert_eq!(names(&mut variables), vec!["frame"]);
+
+            // Stepping within the same frame is not a switch
+            assert!(!variables.set_frame_env(Some(frame_env.clone())));
+
+            // Leaving the browser switches back to the root environment
+            assert!(variables.set_frame_env(None));
+            assert_eq!(variables.env.get().sexp, root_env.sexp);
+            assert_eq!(names(&mut variables), vec!["root"]);
+            assert!(!variables.set_frame_env(None));
+        })
     }
 }
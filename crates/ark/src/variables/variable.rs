@@ -12,6 +12,7 @@ use amalthea::comm::variables_comm::ClipboardFormatFormat;
 use amalthea::comm::variables_comm::Variable;
 use amalthea::comm::variables_comm::VariableKind;
 use anyhow::anyhow;
+use harp::altrep::Altrep;
 use harp::call::RArgument;
 use harp::environment::Binding;
 use harp::environment::BindingValue;
@@ -26,12 +27,10 @@ use harp::r_null;
 use harp::r_symbol;
 use harp::symbol::RSymbol;
 use harp::utils::pairlist_size;
-use harp::utils::r_altrep_class;
 use harp::utils::r_assert_type;
 use harp::utils::r_classes;
 use harp::utils::r_format_s4;
 use harp::utils::r_inherits;
-use harp::utils::r_is_altrep;
 use harp::utils::r_is_data_frame;
 use harp::utils::r_is_function;
 use harp::utils::r_is_matrix;
@@ -48,7 +47,6 @@ use harp::utils::r_vec_type;
 use harp::vector::formatted_vector::FormattedVector;
 use harp::vector::names::Names;
 use harp::vector::CharacterVector;
-use harp::vector::IntegerVector;
 use harp::vector::Vector;
 use harp::List;
 use harp::TableKind;
@@ -59,6 +57,8 @@ use stdext::unwrap;
 
 use crate::methods::ArkGenerics;
 use crate::modules::ARK_ENVS;
+use crate::number_format;
+use crate::number_format::NumberFormat;
 
 // Constants.
 const MAX_DISPLAY_VALUE_ENTRIES: usize = 1_000;
@@ -342,26 +342,20 @@ impl WorkspaceVariableDisplayValue {
     }
 
     fn from_default(value: SEXP) -> anyhow::Result<Self> {
-        let formatted = FormattedVector::new(RObject::from(value))?;
-
-        let mut display_value = String::with_capacity(MAX_DISPLAY_VALUE_LENGTH);
-        let mut is_truncated = false;
-
-        // Performance: value is potentially a very large vector, so we need to be careful
-        // to not format every element of value. Instead only format the necessary elements
-        // to display the first MAX_DISPLAY_VALUE_LENGTH characters.
-        'outer: for (i, elt) in formatted.iter_take(MAX_DISPLAY_VALUE_LENGTH)?.enumerate() {
-            if i > 0 {
-                display_value.push_str(" ");
-            }
-            for char in elt.chars() {
-                if display_value.len() >= MAX_DISPLAY_VALUE_LENGTH {
-                    is_truncated = true;
-                    break 'outer;
-                }
-                display_value.push(char);
-            }
-        }
+        // Performance: value is potentially a very large vector. Elements are separated
+        // by a space so no more than `MAX_DISPLAY_VALUE_LENGTH + 1` of them are needed to
+        // fill the display value and detect truncation.
+        let n = MAX_DISPLAY_VALUE_LENGTH + 1;
+
+        // Bare numbers are formatted like in the data explorer and the clipboard
+        let numbers = number_format::format_numbers(value, 0..n, &NumberFormat::variables());
+        let (display_value, is_truncated) = match numbers {
+            Some(numbers) => join_display_values(numbers.into_iter()),
+            None => {
+                let formatted = FormattedVector::new(RObject::from(value))?;
+                join_display_values(formatted.iter_take(n)?)
+            },
+        };
 
         Ok(Self::new(display_value, is_truncated))
     }
@@ -445,8 +439,13 @@ impl WorkspaceVariableDisplayType {
             };
 
             let mut type_info = display_type.clone();
-            if r_is_altrep(value) {
-                type_info.push_str(r_altrep_class(value).as_str())
+            if let Some(altrep) = Altrep::new(value) {
+                type_info.push_str(altrep.class().to_string().as_str());
+
+                // Lazy vectors, e.g. compact sequences
+                if !altrep.is_materialized() {
+                    type_info.push_str(" (not materialized)");
+                }
             }
 
             return Self::new(display_type, type_info);
@@ -835,9 +834,8 @@ impl PositronVariable {
                 VariableKind::Map
             },
 
-            VECSXP => unsafe {
-                let dim = Rf_getAttrib(x, R_DimSymbol);
-                if dim != R_NilValue && Rf_xlength(dim) == 2 {
+            VECSXP => {
+                if r_is_matrix(x) {
                     VariableKind::Table
                 } else {
                     VariableKind::Map
@@ -845,8 +843,7 @@ impl PositronVariable {
             },
 
             LGLSXP => unsafe {
-                let dim = Rf_getAttrib(x, R_DimSymbol);
-                if dim != R_NilValue && Rf_xlength(dim) == 2 {
+                if r_is_matrix(x) {
                     VariableKind::Table
                 } else if Rf_xlength(x) == 1 {
                     if LOGICAL_ELT(x, 0) == R_NaInt {
@@ -860,8 +857,7 @@ impl PositronVariable {
             },
 
             INTSXP => unsafe {
-                let dim = Rf_getAttrib(x, R_DimSymbol);
-                if dim != R_NilValue && Rf_xlength(dim) == 2 {
+                if r_is_matrix(x) {
                     VariableKind::Table
                 } else if Rf_xlength(x) == 1 {
                     if INTEGER_ELT(x, 0) == R_NaInt {
@@ -875,8 +871,7 @@ impl PositronVariable {
             },
 
             REALSXP => unsafe {
-                let dim = Rf_getAttrib(x, R_DimSymbol);
-                if dim != R_NilValue && Rf_xlength(dim) == 2 {
+                if r_is_matrix(x) {
                     VariableKind::Table
                 } else if Rf_xlength(x) == 1 {
                     if R_IsNA(REAL_ELT(x, 0)) == 1 {
@@ -890,8 +885,7 @@ impl PositronVariable {
             },
 
             CPLXSXP => unsafe {
-                let dim = Rf_getAttrib(x, R_DimSymbol);
-                if dim != R_NilValue && Rf_xlength(dim) == 2 {
+                if r_is_matrix(x) {
                     VariableKind::Table
                 } else if Rf_xlength(x) == 1 {
                     let value = COMPLEX_ELT(x, 0);
@@ -906,8 +900,7 @@ impl PositronVariable {
             },
 
             STRSXP => unsafe {
-                let dim = Rf_getAttrib(x, R_DimSymbol);
-                if dim != R_NilValue && Rf_xlength(dim) == 2 {
+                if r_is_matrix(x) {
                     VariableKind::Table
                 } else if Rf_xlength(x) == 1 {
                     if STRING_ELT(x, 0) == R_NaString {
@@ -993,6 +986,7 @@ impl PositronVariable {
         _format: &ClipboardFormatFormat,
     ) -> anyhow::Result<String> {
         let node = Self::resolve_object_from_path(env, &path)?;
+        let format = NumberFormat::variables();
 
         match node {
             EnvironmentVariableNode::Concrete { object } => {
@@ -1009,16 +1003,28 @@ impl PositronVariable {
                         .try_into()?;
 
                     Ok(deparsed.join("\n"))
+                } else if let Some(numbers) =
+                    number_format::format_numbers(object.sexp, 0..usize::MAX, &format)
+                {
+                    Ok(numbers.join(" "))
                 } else {
                     Ok(FormattedVector::new(object)?.iter()?.join(" "))
                 }
             },
             EnvironmentVariableNode::R6Node { .. } => Ok(String::from("")),
             EnvironmentVariableNode::AtomicVectorElement { object, index } => {
+                let range = index as usize..index as usize + 1;
+                if let Some(numbers) = number_format::format_numbers(object.sexp, range, &format) {
+                    return Ok(numbers.join(""));
+                }
                 let formatted = FormattedVector::new(object)?;
                 Ok(formatted.format_elt(index)?)
             },
             EnvironmentVariableNode::Matrixcolumn { object, index } => {
+                let range = harp::Matrix::new(object.sexp)?.column_range(index)?;
+                if let Some(numbers) = number_format::format_numbers(object.sexp, range, &format) {
+                    return Ok(numbers.join(" "));
+                }
                 let clipped = FormattedVector::new(object)?.column_iter(index)?.join(" ");
                 Ok(clipped)
             },
@@ -1206,15 +1212,11 @@ impl PositronVariable {
                 )));
             },
 
-            EnvironmentVariableNode::Matrixcolumn { object, index } => unsafe {
-                let dim = IntegerVector::new(Rf_getAttrib(object.sexp, R_DimSymbol))?;
-                let n_row = dim.get_unchecked(0).unwrap() as isize;
+            EnvironmentVariableNode::Matrixcolumn { object, index } => {
                 let row_index = parse_index(path_elt)?;
+                let index = harp::Matrix::new(object.sexp)?.index(row_index, index)?;
 
-                Ok(EnvironmentVariableNode::AtomicVectorElement {
-                    object,
-                    index: n_row * index + row_index,
-                })
+                Ok(EnvironmentVariableNode::AtomicVectorElement { object, index })
             },
         }
     }
@@ -1274,31 +1276,10 @@ impl PositronVariable {
 
         for col in (0..n_col).take(MAX_DISPLAY_VALUE_ENTRIES) {
             // The display value of columns concatenates the column vector values into a
-            // single string with maximum length of MAX_DISPLAY_VALUE_LENGTH.
-            let mut is_truncated = false;
-            let mut display_value = String::with_capacity(MAX_DISPLAY_VALUE_LENGTH);
-
-            let iter = formatted
-                // Even if each column element takes 0 characters, `MAX_DISPLAY_VALUE_LENGTH`
-                // is enough to fill the display value because we need to account for the space
-                // between elements.
-                .column_iter_n(col as isize, MAX_DISPLAY_VALUE_LENGTH)?
-                .enumerate();
-
-            'outer: for (i, elt) in iter {
-                if i > 0 {
-                    display_value.push_str(" ");
-                }
-                for char in elt.chars() {
-                    if display_value.len() >= MAX_DISPLAY_VALUE_LENGTH {
-                        is_truncated = true;
-                        // We break the outer loop to avoid adding more characters to the
-                        // display value.
-                        break 'outer;
-                    }
-                    display_value.push(char);
-                }
-            }
+            // single string with maximum length of MAX_DISPLAY_VALUE_LENGTH. As for vectors,
+            // `MAX_DISPLAY_VALUE_LENGTH + 1` elements are enough to detect truncation.
+            let elements = formatted.column_iter_n(col as isize, MAX_DISPLAY_VALUE_LENGTH + 1)?;
+            let (display_value, is_truncated) = join_display_values(elements);
 
             variables.push(make_variable(
                 format!("{}", col),
@@ -1312,7 +1293,7 @@ impl PositronVariable {
     }
 
     fn inspect_matrix_column(matrix: SEXP, index: isize) -> anyhow::Result<Vec<Variable>> {
-        let column = harp::table::tbl_get_column(matrix, index as i32, harp::TableKind::Matrix)?;
+        let column = harp::Matrix::new(matrix)?.column(index)?;
 
         let variables: Vec<Variable> = Self::inspect_vector(column.sexp)?
             .into_iter()
@@ -1677,6 +1658,25 @@ fn parse_index(x: &String) -> harp::Result<isize> {
 // We need to be careful when truncating the string, we don't want to return invalid
 // UTF8 sequences. `chars` makes sure we are not splitting a UTF8 character in half.
 // See also https://doc.rust-lang.org/book/ch08-02-strings.html#slicing-strings
+/// Joins formatted elements with spaces into a display value of at most
+/// `MAX_DISPLAY_VALUE_LENGTH` bytes. Returns whether the value was truncated,
+/// i.e. whether some characters or elements were left out.
+fn join_display_values(elements: impl Iterator<Item = String>) -> (String, bool) {
+    let mut display_value = String::with_capacity(MAX_DISPLAY_VALUE_LENGTH);
+
+    for (i, elt) in elements.enumerate() {
+        let sep = if i > 0 { " " } else { "" };
+        for char in sep.chars().chain(elt.chars()) {
+            if display_value.len() >= MAX_DISPLAY_VALUE_LENGTH {
+                return (display_value, true);
+            }
+            display_value.push(char);
+        }
+    }
+
+    (display_value, false)
+}
+
 fn truncate_chars(value: String, len: usize) -> (bool, String) {
     if value.len() > len {
         (true, value.chars().take(len).collect())
@@ -2192,6 +2192,31 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_number_formatting() {
+        r_task(|| {
+            let env = Environment::new_empty().unwrap();
+            let value = harp::parse_eval_base("c(1.5, 1e20, NA, 100, 1/3)").unwrap();
+            env.bind("x".into(), &value);
+
+            // Doesn't depend on the `digits` option
+            let digits = RObject::from(2);
+            let vars = {
+                let _digits = harp::raii::RLocalOption::new("digits", digits.sexp);
+                PositronVariable::inspect(env.clone().into(), &vec![]).unwrap()
+            };
+
+            let expected = "1.5 1e+20 NA 100 0.3333333";
+            assert_eq!(vars[0].display_value, expected);
+
+            let path = vec![String::from("x")];
+            let clipped =
+                PositronVariable::clip(env.into(), &path, &ClipboardFormatFormat::TextPlain)
+                    .unwrap();
+            assert_eq!(clipped, expected);
+        })
+    }
+
     #[test]
     fn test_matrix_display() {
         r_task(|| {
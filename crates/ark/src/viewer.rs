@@ -1,10 +1,12 @@
 //
 // viewer.rs
 //
-// Copyright (C) 2023-2024 Posit Software, PBC. All rights reserved.
+// Copyright (C) 2023-2025 Posit Software, PBC. All rights reserved.
 //
 //
 
+use std::path::Path;
+
 use amalthea::comm::ui_comm::ShowHtmlFileParams;
 use amalthea::comm::ui_comm::UiFrontendEvent;
 use amalthea::socket::iopub::IOPubMessage;
@@ -14,10 +16,18 @@ use crossbeam::channel::Sender;
 use harp::object::RObject;
 use libr::R_NilValue;
 use libr::SEXP;
+use once_cell::sync::Lazy;
+use regex::Captures;
+use regex::Regex;
 
 use crate::interface::RMain;
 use crate::interface::SessionMode;
 
+static RE_SCRIPT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?s)<script\b[^>]*\bsrc="([^"]+)"[^>]*>\s*</script>"#).unwrap());
+static RE_LINK: Lazy<Regex> = Lazy::new(|| Regex::new(r"<link\b[^>]*>").unwrap());
+static RE_HREF: Lazy<Regex> = Lazy::new(|| Regex::new(r#"\bhref="([^"]+)""#).unwrap());
+
 /// Emit HTML output on IOPub for delivery to the client
 ///
 /// - `iopub_tx` - The IOPub channel to send the output on
@@ -28,8 +38,9 @@ fn emit_html_output_jupyter(
     path: String,
     kind: String,
 ) -> Result<()> {
-    // Read the contents of the file
-    let contents = std::fs::read_to_string(path)?;
+    // Read the contents of the file. Frontends only receive the contents so
+    // the dependencies are inlined.
+    let contents = read_self_contained_html(&path)?;
 
     // Create the output object
     let output = serde_json::json!({
@@ -48,6 +59,76 @@ fn emit_html_output_jupyter(
     Ok(())
 }
 
+/// Read an HTML file and inline its dependencies so that it can be displayed
+/// without access to the file system
+fn read_self_contained_html(path: &str) -> Result<String> {
+    let contents = std::fs::read_to_string(path)?;
+
+    // The dependencies are written next to the file, e.g. in the `lib/`
+    // folder created by `htmltools::html_print()`
+    match Path::new(path).parent() {
+        Some(dir) => Ok(inline_dependencies(&contents, dir)),
+        None => Ok(contents),
+    }
+}
+
+/// Replace the scripts and stylesheets that `html` references with paths
+/// relative to `dir` by inline `<script>` and `<style>` elements. Remote and
+/// missing dependencies are left alone.
+fn inline_dependencies(html: &str, dir: &Path) -> String {
+    let html = RE_SCRIPT.replace_all(html, |captures: &Captures| {
+        match read_dependency(dir, &captures[1]) {
+            // Scripts can't contain a closing tag
+            Some(script) => format!(
+                "<script>{}</script>",
+                script.replace("</script", "<\\/script")
+            ),
+            None => captures[0].to_string(),
+        }
+    });
+
+    let html = RE_LINK.replace_all(&html, |captures: &Captures| {
+        let tag = &captures[0];
+        if !tag.contains(r#"rel="stylesheet""#) {
+            return tag.to_string();
+        }
+        let Some(href) = RE_HREF.captures(tag) else {
+            return tag.to_string();
+        };
+        match read_dependency(dir, &href[1]) {
+            Some(style) => format!("<style>{style}</style>"),
+            None => tag.to_string(),
+        }
+    });
+
+    html.into_owned()
+}
+
+fn read_dependency(dir: &Path, href: &str) -> Option<String> {
+    if href.contains("://") || href.starts_with("//") || href.starts_with("data:") {
+        return None;
+    }
+
+    // Relative references are URL-encoded, e.g. spaces are written as `%20`
+    let href = href.replace("%20", " ");
+
+    match std::fs::read_to_string(dir.join(href.as_str())) {
+        Ok(contents) => Some(contents),
+        Err(err) => {
+            log::warn!("Can't inline HTML dependency '{href}': {err:?}");
+            None
+        },
+    }
+}
+
+/// Returns the contents of an HTML file with its dependencies inlined
+#[harp::register]
+pub unsafe extern "C-unwind" fn ps_html_self_contained(path: SEXP) -> anyhow::Result<SEXP> {
+    let path = RObject::view(path).to::<String>()?;
+    let contents = read_self_contained_html(&path)?;
+    Ok(RObject::from(contents).sexp)
+}
+
 #[harp::register]
 pub unsafe extern "C-unwind" fn ps_html_viewer(
     url: SEXP,
@@ -68,14 +149,8 @@ pub unsafe extern "C-unwind" fn ps_html_viewer(
             // Emit HTML output
             let main = RMain::get();
             let iopub_tx = main.get_iopub_tx().clone();
-            match main.session_mode() {
-                SessionMode::Notebook | SessionMode::Background => {
-                    // In notebook mode, send the output as a Jupyter display_data message
-                    if let Err(err) = emit_html_output_jupyter(iopub_tx, path, label) {
-                        log::error!("Failed to emit HTML output: {:?}", err);
-                    }
-                },
-                SessionMode::Console => {
+            match (main.session_mode(), main.get_ui_comm_tx()) {
+                (SessionMode::Console, Some(ui_comm_tx)) => {
                     let is_plot = RObject::view(is_plot).to::<bool>();
                     let is_plot = match is_plot {
                         Ok(is_plot) => is_plot,
@@ -102,15 +177,16 @@ pub unsafe extern "C-unwind" fn ps_html_viewer(
                     };
 
                     let event = UiFrontendEvent::ShowHtmlFile(params);
-
-                    // TODO: What's the right thing to do in `Console` mode when
-                    // we aren't connected to Positron? Right now we error.
-                    let ui_comm_tx = main
-                        .get_ui_comm_tx()
-                        .ok_or_else(|| anyhow::anyhow!("UI comm not connected."))?;
-
                     ui_comm_tx.send_event(event);
                 },
+                _ => {
+                    // In notebook mode, or in console mode when we aren't
+                    // connected to Positron, send the output as a Jupyter
+                    // display_data message rather than opening a browser
+                    if let Err(err) = emit_html_output_jupyter(iopub_tx, path, label) {
+                        log::error!("Failed to emit HTML output: {:?}", err);
+                    }
+                },
             }
         },
         Err(err) => {
@@ -121,3 +197,34 @@ pub unsafe extern "C-unwind" fn ps_html_viewer(
     // No return value
     Ok(R_NilValue)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::viewer::inline_dependencies;
+
+    #[test]
+    fn test_inline_dependencies() {
+        let dir = tempfile::tempdir().unwrap();
+        let lib = dir.path().join("lib").join("widget-1.0");
+        std::fs::create_dir_all(&lib).unwrap();
+        std::fs::write(lib.join("widget.js"), "var x = '</script>';").unwrap();
+        std::fs::write(lib.join("widget.css"), ".widget { color: red; }").unwrap();
+
+        let html = r#"<head>
+<script src="lib/widget-1.0/widget.js"></script>
+<link href="lib/widget-1.0/widget.css" rel="stylesheet" />
+<script src="https://example.com/remote.js"></script>
+<script src="lib/missing.js"></script>
+</head>"#;
+
+        assert_eq!(
+            inline_dependencies(html, dir.path()),
+            r#"<head>
+<script>var x = '<\/script>';</script>
+<style>.widget { color: red; }</style>
+<script src="https://example.com/remote.js"></script>
+<script src="lib/missing.js"></script>
+</head>"#
+        );
+    }
+}
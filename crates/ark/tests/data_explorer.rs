@@ -29,6 +29,8 @@ use amalthea::comm::data_explorer_comm::DataExplorerFrontendEvent;
 use amalthea::comm::data_explorer_comm::DataSelectionIndices;
 use amalthea::comm::data_explorer_comm::DataSelectionRange;
 use amalthea::comm::data_explorer_comm::DataSelectionSingleCell;
+use amalthea::comm::data_explorer_comm::DatetimeFormatOptions;
+use amalthea::comm::data_explorer_comm::DatetimeStyle;
 use amalthea::comm::data_explorer_comm::ExportDataSelectionParams;
 use amalthea::comm::data_explorer_comm::ExportFormat;
 use amalthea::comm::data_explorer_comm::ExportedData;
@@ -44,15 +46,22 @@ use amalthea::comm::data_explorer_comm::GetRowLabelsParams;
 use amalthea::comm::data_explorer_comm::GetSchemaParams;
 use amalthea::comm::data_explorer_comm::RowFilter;
 use amalthea::comm::data_explorer_comm::RowFilterCondition;
+use amalthea::comm::data_explorer_comm::RowFilterGroup;
 use amalthea::comm::data_explorer_comm::RowFilterParams;
 use amalthea::comm::data_explorer_comm::RowFilterType;
+use amalthea::comm::data_explorer_comm::RowLabelMode;
+use amalthea::comm::data_explorer_comm::RowLabelOptions;
 use amalthea::comm::data_explorer_comm::SearchSchemaParams;
 use amalthea::comm::data_explorer_comm::SearchSchemaResult;
 use amalthea::comm::data_explorer_comm::SearchSchemaSortOrder;
 use amalthea::comm::data_explorer_comm::Selection;
 use amalthea::comm::data_explorer_comm::SetRowFiltersParams;
+use amalthea::comm::data_explorer_comm::SetRowLabelOptionsParams;
 use amalthea::comm::data_explorer_comm::SetSortColumnsParams;
+use amalthea::comm::data_explorer_comm::SpatialBoundingBox;
+use amalthea::comm::data_explorer_comm::SpatialMetadata;
 use amalthea::comm::data_explorer_comm::SummaryStatsBoolean;
+use amalthea::comm::data_explorer_comm::SummaryStatsList;
 use amalthea::comm::data_explorer_comm::SummaryStatsNumber;
 use amalthea::comm::data_explorer_comm::SummaryStatsString;
 use amalthea::comm::data_explorer_comm::TableSchema;
@@ -66,9 +75,9 @@ use ark::data_explorer::format::format_column;
 use ark::data_explorer::format::format_string;
 use ark::data_explorer::r_data_explorer::DataObjectEnvInfo;
 use ark::data_explorer::r_data_explorer::RDataExplorer;
+use ark::events::EVENTS;
 use ark::fixtures::r_test_lock;
 use ark::fixtures::socket_rpc_request;
-use ark::lsp::events::EVENTS;
 use ark::r_task::r_task;
 use ark::thread::RThreadSafe;
 use crossbeam::channel::bounded;
@@ -266,7 +275,22 @@ impl RequestBuilder {
     }
 
     fn set_row_filters(filters: Vec<RowFilter>) -> DataExplorerBackendRequest {
-        DataExplorerBackendRequest::SetRowFilters(SetRowFiltersParams { filters })
+        DataExplorerBackendRequest::SetRowFilters(SetRowFiltersParams {
+            filters,
+            groups: None,
+            format_options: None,
+        })
+    }
+
+    fn set_grouped_row_filters(
+        filters: Vec<RowFilter>,
+        groups: Vec<RowFilterGroup>,
+    ) -> DataExplorerBackendRequest {
+        DataExplorerBackendRequest::SetRowFilters(SetRowFiltersParams {
+            filters,
+            groups: Some(groups),
+            format_options: None,
+        })
     }
 
     fn export_data_selection(
@@ -276,6 +300,8 @@ impl RequestBuilder {
         DataExplorerBackendRequest::ExportDataSelection(ExportDataSelectionParams {
             format,
             selection,
+            factor_values: None,
+            datetime_options: None,
         })
     }
 }
@@ -511,6 +537,15 @@ impl SelectionBuilder {
         ColumnSortKey {
             column_index,
             ascending,
+            natural: None,
+        }
+    }
+
+    fn natural_sort_key(column_index: i64, ascending: bool) -> ColumnSortKey {
+        ColumnSortKey {
+            column_index,
+            ascending,
+            natural: Some(true),
         }
     }
 }
@@ -535,6 +570,8 @@ impl RowFilterBuilder {
             condition: RowFilterCondition::And,
             is_valid: None,
             error_message: None,
+            group_id: None,
+            display_text: None,
         }
     }
 
@@ -556,6 +593,8 @@ impl RowFilterBuilder {
             condition: RowFilterCondition::And,
             is_valid: None,
             error_message: None,
+            group_id: None,
+            display_text: None,
         }
     }
 
@@ -568,6 +607,8 @@ impl RowFilterBuilder {
             condition: RowFilterCondition::And,
             is_valid: None,
             error_message: None,
+            group_id: None,
+            display_text: None,
         }
     }
 
@@ -580,6 +621,8 @@ impl RowFilterBuilder {
             condition: RowFilterCondition::And,
             is_valid: None,
             error_message: None,
+            group_id: None,
+            display_text: None,
         }
     }
 
@@ -592,6 +635,8 @@ impl RowFilterBuilder {
             condition: RowFilterCondition::And,
             is_valid: None,
             error_message: None,
+            group_id: None,
+            display_text: None,
         }
     }
 
@@ -604,6 +649,8 @@ impl RowFilterBuilder {
             condition: RowFilterCondition::And,
             is_valid: None,
             error_message: None,
+            group_id: None,
+            display_text: None,
         }
     }
 
@@ -616,6 +663,8 @@ impl RowFilterBuilder {
             condition: RowFilterCondition::And,
             is_valid: None,
             error_message: None,
+            group_id: None,
+            display_text: None,
         }
     }
 
@@ -634,6 +683,8 @@ impl RowFilterBuilder {
             condition: RowFilterCondition::And,
             is_valid: None,
             error_message: None,
+            group_id: None,
+            display_text: None,
         }
     }
 }
@@ -723,7 +774,9 @@ fn default_format_options() -> FormatOptions {
         small_num_digits: 4,
         max_integral_digits: 7,
         thousands_sep: Some(",".to_string()),
+        decimal_sep: None,
         max_value_length: 100,
+        datetime_options: None,
     }
 }
 
@@ -976,6 +1029,30 @@ fn test_matrix_support() {
     TestAssertions::assert_row_filters_applied(socket, filters, 8, Some(false));
 }
 
+#[test]
+fn test_list_support() {
+    let _lock = r_test_lock();
+    let setup =
+        TestSetup::from_expression("list(a = 1:3, b = list(x = 1), c = 'foo')", None).unwrap();
+    let socket = setup.socket();
+
+    // Lists are shown with a row per element and name, type, and summary columns
+    TestAssertions::assert_schema_columns(socket, vec![0, 1, 2], 3);
+
+    TestAssertions::assert_data_values(socket, 0, 3, vec![0, 1, 2], |data| {
+        let value = |x: &str| ColumnValue::FormattedValue(x.to_string());
+
+        assert_eq!(data[0], vec![value("a"), value("b"), value("c")]);
+        assert_eq!(data[1], vec![
+            value("integer [3]"),
+            value("list [1]"),
+            value("character [1]")
+        ]);
+        assert_eq!(data[2][0], value("1 2 3"));
+        assert_eq!(data[2][2], value("foo"));
+    });
+}
+
 #[test]
 fn test_data_table_support() {
     let _lock = r_test_lock();
@@ -1194,6 +1271,8 @@ fn test_search_filters() {
             value: "marshmallows".to_string(),
         })),
         error_message: None,
+        group_id: None,
+        display_text: None,
     };
     let req = RequestBuilder::set_row_filters(vec![year_filter.clone()]);
 
@@ -1306,6 +1385,7 @@ fn test_live_updates() {
     let sort_keys = vec![ColumnSortKey {
         column_index: 0,
         ascending: true,
+        natural: None,
     }];
     let req = DataExplorerBackendRequest::SetSortColumns(SetSortColumnsParams {
         sort_keys: sort_keys.clone(),
@@ -1424,6 +1504,8 @@ fn test_invalid_filters_preserved() {
         is_valid: None,
         params: None,
         error_message: None,
+        group_id: None,
+        display_text: None,
     };
 
     let req = RequestBuilder::set_row_filters(vec![x_is_empty.clone()]);
@@ -1462,6 +1544,9 @@ fn test_invalid_filters_preserved() {
             assert_eq!(state.row_filters[0].is_valid, Some(false));
             assert!(state.row_filters[0].error_message.is_some());
             assert_eq!(state.table_shape.num_rows, 3);
+
+            // The backend summarizes the filter for the frontends
+            assert_eq!(state.row_filters[0].display_text.as_deref(), Some("x is empty"));
         }
     );
 
@@ -1526,6 +1611,136 @@ fn test_invalid_filters_preserved() {
     });
 }
 
+#[test]
+fn test_compiled_filters() {
+    let _lock = r_test_lock();
+
+    r_task(|| {
+        harp::parse_eval_global(
+            r#"df_filters <- data.frame(x = c(1, 2, 3, 4, 5), y = c("a", "b", "c", "d", "e"))"#,
+        )
+        .unwrap();
+    });
+
+    let setup = TestSetup::new("df_filters");
+    let socket = setup.socket();
+    let schema = TestAssertions::get_column_schema(socket, vec![0, 1]);
+    let x = || schema.columns[0].clone();
+    let y = || schema.columns[1].clone();
+
+    // `x > 1 & x < 5 | y == "a"`
+    let mut y_filter = RowFilterBuilder::text_search(y(), TextSearchType::Contains, "a", true);
+    y_filter.condition = RowFilterCondition::Or;
+    TestAssertions::assert_row_filters_applied(
+        socket,
+        vec![
+            RowFilterBuilder::comparison(x(), FilterComparisonOp::Gt, "1"),
+            RowFilterBuilder::comparison(x(), FilterComparisonOp::Lt, "5"),
+            y_filter,
+        ],
+        4,
+        Some(false),
+    );
+
+    // The invalid regex isn't evaluated because no rows are left
+    let regex_filter = RowFilterBuilder::text_search(y(), TextSearchType::RegexMatch, "(", true);
+    TestAssertions::assert_row_filters_applied(
+        socket,
+        vec![
+            RowFilterBuilder::comparison(x(), FilterComparisonOp::Gt, "10"),
+            regex_filter.clone(),
+        ],
+        0,
+        Some(false),
+    );
+
+    // When a filter fails, the other filters are still applied
+    TestAssertions::assert_row_filters_applied(
+        socket,
+        vec![
+            RowFilterBuilder::comparison(x(), FilterComparisonOp::Gt, "1"),
+            regex_filter,
+        ],
+        4,
+        Some(true),
+    );
+
+    r_task(|| {
+        harp::parse_eval_global("rm(df_filters)").unwrap();
+    });
+}
+
+#[test]
+fn test_grouped_filters() {
+    let _lock = r_test_lock();
+
+    r_task(|| {
+        harp::parse_eval_global(
+            r#"df_groups <- data.frame(x = c(1, 2, 3, 4, 5), y = c("a", "b", "c", "d", "e"))"#,
+        )
+        .unwrap();
+    });
+
+    let setup = TestSetup::new("df_groups");
+    let socket = setup.socket();
+    let schema = TestAssertions::get_column_schema(socket, vec![0, 1]);
+    let x = || schema.columns[0].clone();
+    let y = || schema.columns[1].clone();
+
+    let grouped = |mut filter: RowFilter, condition: RowFilterCondition| {
+        filter.group_id = Some("group".to_string());
+        filter.condition = condition;
+        filter
+    };
+    let groups = vec![RowFilterGroup {
+        group_id: "group".to_string(),
+        condition: RowFilterCondition::And,
+        parent_id: None,
+    }];
+
+    // `x < 3 & (y == "a" | y == "e")`. Without the group, row 5 would match.
+    let filters = vec![
+        RowFilterBuilder::comparison(x(), FilterComparisonOp::Lt, "3"),
+        grouped(
+            RowFilterBuilder::text_search(y(), TextSearchType::Contains, "a", true),
+            RowFilterCondition::And,
+        ),
+        grouped(
+            RowFilterBuilder::text_search(y(), TextSearchType::Contains, "e", true),
+            RowFilterCondition::Or,
+        ),
+    ];
+    let req = RequestBuilder::set_grouped_row_filters(filters.clone(), groups.clone());
+    assert_match!(socket_rpc(socket, req),
+        DataExplorerBackendReply::SetRowFiltersReply(
+            FilterResult { selected_num_rows: 1, had_errors: Some(false) }
+        )
+    );
+
+    // The groups are part of the state
+    TestAssertions::assert_state(socket, |state| {
+        assert_eq!(state.row_filter_groups, Some(groups.clone()));
+        assert_eq!(state.row_filters[2].group_id, Some("group".to_string()));
+    });
+
+    // A failing filter in a group is skipped
+    let mut filters = filters;
+    filters[2] = grouped(
+        RowFilterBuilder::text_search(y(), TextSearchType::RegexMatch, "(", true),
+        RowFilterCondition::Or,
+    );
+    let req = RequestBuilder::set_grouped_row_filters(filters, groups);
+    assert_match!(socket_rpc(socket, req),
+        DataExplorerBackendReply::SetRowFiltersReply(
+            FilterResult { selected_num_rows: 1, had_errors: Some(true) }
+        )
+    );
+
+    r_task(|| {
+        harp::parse_eval_global("rm(df_groups)").unwrap();
+    });
+}
+
 #[test]
 fn test_data_explorer_special_values() {
     let _lock = r_test_lock();
@@ -1604,6 +1819,7 @@ fn test_export_data() {
     let req = RequestBuilder::set_sort_columns(vec![ColumnSortKey {
         column_index: 0,
         ascending: false,
+        natural: None,
     }]);
     socket_rpc(&socket, req);
 
@@ -1663,6 +1879,7 @@ fn test_update_data_filters_reapplied() {
     let req = RequestBuilder::set_sort_columns(vec![ColumnSortKey {
         column_index: 0,
         ascending: true,
+        natural: None,
     }]);
     socket_rpc(&socket, req);
 
@@ -1996,6 +2213,170 @@ fn test_histogram_single_bin_same_values() {
     });
 }
 
+#[test]
+fn test_list_column_profiles() {
+    let _lock = r_test_lock();
+
+    let socket = open_data_explorer_from_expression(
+        "local({ df <- data.frame(id = 1:5); df$x <- list(1:3, NULL, character(), 'a', NA); df })",
+        None,
+    )
+    .unwrap();
+
+    let req = RequestBuilder::get_column_profiles("list_profiles".to_string(), vec![
+        ProfileBuilder::null_count(1),
+        ProfileBuilder::summary_stats(1),
+        ProfileBuilder::small_histogram(1, ColumnHistogramParamsMethod::Fixed, 3, None),
+    ]);
+
+    expect_column_profile_results(&socket, req, |profiles| {
+        // `NULL` and `NA` elements are null
+        assert_eq!(profiles[0].null_count, Some(2));
+
+        let stats = profiles[1].summary_stats.clone().unwrap();
+        assert_eq!(
+            stats.list_stats,
+            Some(SummaryStatsList {
+                num_empty: 1,
+                min_length: Some(0),
+                max_length: Some(3),
+            })
+        );
+
+        // The histogram counts the lengths of the non-null elements
+        let histogram = profiles[2].small_histogram.clone().unwrap();
+        assert_eq!(histogram.bin_counts.iter().sum::<i64>(), 4);
+    });
+}
+
+#[test]
+fn test_datetime_filter_values() {
+    let _lock = r_test_lock();
+    let setup = TestSetup::from_expression(
+        "data.frame(x = as.POSIXct(c(
+            '2024-01-01 10:00:00',
+            '2024-01-01 12:00:00',
+            '2024-01-01 14:00:00'
+        ), tz = 'UTC'))",
+        None,
+    )
+    .unwrap();
+    let socket = setup.socket();
+
+    let req = RequestBuilder::get_schema(vec![0]);
+    let schema = match socket_rpc(socket, req) {
+        DataExplorerBackendReply::GetSchemaReply(schema) => schema,
+        _ => panic!("Expected schema reply"),
+    };
+
+    // Values are interpreted in the time zone of the column
+    let filters = vec![RowFilterBuilder::comparison(
+        schema.columns[0].clone(),
+        FilterComparisonOp::Gt,
+        "2024-01-01 11:00:00",
+    )];
+    TestAssertions::assert_row_filters_applied(socket, filters, 2, Some(false));
+
+    // ISO-8601 values, as displayed with datetime format options, are
+    // accepted with their offset
+    let filters = vec![RowFilterBuilder::comparison(
+        schema.columns[0].clone(),
+        FilterComparisonOp::Eq,
+        "2024-01-01T13:00:00+01:00",
+    )];
+    TestAssertions::assert_row_filters_applied(socket, filters, 1, Some(false));
+
+    let filters = vec![RowFilterBuilder::comparison(
+        schema.columns[0].clone(),
+        FilterComparisonOp::GtEq,
+        "2024-01-01T14:00:00.000Z",
+    )];
+    TestAssertions::assert_row_filters_applied(socket, filters, 1, Some(false));
+
+    // Displayed values follow the datetime format options
+    let mut format_options = default_format_options();
+    format_options.datetime_options = Some(DatetimeFormatOptions {
+        style: DatetimeStyle::Iso,
+        timezone: Some(String::from("Europe/Paris")),
+        sub_second_digits: None,
+    });
+    let req = get_data_values_request(0, 1, vec![0], format_options);
+    assert_match!(socket_rpc(socket, req),
+        DataExplorerBackendReply::GetDataValuesReply(data) => {
+            assert_eq!(data.columns[0], vec![ColumnValue::FormattedValue(
+                "2024-01-01T15:00:00+01:00".to_string()
+            )]);
+        }
+    );
+}
+
+#[test]
+fn test_sf_support() {
+    let _lock = r_test_lock();
+
+    // An sf object built by hand so that the test doesn't depend on sf
+    let setup = TestSetup::from_expression(
+        "local({
+            point <- function(x, y) structure(c(x, y), class = c('XY', 'POINT', 'sfg'))
+            geometry <- structure(
+                list(point(1, 2), point(3.5, -4), point(NA_real_, NA_real_)),
+                class = c('sfc_POINT', 'sfc'),
+                crs = structure(list(input = 'EPSG:4326', wkt = ''), class = 'crs'),
+                bbox = structure(c(xmin = 1, ymin = -4, xmax = 3.5, ymax = 2), class = 'bbox')
+            )
+            x <- data.frame(id = 1:3)
+            x$geometry <- geometry
+            structure(x, class = c('sf', 'data.frame'), sf_column = 'geometry')
+        })",
+        None,
+    )
+    .unwrap();
+    let socket = setup.socket();
+
+    assert_match!(socket_rpc(socket, DataExplorerBackendRequest::GetState),
+        DataExplorerBackendReply::GetStateReply(state) => {
+            assert_eq!(state.spatial_metadata, Some(SpatialMetadata {
+                geometry_column_index: 1,
+                crs: Some(String::from("EPSG:4326")),
+                bbox: Some(SpatialBoundingBox {
+                    xmin: 1.0,
+                    ymin: -4.0,
+                    xmax: 3.5,
+                    ymax: 2.0,
+                }),
+            }));
+        }
+    );
+
+    // Geometries are formatted as WKT summaries
+    TestAssertions::assert_data_values(socket, 0, 3, vec![1], |data| {
+        let value = |x: &str| ColumnValue::FormattedValue(x.to_string());
+        assert_eq!(data[0], vec![
+            value("POINT (1 2)"),
+            value("POINT (3.5 -4)"),
+            value("POINT EMPTY")
+        ]);
+    });
+
+    // Geometries have no summary stats, but don't fail the profile
+    let req = RequestBuilder::get_column_profiles("sf_profiles".to_string(), vec![
+        ProfileBuilder::summary_stats(1),
+    ]);
+    expect_column_profile_results(socket, req, |profiles| {
+        let stats = profiles[0].summary_stats.clone().unwrap();
+        assert_eq!(stats.number_stats, None);
+        assert_eq!(stats.list_stats, None);
+    });
+
+    // Other tables have no spatial metadata
+    let socket = open_data_explorer_from_expression("data.frame(x = 1:3)", None).unwrap();
+    assert_match!(socket_rpc(&socket, DataExplorerBackendRequest::GetState),
+        DataExplorerBackendReply::GetStateReply(state) => {
+            assert_eq!(state.spatial_metadata, None);
+        }
+    );
+}
+
 #[test]
 fn test_frequency_table() {
     let _lock = r_test_lock();
@@ -2061,6 +2442,104 @@ fn test_row_names_matrix() {
     );
 }
 
+#[test]
+fn test_row_label_options() {
+    let _lock = r_test_lock();
+
+    // Automatic row names only repeat the row positions
+    let socket = open_data_explorer_from_expression("data.frame(x = 1:3)", None).unwrap();
+    assert_match!(socket_rpc(&socket, DataExplorerBackendRequest::GetState),
+        DataExplorerBackendReply::GetStateReply(state) => {
+            assert_eq!(state.has_meaningful_row_names, Some(false));
+        }
+    );
+
+    r_task(|| {
+        let code = "x <- data.frame(id = c('a', 'b', 'c'), y = 1:3, row.names = 4:6)";
+        harp::parse_eval_global(code).unwrap();
+    });
+    let socket = open_data_explorer_from_expression("x", Some("x")).unwrap();
+
+    assert_match!(socket_rpc(&socket, DataExplorerBackendRequest::GetState),
+        DataExplorerBackendReply::GetStateReply(state) => {
+            assert_eq!(state.has_meaningful_row_names, Some(true));
+            assert_eq!(state.has_row_labels, true);
+            assert_eq!(state.row_label_options, Some(RowLabelOptions {
+                mode: RowLabelMode::RowNames,
+                column_index: None,
+            }));
+        }
+    );
+
+    let get_row_labels = || {
+        DataExplorerBackendRequest::GetRowLabels(GetRowLabelsParams {
+            selection: ArraySelection::SelectIndices(DataSelectionIndices {
+                indices: vec![0, 2],
+            }),
+            format_options: default_format_options(),
+        })
+    };
+    let set_row_label_options = |mode: RowLabelMode, column_index: Option<i64>| {
+        DataExplorerBackendRequest::SetRowLabelOptions(SetRowLabelOptionsParams {
+            options: RowLabelOptions { mode, column_index },
+        })
+    };
+
+    // Label rows with the values of the `id` column
+    assert_match!(socket_rpc(&socket, set_row_label_options(RowLabelMode::Column, Some(0))),
+        DataExplorerBackendReply::SetRowLabelOptionsReply()
+    );
+    assert_match!(socket_rpc(&socket, get_row_labels()),
+        DataExplorerBackendReply::GetRowLabelsReply(row_labels) => {
+            assert_eq!(row_labels.row_labels, vec![vec!["a", "c"]]);
+        }
+    );
+
+    // The labeling column is followed when the schema changes
+    r_task(|| {
+        harp::parse_eval_global("x <- data.frame(z = 0, id = c('d', 'e', 'f'))").unwrap();
+    });
+    EVENTS.console_prompt.emit(());
+    assert_match!(socket.outgoing_rx.recv_timeout(RECV_TIMEOUT).unwrap(),
+        CommMsg::Data(value) => {
+            assert_match!(serde_json::from_value::<DataExplorerFrontendEvent>(value).unwrap(),
+                DataExplorerFrontendEvent::SchemaUpdate);
+    });
+    assert_match!(socket_rpc(&socket, DataExplorerBackendRequest::GetState),
+        DataExplorerBackendReply::GetStateReply(state) => {
+            assert_eq!(state.has_meaningful_row_names, Some(false));
+            assert_eq!(state.row_label_options, Some(RowLabelOptions {
+                mode: RowLabelMode::Column,
+                column_index: Some(1),
+            }));
+        }
+    );
+    assert_match!(socket_rpc(&socket, get_row_labels()),
+        DataExplorerBackendReply::GetRowLabelsReply(row_labels) => {
+            assert_eq!(row_labels.row_labels, vec![vec!["d", "f"]]);
+        }
+    );
+
+    // Hide the row labels
+    assert_match!(socket_rpc(&socket, set_row_label_options(RowLabelMode::Hidden, None)),
+        DataExplorerBackendReply::SetRowLabelOptionsReply()
+    );
+    assert_match!(socket_rpc(&socket, DataExplorerBackendRequest::GetState),
+        DataExplorerBackendReply::GetStateReply(state) => {
+            assert_eq!(state.has_row_labels, false);
+        }
+    );
+
+    // The labeling column must exist
+    let req = set_row_label_options(RowLabelMode::Column, Some(10));
+    let reply = socket_rpc_request::<DataExplorerBackendRequest, serde_json::Value>(&socket, req);
+    assert!(reply.get("error").is_some());
+
+    r_task(|| {
+        harp::parse_eval_global("rm(x)").unwrap();
+    });
+}
+
 #[test]
 fn test_schema_identification() {
     let _lock = r_test_lock();
@@ -2238,7 +2717,11 @@ fn test_search_schema_data_type_filters() {
 
     // Test filter for all numeric-like types: Integer, Floating and Date
     let req = RequestBuilder::search_schema_data_types(
-        vec![ColumnDisplayType::Integer, ColumnDisplayType::Floating, ColumnDisplayType::Date],
+        vec![
+            ColumnDisplayType::Integer,
+            ColumnDisplayType::Floating,
+            ColumnDisplayType::Date,
+        ],
         SearchSchemaSortOrder::Original,
     );
     TestAssertions::assert_search_matches(socket, req, vec![1, 2, 4]); // age, score, date_joined
@@ -2786,6 +3269,7 @@ fn test_export_with_sort_order() {
     let req = RequestBuilder::set_sort_columns(vec![ColumnSortKey {
         column_index: 1, // 'value' column
         ascending: true,
+        natural: None,
     }]);
     assert_match!(
         socket_rpc(socket, req),
@@ -2802,6 +3286,8 @@ fn test_export_with_sort_order() {
             }),
         },
         format: ExportFormat::Csv,
+        factor_values: None,
+        datetime_options: None,
     });
     assert_match!(socket_rpc(socket, req),
         DataExplorerBackendReply::ExportDataSelectionReply(ExportedData { data, format }) => {
@@ -2820,6 +3306,8 @@ fn test_export_with_sort_order() {
             }),
         },
         format: ExportFormat::Csv,
+        factor_values: None,
+        datetime_options: None,
     });
     assert_match!(socket_rpc(socket, req),
         DataExplorerBackendReply::ExportDataSelectionReply(ExportedData { data, format }) => {
@@ -2839,6 +3327,8 @@ fn test_export_with_sort_order() {
             }),
         },
         format: ExportFormat::Csv,
+        factor_values: None,
+        datetime_options: None,
     });
     assert_match!(socket_rpc(socket, req),
         DataExplorerBackendReply::ExportDataSelectionReply(ExportedData { data, format }) => {
@@ -2852,6 +3342,7 @@ fn test_export_with_sort_order() {
     let req = RequestBuilder::set_sort_columns(vec![ColumnSortKey {
         column_index: 1, // 'value' column
         ascending: false,
+        natural: None,
     }]);
     assert_match!(
         socket_rpc(socket, req),
@@ -2868,6 +3359,8 @@ fn test_export_with_sort_order() {
             }),
         },
         format: ExportFormat::Csv,
+        factor_values: None,
+        datetime_options: None,
     });
     assert_match!(socket_rpc(socket, req),
         DataExplorerBackendReply::ExportDataSelectionReply(ExportedData { data, format }) => {
@@ -2882,10 +3375,12 @@ fn test_export_with_sort_order() {
         ColumnSortKey {
             column_index: 0, // 'id' column
             ascending: true,
+            natural: None,
         },
         ColumnSortKey {
             column_index: 2, // 'name' column as secondary sort
             ascending: false,
+            natural: None,
         },
     ]);
     assert_match!(
@@ -2902,6 +3397,8 @@ fn test_export_with_sort_order() {
             }),
         },
         format: ExportFormat::Tsv, // Also test TSV format
+        factor_values: None,
+        datetime_options: None,
     });
     assert_match!(socket_rpc(socket, req),
         DataExplorerBackendReply::ExportDataSelectionReply(ExportedData { data, format }) => {
@@ -2917,6 +3414,126 @@ fn test_export_with_sort_order() {
     });
 }
 
+#[test]
+fn test_sort_collation() {
+    let _lock = r_test_lock();
+
+    r_task(|| {
+        harp::parse_eval_global(
+            r#"
+            df_collation <- data.frame(
+                id = 1:6,
+                name = c("b", "B", "a", "A", "b", NA)
+            )
+        "#,
+        )
+        .unwrap();
+    });
+
+    let setup = TestSetup::new("df_collation");
+    let socket = setup.socket();
+
+    let assert_sorted_ids = |ascending: bool, expected: Vec<&str>| {
+        let sort_keys = vec![SelectionBuilder::column_sort_key(1, ascending)];
+        TestAssertions::assert_sort_columns_applied(socket, sort_keys);
+
+        TestAssertions::assert_data_values(socket, 0, 6, vec![0], |data| {
+            let expected: Vec<ColumnValue> = expected
+                .iter()
+                .map(|id| ColumnValue::FormattedValue(id.to_string()))
+                .collect();
+            assert_eq!(data[0], expected);
+        });
+    };
+
+    // The C collation is the default. Ties keep their original order, in
+    // both directions, and missing values come last.
+    assert_sorted_ids(true, vec!["4", "2", "3", "1", "5", "6"]);
+    assert_sorted_ids(false, vec!["1", "5", "3", "2", "4", "6"]);
+
+    // ICU sorts lowercase letters before their uppercase counterparts
+    let has_stringi: bool = r_task(|| {
+        harp::parse_eval_global(r#".ps.is_installed("stringi")"#)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    });
+    if has_stringi {
+        r_task(|| {
+            harp::parse_eval_global(r#"options(ark.data_explorer.collation = "icu")"#).unwrap();
+        });
+        assert_sorted_ids(true, vec!["3", "4", "1", "5", "2", "6"]);
+        assert_sorted_ids(false, vec!["2", "1", "5", "4", "3", "6"]);
+    }
+
+    // Unknown collations fall back to the C collation
+    r_task(|| {
+        harp::parse_eval_global(r#"options(ark.data_explorer.collation = "unknown")"#).unwrap();
+    });
+    assert_sorted_ids(true, vec!["4", "2", "3", "1", "5", "6"]);
+
+    // Clean up
+    r_task(|| {
+        harp::parse_eval_global("options(ark.data_explorer.collation = NULL)").unwrap();
+        harp::parse_eval_global("rm(df_collation)").unwrap();
+    });
+}
+
+#[test]
+fn test_natural_sort() {
+    let _lock = r_test_lock();
+
+    r_task(|| {
+        harp::parse_eval_global(
+            r#"
+            df_natural <- data.frame(
+                id = 1:5,
+                file = c("file10", "file2", NA, "file1", "file2")
+            )
+        "#,
+        )
+        .unwrap();
+    });
+
+    let setup = TestSetup::new("df_natural");
+    let socket = setup.socket();
+
+    let assert_sorted_ids = |sort_key: ColumnSortKey, expected: Vec<&str>| {
+        TestAssertions::assert_sort_columns_applied(socket, vec![sort_key]);
+
+        TestAssertions::assert_data_values(socket, 0, 5, vec![0], |data| {
+            let expected: Vec<ColumnValue> = expected
+                .iter()
+                .map(|id| ColumnValue::FormattedValue(id.to_string()))
+                .collect();
+            assert_eq!(data[0], expected);
+        });
+    };
+
+    // Without the flag, "file10" sorts before "file2"
+    assert_sorted_ids(SelectionBuilder::column_sort_key(1, true), vec![
+        "4", "1", "2", "5", "3",
+    ]);
+
+    // Ties keep their original order and missing values come last
+    assert_sorted_ids(SelectionBuilder::natural_sort_key(1, true), vec![
+        "4", "2", "5", "1", "3",
+    ]);
+    assert_sorted_ids(SelectionBuilder::natural_sort_key(1, false), vec![
+        "1", "2", "5", "4", "3",
+    ]);
+
+    // The flag is ignored for other types of columns
+    assert_sorted_ids(SelectionBuilder::natural_sort_key(0, false), vec![
+        "5", "4", "3", "2", "1",
+    ]);
+
+    // Clean up
+    r_task(|| {
+        harp::parse_eval_global("rm(df_natural)").unwrap();
+    });
+}
+
 #[test]
 fn test_empty_data_frame_schema() {
     let _lock = r_test_lock();
@@ -3004,11 +3621,9 @@ fn test_empty_data_frame_state() {
     let _lock = r_test_lock();
 
     // Test state request with 0-row data frame
-    let socket = open_data_explorer_from_expression(
-        "data.frame(x = numeric(0), y = character(0))",
-        None,
-    )
-    .unwrap();
+    let socket =
+        open_data_explorer_from_expression("data.frame(x = numeric(0), y = character(0))", None)
+            .unwrap();
 
     assert_match!(socket_rpc(&socket, DataExplorerBackendRequest::GetState),
         DataExplorerBackendReply::GetStateReply(state) => {
@@ -3032,8 +3647,10 @@ fn test_empty_data_frame_column_profiles() {
     .unwrap();
 
     // Test histogram profile for empty numeric column
-    let histogram_req = ProfileBuilder::small_histogram(0, ColumnHistogramParamsMethod::Fixed, 10, None);
-    let req = RequestBuilder::get_column_profiles("empty_histogram".to_string(), vec![histogram_req]);
+    let histogram_req =
+        ProfileBuilder::small_histogram(0, ColumnHistogramParamsMethod::Fixed, 10, None);
+    let req =
+        RequestBuilder::get_column_profiles("empty_histogram".to_string(), vec![histogram_req]);
 
     expect_column_profile_results(&socket, req, |profiles| {
         let histogram = profiles[0].small_histogram.clone().unwrap();
@@ -3043,7 +3660,8 @@ fn test_empty_data_frame_column_profiles() {
 
     // Test frequency table for empty string column
     let freq_table_req = ProfileBuilder::small_frequency_table(1, 5);
-    let req = RequestBuilder::get_column_profiles("empty_freq_table".to_string(), vec![freq_table_req]);
+    let req =
+        RequestBuilder::get_column_profiles("empty_freq_table".to_string(), vec![freq_table_req]);
 
     expect_column_profile_results(&socket, req, |profiles| {
         let freq_table = profiles[0].small_frequency_table.clone().unwrap();
@@ -3070,8 +3688,10 @@ fn test_single_row_data_frame_column_profiles() {
     .unwrap();
 
     // Test histogram profile for single value numeric column
-    let histogram_req = ProfileBuilder::small_histogram(0, ColumnHistogramParamsMethod::Fixed, 10, None);
-    let req = RequestBuilder::get_column_profiles("single_histogram".to_string(), vec![histogram_req]);
+    let histogram_req =
+        ProfileBuilder::small_histogram(0, ColumnHistogramParamsMethod::Fixed, 10, None);
+    let req =
+        RequestBuilder::get_column_profiles("single_histogram".to_string(), vec![histogram_req]);
 
     expect_column_profile_results(&socket, req, |profiles| {
         let histogram = profiles[0].small_histogram.clone().unwrap();
@@ -3081,7 +3701,8 @@ fn test_single_row_data_frame_column_profiles() {
 
     // Test frequency table for single value string column
     let freq_table_req = ProfileBuilder::small_frequency_table(1, 5);
-    let req = RequestBuilder::get_column_profiles("single_freq_table".to_string(), vec![freq_table_req]);
+    let req =
+        RequestBuilder::get_column_profiles("single_freq_table".to_string(), vec![freq_table_req]);
 
     expect_column_profile_results(&socket, req, |profiles| {
         let freq_table = profiles[0].small_frequency_table.clone().unwrap();
@@ -3099,7 +3720,10 @@ fn test_single_row_data_frame_column_profiles() {
 
     for method in histogram_methods {
         let histogram_req = ProfileBuilder::small_histogram(3, method.clone(), 10, None); // single_int column
-        let req = RequestBuilder::get_column_profiles(format!("single_histogram_{:?}", method), vec![histogram_req]);
+        let req =
+            RequestBuilder::get_column_profiles(format!("single_histogram_{:?}", method), vec![
+                histogram_req,
+            ]);
 
         expect_column_profile_results(&socket, req, |profiles| {
             let histogram = profiles[0].small_histogram.clone().unwrap();
@@ -1,7 +1,13 @@
 use amalthea::fixtures::dummy_frontend::ExecuteRequestOptions;
+use amalthea::wire::complete_request::CompleteRequest;
+use amalthea::wire::diagnostics_request::DiagnosticsRequest;
+use amalthea::wire::execute_warnings::ExecuteWarning;
+use amalthea::wire::inspect_request::InspectRequest;
 use amalthea::wire::jupyter_message::Message;
+use amalthea::wire::jupyter_message::Status;
 use amalthea::wire::kernel_info_request::KernelInfoRequest;
 use ark::fixtures::DummyArkFrontend;
+use ark::r_task::r_task;
 use stdext::assert_match;
 
 #[test]
@@ -21,6 +27,44 @@ fn test_kernel_info() {
     frontend.recv_iopub_idle();
 }
 
+#[test]
+fn test_complete_request() {
+    let frontend = DummyArkFrontend::lock();
+
+    frontend.send_shell(CompleteRequest {
+        code: String::from("base::paste"),
+        cursor_pos: 11,
+    });
+
+    assert_match!(frontend.recv_shell(), Message::CompleteReply(reply) => {
+        assert_eq!(reply.content.cursor_start, 6);
+        assert_eq!(reply.content.cursor_end, 11);
+        assert!(reply.content.matches.iter().any(|m| m.starts_with("paste0")));
+    });
+
+    frontend.recv_iopub_busy();
+    frontend.recv_iopub_idle();
+}
+
+#[test]
+fn test_inspect_request() {
+    let frontend = DummyArkFrontend::lock();
+
+    frontend.send_shell(InspectRequest {
+        code: String::from("paste(\"a\")"),
+        cursor_pos: 3,
+        detail_level: 0,
+    });
+
+    assert_match!(frontend.recv_shell(), Message::InspectReply(reply) => {
+        assert!(reply.content.found);
+        assert!(reply.content.data["text/markdown"].is_string());
+    });
+
+    frontend.recv_iopub_busy();
+    frontend.recv_iopub_idle();
+}
+
 #[test]
 fn test_execute_request() {
     let frontend = DummyArkFrontend::lock();
@@ -38,6 +82,55 @@ fn test_execute_request() {
     assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
 }
 
+#[test]
+fn test_execute_request_mime_bundle() {
+    let frontend = DummyArkFrontend::lock();
+
+    let code = r#"
+        .ps.display.registerRenderer("ark_test_display", function(x) {
+            list("text/html" = "<b>html</b>", "text/plain" = "ignored")
+        })
+        structure(list(), class = "ark_test_display")
+    "#;
+    frontend.send_execute_request(code, ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+
+    let input = frontend.recv_iopub_execute_input();
+    assert_match!(frontend.recv_iopub(), Message::ExecuteResult(msg) => {
+        assert_eq!(msg.content.data["text/html"], "<b>html</b>");
+        assert_ne!(msg.content.data["text/plain"], "ignored");
+    });
+
+    frontend.recv_iopub_idle();
+    assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
+}
+
+#[test]
+fn test_diagnostics_request() {
+    let frontend = DummyArkFrontend::lock();
+
+    frontend.send_control(DiagnosticsRequest {});
+    frontend.recv_iopub_busy();
+
+    assert_match!(frontend.recv_control(), Message::DiagnosticsReply(reply) => {
+        assert_eq!(reply.content.status, Status::Ok);
+
+        let diagnostics = reply.content.diagnostics;
+        assert_eq!(diagnostics["r_thread"]["responsive"], true);
+        assert_eq!(diagnostics["r_thread"]["evaluating"], false);
+        assert!(diagnostics["r_thread"]["gc_count"].is_u64());
+        assert!(diagnostics["pending_tasks"]["idle"].is_u64());
+        assert!(diagnostics["queue_latency"]["interactive"]["started"].is_u64());
+
+        let session = &diagnostics["session"];
+        assert!(session["r_version"].as_str().unwrap().starts_with("R version"));
+        assert!(session["library_paths"].as_array().is_some_and(|paths| !paths.is_empty()));
+        assert!(session["loaded_packages"]["base"].is_string());
+    });
+
+    frontend.recv_iopub_idle();
+}
+
 #[test]
 fn test_execute_request_empty() {
     let frontend = DummyArkFrontend::lock();
@@ -83,6 +176,39 @@ fn test_execute_request_multiple_lines() {
     assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count)
 }
 
+#[test]
+fn test_execute_request_custom_prompts() {
+    let frontend = DummyArkFrontend::lock();
+
+    let execute_invisibly = |code: &str| {
+        frontend.send_execute_request(code, ExecuteRequestOptions::default());
+        frontend.recv_iopub_busy();
+        let input = frontend.recv_iopub_execute_input();
+        frontend.recv_iopub_idle();
+        assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
+    };
+
+    let execute = |code: &str, result: &str| {
+        frontend.send_execute_request(code, ExecuteRequestOptions::default());
+        frontend.recv_iopub_busy();
+        let input = frontend.recv_iopub_execute_input();
+        assert_eq!(frontend.recv_iopub_execute_result(), result);
+        frontend.recv_iopub_idle();
+        assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
+    };
+
+    // Non-default continuation prompt
+    execute_invisibly("options(continue = '... ')");
+    execute("1 +\n  2", "[1] 3");
+
+    // Continuation prompt that is the same as the top-level prompt
+    execute_invisibly("options(prompt = 'R> ', continue = 'R> ')");
+    execute("1 +\n  2 +\n  3", "[1] 6");
+    execute("x <- 1\nx + 1", "[1] 2");
+
+    execute_invisibly("options(prompt = '> ', continue = '+ ')");
+}
+
 #[test]
 fn test_execute_request_incomplete() {
     let frontend = DummyArkFrontend::lock();
@@ -369,6 +495,119 @@ fn test_execute_request_error() {
     );
 }
 
+#[test]
+fn test_execute_request_error_metadata() {
+    let frontend = DummyArkFrontend::lock();
+
+    let code = "stop(structure(class = c('my_error', 'error', 'condition'), list(message = 'foobar', call = NULL, path = 'data.csv')))";
+    frontend.send_execute_request(code, ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+
+    let input = frontend.recv_iopub_execute_input();
+    assert_eq!(input.code, code);
+
+    // The classes and custom fields of the condition are sent as metadata
+    assert_match!(frontend.recv_iopub(), Message::ExecuteError(data) => {
+        assert!(data.content.exception.evalue.contains("foobar"));
+
+        let metadata = data.content.metadata.unwrap();
+        assert_eq!(metadata["class"][0], "my_error");
+        assert_eq!(metadata["fields"]["path"], "data.csv");
+    });
+
+    frontend.recv_iopub_idle();
+
+    assert_eq!(
+        frontend.recv_shell_execute_reply_exception(),
+        input.execution_count
+    );
+}
+
+#[test]
+fn test_execute_reply_metadata() {
+    let frontend = DummyArkFrontend::lock();
+
+    frontend.send_execute_request("Sys.sleep(0.1)", ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+    frontend.recv_iopub_execute_input();
+    frontend.recv_iopub_idle();
+
+    // The metadata is sent in the metadata frame of the reply
+    let reply = frontend.recv_shell_wire();
+    assert_match!(Message::try_from(&reply).unwrap(), Message::ExecuteReply(_));
+    let metadata = reply.metadata;
+    assert!(metadata["duration_ms"].as_u64().unwrap() >= 100);
+    assert_eq!(metadata["warnings"], false);
+    assert_eq!(metadata["error_class"], serde_json::Value::Null);
+
+    // Warnings are still emitted, and also sent in structured form on IOPub
+    let id = frontend.send_execute_request("warning('oops')", ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+    frontend.recv_iopub_execute_input();
+    frontend.recv_iopub_stream_stderr("Warning message:\noops \n");
+
+    let msg = frontend.recv_iopub();
+    assert_match!(msg, Message::ExecuteWarnings(data) => {
+        assert_eq!(data.parent_header.unwrap().msg_id, id);
+        assert_eq!(data.content.warnings, vec![ExecuteWarning {
+            message: String::from("oops"),
+            call: None,
+            class: vec![
+                String::from("simpleWarning"),
+                String::from("warning"),
+                String::from("condition"),
+            ],
+        }]);
+    });
+    frontend.recv_iopub_idle();
+
+    let reply = frontend.recv_shell_wire();
+    assert_match!(Message::try_from(&reply).unwrap(), Message::ExecuteReply(_));
+    assert_eq!(reply.metadata["warnings"], true);
+
+    // The flag is reset for the next request
+    frontend.send_execute_request("1", ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+    frontend.recv_iopub_execute_input();
+    assert_eq!(frontend.recv_iopub_execute_result(), "[1] 1");
+    frontend.recv_iopub_idle();
+
+    let reply = frontend.recv_shell_wire();
+    assert_match!(Message::try_from(&reply).unwrap(), Message::ExecuteReply(_));
+    assert_eq!(reply.metadata["warnings"], false);
+
+    // Warnings signalled by tasks while the request is running don't belong
+    // to the request. `signalCondition()` runs the global handler without
+    // R's deferred printing of warnings.
+    frontend.send_execute_request("Sys.sleep(0.2)", ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+    frontend.recv_iopub_execute_input();
+    r_task(|| {
+        harp::parse_eval_global("signalCondition(simpleWarning('from task'))").unwrap();
+    });
+    frontend.recv_iopub_idle();
+
+    let reply = frontend.recv_shell_wire();
+    assert_match!(Message::try_from(&reply).unwrap(), Message::ExecuteReply(_));
+    assert_eq!(reply.metadata["warnings"], false);
+
+    // Errors include the class of the condition
+    let code = "stop(structure(class = c('my_error', 'error', 'condition'), list(message = 'foobar', call = NULL)))";
+    frontend.send_execute_request(code, ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+    frontend.recv_iopub_execute_input();
+    assert!(frontend.recv_iopub_execute_error().contains("foobar"));
+    frontend.recv_iopub_idle();
+
+    let reply = frontend.recv_shell_wire();
+    assert_match!(
+        Message::try_from(&reply).unwrap(),
+        Message::ExecuteReplyException(_)
+    );
+    assert_eq!(reply.metadata["error_class"][0], "my_error");
+    assert!(reply.metadata["duration_ms"].is_u64());
+}
+
 #[test]
 fn test_execute_request_error_multiple_expressions() {
     let frontend = DummyArkFrontend::lock();
@@ -390,6 +629,46 @@ fn test_execute_request_error_multiple_expressions() {
     );
 }
 
+#[test]
+fn test_execute_request_expression_granularity() {
+    let frontend = DummyArkFrontend::lock();
+
+    let execute_invisibly = |code: &str| {
+        frontend.send_execute_request(code, ExecuteRequestOptions::default());
+        frontend.recv_iopub_busy();
+        let input = frontend.recv_iopub_execute_input();
+        frontend.recv_iopub_idle();
+        assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
+    };
+
+    execute_invisibly("options(ark.execution_granularity = 'expression')");
+
+    // Expressions are split even when they share a line, and errors don't
+    // prevent evaluation of the next expressions
+    let code = "1; stop('foo')\n# comment\n2; stop('bar')\n3";
+    frontend.send_execute_request(code, ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+
+    let input = frontend.recv_iopub_execute_input();
+    assert_eq!(input.code, code);
+
+    frontend.recv_iopub_stream_stdout("[1] 1\n");
+    assert!(frontend.recv_iopub_execute_error().contains("foo"));
+    frontend.recv_iopub_stream_stdout("[1] 2\n");
+    assert!(frontend.recv_iopub_execute_error().contains("bar"));
+    assert_eq!(frontend.recv_iopub_execute_result(), "[1] 3");
+
+    frontend.recv_iopub_idle();
+
+    // The request fails since some expressions failed
+    assert_eq!(
+        frontend.recv_shell_execute_reply_exception(),
+        input.execution_count
+    );
+
+    execute_invisibly("options(ark.execution_granularity = NULL)");
+}
+
 #[test]
 fn test_execute_request_multiple_expressions() {
     let frontend = DummyArkFrontend::lock();
@@ -414,6 +693,61 @@ fn test_execute_request_multiple_expressions() {
     assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
 }
 
+#[test]
+fn test_execute_request_source_file() {
+    let frontend = DummyArkFrontend::lock();
+
+    let file = tempfile::Builder::new().suffix(".R").tempfile().unwrap();
+    std::fs::write(file.path(), "x <- 1\nx + 1\nstop('oops')\nx + 2\n").unwrap();
+
+    let options = serde_json::json!({ "path": file.path(), "echo": true });
+    let code = format!("#| ark-source: {options}");
+    frontend.send_execute_request(&code, ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+
+    // The magic comment is translated into the R call running the file
+    let input = frontend.recv_iopub_execute_input();
+    assert!(input.code.starts_with(".ps.source_file("));
+
+    // Expressions are echoed and stop at the first error
+    frontend.recv_iopub_stream_stdout("> x <- 1\n> x + 1\n[1] 2\n> stop('oops')\n");
+    assert!(frontend.recv_iopub_execute_error().contains("oops"));
+
+    frontend.recv_iopub_idle();
+
+    assert_eq!(
+        frontend.recv_shell_execute_reply_exception(),
+        input.execution_count
+    );
+}
+
+#[test]
+fn test_execute_request_source_file_continue_on_error() {
+    let frontend = DummyArkFrontend::lock();
+
+    let file = tempfile::Builder::new().suffix(".R").tempfile().unwrap();
+    std::fs::write(file.path(), "stop('oops')\n").unwrap();
+
+    let options = serde_json::json!({
+        "path": file.path(),
+        "echo": false,
+        "stop_on_error": false,
+    });
+    let code = format!("#| ark-source: {options}");
+    frontend.send_execute_request(&code, ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+
+    let input = frontend.recv_iopub_execute_input();
+
+    // Errors are reported on stderr without failing the request
+    let name = file.path().file_name().unwrap().to_string_lossy();
+    frontend.recv_iopub_stream_stderr(&format!("Error: oops\n1 error while running `{name}`.\n"));
+
+    frontend.recv_iopub_idle();
+
+    assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
+}
+
 #[test]
 fn test_execute_request_single_line_buffer_overflow() {
     let frontend = DummyArkFrontend::lock();
@@ -590,6 +924,215 @@ fn test_stdin_from_menu() {
     assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
 }
 
+#[test]
+fn test_stdin_metadata() {
+    let frontend = DummyArkFrontend::lock();
+
+    let code = "menu(c('a', 'b'), title = 'Pick one')";
+    frontend.send_execute_request(code, ExecuteRequestOptions { allow_stdin: true });
+    frontend.recv_iopub_busy();
+
+    let input = frontend.recv_iopub_execute_input();
+    assert_eq!(input.code, code);
+
+    frontend.recv_iopub_stream_stdout("Pick one\n\n1: a\n2: b\n\n");
+
+    let request = frontend.recv_stdin_input_request_content();
+    assert_eq!(request.prompt, String::from("Selection: "));
+    assert_eq!(
+        request.metadata,
+        Some(serde_json::json!({
+            "kind": "menu",
+            "choices": ["a", "b"],
+            "title": "Pick one",
+        }))
+    );
+
+    frontend.send_stdin_input_reply(String::from("1"));
+    assert_eq!(frontend.recv_iopub_execute_result(), "[1] 1");
+    frontend.recv_iopub_idle();
+    assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
+
+    let code = "askYesNo('Continue?')";
+    frontend.send_execute_request(code, ExecuteRequestOptions { allow_stdin: true });
+    frontend.recv_iopub_busy();
+
+    let input = frontend.recv_iopub_execute_input();
+    assert_eq!(input.code, code);
+
+    let request = frontend.recv_stdin_input_request_content();
+    assert_eq!(request.prompt, String::from("Continue? (Yes/no/cancel) "));
+    assert!(!request.password);
+    assert_eq!(
+        request.metadata,
+        Some(serde_json::json!({
+            "kind": "yes_no",
+            "choices": ["Yes", "No", "Cancel"],
+            "default": "Yes",
+        }))
+    );
+
+    frontend.send_stdin_input_reply(String::from("n"));
+    assert_eq!(frontend.recv_iopub_execute_result(), "[1] FALSE");
+    frontend.recv_iopub_idle();
+    assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
+}
+
+#[test]
+fn test_session_save_restore() {
+    let frontend = DummyArkFrontend::lock();
+
+    let code = r#"
+session_x <- 1:3
+session_path <- .ps.rpc.save_session(tempfile(), list("session_x"))
+rm(session_x)
+restored <- .ps.rpc.restore_session(session_path)
+identical(restored, list("session_x")) && identical(session_x, 1:3)
+"#;
+    frontend.send_execute_request(code, ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+
+    let input = frontend.recv_iopub_execute_input();
+    assert_eq!(frontend.recv_iopub_execute_result(), "[1] TRUE");
+
+    frontend.recv_iopub_idle();
+    assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
+}
+
+#[test]
+fn test_last_backtrace() {
+    let frontend = DummyArkFrontend::lock();
+
+    let code = "f <- function() stop('oops')\ng <- function() f()\ng()";
+    frontend.send_execute_request(code, ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+
+    let input = frontend.recv_iopub_execute_input();
+    assert!(frontend.recv_iopub_execute_error().contains("oops"));
+
+    frontend.recv_iopub_idle();
+    assert_eq!(
+        frontend.recv_shell_execute_reply_exception(),
+        input.execution_count
+    );
+
+    // The frames go from the outermost call to the innermost one
+    let code = r#"
+bt <- .ps.rpc.last_backtrace()
+identical(bt$message, "oops") &&
+  identical(bt$frames[[1]]$fn, "g") &&
+  identical(bt$frames[[1]]$parent, 0L) &&
+  identical(bt$frames[[2]]$fn, "f") &&
+  identical(bt$frames[[2]]$parent, 1L)
+"#;
+    frontend.send_execute_request(code, ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+
+    let input = frontend.recv_iopub_execute_input();
+    assert_eq!(frontend.recv_iopub_execute_result(), "[1] TRUE");
+
+    frontend.recv_iopub_idle();
+    assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
+}
+
+#[test]
+fn test_package_rpcs() {
+    let frontend = DummyArkFrontend::lock();
+
+    // `base` is attached, `tools` is installed but not attached
+    let code = r#"
+libs <- .ps.rpc.get_library_paths()
+pkgs <- .ps.rpc.list_installed_packages()
+names <- vapply(pkgs, `[[`, "", "name")
+base <- pkgs[[match("base", names)]]
+tools <- pkgs[[match("tools", names)]]
+identical(libs, as.list(.libPaths())) &&
+  !anyDuplicated(names) &&
+  identical(base$version, as.character(packageVersion("base"))) &&
+  base$library %in% .libPaths() &&
+  isTRUE(base$attached) &&
+  isFALSE(tools$attached)
+"#;
+    frontend.send_execute_request(code, ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+
+    let input = frontend.recv_iopub_execute_input();
+    assert_eq!(frontend.recv_iopub_execute_result(), "[1] TRUE");
+
+    frontend.recv_iopub_idle();
+    assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
+}
+
+#[test]
+fn test_package_rpcs_renv_status() {
+    let frontend = DummyArkFrontend::lock();
+
+    // Without renv, only the absence of a project can be checked. With renv,
+    // a package of the lockfile missing from the project library is out of
+    // sync.
+    let code = r#"
+local({
+  old <- Sys.getenv("RENV_PROJECT", unset = NA)
+  on.exit(if (is.na(old)) Sys.unsetenv("RENV_PROJECT") else Sys.setenv(RENV_PROJECT = old))
+
+  Sys.unsetenv("RENV_PROJECT")
+  if (!is.null(.ps.rpc.get_renv_status())) {
+    return(FALSE)
+  }
+  if (!.ps.is_installed("renv", "1.0.0")) {
+    return(TRUE)
+  }
+
+  project <- tempfile()
+  dir.create(project)
+  writeLines(
+    '{"R": {"Version": "4.3.0", "Repositories": []}, "Packages": {"arkNotInstalled": {"Package": "arkNotInstalled", "Version": "1.0.0", "Source": "Repository"}}}',
+    file.path(project, "renv.lock")
+  )
+  Sys.setenv(RENV_PROJECT = project)
+
+  status <- .ps.rpc.get_renv_status()
+  missing <- Filter(function(pkg) identical(pkg$name, "arkNotInstalled"), status$out_of_sync)
+  identical(status$project, project) &&
+    isFALSE(status$synchronized) &&
+    length(missing) == 1 &&
+    identical(missing[[1]]$lockfile_version, "1.0.0") &&
+    is.null(missing[[1]]$library_version)
+})
+"#;
+    frontend.send_execute_request(code, ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+
+    let input = frontend.recv_iopub_execute_input();
+    assert_eq!(frontend.recv_iopub_execute_result(), "[1] TRUE");
+
+    frontend.recv_iopub_idle();
+    assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
+}
+
+#[test]
+fn test_package_rpcs_manage_packages_validation() {
+    let frontend = DummyArkFrontend::lock();
+
+    // Invalid requests fail before a background process is started
+    let code = r#"
+msg <- tryCatch(.ps.rpc.manage_packages("remove", list("base")), error = conditionMessage)
+if (.ps.is_installed("callr")) {
+  identical(msg, "Can't remove `base` while it is loaded.")
+} else {
+  identical(msg, "The callr package is required to manage packages.")
+}
+"#;
+    frontend.send_execute_request(code, ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+
+    let input = frontend.recv_iopub_execute_input();
+    assert_eq!(frontend.recv_iopub_execute_result(), "[1] TRUE");
+
+    frontend.recv_iopub_idle();
+    assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
+}
+
 #[test]
 fn test_env_vars() {
     // These environment variables are set by R's shell script frontend.
@@ -0,0 +1,51 @@
+use std::io::Write;
+
+use amalthea::fixtures::dummy_frontend::ExecuteRequestOptions;
+use amalthea::wire::jupyter_message::Message;
+use amalthea::wire::stream::Stream;
+use ark::fixtures::DummyArkFrontendRprofile;
+use stdext::assert_match;
+
+// SAFETY:
+// Do not write any other tests related to `.Rprofile` in
+// this integration test file. We can only start R up once
+// per process, so we can only run one `.Rprofile`. Use a
+// separate integration test (i.e. separate process) if you
+// need to test more details related to `.Rprofile` usage.
+
+#[test]
+fn test_r_profile_error_is_reported() {
+    // The `\n` is critical, otherwise R's `source()` silently fails
+    let contents = "stop('oh no')\n";
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    write!(file, "{contents}").unwrap();
+
+    let path = file.path();
+    let path = path.to_str().unwrap();
+
+    unsafe { std::env::set_var("R_PROFILE_USER", path) };
+
+    let frontend = DummyArkFrontendRprofile::lock();
+
+    // The error is shown in the console
+    assert_match!(frontend.recv_iopub(), Message::Stream(data) => {
+        assert_eq!(data.content.name, Stream::Stderr);
+        assert!(data.content.text.starts_with("Error while sourcing user R profile"));
+        assert!(data.content.text.contains("oh no"));
+    });
+
+    // And recorded as a startup diagnostic
+    let code = r#"
+        diagnostics <- .ps.rpc.get_startup_diagnostics()
+        cat(length(diagnostics), diagnostics[[1]]$kind, grepl("oh no", diagnostics[[1]]$message))
+    "#;
+    frontend.send_execute_request(code, ExecuteRequestOptions::default());
+    frontend.recv_iopub_busy();
+
+    let input = frontend.recv_iopub_execute_input();
+    frontend.recv_iopub_stream_stdout("1 user TRUE");
+    frontend.recv_iopub_idle();
+
+    assert_eq!(frontend.recv_shell_execute_reply(), input.execution_count);
+}
@@ -0,0 +1,92 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use ark::fixtures::DummyArkFrontend;
+use ark::r_task::r_task;
+use ark::r_task::r_task_background;
+use ark::r_task::r_task_cancellable;
+use ark::r_task::RTaskLane;
+use ark::r_task::RTaskToken;
+use crossbeam::channel::bounded;
+use crossbeam::channel::Sender;
+
+// These tests need a running `RMain` to go through the task queues, unlike
+// unit tests which run tasks on the calling thread.
+
+#[test]
+fn test_r_task_interactive_lane_runs_first() {
+    let _frontend = DummyArkFrontend::lock();
+
+    let release_tx = block_r_thread();
+    let order = Arc::new(Mutex::new(Vec::new()));
+
+    let background = std::thread::spawn({
+        let order = order.clone();
+        move || r_task_background(|| order.lock().unwrap().push("background"))
+    });
+    std::thread::sleep(Duration::from_millis(100));
+
+    let interactive = std::thread::spawn({
+        let order = order.clone();
+        move || r_task(|| order.lock().unwrap().push("interactive"))
+    });
+    std::thread::sleep(Duration::from_millis(100));
+
+    // Both tasks are now queued, the interactive one was queued last but
+    // runs first
+    release_tx.send(()).unwrap();
+    background.join().unwrap();
+    interactive.join().unwrap();
+
+    assert_eq!(*order.lock().unwrap(), vec!["interactive", "background"]);
+}
+
+#[test]
+fn test_r_task_cancelled_token_skips_task() {
+    let _frontend = DummyArkFrontend::lock();
+
+    let release_tx = block_r_thread();
+    let token = RTaskToken::new();
+    let ran = Arc::new(AtomicBool::new(false));
+
+    let task = std::thread::spawn({
+        let token = token.clone();
+        let ran = ran.clone();
+        move || {
+            r_task_cancellable(&token, RTaskLane::Interactive, || {
+                ran.store(true, Ordering::Relaxed)
+            })
+        }
+    });
+    std::thread::sleep(Duration::from_millis(100));
+
+    // Cancel while the task is queued
+    token.cancel();
+    release_tx.send(()).unwrap();
+
+    assert!(task.join().unwrap().is_none());
+    assert!(!ran.load(Ordering::Relaxed));
+
+    // A cancelled token skips tasks right away
+    assert!(r_task_cancellable(&token, RTaskLane::Interactive, || ()).is_none());
+}
+
+/// Occupies the R thread with a task until a message is sent on the returned
+/// channel, so that tasks queue up in the meantime
+fn block_r_thread() -> Sender<()> {
+    let (started_tx, started_rx) = bounded::<()>(0);
+    let (release_tx, release_rx) = bounded::<()>(0);
+
+    std::thread::spawn(move || {
+        r_task(|| {
+            started_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+        })
+    });
+    started_rx.recv().unwrap();
+
+    release_tx
+}
@@ -9,14 +9,19 @@ use amalthea::comm::comm_channel::CommMsg;
 use amalthea::comm::event::CommManagerEvent;
 use amalthea::comm::variables_comm::ClearParams;
 use amalthea::comm::variables_comm::DeleteParams;
+use amalthea::comm::variables_comm::DeletionResult;
+use amalthea::comm::variables_comm::FailedDeletion;
+use amalthea::comm::variables_comm::LoadFileParams;
+use amalthea::comm::variables_comm::ProgressParams;
 use amalthea::comm::variables_comm::QueryTableSummaryParams;
+use amalthea::comm::variables_comm::SaveRdsParams;
 use amalthea::comm::variables_comm::VariablesBackendReply;
 use amalthea::comm::variables_comm::VariablesBackendRequest;
 use amalthea::comm::variables_comm::VariablesFrontendEvent;
 use amalthea::socket::comm::CommInitiator;
 use amalthea::socket::comm::CommSocket;
+use ark::events::EVENTS;
 use ark::fixtures::r_test_lock;
-use ark::lsp::events::EVENTS;
 use ark::r_task::r_task;
 use ark::thread::RThreadSafe;
 use ark::variables::r_variables::LastValue;
@@ -35,6 +40,7 @@ use libr::Rboolean_TRUE;
 use libr::Rf_ScalarInteger;
 use libr::Rf_defineVar;
 use libr::Rf_xlength;
+use stdext::assert_match;
 
 /**
  * Basic test for the R environment list. This test:
@@ -183,6 +189,7 @@ fn test_variables_list() {
     // Request that the environment be cleared
     let clear = VariablesBackendRequest::Clear(ClearParams {
         include_hidden_objects: true,
+        dry_run: None,
     });
     let data = serde_json::to_value(clear).unwrap();
     let request_id = String::from("clear-id-1235");
@@ -224,7 +231,10 @@ fn test_variables_list() {
     // Ensure we get a reply
     let reply: VariablesBackendReply = serde_json::from_value(data).unwrap();
     match reply {
-        VariablesBackendReply::ClearReply() => {},
+        VariablesBackendReply::ClearReply(result) => {
+            assert_eq!(result.deleted.len(), 1);
+            assert!(result.failed.is_empty());
+        },
         _ => panic!("Expected clear reply"),
     }
 
@@ -267,6 +277,7 @@ fn test_variables_list() {
     // Request that a environment be deleted
     let delete = VariablesBackendRequest::Delete(DeleteParams {
         names: vec![String::from("a")],
+        dry_run: None,
     });
     let data = serde_json::to_value(delete).unwrap();
     let request_id = String::from("delete-id-1236");
@@ -274,6 +285,21 @@ fn test_variables_list() {
         .send(CommMsg::Rpc(request_id.clone(), data))
         .unwrap();
 
+    // Ensure we get an event notifying us of the change
+    let data = match outgoing_rx.recv().unwrap() {
+        CommMsg::Data(data) => data,
+        msg => panic!("Expected data message, got {:?}", msg),
+    };
+
+    let evt: VariablesFrontendEvent = serde_json::from_value(data).unwrap();
+    match evt {
+        VariablesFrontendEvent::Update(params) => {
+            assert_eq!(params.assigned.len(), 0);
+            assert_eq!(params.removed, ["a"]);
+        },
+        _ => panic!("Expected update event"),
+    }
+
     let data = match outgoing_rx.recv().unwrap() {
         CommMsg::Rpc(reply_id, data) => {
             assert_eq!(request_id, reply_id);
@@ -285,8 +311,9 @@ fn test_variables_list() {
     let reply: VariablesBackendReply = serde_json::from_value(data).unwrap();
 
     match reply {
-        VariablesBackendReply::DeleteReply(update) => {
-            assert_eq!(update, ["a"]);
+        VariablesBackendReply::DeleteReply(result) => {
+            assert_eq!(result.deleted, ["a"]);
+            assert!(result.failed.is_empty());
         },
         _ => panic!("Expected delete reply"),
     };
@@ -763,3 +790,280 @@ fn test_query_table_summary() {
         harp::parse_eval_global(code).unwrap();
     });
 }
+
+#[test]
+fn test_variables_delete_locked_bindings() {
+    let _lock = r_test_lock();
+
+    // Create a new environment for the test with a locked binding. Its parent
+    // is the base environment so that we can evaluate code in it.
+    let test_env = r_task(|| {
+        let env = RFunction::new("base", "new.env")
+            .param("parent", R_ENVS.base)
+            .call()
+            .unwrap();
+        harp::parse_eval0(
+            "a <- 1; b <- 2; .c <- 3; lockBinding('b', environment())",
+            env.sexp,
+        )
+        .unwrap();
+        RThreadSafe::new(env)
+    });
+
+    let comm = CommSocket::new(
+        CommInitiator::FrontEnd,
+        String::from("test-delete-locked-bindings-comm-id"),
+        String::from("positron.environment"),
+    );
+    let (comm_manager_tx, _) = bounded::<CommManagerEvent>(0);
+
+    let incoming_tx = comm.incoming_tx.clone();
+    let outgoing_rx = comm.outgoing_rx.clone();
+    r_task(|| {
+        let test_env = test_env.get().clone();
+        RVariables::start(test_env, comm.clone(), comm_manager_tx.clone());
+    });
+
+    // Consume the initial refresh event
+    let _ = outgoing_rx.recv().unwrap();
+
+    // Sends a request and returns its reply, skipping update events
+    let rpc = |request: VariablesBackendRequest| -> VariablesBackendReply {
+        let data = serde_json::to_value(request).unwrap();
+        let request_id = uuid::Uuid::new_v4().to_string();
+        incoming_tx
+            .send(CommMsg::Rpc(request_id.clone(), data))
+            .unwrap();
+
+        loop {
+            match outgoing_rx.recv().unwrap() {
+                CommMsg::Data(_) => continue,
+                CommMsg::Rpc(reply_id, data) => {
+                    assert_eq!(request_id, reply_id);
+                    return serde_json::from_value(data).unwrap();
+                },
+                msg => panic!("Expected RPC message, got {:?}", msg),
+            }
+        }
+    };
+
+    let failed = |name: &str, reason: &str| FailedDeletion {
+        name: name.to_string(),
+        reason: reason.to_string(),
+    };
+
+    // A dry run reports which bindings would be deleted
+    let reply = rpc(VariablesBackendRequest::Delete(DeleteParams {
+        names: vec![
+            String::from("a"),
+            String::from("b"),
+            String::from("missing"),
+        ],
+        dry_run: Some(true),
+    }));
+    assert_eq!(
+        reply,
+        VariablesBackendReply::DeleteReply(DeletionResult {
+            deleted: vec![String::from("a")],
+            failed: vec![
+                failed("b", "Binding is locked"),
+                failed("missing", "Object not found")
+            ],
+        })
+    );
+    r_task(|| {
+        let exists: bool =
+            harp::parse_eval0("exists('a', inherits = FALSE)", test_env.get().sexp)
+                .unwrap()
+                .try_into()
+                .unwrap();
+        assert!(exists);
+    });
+
+    // Deleting skips the locked binding
+    let reply = rpc(VariablesBackendRequest::Delete(DeleteParams {
+        names: vec![String::from("a"), String::from("b")],
+        dry_run: None,
+    }));
+    assert_eq!(
+        reply,
+        VariablesBackendReply::DeleteReply(DeletionResult {
+            deleted: vec![String::from("a")],
+            failed: vec![failed("b", "Binding is locked")],
+        })
+    );
+
+    // Hidden objects are only cleared on request
+    let reply = rpc(VariablesBackendRequest::Clear(ClearParams {
+        include_hidden_objects: false,
+        dry_run: Some(true),
+    }));
+    assert_eq!(
+        reply,
+        VariablesBackendReply::ClearReply(DeletionResult {
+            deleted: vec![],
+            failed: vec![failed("b", "Binding is locked")],
+        })
+    );
+
+    let reply = rpc(VariablesBackendRequest::Clear(ClearParams {
+        include_hidden_objects: true,
+        dry_run: None,
+    }));
+    assert_eq!(
+        reply,
+        VariablesBackendReply::ClearReply(DeletionResult {
+            deleted: vec![String::from(".c")],
+            failed: vec![failed("b", "Binding is locked")],
+        })
+    );
+
+    // Close the comm. Otherwise the thread panics
+    incoming_tx.send(CommMsg::Close).unwrap();
+}
+
+#[test]
+fn test_variables_save_load_files() {
+    let _lock = r_test_lock();
+
+    let test_env = r_task(|| {
+        let env = RFunction::new("base", "new.env")
+            .param("parent", R_ENVS.base)
+            .call()
+            .unwrap();
+        harp::parse_eval0("x <- list(a = 1:3, b = 'foo')", env.sexp).unwrap();
+        RThreadSafe::new(env)
+    });
+
+    let (rds_file, rdata_file) = r_task(|| {
+        let rds: String = harp::parse_eval_global("tempfile(fileext = '.rds')")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let rdata: String = harp::parse_eval_global("tempfile(fileext = '.RData')")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        (rds, rdata)
+    });
+
+    let comm = CommSocket::new(
+        CommInitiator::FrontEnd,
+        String::from("test-save-load-comm-id"),
+        String::from("positron.environment"),
+    );
+    let (comm_manager_tx, _) = bounded::<CommManagerEvent>(0);
+
+    let incoming_tx = comm.incoming_tx.clone();
+    let outgoing_rx = comm.outgoing_rx.clone();
+    r_task(|| {
+        let test_env = test_env.get().clone();
+        RVariables::start(test_env, comm.clone(), comm_manager_tx.clone());
+    });
+
+    // Consume the initial refresh event
+    let _ = outgoing_rx.recv().unwrap();
+
+    // Sends a request and returns its reply along with the progress events
+    // received in the meantime
+    let rpc = |request: VariablesBackendRequest| -> (VariablesBackendReply, Vec<ProgressParams>) {
+        let data = serde_json::to_value(request).unwrap();
+        let request_id = uuid::Uuid::new_v4().to_string();
+        incoming_tx
+            .send(CommMsg::Rpc(request_id.clone(), data))
+            .unwrap();
+
+        let mut progress = vec![];
+        loop {
+            match outgoing_rx.recv().unwrap() {
+                CommMsg::Data(data) => {
+                    if let VariablesFrontendEvent::Progress(params) =
+                        serde_json::from_value(data).unwrap()
+                    {
+                        progress.push(params);
+                    }
+                },
+                CommMsg::Rpc(reply_id, data) => {
+                    assert_eq!(request_id, reply_id);
+                    return (serde_json::from_value(data).unwrap(), progress);
+                },
+                msg => panic!("Expected RPC message, got {:?}", msg),
+            }
+        }
+    };
+
+    let (reply, progress) = rpc(VariablesBackendRequest::SaveRds(SaveRdsParams {
+        path: vec![String::from("x")],
+        file: rds_file.clone(),
+        operation_id: String::from("save"),
+    }));
+    assert_eq!(reply, VariablesBackendReply::SaveRdsReply());
+
+    // The last progress event reports that all bytes were written
+    let last = progress.last().unwrap();
+    assert_eq!(last.operation_id, "save");
+    assert_eq!(Some(last.bytes), last.total_bytes);
+
+    let (reply, progress) = rpc(VariablesBackendRequest::LoadFile(LoadFileParams {
+        file: rds_file,
+        name: Some(String::from("y")),
+        operation_id: String::from("load"),
+    }));
+    assert_eq!(
+        reply,
+        VariablesBackendReply::LoadFileReply(vec![String::from("y")])
+    );
+    assert!(progress.iter().all(|params| params.operation_id == "load"));
+
+    // `.RData` files may contain several objects
+    r_task(|| {
+        let env = test_env.get();
+        harp::parse_eval0("z <- 1", env.sexp).unwrap();
+        RFunction::new("base", "save")
+            .param("list", vec![String::from("x"), String::from("z")])
+            .param("file", rdata_file.as_str())
+            .param("envir", env.clone())
+            .call()
+            .unwrap();
+        harp::parse_eval0("rm(x, z)", env.sexp).unwrap();
+    });
+
+    let (reply, _) = rpc(VariablesBackendRequest::LoadFile(LoadFileParams {
+        file: rdata_file,
+        name: None,
+        operation_id: String::from("load"),
+    }));
+    assert_eq!(
+        reply,
+        VariablesBackendReply::LoadFileReply(vec![String::from("x"), String::from("z")])
+    );
+
+    r_task(|| {
+        let identical: bool = harp::parse_eval0("identical(x, y)", test_env.get().sexp)
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert!(identical);
+    });
+
+    // Other kinds of files can't be loaded
+    let request = VariablesBackendRequest::LoadFile(LoadFileParams {
+        file: String::from("data.csv"),
+        name: None,
+        operation_id: String::from("load"),
+    });
+    let request_id = String::from("load-csv");
+    incoming_tx
+        .send(CommMsg::Rpc(
+            request_id.clone(),
+            serde_json::to_value(request).unwrap(),
+        ))
+        .unwrap();
+    assert_match!(outgoing_rx.recv().unwrap(), CommMsg::Rpc(reply_id, data) => {
+        assert_eq!(reply_id, request_id);
+        assert!(data.get("error").is_some());
+    });
+
+    // Close the comm. Otherwise the thread panics
+    incoming_tx.send(CommMsg::Close).unwrap();
+}
@@ -147,6 +147,7 @@ impl ShellHandler for Shell {
 
             if let Err(err) = self.iopub.send(IOPubMessage::ExecuteError(ExecuteError {
                 exception: exception.clone(),
+                metadata: None,
             })) {
                 warn!(
                     "Could not publish error from computation {} on iopub: {}",
@@ -60,6 +60,12 @@ pub fn vector(_attr: TokenStream, item: TokenStream) -> TokenStream {
             }
         }
 
+        impl std::convert::From<#ident> for crate::object::RObject {
+            fn from(value: #ident) -> Self {
+                value.object
+            }
+        }
+
         impl std::convert::TryFrom<libr::SEXP> for #ident {
             type Error = crate::error::Error;
             fn try_from(value: libr::SEXP) -> Result<Self, Self::Error> {
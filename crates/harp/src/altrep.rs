@@ -0,0 +1,127 @@
+//
+// altrep.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use libr::*;
+
+use crate::object::r_int_get;
+use crate::symbol::RSymbol;
+use crate::utils::r_is_altrep;
+
+/// An ALTREP object, i.e. a vector whose data is provided by the methods of
+/// an ALTREP class rather than stored contiguously, e.g. compact sequences
+/// like `1:10`, deferred string conversions, or memory-mapped vectors.
+///
+/// Like `RSymbol`, this is a lightweight view that doesn't protect the
+/// object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Altrep {
+    pub sexp: SEXP,
+}
+
+/// The class of an ALTREP object, e.g. `base::compact_intseq`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AltrepClass {
+    pub name: String,
+    pub package: String,
+    /// The type of the vectors of this class, e.g. `INTSXP`
+    pub base_type: SEXPTYPE,
+}
+
+impl Altrep {
+    /// Returns `None` if `x` is not an ALTREP object
+    pub fn new(x: SEXP) -> Option<Self> {
+        if r_is_altrep(x) {
+            Some(Self { sexp: x })
+        } else {
+            None
+        }
+    }
+
+    /// The class object, e.g. to account for its size
+    pub fn class_object(&self) -> SEXP {
+        unsafe { ALTREP_CLASS(self.sexp) }
+    }
+
+    pub fn class(&self) -> AltrepClass {
+        unsafe {
+            // The class is identified by a pairlist of the class symbol, the
+            // package symbol, and the base type, stored as its attributes
+            let info = ATTRIB(self.class_object());
+
+            AltrepClass {
+                name: RSymbol::new_unchecked(CAR(info)).to_string(),
+                package: RSymbol::new_unchecked(CADR(info)).to_string(),
+                base_type: r_int_get(CADDR(info), 0) as SEXPTYPE,
+            }
+        }
+    }
+
+    pub fn data1(&self) -> SEXP {
+        unsafe { R_altrep_data1(self.sexp) }
+    }
+
+    pub fn data2(&self) -> SEXP {
+        unsafe { R_altrep_data2(self.sexp) }
+    }
+
+    /// Whether the data of the vector has been materialized in memory, e.g.
+    /// a compact sequence that has been expanded. Doesn't materialize the
+    /// data, unlike `DATAPTR()`.
+    pub fn is_materialized(&self) -> bool {
+        unsafe { !DATAPTR_OR_NULL(self.sexp).is_null() }
+    }
+}
+
+impl std::fmt::Display for AltrepClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}::{}", self.package, self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libr::DATAPTR;
+    use libr::INTSXP;
+    use libr::STRSXP;
+
+    use crate::altrep::Altrep;
+
+    #[test]
+    fn test_altrep_class() {
+        crate::r_task(|| {
+            let x = harp::parse_eval_base("1:10").unwrap();
+            let altrep = Altrep::new(x.sexp).unwrap();
+
+            let class = altrep.class();
+            assert_eq!(class.name, "compact_intseq");
+            assert_eq!(class.package, "base");
+            assert_eq!(class.base_type, INTSXP);
+            assert_eq!(class.to_string(), "base::compact_intseq");
+
+            let x = harp::parse_eval_base("as.character(1:3)").unwrap();
+            let class = Altrep::new(x.sexp).unwrap().class();
+            assert_eq!(class.to_string(), "base::deferred_string");
+            assert_eq!(class.base_type, STRSXP);
+
+            let x = harp::parse_eval_base("c(1L, 2L)").unwrap();
+            assert!(Altrep::new(x.sexp).is_none());
+        })
+    }
+
+    #[test]
+    fn test_altrep_is_materialized() {
+        crate::r_task(|| {
+            let x = harp::parse_eval_base("1:10").unwrap();
+            let altrep = Altrep::new(x.sexp).unwrap();
+            assert!(!altrep.is_materialized());
+
+            // Expands the sequence
+            unsafe { DATAPTR(x.sexp) };
+            assert!(altrep.is_materialized());
+        })
+    }
+}
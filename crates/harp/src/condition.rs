@@ -0,0 +1,115 @@
+//
+// condition.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use crate::exec::RFunction;
+use crate::exec::RFunctionExt;
+use crate::modules::HARP_ENV;
+use crate::object::RObject;
+use crate::utils::r_is_null;
+
+/// An R condition caught by `try_catch()`
+///
+/// Retains the message, class vector, and field names of the condition so
+/// callers can branch on condition classes, e.g. `rlang_error`, from any
+/// thread. The condition object itself stays on the R side, among the most
+/// recently caught conditions, and can be retrieved on the R thread with
+/// `object()`, e.g. to re-signal it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConditionObject {
+    pub message: String,
+    pub class: Vec<String>,
+    pub fields: Vec<String>,
+    id: i32,
+}
+
+impl ConditionObject {
+    pub(crate) fn new(message: String, class: Vec<String>, fields: Vec<String>, id: i32) -> Self {
+        Self {
+            message,
+            class,
+            fields,
+            id,
+        }
+    }
+
+    pub fn inherits(&self, class: &str) -> bool {
+        self.class.iter().any(|c| c == class)
+    }
+
+    pub fn is_rlang_error(&self) -> bool {
+        self.inherits("rlang_error")
+    }
+
+    /// Returns the R condition, or `None` if it has been evicted by more
+    /// recent conditions. Must be called on the R thread.
+    pub fn object(&self) -> Option<RObject> {
+        let object = RFunction::new("", "condition_get")
+            .add(self.id)
+            .call_in(unsafe { HARP_ENV.unwrap() })
+            .ok()?;
+
+        if r_is_null(object.sexp) {
+            return None;
+        }
+        Some(object)
+    }
+
+    /// Returns a field of the R condition. Must be called on the R thread.
+    pub fn field(&self, name: &str) -> Option<RObject> {
+        if !self.fields.iter().any(|field| field == name) {
+            return None;
+        }
+
+        let object = self.object()?;
+        let field = RFunction::new("base", "[[")
+            .add(object)
+            .add(name)
+            .call()
+            .ok()?;
+
+        Some(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stdext::assert_match;
+
+    use crate::error::Error;
+    use crate::exec::RFunction;
+    use crate::exec::RFunctionExt;
+
+    #[test]
+    fn test_condition_object() {
+        crate::r_task(|| {
+            let cnd = crate::parse_eval_base(
+                "structure(
+                    list(message = 'ouch', call = NULL, data = 42L),
+                    class = c('my_error', 'error', 'condition')
+                )",
+            )
+            .unwrap();
+
+            let err = RFunction::new("base", "stop").add(cnd).call();
+
+            assert_match!(err, Err(Error::TryCatchError { condition: Some(condition), .. }) => {
+                assert_eq!(condition.message, "ouch");
+                assert_eq!(condition.class, vec!["my_error", "error", "condition"]);
+                assert_eq!(condition.fields, vec!["data"]);
+                assert!(condition.inherits("my_error"));
+                assert!(!condition.is_rlang_error());
+
+                let data = condition.field("data").unwrap();
+                assert_eq!(i32::try_from(data).unwrap(), 42);
+                assert!(condition.field("foo").is_none());
+
+                let object = condition.object().unwrap();
+                assert!(crate::utils::r_inherits(object.sexp, "my_error"));
+            });
+        })
+    }
+}
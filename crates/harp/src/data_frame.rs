@@ -1,7 +1,13 @@
 use libr::*;
 
+use crate::list_get;
+use crate::r_dim;
+use crate::r_int_get;
 use crate::r_length;
 use crate::utils::*;
+use crate::vector::CharacterVector;
+use crate::vector::IntegerVector;
+use crate::vector::ListBuilder;
 use crate::vector::Vector;
 use crate::List;
 use crate::RObject;
@@ -48,7 +54,7 @@ impl DataFrame {
                 return Err(harp::anyhow!("Data frame column must be a vector"));
             }
 
-            if obj.length() as usize != nrow {
+            if column_size(obj.sexp)? != nrow {
                 return Err(harp::anyhow!(
                     "Data frame column must be the same size as the number of rows"
                 ));
@@ -78,6 +84,36 @@ impl DataFrame {
             .ok_or_else(|| harp::unreachable!("missing column"))
     }
 
+    pub fn col_at(&self, index: usize) -> harp::Result<RObject> {
+        if index >= self.ncol {
+            return Err(harp::Error::ValueOutOfRange {
+                value: index as i64,
+                min: 0,
+                max: self.ncol as i64 - 1,
+            });
+        }
+
+        self.list
+            .get(index as isize)?
+            .ok_or_else(|| harp::unreachable!("missing column"))
+    }
+
+    /// Returns the column `name` converted to `T`, e.g. `Vec<i32>`
+    pub fn col_as<T>(&self, name: &str) -> harp::Result<T>
+    where
+        T: TryFrom<RObject, Error = harp::Error>,
+    {
+        self.col(name)?.try_into()
+    }
+
+    /// Returns the column at `index` converted to `T`
+    pub fn col_at_as<T>(&self, index: usize) -> harp::Result<T>
+    where
+        T: TryFrom<RObject, Error = harp::Error>,
+    {
+        self.col_at(index)?.try_into()
+    }
+
     /// Compute the number of columns of a data frame
     ///
     /// # Notes
@@ -140,6 +176,89 @@ impl DataFrame {
     }
 }
 
+/// The number of rows of a data frame column. Data frame columns, records
+/// like `POSIXlt`, and matrix columns are not sized by their length.
+fn column_size(x: SEXP) -> harp::Result<usize> {
+    if r_is_data_frame(x) {
+        return Ok(DataFrame::n_row(x)? as usize);
+    }
+
+    if r_inherits(x, "POSIXlt") {
+        // Fields are stored as a list of vectors of the same size
+        if r_length(x) == 0 {
+            return Ok(0);
+        }
+        return Ok(r_length(list_get(x, 0)) as usize);
+    }
+
+    let dim = r_dim(x);
+    if r_typeof(dim) == INTSXP && r_length(dim) > 0 {
+        return Ok(r_int_get(dim, 0) as usize);
+    }
+
+    Ok(r_length(x) as usize)
+}
+
+/// Builds a data frame from columns
+///
+/// Sets the names, compact row names, and class of the data frame. Columns
+/// must all have the same size.
+#[derive(Default)]
+pub struct DataFrameBuilder {
+    columns: ListBuilder,
+    names: Vec<String>,
+    nrow: Option<usize>,
+}
+
+impl DataFrameBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn column(&mut self, name: &str, value: impl Into<RObject>) -> &mut Self {
+        self.columns.push(value);
+        self.names.push(name.to_string());
+        self
+    }
+
+    /// Sets the number of rows, which is otherwise taken from the first
+    /// column. Required for data frames without columns.
+    pub fn nrow(&mut self, nrow: usize) -> &mut Self {
+        self.nrow = Some(nrow);
+        self
+    }
+
+    pub fn build(&self) -> harp::Result<RObject> {
+        let list = self.columns.build()?;
+
+        let sizes: Vec<usize> = List::new(list.sexp)?
+            .iter()
+            .map(|col| r_length(col) as usize)
+            .collect();
+
+        let nrow = self.nrow.or(sizes.first().copied()).unwrap_or(0);
+        if sizes.iter().any(|&size| size != nrow) {
+            return Err(harp::anyhow!(
+                "Data frame column must be the same size as the number of rows"
+            ));
+        }
+        let Ok(nrow) = i32::try_from(nrow) else {
+            return Err(harp::anyhow!("Number of rows must fit in a `i32`."));
+        };
+
+        list.set_attr(
+            "names",
+            CharacterVector::from_iter(self.names.iter().map(String::as_str)),
+        )?;
+
+        // Compact row names of the form `c(NA, -nrow)`
+        list.set_attr("row.names", IntegerVector::from_iter([None, Some(-nrow)]))?;
+        list.set_attr("class", "data.frame")?;
+
+        Ok(list)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use stdext::assert_match;
@@ -148,8 +267,11 @@ mod tests {
     use crate::r_chr_poke;
     use crate::r_list_poke;
     use crate::r_null;
+    use crate::vector::CharacterVector;
+    use crate::vector::IntegerVector;
     use crate::vector::Vector;
     use crate::DataFrame;
+    use crate::DataFrameBuilder;
     use crate::List;
     use crate::RObject;
 
@@ -226,6 +348,26 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_data_frame_compound_columns() {
+        crate::r_task(|| {
+            let df = harp::parse_eval_base(
+                "
+                df <- data.frame(x = 1:2)
+                df$lt <- as.POSIXlt(c('2024-01-01', '2024-01-02'), tz = 'UTC')
+                df$mat <- matrix(1:6, nrow = 2)
+                df$df <- data.frame(a = 1:2, b = 3:4, c = 5:6)
+                df
+                ",
+            )
+            .unwrap();
+            let df = DataFrame::new(df.sexp).unwrap();
+
+            assert_eq!(df.nrow, 2);
+            assert_eq!(df.ncol, 4);
+        })
+    }
+
     #[test]
     fn test_data_frame_col() {
         crate::r_task(|| {
@@ -243,4 +385,59 @@ mod tests {
             });
         })
     }
+
+    #[test]
+    fn test_data_frame_col_at() {
+        crate::r_task(|| {
+            let df = harp::parse_eval_base("data.frame(x = 1:2, y = c('a', 'b'))").unwrap();
+            let df = DataFrame::new(df.sexp).unwrap();
+
+            let x: Vec<i32> = df.col_at_as(0).unwrap();
+            assert_eq!(x, vec![1, 2]);
+
+            let y: Vec<String> = df.col_as("y").unwrap();
+            assert_eq!(y, vec!["a", "b"]);
+
+            assert_match!(
+                df.col_at(2),
+                harp::Result::Err(harp::Error::ValueOutOfRange {
+                    value: 2,
+                    min: 0,
+                    max: 1
+                })
+            );
+        })
+    }
+
+    #[test]
+    fn test_data_frame_builder() {
+        crate::r_task(|| {
+            let df = DataFrameBuilder::new()
+                .column("x", IntegerVector::from_iter([1, 2]))
+                .column("y", CharacterVector::from_iter(["a", "b"]))
+                .build()
+                .unwrap();
+
+            let expected = harp::parse_eval_base("data.frame(x = 1:2, y = c('a', 'b'))").unwrap();
+            assert!(crate::is_identical(df.sexp, expected.sexp));
+
+            let df = DataFrame::new(df.sexp).unwrap();
+            assert_eq!(df.nrow, 2);
+            assert_eq!(df.ncol, 2);
+
+            // Empty data frame with rows
+            let df = DataFrameBuilder::new().nrow(3).build().unwrap();
+            assert_eq!(DataFrame::n_row(df.sexp).unwrap(), 3);
+            assert_eq!(DataFrame::n_col(df.sexp).unwrap(), 0);
+
+            // Mismatched sizes
+            let df = DataFrameBuilder::new()
+                .column("x", IntegerVector::from_iter([1, 2]))
+                .column("y", IntegerVector::from_iter([1]))
+                .build();
+            assert_match!(df, harp::Result::Err(err) => {
+                assert!(format!("{err}").contains("must be the same size"))
+            });
+        })
+    }
 }
@@ -0,0 +1,246 @@
+//
+// encoding.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+use std::ffi::CStr;
+
+use libr::cetype_t_CE_BYTES;
+use libr::cetype_t_CE_LATIN1;
+use libr::cetype_t_CE_UTF8;
+use libr::vmaxget;
+use libr::vmaxset;
+use libr::Rf_getCharCE;
+use libr::Rf_translateCharUTF8;
+use libr::R_CHAR;
+use libr::SEXP;
+
+use crate::exec::try_catch;
+use crate::object::r_length;
+
+/// The encoding declared by an R string, see `?Encoding`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CharEncoding {
+    /// ASCII strings are never marked since they are valid in all encodings
+    Ascii,
+    Utf8,
+    Latin1,
+    /// Raw bytes that can't be translated to another encoding
+    Bytes,
+    /// The encoding of the current locale, i.e. "unknown" in `Encoding()`
+    Native,
+}
+
+/// Returns the declared encoding of the CHARSXP `x`
+pub fn r_char_encoding(x: SEXP) -> CharEncoding {
+    unsafe {
+        if r_char_bytes(x).is_ascii() {
+            return CharEncoding::Ascii;
+        }
+
+        match Rf_getCharCE(x) {
+            cetype_t_CE_UTF8 => CharEncoding::Utf8,
+            cetype_t_CE_LATIN1 => CharEncoding::Latin1,
+            cetype_t_CE_BYTES => CharEncoding::Bytes,
+            _ => CharEncoding::Native,
+        }
+    }
+}
+
+/// Converts the CHARSXP `x` to a Rust string according to its declared
+/// encoding:
+///
+/// - ASCII and UTF-8 strings are copied without translation. This is also
+///   the case of native strings in a UTF-8 session.
+/// - Latin-1 strings are decoded on the Rust side, which can't fail.
+/// - Strings in the native encoding are translated by R. Translation errors
+///   are caught and the untranslated bytes are used instead.
+/// - Bytes can't be translated. Non-ASCII bytes are escaped as `\xNN` like
+///   R prints them.
+///
+/// Invalid UTF-8 is replaced with the replacement character, e.g. when a
+/// string is wrongly marked as UTF-8.
+/// <https://github.com/posit-dev/positron/issues/2698>
+///
+/// `NA` is not checked and converts to `"NA"`.
+pub fn r_char_to_string(x: SEXP) -> String {
+    let bytes = unsafe { r_char_bytes(x) };
+
+    // Fast path for the common case, skips the ASCII scan and the guarded
+    // translation
+    if is_utf8_locale() && !is_latin1_or_bytes(x) {
+        return String::from_utf8_lossy(bytes).into_owned();
+    }
+
+    match r_char_encoding(x) {
+        CharEncoding::Ascii | CharEncoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+        CharEncoding::Latin1 => bytes.iter().map(|byte| *byte as char).collect(),
+        CharEncoding::Bytes => escape_bytes(bytes),
+        CharEncoding::Native => match translate_char_utf8(x) {
+            Ok(translated) => String::from_utf8_lossy(&translated).into_owned(),
+            Err(err) => {
+                log::warn!("Can't translate string to UTF-8: {err:?}");
+                String::from_utf8_lossy(bytes).into_owned()
+            },
+        },
+    }
+}
+
+/// Whether the session locale is UTF-8, in which case native strings don't
+/// need translation. Conservatively `false` if R doesn't expose it.
+fn is_utf8_locale() -> bool {
+    unsafe { libr::has::utf8locale() && libr::get(libr::utf8locale) != 0 }
+}
+
+fn is_latin1_or_bytes(x: SEXP) -> bool {
+    unsafe { matches!(Rf_getCharCE(x), cetype_t_CE_LATIN1 | cetype_t_CE_BYTES) }
+}
+
+/// The bytes of a CHARSXP, without the nul terminator. Borrowed from the R
+/// heap, so only valid while `x` is protected.
+unsafe fn r_char_bytes<'a>(x: SEXP) -> &'a [u8] {
+    let n = r_length(x) as usize;
+    if n == 0 {
+        return &[];
+    }
+    std::slice::from_raw_parts(R_CHAR(x) as *const u8, n)
+}
+
+fn translate_char_utf8(x: SEXP) -> crate::Result<Vec<u8>> {
+    try_catch(|| unsafe {
+        // Translation allocates with `R_alloc()`, reset the stack afterwards.
+        // In case of error, R resets it when unwinding.
+        let vmax = vmaxget();
+        let translated = CStr::from_ptr(Rf_translateCharUTF8(x)).to_bytes().to_vec();
+        vmaxset(vmax);
+        translated
+    })
+}
+
+fn escape_bytes(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len());
+
+    for byte in bytes {
+        if byte.is_ascii() {
+            out.push(*byte as char);
+        } else {
+            out.push_str(&format!("\\x{byte:02x}"));
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use libr::STRING_ELT;
+
+    use crate::encoding::is_utf8_locale;
+    use crate::encoding::r_char_encoding;
+    use crate::encoding::r_char_to_string;
+    use crate::encoding::CharEncoding;
+    use crate::environment::R_ENVS;
+    use crate::exec::RFunction;
+    use crate::exec::RFunctionExt;
+    use crate::object::RObject;
+
+    fn eval_char(code: &str) -> (RObject, CharEncoding, String) {
+        let env = RFunction::new("base", "new.env")
+            .param("parent", R_ENVS.base)
+            .call()
+            .unwrap();
+
+        let x = harp::parse_eval0(code, env).unwrap();
+        let elt = unsafe { STRING_ELT(x.sexp, 0) };
+        (x, r_char_encoding(elt), r_char_to_string(elt))
+    }
+
+    #[test]
+    fn test_r_char_to_string_round_trip() {
+        crate::r_task(|| {
+            let strings = vec![
+                String::from(""),
+                String::from("ascii"),
+                String::from("café"),
+                String::from("日本語"),
+                String::from("emoji 🎉"),
+                String::from("Ελληνικά\ttab"),
+            ];
+
+            let x = RObject::from(strings.clone());
+            let out: Vec<String> = x.clone().try_into().unwrap();
+            assert_eq!(out, strings);
+
+            for (i, string) in strings.iter().enumerate() {
+                let elt = unsafe { STRING_ELT(x.sexp, i as isize) };
+                let expected = if string.is_ascii() {
+                    CharEncoding::Ascii
+                } else {
+                    CharEncoding::Utf8
+                };
+                assert_eq!(r_char_encoding(elt), expected);
+                assert_eq!(r_char_to_string(elt), *string);
+            }
+        })
+    }
+
+    #[test]
+    fn test_r_char_to_string_latin1() {
+        crate::r_task(|| {
+            let (_x, encoding, string) =
+                eval_char("iconv('caf\\u00e9 \\u00bd', from = 'UTF-8', to = 'latin1')");
+            assert_eq!(encoding, CharEncoding::Latin1);
+            assert_eq!(string, "café ½");
+
+            // Latin-1 strings make it back to R as UTF-8 strings
+            let out = RFunction::new("base", "identical")
+                .add(RObject::from(string.as_str()))
+                .add(harp::parse_eval_base("'caf\\u00e9 \\u00bd'").unwrap())
+                .call()
+                .unwrap();
+            assert!(bool::try_from(out).unwrap());
+        })
+    }
+
+    #[test]
+    fn test_r_char_to_string_bytes() {
+        crate::r_task(|| {
+            // Used to fail with "translating strings with "bytes" encoding is
+            // not allowed"
+            let (_x, encoding, string) = eval_char("x <- 'caf\\u00e9'; Encoding(x) <- 'bytes'; x");
+            assert_eq!(encoding, CharEncoding::Bytes);
+            assert_eq!(string, "caf\\xc3\\xa9");
+        })
+    }
+
+    #[test]
+    fn test_r_char_to_string_native() {
+        crate::r_task(|| {
+            let utf8_locale = harp::parse_eval_base("l10n_info()[['UTF-8']]").unwrap();
+            if !bool::try_from(utf8_locale).unwrap() {
+                return;
+            }
+
+            // Native strings are copied without translation
+            assert!(is_utf8_locale());
+
+            let (_x, encoding, string) =
+                eval_char("x <- 'caf\\u00e9'; Encoding(x) <- 'unknown'; x");
+            assert_eq!(encoding, CharEncoding::Native);
+            assert_eq!(string, "café");
+        })
+    }
+
+    #[test]
+    fn test_r_char_to_string_invalid_utf8() {
+        crate::r_task(|| {
+            let (_x, encoding, string) = eval_char(
+                "x <- iconv(intToUtf8(0xa0), from = 'UTF-8', to = 'latin1'); Encoding(x) <- 'UTF-8'; x",
+            );
+            assert_eq!(encoding, CharEncoding::Utf8);
+            assert_eq!(string, String::from(char::REPLACEMENT_CHARACTER));
+        })
+    }
+}
@@ -114,6 +114,12 @@ impl Environment {
         EnvironmentIter::new(self.clone())
     }
 
+    /// Takes a stable copy of the bindings, see `EnvironmentSnapshot`. Prefer
+    /// this over `iter()` when R code may run while the bindings are used.
+    pub fn snapshot(&self) -> EnvironmentSnapshot {
+        EnvironmentSnapshot::new(self)
+    }
+
     pub fn exists(&self, name: impl Into<RSymbol>) -> bool {
         unsafe { libr::R_existsVarInFrame(self.inner.sexp, name.into().sexp) != 0 }
     }
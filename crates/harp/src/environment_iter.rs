@@ -74,6 +74,55 @@ impl Iterator for EnvironmentIter {
     }
 }
 
+/// A stable copy of the bindings of an environment, sorted by name.
+///
+/// `EnvironmentIter` looks up bindings lazily, so R code running between
+/// two iterations (e.g. to format a value) may add, remove, or reassign
+/// bindings that the iterator then observes halfway through. A snapshot
+/// instead reads all bindings in a single pass, without evaluating any R
+/// code, and keeps its own references to the values. It is unaffected by
+/// later mutations of the environment.
+pub struct EnvironmentSnapshot {
+    names: Vec<String>,
+    bindings: Vec<Binding>,
+}
+
+impl EnvironmentSnapshot {
+    pub fn new(env: &Environment) -> Self {
+        // Bindings that can't be read are skipped, like unbound variables
+        let mut bindings: Vec<Binding> = env
+            .names()
+            .iter()
+            .filter_map(|name| Binding::new(env, name.into()).ok())
+            .collect();
+        bindings.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let names = bindings.iter().map(|b| String::from(b.name)).collect();
+
+        Self { names, bindings }
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn bindings(&self) -> &[Binding] {
+        &self.bindings
+    }
+
+    pub fn into_bindings(self) -> Vec<Binding> {
+        self.bindings
+    }
+
+    pub fn len(&self) -> usize {
+        self.bindings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+}
+
 impl Binding {
     pub fn new(env: &Environment, name: RSymbol) -> harp::Result<Self> {
         unsafe {
@@ -185,6 +234,35 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_environment_snapshot() {
+        r_task(|| {
+            let env = Environment::new_empty().unwrap();
+            env.bind(RSymbol::from("b"), &harp::parse_eval_base("1").unwrap());
+            env.bind(RSymbol::from("a"), &harp::parse_eval_base("2").unwrap());
+
+            let snapshot = env.snapshot();
+            assert_eq!(snapshot.names(), &[String::from("a"), String::from("b")]);
+
+            // Mutations after the snapshot was taken are not observed
+            env.bind(RSymbol::from("c"), &harp::parse_eval_base("3").unwrap());
+            unsafe { R_removeVarFromFrame(r_symbol!("a"), env.inner.sexp) };
+            env.bind(RSymbol::from("b"), &harp::parse_eval_base("4").unwrap());
+
+            assert_eq!(snapshot.len(), 2);
+            assert_eq!(snapshot.names(), &[String::from("a"), String::from("b")]);
+
+            let BindingValue::Standard { object } = &snapshot.bindings()[1].value else {
+                panic!("Expected a standard binding");
+            };
+            assert_eq!(f64::try_from(object.clone()).unwrap(), 1.0);
+
+            // A new snapshot sees the mutations
+            let snapshot = env.snapshot();
+            assert_eq!(snapshot.names(), &[String::from("b"), String::from("c")]);
+        })
+    }
+
     #[test]
     #[allow(non_snake_case)]
     fn test_binding_eq() {
@@ -8,7 +8,10 @@
 use std::backtrace::Backtrace;
 use std::fmt;
 use std::str::Utf8Error;
+use std::time::Duration;
 
+use crate::condition::ConditionObject;
+use crate::srcref::SrcRef;
 use crate::utils::r_type2char;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -28,6 +31,7 @@ pub enum Error {
         class: Option<Vec<String>>,
         r_trace: String,
         rust_trace: Option<Backtrace>,
+        condition: Option<ConditionObject>,
     },
     TopLevelExecError {
         message: String,
@@ -48,6 +52,10 @@ pub enum Error {
         message: String,
         line: i32,
     },
+    SrcRefError {
+        error: Box<Error>,
+        srcref: SrcRef,
+    },
     MissingValueError,
     MissingColumnError {
         name: String,
@@ -66,11 +74,46 @@ pub enum Error {
         backtrace: Backtrace,
         span_trace: tracing_error::SpanTrace,
     },
+    Timeout {
+        timeout: Duration,
+    },
     Anyhow(anyhow::Error),
 }
 
 pub const R_BACKTRACE_HEADER: &str = "R thread backtrace:";
 
+impl Error {
+    /// Whether this is an R error inheriting from `class`, e.g.
+    /// `"rlang_error"` or a custom condition class.
+    pub fn inherits(&self, class: &str) -> bool {
+        match self {
+            Error::TryCatchError {
+                class: Some(classes),
+                ..
+            } => classes.iter().any(|c| c == class),
+            Error::SrcRefError { error, .. } => error.inherits(class),
+            _ => false,
+        }
+    }
+
+    /// The R condition of an R error, if any
+    pub fn condition(&self) -> Option<&ConditionObject> {
+        match self {
+            Error::TryCatchError { condition, .. } => condition.as_ref(),
+            Error::SrcRefError { error, .. } => error.condition(),
+            _ => None,
+        }
+    }
+
+    /// The span of the expression that caused the error, if known
+    pub fn srcref(&self) -> Option<&SrcRef> {
+        match self {
+            Error::SrcRefError { srcref, .. } => Some(srcref),
+            _ => None,
+        }
+    }
+}
+
 // empty implementation required for 'anyhow'
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
@@ -204,6 +247,15 @@ impl fmt::Display for Error {
                 write!(f, "Syntax error on line {} when parsing: {}", line, message)
             },
 
+            Error::SrcRefError { error, srcref } => {
+                write!(
+                    f,
+                    "Error at line {}, column {}: {error}",
+                    srcref.line.start + 1,
+                    srcref.column.start + 1
+                )
+            },
+
             Error::MissingValueError => {
                 write!(f, "Missing value")
             },
@@ -216,6 +268,10 @@ impl fmt::Display for Error {
                 write!(f, "C stack usage too close to the limit")
             },
 
+            Error::Timeout { timeout } => {
+                write!(f, "Evaluation timed out after {} ms", timeout.as_millis())
+            },
+
             Error::Anyhow(err) => {
                 write!(f, "{err:?}")
             },
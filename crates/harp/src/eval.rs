@@ -5,6 +5,8 @@
 //
 //
 
+use std::time::Duration;
+
 use crate::environment::R_ENVS;
 use crate::error::Error;
 use crate::object::RObject;
@@ -13,6 +15,9 @@ use crate::object::RObject;
 pub struct RParseEvalOptions {
     pub forbid_function_calls: bool,
     pub env: RObject,
+    /// Interrupt the evaluation if it takes longer than this, see
+    /// `harp::try_eval_timeout()`
+    pub timeout: Option<Duration>,
 }
 
 impl Default for RParseEvalOptions {
@@ -20,6 +25,7 @@ impl Default for RParseEvalOptions {
         Self {
             forbid_function_calls: false,
             env: RObject::view(R_ENVS.global),
+            timeout: None,
         }
     }
 }
@@ -58,8 +64,63 @@ pub fn parse_eval(code: &str, options: RParseEvalOptions) -> harp::Result<RObjec
 
     for i in 0..exprs.length() {
         let expr = harp::list_get(exprs.sexp, i);
-        value = harp::try_eval(expr, options.env.sexp)?;
+        value = match options.timeout {
+            Some(timeout) => harp::try_eval_timeout(expr, options.env.sexp, timeout)?,
+            None => harp::try_eval(expr, options.env.sexp)?,
+        };
+    }
+
+    Ok(value)
+}
+
+/// Parses code with srcrefs and evaluates each expression in turn
+///
+/// Evaluation errors are wrapped in a `SrcRefError` pointing to the span of
+/// the expression that failed, so they can be mapped back to positions in
+/// the source.
+pub fn parse_eval_with_srcrefs(code: &str, env: impl Into<RObject>) -> harp::Result<RObject> {
+    let env = env.into();
+    let parsed = harp::parse_exprs_with_spans(code)?;
+
+    let mut value = RObject::null();
+
+    for (i, srcref) in parsed.spans.into_iter().enumerate() {
+        let expr = harp::list_get(parsed.exprs.sexp, i as isize);
+        value = harp::try_eval(expr, env.sexp).map_err(|error| Error::SrcRefError {
+            error: Box::new(error),
+            srcref,
+        })?;
     }
 
     Ok(value)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Range;
+
+    use stdext::assert_match;
+
+    use crate::environment::R_ENVS;
+    use crate::error::Error;
+
+    #[test]
+    fn test_parse_eval_with_srcrefs() {
+        crate::r_task(|| {
+            let value = crate::parse_eval_with_srcrefs("x <- 1L\nx + 1L", R_ENVS.base).unwrap();
+            assert_eq!(i32::try_from(value).unwrap(), 2);
+
+            let value = crate::parse_eval_with_srcrefs("", R_ENVS.base).unwrap();
+            assert!(value.is_null());
+
+            let out = crate::parse_eval_with_srcrefs("1\n\n  stop('ouch')\n2", R_ENVS.base);
+            assert_match!(out, Err(Error::SrcRefError { error, srcref }) => {
+                assert_match!(*error, Error::TryCatchError { message, .. } => {
+                    assert_eq!(message, "ouch");
+                });
+                assert_eq!(srcref.line, Range { start: 2, end: 3 });
+                assert_eq!(srcref.column, Range { start: 2, end: 14 });
+            });
+        })
+    }
+}
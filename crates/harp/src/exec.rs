@@ -5,23 +5,34 @@
 //
 //
 
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::ffi::CStr;
 use std::mem::take;
 use std::os::raw::c_void;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::anyhow;
 use libr::*;
 use once_cell::sync::Lazy;
+use parking_lot::Condvar;
+use parking_lot::Mutex;
 use regex::Regex;
 
 use crate::call::RCall;
+use crate::condition::ConditionObject;
 use crate::environment::R_ENVS;
 use crate::error::Error;
 use crate::error::Result;
+use crate::interrupts::interrupts_pending;
+use crate::interrupts::set_interrupts_pending;
 use crate::modules::HARP_ENV;
 use crate::object::r_null_or_try_into;
 use crate::object::RObject;
 use crate::r_symbol;
+use crate::raii::RLocalInterruptsSuspended;
 use crate::utils::r_stringify;
 
 pub static RE_STACK_OVERFLOW: Lazy<Regex> =
@@ -30,6 +41,7 @@ pub static RE_STACK_OVERFLOW: Lazy<Regex> =
 pub struct RFunction {
     pub call: RCall,
     is_namespaced: bool,
+    env: Option<RObject>,
 }
 
 struct CallbackData<'a, F, T>
@@ -53,6 +65,7 @@ impl RFunction {
         RFunction {
             call: RCall::new(function),
             is_namespaced: false,
+            env: None,
         }
     }
 
@@ -71,11 +84,45 @@ impl RFunction {
             RFunction {
                 call: RCall::new(fun),
                 is_namespaced,
+                env: None,
             }
         }
     }
 
+    /// Adds the values of an iterator as positional arguments, like the
+    /// argument list of `do.call()`
+    pub fn add_all<T: Into<RObject>>(&mut self, values: impl IntoIterator<Item = T>) -> &mut Self {
+        for value in values {
+            self.call.add(value);
+        }
+        self
+    }
+
+    /// Adds named arguments, e.g. dots forwarded from a `HashMap`. Arguments
+    /// are added in iteration order, which is unspecified for a `HashMap`.
+    pub fn param_all<K: AsRef<str>, T: Into<RObject>>(
+        &mut self,
+        params: impl IntoIterator<Item = (K, T)>,
+    ) -> &mut Self {
+        for (name, value) in params {
+            self.call.param(name.as_ref(), value);
+        }
+        self
+    }
+
+    /// Sets the environment in which `call()` evaluates the function call.
+    /// Errors keep their R classes, see `Error::inherits()`.
+    pub fn env(&mut self, env: impl Into<RObject>) -> &mut Self {
+        self.env = Some(env.into());
+        self
+    }
+
     pub fn call(&mut self) -> Result<RObject> {
+        if let Some(env) = &self.env {
+            let env = env.sexp;
+            return self.call_in(env);
+        }
+
         // FIXME: Once we have ArkFunction (see
         // https://github.com/posit-dev/positron/issues/2324), we no longer need
         // this logic to call in global. This probably shouldn't be the default?
@@ -92,6 +139,14 @@ impl RFunction {
         let user_call = self.call.build();
         try_eval(user_call.sexp, env)
     }
+
+    /// Call the function in `env`, interrupting it after `timeout`
+    ///
+    /// See `try_eval_timeout()`.
+    pub fn call_in_timeout(&mut self, env: SEXP, timeout: Duration) -> Result<RObject> {
+        let user_call = self.call.build();
+        try_eval_timeout(user_call.sexp, env, timeout)
+    }
 }
 
 /// Evaluate R code in a context protected from errors and longjumps
@@ -109,6 +164,125 @@ pub fn try_eval(expr: SEXP, env: SEXP) -> crate::Result<RObject> {
     res
 }
 
+/// Evaluate R code with a wall-clock timeout
+///
+/// Like `try_eval()`, but a shared watchdog thread schedules an R interrupt
+/// once `timeout` has elapsed. The interrupt unwinds the evaluation and is
+/// reported as an `Error::Timeout`.
+///
+/// Interrupts are lifted for the duration of the evaluation, as they are
+/// suspended within `r_sandbox()`, e.g. in tasks run by ark's `r_task()`.
+/// Note that R only checks for interrupts at specific points, e.g. when
+/// evaluating closures or in loops. Native code that doesn't call
+/// `R_CheckUserInterrupt()` runs to completion.
+pub fn try_eval_timeout(expr: SEXP, env: SEXP, timeout: Duration) -> crate::Result<RObject> {
+    // Keep an interrupt requested by the user out of our evaluation, it is
+    // restored afterwards
+    let pending = interrupts_pending();
+    set_interrupts_pending(false);
+
+    let (res, fired) = {
+        let _interrupts = RLocalInterruptsSuspended::new(false);
+
+        let id = INTERRUPT_TIMER.schedule(timeout);
+        let res = try_eval(expr, env);
+        (res, INTERRUPT_TIMER.cancel(id))
+    };
+
+    if fired {
+        // Our interrupt may have been scheduled after the evaluation
+        // completed, make sure it doesn't leak into the next one
+        set_interrupts_pending(pending);
+
+        if res.is_err() {
+            return Err(Error::Timeout { timeout });
+        }
+    } else {
+        set_interrupts_pending(pending || interrupts_pending());
+    }
+
+    res
+}
+
+/// Watchdog shared by all timed evaluations. A single thread schedules the
+/// interrupts of the pending timers, which may be nested.
+static INTERRUPT_TIMER: Lazy<InterruptTimer> = Lazy::new(InterruptTimer::new);
+
+struct InterruptTimer {
+    state: Arc<(Mutex<InterruptTimerState>, Condvar)>,
+}
+
+#[derive(Default)]
+struct InterruptTimerState {
+    next_id: u64,
+
+    /// Deadlines of the pending timers
+    deadlines: HashMap<u64, Instant>,
+
+    /// Timers that scheduled their interrupt and haven't been cancelled yet
+    fired: HashSet<u64>,
+}
+
+impl InterruptTimer {
+    fn new() -> Self {
+        let state = Arc::new((Mutex::new(InterruptTimerState::default()), Condvar::new()));
+
+        let watchdog_state = state.clone();
+        stdext::spawn!("harp-eval-timeout", move || {
+            let (lock, cvar) = &*watchdog_state;
+            let mut state = lock.lock();
+
+            loop {
+                let now = Instant::now();
+                let expired: Vec<u64> = state
+                    .deadlines
+                    .iter()
+                    .filter(|(_, deadline)| **deadline <= now)
+                    .map(|(id, _)| *id)
+                    .collect();
+
+                for id in expired {
+                    state.deadlines.remove(&id);
+                    state.fired.insert(id);
+                    set_interrupts_pending(true);
+                }
+
+                match state.deadlines.values().min().copied() {
+                    Some(deadline) => {
+                        cvar.wait_until(&mut state, deadline);
+                    },
+                    None => cvar.wait(&mut state),
+                }
+            }
+        });
+
+        Self { state }
+    }
+
+    /// Schedules an interrupt after `timeout` and returns the timer ID
+    fn schedule(&self, timeout: Duration) -> u64 {
+        let (lock, cvar) = &*self.state;
+        let mut state = lock.lock();
+
+        let id = state.next_id;
+        state.next_id += 1;
+
+        state.deadlines.insert(id, Instant::now() + timeout);
+        cvar.notify_one();
+
+        id
+    }
+
+    /// Cancels the timer `id` and returns whether its interrupt was scheduled
+    fn cancel(&self, id: u64) -> bool {
+        let (lock, _) = &*self.state;
+        let mut state = lock.lock();
+
+        state.deadlines.remove(&id);
+        state.fired.remove(&id)
+    }
+}
+
 impl From<&str> for RFunction {
     fn from(function: &str) -> Self {
         RFunction::new("", function)
@@ -223,8 +397,8 @@ where
                 Rf_eval(call.sexp, HARP_ENV.unwrap()).into()
             };
 
-            // Invariant of error slot: List of length 4 [message, call, class, trace],
-            // with `trace` possibly an empty string.
+            // Invariant of error slot: List of length 6 [message, call, class, trace,
+            // fields, id], with `trace` possibly an empty string.
 
             let message: String = RObject::view(harp::list_get(err.sexp, 0)).try_into()?;
 
@@ -236,6 +410,16 @@ where
 
             let r_trace: String = RObject::view(harp::list_get(err.sexp, 3)).try_into()?;
 
+            let fields: Vec<String> = RObject::view(harp::list_get(err.sexp, 4)).try_into()?;
+            let id: i32 = RObject::view(harp::list_get(err.sexp, 5)).try_into()?;
+
+            let condition = ConditionObject::new(
+                message.clone(),
+                class.clone().unwrap_or_default(),
+                fields,
+                id,
+            );
+
             let rust_trace = std::backtrace::Backtrace::force_capture();
 
             *(data.res) = Some(Err(Error::TryCatchError {
@@ -244,6 +428,7 @@ where
                 class,
                 r_trace,
                 rust_trace: Some(rust_trace),
+                condition: Some(condition),
             }));
 
             Ok(())
@@ -562,6 +747,116 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_dynamic_arguments() {
+        crate::r_task(|| {
+            let result = RFunction::new("base", "paste")
+                .add_all(vec!["a", "b", "c"])
+                .param("sep", "-")
+                .call()
+                .unwrap();
+            assert_eq!(String::try_from(result).unwrap(), "a-b-c");
+
+            let mut dots = std::collections::HashMap::new();
+            dots.insert("x", 1);
+            dots.insert("y", 2);
+            let result = RFunction::new("base", "list")
+                .param_all(dots)
+                .call()
+                .unwrap();
+            let result: std::collections::HashMap<String, i32> = result.try_into().unwrap();
+            assert_eq!(result.get("x"), Some(&1));
+            assert_eq!(result.get("y"), Some(&2));
+        })
+    }
+
+    #[test]
+    fn test_call_env() {
+        crate::r_task(|| {
+            let env =
+                crate::parse_eval_base("local({ f <- function() 42L; environment() })").unwrap();
+            let result = RFunction::from("f").env(env).call().unwrap();
+            assert_eq!(i32::try_from(result).unwrap(), 42);
+
+            // Error classes are preserved
+            let cnd = crate::parse_eval_base(
+                "structure(list(message = 'ouch'), class = c('my_error', 'error', 'condition'))",
+            )
+            .unwrap();
+            let err = RFunction::from("stop")
+                .add(cnd)
+                .env(R_ENVS.base)
+                .call()
+                .unwrap_err();
+            assert!(err.inherits("my_error"));
+            assert!(!err.inherits("warning"));
+        })
+    }
+
+    #[test]
+    fn test_try_eval_timeout() {
+        crate::r_task(|| {
+            let timeout = Duration::from_millis(100);
+
+            let expr = crate::parse_expr("repeat {}").unwrap();
+            let out = try_eval_timeout(expr.sexp, R_ENVS.base, timeout);
+            assert_match!(out, Err(Error::Timeout { timeout: t }) => {
+                assert_eq!(t, timeout);
+            });
+
+            // Fast evaluations aren't affected
+            let expr = crate::parse_expr("1L + 1L").unwrap();
+            let out = try_eval_timeout(expr.sexp, R_ENVS.base, timeout).unwrap();
+            assert_eq!(i32::try_from(out).unwrap(), 2);
+
+            let out = RFunction::new("base", "identity")
+                .add(1)
+                .call_in_timeout(R_ENVS.base, timeout)
+                .unwrap();
+            assert_eq!(i32::try_from(out).unwrap(), 1);
+
+            // No interrupt is left pending once the deadline has passed
+            std::thread::sleep(timeout * 2);
+            let expr = crate::parse_expr("for (i in 1:1000) NULL").unwrap();
+            assert!(try_eval(expr.sexp, R_ENVS.base).is_ok());
+        })
+    }
+
+    #[test]
+    fn test_try_eval_timeout_suspended_interrupts() {
+        crate::r_task(|| {
+            // Like in ark's R tasks
+            let _suspended = crate::raii::RLocalInterruptsSuspended::new(true);
+            let timeout = Duration::from_millis(100);
+
+            let expr = crate::parse_expr("repeat {}").unwrap();
+            let out = try_eval_timeout(expr.sexp, R_ENVS.base, timeout);
+            assert_match!(out, Err(Error::Timeout { .. }));
+
+            // Errors are only reported as timeouts when our interrupt fired
+            let expr = crate::parse_expr("stop('oops')").unwrap();
+            let out = try_eval_timeout(expr.sexp, R_ENVS.base, timeout);
+            assert_match!(out, Err(Error::TryCatchError { .. }));
+        })
+    }
+
+    #[test]
+    fn test_try_eval_timeout_preserves_pending_interrupt() {
+        crate::r_task(|| {
+            // Interrupts requested while suspended stay pending, and don't
+            // interrupt the timed evaluation
+            let _suspended = crate::raii::RLocalInterruptsSuspended::new(true);
+            set_interrupts_pending(true);
+
+            let expr = crate::parse_expr("{ for (i in 1:10000) NULL; 1L + 1L }").unwrap();
+            let out = try_eval_timeout(expr.sexp, R_ENVS.base, Duration::from_secs(10));
+            assert!(interrupts_pending());
+
+            set_interrupts_pending(false);
+            assert_eq!(i32::try_from(out.unwrap()).unwrap(), 2);
+        })
+    }
+
     #[test]
     fn test_try_catch_error() {
         crate::r_task(|| unsafe {
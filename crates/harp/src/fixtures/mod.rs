@@ -29,7 +29,8 @@ static INIT: Once = Once::new();
 
 /// Run code accessing the R API in a safe context.
 ///
-/// Takes a lock on `R_TEST_LOCK` and ensures R is initialized.
+/// Takes a lock on `R_TEST_LOCK` and ensures R is initialized. Panics if `f`
+/// leaves the protection stack unbalanced, see `with_protect_audit()`.
 ///
 /// Note: `harp::r_task()` should only be used in Harp tests. Use
 /// `ark::r_task()` in Ark tests so that Ark initialisation also takes place.
@@ -38,7 +39,7 @@ pub(crate) fn r_task<F: FnOnce()>(f: F) {
     let guard = R_TEST_LOCK.lock();
 
     r_test_init();
-    f();
+    crate::protect::with_protect_audit(f);
 
     drop(guard);
 }
@@ -0,0 +1,9 @@
+//
+// interrupts.rs
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+pub use crate::sys::interrupts::interrupts_pending;
+pub use crate::sys::interrupts::set_interrupts_pending;
@@ -4,11 +4,14 @@
 // Copyright (C) 2023 Posit Software, PBC. All rights reserved.
 //
 //
+pub mod altrep;
 pub mod attrib;
 pub mod call;
 mod column_names;
 pub mod command;
+pub mod condition;
 pub mod data_frame;
+pub mod encoding;
 pub mod environment;
 pub mod environment_iter;
 pub mod envvar;
@@ -18,6 +21,7 @@ pub mod exec;
 pub mod external_ptr;
 pub mod fixtures;
 pub mod format;
+pub mod interrupts;
 pub mod json;
 pub mod library;
 pub mod line_ending;
@@ -65,6 +69,7 @@ pub use harp::error::as_result;
 pub use harp::exec::top_level_exec;
 pub use harp::exec::try_catch;
 pub use harp::exec::try_eval;
+pub use harp::exec::try_eval_timeout;
 #[cfg(test)]
 pub(crate) use harp::fixtures::r_task;
 pub use harp::object::list_get;
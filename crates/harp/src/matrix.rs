@@ -1,20 +1,47 @@
+use std::ops::Range;
+
 use libr::*;
 
+use crate::exec::RFunction;
+use crate::exec::RFunctionExt;
+use crate::object::RObject;
 use crate::r_dim;
 use crate::r_int_get;
 use crate::r_length;
 use crate::utils::*;
+use crate::vector::CharacterVector;
+use crate::vector::Vector;
 
 /// Matrix support
 ///
-/// # Notes
-///
-/// Currently we only utilize this for the static [Matrix::dim()] method,
-/// but we could actually wrap matrices here (with validation) and provide
-/// additional methods, like [harp::DataFrame].
-pub struct Matrix {}
+/// Wraps a validated matrix, i.e. an object with a `dim` attribute of length
+/// 2, and provides typed iterators over its columns and rows, access to its
+/// dimnames, and helpers to locate elements in the underlying vector (which
+/// is stored in column-major order). The static [Matrix::dim()] method can
+/// be used without wrapping.
+pub struct Matrix {
+    pub object: RObject,
+    n_row: i32,
+    n_col: i32,
+}
+
+/// Iterator over the values of a column or row of a matrix. `NA` values are
+/// returned as `None`.
+pub struct MatrixIter<V> {
+    vector: V,
+    indices: std::iter::StepBy<Range<isize>>,
+}
 
 impl Matrix {
+    pub fn new(x: SEXP) -> crate::Result<Self> {
+        let (n_row, n_col) = Self::dim(x)?;
+        Ok(Self {
+            object: RObject::new(x),
+            n_row,
+            n_col,
+        })
+    }
+
     /// Compute the dimensions of a matrix
     pub fn dim(x: SEXP) -> crate::Result<(i32, i32)> {
         if !r_is_matrix(x) {
@@ -31,4 +58,191 @@ impl Matrix {
 
         Ok((r_int_get(dim, 0), r_int_get(dim, 1)))
     }
+
+    pub fn n_row(&self) -> i32 {
+        self.n_row
+    }
+
+    pub fn n_col(&self) -> i32 {
+        self.n_col
+    }
+
+    /// The row names, i.e. the first element of `dimnames`
+    pub fn row_names(&self) -> Option<CharacterVector> {
+        self.dimnames(0)
+    }
+
+    /// The column names, i.e. the second element of `dimnames`
+    pub fn column_names(&self) -> Option<CharacterVector> {
+        self.dimnames(1)
+    }
+
+    fn dimnames(&self, i: isize) -> Option<CharacterVector> {
+        unsafe {
+            let dimnames = Rf_getAttrib(self.object.sexp, R_DimNamesSymbol);
+            if r_typeof(dimnames) != VECSXP || r_length(dimnames) != 2 {
+                return None;
+            }
+
+            let names = VECTOR_ELT(dimnames, i);
+            if r_typeof(names) != STRSXP {
+                return None;
+            }
+
+            Some(CharacterVector::new_unchecked(names))
+        }
+    }
+
+    /// The index of an element in the underlying vector (0-based)
+    pub fn index(&self, row: isize, column: isize) -> crate::Result<isize> {
+        self.check_row(row)?;
+        self.check_column(column)?;
+        Ok(column * self.n_row as isize + row)
+    }
+
+    /// The range of the elements of a column in the underlying vector
+    pub fn column_range(&self, column: isize) -> crate::Result<Range<usize>> {
+        self.check_column(column)?;
+        let n_row = self.n_row as usize;
+        let start = column as usize * n_row;
+        Ok(start..start + n_row)
+    }
+
+    /// Extracts a column as a vector with `x[, column]`, keeping the row
+    /// names. Unlike the iterators, this supports all types of matrices,
+    /// e.g. lists and classed objects.
+    pub fn column(&self, column: isize) -> crate::Result<RObject> {
+        self.check_column(column)?;
+        RFunction::new("base", "[")
+            .add(self.object.sexp)
+            .add(unsafe { R_MissingArg })
+            .add(RObject::from(column as i32 + 1))
+            .call()
+    }
+
+    /// Iterates over the values of a column. Fails if the matrix is not of
+    /// the type of `V`.
+    pub fn column_iter<V: Vector>(&self, column: isize) -> crate::Result<MatrixIter<V>> {
+        let range = self.column_range(column)?;
+        let indices = range.start as isize..range.end as isize;
+        Ok(MatrixIter {
+            vector: V::new(self.object.sexp)?,
+            indices: indices.step_by(1),
+        })
+    }
+
+    /// Iterates over the values of a row. Fails if the matrix is not of the
+    /// type of `V`.
+    pub fn row_iter<V: Vector>(&self, row: isize) -> crate::Result<MatrixIter<V>> {
+        self.check_row(row)?;
+        let n_row = self.n_row as isize;
+        let indices = row..n_row * self.n_col as isize;
+        Ok(MatrixIter {
+            vector: V::new(self.object.sexp)?,
+            indices: indices.step_by(n_row as usize),
+        })
+    }
+
+    fn check_row(&self, row: isize) -> crate::Result<()> {
+        if row < 0 || row >= self.n_row as isize {
+            return Err(crate::anyhow!("Row {row} out of bounds"));
+        }
+        Ok(())
+    }
+
+    fn check_column(&self, column: isize) -> crate::Result<()> {
+        if column < 0 || column >= self.n_col as isize {
+            return Err(crate::anyhow!("Column {column} out of bounds"));
+        }
+        Ok(())
+    }
+}
+
+impl<V: Vector> Iterator for MatrixIter<V> {
+    type Item = Option<V::Type>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.indices
+            .next()
+            .map(|index| self.vector.get_unchecked(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::matrix::Matrix;
+    use crate::vector::CharacterVector;
+    use crate::vector::IntegerVector;
+    use crate::vector::NumericVector;
+    use crate::vector::Vector;
+
+    #[test]
+    fn test_matrix_iterators() {
+        crate::r_task(|| {
+            let x = harp::parse_eval_base("matrix(c(1:5, NA), nrow = 2)").unwrap();
+            let matrix = Matrix::new(x.sexp).unwrap();
+            assert_eq!((matrix.n_row(), matrix.n_col()), (2, 3));
+
+            let column: Vec<Option<i32>> =
+                matrix.column_iter::<IntegerVector>(1).unwrap().collect();
+            assert_eq!(column, vec![Some(3), Some(4)]);
+
+            let row: Vec<Option<i32>> = matrix.row_iter::<IntegerVector>(1).unwrap().collect();
+            assert_eq!(row, vec![Some(2), Some(4), None]);
+
+            assert_eq!(matrix.index(1, 2).unwrap(), 5);
+            assert_eq!(matrix.column_range(2).unwrap(), 4..6);
+
+            // Out of bounds
+            assert!(matrix.column_iter::<IntegerVector>(3).is_err());
+            assert!(matrix.row_iter::<IntegerVector>(-1).is_err());
+            assert!(matrix.index(2, 0).is_err());
+
+            // Wrong type
+            assert!(matrix.column_iter::<NumericVector>(0).is_err());
+
+            // Not a matrix
+            let x = harp::parse_eval_base("1:3").unwrap();
+            assert!(Matrix::new(x.sexp).is_err());
+        })
+    }
+
+    #[test]
+    fn test_matrix_dimnames() {
+        crate::r_task(|| {
+            let x = harp::parse_eval_base(
+                "matrix(1:4, nrow = 2, dimnames = list(c('a', 'b'), c('x', 'y')))",
+            )
+            .unwrap();
+            let matrix = Matrix::new(x.sexp).unwrap();
+
+            let names = |names: Option<CharacterVector>| -> Vec<Option<String>> {
+                names.unwrap().iter().collect()
+            };
+            assert_eq!(names(matrix.row_names()), vec![
+                Some(String::from("a")),
+                Some(String::from("b"))
+            ]);
+            assert_eq!(names(matrix.column_names()), vec![
+                Some(String::from("x")),
+                Some(String::from("y"))
+            ]);
+
+            let column = matrix.column(1).unwrap();
+            let column: Vec<i32> = column.try_into().unwrap();
+            assert_eq!(column, vec![3, 4]);
+
+            // Partial dimnames
+            let code = "matrix(1:4, nrow = 2, dimnames = list(NULL, c('x', 'y')))";
+            let x = harp::parse_eval_base(code).unwrap();
+            let matrix = Matrix::new(x.sexp).unwrap();
+            assert!(matrix.row_names().is_none());
+            assert!(matrix.column_names().is_some());
+
+            let x = harp::parse_eval_base("matrix(1:4, nrow = 2)").unwrap();
+            let matrix = Matrix::new(x.sexp).unwrap();
+            assert!(matrix.row_names().is_none());
+            assert!(matrix.column_names().is_none());
+        })
+    }
 }
@@ -19,6 +19,7 @@ use libr::*;
 use crate::error::Error;
 use crate::exec::RFunction;
 use crate::exec::RFunctionExt;
+use crate::protect::RProtect;
 use crate::r_inherits;
 use crate::r_symbol;
 use crate::size::r_size;
@@ -31,6 +32,7 @@ use crate::utils::r_is_null;
 use crate::utils::r_is_object;
 use crate::utils::r_is_s4;
 use crate::utils::r_str_to_owned_utf8;
+use crate::utils::r_type2char;
 use crate::utils::r_typeof;
 
 // Objects are protected using a doubly-linked list,
@@ -45,7 +47,8 @@ unsafe fn protect(object: SEXP) -> SEXP {
     }
 
     // Protect the incoming object, just in case.
-    Rf_protect(object);
+    let mut protect = RProtect::new();
+    protect.add(object);
 
     // Initialize the precious list.
     PRECIOUS_LIST_ONCE.call_once(|| {
@@ -62,7 +65,7 @@ unsafe fn protect(object: SEXP) -> SEXP {
 
     // The new cell will be inserted between the existing head and tail,
     // so create a new cell referencing the head and tail of the list.
-    let cell = Rf_protect(Rf_cons(head, tail));
+    let cell = protect.add(Rf_cons(head, tail));
 
     // Set the TAG on the cell so the object is protected.
     SET_TAG(cell, object);
@@ -73,9 +76,6 @@ unsafe fn protect(object: SEXP) -> SEXP {
     // Point the CAR of the current tail to the newly-created cell.
     SETCAR(tail, cell);
 
-    // Clean up the protect stack and return.
-    Rf_unprotect(2);
-
     // Uncomment if debugging protection issues
     // trace!("Protecting cell:   {:?}", cell);
     return cell;
@@ -460,6 +460,52 @@ impl RObject {
         }
     }
 
+    /// List accessor with type and bounds checks; get an element of a list
+    /// as another RObject.
+    ///
+    /// - `i` - The 0-based index of the element to return.
+    pub fn list_elt(&self, i: usize) -> crate::error::Result<RObject> {
+        r_assert_type(self.sexp, &[VECSXP])?;
+
+        let length = self.length() as usize;
+        if i >= length {
+            return Err(Error::ValueOutOfRange {
+                value: i as i64,
+                min: 0,
+                max: length as i64 - 1,
+            });
+        }
+
+        Ok(RObject::new(unsafe {
+            VECTOR_ELT(self.sexp, i as R_xlen_t)
+        }))
+    }
+
+    /// Gets a named attribute from the object, e.g. `attr("names")`. Returns
+    /// `None` if the attribute is not set.
+    pub fn attr(&self, name: &str) -> Option<RObject> {
+        self.get_attribute(name)
+    }
+
+    /// Sets a named attribute on the object. Unlike `set_attribute()`, errors
+    /// raised by R, e.g. for `names` of the wrong length, are returned rather
+    /// than thrown.
+    pub fn set_attr(&self, name: &str, value: impl Into<RObject>) -> crate::error::Result<()> {
+        // These can't have attributes
+        let kind = self.kind();
+        if matches!(kind, NILSXP | SYMSXP | CHARSXP) {
+            return Err(harp::anyhow!(
+                "Can't set attribute `{name}` on an object of type {}",
+                r_type2char(kind)
+            ));
+        }
+
+        let value: RObject = value.into();
+        crate::try_catch(|| unsafe {
+            Rf_setAttrib(self.sexp, r_symbol!(name), value.sexp);
+        })
+    }
+
     /// Gets a vector containing names for the object's values (from the `names`
     /// attribute). Returns `None` if the object's value(s) don't have names.
     pub fn names(&self) -> Option<Vec<Option<String>>> {
@@ -474,9 +520,9 @@ impl RObject {
 
     pub fn set_attribute(&self, name: &str, value: SEXP) {
         unsafe {
-            Rf_protect(value);
+            let mut protect = RProtect::new();
+            protect.add(value);
             Rf_setAttrib(self.sexp, r_symbol!(name), value);
-            Rf_unprotect(1);
         }
     }
 
@@ -637,14 +683,14 @@ impl From<f64> for RObject {
 impl From<&str> for RObject {
     fn from(value: &str) -> Self {
         unsafe {
-            let vector = Rf_protect(Rf_allocVector(STRSXP, 1));
+            let mut protect = RProtect::new();
+            let vector = protect.add(Rf_allocVector(STRSXP, 1));
             let element = Rf_mkCharLenCE(
                 value.as_ptr() as *mut c_char,
                 value.len() as i32,
                 cetype_t_CE_UTF8,
             );
             SET_STRING_ELT(vector, 0, element);
-            Rf_unprotect(1);
             return RObject::new(vector);
         }
     }
@@ -702,7 +748,8 @@ impl From<HashMap<String, String>> for RObject {
     fn from(value: HashMap<String, String>) -> Self {
         unsafe {
             // Allocate the vector of values
-            let values = Rf_protect(Rf_allocVector(STRSXP, value.len() as isize));
+            let mut protect = RProtect::new();
+            let values = protect.add(Rf_allocVector(STRSXP, value.len() as isize));
 
             // Allocate the vector of names; this will be protected by attaching
             // it to the values vector as an attribute
@@ -730,9 +777,6 @@ impl From<HashMap<String, String>> for RObject {
                 SET_STRING_ELT(names, idx as isize, key_str);
             }
 
-            // Clean up the protect stack and return the RObject from the values
-            // vector
-            Rf_unprotect(1);
             RObject::new(values)
         }
     }
@@ -1772,6 +1816,46 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_list_elt() {
+        crate::r_task(|| {
+            let x = parse_eval_global("list(1L, 'a')").unwrap();
+            assert_eq!(i32::try_from(x.list_elt(0).unwrap()).unwrap(), 1);
+            assert_eq!(String::try_from(x.list_elt(1).unwrap()).unwrap(), "a");
+
+            assert_match!(
+                x.list_elt(2),
+                Err(Error::ValueOutOfRange {
+                    value: 2,
+                    min: 0,
+                    max: 1
+                })
+            );
+
+            let x = parse_eval_global("1:2").unwrap();
+            assert_match!(x.list_elt(0), Err(Error::UnexpectedType(INTSXP, _)));
+        })
+    }
+
+    #[test]
+    fn test_attr() {
+        crate::r_task(|| {
+            let x = parse_eval_global("list(1, 2)").unwrap();
+            assert!(x.attr("names").is_none());
+
+            let names = vec![String::from("a"), String::from("b")];
+            x.set_attr("names", names).unwrap();
+            let names: Vec<String> = x.attr("names").unwrap().try_into().unwrap();
+            assert_eq!(names, vec!["a", "b"]);
+
+            // R errors are returned
+            let names = vec![String::from("a"), String::from("b"), String::from("c")];
+            assert!(x.set_attr("names", names).is_err());
+
+            assert!(RObject::null().set_attr("foo", 1).is_err());
+        })
+    }
+
     #[test]
     fn test_is_null() {
         crate::r_task(|| {
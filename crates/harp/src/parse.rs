@@ -71,6 +71,28 @@ pub fn parse_exprs_with_srcrefs(text: &str) -> crate::Result<RObject> {
     parse_exprs_ext(&ParseInput::SrcFile(&srcfile))
 }
 
+/// Expressions parsed with srcrefs along with the span of each of them
+pub struct ParsedExprs {
+    /// An EXPRSXP vector
+    pub exprs: RObject,
+    pub spans: Vec<srcref::SrcRef>,
+}
+
+/// Same but also returns the span of each expression, e.g. to map errors
+/// back to positions in the source
+pub fn parse_exprs_with_spans(text: &str) -> crate::Result<ParsedExprs> {
+    let exprs = parse_exprs_with_srcrefs(text)?;
+
+    // Empty code doesn't get a `srcref` attribute
+    let spans = if exprs.length() == 0 {
+        Vec::new()
+    } else {
+        exprs.srcrefs()?
+    };
+
+    Ok(ParsedExprs { exprs, spans })
+}
+
 pub fn parse_exprs_ext<'a>(input: &ParseInput<'a>) -> crate::Result<RObject> {
     let status = parse_status(input)?;
     match status {
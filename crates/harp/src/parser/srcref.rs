@@ -18,7 +18,7 @@ use crate::RObject;
 
 /// Structured representation of `srcref` integer vectors
 /// 0-based offsets.
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct SrcRef {
     /// Lines and virtual lines may differ if a `#line` directive is used in code:
     /// the former just counts actual lines, the latter respects the directive.
@@ -5,22 +5,49 @@
 //
 //
 
+use std::collections::HashMap;
+use std::panic::Location;
+use std::sync::Mutex;
+
 use libr::Rf_protect;
 use libr::Rf_unprotect;
 use libr::SEXP;
+use once_cell::sync::Lazy;
 
 // NOTE: The RProtect struct uses R's stack-based object protection, and so is
 // only appropriate for R objects with 'automatic' lifetime. In general, this
 // should only be used when interfacing with native R APIs; general usages
 // should use the RObject struct instead.
+//
+// In debug builds, guards check that the protection stack is balanced when
+// they are dropped, i.e. that the code running within their scope didn't
+// leave objects on the stack or unprotect objects it doesn't own. Imbalances
+// are recorded per call site of `RProtect::new()` and reported at the end of
+// each test task, see `with_protect_audit()`. All balanced protections in
+// harp go through this guard. The remaining raw `Rf_protect()` calls protect
+// objects right before a longjump, which R unwinds for us.
+//
+// The audit reads `R_PPStackTop`. This variable is exported by R but is not
+// part of its API, so the audit is disabled when it's missing.
 pub struct RProtect {
     count: i32,
+    #[cfg(debug_assertions)]
+    top: Option<i32>,
+    #[cfg(debug_assertions)]
+    location: &'static Location<'static>,
 }
 
 impl RProtect {
     /// SAFETY: Assumes that the R lock is held.
+    #[track_caller]
     pub unsafe fn new() -> Self {
-        Self { count: 0 }
+        Self {
+            count: 0,
+            #[cfg(debug_assertions)]
+            top: protect_stack_top(),
+            #[cfg(debug_assertions)]
+            location: Location::caller(),
+        }
     }
 
     /// SAFETY: Assumes that the R lock is held.
@@ -33,6 +60,180 @@ impl RProtect {
 impl Drop for RProtect {
     /// SAFETY: Assumes that the R lock is held.
     fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        if let (Some(top), Some(current)) = (self.top, protect_stack_top()) {
+            let offset = current - (top + self.count);
+            if offset != 0 {
+                log::error!(
+                    "Unbalanced protection stack ({offset:+}) when dropping `RProtect` created at {}",
+                    self.location
+                );
+                record_imbalance(self.location, offset);
+            }
+        }
+
         unsafe { Rf_unprotect(self.count) }
     }
 }
+
+/// An unbalanced protection stack detected when dropping an `RProtect` guard
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtectImbalance {
+    /// Where the guard was created
+    pub location: &'static Location<'static>,
+    /// Number of objects left on the stack (positive) or unprotected too
+    /// many times (negative), as of the last occurrence
+    pub offset: i32,
+    pub occurrences: usize,
+}
+
+impl std::fmt::Display for ProtectImbalance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {:+} ({} occurrence(s))",
+            self.location, self.offset, self.occurrences
+        )
+    }
+}
+
+static IMBALANCES: Lazy<Mutex<HashMap<&'static Location<'static>, ProtectImbalance>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn record_imbalance(location: &'static Location<'static>, offset: i32) {
+    let mut imbalances = IMBALANCES.lock().unwrap();
+
+    let imbalance = imbalances.entry(location).or_insert(ProtectImbalance {
+        location,
+        offset,
+        occurrences: 0,
+    });
+    imbalance.offset = offset;
+    imbalance.occurrences += 1;
+}
+
+/// Returns the imbalances recorded since the last call, sorted by location
+pub fn take_protect_imbalances() -> Vec<ProtectImbalance> {
+    let mut imbalances: Vec<ProtectImbalance> = IMBALANCES
+        .lock()
+        .unwrap()
+        .drain()
+        .map(|(_, imbalance)| imbalance)
+        .collect();
+
+    imbalances.sort_by_key(|imbalance| {
+        let location = imbalance.location;
+        (location.file(), location.line(), location.column())
+    });
+
+    imbalances
+}
+
+/// The current size of the protection stack, if R exposes it
+fn protect_stack_top() -> Option<i32> {
+    unsafe {
+        if libr::has::R_PPStackTop() {
+            Some(libr::get(libr::R_PPStackTop))
+        } else {
+            None
+        }
+    }
+}
+
+/// Runs `f` and panics if it leaked protected objects or if unbalanced
+/// `RProtect` guards were dropped in the meantime. Used at the teardown of
+/// test tasks. The panic message summarises the imbalances per call site,
+/// objects leaked by `f` itself are attributed to the caller of
+/// `with_protect_audit()`. Only audits debug builds.
+#[track_caller]
+pub fn with_protect_audit<T>(f: impl FnOnce() -> T) -> T {
+    if !cfg!(debug_assertions) {
+        return f();
+    }
+
+    // Start from a clean slate so that we only report our own imbalances
+    take_protect_imbalances();
+
+    let top = protect_stack_top();
+    let out = f();
+
+    if let (Some(top), Some(current)) = (top, protect_stack_top()) {
+        if current != top {
+            record_imbalance(Location::caller(), current - top);
+        }
+    }
+
+    let imbalances = take_protect_imbalances();
+    if !imbalances.is_empty() {
+        let summary: Vec<String> = imbalances
+            .into_iter()
+            .map(|imbalance| imbalance.to_string())
+            .collect();
+        panic!("Unbalanced protection stack:\n{}", summary.join("\n"));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use libr::R_NilValue;
+    use libr::Rf_protect;
+    use libr::Rf_unprotect;
+
+    use crate::protect::take_protect_imbalances;
+    use crate::protect::with_protect_audit;
+    use crate::protect::RProtect;
+    use crate::r_task;
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_protect_imbalance() {
+        r_task(|| unsafe {
+            let mut protect = RProtect::new();
+            protect.add(R_NilValue);
+            drop(protect);
+
+            // Balanced guards are not reported
+            assert!(take_protect_imbalances().is_empty());
+
+            let line = line!() + 1;
+            let mut protect = RProtect::new();
+            protect.add(R_NilValue);
+
+            // Leaked within the scope of the guard
+            Rf_protect(R_NilValue);
+            drop(protect);
+            Rf_unprotect(1);
+
+            let imbalances = take_protect_imbalances();
+            assert_eq!(imbalances.len(), 1);
+            assert_eq!(imbalances[0].offset, 1);
+            assert_eq!(imbalances[0].occurrences, 1);
+            assert_eq!(imbalances[0].location.file(), file!());
+            assert_eq!(imbalances[0].location.line(), line);
+
+            // Imbalances are drained
+            assert!(take_protect_imbalances().is_empty());
+        })
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn test_protect_audit_detects_imbalanced_task() {
+        r_task(|| unsafe {
+            // Balanced tasks pass through their result
+            assert_eq!(with_protect_audit(|| 1), 1);
+
+            let line = line!() + 1;
+            let result = std::panic::catch_unwind(|| with_protect_audit(|| Rf_protect(R_NilValue)));
+            Rf_unprotect(1);
+
+            let error = result.unwrap_err();
+            let message = error.downcast_ref::<String>().unwrap();
+            assert!(message.contains("Unbalanced protection stack"));
+            assert!(message.contains(&format!("{}:{line}", file!())));
+            assert!(message.contains("+1 (1 occurrence(s))"));
+        })
+    }
+}
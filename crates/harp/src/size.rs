@@ -13,13 +13,13 @@ use std::u32;
 use libc::c_double;
 use libr::*;
 
+use crate::altrep::Altrep;
 use crate::environment::BindingValue;
 use crate::environment::Environment;
 use crate::environment::R_ENVS;
 use crate::list_get;
 use crate::object::r_chr_get;
 use crate::object::r_length;
-use crate::r_is_altrep;
 use crate::r_symbol;
 use crate::r_typeof;
 use crate::RObject;
@@ -87,14 +87,19 @@ fn obj_size_tree(
         sizeof_node
     };
 
-    if r_is_altrep(x) {
-        let klass = unsafe { libr::ALTREP_CLASS(x) };
+    if let Some(altrep) = Altrep::new(x) {
         size += 3 * size_of::<SEXP>();
 
-        size += obj_size_tree(klass, base_env, sizeof_node, sizeof_vector, seen, depth + 1);
-
         size += obj_size_tree(
-            unsafe { libr::R_altrep_data1(x) },
+            altrep.class_object(),
+            base_env,
+            sizeof_node,
+            sizeof_vector,
+            seen,
+            depth + 1,
+        );
+        size += obj_size_tree(
+            altrep.data1(),
             base_env,
             sizeof_node,
             sizeof_vector,
@@ -102,7 +107,7 @@ fn obj_size_tree(
             depth + 1,
         );
         size += obj_size_tree(
-            unsafe { libr::R_altrep_data2(x) },
+            altrep.data2(),
             base_env,
             sizeof_node,
             sizeof_vector,
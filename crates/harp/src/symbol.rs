@@ -6,12 +6,8 @@
 //
 
 use std::cmp::Ordering;
-use std::ffi::CStr;
 use std::ops::Deref;
 
-use libr::vmaxget;
-use libr::vmaxset;
-use libr::Rf_translateCharUTF8;
 use libr::PRINTNAME;
 use libr::R_CHAR;
 use libr::SEXP;
@@ -97,12 +93,7 @@ impl std::fmt::Display for RSymbol {
 
 impl PartialEq<&str> for RSymbol {
     fn eq(&self, other: &&str) -> bool {
-        unsafe {
-            let vmax = vmaxget();
-            let utf8text = Rf_translateCharUTF8(PRINTNAME(self.sexp));
-            vmaxset(vmax);
-            CStr::from_ptr(utf8text).to_str().unwrap() == *other
-        }
+        unsafe { r_str_to_owned_utf8_unchecked(PRINTNAME(self.sexp)) == *other }
     }
 }
 
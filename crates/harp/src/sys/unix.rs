@@ -6,6 +6,7 @@
  */
 
 pub mod command;
+pub mod interrupts;
 pub mod library;
 pub mod line_ending;
 pub mod polled_events;
@@ -0,0 +1,20 @@
+/*
+ * interrupts.rs
+ *
+ * Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+ *
+ */
+
+use libr::R_interrupts_pending;
+
+pub fn interrupts_pending() -> bool {
+    unsafe { libr::get(R_interrupts_pending) == 1 }
+}
+
+pub fn set_interrupts_pending(pending: bool) {
+    if pending {
+        unsafe { libr::set(R_interrupts_pending, 1) };
+    } else {
+        unsafe { libr::set(R_interrupts_pending, 0) };
+    }
+}
@@ -0,0 +1,22 @@
+/*
+ * interrupts.rs
+ *
+ * Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+ *
+ */
+
+use libr::Rboolean_FALSE;
+use libr::Rboolean_TRUE;
+use libr::UserBreak;
+
+pub fn interrupts_pending() -> bool {
+    unsafe { libr::get(UserBreak) == Rboolean_TRUE }
+}
+
+pub fn set_interrupts_pending(pending: bool) {
+    if pending {
+        unsafe { libr::set(UserBreak, Rboolean_TRUE) };
+    } else {
+        unsafe { libr::set(UserBreak, Rboolean_FALSE) };
+    }
+}
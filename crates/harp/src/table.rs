@@ -1,10 +1,10 @@
 use libr::*;
 
-use crate::exec::RFunction;
-use crate::exec::RFunctionExt;
 use crate::object::RObject;
 use crate::utils::r_is_data_frame;
 use crate::utils::r_is_matrix;
+use crate::DataFrame;
+use crate::Matrix;
 
 #[derive(Clone, Copy)]
 pub enum TableKind {
@@ -31,20 +31,7 @@ pub fn table_kind(x: SEXP) -> Option<TableKind> {
 pub fn tbl_get_column(x: SEXP, column_index: i32, kind: TableKind) -> anyhow::Result<RObject> {
     // Get the column to sort by
     match kind {
-        TableKind::Dataframe => {
-            let column = RFunction::new("base", "[[")
-                .add(x)
-                .add(RObject::from(column_index + 1))
-                .call()?;
-            Ok(column)
-        },
-        TableKind::Matrix => {
-            let column = RFunction::new("base", "[")
-                .add(x)
-                .add(unsafe { R_MissingArg })
-                .add(RObject::from(column_index + 1))
-                .call()?;
-            Ok(column)
-        },
+        TableKind::Dataframe => Ok(DataFrame::new(x)?.col_at(column_index as usize)?),
+        TableKind::Matrix => Ok(Matrix::new(x)?.column(column_index as isize)?),
     }
 }
@@ -15,6 +15,7 @@ use regex::Regex;
 
 use crate::call::r_expr_quote;
 use crate::call::RArgument;
+use crate::encoding::r_char_to_string;
 use crate::environment::Environment;
 use crate::error::Error;
 use crate::error::Result;
@@ -212,28 +213,10 @@ pub fn r_str_to_owned_utf8(x: SEXP) -> Result<String> {
 ///
 /// - `x` is a CHARSXP that is assumed to not be missing.
 ///
-/// Unchecked here only refers to checking for `NA`. Otherwise it still attempts
-/// a UTF-8 translation and will replace any remaining invalid UTF-8 characters
-/// with the UTF-8 replacement character.
+/// Unchecked here only refers to checking for `NA`. Otherwise the string is
+/// converted according to its declared encoding, see `r_char_to_string()`.
 pub fn r_str_to_owned_utf8_unchecked(x: SEXP) -> String {
-    unsafe {
-        // Attempt to translate it to a UTF-8 C string (note that this allocates
-        // with `R_alloc()` so we need to save and reset the protection stack).
-        // Sadly this can still result in invalid UTF-8 bytes, so we are forced
-        // to use `to_string_lossy()` below to be absolutely sure that invalid
-        // UTF-8 has been replaced with the valid UTF-8 replacement character.
-        // https://github.com/posit-dev/positron/issues/2698
-        let vmax = vmaxget();
-        let x = Rf_translateCharUTF8(x);
-        vmaxset(vmax);
-
-        // `const char*` -> `Cstr` wrapper -> `Cow<'static, str>` -> `String`
-        let x = CStr::from_ptr(x);
-        let x = x.to_string_lossy();
-        let x = x.to_string();
-
-        x
-    }
+    r_char_to_string(x)
 }
 
 pub fn pairlist_size(mut pairlist: SEXP) -> Result<isize> {
@@ -287,15 +270,6 @@ pub fn r_vec_shape(value: SEXP) -> String {
     }
 }
 
-pub fn r_altrep_class(object: SEXP) -> String {
-    let serialized_klass = unsafe { ATTRIB(ALTREP_CLASS(object)) };
-
-    let klass = RSymbol::new_unchecked(unsafe { CAR(serialized_klass) });
-    let pkg = RSymbol::new_unchecked(unsafe { CADR(serialized_klass) });
-
-    format!("{}::{}", pkg, klass)
-}
-
 pub fn r_typeof(object: SEXP) -> u32 {
     // SAFETY: The type of an R object is typically considered constant,
     // and TYPEOF merely queries the R type directly from the SEXPREC struct.
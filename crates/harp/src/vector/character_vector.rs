@@ -126,6 +126,44 @@ impl TryFrom<&[SEXP]> for CharacterVector {
     }
 }
 
+/// Builds a character vector from an iterator. `None` values become `NA`.
+impl<S: AsRef<str>> FromIterator<Option<S>> for CharacterVector {
+    fn from_iter<I: IntoIterator<Item = Option<S>>>(iter: I) -> Self {
+        let values: Vec<Option<S>> = iter.into_iter().collect();
+
+        unsafe {
+            let vector = CharacterVector::with_length(values.len());
+            for (i, value) in values.iter().enumerate() {
+                let charsexp = match value {
+                    Some(value) => {
+                        let value = value.as_ref();
+                        Rf_mkCharLenCE(
+                            value.as_ptr() as *const c_char,
+                            value.len() as i32,
+                            cetype_t_CE_UTF8,
+                        )
+                    },
+                    None => R_NaString,
+                };
+                SET_STRING_ELT(vector.data(), i as R_xlen_t, charsexp);
+            }
+            vector
+        }
+    }
+}
+
+impl FromIterator<String> for CharacterVector {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        iter.into_iter().map(Some).collect()
+    }
+}
+
+impl<'a> FromIterator<&'a str> for CharacterVector {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        iter.into_iter().map(Some).collect()
+    }
+}
+
 impl TryFrom<&CharacterVector> for Vec<String> {
     type Error = harp::Error;
 
@@ -190,4 +228,19 @@ mod test {
             assert_eq!(s, alphabet);
         })
     }
+
+    #[test]
+    fn test_from_iter() {
+        crate::r_task(|| {
+            let vector = CharacterVector::from_iter(["a", "b"].map(String::from));
+            assert_eq!(vector, ["a", "b"]);
+
+            let vector: CharacterVector = vec![Some("a"), None].into_iter().collect();
+            assert_eq!(vector.get(0).unwrap(), Some(String::from("a")));
+            assert_eq!(vector.get(1).unwrap(), None);
+
+            let vector = CharacterVector::from_iter(std::iter::empty::<&str>());
+            assert_eq!(unsafe { vector.len() }, 0);
+        })
+    }
 }
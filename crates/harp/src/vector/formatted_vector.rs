@@ -5,6 +5,8 @@
 //
 //
 
+use std::ops::Range;
+
 use anyhow::anyhow;
 use libr::CPLXSXP;
 use libr::INTSXP;
@@ -35,8 +37,20 @@ impl Default for FormatOptions {
     }
 }
 
+/// Number of elements of classed vectors formatted at once. Their `format()`
+/// method is called on chunks of this size as iteration progresses.
+const FORMAT_CHUNK_SIZE: usize = 1000;
+
+/// Formats the elements of an atomic vector
+///
+/// Elements are formatted lazily, as they are iterated over, so only the
+/// elements that are consumed get formatted. Atomic vectors are formatted
+/// element by element. Classed vectors are formatted by chunks of
+/// `FORMAT_CHUNK_SIZE` elements, so that memory stays bounded regardless of
+/// the length of the vector.
 pub struct FormattedVector {
     vector: RObject,
+    len: usize,
 }
 
 impl FormattedVector {
@@ -44,67 +58,66 @@ impl FormattedVector {
         r_assert_type(vector.sexp, &[
             RAWSXP, LGLSXP, INTSXP, REALSXP, STRSXP, CPLXSXP,
         ])?;
-        Ok(Self { vector })
+        let len = r_length(vector.sexp) as usize;
+        Ok(Self { vector, len })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
-    /// Returns an iterator for the vector.
-    /// Performance: for S3 objects this will cause the iterator to
-    /// format the entire vector before starting the iteration.
+    /// Returns an iterator over all elements of the vector.
     pub fn iter(&self) -> anyhow::Result<FormattedVectorIter> {
-        FormattedVectorIter::new_unchecked(self.vector.clone(), None)
+        self.format_range(0, self.len)
     }
 
     /// Returns an iterator over the first `n` elements of a vector.
-    /// Should be used when the vector is potentially large and you won't need to
-    /// iterate over the entire vector.
     pub fn iter_take(&self, n: usize) -> anyhow::Result<FormattedVectorIter> {
-        // The iterators for atomic values and factors are lazy and don't need any special
-        // treatment.
-        let length = r_length(self.vector.sexp);
-        let n = n.min(length as usize);
+        self.format_range(0, n.min(self.len))
+    }
 
-        FormattedVectorIter::new_unchecked(self.vector.clone(), Some(Box::new(0..n as i64)))
+    /// Returns an iterator over the `len` elements starting at `start`.
+    pub fn format_range(&self, start: usize, len: usize) -> anyhow::Result<FormattedVectorIter> {
+        let end = start.saturating_add(len);
+        if end > self.len {
+            return Err(anyhow!(
+                "Range {start}..{end} out of bounds for vector of length {}",
+                self.len
+            ));
+        }
+        FormattedVectorIter::new(self.vector.clone(), start..end)
     }
 
     /// Formats a single element of a vector
     pub fn format_elt(&self, index: isize) -> anyhow::Result<String> {
-        // Check if the index is allowed
-        let length = r_length(self.vector.sexp);
-
-        if index < 0 || index >= length as isize {
+        if index < 0 || index as usize >= self.len {
             return Err(anyhow!("Index out of bounds"));
         }
 
-        let indices = vec![index as i64].into_iter();
-        let result: Vec<String> =
-            FormattedVectorIter::new_unchecked(self.vector.clone(), Some(Box::new(indices)))?
-                .collect();
-
-        if result.len() != 1 {
-            return Err(anyhow!("Unexpected error"));
-        }
-
-        Ok(result[0].clone())
+        self.format_range(index as usize, 1)?
+            .next()
+            .ok_or_else(|| anyhow!("Can't format element {index}"))
     }
 
     /// Returns an iterator over a column of a matrix.
-    /// Subset a vector and return an iterator for the selected column.
     pub fn column_iter(&self, column: isize) -> anyhow::Result<FormattedVectorIter> {
-        let indices = self.column_iter_indices(column)?;
-        FormattedVectorIter::new_unchecked(self.vector.clone(), Some(Box::new(indices)))
+        let (n_row, _n_col) = harp::Matrix::dim(self.vector.sexp)?;
+        self.column_iter_n(column, n_row as usize)
     }
 
     /// Returns an iterator over the first `n` elements of a column of a matrix.
     pub fn column_iter_n(&self, column: isize, n: usize) -> anyhow::Result<FormattedVectorIter> {
-        let indices = self.column_iter_indices(column)?.take(n);
-        FormattedVectorIter::new_unchecked(self.vector.clone(), Some(Box::new(indices)))
-    }
+        let (n_row, n_col) = harp::Matrix::dim(self.vector.sexp)?;
+        if column < 0 || column >= n_col as isize {
+            return Err(anyhow!("Column {column} out of bounds"));
+        }
 
-    fn column_iter_indices(&self, column: isize) -> anyhow::Result<std::ops::Range<i64>> {
-        let (n_row, _n_col) = harp::Matrix::dim(self.vector.sexp)?;
-        let start = column as i64 * n_row as i64;
-        let end = start + n_row as i64;
-        Ok(start..end)
+        let n_row = n_row as usize;
+        self.format_range(column as usize * n_row, n.min(n_row))
     }
 }
 
@@ -148,71 +161,102 @@ impl AtomicVector {
             AtomicVector::Complex(v) => v.format_elt_unchecked(index, Some(&options)),
         }
     }
-
-    fn len(&self) -> usize {
-        unsafe {
-            match self {
-                AtomicVector::Raw(v) => v.len(),
-                AtomicVector::Logical(v) => v.len(),
-                AtomicVector::Integer(v) => v.len(),
-                AtomicVector::Numeric(v) => v.len(),
-                AtomicVector::Character(v) => v.len(),
-                AtomicVector::Complex(v) => v.len(),
-            }
-        }
-    }
 }
 
+/// Iterator over the formatted elements of a range of a vector
+///
+/// Iteration stops early if a chunk of a classed vector fails to format.
+/// The error is logged.
 pub struct FormattedVectorIter {
-    vector: AtomicVector,
-    indices: Box<dyn Iterator<Item = i64>>,
+    source: FormatSource,
+    range: Range<usize>,
 }
 
-impl FormattedVectorIter {
-    /// Creates a new iterator over the formatted elements of a vector.
-    /// If `indices` is `None`, the iterator will iterate over all elements of the vector.
-    /// If `indices` is `Some`, the iterator will iterate over the elements at the specified indices.
-    /// SAFETY: The caller must make sure that indices are valid and in bounds. Iteration may panic
-    /// if the indices are out of bounds.
-    fn new_unchecked(
+enum FormatSource {
+    /// Atomic vectors are formatted element by element
+    Atomic(AtomicVector),
+
+    /// Classed vectors are formatted by chunks. `chunk` holds the formatted
+    /// elements from `chunk_start`.
+    Object {
         vector: RObject,
-        indices: Option<Box<dyn Iterator<Item = i64>>>,
-    ) -> anyhow::Result<Self> {
-        // For atomic vectors we just create the iterator directly
+        chunk: Option<AtomicVector>,
+        chunk_start: usize,
+        chunk_len: usize,
+    },
+}
+
+impl FormattedVectorIter {
+    /// Creates a new iterator over the formatted elements of `range`. The
+    /// caller must make sure that the range is in bounds.
+    fn new(vector: RObject, range: Range<usize>) -> anyhow::Result<Self> {
         if !r_is_object(vector.sexp) {
-            return Self::from_atomic(AtomicVector::new(vector)?, indices);
+            let source = FormatSource::Atomic(AtomicVector::new(vector)?);
+            return Ok(Self { source, range });
         }
 
-        // For objects we need to format the vector before iterating. However, we can't
-        // format the entire vector at once because it might be too large. Instead, we
-        // subset the vector prior to formatting.
-        let subset = match indices {
-            None => vector,
-            Some(indices) => {
-                let indices = indices.collect::<Vec<i64>>();
-                RObject::from(r_subset_vec(vector.sexp, indices)?)
-            },
+        let source = FormatSource::Object {
+            vector,
+            chunk: None,
+            chunk_start: 0,
+            chunk_len: 0,
         };
-        let formatted = RObject::from(r_format_vec(subset.sexp)?);
+        let mut iter = Self { source, range };
 
-        // We already formatted the selected subset, so we can create an iterator over `None`
-        // indices, ie, over all elements.
-        Self::from_atomic(AtomicVector::new(formatted)?, None)
+        // Format the first chunk right away so that failures of the
+        // `format()` method are reported to the caller
+        if !iter.range.is_empty() {
+            iter.load_chunk(iter.range.start)?;
+        }
+
+        Ok(iter)
     }
 
-    fn from_atomic(
-        vector: AtomicVector,
-        indices: Option<Box<dyn Iterator<Item = i64>>>,
-    ) -> anyhow::Result<Self> {
-        let indices = match indices {
-            Some(indices) => indices,
-            None => {
-                let len = vector.len();
-                Box::new(0..len as i64)
-            },
+    fn load_chunk(&mut self, start: usize) -> anyhow::Result<()> {
+        let FormatSource::Object {
+            vector,
+            chunk,
+            chunk_start,
+            chunk_len,
+        } = &mut self.source
+        else {
+            return Ok(());
         };
 
-        return Ok(Self { vector, indices });
+        let len = FORMAT_CHUNK_SIZE.min(self.range.end - start);
+        let indices: Vec<i64> = (start..start + len).map(|i| i as i64).collect();
+
+        let subset = RObject::from(r_subset_vec(vector.sexp, indices)?);
+        let formatted = RObject::from(r_format_vec(subset.sexp)?);
+
+        *chunk = Some(AtomicVector::new(formatted)?);
+        *chunk_start = start;
+        *chunk_len = len;
+
+        Ok(())
+    }
+
+    fn format_element(&mut self, index: usize) -> anyhow::Result<String> {
+        if let FormatSource::Object {
+            chunk_start,
+            chunk_len,
+            ..
+        } = &self.source
+        {
+            if index < *chunk_start || index >= *chunk_start + *chunk_len {
+                self.load_chunk(index)?;
+            }
+        }
+
+        match &self.source {
+            FormatSource::Atomic(vector) => Ok(vector.format_element(index as isize)),
+            FormatSource::Object {
+                chunk: Some(chunk),
+                chunk_start,
+                ..
+            } => Ok(chunk.format_element((index - chunk_start) as isize)),
+            FormatSource::Object { chunk: None, .. } => Err(anyhow!("Chunk not formatted")),
+        }
     }
 }
 
@@ -220,12 +264,21 @@ impl Iterator for FormattedVectorIter {
     type Item = String;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(index) = self.indices.next() {
-            Some(self.vector.format_element(index as isize))
-        } else {
-            None
+        let index = self.range.next()?;
+
+        match self.format_element(index) {
+            Ok(elt) => Some(elt),
+            Err(err) => {
+                log::error!("Can't format element {index}: {err:?}");
+                self.range = self.range.end..self.range.end;
+                None
+            },
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.range.len()))
+    }
 }
 
 #[cfg(test)]
@@ -281,4 +334,45 @@ mod tests {
             assert_eq!(out, String::from(r#""1" "2" "\"a\"" "NA" NA"#));
         })
     }
+
+    #[test]
+    fn test_format_range() {
+        r_task(|| {
+            let x = harp::parse_eval_base("1:10").unwrap();
+            let formatted = FormattedVector::new(x).unwrap();
+
+            let out = formatted.format_range(2, 3).unwrap().join(" ");
+            assert_eq!(out, String::from("3 4 5"));
+
+            assert_eq!(formatted.format_range(10, 0).unwrap().count(), 0);
+            assert!(formatted.format_range(8, 3).is_err());
+            assert!(formatted.format_elt(10).is_err());
+        })
+    }
+
+    #[test]
+    fn test_format_range_chunked() {
+        r_task(|| {
+            // Classed vectors are formatted by chunks. Formatted elements are
+            // quoted like character vectors.
+            let x = harp::parse_eval_base("factor(rep(c('a', 'b'), 1500))").unwrap();
+            let formatted = FormattedVector::new(x).unwrap();
+
+            let out: Vec<String> = formatted.iter().unwrap().collect();
+            assert_eq!(out.len(), 3000);
+            assert_eq!(out[999], r#""b""#);
+            assert_eq!(out[1000], r#""a""#);
+            assert_eq!(out[2999], r#""b""#);
+
+            let out = formatted.format_range(998, 4).unwrap().join(" ");
+            assert_eq!(out, String::from(r#""a" "b" "a" "b""#));
+
+            let x =
+                harp::parse_eval_base("structure(factor(letters[1:6]), dim = c(3L, 2L))").unwrap();
+            let formatted = FormattedVector::new(x).unwrap();
+            let out = formatted.column_iter_n(1, 2).unwrap().join(" ");
+            assert_eq!(out, String::from(r#""d" "e""#));
+            assert!(formatted.column_iter(2).is_err());
+        })
+    }
 }
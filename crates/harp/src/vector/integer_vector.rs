@@ -76,6 +76,30 @@ impl Vector for IntegerVector {
     }
 }
 
+/// Builds an integer vector from an iterator. `None` values become `NA`.
+impl FromIterator<Option<i32>> for IntegerVector {
+    fn from_iter<I: IntoIterator<Item = Option<i32>>>(iter: I) -> Self {
+        iter.into_iter()
+            .map(|value| value.unwrap_or(unsafe { R_NaInt }))
+            .collect()
+    }
+}
+
+impl FromIterator<i32> for IntegerVector {
+    fn from_iter<I: IntoIterator<Item = i32>>(iter: I) -> Self {
+        let values: Vec<i32> = iter.into_iter().collect();
+
+        unsafe {
+            let vector = IntegerVector::with_length(values.len());
+            if !values.is_empty() {
+                let dataptr = DATAPTR(vector.data()) as *mut i32;
+                std::ptr::copy_nonoverlapping(values.as_ptr(), dataptr, values.len());
+            }
+            vector
+        }
+    }
+}
+
 impl TryFrom<&IntegerVector> for Vec<i32> {
     type Error = harp::Error;
 
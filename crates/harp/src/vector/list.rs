@@ -12,6 +12,7 @@ use crate::object::r_length;
 use crate::object::r_list_poke;
 use crate::object::RObject;
 use crate::r_typeof;
+use crate::vector::CharacterVector;
 
 #[derive(Debug)]
 pub struct List {
@@ -99,6 +100,68 @@ impl super::Vector for List {
     }
 }
 
+/// Builds an R list from Rust values
+///
+/// Pushed values are protected until the list is built, so they can be
+/// allocated one after the other with the R API. Names are only set on the
+/// list if at least one value is named.
+#[derive(Default)]
+pub struct ListBuilder {
+    values: Vec<RObject>,
+    names: Vec<String>,
+    named: bool,
+}
+
+impl ListBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            values: Vec::with_capacity(capacity),
+            names: Vec::with_capacity(capacity),
+            named: false,
+        }
+    }
+
+    pub fn push(&mut self, value: impl Into<RObject>) -> &mut Self {
+        self.values.push(value.into());
+        self.names.push(String::new());
+        self
+    }
+
+    pub fn push_named(&mut self, name: &str, value: impl Into<RObject>) -> &mut Self {
+        self.values.push(value.into());
+        self.names.push(name.to_string());
+        self.named = true;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn build(&self) -> crate::Result<RObject> {
+        let list = RObject::new(crate::alloc_list(self.values.len())?);
+
+        for (i, value) in self.values.iter().enumerate() {
+            r_list_poke(list.sexp, i as libr::R_xlen_t, value.sexp);
+        }
+
+        if self.named {
+            let names = CharacterVector::from_iter(self.names.iter().map(String::as_str));
+            list.set_attr("names", names)?;
+        }
+
+        Ok(list)
+    }
+}
+
 impl ListIter {
     pub fn new(x: SEXP) -> crate::Result<Self> {
         match r_typeof(x) {
@@ -143,6 +206,7 @@ impl std::iter::Iterator for ListIter {
 mod test {
     use crate::fixtures::r_task;
     use crate::vector::list::List;
+    use crate::vector::list::ListBuilder;
     use crate::vector::Vector;
     use crate::RObject;
 
@@ -166,6 +230,31 @@ mod test {
             assert!(it.next().is_none());
         })
     }
+
+    #[test]
+    fn test_list_builder() {
+        r_task(|| {
+            let xs = ListBuilder::new().build().unwrap();
+            assert_eq!(crate::r_length(xs.sexp), 0);
+            assert!(xs.attr("names").is_none());
+
+            let xs = ListBuilder::new().push(1).push("foo").build().unwrap();
+            assert_eq!(crate::r_length(xs.sexp), 2);
+            assert!(xs.attr("names").is_none());
+            assert!(crate::is_identical(
+                xs.list_elt(1).unwrap().sexp,
+                RObject::from("foo").sexp
+            ));
+
+            let xs = ListBuilder::new()
+                .push(1)
+                .push_named("b", 2)
+                .build()
+                .unwrap();
+            let names: Vec<String> = xs.attr("names").unwrap().try_into().unwrap();
+            assert_eq!(names, vec!["", "b"]);
+        })
+    }
 }
 
 impl TryFrom<SEXP> for List {
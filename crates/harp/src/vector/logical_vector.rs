@@ -80,6 +80,31 @@ impl Vector for LogicalVector {
     }
 }
 
+/// Builds a logical vector from an iterator. `None` values become `NA`.
+impl FromIterator<Option<bool>> for LogicalVector {
+    fn from_iter<I: IntoIterator<Item = Option<bool>>>(iter: I) -> Self {
+        let values: Vec<Option<bool>> = iter.into_iter().collect();
+
+        unsafe {
+            let vector = LogicalVector::with_length(values.len());
+            let dataptr = DATAPTR(vector.data()) as *mut i32;
+            for (i, value) in values.iter().enumerate() {
+                *(dataptr.add(i)) = match value {
+                    Some(value) => *value as i32,
+                    None => R_NaInt,
+                };
+            }
+            vector
+        }
+    }
+}
+
+impl FromIterator<bool> for LogicalVector {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        iter.into_iter().map(Some).collect()
+    }
+}
+
 impl TryFrom<&LogicalVector> for Vec<bool> {
     type Error = harp::Error;
 
@@ -15,6 +15,7 @@ use crate::utils::r_assert_type;
 
 pub mod list;
 pub use list::List;
+pub use list::ListBuilder;
 
 pub mod character_vector;
 pub use character_vector::CharacterVector;
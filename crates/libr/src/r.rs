@@ -145,6 +145,8 @@ functions::generate! {
 
     pub fn Rf_getAttrib(arg1: SEXP, arg2: SEXP) -> SEXP;
 
+    pub fn Rf_getCharCE(arg1: SEXP) -> cetype_t;
+
     pub fn Rf_duplicate(arg: SEXP) -> SEXP;
 
     pub fn Rf_shallow_duplicate(arg: SEXP) -> SEXP;
@@ -227,6 +229,8 @@ functions::generate! {
 
     pub fn DATAPTR_RO(x: SEXP) -> *const std::ffi::c_void;
 
+    pub fn DATAPTR_OR_NULL(x: SEXP) -> *const std::ffi::c_void;
+
     pub fn ENCLOS(x: SEXP) -> SEXP;
 
     pub fn EXTPTR_PROT(x: SEXP) -> SEXP;
@@ -689,6 +693,14 @@ mutable_globals::generate! {
 
     pub static mut R_Srcref: SEXP;
 
+    /// The top of the pointer protection stack. Not part of the API, only
+    /// used to audit protection balance in debug builds.
+    pub static mut R_PPStackTop: std::ffi::c_int;
+
+    /// Whether the session runs in a UTF-8 locale. Not part of the API, only
+    /// used to skip string translations that are no-ops.
+    pub static mut utf8locale: Rboolean;
+
     // -----------------------------------------------------------------------------------
     // Unix
 
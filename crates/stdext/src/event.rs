@@ -36,6 +36,25 @@ impl<T> Event<T> {
         let mut listeners = self.listeners.lock().unwrap();
         listeners.remove(&id);
     }
+
+    /// Like `listen()` but returns a handle that removes the listener when
+    /// dropped
+    pub fn subscribe(&'static self, callback: impl Fn(&T) + Send + 'static) -> Subscription<T> {
+        let id = self.listen(callback);
+        Subscription { event: self, id }
+    }
+}
+
+/// A listener registered with `Event::subscribe()`
+pub struct Subscription<T: 'static> {
+    event: &'static Event<T>,
+    id: i32,
+}
+
+impl<T> Drop for Subscription<T> {
+    fn drop(&mut self) {
+        self.event.remove(self.id);
+    }
 }
 
 #[cfg(test)]
@@ -74,4 +93,30 @@ mod tests {
         signals.string.remove(id);
         signals.string.emit("hello");
     }
+
+    #[test]
+    fn test_subscription() {
+        use std::sync::atomic::AtomicI32;
+        use std::sync::atomic::Ordering;
+        use std::sync::Arc;
+
+        static EVENT: std::sync::LazyLock<Event<i32>> =
+            std::sync::LazyLock::new(|| Event::default());
+        let count = Arc::new(AtomicI32::new(0));
+
+        let subscription = EVENT.subscribe({
+            let count = count.clone();
+            move |number| {
+                count.fetch_add(*number, Ordering::Relaxed);
+            }
+        });
+
+        EVENT.emit(1);
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+
+        // Dropping the subscription removes the listener
+        drop(subscription);
+        EVENT.emit(1);
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+    }
 }
@@ -0,0 +1,524 @@
+#!/usr/bin/env node
+//
+// generate-comms.js
+//
+// Copyright (C) 2025 Posit Software, PBC. All rights reserved.
+//
+//
+
+// Generates the Rust bindings in `crates/amalthea/src/comm/` from the OpenRPC
+// comm contracts in `crates/amalthea/comms/`. The contracts mirror the ones
+// maintained in the Positron repository and the output follows the Rust
+// flavour of Positron's `generate-comms.ts`.
+//
+// Usage: node scripts/generate-comms.js [comm ...]
+
+const fs = require('fs');
+const path = require('path');
+
+const root = path.resolve(__dirname, '..');
+const commsDir = path.join(root, 'crates', 'amalthea', 'comms');
+const outputDir = path.join(root, 'crates', 'amalthea', 'src', 'comm');
+
+// Structs that derive `Copy` in addition to the usual traits
+const copyStructs = ['plot_size', 'plot_render_settings'];
+
+// Property names that can't be used as Rust field names
+const rustKeywords = ['type', 'fn', 'match', 'mod', 'ref', 'self', 'struct', 'use', 'where'];
+
+// Enum values that can't be turned into identifiers as is
+const symbolNames = {
+	'=': 'Eq',
+	'!=': 'NotEq',
+	'<': 'Lt',
+	'<=': 'LtEq',
+	'>': 'Gt',
+	'>=': 'GtEq',
+};
+
+const derives = 'Clone, Debug, Serialize, Deserialize, PartialEq';
+const enumDerives = `${derives}, strum_macros::Display, strum_macros::EnumString`;
+
+function main() {
+	const requested = process.argv.slice(2);
+	const names = commNames().filter((name) => !requested.length || requested.includes(name));
+
+	for (const name of requested) {
+		if (!names.includes(name)) {
+			throw new Error(`No contract found for comm '${name}'`);
+		}
+	}
+
+	for (const name of names) {
+		const file = path.join(outputDir, `${name}_comm.rs`);
+		fs.writeFileSync(file, createRustComm(name));
+		console.log(`Wrote ${path.relative(root, file)}`);
+	}
+}
+
+function commNames() {
+	const names = new Set();
+	for (const file of fs.readdirSync(commsDir)) {
+		const match = file.match(/^(.*)-(backend|frontend)-openrpc\.json$/);
+		if (match) {
+			names.add(match[1]);
+		}
+	}
+	return [...names].sort();
+}
+
+function readContract(name, side) {
+	const file = path.join(commsDir, `${name}-${side}-openrpc.json`);
+	if (!fs.existsSync(file)) {
+		return { methods: [] };
+	}
+	return JSON.parse(fs.readFileSync(file, 'utf8'));
+}
+
+function createRustComm(name) {
+	const backend = readContract(name, 'backend');
+	const frontend = readContract(name, 'frontend');
+	const comm = new Comm(name, backend, frontend);
+
+	let out = `// @generated
+
+/*---------------------------------------------------------------------------------------------
+ *  Copyright (C) 2024-2025 Posit Software, PBC. All rights reserved.
+ *--------------------------------------------------------------------------------------------*/
+
+//
+// AUTO-GENERATED from ${name}.json; do not edit.
+//
+
+`;
+
+	// Render the body first so external references are known for the imports
+	const body = [
+		...comm.valueContracts(),
+		...comm.enums(),
+		...comm.unions(),
+		...comm.paramContracts(),
+		...comm.rpcEnums(),
+	].join('');
+
+	out += 'use serde::Deserialize;\n';
+	out += 'use serde::Serialize;\n';
+	for (const [type, source] of comm.imports) {
+		out += `use super::${source}_comm::${type};\n`;
+	}
+	out += '\n';
+	out += body;
+
+	return out;
+}
+
+class Comm {
+	constructor(name, backend, frontend) {
+		this.name = name;
+		this.backend = backend;
+		this.frontend = frontend;
+		this.imports = new Map();
+	}
+
+	methods() {
+		return [...this.backend.methods, ...this.frontend.methods];
+	}
+
+	components() {
+		return [
+			...Object.entries(this.backend.components?.schemas ?? {}),
+			...Object.entries(this.frontend.components?.schemas ?? {}),
+		];
+	}
+
+	frontendRequests() {
+		return this.frontend.methods.filter((method) => method.result !== undefined);
+	}
+
+	frontendEvents() {
+		return this.frontend.methods.filter((method) => method.result === undefined);
+	}
+
+	// Structs and type aliases, in the order they are reached from the methods
+	// and then from the shared components.
+	*valueContracts() {
+		for (const method of this.methods()) {
+			for (const param of method.params ?? []) {
+				yield* this.valueContract(param.schema, param.name, `${pascal(param.name)} in Params`);
+			}
+			const schema = method.result?.schema;
+			if (schema) {
+				yield* this.valueContract(schema, resultName(method), 'Result in Methods');
+			}
+		}
+		for (const [name, schema] of this.components()) {
+			yield* this.valueContract(schema, name, `${pascal(name)} in Schemas`);
+		}
+	}
+
+	*valueContract(schema, name, fallback) {
+		if (schema.$ref || schema.oneOf || schema.enum) {
+			return;
+		}
+
+		const type = nonNullType(schema);
+
+		if (type === 'array') {
+			const items = schema.items;
+			yield* this.valueContract(items, items.name ?? name, `Items in ${pascal(name)}`);
+			return;
+		}
+
+		if (type === 'object' && schema.properties) {
+			yield* this.struct(schema, name, fallback);
+			for (const [prop, propSchema] of Object.entries(schema.properties)) {
+				if (nonNullType(propSchema) === 'object' && propSchema.properties) {
+					yield* this.valueContract(propSchema, prop, `${pascal(prop)} in Properties`);
+				}
+			}
+			return;
+		}
+
+		if (type === undefined) {
+			yield comment('', schema.description ?? fallback);
+			yield `pub type ${pascal(name)} = serde_json::Value;\n\n`;
+		}
+	}
+
+	*struct(schema, name, fallback) {
+		const required = schema.required ?? [];
+		const copy = copyStructs.includes(name) ? 'Copy, ' : '';
+
+		yield comment('', schema.description ?? fallback);
+		yield `#[derive(${copy}${derives})]\n`;
+		yield `pub struct ${pascal(name)} {\n`;
+
+		const fields = Object.entries(schema.properties).map(([prop, propSchema]) => {
+			let field = '';
+			if (propSchema.description) {
+				field += comment('\t', propSchema.description);
+			}
+			let ident = prop;
+			if (rustKeywords.includes(prop)) {
+				field += `\t#[serde(rename = "${prop}")]\n`;
+				ident = `${name}_${prop}`;
+			}
+			let type = this.rustType(propSchema, pascal(name) + pascal(prop), prop);
+			if (!required.includes(prop) && !type.startsWith('Option<')) {
+				type = `Option<${type}>`;
+			}
+			return field + `\tpub ${ident}: ${type}`;
+		});
+
+		yield fields.join(',\n\n');
+		yield '\n}\n\n';
+	}
+
+	// String enumerations, in the order they are reached from the method
+	// parameters and then from the shared components.
+	*enums() {
+		for (const method of this.methods()) {
+			for (const param of method.params ?? []) {
+				yield* this.propertyEnum(param.schema, method.name, param.name);
+			}
+		}
+		for (const [name, schema] of this.components()) {
+			if (schema.enum) {
+				yield* this.enumeration(schema.enum, pascal(name), `Possible values for ${pascal(name)}`, true);
+			} else if (schema.properties) {
+				for (const [prop, propSchema] of Object.entries(schema.properties)) {
+					yield* this.propertyEnum(propSchema, name, prop);
+				}
+			}
+		}
+	}
+
+	*propertyEnum(schema, parent, name) {
+		if (nonNullType(schema) === 'array') {
+			yield* this.propertyEnum(schema.items, parent, name);
+		} else if (schema.enum) {
+			const description = `Possible values for ${pascal(name)} in ${pascal(parent)}`;
+			yield* this.enumeration(schema.enum, pascal(parent) + pascal(name), description, false);
+		}
+	}
+
+	*enumeration(values, name, description, copy) {
+		yield comment('', description);
+		yield `#[derive(${copy ? 'Copy, ' : ''}${enumDerives})]\n`;
+		yield `pub enum ${name} {\n`;
+
+		const variants = values.map((value) => {
+			return `\t#[serde(rename = "${value}")]\n` +
+				`\t#[strum(to_string = "${value}")]\n` +
+				`\t${enumVariant(value)}`;
+		});
+
+		yield variants.join(',\n\n');
+		yield '\n}\n\n';
+	}
+
+	// Untagged unions, either shared components or inlined in an object
+	*unions() {
+		for (const [name, schema] of this.components()) {
+			if (schema.oneOf) {
+				let description = `Union type ${pascal(name)}`;
+				if (schema.description) {
+					description += `\n${schema.description}`;
+				}
+				yield* this.union(schema, pascal(name), description);
+			} else if (schema.properties) {
+				for (const [prop, propSchema] of Object.entries(schema.properties)) {
+					if (propSchema.oneOf) {
+						const description = `Union type ${pascal(prop)} in Properties`;
+						yield* this.union(propSchema, pascal(prop), description);
+					}
+				}
+			}
+		}
+	}
+
+	*union(schema, name, description) {
+		yield comment('', description);
+		yield `#[derive(${derives})]\n`;
+		yield '#[serde(untagged)]\n';
+		yield `pub enum ${name} {\n`;
+
+		const variants = schema.oneOf.map((option) => {
+			return `\t${pascal(option.name)}(${this.rustType(option, pascal(option.name), option.name)})`;
+		});
+
+		yield variants.join(',\n\n');
+		yield '\n}\n\n';
+	}
+
+	*paramContracts() {
+		for (const method of this.methods()) {
+			const params = method.params ?? [];
+			if (!params.length) {
+				continue;
+			}
+
+			yield comment('', `Parameters for the ${pascal(method.name)} method.`);
+			yield `#[derive(${derives})]\n`;
+			yield `pub struct ${pascal(method.name)}Params {\n`;
+
+			const fields = params.map((param) => {
+				let field = '';
+				if (param.description) {
+					field += comment('\t', param.description);
+				}
+				let type = this.rustType(param.schema, pascal(method.name) + pascal(param.name), param.name);
+				if (param.required === false && !type.startsWith('Option<')) {
+					type = `Option<${type}>`;
+				}
+				return field + `\tpub ${param.name}: ${type},\n`;
+			});
+
+			yield fields.join('\n');
+			yield '}\n\n';
+		}
+	}
+
+	*rpcEnums() {
+		const prefix = pascal(this.name);
+		const requests = this.frontendRequests();
+
+		yield this.rpcEnum(
+			`Backend RPC request types for the ${this.name} comm`,
+			`${prefix}BackendRequest`,
+			'params',
+			this.backend.methods.map((method) => this.requestVariant(method, true)),
+		);
+		yield this.rpcEnum(
+			`Backend RPC Reply types for the ${this.name} comm`,
+			`${prefix}BackendReply`,
+			'result',
+			this.backend.methods.map((method) => this.replyVariant(method)),
+		);
+		yield this.rpcEnum(
+			`Frontend RPC request types for the ${this.name} comm`,
+			`${prefix}FrontendRequest`,
+			'params',
+			requests.map((method) => this.requestVariant(method, true)),
+		);
+		yield this.rpcEnum(
+			`Frontend RPC Reply types for the ${this.name} comm`,
+			`${prefix}FrontendReply`,
+			'result',
+			requests.map((method) => this.replyVariant(method)),
+		);
+		yield this.rpcEnum(
+			`Frontend events for the ${this.name} comm`,
+			`${prefix}FrontendEvent`,
+			'params',
+			this.frontendEvents().map((method) => this.requestVariant(method, false)),
+		);
+
+		if (requests.length) {
+			yield this.replyConversion(prefix, requests);
+		}
+	}
+
+	rpcEnum(title, name, content, variants) {
+		let out = `/**\n * ${title}\n */\n`;
+		out += `#[derive(${derives})]\n`;
+		out += `#[serde(tag = "method", content = "${content}")]\n`;
+		out += `pub enum ${name} {\n`;
+		out += variants.join('');
+		out += '}\n\n';
+		return out;
+	}
+
+	requestVariant(method, rpc) {
+		let out = '';
+		if (rpc && method.summary) {
+			out += comment('\t', method.summary);
+			if (method.description) {
+				out += '\t///\n';
+			}
+		}
+		if (method.description) {
+			out += comment('\t', method.description);
+		}
+		out += `\t#[serde(rename = "${method.name}")]\n`;
+		out += `\t${pascal(method.name)}${method.params?.length ? `(${pascal(method.name)}Params)` : ''},\n\n`;
+		return out;
+	}
+
+	replyVariant(method) {
+		const variant = `${pascal(method.name)}Reply`;
+		const schema = method.result?.schema;
+
+		if (!schema) {
+			return comment('\t', `Reply for the ${method.name} method (no result)`) + `\t${variant}(),\n\n`;
+		}
+
+		let out = schema.description ? comment('\t', schema.description) : '';
+		if (schema.type === 'null') {
+			return out + `\t${variant}(),\n\n`;
+		}
+
+		return out + `\t${variant}(${this.rustType(schema, pascal(resultName(method)), resultName(method))}),\n\n`;
+	}
+
+	replyConversion(prefix, requests) {
+		const fn = `${this.name}_frontend_reply_from_value`;
+		let out = '/**\n* Conversion of JSON values to frontend RPC Reply types\n*/\n';
+		out += `pub fn ${fn}(\n`;
+		out += '\treply: serde_json::Value,\n';
+		out += `\trequest: &${prefix}FrontendRequest,\n`;
+		out += `) -> anyhow::Result<${prefix}FrontendReply> {\n`;
+		out += '\tmatch request {\n';
+		for (const method of requests) {
+			const variant = pascal(method.name);
+			const pattern = method.params?.length ? `${variant}(_)` : variant;
+			const schema = method.result?.schema;
+			const value = schema && schema.type !== 'null' ? 'serde_json::from_value(reply)?' : '';
+			out += `\t\t${prefix}FrontendRequest::${pattern} => Ok(${prefix}FrontendReply::${variant}Reply(${value})),\n`;
+		}
+		out += '\t}\n';
+		out += '}\n\n';
+		return out;
+	}
+
+	// The Rust type of a schema. `inlineName` names inline enumerations and
+	// `name` names inline objects, unions, and untyped values.
+	rustType(schema, inlineName, name) {
+		if (schema.$ref) {
+			return this.refType(schema.$ref);
+		}
+		if (schema.oneOf) {
+			return pascal(name);
+		}
+		if (schema.enum) {
+			return inlineName;
+		}
+
+		const type = nonNullType(schema);
+		let rust;
+		switch (type) {
+			case 'boolean':
+				rust = 'bool';
+				break;
+			case 'integer':
+				rust = 'i64';
+				break;
+			case 'number':
+				rust = 'f64';
+				break;
+			case 'string':
+				rust = 'String';
+				break;
+			case 'array': {
+				const items = schema.items;
+				rust = `Vec<${this.rustType(items, inlineName, items.name ?? name)}>`;
+				break;
+			}
+			case 'object':
+				rust = pascal(schema.name ?? name);
+				break;
+			case undefined:
+				rust = pascal(schema.name ?? name);
+				break;
+			default:
+				throw new Error(`Unsupported schema type '${type}' for '${name}'`);
+		}
+
+		if (Array.isArray(schema.type) && schema.type.includes('null')) {
+			rust = `Option<${rust}>`;
+		}
+		return rust;
+	}
+
+	refType(ref) {
+		const [file, pointer] = ref.split('#');
+		const type = pascal(pointer.split('/').pop());
+		if (file) {
+			const source = file.replace(/-(backend|frontend)-openrpc\.json$/, '');
+			this.imports.set(type, source);
+		}
+		return type;
+	}
+}
+
+function resultName(method) {
+	return method.result?.schema?.name ?? `${method.name}_result`;
+}
+
+function nonNullType(schema) {
+	if (Array.isArray(schema.type)) {
+		return schema.type.find((type) => type !== 'null');
+	}
+	return schema.type;
+}
+
+function pascal(name) {
+	return name
+		.split(/[^A-Za-z0-9]+/)
+		.filter((part) => part.length)
+		.map((part) => part[0].toUpperCase() + part.slice(1))
+		.join('');
+}
+
+function enumVariant(value) {
+	return symbolNames[value] ?? pascal(value);
+}
+
+// Wraps `text` at 70 columns into `///` doc comment lines prefixed with `indent`
+function comment(indent, text) {
+	let out = '';
+	for (const line of text.split('\n')) {
+		let current = '';
+		for (const word of line.split(' ')) {
+			if (current.length && current.length + word.length > 70) {
+				out += `${indent}/// ${current.trimEnd()}\n`;
+				current = '';
+			}
+			current += word + ' ';
+		}
+		out += `${indent}/// ${current.trimEnd()}\n`;
+	}
+	return out;
+}
+
+main();